@@ -1,9 +1,16 @@
 use crate::audio_feedback::{play_feedback_sound, play_feedback_sound_blocking, SoundType};
+use crate::audio_toolkit::audio::{Denoiser, PreprocessChain};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::{
+    apply_casing, apply_spoken_formatting, filter_profanity, normalize_numbers,
+};
+use crate::events::{self, TranscriptionFinished, TranscriptionStarted};
+use crate::external_command::run_external_command;
 use crate::managers::audio::AudioRecordingManager;
-use crate::managers::history::HistoryManager;
+use crate::managers::history::{HistoryManager, TranscriptionMetadata};
 use crate::managers::transcription::TranscriptionManager;
 use crate::overlay::{show_recording_overlay, show_transcribing_overlay};
-use crate::settings::{get_settings, AppSettings};
+use crate::settings::{get_settings, write_settings, AppSettings, AudioSource, OutputMode};
 use crate::tray::{change_tray_icon, TrayIconState};
 use crate::utils;
 use async_openai::types::{
@@ -11,23 +18,170 @@ use async_openai::types::{
     CreateChatCompletionRequestArgs,
 };
 use ferrous_opencc::{config::BuiltinConfig, OpenCC};
-use log::{debug, error};
+use log::{debug, error, info, warn};
 use once_cell::sync::Lazy;
+use serde::Serialize;
 use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Instant;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::AppHandle;
+use tauri::Emitter;
 use tauri::Manager;
 
 // Shortcut Action Trait
 pub trait ShortcutAction: Send + Sync {
     fn start(&self, app: &AppHandle, binding_id: &str, shortcut_str: &str);
     fn stop(&self, app: &AppHandle, binding_id: &str, shortcut_str: &str);
+
+    /// One-shot actions run `start` on every press, ignoring push-to-talk and toggle state
+    fn is_instant(&self) -> bool {
+        false
+    }
 }
 
 // Transcribe Action
 struct TranscribeAction;
 
+/// Transcriptions from consecutive recordings waiting to be pasted as one
+#[derive(Default)]
+struct StitchedDictation {
+    text: String,
+    generation: u64,
+}
+
+static STITCHED_DICTATION: Lazy<Mutex<StitchedDictation>> =
+    Lazy::new(|| Mutex::new(StitchedDictation::default()));
+
+/// Join two dictation segments, adding a space unless punctuation or existing
+/// whitespace makes one unnecessary
+fn join_segments(previous: &str, next: &str) -> String {
+    let next = next.trim_start();
+    if previous.is_empty() {
+        return next.to_string();
+    }
+    if next.is_empty() {
+        return previous.to_string();
+    }
+
+    let needs_space = !previous.ends_with(char::is_whitespace)
+        && !next.starts_with(|c: char| matches!(c, '.' | ',' | '!' | '?' | ';' | ':' | ')'));
+    if needs_space {
+        format!("{} {}", previous, next)
+    } else {
+        format!("{}{}", previous, next)
+    }
+}
+
+/// Starting a new recording keeps the stitched text from being flushed
+fn hold_stitched_dictation() {
+    STITCHED_DICTATION.lock().unwrap().generation += 1;
+}
+
+fn push_stitched_segment(text: &str) {
+    let mut stitched = STITCHED_DICTATION.lock().unwrap();
+    stitched.text = join_segments(&stitched.text, text);
+}
+
+/// Schedules the stitched text to be pasted once a recording finishes,
+/// however it finishes. If another recording starts within `window` the
+/// flush is skipped and that recording's guard reschedules it.
+struct StitchFlushGuard {
+    app: AppHandle,
+    window: Duration,
+    mode: OutputMode,
+}
+
+impl Drop for StitchFlushGuard {
+    fn drop(&mut self) {
+        let generation = {
+            let mut stitched = STITCHED_DICTATION.lock().unwrap();
+            stitched.generation += 1;
+            stitched.generation
+        };
+        let app = self.app.clone();
+        let window = self.window;
+        let mode = self.mode;
+
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(window).await;
+
+            let text = {
+                let mut stitched = STITCHED_DICTATION.lock().unwrap();
+                if stitched.generation != generation {
+                    return;
+                }
+                std::mem::take(&mut stitched.text)
+            };
+            if text.is_empty() {
+                return;
+            }
+
+            debug!("Pasting stitched dictation ({} chars)", text.len());
+            let ah = app.clone();
+            app.run_on_main_thread(move || {
+                if let Err(e) = utils::output(text, mode, ah) {
+                    error!("Failed to output stitched dictation: {}", e);
+                }
+            })
+            .unwrap_or_else(|e| error!("Failed to run paste on main thread: {:?}", e));
+        });
+    }
+}
+
+/// A finished transcription waiting out the retake window before it is pasted
+struct PendingOutput {
+    id: u64,
+    binding_id: String,
+}
+
+static PENDING_OUTPUT: Lazy<Mutex<Option<PendingOutput>>> = Lazy::new(|| Mutex::new(None));
+static NEXT_OUTPUT_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Serialize)]
+struct RetakeWindowEvent {
+    binding_id: String,
+    window_ms: u64,
+}
+
+/// Hold a transcription for `window_ms` so the user can retake it.
+/// Returns false if `take_pending_output` claimed it in the meantime.
+async fn wait_for_retake_window(app: &AppHandle, binding_id: &str, window_ms: u64) -> bool {
+    let id = NEXT_OUTPUT_ID.fetch_add(1, Ordering::Relaxed);
+    *PENDING_OUTPUT.lock().unwrap() = Some(PendingOutput {
+        id,
+        binding_id: binding_id.to_string(),
+    });
+
+    let _ = app.emit(
+        "retake-window",
+        RetakeWindowEvent {
+            binding_id: binding_id.to_string(),
+            window_ms,
+        },
+    );
+    tokio::time::sleep(Duration::from_millis(window_ms)).await;
+
+    let mut pending = PENDING_OUTPUT.lock().unwrap();
+    match pending.as_ref() {
+        Some(p) if p.id == id => {
+            *pending = None;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Claim the transcription currently in its retake window, if any, so it is
+/// never pasted. Returns the binding that produced it.
+pub fn take_pending_output() -> Option<String> {
+    PENDING_OUTPUT
+        .lock()
+        .unwrap()
+        .take()
+        .map(|pending| pending.binding_id)
+}
+
 async fn maybe_post_process_transcription(
     settings: &AppSettings,
     transcription: &str,
@@ -161,6 +315,33 @@ async fn maybe_post_process_transcription(
     }
 }
 
+/// Pipe the transcript through the user's external command, if one is
+/// configured. A failing command leaves the transcript untouched.
+async fn maybe_run_external_command(settings: &AppSettings, text: &str) -> Option<String> {
+    if !settings.external_command_enabled || settings.external_command.is_empty() {
+        return None;
+    }
+
+    let timeout = Duration::from_millis(settings.external_command_timeout_ms);
+    match run_external_command(
+        &settings.external_command,
+        &settings.external_command_args,
+        timeout,
+        text,
+    )
+    .await
+    {
+        Ok(output) => Some(output),
+        Err(e) => {
+            warn!(
+                "External command failed, keeping the original transcript: {}",
+                e
+            );
+            None
+        }
+    }
+}
+
 async fn maybe_convert_chinese_variant(
     settings: &AppSettings,
     transcription: &str,
@@ -214,6 +395,8 @@ impl ShortcutAction for TranscribeAction {
         let tm = app.state::<Arc<TranscriptionManager>>();
         tm.initiate_model_load();
 
+        hold_stitched_dictation();
+
         let binding_id = binding_id.to_string();
         change_tray_icon(app, TrayIconState::Recording);
         show_recording_overlay(app);
@@ -277,6 +460,11 @@ impl ShortcutAction for TranscribeAction {
         let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
         let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
 
+        if rm.take_stopped_at_limit(binding_id) {
+            debug!("Recording for {} already stopped at its limit", binding_id);
+            return;
+        }
+
         change_tray_icon(app, TrayIconState::Transcribing);
         show_transcribing_overlay(app);
 
@@ -287,6 +475,10 @@ impl ShortcutAction for TranscribeAction {
         play_feedback_sound(app, SoundType::Stop);
 
         let binding_id = binding_id.to_string(); // Clone binding_id for the async task
+        let recording_id = rm.recording_id();
+
+        // The app the text is meant for, recorded with the history entry
+        let app_name = utils::frontmost_app().map(|app| app.name);
 
         tauri::async_runtime::spawn(async move {
             let binding_id = binding_id.clone(); // Clone for the inner async task
@@ -295,6 +487,14 @@ impl ShortcutAction for TranscribeAction {
                 binding_id
             );
 
+            // With stitching on, pasting waits until no new recording follows
+            let stitch_window = get_settings(&ah).dictation_stitch_window_secs;
+            let _stitch_flush = (stitch_window > 0).then(|| StitchFlushGuard {
+                app: ah.clone(),
+                window: Duration::from_secs(stitch_window),
+                mode: get_settings(&ah).output_mode_for(&binding_id),
+            });
+
             let stop_recording_time = Instant::now();
             if let Some(samples) = rm.stop_recording(&binding_id) {
                 debug!(
@@ -305,7 +505,51 @@ impl ShortcutAction for TranscribeAction {
 
                 let transcription_time = Instant::now();
                 let samples_clone = samples.clone(); // Clone for history saving
-                match tm.transcribe(samples) {
+
+                // Long takes were partly transcribed while recording; only
+                // the audio after the last cut is left
+                let (mut samples, earlier_text) =
+                    match rm.take_segments().map(|segments| segments.finish()) {
+                        Some((texts, tail)) if !texts.is_empty() => {
+                            debug!("{} segments were transcribed while recording", texts.len());
+                            let earlier = texts
+                                .iter()
+                                .fold(String::new(), |text, next| join_segments(&text, next));
+                            (tail, Some(earlier))
+                        }
+                        _ => (samples, None),
+                    };
+                if get_settings(&ah).denoise_enabled {
+                    let mut chain = PreprocessChain::new();
+                    chain.push(Denoiser::new());
+                    chain.process(&mut samples, WHISPER_SAMPLE_RATE as usize);
+                }
+                events::emit(
+                    &ah,
+                    TranscriptionStarted {
+                        recording_id,
+                        binding_id: binding_id.clone(),
+                        started_at: events::now_ms(),
+                        audio_ms: (samples_clone.len() * 1000 / WHISPER_SAMPLE_RATE as usize)
+                            as u64,
+                    },
+                );
+                let result = tm.transcribe(samples).map(|text| match &earlier_text {
+                    Some(earlier) => join_segments(earlier, &text),
+                    None => text,
+                });
+                events::emit(
+                    &ah,
+                    TranscriptionFinished {
+                        recording_id,
+                        binding_id: binding_id.clone(),
+                        finished_at: events::now_ms(),
+                        elapsed_ms: transcription_time.elapsed().as_millis() as u64,
+                        text: result.as_ref().cloned().unwrap_or_default(),
+                        error: result.as_ref().err().map(|e| e.to_string()),
+                    },
+                );
+                match result {
                     Ok(transcription) => {
                         debug!(
                             "Transcription completed in {:?}: '{}'",
@@ -314,20 +558,51 @@ impl ShortcutAction for TranscribeAction {
                         );
                         if !transcription.is_empty() {
                             let settings = get_settings(&ah);
+
+                            if settings.retake_window_ms > 0
+                                && !wait_for_retake_window(
+                                    &ah,
+                                    &binding_id,
+                                    settings.retake_window_ms,
+                                )
+                                .await
+                            {
+                                debug!("Transcription discarded by retake");
+                                return;
+                            }
+
                             let mut final_text = transcription.clone();
                             let mut post_processed_text: Option<String> = None;
                             let mut post_process_prompt: Option<String> = None;
 
-                            // First, check if Chinese variant conversion is needed
+                            // Spoken commands become formatting before any other stage
+                            // reads the text
+                            if settings.spoken_formatting_for(&binding_id) {
+                                let formatted =
+                                    apply_spoken_formatting(&final_text, &settings.spoken_commands);
+                                if formatted != final_text {
+                                    final_text = formatted.clone();
+                                    post_processed_text = Some(formatted);
+                                }
+                            }
+
+                            let normalized =
+                                normalize_numbers(&final_text, &settings.number_formatting);
+                            if normalized != final_text {
+                                final_text = normalized.clone();
+                                post_processed_text = Some(normalized);
+                            }
+
+                            // Then check if Chinese variant conversion is needed
                             if let Some(converted_text) =
-                                maybe_convert_chinese_variant(&settings, &transcription).await
+                                maybe_convert_chinese_variant(&settings, &final_text).await
                             {
                                 final_text = converted_text.clone();
                                 post_processed_text = Some(converted_text);
                             }
                             // Then apply regular post-processing if enabled
                             else if let Some(processed_text) =
-                                maybe_post_process_transcription(&settings, &transcription).await
+                                maybe_post_process_transcription(&settings, &final_text).await
                             {
                                 final_text = processed_text.clone();
                                 post_processed_text = Some(processed_text);
@@ -344,9 +619,46 @@ impl ShortcutAction for TranscribeAction {
                                 }
                             }
 
+                            if let Some(command_text) =
+                                maybe_run_external_command(&settings, &final_text).await
+                            {
+                                final_text = command_text.clone();
+                                post_processed_text = Some(command_text);
+                            }
+
+                            let filtered = filter_profanity(
+                                &final_text,
+                                settings.profanity_filter,
+                                &settings.profanity_words,
+                            );
+                            if filtered != final_text {
+                                final_text = filtered.clone();
+                                post_processed_text = Some(filtered);
+                            }
+
+                            // Casing is the last stage so nothing above undoes it
+                            let cased_text =
+                                apply_casing(&final_text, settings.text_casing_for(&binding_id));
+                            if cased_text != final_text {
+                                final_text = cased_text.clone();
+                                post_processed_text = Some(cased_text);
+                            }
+
                             // Save to history with post-processed text and prompt
                             let hm_clone = Arc::clone(&hm);
                             let transcription_for_history = transcription.clone();
+                            let metadata = TranscriptionMetadata {
+                                model: tm.get_current_model(),
+                                source: Some(
+                                    settings
+                                        .audio_source
+                                        .unwrap_or(AudioSource::Microphone)
+                                        .into(),
+                                ),
+                                latency_ms: Some(stop_recording_time.elapsed().as_millis() as u64),
+                                language: Some(settings.selected_language.clone()),
+                                app_name,
+                            };
                             tauri::async_runtime::spawn(async move {
                                 if let Err(e) = hm_clone
                                     .save_transcription(
@@ -354,6 +666,7 @@ impl ShortcutAction for TranscribeAction {
                                         transcription_for_history,
                                         post_processed_text,
                                         post_process_prompt,
+                                        metadata,
                                     )
                                     .await
                                 {
@@ -361,13 +674,22 @@ impl ShortcutAction for TranscribeAction {
                                 }
                             });
 
+                            if stitch_window > 0 {
+                                push_stitched_segment(&final_text);
+                                utils::hide_recording_overlay(&ah);
+                                change_tray_icon(&ah, TrayIconState::Idle);
+                                return;
+                            }
+
                             // Paste the final text (either processed or original)
                             let ah_clone = ah.clone();
                             let paste_time = Instant::now();
+                            let mode = settings.output_mode_for(&binding_id);
                             ah.run_on_main_thread(move || {
-                                match utils::paste(final_text, ah_clone.clone()) {
+                                match utils::output(final_text, mode, ah_clone.clone()) {
                                     Ok(()) => debug!(
-                                        "Text pasted successfully in {:?}",
+                                        "Text output ({:?}) in {:?}",
+                                        mode,
                                         paste_time.elapsed()
                                     ),
                                     Err(e) => error!("Failed to paste transcription: {}", e),
@@ -429,6 +751,46 @@ impl ShortcutAction for TestAction {
     }
 }
 
+// Cycle Language Action
+struct CycleLanguageAction;
+
+/// The language after `current` in `cycle`, wrapping around. A language that
+/// isn't in the list jumps to the first entry.
+fn next_language(cycle: &[String], current: &str) -> Option<String> {
+    let next = match cycle.iter().position(|l| l == current) {
+        Some(i) => (i + 1) % cycle.len(),
+        None => 0,
+    };
+    cycle.get(next).cloned()
+}
+
+impl ShortcutAction for CycleLanguageAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        let mut settings = get_settings(app);
+        let Some(language) = next_language(&settings.language_cycle, &settings.selected_language)
+        else {
+            warn!("Language cycle list is empty; add languages in settings to use this shortcut");
+            return;
+        };
+
+        // Transcription reads the language from settings on every run, so the
+        // next recording or caption window already uses it
+        info!("🌐 Switched transcription language to '{}'", language);
+        settings.selected_language = language.clone();
+        write_settings(app, settings);
+
+        if let Err(e) = app.emit("language-changed", language) {
+            error!("Failed to emit language-changed event: {}", e);
+        }
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {}
+
+    fn is_instant(&self) -> bool {
+        true
+    }
+}
+
 // Static Action Map
 pub static ACTION_MAP: Lazy<HashMap<String, Arc<dyn ShortcutAction>>> = Lazy::new(|| {
     let mut map = HashMap::new();
@@ -436,9 +798,36 @@ pub static ACTION_MAP: Lazy<HashMap<String, Arc<dyn ShortcutAction>>> = Lazy::ne
         "transcribe".to_string(),
         Arc::new(TranscribeAction) as Arc<dyn ShortcutAction>,
     );
+    map.insert(
+        "cycle_language".to_string(),
+        Arc::new(CycleLanguageAction) as Arc<dyn ShortcutAction>,
+    );
     map.insert(
         "test".to_string(),
         Arc::new(TestAction) as Arc<dyn ShortcutAction>,
     );
     map
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_segments_spacing() {
+        assert_eq!(join_segments("", "hello"), "hello");
+        assert_eq!(join_segments("hello", "world"), "hello world");
+        assert_eq!(join_segments("hello", ", world"), "hello, world");
+        assert_eq!(join_segments("hello ", " world"), "hello world");
+        assert_eq!(join_segments("done", "."), "done.");
+    }
+
+    #[test]
+    fn test_next_language_wraps_around() {
+        let cycle: Vec<String> = ["en", "vi", "ja"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(next_language(&cycle, "en").as_deref(), Some("vi"));
+        assert_eq!(next_language(&cycle, "ja").as_deref(), Some("en"));
+        assert_eq!(next_language(&cycle, "fr").as_deref(), Some("en"));
+        assert_eq!(next_language(&[], "en"), None);
+    }
+}
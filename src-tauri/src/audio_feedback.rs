@@ -1,14 +1,23 @@
+use crate::audio_toolkit::find_device;
+use crate::managers::audio::AudioRecordingManager;
 use crate::settings::SoundTheme;
 use crate::settings::{self, AppSettings};
-use cpal::traits::{DeviceTrait, HostTrait};
 use log::{debug, error, warn};
 use rodio::OutputStreamBuilder;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Manager};
 
+// How long a feedback sound waits for the speaker to pause
+const SPEECH_WAIT: Duration = Duration::from_millis(1500);
+const SPEECH_POLL_INTERVAL: Duration = Duration::from_millis(30);
+// Volume multiplier for a sound that has to play over speech anyway
+const DUCKED_VOLUME: f32 = 0.2;
+
 pub enum SoundType {
     Start,
     Stop,
@@ -46,7 +55,7 @@ pub fn play_feedback_sound(app: &AppHandle, sound_type: SoundType) {
         return;
     }
     if let Some(path) = resolve_sound_path(app, &settings, sound_type) {
-        play_sound_async(app, path);
+        play_sound_async(app, path, true);
     }
 }
 
@@ -56,62 +65,79 @@ pub fn play_feedback_sound_blocking(app: &AppHandle, sound_type: SoundType) {
         return;
     }
     if let Some(path) = resolve_sound_path(app, &settings, sound_type) {
-        play_sound_blocking(app, &path);
+        play_sound_blocking(app, &path, true);
     }
 }
 
 pub fn play_test_sound(app: &AppHandle, sound_type: SoundType) {
     let settings = settings::get_settings(app);
     if let Some(path) = resolve_sound_path(app, &settings, sound_type) {
-        play_sound_async(app, path);
+        play_sound_async(app, path, false);
+    }
+}
+
+/// Hold a feedback sound back while the recorder's VAD hears speech so it
+/// doesn't end up in the recording. Returns the volume multiplier to play
+/// at: full once the speaker pauses, ducked if they keep talking.
+fn wait_for_speech_pause(app: &AppHandle) -> f32 {
+    let Some(rm) = app.try_state::<Arc<AudioRecordingManager>>() else {
+        return 1.0;
+    };
+
+    let deadline = Instant::now() + SPEECH_WAIT;
+    while rm.is_speech_active() {
+        if Instant::now() >= deadline {
+            debug!("Speech still active, ducking feedback sound");
+            return DUCKED_VOLUME;
+        }
+        thread::sleep(SPEECH_POLL_INTERVAL);
     }
+    1.0
 }
 
-fn play_sound_async(app: &AppHandle, path: PathBuf) {
+fn play_sound_async(app: &AppHandle, path: PathBuf, duck: bool) {
     let app_handle = app.clone();
     thread::spawn(move || {
-        if let Err(e) = play_sound_at_path(&app_handle, path.as_path()) {
+        if let Err(e) = play_sound_at_path(&app_handle, path.as_path(), duck) {
             error!("Failed to play sound '{}': {}", path.display(), e);
         }
     });
 }
 
-fn play_sound_blocking(app: &AppHandle, path: &Path) {
-    if let Err(e) = play_sound_at_path(app, path) {
+fn play_sound_blocking(app: &AppHandle, path: &Path, duck: bool) {
+    if let Err(e) = play_sound_at_path(app, path, duck) {
         error!("Failed to play sound '{}': {}", path.display(), e);
     }
 }
 
-fn play_sound_at_path(app: &AppHandle, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+fn play_sound_at_path(
+    app: &AppHandle,
+    path: &Path,
+    duck: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     let settings = settings::get_settings(app);
-    let volume = settings.audio_feedback_volume;
+    let mut volume = settings.audio_feedback_volume;
+    if duck {
+        volume *= wait_for_speech_pause(app);
+    }
     let selected_device = settings.selected_output_device.clone();
     play_audio_file(path, selected_device, volume)
 }
 
-fn play_audio_file(
-    path: &std::path::Path,
+/// Open `selected_device`, falling back to the default output device when
+/// it's unset or no longer connected
+pub fn open_output_stream(
     selected_device: Option<String>,
-    volume: f32,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<rodio::OutputStream, Box<dyn std::error::Error>> {
     let stream_builder = if let Some(device_name) = selected_device {
         if device_name == "Default" {
             debug!("Using default device");
             OutputStreamBuilder::from_default_device()?
         } else {
-            let host = crate::audio_toolkit::get_cpal_host();
-            let devices = host.output_devices()?;
-
-            let mut found_device = None;
-            for device in devices {
-                if device.name()? == device_name {
-                    found_device = Some(device);
-                    break;
-                }
-            }
+            let devices = crate::audio_toolkit::list_output_devices()?;
 
-            match found_device {
-                Some(device) => OutputStreamBuilder::from_device(device)?,
+            match find_device(devices, &device_name) {
+                Some(info) => OutputStreamBuilder::from_device(info.device)?,
                 None => {
                     warn!("Device '{}' not found, using default device", device_name);
                     OutputStreamBuilder::from_default_device()?
@@ -123,7 +149,15 @@ fn play_audio_file(
         OutputStreamBuilder::from_default_device()?
     };
 
-    let stream_handle = stream_builder.open_stream()?;
+    Ok(stream_builder.open_stream()?)
+}
+
+fn play_audio_file(
+    path: &std::path::Path,
+    selected_device: Option<String>,
+    volume: f32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stream_handle = open_output_stream(selected_device)?;
     let mixer = stream_handle.mixer();
 
     let file = File::open(path)?;
@@ -0,0 +1,59 @@
+use super::preprocessor::PreprocessStage;
+use super::resampler::FrameResampler;
+use nnnoiseless::DenoiseState;
+use std::time::Duration;
+
+// RNNoise only runs on 10ms frames of 48kHz audio
+const DENOISE_SAMPLE_RATE: usize = 48000;
+const FRAME_DURATION: Duration = Duration::from_millis(10);
+// RNNoise expects samples in the i16 range
+const I16_SCALE: f32 = i16::MAX as f32;
+
+/// RNNoise noise suppression (via nnnoiseless) as a preprocessing stage.
+/// Audio at other rates is resampled to 48kHz and back around the denoiser.
+pub struct Denoiser {
+    state: Box<DenoiseState<'static>>,
+}
+
+impl Denoiser {
+    pub fn new() -> Self {
+        Self {
+            state: DenoiseState::new(),
+        }
+    }
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PreprocessStage for Denoiser {
+    fn process(&mut self, samples: &mut Vec<f32>, sample_rate: usize) {
+        let mut upsampler = FrameResampler::new(sample_rate, DENOISE_SAMPLE_RATE, FRAME_DURATION);
+        let mut downsampler = FrameResampler::new(DENOISE_SAMPLE_RATE, sample_rate, FRAME_DURATION);
+        let mut output = Vec::with_capacity(samples.len());
+        let mut scaled = [0.0f32; DenoiseState::FRAME_SIZE];
+        let mut denoised = [0.0f32; DenoiseState::FRAME_SIZE];
+
+        let state = &mut self.state;
+        let mut on_frame = |frame: &[f32]| {
+            for (dst, &src) in scaled.iter_mut().zip(frame) {
+                *dst = src * I16_SCALE;
+            }
+            state.process_frame(&mut denoised, &scaled);
+            for sample in denoised.iter_mut() {
+                *sample /= I16_SCALE;
+            }
+            downsampler.push(&denoised, |chunk| output.extend_from_slice(chunk));
+        };
+        upsampler.push(samples, &mut on_frame);
+        upsampler.finish(&mut on_frame);
+        downsampler.finish(|chunk| output.extend_from_slice(chunk));
+
+        // Resampling pads the tail; keep the original length so timestamps line up
+        output.resize(samples.len(), 0.0);
+        *samples = output;
+    }
+}
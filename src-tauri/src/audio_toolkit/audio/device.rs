@@ -1,52 +1,256 @@
+use crate::audio_toolkit::error::Result;
 use cpal::traits::{DeviceTrait, HostTrait};
+use cpal::{SampleFormat, SupportedStreamConfig, SupportedStreamConfigRange};
+use serde::Serialize;
+use std::collections::HashMap;
+
+// Rates listed as supported when a device's range covers them
+const COMMON_SAMPLE_RATES: [u32; 10] = [
+    8000, 11025, 16000, 22050, 32000, 44100, 48000, 88200, 96000, 192000,
+];
 
 pub struct CpalDeviceInfo {
+    /// Stable identifier to persist instead of the name or index
+    pub id: String,
     pub index: String,
     pub name: String,
     pub is_default: bool,
     pub device: cpal::Device,
 }
 
-pub fn list_input_devices() -> Result<Vec<CpalDeviceInfo>, Box<dyn std::error::Error>> {
-    let host = crate::audio_toolkit::get_cpal_host();
-    let default_name = host.default_input_device().and_then(|d| d.name().ok());
-
-    let mut out = Vec::<CpalDeviceInfo>::new();
+/// Build stable ids for devices in enumeration order.
+///
+/// cpal doesn't expose platform UIDs, so a device is keyed by its name plus its
+/// default format, which tells apart most identically named devices (e.g. two
+/// "USB Audio Device" entries that are a mic and a headset). Devices that still
+/// share a key get a "#2", "#3"... suffix.
+fn stable_ids(keys: Vec<String>) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    keys.into_iter()
+        .map(|key| {
+            let count = seen.entry(key.clone()).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                key
+            } else {
+                format!("{}#{}", key, count)
+            }
+        })
+        .collect()
+}
 
-    for (index, device) in host.input_devices()?.enumerate() {
-        let name = device.name().unwrap_or_else(|_| "Unknown".into());
+fn device_key(name: &str, config: Option<SupportedStreamConfig>) -> String {
+    match config {
+        Some(config) => format!(
+            "{}|{}ch@{}",
+            name,
+            config.channels(),
+            config.sample_rate().0
+        ),
+        None => name.to_string(),
+    }
+}
 
-        let is_default = Some(name.clone()) == default_name;
+fn collect_devices(
+    devices: impl Iterator<Item = cpal::Device>,
+    default_name: Option<String>,
+    default_config: impl Fn(&cpal::Device) -> Option<SupportedStreamConfig>,
+) -> Vec<CpalDeviceInfo> {
+    let devices: Vec<(String, cpal::Device)> = devices
+        .map(|device| (device.name().unwrap_or_else(|_| "Unknown".into()), device))
+        .collect();
+    let ids = stable_ids(
+        devices
+            .iter()
+            .map(|(name, device)| device_key(name, default_config(device)))
+            .collect(),
+    );
 
-        out.push(CpalDeviceInfo {
+    devices
+        .into_iter()
+        .zip(ids)
+        .enumerate()
+        .map(|(index, ((name, device), id))| CpalDeviceInfo {
+            id,
             index: index.to_string(),
+            is_default: Some(&name) == default_name.as_ref(),
             name,
-            is_default,
             device,
-        });
-    }
+        })
+        .collect()
+}
 
-    Ok(out)
+pub fn list_input_devices() -> Result<Vec<CpalDeviceInfo>> {
+    let host = crate::audio_toolkit::get_cpal_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+    Ok(collect_devices(host.input_devices()?, default_name, |d| {
+        d.default_input_config().ok()
+    }))
 }
 
-pub fn list_output_devices() -> Result<Vec<CpalDeviceInfo>, Box<dyn std::error::Error>> {
+pub fn list_output_devices() -> Result<Vec<CpalDeviceInfo>> {
     let host = crate::audio_toolkit::get_cpal_host();
     let default_name = host.default_output_device().and_then(|d| d.name().ok());
 
-    let mut out = Vec::<CpalDeviceInfo>::new();
+    Ok(collect_devices(host.output_devices()?, default_name, |d| {
+        d.default_output_config().ok()
+    }))
+}
 
-    for (index, device) in host.output_devices()?.enumerate() {
-        let name = device.name().unwrap_or_else(|_| "Unknown".into());
+/// One range of stream configs a device supports
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct ConfigRange {
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    /// e.g. "f32" or "i16"
+    pub sample_format: String,
+}
 
-        let is_default = Some(name.clone()) == default_name;
+impl From<&SupportedStreamConfigRange> for ConfigRange {
+    fn from(range: &SupportedStreamConfigRange) -> Self {
+        Self {
+            channels: range.channels(),
+            min_sample_rate: range.min_sample_rate().0,
+            max_sample_rate: range.max_sample_rate().0,
+            sample_format: sample_format_name(range.sample_format()),
+        }
+    }
+}
 
-        out.push(CpalDeviceInfo {
-            index: index.to_string(),
-            name,
-            is_default,
-            device,
-        });
+/// What a device can be opened with, from the configs cpal reports for it
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+pub struct DeviceCapabilities {
+    /// Common rates inside any supported range, ascending
+    pub sample_rates: Vec<u32>,
+    pub channel_counts: Vec<u16>,
+    pub sample_formats: Vec<String>,
+    pub ranges: Vec<ConfigRange>,
+    pub default_sample_rate: Option<u32>,
+    pub default_channels: Option<u16>,
+}
+
+impl DeviceCapabilities {
+    fn from_ranges(ranges: Vec<ConfigRange>, default: Option<SupportedStreamConfig>) -> Self {
+        let sample_rates = COMMON_SAMPLE_RATES
+            .into_iter()
+            .filter(|&rate| {
+                ranges
+                    .iter()
+                    .any(|r| r.min_sample_rate <= rate && rate <= r.max_sample_rate)
+            })
+            .collect();
+        let mut channel_counts: Vec<u16> = ranges.iter().map(|r| r.channels).collect();
+        channel_counts.sort_unstable();
+        channel_counts.dedup();
+        let mut sample_formats: Vec<String> =
+            ranges.iter().map(|r| r.sample_format.clone()).collect();
+        sample_formats.sort();
+        sample_formats.dedup();
+
+        Self {
+            sample_rates,
+            channel_counts,
+            sample_formats,
+            default_sample_rate: default.as_ref().map(|c| c.sample_rate().0),
+            default_channels: default.as_ref().map(|c| c.channels()),
+            ranges,
+        }
+    }
+
+    /// Whether any supported config has `format`
+    pub fn supports_format(&self, format: SampleFormat) -> bool {
+        let name = sample_format_name(format);
+        self.sample_formats.contains(&name)
+    }
+
+    /// Most channels any supported config has, 0 if there are none
+    pub fn max_channels(&self) -> u16 {
+        self.channel_counts.last().copied().unwrap_or(0)
     }
+}
+
+fn sample_format_name(format: SampleFormat) -> String {
+    format!("{:?}", format).to_lowercase()
+}
+
+/// Formats `device` can be opened with for recording
+pub fn input_capabilities(device: &cpal::Device) -> Result<DeviceCapabilities> {
+    let ranges = device
+        .supported_input_configs()?
+        .map(|range| ConfigRange::from(&range))
+        .collect();
+    Ok(DeviceCapabilities::from_ranges(
+        ranges,
+        device.default_input_config().ok(),
+    ))
+}
 
-    Ok(out)
+/// Formats `device` can be opened with for playback or loopback capture
+pub fn output_capabilities(device: &cpal::Device) -> Result<DeviceCapabilities> {
+    let ranges = device
+        .supported_output_configs()?
+        .map(|range| ConfigRange::from(&range))
+        .collect();
+    Ok(DeviceCapabilities::from_ranges(
+        ranges,
+        device.default_output_config().ok(),
+    ))
+}
+
+/// Find a saved device. `key` is a stable id, or a display name saved by
+/// older versions.
+pub fn find_device(devices: Vec<CpalDeviceInfo>, key: &str) -> Option<CpalDeviceInfo> {
+    match devices.iter().position(|d| d.id == key) {
+        Some(i) => devices.into_iter().nth(i),
+        None => devices.into_iter().find(|d| d.name == key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_summarize_ranges() {
+        let range = |channels, min, max, format: &str| ConfigRange {
+            channels,
+            min_sample_rate: min,
+            max_sample_rate: max,
+            sample_format: format.to_string(),
+        };
+        let caps = DeviceCapabilities::from_ranges(
+            vec![
+                range(2, 44100, 48000, "f32"),
+                range(1, 16000, 16000, "i16"),
+                range(2, 44100, 48000, "i16"),
+            ],
+            None,
+        );
+
+        assert_eq!(caps.sample_rates, vec![16000, 44100, 48000]);
+        assert_eq!(caps.channel_counts, vec![1, 2]);
+        assert_eq!(caps.sample_formats, vec!["f32", "i16"]);
+        assert!(caps.supports_format(SampleFormat::I16));
+        assert!(!caps.supports_format(SampleFormat::U8));
+        assert_eq!(caps.max_channels(), 2);
+    }
+
+    #[test]
+    fn test_duplicate_keys_get_suffixes() {
+        let keys = vec![
+            "USB Audio Device|1ch@48000".to_string(),
+            "MacBook Pro Microphone|1ch@48000".to_string(),
+            "USB Audio Device|1ch@48000".to_string(),
+        ];
+        assert_eq!(
+            stable_ids(keys),
+            vec![
+                "USB Audio Device|1ch@48000",
+                "MacBook Pro Microphone|1ch@48000",
+                "USB Audio Device|1ch@48000#2",
+            ]
+        );
+    }
 }
@@ -0,0 +1,56 @@
+use cpal::{FromSample, Sample};
+
+/// How a multi-channel frame is reduced to one mono sample
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Downmix {
+    /// Mean of all channels
+    #[default]
+    Average,
+    /// Only this channel, counting from 0. Frames with fewer channels are
+    /// averaged instead.
+    Channel(usize),
+}
+
+impl Downmix {
+    /// The mono sample for one interleaved `frame`
+    pub fn mix<T>(self, frame: &[T]) -> f32
+    where
+        T: Sample,
+        f32: FromSample<T>,
+    {
+        match self {
+            Downmix::Channel(index) if index < frame.len() => frame[index].to_sample::<f32>(),
+            _ => frame.iter().map(|&s| s.to_sample::<f32>()).sum::<f32>() / frame.len() as f32,
+        }
+    }
+
+    /// Mono samples for planar audio, one slice per channel
+    pub fn mix_planar(self, planes: &[Vec<f32>]) -> Vec<f32> {
+        let len = planes.iter().map(Vec::len).min().unwrap_or(0);
+        match self {
+            Downmix::Channel(index) if index < planes.len() => planes[index][..len].to_vec(),
+            _ => (0..len)
+                .map(|i| planes.iter().map(|plane| plane[i]).sum::<f32>() / planes.len() as f32)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selects_or_averages_channels() {
+        let frame = [0.2f32, 0.6];
+        assert!((Downmix::Average.mix(&frame) - 0.4).abs() < 1e-6);
+        assert_eq!(Downmix::Channel(1).mix(&frame), 0.6);
+        // Missing channels fall back to the average
+        assert!((Downmix::Channel(3).mix(&frame) - 0.4).abs() < 1e-6);
+
+        let planes = vec![vec![0.2, 0.4], vec![0.6, 0.8, 1.0]];
+        assert_eq!(Downmix::Channel(0).mix_planar(&planes), vec![0.2, 0.4]);
+        let averaged = Downmix::Average.mix_planar(&planes);
+        assert!((averaged[1] - 0.6).abs() < 1e-6);
+    }
+}
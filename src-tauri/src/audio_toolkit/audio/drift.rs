@@ -0,0 +1,179 @@
+use std::collections::VecDeque;
+
+// Samples per second of the streams being aligned
+const SAMPLE_RATE: f64 = 16000.0;
+// The queue depth is averaged over about this long before it is trusted;
+// callbacks deliver in bursts, so single readings swing by a whole buffer
+const FILL_SMOOTHING_SECS: f64 = 1.0;
+// How quickly a change in depth is corrected; long enough that the pitch
+// shift stays far below anything audible or disturbing to the canceller
+const CORRECTION_SECS: f64 = 10.0;
+// Largest rate correction applied. Real clocks differ by a few hundred ppm;
+// more than this means one stream stalled, not drifted.
+const MAX_CORRECTION: f64 = 0.002;
+
+/// Pulls a stream from a queue filled on another clock, resampling it by a
+/// tiny adaptive ratio so the queue's depth holds at the level it settled at
+/// instead of slowly growing or draining. Two streams nominally at the same
+/// rate stay aligned however long they run.
+///
+/// The depth seen over the first second sets the target. Rate adjustments
+/// come from a PI loop on the smoothed depth; the integral converges on the
+/// real clock difference, reported by `drift_ppm`.
+#[derive(Debug, Clone)]
+pub struct DriftCompensator {
+    // Input samples consumed per output sample
+    ratio: f64,
+    integral: f64,
+    fill: f64,
+    target: Option<f64>,
+    settled_for: f64,
+    // Interpolation state: `last` is the sample at position 0, the queue's
+    // front at position 1
+    last: f32,
+    position: f64,
+}
+
+impl Default for DriftCompensator {
+    fn default() -> Self {
+        Self {
+            ratio: 1.0,
+            integral: 0.0,
+            fill: 0.0,
+            target: None,
+            settled_for: 0.0,
+            last: 0.0,
+            position: 0.0,
+        }
+    }
+}
+
+impl DriftCompensator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Estimated rate of the queued stream relative to the consumer, in
+    /// parts per million; positive when the queued stream runs fast
+    pub fn drift_ppm(&self) -> f64 {
+        self.integral / CORRECTION_SECS.powi(2) * 1e6
+    }
+
+    /// Fill `out` from `queue`, padding with silence if it runs dry
+    pub fn pull(&mut self, queue: &mut VecDeque<f32>, out: &mut [f32]) {
+        self.track(queue.len(), out.len());
+
+        let mut starved = false;
+        for slot in out.iter_mut() {
+            let next = queue.front().copied().unwrap_or(0.0);
+            *slot = self.last + (next - self.last) * self.position as f32;
+
+            self.position += self.ratio;
+            while self.position >= 1.0 {
+                self.position -= 1.0;
+                self.last = match queue.pop_front() {
+                    Some(sample) => sample,
+                    None => {
+                        starved = true;
+                        0.0
+                    }
+                };
+            }
+        }
+
+        if starved {
+            // The producer stalled (loopback often stops on silence), so the
+            // depth says nothing about the clocks. Relock once it resumes and
+            // keep the learned drift.
+            self.target = None;
+            self.settled_for = 0.0;
+        }
+    }
+
+    fn track(&mut self, depth: usize, consumed: usize) {
+        let dt = consumed as f64 / SAMPLE_RATE;
+        let depth = depth as f64;
+
+        let Some(target) = self.target else {
+            if depth == 0.0 {
+                return;
+            }
+            // Plain running mean while settling
+            self.settled_for += dt;
+            let weight = dt / self.settled_for;
+            self.fill += (depth - self.fill) * weight;
+            if self.settled_for >= FILL_SMOOTHING_SECS {
+                self.target = Some(self.fill);
+            }
+            return;
+        };
+
+        let alpha = (dt / FILL_SMOOTHING_SECS).min(1.0);
+        self.fill += (depth - self.fill) * alpha;
+
+        // Critically damped loop: proportional on the depth error, integral
+        // absorbing the steady clock difference
+        let error = (self.fill - target) / SAMPLE_RATE;
+        let max_integral = MAX_CORRECTION * CORRECTION_SECS.powi(2);
+        self.integral = (self.integral + error * dt).clamp(-max_integral, max_integral);
+        let correction = 2.0 * error / CORRECTION_SECS + self.integral / CORRECTION_SECS.powi(2);
+        self.ratio = 1.0 + correction.clamp(-MAX_CORRECTION, MAX_CORRECTION);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Run a producer `ppm` faster than the consumer for `secs`, returning the
+    // compensator and the queue depth at each pull after the first minute
+    fn simulate(ppm: f64, secs: usize) -> (DriftCompensator, Vec<usize>) {
+        let mut drift = DriftCompensator::new();
+        // Loopback typically starts a few buffers ahead of the mic
+        let mut queue: VecDeque<f32> = std::iter::repeat(0.1).take(800).collect();
+        let mut out = vec![0.0; 480];
+        let mut produced = 0.0;
+        let mut depths = Vec::new();
+
+        for frame in 0..secs * 1000 / 30 {
+            // Producer delivers 10ms bursts, the consumer takes 30ms frames
+            for _ in 0..3 {
+                produced += 160.0 * (1.0 + ppm / 1e6);
+                let whole = produced.floor();
+                produced -= whole;
+                queue.extend(std::iter::repeat(0.1).take(whole as usize));
+            }
+            drift.pull(&mut queue, &mut out);
+            if frame >= 2000 {
+                depths.push(queue.len());
+            }
+        }
+        (drift, depths)
+    }
+
+    #[test]
+    fn test_holds_depth_against_drift() {
+        for ppm in [-300.0, 0.0, 500.0] {
+            let (drift, depths) = simulate(ppm, 600);
+            let max = *depths.iter().max().unwrap();
+            let min = *depths.iter().min().unwrap();
+            // Ten minutes at 500ppm would otherwise add 4800 samples
+            assert!(max - min < 200, "{ppm}ppm: depth ranged {min}..{max}");
+            assert!(
+                (drift.drift_ppm() - ppm).abs() < 50.0,
+                "{ppm}ppm estimated as {}",
+                drift.drift_ppm()
+            );
+        }
+    }
+
+    #[test]
+    fn test_unit_ratio_passes_samples_through() {
+        let mut drift = DriftCompensator::new();
+        let mut queue: VecDeque<f32> = (1..=8).map(|i| i as f32).collect();
+        let mut out = vec![0.0; 4];
+        drift.pull(&mut queue, &mut out);
+        // One sample of interpolation latency
+        assert_eq!(out, vec![0.0, 1.0, 2.0, 3.0]);
+    }
+}
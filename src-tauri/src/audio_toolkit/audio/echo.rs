@@ -0,0 +1,176 @@
+use super::drift::DriftCompensator;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+// 128ms at 16kHz: covers loopback/mic misalignment plus the room's echo tail
+const FILTER_TAPS: usize = 2048;
+// NLMS step size; larger adapts faster but leaves more residual echo
+const STEP_SIZE: f32 = 0.3;
+// Keeps the normalisation stable while the far end is silent
+const REGULARISATION: f32 = 1e-3;
+// Geigel double-talk detector: a mic peak above this fraction of the recent
+// far-end peak means the user is talking, so the filter stops adapting
+const DOUBLE_TALK_THRESHOLD: f32 = 0.5;
+// Far-end audio queued beyond this (1s at 16kHz) is stale and dropped
+const MAX_REFERENCE_SAMPLES: usize = 16000;
+
+/// Far-end (system audio) samples at 16kHz, shared between the loopback
+/// capture that fills it and the canceller that consumes it. The loopback
+/// runs on the output device's clock, so samples are taken through a
+/// `DriftCompensator` to stay aligned with the mic over long recordings.
+#[derive(Clone, Default)]
+pub struct EchoReference {
+    inner: Arc<Mutex<ReferenceQueue>>,
+}
+
+#[derive(Default)]
+struct ReferenceQueue {
+    samples: VecDeque<f32>,
+    drift: DriftCompensator,
+}
+
+impl EchoReference {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, samples: &[f32]) {
+        let queue = &mut self.inner.lock().unwrap().samples;
+        queue.extend(samples);
+        if queue.len() > MAX_REFERENCE_SAMPLES {
+            let excess = queue.len() - MAX_REFERENCE_SAMPLES;
+            queue.drain(..excess);
+        }
+    }
+
+    /// How far the loopback's clock runs ahead of the mic's, in ppm
+    pub fn drift_ppm(&self) -> f64 {
+        self.inner.lock().unwrap().drift.drift_ppm()
+    }
+
+    /// Fill `out` with the oldest queued samples, padding with silence when
+    /// the loopback hasn't delivered enough yet
+    fn take(&self, out: &mut [f32]) {
+        let ReferenceQueue { samples, drift } = &mut *self.inner.lock().unwrap();
+        drift.pull(samples, out);
+    }
+}
+
+/// Removes speaker feedback from the microphone with an NLMS adaptive filter
+/// driven by the system-audio loopback. Reference samples are consumed in step
+/// with microphone samples, so both must be at the same rate.
+pub struct EchoCanceller {
+    reference: EchoReference,
+    weights: Vec<f32>,
+    // Most recent far-end samples, newest at `cursor`
+    history: Vec<f32>,
+    cursor: usize,
+    energy: f32,
+    far: Vec<f32>,
+}
+
+impl EchoCanceller {
+    pub fn new(reference: EchoReference) -> Self {
+        Self {
+            reference,
+            weights: vec![0.0; FILTER_TAPS],
+            history: vec![0.0; FILTER_TAPS],
+            cursor: 0,
+            energy: 0.0,
+            far: Vec::new(),
+        }
+    }
+
+    /// Cancel echo from one frame of microphone audio in place
+    pub fn process(&mut self, mic: &mut [f32]) {
+        self.far.resize(mic.len(), 0.0);
+        self.reference.take(&mut self.far);
+
+        let mic_peak = mic.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        let far_peak = self
+            .history
+            .iter()
+            .chain(self.far.iter())
+            .fold(0.0f32, |m, s| m.max(s.abs()));
+        let adapt = mic_peak <= DOUBLE_TALK_THRESHOLD * far_peak;
+
+        let far_frame = std::mem::take(&mut self.far);
+        for (sample, &far) in mic.iter_mut().zip(far_frame.iter()) {
+            self.cursor = (self.cursor + 1) % FILTER_TAPS;
+            let oldest = self.history[self.cursor];
+            self.energy = (self.energy + far * far - oldest * oldest).max(0.0);
+            self.history[self.cursor] = far;
+
+            let estimate = self.dot_history();
+            let error = *sample - estimate;
+            *sample = error;
+
+            if adapt {
+                let gain = STEP_SIZE * error / (self.energy + REGULARISATION);
+                self.update_weights(gain);
+            }
+        }
+        self.far = far_frame;
+    }
+
+    // weights[k] pairs with the far-end sample k steps in the past
+    fn history_newest_first(&self) -> impl Iterator<Item = &f32> {
+        let (older, newer) = self.history.split_at(self.cursor + 1);
+        older.iter().rev().chain(newer.iter().rev())
+    }
+
+    fn dot_history(&self) -> f32 {
+        self.history_newest_first()
+            .zip(self.weights.iter())
+            .map(|(x, w)| x * w)
+            .sum()
+    }
+
+    fn update_weights(&mut self, gain: f32) {
+        let (older, newer) = self.history.split_at(self.cursor + 1);
+        let taps = older.iter().rev().chain(newer.iter().rev());
+        for (w, &x) in self.weights.iter_mut().zip(taps) {
+            *w += gain * x;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancels_delayed_echo() {
+        // Deterministic white noise as the far end
+        let mut state = 12345u32;
+        let far: Vec<f32> = (0..16000 * 4)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                (state >> 16) as f32 / 32768.0 - 1.0
+            })
+            .collect();
+        let delay = 40;
+        let mic: Vec<f32> = (0..far.len())
+            .map(|i| {
+                if i >= delay {
+                    0.4 * far[i - delay]
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        let reference = EchoReference::new();
+        let mut canceller = EchoCanceller::new(reference.clone());
+        let mut last = Vec::new();
+        for (far_frame, mic_frame) in far.chunks(480).zip(mic.chunks(480)) {
+            reference.push(far_frame);
+            last = mic_frame.to_vec();
+            canceller.process(&mut last);
+        }
+
+        let before: f32 = mic[mic.len() - 480..].iter().map(|s| s * s).sum();
+        let after: f32 = last.iter().map(|s| s * s).sum();
+        assert!(after < before * 0.01, "residual {after} vs echo {before}");
+    }
+}
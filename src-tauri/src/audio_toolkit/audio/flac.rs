@@ -0,0 +1,386 @@
+//! Lossless FLAC encoding for stored recordings.
+//!
+//! Writes 16-bit mono streams with FLAC's fixed predictors and Rice-coded
+//! residuals. That is enough to bring speech well under WAV size without a
+//! native encoder; decoding goes through symphonia like any other file.
+
+use crate::audio_toolkit::error::Result;
+use log::debug;
+use std::path::Path;
+
+/// Samples per frame
+const BLOCK_SIZE: usize = 4096;
+const BITS_PER_SAMPLE: u32 = 16;
+/// Largest Rice parameter; 15 is reserved as the escape code
+const MAX_RICE_PARAM: u32 = 14;
+const MAX_PARTITION_ORDER: u32 = 6;
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    /// Append the low `bits` bits of `value`, most significant first
+    fn write(&mut self, value: u64, bits: u32) {
+        debug_assert!(bits <= 32);
+        if bits == 0 {
+            return;
+        }
+        self.acc = (self.acc << bits) | (value & ((1u64 << bits) - 1));
+        self.bits += bits;
+        while self.bits >= 8 {
+            self.bits -= 8;
+            self.bytes.push((self.acc >> self.bits) as u8);
+        }
+    }
+
+    fn write_signed(&mut self, value: i64, bits: u32) {
+        self.write(value as u64, bits);
+    }
+
+    /// `zeros` zero bits followed by a one
+    fn write_unary(&mut self, mut zeros: u64) {
+        while zeros >= 32 {
+            self.write(0, 32);
+            zeros -= 32;
+        }
+        self.write(1, zeros as u32 + 1);
+    }
+
+    /// Pad with zero bits to the next byte boundary
+    fn align(&mut self) {
+        if self.bits > 0 {
+            self.write(0, 8 - self.bits);
+        }
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+/// Frame numbers use the same variable-length coding as UTF-8
+fn write_frame_number(w: &mut BitWriter, n: u64) {
+    if n < 0x80 {
+        w.write(n, 8);
+        return;
+    }
+    let mut continuation = Vec::new();
+    let mut rest = n;
+    // Bits left for the leading byte shrink by one per continuation byte
+    let mut lead_bits = 6;
+    while rest >= 1 << lead_bits {
+        continuation.push(0x80 | (rest & 0x3f));
+        rest >>= 6;
+        lead_bits -= 1;
+    }
+    let len = continuation.len() as u32 + 1;
+    let marker = (0xffu64 << (8 - len)) & 0xff;
+    w.write(marker | rest, 8);
+    for byte in continuation.iter().rev() {
+        w.write(*byte, 8);
+    }
+}
+
+/// Residual of the fixed predictor of `order` (0..=4) for each sample after
+/// the first `order`
+fn fixed_residual(samples: &[i32], order: usize) -> Vec<i64> {
+    (order..samples.len())
+        .map(|i| {
+            let x = |back: usize| samples[i - back] as i64;
+            match order {
+                0 => x(0),
+                1 => x(0) - x(1),
+                2 => x(0) - 2 * x(1) + x(2),
+                3 => x(0) - 3 * x(1) + 3 * x(2) - x(3),
+                _ => x(0) - 4 * x(1) + 6 * x(2) - 4 * x(3) + x(4),
+            }
+        })
+        .collect()
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Cheapest Rice parameter for a partition and its cost in bits
+fn best_rice_param(values: &[u64]) -> (u32, u64) {
+    (0..=MAX_RICE_PARAM)
+        .map(|k| {
+            let bits = values.iter().map(|&u| (u >> k) + 1 + k as u64).sum::<u64>();
+            (k, bits)
+        })
+        .min_by_key(|&(_, bits)| bits)
+        .unwrap_or((0, 0))
+}
+
+/// Partition order and per-partition Rice parameters with their total cost
+struct ResidualPlan {
+    order: u32,
+    params: Vec<u32>,
+    bits: u64,
+}
+
+fn plan_residual(residual: &[u64], block_size: usize, predictor_order: usize) -> ResidualPlan {
+    let mut best: Option<ResidualPlan> = None;
+    for order in 0..=MAX_PARTITION_ORDER {
+        let partitions = 1usize << order;
+        if block_size % partitions != 0 || block_size / partitions < predictor_order {
+            break;
+        }
+        let per_partition = block_size / partitions;
+        let mut params = Vec::with_capacity(partitions);
+        let mut bits = 0;
+        let mut start = 0;
+        for p in 0..partitions {
+            // The warm-up samples come out of the first partition
+            let len = if p == 0 {
+                per_partition - predictor_order
+            } else {
+                per_partition
+            };
+            let (param, cost) = best_rice_param(&residual[start..start + len]);
+            params.push(param);
+            bits += 4 + cost;
+            start += len;
+        }
+        if best.as_ref().is_none_or(|b| bits < b.bits) {
+            best = Some(ResidualPlan {
+                order,
+                params,
+                bits,
+            });
+        }
+    }
+    best.expect("partition order 0 always fits")
+}
+
+fn write_subframe(w: &mut BitWriter, block: &[i32]) {
+    // Zero padding bit and no wasted bits around the type
+    if block.iter().all(|&s| s == block[0]) {
+        w.write(0, 1);
+        w.write(0b000000, 6);
+        w.write(0, 1);
+        w.write_signed(block[0] as i64, BITS_PER_SAMPLE);
+        return;
+    }
+
+    let best = (0..=4usize.min(block.len() - 1))
+        .map(|order| {
+            let residual: Vec<u64> = fixed_residual(block, order)
+                .into_iter()
+                .map(zigzag)
+                .collect();
+            let plan = plan_residual(&residual, block.len(), order);
+            (order, residual, plan)
+        })
+        .min_by_key(|(order, _, plan)| *order as u64 * BITS_PER_SAMPLE as u64 + plan.bits);
+
+    let verbatim_bits = block.len() as u64 * BITS_PER_SAMPLE as u64;
+    match best {
+        Some((order, residual, plan))
+            if order as u64 * BITS_PER_SAMPLE as u64 + plan.bits + 6 < verbatim_bits =>
+        {
+            w.write(0, 1);
+            w.write(0b001000 | order as u64, 6);
+            w.write(0, 1);
+            for &s in &block[..order] {
+                w.write_signed(s as i64, BITS_PER_SAMPLE);
+            }
+            // Rice coding with 4-bit parameters
+            w.write(0b00, 2);
+            w.write(plan.order as u64, 4);
+            let partitions = 1usize << plan.order;
+            let per_partition = block.len() / partitions;
+            let mut start = 0;
+            for (p, &param) in plan.params.iter().enumerate() {
+                let len = if p == 0 {
+                    per_partition - order
+                } else {
+                    per_partition
+                };
+                w.write(param as u64, 4);
+                for &u in &residual[start..start + len] {
+                    w.write_unary(u >> param);
+                    w.write(u, param);
+                }
+                start += len;
+            }
+        }
+        _ => {
+            w.write(0, 1);
+            w.write(0b000001, 6);
+            w.write(0, 1);
+            for &s in block {
+                w.write_signed(s as i64, BITS_PER_SAMPLE);
+            }
+        }
+    }
+}
+
+fn write_frame(out: &mut Vec<u8>, block: &[i32], frame_number: u64) {
+    let mut w = BitWriter::default();
+    // Sync code, fixed block size strategy
+    w.write(0xfff8, 16);
+    // Block size as a 16-bit value after the header, sample rate from
+    // STREAMINFO, mono, 16 bits per sample
+    w.write(0b0111, 4);
+    w.write(0b0000, 4);
+    w.write(0b0000, 4);
+    w.write(0b100, 3);
+    w.write(0, 1);
+    write_frame_number(&mut w, frame_number);
+    w.write(block.len() as u64 - 1, 16);
+    let crc = crc8(&w.bytes);
+    w.write(crc as u64, 8);
+
+    write_subframe(&mut w, block);
+    w.align();
+    let crc = crc16(&w.bytes);
+    w.write(crc as u64, 16);
+
+    out.extend_from_slice(&w.bytes);
+}
+
+/// Encode mono f32 samples as a 16-bit FLAC stream
+pub fn encode_flac(samples: &[f32], sample_rate: u32) -> Vec<u8> {
+    let pcm: Vec<i32> = samples
+        .iter()
+        .map(|s| (s * i16::MAX as f32) as i16 as i32)
+        .collect();
+
+    let mut w = BitWriter::default();
+    w.bytes.extend_from_slice(b"fLaC");
+    // Last metadata block, STREAMINFO, 34 bytes
+    w.write(1, 1);
+    w.write(0, 7);
+    w.write(34, 24);
+    w.write(BLOCK_SIZE as u64, 16);
+    w.write(BLOCK_SIZE as u64, 16);
+    // Frame sizes unknown
+    w.write(0, 24);
+    w.write(0, 24);
+    w.write(sample_rate as u64, 20);
+    w.write(0, 3);
+    w.write(BITS_PER_SAMPLE as u64 - 1, 5);
+    w.write(pcm.len() as u64 >> 32, 4);
+    w.write(pcm.len() as u64, 32);
+    // No MD5 signature
+    for _ in 0..4 {
+        w.write(0, 32);
+    }
+
+    let mut out = w.bytes;
+    for (frame_number, block) in pcm.chunks(BLOCK_SIZE).enumerate() {
+        write_frame(&mut out, block, frame_number as u64);
+    }
+    out
+}
+
+/// Save audio samples as a FLAC file
+pub async fn save_flac_file<P: AsRef<Path>>(file_path: P, samples: &[f32]) -> Result<()> {
+    std::fs::write(file_path.as_ref(), encode_flac(samples, 16000))?;
+    debug!("Saved FLAC file: {:?}", file_path.as_ref());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::DecoderOptions;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    fn decode(bytes: Vec<u8>) -> Vec<i16> {
+        let mss = MediaSourceStream::new(Box::new(Cursor::new(bytes)), Default::default());
+        let mut hint = Hint::new();
+        hint.with_extension("flac");
+        let mut format = symphonia::default::get_probe()
+            .format(
+                &hint,
+                mss,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .unwrap()
+            .format;
+        let track = format.default_track().unwrap();
+        assert_eq!(track.codec_params.sample_rate, Some(16000));
+        let mut decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .unwrap();
+
+        let mut samples = Vec::new();
+        while let Ok(packet) = format.next_packet() {
+            let decoded = decoder.decode(&packet).unwrap();
+            let mut buf = SampleBuffer::<i16>::new(decoded.capacity() as u64, *decoded.spec());
+            buf.copy_interleaved_ref(decoded);
+            samples.extend_from_slice(buf.samples());
+        }
+        samples
+    }
+
+    #[test]
+    fn test_round_trip_is_lossless() {
+        // Tone with a little noise, silence and a short final block
+        let samples: Vec<f32> = (0..BLOCK_SIZE * 2 + 1234)
+            .map(|i| {
+                if (BLOCK_SIZE..BLOCK_SIZE + 500).contains(&i) {
+                    0.0
+                } else {
+                    let t = i as f32 / 16000.0;
+                    0.5 * (t * 440.0 * std::f32::consts::TAU).sin()
+                        + 0.01 * ((i * 7919 % 101) as f32 / 101.0 - 0.5)
+                }
+            })
+            .collect();
+        let expected: Vec<i16> = samples
+            .iter()
+            .map(|s| (s * i16::MAX as f32) as i16)
+            .collect();
+
+        let encoded = encode_flac(&samples, 16000);
+        assert!(encoded.len() < expected.len() * 2);
+        assert_eq!(decode(encoded), expected);
+    }
+
+    #[test]
+    fn test_frame_number_coding() {
+        let mut w = BitWriter::default();
+        write_frame_number(&mut w, 0x7f);
+        write_frame_number(&mut w, 0x80);
+        write_frame_number(&mut w, 0x800);
+        assert_eq!(w.bytes, vec![0x7f, 0xc2, 0x80, 0xe0, 0xa0, 0x80]);
+    }
+}
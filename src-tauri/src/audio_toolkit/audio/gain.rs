@@ -0,0 +1,93 @@
+/// Highest boost accepted, far past what normalization can make up for
+pub const MAX_GAIN_DB: f32 = 30.0;
+/// Lowest setting, for inputs that clip
+pub const MIN_GAIN_DB: f32 = -20.0;
+
+// Level above which the soft limiter starts bending samples toward full scale
+const LIMITER_THRESHOLD: f32 = 0.8;
+
+/// Software gain for one device, applied to mono samples before anything
+/// else sees them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gain {
+    factor: f32,
+    limiter: bool,
+}
+
+impl Default for Gain {
+    fn default() -> Self {
+        Self {
+            factor: 1.0,
+            limiter: false,
+        }
+    }
+}
+
+impl Gain {
+    /// Boost or cut by `db`, clamped to `MIN_GAIN_DB..=MAX_GAIN_DB`. With
+    /// `limiter` loud samples are compressed toward full scale instead of
+    /// clipped.
+    pub fn from_db(db: f32, limiter: bool) -> Self {
+        let db = if db.is_finite() { db } else { 0.0 };
+        Self {
+            factor: 10f32.powf(db.clamp(MIN_GAIN_DB, MAX_GAIN_DB) / 20.0),
+            limiter,
+        }
+    }
+
+    /// Whether applying this gain leaves samples as they are
+    pub fn is_unity(&self) -> bool {
+        self.factor == 1.0 && !self.limiter
+    }
+
+    pub fn apply(&self, sample: f32) -> f32 {
+        let boosted = sample * self.factor;
+        if !self.limiter {
+            return boosted.clamp(-1.0, 1.0);
+        }
+        let magnitude = boosted.abs();
+        if magnitude <= LIMITER_THRESHOLD {
+            return boosted;
+        }
+        // tanh knee from the threshold, approaching full scale
+        let headroom = 1.0 - LIMITER_THRESHOLD;
+        let limited =
+            LIMITER_THRESHOLD + headroom * ((magnitude - LIMITER_THRESHOLD) / headroom).tanh();
+        limited.copysign(boosted)
+    }
+
+    pub fn apply_slice(&self, samples: &mut [f32]) {
+        if self.is_unity() {
+            return;
+        }
+        for sample in samples {
+            *sample = self.apply(*sample);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boost_clips_or_limits() {
+        let mut samples = [0.1f32, -0.5];
+        Gain::from_db(20.0, false).apply_slice(&mut samples);
+        assert!((samples[0] - 1.0).abs() < 1e-5);
+        assert_eq!(samples[1], -1.0);
+
+        let limited = Gain::from_db(20.0, true);
+        // Quiet samples pass through the limiter unchanged
+        assert!((limited.apply(0.05) - 0.5).abs() < 1e-5);
+        let loud = limited.apply(-0.09);
+        assert!(loud < -LIMITER_THRESHOLD && loud > -1.0);
+
+        // Out-of-range settings are clamped
+        assert_eq!(
+            Gain::from_db(100.0, false),
+            Gain::from_db(MAX_GAIN_DB, false)
+        );
+        assert!(Gain::from_db(0.0, false).is_unity());
+    }
+}
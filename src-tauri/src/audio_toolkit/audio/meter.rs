@@ -0,0 +1,183 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Samples at least this loud are taken to be at full scale
+const FULL_SCALE: f32 = 0.999;
+/// Full-scale samples in a row that count as one clip; single ones are
+/// usually just a loud transient that happened to peak there
+const CLIP_RUN: usize = 3;
+/// Clips within `CLIP_WINDOW` that raise a clipping alert
+const CLIP_THRESHOLD: usize = 3;
+const CLIP_WINDOW: Duration = Duration::from_secs(5);
+/// Least time between two clipping alerts
+const CLIP_COOLDOWN: Duration = Duration::from_secs(60);
+/// How long the held peak and clip indicator stay before falling back
+const PEAK_HOLD: Duration = Duration::from_millis(1500);
+/// How fast the held peak falls once released
+const PEAK_FALL_DB_PER_SEC: f32 = 20.0;
+/// Level reported for silence
+const FLOOR_DB: f32 = -100.0;
+
+/// One reading of the level meter, sent as the `mic-level` event
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LevelFrame {
+    /// Level bars from the visualiser, 0 to 1
+    pub bars: Vec<f32>,
+    /// Loudest sample since the previous reading, in dBFS
+    pub peak_db: f32,
+    /// Loudest recent peak, held for a moment before it falls back
+    pub held_peak_db: f32,
+    /// The input clipped within the hold time
+    pub clipping: bool,
+}
+
+/// Peak-hold meter with clip detection over the input samples
+pub struct LevelMeter {
+    // Loudest sample since the last reading
+    peak: f32,
+    held_db: f32,
+    held_at: Option<Instant>,
+    // Full-scale samples in a row so far
+    run: usize,
+    clips: VecDeque<Instant>,
+    last_clip: Option<Instant>,
+    alerted_at: Option<Instant>,
+    alert: Option<usize>,
+}
+
+impl LevelMeter {
+    pub fn new() -> Self {
+        Self {
+            peak: 0.0,
+            held_db: FLOOR_DB,
+            held_at: None,
+            run: 0,
+            clips: VecDeque::new(),
+            last_clip: None,
+            alerted_at: None,
+            alert: None,
+        }
+    }
+
+    /// Measure input that arrived at `now`
+    pub fn feed(&mut self, samples: &[f32], now: Instant) {
+        for &sample in samples {
+            let level = sample.abs();
+            self.peak = self.peak.max(level);
+            if level < FULL_SCALE {
+                self.run = 0;
+                continue;
+            }
+            self.run += 1;
+            if self.run == CLIP_RUN {
+                self.clip(now);
+            }
+        }
+    }
+
+    fn clip(&mut self, now: Instant) {
+        self.last_clip = Some(now);
+        while self
+            .clips
+            .front()
+            .is_some_and(|first| now.saturating_duration_since(*first) > CLIP_WINDOW)
+        {
+            self.clips.pop_front();
+        }
+        self.clips.push_back(now);
+        let cooling_down = self
+            .alerted_at
+            .is_some_and(|at| now.saturating_duration_since(at) < CLIP_COOLDOWN);
+        if self.clips.len() >= CLIP_THRESHOLD && !cooling_down {
+            self.alerted_at = Some(now);
+            self.alert = Some(self.clips.len());
+        }
+    }
+
+    /// Clips counted within the window if they came often enough to warn
+    /// about since the last call
+    pub fn take_alert(&mut self) -> Option<usize> {
+        self.alert.take()
+    }
+
+    /// The reading at `now`, with `bars` from the visualiser. Starts the
+    /// next peak measurement.
+    pub fn frame(&mut self, bars: Vec<f32>, now: Instant) -> LevelFrame {
+        let peak_db = to_db(std::mem::take(&mut self.peak));
+        let held = match self.held_at {
+            Some(at) => {
+                let released = now.saturating_duration_since(at).saturating_sub(PEAK_HOLD);
+                (self.held_db - PEAK_FALL_DB_PER_SEC * released.as_secs_f32()).max(FLOOR_DB)
+            }
+            None => FLOOR_DB,
+        };
+        if peak_db >= held {
+            self.held_db = peak_db;
+            self.held_at = Some(now);
+        }
+        LevelFrame {
+            bars,
+            peak_db,
+            held_peak_db: peak_db.max(held),
+            clipping: self
+                .last_clip
+                .is_some_and(|at| now.saturating_duration_since(at) <= PEAK_HOLD),
+        }
+    }
+}
+
+impl Default for LevelMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn to_db(level: f32) -> f32 {
+    if level > 0.0 {
+        (20.0 * level.log10()).max(FLOOR_DB)
+    } else {
+        FLOOR_DB
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peak_hold_and_clipping_alert() {
+        let start = Instant::now();
+        let mut meter = LevelMeter::new();
+        meter.feed(&[0.1, -0.5, 0.2], start);
+        let frame = meter.frame(vec![0.3], start);
+        assert!((frame.peak_db - -6.0206).abs() < 1e-3);
+        assert_eq!(frame.held_peak_db, frame.peak_db);
+        assert!(!frame.clipping);
+
+        // Quieter input leaves the peak held, then it falls
+        meter.feed(&[0.01], start);
+        let frame = meter.frame(vec![0.0], start + Duration::from_secs(1));
+        assert!((frame.peak_db - -40.0).abs() < 1e-3);
+        assert!((frame.held_peak_db - -6.0206).abs() < 1e-3);
+        let frame = meter.frame(vec![0.0], start + Duration::from_millis(2500));
+        assert!((frame.held_peak_db - -26.0206).abs() < 1e-3);
+
+        // Lone full-scale samples don't count; runs of them do, and enough
+        // runs raise one alert
+        meter.feed(&[1.0, 0.5, -1.0, 0.5], start);
+        assert!(!meter.frame(Vec::new(), start).clipping);
+        for n in 0..CLIP_THRESHOLD as u64 {
+            assert_eq!(meter.take_alert(), None);
+            meter.feed(&[1.0, 1.0, -1.0, 0.0], start + Duration::from_secs(n));
+        }
+        assert_eq!(meter.take_alert(), Some(CLIP_THRESHOLD));
+        assert_eq!(meter.take_alert(), None);
+        let later = start + Duration::from_secs(CLIP_THRESHOLD as u64);
+        assert!(meter.frame(Vec::new(), later).clipping);
+
+        // Not again until the cooldown has passed
+        meter.feed(&[1.0; 8], later);
+        assert_eq!(meter.take_alert(), None);
+    }
+}
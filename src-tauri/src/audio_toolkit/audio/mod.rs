@@ -1,14 +1,38 @@
 // Re-export all audio components
+mod decode;
+mod denoise;
 mod device;
+mod downmix;
+mod drift;
+mod echo;
+mod flac;
+mod gain;
+mod meter;
 mod preprocessor;
 mod recorder;
 mod resampler;
+mod trim;
 mod utils;
 mod visualizer;
+mod waveform;
 
-pub use device::{list_input_devices, list_output_devices, CpalDeviceInfo};
-pub use preprocessor::preprocess_audio;
-pub use recorder::AudioRecorder;
+pub use decode::decode_audio_file;
+pub use denoise::Denoiser;
+pub use device::{
+    find_device, input_capabilities, list_input_devices, list_output_devices, output_capabilities,
+    CpalDeviceInfo, DeviceCapabilities,
+};
+pub use downmix::Downmix;
+pub use echo::{EchoCanceller, EchoReference};
+pub use flac::{encode_flac, save_flac_file};
+pub use gain::{Gain, MAX_GAIN_DB, MIN_GAIN_DB};
+pub use meter::{LevelFrame, LevelMeter};
+pub use preprocessor::{preprocess_audio, PreprocessChain, PreprocessStage};
+pub use recorder::{AudioRecorder, RECORDABLE_FORMATS};
 pub use resampler::FrameResampler;
+pub use trim::trim_silence;
 pub use utils::save_wav_file;
-pub use visualizer::AudioVisualiser;
+pub use visualizer::{
+    request_spectrum, requested_spectrum_bands, AudioVisualiser, SpectrumFrame, MAX_SPECTRUM_BANDS,
+};
+pub use waveform::waveform_envelope;
@@ -76,6 +76,74 @@ pub fn apply_high_pass_filter(samples: &mut [f32], sample_rate: usize) {
     }
 }
 
+/// One step of a `PreprocessChain`. Stages may change the buffer length
+/// (e.g. when they resample internally) so they get the `Vec` itself.
+pub trait PreprocessStage: Send {
+    fn process(&mut self, samples: &mut Vec<f32>, sample_rate: usize);
+}
+
+pub struct RemoveDcOffset;
+
+impl PreprocessStage for RemoveDcOffset {
+    fn process(&mut self, samples: &mut Vec<f32>, _sample_rate: usize) {
+        remove_dc_offset(samples);
+    }
+}
+
+pub struct HighPassFilter;
+
+impl PreprocessStage for HighPassFilter {
+    fn process(&mut self, samples: &mut Vec<f32>, sample_rate: usize) {
+        apply_high_pass_filter(samples, sample_rate);
+    }
+}
+
+pub struct Normalize;
+
+impl PreprocessStage for Normalize {
+    fn process(&mut self, samples: &mut Vec<f32>, _sample_rate: usize) {
+        normalize_audio(samples);
+    }
+}
+
+/// Ordered list of preprocessing stages run over a buffer before transcription
+#[derive(Default)]
+pub struct PreprocessChain {
+    stages: Vec<Box<dyn PreprocessStage>>,
+}
+
+impl PreprocessChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// DC offset removal, 80Hz high-pass, then normalization
+    pub fn standard() -> Self {
+        let mut chain = Self::new();
+        chain.push(RemoveDcOffset);
+        chain.push(HighPassFilter);
+        chain.push(Normalize);
+        chain
+    }
+
+    pub fn push(&mut self, stage: impl PreprocessStage + 'static) {
+        self.stages.push(Box::new(stage));
+    }
+
+    pub fn insert(&mut self, index: usize, stage: impl PreprocessStage + 'static) {
+        self.stages.insert(index.min(self.stages.len()), Box::new(stage));
+    }
+
+    pub fn process(&mut self, samples: &mut Vec<f32>, sample_rate: usize) {
+        if samples.is_empty() {
+            return;
+        }
+        for stage in self.stages.iter_mut() {
+            stage.process(samples, sample_rate);
+        }
+    }
+}
+
 /// Apply all preprocessing steps to improve transcription quality
 /// This is similar to what professional speech recognition systems do
 pub fn preprocess_audio(samples: &mut [f32], sample_rate: usize) {
@@ -131,5 +199,24 @@ mod tests {
         let mean: f32 = samples.iter().sum::<f32>() / samples.len() as f32;
         assert!(mean.abs() < 0.1);
     }
+
+    #[test]
+    fn test_chain_runs_stages_in_order() {
+        struct Scale(f32);
+        impl PreprocessStage for Scale {
+            fn process(&mut self, samples: &mut Vec<f32>, _sample_rate: usize) {
+                samples.iter_mut().for_each(|s| *s *= self.0);
+            }
+        }
+
+        let mut chain = PreprocessChain::standard();
+        chain.push(Scale(0.5));
+        let mut samples = vec![0.1, -0.4, 0.2, 0.1];
+        chain.process(&mut samples, 16000);
+
+        // Normalized to 0.95 before the final stage halves it
+        let max = samples.iter().fold(0.0f32, |a, &s| a.max(s.abs()));
+        assert!((max - 0.475).abs() < 0.01);
+    }
 }
 
@@ -1,8 +1,10 @@
 use std::{
     collections::VecDeque,
-    io::Error,
-    sync::{mpsc, Arc, Mutex},
-    time::Duration,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
 use cpal::{
@@ -11,16 +13,32 @@ use cpal::{
 };
 
 use crate::audio_toolkit::{
-    audio::{AudioVisualiser, FrameResampler},
+    audio::{
+        requested_spectrum_bands, trim_silence, AudioVisualiser, Downmix, EchoCanceller,
+        EchoReference, FrameResampler, Gain, LevelFrame, LevelMeter, SpectrumFrame,
+    },
     constants,
+    error::{AudioError, Result},
+    glitch,
+    ring_buffer::SampleRingBuffer,
+    sample_queue::{sample_queue, SampleConsumer},
+    simulated::{self, SimulatedSource},
+    stream::{AudioFrameStream, FrameBroadcaster},
+    system_audio::CaptureFormat,
+    timed::{self, FrameClock, TimedSamples},
     vad::{self, VadFrame},
+    wasapi::WasapiOptions,
     VoiceActivityDetector,
 };
+use crate::metrics::{self, CaptureStream, Glitch, Stage};
 
 enum Cmd {
-    Start,
-    Stop(mpsc::Sender<Vec<f32>>),
-    ReadSamples(mpsc::Sender<Vec<f32>>),
+    Start(Duration),
+    Pause,
+    Resume,
+    Stop(mpsc::Sender<Vec<f32>>, Duration),
+    ReadSamples(mpsc::Sender<TimedSamples>),
+    SetEchoReference(Option<EchoReference>),
     Shutdown,
 }
 
@@ -29,23 +47,79 @@ pub struct AudioRecorder {
     cmd_tx: Option<mpsc::Sender<Cmd>>,
     worker_handle: Option<std::thread::JoinHandle<()>>,
     vad: Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
-    level_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
+    level_cb: Option<Arc<dyn Fn(LevelFrame) + Send + Sync + 'static>>,
+    spectrum_cb: Option<Arc<dyn Fn(SpectrumFrame) + Send + Sync + 'static>>,
+    clipping_cb: Option<Arc<dyn Fn(usize) + Send + Sync + 'static>>,
     // Continuous buffer for always-on mode (like system audio)
-    continuous_buffer: Arc<Mutex<VecDeque<f32>>>,
+    continuous_buffer: Arc<Mutex<SampleRingBuffer>>,
+    // Resampled 16kHz frames for async subscribers
+    frames: Arc<FrameBroadcaster>,
+    // The subset of `frames` that ends up in the recording
+    recorded_frames: Arc<FrameBroadcaster>,
+    // VAD decision for the latest recorded frame
+    speaking: Arc<AtomicBool>,
+    // Samples the callback couldn't queue because the worker fell behind
+    dropped: Arc<AtomicU64>,
+    // How multi-channel input is reduced to mono
+    downmix: Downmix,
+    // Software gain applied in the callback, after downmixing
+    gain: Gain,
+    // How the device is opened on Windows
+    wasapi: WasapiOptions,
+}
+
+/// The open input stream, held by the worker to keep it running
+#[allow(dead_code)]
+enum InputStream {
+    Cpal(cpal::Stream),
+    #[cfg(target_os = "windows")]
+    Wasapi(crate::audio_toolkit::wasapi::WasapiStream),
 }
 
 impl AudioRecorder {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new() -> Result<Self> {
         Ok(AudioRecorder {
             device: None,
             cmd_tx: None,
             worker_handle: None,
             vad: None,
             level_cb: None,
-            continuous_buffer: Arc::new(Mutex::new(VecDeque::with_capacity(480000))), // 30s at 16kHz
+            spectrum_cb: None,
+            clipping_cb: None,
+            continuous_buffer: Arc::new(Mutex::new(SampleRingBuffer::with_format(
+                CONTINUOUS_BUFFER_SECONDS,
+                CaptureFormat {
+                    sample_rate: constants::WHISPER_SAMPLE_RATE,
+                    channels: 1,
+                },
+            ))),
+            frames: Arc::new(FrameBroadcaster::new(constants::WHISPER_SAMPLE_RATE)),
+            recorded_frames: Arc::new(FrameBroadcaster::new(constants::WHISPER_SAMPLE_RATE)),
+            speaking: Arc::new(AtomicBool::new(false)),
+            dropped: Arc::new(AtomicU64::new(0)),
+            downmix: Downmix::default(),
+            gain: Gain::default(),
+            wasapi: WasapiOptions::default(),
         })
     }
 
+    /// Reduce multi-channel input to mono with `downmix` from the next
+    /// `open` on
+    pub fn set_downmix(&mut self, downmix: Downmix) {
+        self.downmix = downmix;
+    }
+
+    /// Apply `gain` to the input from the next `open` on
+    pub fn set_gain(&mut self, gain: Gain) {
+        self.gain = gain;
+    }
+
+    /// Open the device with `options` from the next `open` on. Only Windows
+    /// has them; elsewhere they are ignored.
+    pub fn set_wasapi_options(&mut self, options: WasapiOptions) {
+        self.wasapi = options;
+    }
+
     pub fn with_vad(mut self, vad: Box<dyn VoiceActivityDetector>) -> Self {
         self.vad = Some(Arc::new(Mutex::new(vad)));
         self
@@ -53,18 +127,62 @@ impl AudioRecorder {
 
     pub fn with_level_callback<F>(mut self, cb: F) -> Self
     where
-        F: Fn(Vec<f32>) + Send + Sync + 'static,
+        F: Fn(LevelFrame) + Send + Sync + 'static,
     {
         self.level_cb = Some(Arc::new(cb));
         self
     }
 
-    pub fn open(&mut self, device: Option<Device>) -> Result<(), Box<dyn std::error::Error>> {
+    /// Call `cb` with the input's spectrum while one is requested through
+    /// `request_spectrum`
+    pub fn with_spectrum_callback<F>(mut self, cb: F) -> Self
+    where
+        F: Fn(SpectrumFrame) + Send + Sync + 'static,
+    {
+        self.spectrum_cb = Some(Arc::new(cb));
+        self
+    }
+
+    /// Call `cb` with the clips counted recently when the input keeps hitting
+    /// full scale, at most once a minute
+    pub fn with_clipping_callback<F>(mut self, cb: F) -> Self
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        self.clipping_cb = Some(Arc::new(cb));
+        self
+    }
+
+    /// Subscribe to every 16kHz mono frame the open device produces, whether
+    /// or not a recording is in progress
+    pub fn frames(&self) -> AudioFrameStream {
+        self.frames.subscribe()
+    }
+
+    /// Subscribe to the frames kept by the recording in progress: nothing
+    /// while idle or paused
+    pub fn recorded_frames(&self) -> AudioFrameStream {
+        self.recorded_frames.subscribe()
+    }
+
+    /// Whether the VAD heard speech in the most recent frame of the recording
+    /// in progress. Always false while not recording.
+    pub fn is_speaking(&self) -> bool {
+        self.speaking.load(Ordering::Relaxed)
+    }
+
+    /// Samples lost since the recorder was created because the worker didn't
+    /// keep up with the device
+    pub fn dropped_samples(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn open(&mut self, device: Option<Device>) -> Result<()> {
         if self.worker_handle.is_some() {
             return Ok(()); // already open
         }
 
-        let (sample_tx, sample_rx) = mpsc::channel::<Vec<f32>>();
+        let (mut producer, consumer) = sample_queue(INPUT_QUEUE_CAPACITY);
         let (cmd_tx, cmd_rx) = mpsc::channel::<Cmd>();
 
         let host = crate::audio_toolkit::get_cpal_host();
@@ -72,61 +190,76 @@ impl AudioRecorder {
             Some(dev) => dev,
             None => host
                 .default_input_device()
-                .ok_or_else(|| Error::new(std::io::ErrorKind::NotFound, "No input device found"))?,
+                .ok_or_else(|| AudioError::DeviceNotFound("no default input device".to_string()))?,
         };
 
         let thread_device = device.clone();
+        let downmix = self.downmix;
+        let gain = self.gain;
+        let wasapi = self.wasapi;
         let vad = self.vad.clone();
-        // Move the optional level callback into the worker thread
+        // Move the optional level callbacks into the worker thread
         let level_cb = self.level_cb.clone();
+        let spectrum_cb = self.spectrum_cb.clone();
+        let clipping_cb = self.clipping_cb.clone();
         let continuous_buffer = Arc::clone(&self.continuous_buffer);
+        let frames = Arc::clone(&self.frames);
+        let recorded_frames = Arc::clone(&self.recorded_frames);
+        let speaking = Arc::clone(&self.speaking);
+        let dropped = Arc::clone(&self.dropped);
 
-        let worker = std::thread::spawn(move || {
-            let config = AudioRecorder::get_preferred_config(&thread_device)
-                .expect("failed to fetch preferred config");
+        // The stream has to live on the worker thread, which reports back
+        // whether it could be built
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<()>>();
 
-            let sample_rate = config.sample_rate().0;
-            let channels = config.channels() as usize;
-
-            log::info!(
-                "Using device: {:?}\nSample rate: {}\nChannels: {}\nFormat: {:?}",
-                thread_device.name(),
-                sample_rate,
-                channels,
-                config.sample_format()
-            );
-
-            let stream = match config.sample_format() {
-                cpal::SampleFormat::U8 => {
-                    AudioRecorder::build_stream::<u8>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                cpal::SampleFormat::I8 => {
-                    AudioRecorder::build_stream::<i8>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                cpal::SampleFormat::I16 => {
-                    AudioRecorder::build_stream::<i16>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                cpal::SampleFormat::I32 => {
-                    AudioRecorder::build_stream::<i32>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                cpal::SampleFormat::F32 => {
-                    AudioRecorder::build_stream::<f32>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
+        let worker = std::thread::spawn(move || {
+            let mut boosted = Vec::new();
+            let sink = move |chunk: &[f32], captured_at: Instant| {
+                producer.stamp(captured_at);
+                if gain.is_unity() {
+                    producer.push_slice(chunk);
+                    return;
                 }
-                _ => panic!("unsupported sample format"),
+                boosted.clear();
+                boosted.extend_from_slice(chunk);
+                gain.apply_slice(&mut boosted);
+                producer.push_slice(&boosted);
             };
-
-            stream.play().expect("failed to start stream");
+            let (_stream, sample_rate) =
+                match AudioRecorder::open_input(&thread_device, downmix, wasapi, sink) {
+                    Ok(opened) => {
+                        let _ = ready_tx.send(Ok(()));
+                        opened
+                    }
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
 
             // keep the stream alive while we process samples
-            run_consumer(sample_rate, vad, sample_rx, cmd_rx, level_cb, continuous_buffer);
+            run_consumer(
+                sample_rate,
+                vad,
+                consumer,
+                cmd_rx,
+                level_cb,
+                spectrum_cb,
+                clipping_cb,
+                continuous_buffer,
+                frames,
+                recorded_frames,
+                speaking,
+                dropped,
+            );
             // stream is dropped here, after run_consumer returns
         });
 
+        if let Err(e) = ready_rx.recv().unwrap_or(Err(AudioError::StreamClosed)) {
+            let _ = worker.join();
+            return Err(e);
+        }
+
         self.device = Some(device);
         self.cmd_tx = Some(cmd_tx);
         self.worker_handle = Some(worker);
@@ -134,24 +267,122 @@ impl AudioRecorder {
         Ok(())
     }
 
-    pub fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Like `open`, but fed by `source` instead of an input device: a fake
+    /// microphone for tests and machines without one
+    pub fn open_simulated(&mut self, source: &SimulatedSource) -> Result<()> {
+        if self.worker_handle.is_some() {
+            return Ok(()); // already open
+        }
+
+        let signal = source.signal(constants::WHISPER_SAMPLE_RATE)?;
+        log::info!("Using simulated microphone: {:?}", source);
+
+        let (mut producer, consumer) = sample_queue(INPUT_QUEUE_CAPACITY);
+        let (cmd_tx, cmd_rx) = mpsc::channel::<Cmd>();
+
+        let vad = self.vad.clone();
+        let level_cb = self.level_cb.clone();
+        let spectrum_cb = self.spectrum_cb.clone();
+        let clipping_cb = self.clipping_cb.clone();
+        let continuous_buffer = Arc::clone(&self.continuous_buffer);
+        let frames = Arc::clone(&self.frames);
+        let recorded_frames = Arc::clone(&self.recorded_frames);
+        let speaking = Arc::clone(&self.speaking);
+        let dropped = Arc::clone(&self.dropped);
+
+        let worker = std::thread::spawn(move || {
+            let stop = Arc::new(AtomicBool::new(false));
+            let player = simulated::spawn_player(
+                signal,
+                constants::WHISPER_SAMPLE_RATE,
+                Arc::clone(&stop),
+                move |chunk, captured_at| {
+                    producer.stamp(captured_at);
+                    producer.push_slice(chunk);
+                },
+            );
+
+            run_consumer(
+                constants::WHISPER_SAMPLE_RATE,
+                vad,
+                consumer,
+                cmd_rx,
+                level_cb,
+                spectrum_cb,
+                clipping_cb,
+                continuous_buffer,
+                frames,
+                recorded_frames,
+                speaking,
+                dropped,
+            );
+
+            stop.store(true, Ordering::Relaxed);
+            let _ = player.join();
+        });
+
+        self.cmd_tx = Some(cmd_tx);
+        self.worker_handle = Some(worker);
+
+        Ok(())
+    }
+
+    pub fn start(&self) -> Result<()> {
+        self.start_with_pre_roll(Duration::ZERO)
+    }
+
+    /// Start recording with up to `pre_roll` of the audio heard just before,
+    /// so speech that began as the hotkey fired isn't clipped. Only a stream
+    /// that was already open has any to give; capped at `MAX_PRE_ROLL`.
+    pub fn start_with_pre_roll(&self, pre_roll: Duration) -> Result<()> {
+        if let Some(tx) = &self.cmd_tx {
+            tx.send(Cmd::Start(pre_roll))?;
+        }
+        Ok(())
+    }
+
+    /// Discard audio until `resume`, keeping what was recorded so far
+    pub fn pause(&self) -> Result<()> {
+        if let Some(tx) = &self.cmd_tx {
+            tx.send(Cmd::Pause)?;
+        }
+        Ok(())
+    }
+
+    pub fn resume(&self) -> Result<()> {
+        if let Some(tx) = &self.cmd_tx {
+            tx.send(Cmd::Resume)?;
+        }
+        Ok(())
+    }
+
+    /// Cancel echo of `reference` (system audio at 16kHz) from every frame
+    /// until it is cleared with `None`
+    pub fn set_echo_reference(&self, reference: Option<EchoReference>) -> Result<()> {
         if let Some(tx) = &self.cmd_tx {
-            tx.send(Cmd::Start)?;
+            tx.send(Cmd::SetEchoReference(reference))?;
         }
         Ok(())
     }
 
-    pub fn stop(&self) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+    pub fn stop(&self) -> Result<Vec<f32>> {
+        self.stop_with_padding(Duration::from_millis(DEFAULT_TRIM_PADDING_MS))
+    }
+
+    /// Stop recording and return the samples with leading/trailing non-speech
+    /// trimmed, keeping `padding` of context around the detected speech.
+    pub fn stop_with_padding(&self, padding: Duration) -> Result<Vec<f32>> {
         let (resp_tx, resp_rx) = mpsc::channel();
         if let Some(tx) = &self.cmd_tx {
-            tx.send(Cmd::Stop(resp_tx))?;
+            tx.send(Cmd::Stop(resp_tx, padding))?;
         }
         Ok(resp_rx.recv()?) // wait for the samples
     }
 
     /// Read samples from continuous buffer without stopping recording
-    /// This is for always-on mode where we want continuous transcription
-    pub fn read_samples(&self) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+    /// This is for always-on mode where we want continuous transcription.
+    /// The samples are 16kHz and carry their capture times.
+    pub fn read_samples(&self) -> Result<TimedSamples> {
         let (resp_tx, resp_rx) = mpsc::channel();
         if let Some(tx) = &self.cmd_tx {
             tx.send(Cmd::ReadSamples(resp_tx))?;
@@ -159,7 +390,7 @@ impl AudioRecorder {
         Ok(resp_rx.recv()?)
     }
 
-    pub fn close(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn close(&mut self) -> Result<()> {
         if let Some(tx) = self.cmd_tx.take() {
             let _ = tx.send(Cmd::Shutdown);
         }
@@ -170,19 +401,160 @@ impl AudioRecorder {
         Ok(())
     }
 
-    fn build_stream<T>(
+    /// Open `device` the way `open` does and collect the mono chunks it
+    /// delivers in the next `duration`, each with its arrival time, plus the
+    /// rate the stream was opened at. Blocks the calling thread and leaves any
+    /// open recorder alone.
+    pub fn capture_raw(
+        device: Option<Device>,
+        downmix: Downmix,
+        duration: Duration,
+    ) -> Result<(u32, Vec<(Duration, Vec<f32>)>)> {
+        let device = match device {
+            Some(dev) => dev,
+            None => crate::audio_toolkit::get_cpal_host()
+                .default_input_device()
+                .ok_or_else(|| AudioError::DeviceNotFound("no default input device".to_string()))?,
+        };
+        let (sample_tx, sample_rx) = mpsc::channel::<Vec<f32>>();
+        let sink = move |chunk: &[f32], _: Instant| {
+            let _ = sample_tx.send(chunk.to_vec());
+        };
+        let (stream, sample_rate) = AudioRecorder::start_input_stream(&device, downmix, sink)?;
+        let started = std::time::Instant::now();
+        let mut chunks = Vec::new();
+        while let Some(remaining) = duration.checked_sub(started.elapsed()) {
+            match sample_rx.recv_timeout(remaining) {
+                Ok(samples) => chunks.push((started.elapsed(), samples)),
+                Err(_) => break,
+            }
+        }
+        drop(stream);
+
+        Ok((sample_rate, chunks))
+    }
+
+    /// Start `device` through WASAPI when `wasapi` asks for more than cpal
+    /// can do, else as `start_input_stream` does. Falls back to cpal if the
+    /// device can't be opened that way, e.g. when another app holds it in
+    /// exclusive mode.
+    fn open_input<F>(
+        device: &Device,
+        downmix: Downmix,
+        wasapi: WasapiOptions,
+        sink: F,
+    ) -> Result<(InputStream, u32)>
+    where
+        F: FnMut(&[f32], Instant) + Send + 'static,
+    {
+        #[cfg(target_os = "windows")]
+        if !wasapi.is_default() {
+            use crate::audio_toolkit::wasapi::{self, Flow};
+
+            let sink = Arc::new(Mutex::new(sink));
+            let wasapi_sink = Arc::clone(&sink);
+            let mut mono = Vec::new();
+            let opened = wasapi::open(
+                device.name().ok(),
+                Flow::Capture,
+                wasapi,
+                move |data, channels, captured_at| {
+                    mono.clear();
+                    if channels == 1 {
+                        mono.extend_from_slice(data);
+                    } else {
+                        mono.extend(data.chunks_exact(channels).map(|frame| downmix.mix(frame)));
+                    }
+                    (wasapi_sink.lock().unwrap())(&mono, captured_at);
+                },
+            );
+            match opened {
+                Ok((stream, format)) => {
+                    return Ok((InputStream::Wasapi(stream), format.sample_rate))
+                }
+                Err(e) => log::warn!(
+                    "Opening the microphone with {:?} failed, using shared mode: {}",
+                    wasapi,
+                    e
+                ),
+            }
+            let sink = move |chunk: &[f32], at: Instant| (sink.lock().unwrap())(chunk, at);
+            let (stream, sample_rate) = AudioRecorder::start_input_stream(device, downmix, sink)?;
+            return Ok((InputStream::Cpal(stream), sample_rate));
+        }
+        #[cfg(not(target_os = "windows"))]
+        let _ = wasapi;
+
+        let (stream, sample_rate) = AudioRecorder::start_input_stream(device, downmix, sink)?;
+        Ok((InputStream::Cpal(stream), sample_rate))
+    }
+
+    /// Build and play an input stream on `device` in its preferred format,
+    /// handing chunks downmixed to mono to `sink` on the callback thread.
+    /// Returns the stream and its rate.
+    fn start_input_stream<F>(
+        device: &Device,
+        downmix: Downmix,
+        sink: F,
+    ) -> Result<(cpal::Stream, u32)>
+    where
+        F: FnMut(&[f32], Instant) + Send + 'static,
+    {
+        let config = AudioRecorder::get_preferred_config(device)?;
+        let sample_rate = config.sample_rate().0;
+        let channels = config.channels() as usize;
+
+        log::info!(
+            "Using device: {:?}\nSample rate: {}\nChannels: {}\nFormat: {:?}",
+            device.name(),
+            sample_rate,
+            channels,
+            config.sample_format()
+        );
+
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::U8 => {
+                AudioRecorder::build_stream::<u8, F>(device, &config, sink, channels, downmix)?
+            }
+            cpal::SampleFormat::I8 => {
+                AudioRecorder::build_stream::<i8, F>(device, &config, sink, channels, downmix)?
+            }
+            cpal::SampleFormat::I16 => {
+                AudioRecorder::build_stream::<i16, F>(device, &config, sink, channels, downmix)?
+            }
+            cpal::SampleFormat::I32 => {
+                AudioRecorder::build_stream::<i32, F>(device, &config, sink, channels, downmix)?
+            }
+            cpal::SampleFormat::F32 => {
+                AudioRecorder::build_stream::<f32, F>(device, &config, sink, channels, downmix)?
+            }
+            format => {
+                return Err(AudioError::UnsupportedFormat(format!(
+                    "sample format {:?}",
+                    format
+                )))
+            }
+        };
+
+        stream.play()?;
+        Ok((stream, sample_rate))
+    }
+
+    fn build_stream<T, F>(
         device: &cpal::Device,
         config: &cpal::SupportedStreamConfig,
-        sample_tx: mpsc::Sender<Vec<f32>>,
+        mut sink: F,
         channels: usize,
+        downmix: Downmix,
     ) -> Result<cpal::Stream, cpal::BuildStreamError>
     where
         T: Sample + SizedSample + Send + 'static,
         f32: cpal::FromSample<T>,
+        F: FnMut(&[f32], Instant) + Send + 'static,
     {
         let mut output_buffer = Vec::new();
 
-        let stream_cb = move |data: &[T], _: &cpal::InputCallbackInfo| {
+        let stream_cb = move |data: &[T], info: &cpal::InputCallbackInfo| {
             output_buffer.clear();
 
             if channels == 1 {
@@ -194,18 +566,11 @@ impl AudioRecorder {
                 output_buffer.reserve(frame_count);
 
                 for frame in data.chunks_exact(channels) {
-                    let mono_sample = frame
-                        .iter()
-                        .map(|&sample| sample.to_sample::<f32>())
-                        .sum::<f32>()
-                        / channels as f32;
-                    output_buffer.push(mono_sample);
+                    output_buffer.push(downmix.mix(frame));
                 }
             }
 
-            if sample_tx.send(output_buffer.clone()).is_err() {
-                log::error!("Failed to send samples");
-            }
+            sink(&output_buffer, timed::cpal_capture_time(info));
         };
 
         device.build_input_stream(
@@ -216,9 +581,7 @@ impl AudioRecorder {
         )
     }
 
-    fn get_preferred_config(
-        device: &cpal::Device,
-    ) -> Result<cpal::SupportedStreamConfig, Box<dyn std::error::Error>> {
+    fn get_preferred_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig> {
         let supported_configs = device.supported_input_configs()?;
 
         // Try to find a config that supports 16kHz
@@ -238,22 +601,80 @@ impl AudioRecorder {
     }
 }
 
+/// Sample formats `start_input_stream` can open a device in
+pub const RECORDABLE_FORMATS: [cpal::SampleFormat; 5] = [
+    cpal::SampleFormat::U8,
+    cpal::SampleFormat::I8,
+    cpal::SampleFormat::I16,
+    cpal::SampleFormat::I32,
+    cpal::SampleFormat::F32,
+];
+
+const DEFAULT_TRIM_PADDING_MS: u64 = 300;
+const FRAME_DURATION: Duration = Duration::from_millis(30);
+/// Longest pre-roll kept while not recording
+const MAX_PRE_ROLL: Duration = Duration::from_secs(3);
+/// Audio the continuous buffer keeps for always-on mode
+const CONTINUOUS_BUFFER_SECONDS: u32 = 30;
+/// A second of audio at the highest rates devices commonly run at
+const INPUT_QUEUE_CAPACITY: usize = 192_000;
+/// How long the worker waits when the callback has queued nothing new
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// VAD decision for one frame; everything counts as speech without a VAD
+fn is_speech_frame(
+    vad: &Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
+    samples: &[f32],
+) -> bool {
+    match vad {
+        Some(vad_arc) => metrics::time(Stage::Vad, || {
+            let mut det = vad_arc.lock().unwrap();
+            det.push_frame(samples)
+                .unwrap_or(VadFrame::Speech(samples))
+                .is_speech()
+        }),
+        None => true,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn run_consumer(
     in_sample_rate: u32,
     vad: Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
-    sample_rx: mpsc::Receiver<Vec<f32>>,
+    mut samples: SampleConsumer,
     cmd_rx: mpsc::Receiver<Cmd>,
-    level_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
-    continuous_buffer: Arc<Mutex<VecDeque<f32>>>,
+    level_cb: Option<Arc<dyn Fn(LevelFrame) + Send + Sync + 'static>>,
+    spectrum_cb: Option<Arc<dyn Fn(SpectrumFrame) + Send + Sync + 'static>>,
+    clipping_cb: Option<Arc<dyn Fn(usize) + Send + Sync + 'static>>,
+    continuous_buffer: Arc<Mutex<SampleRingBuffer>>,
+    frames: Arc<FrameBroadcaster>,
+    recorded_frames: Arc<FrameBroadcaster>,
+    speaking: Arc<AtomicBool>,
+    dropped: Arc<AtomicU64>,
 ) {
     let mut frame_resampler = FrameResampler::new(
         in_sample_rate as usize,
         constants::WHISPER_SAMPLE_RATE as usize,
-        Duration::from_millis(30),
+        FRAME_DURATION,
     );
+    let frame_len =
+        constants::WHISPER_SAMPLE_RATE as usize * FRAME_DURATION.as_millis() as usize / 1000;
 
     let mut processed_samples = Vec::<f32>::new();
+    // One VAD decision per resampled frame in `processed_samples`
+    let mut speech_frames = Vec::<bool>::new();
+    // Latest frames heard while not recording, whole frames only
+    let max_pre_roll_len =
+        frame_len * (MAX_PRE_ROLL.as_millis() / FRAME_DURATION.as_millis()) as usize;
+    let mut pre_roll = VecDeque::<f32>::with_capacity(max_pre_roll_len);
     let mut recording = false;
+    // Recording but discarding audio until resumed
+    let mut paused = false;
+    let mut echo: Option<EchoCanceller> = None;
+    // Capture time of each resampled frame, from the input's stamps
+    let mut clock = FrameClock::new(FRAME_DURATION);
+
+    let mut meter = LevelMeter::new();
 
     // ---------- spectrum visualisation setup ---------------------------- //
     const BUCKETS: usize = 16;
@@ -268,90 +689,244 @@ fn run_consumer(
 
     fn handle_frame(
         samples: &[f32],
+        at: Instant,
+        echo: &mut Option<EchoCanceller>,
         recording: bool,
         vad: &Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
         out_buf: &mut Vec<f32>,
-        continuous_buf: &Arc<Mutex<VecDeque<f32>>>,
+        speech_frames: &mut Vec<bool>,
+        pre_roll: &mut VecDeque<f32>,
+        max_pre_roll_len: usize,
+        continuous_buf: &Arc<Mutex<SampleRingBuffer>>,
+        frames: &FrameBroadcaster,
+        recorded_frames: &FrameBroadcaster,
     ) {
-        // Always add to continuous buffer for always-on mode
-        {
-            let mut cont_buf = continuous_buf.lock().unwrap();
-            cont_buf.extend(samples);
-            // Keep buffer size reasonable (max 30 seconds at 16kHz)
-            const MAX_BUFFER_SIZE: usize = 480000;
-            if cont_buf.len() > MAX_BUFFER_SIZE {
-                let excess = cont_buf.len() - MAX_BUFFER_SIZE;
-                cont_buf.drain(..excess);
+        let mut cancelled;
+        let samples = match echo {
+            Some(canceller) => {
+                cancelled = samples.to_vec();
+                canceller.process(&mut cancelled);
+                &cancelled[..]
             }
-        }
-        
+            None => samples,
+        };
+        frames.publish(samples, at);
+
+        // Always add to continuous buffer for always-on mode; once full it
+        // overwrites the oldest audio
+        continuous_buf
+            .lock()
+            .unwrap()
+            .extend_at(samples.iter().copied(), at);
+
         if !recording {
+            pre_roll.extend(samples);
+            if pre_roll.len() > max_pre_roll_len {
+                let excess = pre_roll.len() - max_pre_roll_len;
+                pre_roll.drain(..excess);
+            }
             return;
         }
 
-        if let Some(vad_arc) = vad {
-            let mut det = vad_arc.lock().unwrap();
-            match det.push_frame(samples).unwrap_or(VadFrame::Speech(samples)) {
-                VadFrame::Speech(buf) => out_buf.extend_from_slice(buf),
-                VadFrame::Noise => {}
-            }
-        } else {
-            out_buf.extend_from_slice(samples);
-        }
+        // Keep every frame and remember the VAD decision; non-speech is only
+        // trimmed from the head/tail once recording stops.
+        let is_speech = is_speech_frame(vad, samples);
+        recorded_frames.publish(samples, at);
+        out_buf.extend_from_slice(samples);
+        speech_frames.push(is_speech);
     }
 
+    let mut raw = TimedSamples::new();
     loop {
-        let raw = match sample_rx.recv() {
-            Ok(s) => s,
-            Err(_) => break, // stream closed
-        };
+        raw.clear();
+        // Checked before draining so the last samples still get processed
+        let closed = samples.is_abandoned();
+        samples.pop_timed(&mut raw);
+        let overflow = samples.take_overflow();
+        if overflow > 0 {
+            log::warn!("Input queue full, dropped {} samples", overflow);
+            dropped.fetch_add(overflow, Ordering::Relaxed);
+            glitch::report(CaptureStream::Microphone, Glitch::Overrun);
+        }
+        if raw.is_empty() {
+            if closed {
+                break; // stream closed
+            }
+            std::thread::sleep(QUEUE_POLL_INTERVAL);
+            continue;
+        }
 
         // ---------- spectrum processing ---------------------------------- //
-        if let Some(buckets) = visualizer.feed(&raw) {
+        let now = Instant::now();
+        meter.feed(&raw.samples, now);
+        if let Some(clips) = meter.take_alert() {
+            log::warn!("Microphone input clipped {} times in a few seconds", clips);
+            if let Some(cb) = &clipping_cb {
+                cb(clips);
+            }
+        }
+        if spectrum_cb.is_some() {
+            visualizer.set_spectrum_bands(requested_spectrum_bands());
+        }
+        if let Some(buckets) = visualizer.feed(&raw.samples) {
             if let Some(cb) = &level_cb {
-                cb(buckets);
+                cb(meter.frame(buckets, now));
+            }
+            if let (Some(cb), Some(frame)) = (&spectrum_cb, visualizer.spectrum()) {
+                cb(frame.clone());
             }
         }
 
         // ---------- existing pipeline ------------------------------------ //
+        // Resampling is timed apart from the per-frame work it calls into
+        let resample_started = Instant::now();
+        let mut in_frames = Duration::ZERO;
         let continuous_buffer_clone = Arc::clone(&continuous_buffer);
-        frame_resampler.push(&raw, &mut |frame: &[f32]| {
-            handle_frame(frame, recording, &vad, &mut processed_samples, &continuous_buffer_clone)
-        });
+        // Each stamped chunk is pushed on its own so a gap before it moves
+        // the frame times on instead of closing up
+        for (chunk, at) in raw.chunks() {
+            if let Some(gap) = at.and_then(|at| clock.observe(at)) {
+                log::debug!("Microphone audio missing for {:?}", gap);
+                glitch::report_gap(CaptureStream::Microphone, gap);
+            }
+            frame_resampler.push(chunk, &mut |frame: &[f32]| {
+                let frame_started = Instant::now();
+                handle_frame(
+                    frame,
+                    clock.tick(),
+                    &mut echo,
+                    recording && !paused,
+                    &vad,
+                    &mut processed_samples,
+                    &mut speech_frames,
+                    &mut pre_roll,
+                    max_pre_roll_len,
+                    &continuous_buffer_clone,
+                    &frames,
+                    &recorded_frames,
+                );
+                in_frames += frame_started.elapsed();
+            });
+        }
+        metrics::record(
+            Stage::Resample,
+            resample_started.elapsed().saturating_sub(in_frames),
+        );
 
         // non-blocking check for a command
         while let Ok(cmd) = cmd_rx.try_recv() {
             match cmd {
-                Cmd::Start => {
+                Cmd::Start(pre_roll_duration) => {
                     processed_samples.clear();
+                    speech_frames.clear();
                     recording = true;
+                    paused = false;
                     visualizer.reset(); // Reset visualization buffer
                     if let Some(v) = &vad {
                         v.lock().unwrap().reset();
                     }
+
+                    // Seed the recording with the tail of the pre-roll, run
+                    // through the VAD so trimming still sees it
+                    let wanted = frame_len
+                        * (pre_roll_duration.min(MAX_PRE_ROLL).as_millis()
+                            / FRAME_DURATION.as_millis()) as usize;
+                    let skip = pre_roll.len().saturating_sub(wanted);
+                    let seeded: Vec<f32> = pre_roll.drain(..).skip(skip).collect();
+                    // The pre-roll ends where the next frame begins
+                    let next_at = clock.peek().unwrap_or_else(Instant::now);
+                    let count = seeded.len() / frame_len;
+                    for (i, frame) in seeded.chunks_exact(frame_len).enumerate() {
+                        let back = FRAME_DURATION * (count - i) as u32;
+                        let at = next_at.checked_sub(back).unwrap_or(next_at);
+                        speech_frames.push(is_speech_frame(&vad, frame));
+                        recorded_frames.publish(frame, at);
+                        processed_samples.extend_from_slice(frame);
+                    }
                 }
-                Cmd::Stop(reply_tx) => {
+                Cmd::Pause => paused = recording,
+                Cmd::Resume => paused = false,
+                Cmd::Stop(reply_tx, padding) => {
                     recording = false;
 
                     let continuous_buffer_clone = Arc::clone(&continuous_buffer);
                     frame_resampler.finish(&mut |frame: &[f32]| {
                         // we still want to process the last few frames
-                        handle_frame(frame, true, &vad, &mut processed_samples, &continuous_buffer_clone)
+                        handle_frame(
+                            frame,
+                            clock.tick(),
+                            &mut echo,
+                            !paused,
+                            &vad,
+                            &mut processed_samples,
+                            &mut speech_frames,
+                            &mut pre_roll,
+                            max_pre_roll_len,
+                            &continuous_buffer_clone,
+                            &frames,
+                            &recorded_frames,
+                        )
                     });
 
-                    let _ = reply_tx.send(std::mem::take(&mut processed_samples));
+                    let padding_frames =
+                        (padding.as_millis() / FRAME_DURATION.as_millis()) as usize;
+                    let trimmed = trim_silence(
+                        &processed_samples,
+                        &speech_frames,
+                        frame_len,
+                        padding_frames,
+                    );
+                    log::debug!(
+                        "Trimmed recording from {} to {} samples",
+                        processed_samples.len(),
+                        trimmed.len()
+                    );
+                    processed_samples.clear();
+                    speech_frames.clear();
+                    paused = false;
+
+                    let _ = reply_tx.send(trimmed);
                 }
                 Cmd::ReadSamples(reply_tx) => {
                     // Read from continuous buffer without stopping recording
-                    let samples = {
-                        let mut cont_buf = continuous_buffer.lock().unwrap();
-                        let samples: Vec<f32> = cont_buf.drain(..).collect();
-                        samples
-                    };
+                    let samples = continuous_buffer.lock().unwrap().drain_timed();
                     let _ = reply_tx.send(samples);
                 }
+                Cmd::SetEchoReference(reference) => {
+                    echo = reference.map(EchoCanceller::new);
+                }
                 Cmd::Shutdown => return,
             }
         }
+
+        let is_speaking = recording && !paused && speech_frames.last().copied().unwrap_or(false);
+        speaking.store(is_speaking, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulated_microphone_records() {
+        let mut recorder = AudioRecorder::new().unwrap();
+        recorder
+            .open_simulated(&SimulatedSource::Tone { frequency: 440.0 })
+            .unwrap();
+
+        recorder.start().unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        let samples = recorder.stop().unwrap();
+        recorder.close().unwrap();
+
+        // About 300ms at 16kHz; without a VAD nothing is trimmed
+        let expected = constants::WHISPER_SAMPLE_RATE as usize * 3 / 10;
+        assert!(
+            samples.len() >= expected / 2 && samples.len() <= expected * 2,
+            "got {} samples",
+            samples.len()
+        );
+        assert!(samples.iter().any(|s| s.abs() > 0.1));
     }
 }
@@ -85,6 +85,28 @@ impl FrameResampler {
         }
     }
 
+    /// Like `finish`, but emits only as much audio as was pushed: the zero
+    /// padding of the last chunk is cut off and the last frame may be short.
+    /// For handing over to another resampler mid-stream.
+    pub fn flush(&mut self, mut emit: impl FnMut(&[f32])) {
+        if let Some(ref mut resampler) = self.resampler {
+            if !self.in_buf.is_empty() {
+                let pushed = self.in_buf.len();
+                self.in_buf.resize(self.chunk_in, 0.0);
+                if let Ok(out) = resampler.process(&[&self.in_buf[..]], None) {
+                    let keep = out[0].len() * pushed / self.chunk_in;
+                    self.emit_frames(&out[0][..keep], &mut emit);
+                }
+                self.in_buf.clear();
+            }
+        }
+
+        if !self.pending.is_empty() {
+            emit(&self.pending);
+            self.pending.clear();
+        }
+    }
+
     fn emit_frames(&mut self, mut data: &[f32], emit: &mut impl FnMut(&[f32])) {
         while !data.is_empty() {
             let space = self.frame_samples - self.pending.len();
@@ -0,0 +1,64 @@
+/// Trim leading/trailing non-speech from a recording using per-frame VAD
+/// decisions captured while recording.
+///
+/// `speech_frames[i]` describes the `i`-th `frame_len` chunk of `samples`.
+/// `padding_frames` frames of context are kept on each side of the speech
+/// region. Returns an empty buffer if no frame was classified as speech.
+pub fn trim_silence(
+    samples: &[f32],
+    speech_frames: &[bool],
+    frame_len: usize,
+    padding_frames: usize,
+) -> Vec<f32> {
+    if frame_len == 0 {
+        return samples.to_vec();
+    }
+
+    let first = match speech_frames.iter().position(|&s| s) {
+        Some(i) => i,
+        None => return Vec::new(),
+    };
+    let last = speech_frames.iter().rposition(|&s| s).unwrap_or(first);
+
+    let start = first.saturating_sub(padding_frames) * frame_len;
+    let end = ((last + 1 + padding_frames) * frame_len).min(samples.len());
+
+    if start >= end {
+        return Vec::new();
+    }
+    samples[start..end].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trims_head_and_tail_with_padding() {
+        // 6 frames of 2 samples, speech in frames 2..=3
+        let samples: Vec<f32> = (0..12).map(|i| i as f32).collect();
+        let flags = [false, false, true, true, false, false];
+
+        let trimmed = trim_silence(&samples, &flags, 2, 1);
+        assert_eq!(trimmed, vec![2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+
+        let tight = trim_silence(&samples, &flags, 2, 0);
+        assert_eq!(tight, vec![4.0, 5.0, 6.0, 7.0]);
+    }
+
+    #[test]
+    fn test_no_speech_returns_empty() {
+        let samples = vec![0.0; 8];
+        assert!(trim_silence(&samples, &[false; 4], 2, 3).is_empty());
+    }
+
+    #[test]
+    fn test_padding_clamps_to_bounds() {
+        // Last frame is short (tail of the resampler flush)
+        let samples: Vec<f32> = (0..5).map(|i| i as f32).collect();
+        let flags = [true, false, true];
+
+        let trimmed = trim_silence(&samples, &flags, 2, 10);
+        assert_eq!(trimmed, samples);
+    }
+}
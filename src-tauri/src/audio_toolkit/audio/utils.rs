@@ -1,4 +1,4 @@
-use anyhow::Result;
+use crate::audio_toolkit::error::Result;
 use hound::{WavSpec, WavWriter};
 use log::debug;
 use std::path::Path;
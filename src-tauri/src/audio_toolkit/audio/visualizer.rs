@@ -1,4 +1,6 @@
 use rustfft::{num_complex::Complex32, Fft, FftPlanner};
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 const DB_MIN: f32 = -55.0;
@@ -6,6 +8,39 @@ const DB_MAX: f32 = -8.0;
 const GAIN: f32 = 1.3;
 const CURVE_POWER: f32 = 0.7;
 
+/// Most spectrum bands that can be requested
+pub const MAX_SPECTRUM_BANDS: usize = 128;
+/// Lowest frequency the spectrum covers; it reaches up to `SPECTRUM_MAX_HZ`
+/// or Nyquist, whichever is lower
+const SPECTRUM_MIN_HZ: f32 = 50.0;
+const SPECTRUM_MAX_HZ: f32 = 16000.0;
+/// Level reported for silent bands
+const SPECTRUM_FLOOR_DB: f32 = -100.0;
+
+/// Spectrum bands the frontend asked for, 0 while nobody is listening
+static SPECTRUM_BANDS: AtomicUsize = AtomicUsize::new(0);
+
+/// Have visualisers compute a `bands`-band spectrum alongside their level
+/// bars, or stop with 0. Takes effect on the next window they process.
+pub fn request_spectrum(bands: usize) {
+    SPECTRUM_BANDS.store(bands.min(MAX_SPECTRUM_BANDS), Ordering::Relaxed);
+}
+
+/// Spectrum bands visualisers should compute, 0 for none
+pub fn requested_spectrum_bands() -> usize {
+    SPECTRUM_BANDS.load(Ordering::Relaxed)
+}
+
+/// One window's spectrum: bands spaced evenly on a log scale from `min_hz`
+/// to `max_hz`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SpectrumFrame {
+    /// Loudest bin of each band in dBFS, a full-scale sine reading 0
+    pub bands: Vec<f32>,
+    pub min_hz: f32,
+    pub max_hz: f32,
+}
+
 pub struct AudioVisualiser {
     fft: Arc<dyn Fft<f32>>,
     window: Vec<f32>,
@@ -15,6 +50,9 @@ pub struct AudioVisualiser {
     buffer: Vec<f32>,
     window_size: usize,
     buckets: usize,
+    sample_rate: u32,
+    spectrum_ranges: Vec<(usize, usize)>,
+    spectrum: Option<SpectrumFrame>,
 }
 
 impl AudioVisualiser {
@@ -50,19 +88,7 @@ impl AudioVisualiser {
             let start_hz = freq_min + (freq_max - freq_min) * log_start;
             let end_hz = freq_min + (freq_max - freq_min) * log_end;
 
-            let start_bin = ((start_hz * window_size as f32) / sample_rate as f32) as usize;
-            let mut end_bin = ((end_hz * window_size as f32) / sample_rate as f32) as usize;
-
-            // Ensure each bucket has at least one bin
-            if end_bin <= start_bin {
-                end_bin = start_bin + 1;
-            }
-
-            // Clamp to valid range
-            let start_bin = start_bin.min(window_size / 2);
-            let end_bin = end_bin.min(window_size / 2);
-
-            bucket_ranges.push((start_bin, end_bin));
+            bucket_ranges.push(bin_range(start_hz, end_hz, sample_rate, window_size));
         }
 
         Self {
@@ -74,7 +100,62 @@ impl AudioVisualiser {
             buffer: Vec::with_capacity(window_size * 2),
             window_size,
             buckets,
+            sample_rate,
+            spectrum_ranges: Vec::new(),
+            spectrum: None,
+        }
+    }
+
+    /// Also compute a `bands`-band spectrum for every window from now on, or
+    /// stop with 0
+    pub fn set_spectrum_bands(&mut self, bands: usize) {
+        if bands == self.spectrum_ranges.len() {
+            return;
         }
+        self.spectrum = None;
+        let (min_hz, max_hz) = self.spectrum_span();
+        let ratio = max_hz / min_hz;
+        self.spectrum_ranges = (0..bands)
+            .map(|b| {
+                let start_hz = min_hz * ratio.powf(b as f32 / bands as f32);
+                let end_hz = min_hz * ratio.powf((b + 1) as f32 / bands as f32);
+                bin_range(start_hz, end_hz, self.sample_rate, self.window_size)
+            })
+            .collect();
+    }
+
+    /// Spectrum of the last window processed, if one is being computed
+    pub fn spectrum(&self) -> Option<&SpectrumFrame> {
+        self.spectrum.as_ref()
+    }
+
+    fn spectrum_span(&self) -> (f32, f32) {
+        let nyquist = self.sample_rate as f32 / 2.0;
+        (
+            SPECTRUM_MIN_HZ.min(nyquist / 2.0),
+            SPECTRUM_MAX_HZ.min(nyquist),
+        )
+    }
+
+    // Loudest bin of each spectrum band in the FFT output, in dBFS
+    fn compute_spectrum(&self) -> Vec<f32> {
+        // A full-scale sine peaks at a quarter of the window through the Hann
+        // window
+        let full_scale = self.window_size as f32 / 4.0;
+        self.spectrum_ranges
+            .iter()
+            .map(|&(start_bin, end_bin)| {
+                let peak = self.fft_input[start_bin..end_bin]
+                    .iter()
+                    .map(|bin| bin.norm())
+                    .fold(0.0f32, f32::max);
+                if peak > 0.0 {
+                    (20.0 * (peak / full_scale).log10()).max(SPECTRUM_FLOOR_DB)
+                } else {
+                    SPECTRUM_FLOOR_DB
+                }
+            })
+            .collect()
     }
 
     pub fn feed(&mut self, samples: &[f32]) -> Option<Vec<f32>> {
@@ -101,6 +182,15 @@ impl AudioVisualiser {
         // Perform FFT
         self.fft.process(&mut self.fft_input);
 
+        if !self.spectrum_ranges.is_empty() {
+            let (min_hz, max_hz) = self.spectrum_span();
+            self.spectrum = Some(SpectrumFrame {
+                bands: self.compute_spectrum(),
+                min_hz,
+                max_hz,
+            });
+        }
+
         // Compute power spectrum and bucket levels
         let mut buckets = vec![0.0; self.buckets];
 
@@ -150,7 +240,51 @@ impl AudioVisualiser {
 
     pub fn reset(&mut self) {
         self.buffer.clear();
+        self.spectrum = None;
         // Reset noise floor to initial values
         self.noise_floor.fill(-40.0);
     }
 }
+
+/// FFT bins covering `start_hz` to `end_hz`, at least one
+fn bin_range(start_hz: f32, end_hz: f32, sample_rate: u32, window_size: usize) -> (usize, usize) {
+    let start_bin = ((start_hz * window_size as f32) / sample_rate as f32) as usize;
+    let mut end_bin = ((end_hz * window_size as f32) / sample_rate as f32) as usize;
+
+    // Ensure each bucket has at least one bin
+    if end_bin <= start_bin {
+        end_bin = start_bin + 1;
+    }
+
+    // Clamp to valid range
+    (start_bin.min(window_size / 2), end_bin.min(window_size / 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_spectrum_reads_full_scale_sine_at_0_dbfs() {
+        let mut visualiser = AudioVisualiser::new(48000, 512, 16, 400.0, 4000.0);
+        // Centred on FFT bin 11, about 1kHz
+        let hz = 48000.0 / 512.0 * 11.0;
+        let sine: Vec<f32> = (0..512)
+            .map(|i| (2.0 * PI * hz * i as f32 / 48000.0).sin())
+            .collect();
+        assert!(visualiser.feed(&sine).is_some());
+        assert!(visualiser.spectrum().is_none());
+
+        visualiser.set_spectrum_bands(32);
+        assert!(visualiser.feed(&sine).is_some());
+        let frame = visualiser.spectrum().unwrap();
+        assert_eq!(frame.bands.len(), 32);
+        assert_eq!((frame.min_hz, frame.max_hz), (50.0, 16000.0));
+        let loudest = frame.bands.iter().copied().fold(f32::MIN, f32::max);
+        assert!(loudest.abs() < 0.1, "loudest band at {} dBFS", loudest);
+        // Bands well away from the tone are near silent
+        assert!(frame.bands[0] < -60.0);
+        assert!(frame.bands[31] < -60.0);
+    }
+}
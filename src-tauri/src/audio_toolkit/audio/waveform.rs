@@ -0,0 +1,35 @@
+/// Downsample a recording to `points` peak amplitudes for waveform previews.
+///
+/// Each point is the largest absolute sample in its slice of the recording,
+/// clamped to 0.0..=1.0. Recordings shorter than `points` produce one point
+/// per sample.
+pub fn waveform_envelope(samples: &[f32], points: usize) -> Vec<f32> {
+    if samples.is_empty() || points == 0 {
+        return Vec::new();
+    }
+
+    let points = points.min(samples.len());
+    (0..points)
+        .map(|i| {
+            let start = i * samples.len() / points;
+            let end = ((i + 1) * samples.len() / points).max(start + 1);
+            samples[start..end]
+                .iter()
+                .fold(0.0f32, |peak, s| peak.max(s.abs()))
+                .min(1.0)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_keeps_peak_of_each_bucket() {
+        let samples = [0.1, -0.5, 0.2, 0.3, -0.1, 2.0];
+        assert_eq!(waveform_envelope(&samples, 3), vec![0.5, 0.3, 1.0]);
+        assert_eq!(waveform_envelope(&samples[..2], 200), vec![0.1, 0.5]);
+        assert!(waveform_envelope(&[], 200).is_empty());
+    }
+}
@@ -0,0 +1,284 @@
+// BlackHole detection, guided install and routing check
+// Tells the UI whether the BlackHole loopback driver is installed and which
+// channel variant, finds a downloaded installer package to open, and checks
+// the routing end to end: a test tone is played through the output while the
+// BlackHole input listens for it.
+
+use crate::audio_toolkit::audio::Downmix;
+use crate::audio_toolkit::error::{AudioError, Result};
+use crate::audio_toolkit::loopback_diagnosis::routes_to_loopback;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{Device, FromSample, Sample, SizedSample};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Where BlackHole is downloaded from
+pub const BLACKHOLE_DOWNLOAD_URL: &str = "https://existential.audio/blackhole/";
+
+/// How long the test tone plays
+pub const ROUTING_CHECK_DURATION: Duration = Duration::from_millis(1500);
+
+const TONE_HZ: f32 = 1000.0;
+const TONE_AMPLITUDE: f32 = 0.2;
+// Level of the tone in the captured audio above which it counts as arrived
+const TONE_DETECTED_LEVEL: f32 = 0.01;
+
+/// What is known about the installed BlackHole driver
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct BlackHoleStatus {
+    pub installed: bool,
+    /// e.g. "BlackHole 2ch"
+    pub device: Option<String>,
+    /// Channel count of the variant, from the device name or its config
+    pub channels: Option<u16>,
+    /// The system output, and whether it plays into BlackHole
+    pub default_output: Option<String>,
+    pub routed: bool,
+}
+
+/// Result of `verify_routing`
+#[derive(Clone, Debug, Serialize)]
+pub struct RoutingCheck {
+    pub output: String,
+    pub loopback: String,
+    /// Amplitude of the test tone in the captured audio
+    pub tone_level: f32,
+    pub peak: f32,
+    pub tone_detected: bool,
+}
+
+fn is_blackhole_name(name: &str) -> bool {
+    name.to_lowercase().contains("blackhole")
+}
+
+/// Channel count in a name like "BlackHole 16ch"
+fn parse_variant(name: &str) -> Option<u16> {
+    name.split_whitespace()
+        .find_map(|word| word.to_lowercase().strip_suffix("ch")?.parse().ok())
+}
+
+/// The first BlackHole input device
+pub fn find_blackhole_input() -> Option<Device> {
+    let host = crate::audio_toolkit::get_cpal_host();
+    let devices = host.input_devices().ok()?;
+    devices
+        .into_iter()
+        .find(|d| d.name().is_ok_and(|name| is_blackhole_name(&name)))
+}
+
+pub fn detect_blackhole() -> BlackHoleStatus {
+    let device = find_blackhole_input();
+    let name = device.as_ref().and_then(|d| d.name().ok());
+    let channels = name.as_deref().and_then(parse_variant).or_else(|| {
+        device
+            .as_ref()
+            .and_then(|d| d.default_input_config().ok())
+            .map(|c| c.channels())
+    });
+    let default_output = crate::audio_toolkit::get_cpal_host()
+        .default_output_device()
+        .and_then(|d| d.name().ok());
+
+    BlackHoleStatus {
+        installed: device.is_some(),
+        device: name,
+        channels,
+        routed: default_output.as_deref().is_some_and(routes_to_loopback),
+        default_output,
+    }
+}
+
+/// Whether `file_name` is a BlackHole installer package, of the `channels`
+/// variant if given
+pub fn is_blackhole_installer(file_name: &str, channels: Option<u16>) -> bool {
+    let name = file_name.to_lowercase();
+    name.starts_with("blackhole")
+        && name.ends_with(".pkg")
+        && channels.map_or(true, |c| name.contains(&format!("{}ch", c)))
+}
+
+/// The most recently downloaded BlackHole installer in `dir`
+pub fn find_installer(dir: &Path, channels: Option<u16>) -> Option<std::path::PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| is_blackhole_installer(name, channels))
+        })
+        .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok())
+        .map(|entry| entry.path())
+}
+
+/// Amplitude of the `frequency` component of `samples` (Goertzel)
+fn tone_level(samples: &[f32], sample_rate: u32, frequency: f32) -> f32 {
+    if samples.is_empty() || sample_rate == 0 {
+        return 0.0;
+    }
+    let omega = 2.0 * std::f64::consts::PI * frequency as f64 / sample_rate as f64;
+    let coeff = 2.0 * omega.cos();
+    let (mut s1, mut s2) = (0.0f64, 0.0f64);
+    for &sample in samples {
+        let s0 = sample as f64 + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+    let power = (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0);
+    (2.0 * power.sqrt() / samples.len() as f64) as f32
+}
+
+fn build_tone<T>(
+    device: &Device,
+    config: &cpal::StreamConfig,
+) -> std::result::Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let step = TONE_HZ / config.sample_rate.0 as f32;
+    let mut phase = 0.0f32;
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            for frame in data.chunks_mut(channels) {
+                let value = (phase * 2.0 * std::f32::consts::PI).sin() * TONE_AMPLITUDE;
+                frame.fill(T::from_sample(value));
+                phase = (phase + step).fract();
+            }
+        },
+        |err| log::error!("Test tone stream error: {}", err),
+        None,
+    )
+}
+
+fn build_listener<T>(
+    device: &Device,
+    config: &cpal::StreamConfig,
+    captured: Arc<Mutex<Vec<f32>>>,
+) -> std::result::Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels as usize;
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let mut captured = captured.lock().unwrap();
+            captured.extend(data.chunks(channels).map(|f| Downmix::Average.mix(f)));
+        },
+        |err| log::error!("BlackHole listener stream error: {}", err),
+        None,
+    )
+}
+
+/// Play a test tone through `output`, or the default output, and check that
+/// it arrives at the BlackHole input
+pub fn verify_routing(output: Option<Device>) -> Result<RoutingCheck> {
+    let loopback = find_blackhole_input()
+        .ok_or_else(|| AudioError::DeviceNotFound("BlackHole is not installed".to_string()))?;
+    let output = match output {
+        Some(device) => device,
+        None => crate::audio_toolkit::get_cpal_host()
+            .default_output_device()
+            .ok_or_else(|| AudioError::DeviceNotFound("no output device".to_string()))?,
+    };
+
+    let input_config = loopback.default_input_config()?;
+    let sample_rate = input_config.sample_rate().0;
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let listener = match input_config.sample_format() {
+        cpal::SampleFormat::F32 => {
+            build_listener::<f32>(&loopback, &input_config.config(), captured.clone())
+        }
+        cpal::SampleFormat::I16 => {
+            build_listener::<i16>(&loopback, &input_config.config(), captured.clone())
+        }
+        cpal::SampleFormat::I32 => {
+            build_listener::<i32>(&loopback, &input_config.config(), captured.clone())
+        }
+        format => {
+            return Err(AudioError::UnsupportedFormat(format!(
+                "BlackHole delivers {:?} samples",
+                format
+            )))
+        }
+    }?;
+
+    let output_config = output.default_output_config()?;
+    let tone = match output_config.sample_format() {
+        cpal::SampleFormat::F32 => build_tone::<f32>(&output, &output_config.config()),
+        cpal::SampleFormat::I16 => build_tone::<i16>(&output, &output_config.config()),
+        cpal::SampleFormat::I32 => build_tone::<i32>(&output, &output_config.config()),
+        format => {
+            return Err(AudioError::UnsupportedFormat(format!(
+                "the output takes {:?} samples",
+                format
+            )))
+        }
+    }?;
+
+    listener.play()?;
+    tone.play()?;
+    std::thread::sleep(ROUTING_CHECK_DURATION);
+    drop(tone);
+    drop(listener);
+
+    let captured = captured.lock().unwrap();
+    let tone_level = tone_level(&captured, sample_rate, TONE_HZ);
+    let peak = captured.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let check = RoutingCheck {
+        output: output.name().unwrap_or_else(|_| "Unknown".to_string()),
+        loopback: loopback.name().unwrap_or_else(|_| "BlackHole".to_string()),
+        tone_level,
+        peak,
+        tone_detected: tone_level >= TONE_DETECTED_LEVEL,
+    };
+    log::info!(
+        "BlackHole routing check: tone {} (level {:.4}, peak {:.4}) from '{}'",
+        if check.tone_detected {
+            "arrived"
+        } else {
+            "missing"
+        },
+        check.tone_level,
+        check.peak,
+        check.output
+    );
+    Ok(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variant_and_installer_names() {
+        assert_eq!(parse_variant("BlackHole 2ch"), Some(2));
+        assert_eq!(parse_variant("BlackHole 64ch"), Some(64));
+        assert_eq!(parse_variant("BlackHole"), None);
+        assert!(is_blackhole_installer("BlackHole2ch-0.6.1.pkg", None));
+        assert!(is_blackhole_installer("BlackHole16ch-0.6.1.pkg", Some(16)));
+        assert!(!is_blackhole_installer("BlackHole2ch-0.6.1.pkg", Some(16)));
+        assert!(!is_blackhole_installer("BlackHole2ch-0.6.1.zip", None));
+    }
+
+    #[test]
+    fn test_tone_level_finds_the_tone() {
+        let rate = 48_000;
+        let sine = |hz: f32, amplitude: f32| -> Vec<f32> {
+            (0..rate)
+                .map(|i| {
+                    (2.0 * std::f32::consts::PI * hz * i as f32 / rate as f32).sin() * amplitude
+                })
+                .collect()
+        };
+        assert!((tone_level(&sine(TONE_HZ, 0.2), rate, TONE_HZ) - 0.2).abs() < 0.01);
+        assert!(tone_level(&sine(440.0, 0.2), rate, TONE_HZ) < TONE_DETECTED_LEVEL);
+        assert_eq!(tone_level(&[], rate, TONE_HZ), 0.0);
+    }
+}
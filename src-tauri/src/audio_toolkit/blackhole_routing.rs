@@ -0,0 +1,142 @@
+// Multi-output device routing system audio into BlackHole on macOS
+// Creates the device users would otherwise build by hand in Audio MIDI Setup:
+// the current speakers as the main device plus BlackHole, so audio is heard
+// and captured at once. The device is public so it shows up in Sound settings,
+// and is found again later by its fixed UID.
+
+use crate::audio_toolkit::coreaudio::{
+    address, check, device_for_uid, devices, get_property, get_string, has_output_streams,
+    AudioHardwareCreateAggregateDevice, AudioHardwareDestroyAggregateDevice, AudioObjectId,
+    AudioObjectSetPropertyData, CfOwned, AGGREGATE_MAIN_SUBDEVICE, DEVICE_UID,
+    HARDWARE_DEFAULT_OUTPUT, OBJECT_NAME, SCOPE_GLOBAL, SYSTEM_OBJECT, UNKNOWN_OBJECT,
+};
+use crate::audio_toolkit::error::{AudioError, Result};
+use core_foundation_sys::dictionary::CFDictionaryRef;
+use std::os::raw::c_void;
+
+/// UID of the device this module manages
+pub const MULTI_OUTPUT_UID: &str = "com.handy.blackhole-multi-output";
+/// Name shown in Sound settings and Audio MIDI Setup
+pub const MULTI_OUTPUT_NAME: &str = "Handy Multi-Output";
+
+fn set_default_output(device: AudioObjectId) -> Result<()> {
+    let status = unsafe {
+        AudioObjectSetPropertyData(
+            SYSTEM_OBJECT,
+            &address(HARDWARE_DEFAULT_OUTPUT, SCOPE_GLOBAL),
+            0,
+            std::ptr::null(),
+            std::mem::size_of::<AudioObjectId>() as u32,
+            &device as *const AudioObjectId as *const c_void,
+        )
+    };
+    check(status, "change the system output")
+}
+
+fn is_blackhole(device: AudioObjectId) -> bool {
+    get_string(device, OBJECT_NAME).is_some_and(|name| name.to_lowercase().contains("blackhole"))
+}
+
+/// Create the multi-output device from BlackHole and the current speakers,
+/// or reuse it if it exists, and make it the system output. Returns the
+/// speakers' name.
+pub fn setup_blackhole_routing() -> Result<String> {
+    let devices = devices()?;
+    let blackhole = devices
+        .iter()
+        .copied()
+        .find(|&d| is_blackhole(d))
+        .ok_or_else(|| AudioError::DeviceNotFound("BlackHole is not installed".to_string()))?;
+
+    if let Some(existing) = device_for_uid(MULTI_OUTPUT_UID) {
+        set_default_output(existing)?;
+        let speakers = get_string(existing, AGGREGATE_MAIN_SUBDEVICE)
+            .and_then(|uid| device_for_uid(&uid))
+            .and_then(|d| get_string(d, OBJECT_NAME));
+        return Ok(speakers.unwrap_or_else(|| MULTI_OUTPUT_NAME.to_string()));
+    }
+
+    // The current output, unless it is BlackHole itself, in which case the
+    // first real output device stands in for the speakers
+    let current: AudioObjectId = get_property(SYSTEM_OBJECT, HARDWARE_DEFAULT_OUTPUT)?;
+    let speakers = if current != UNKNOWN_OBJECT && !is_blackhole(current) {
+        current
+    } else {
+        devices
+            .iter()
+            .copied()
+            .find(|&d| d != blackhole && !is_blackhole(d) && has_output_streams(d))
+            .ok_or_else(|| AudioError::DeviceNotFound("no speakers to play through".to_string()))?
+    };
+    let speakers_uid = get_string(speakers, DEVICE_UID)
+        .ok_or_else(|| AudioError::Backend("the speakers have no UID".to_string()))?;
+    let blackhole_uid = get_string(blackhole, DEVICE_UID)
+        .ok_or_else(|| AudioError::Backend("BlackHole has no UID".to_string()))?;
+
+    let speakers_entry = CfOwned::string(&speakers_uid);
+    let blackhole_entry = CfOwned::string(&blackhole_uid);
+    let on = CfOwned::number(1);
+    let off = CfOwned::number(0);
+    // BlackHole runs on its own clock, so it follows the speakers'
+    let speakers_sub = CfOwned::dictionary(&[("uid", &speakers_entry)]);
+    let blackhole_sub = CfOwned::dictionary(&[("uid", &blackhole_entry), ("drift", &on)]);
+    let subdevices = CfOwned::array(&[&speakers_sub, &blackhole_sub]);
+    let uid = CfOwned::string(MULTI_OUTPUT_UID);
+    let name = CfOwned::string(MULTI_OUTPUT_NAME);
+    let description = CfOwned::dictionary(&[
+        ("uid", &uid),
+        ("name", &name),
+        ("subdevices", &subdevices),
+        // The main device keeps the volume keys working
+        ("master", &speakers_entry),
+        // Stacked means every subdevice plays the same audio
+        ("stacked", &on),
+        ("private", &off),
+    ]);
+
+    let mut device = UNKNOWN_OBJECT;
+    let status = unsafe {
+        AudioHardwareCreateAggregateDevice(description.0 as CFDictionaryRef, &mut device)
+    };
+    check(status, "create the multi-output device")?;
+    set_default_output(device)?;
+
+    let speakers_name = get_string(speakers, OBJECT_NAME).unwrap_or(speakers_uid);
+    log::info!(
+        "Created '{}' from BlackHole and '{}'",
+        MULTI_OUTPUT_NAME,
+        speakers_name
+    );
+    Ok(speakers_name)
+}
+
+/// Switch the system output back to the speakers and remove the device made
+/// by `setup_blackhole_routing`. Does nothing if it doesn't exist.
+pub fn teardown_blackhole_routing() -> Result<()> {
+    let Some(device) = device_for_uid(MULTI_OUTPUT_UID) else {
+        return Ok(());
+    };
+
+    let current: AudioObjectId = get_property(SYSTEM_OBJECT, HARDWARE_DEFAULT_OUTPUT)?;
+    if current == device {
+        let speakers =
+            get_string(device, AGGREGATE_MAIN_SUBDEVICE).and_then(|uid| device_for_uid(&uid));
+        match speakers {
+            Some(speakers) => set_default_output(speakers)?,
+            None => log::warn!(
+                "Speakers of '{}' are gone, output left as is",
+                MULTI_OUTPUT_NAME
+            ),
+        }
+    }
+
+    let status = unsafe { AudioHardwareDestroyAggregateDevice(device) };
+    check(status, "remove the multi-output device")?;
+    log::info!("Removed '{}'", MULTI_OUTPUT_NAME);
+    Ok(())
+}
+
+/// Whether the device made by `setup_blackhole_routing` exists
+pub fn blackhole_routing_exists() -> bool {
+    device_for_uid(MULTI_OUTPUT_UID).is_some()
+}
@@ -0,0 +1,282 @@
+// Shared CoreAudio plumbing for macOS
+// Property access on audio objects plus the Core Foundation values used to
+// describe aggregate devices. Used by the BlackHole routing and the process tap.
+
+use crate::audio_toolkit::error::{AudioError, Result};
+use core_foundation_sys::array::{kCFTypeArrayCallBacks, CFArrayCreate};
+use core_foundation_sys::base::{CFRelease, CFTypeRef};
+use core_foundation_sys::dictionary::{
+    kCFTypeDictionaryKeyCallBacks, kCFTypeDictionaryValueCallBacks, CFDictionaryCreate,
+    CFDictionaryRef,
+};
+use core_foundation_sys::number::{kCFNumberSInt32Type, CFNumberCreate};
+use core_foundation_sys::string::{
+    kCFStringEncodingUTF8, CFStringCreateWithBytes, CFStringGetCString, CFStringGetLength,
+    CFStringGetMaximumSizeForEncoding, CFStringRef,
+};
+use std::os::raw::{c_char, c_void};
+
+pub(crate) type AudioObjectId = u32;
+pub(crate) type OsStatus = i32;
+
+#[repr(C)]
+pub(crate) struct PropertyAddress {
+    pub(crate) selector: u32,
+    pub(crate) scope: u32,
+    pub(crate) element: u32,
+}
+
+pub(crate) const SYSTEM_OBJECT: AudioObjectId = 1;
+pub(crate) const UNKNOWN_OBJECT: AudioObjectId = 0;
+
+pub(crate) const fn fourcc(code: &[u8; 4]) -> u32 {
+    u32::from_be_bytes(*code)
+}
+
+pub(crate) const SCOPE_GLOBAL: u32 = fourcc(b"glob");
+pub(crate) const SCOPE_OUTPUT: u32 = fourcc(b"outp");
+pub(crate) const ELEMENT_MAIN: u32 = 0;
+
+pub(crate) const HARDWARE_DEVICES: u32 = fourcc(b"dev#");
+pub(crate) const HARDWARE_DEFAULT_OUTPUT: u32 = fourcc(b"dOut");
+pub(crate) const HARDWARE_TRANSLATE_UID: u32 = fourcc(b"uidd");
+pub(crate) const DEVICE_UID: u32 = fourcc(b"uid ");
+pub(crate) const DEVICE_STREAMS: u32 = fourcc(b"stm#");
+pub(crate) const OBJECT_NAME: u32 = fourcc(b"lnam");
+pub(crate) const AGGREGATE_MAIN_SUBDEVICE: u32 = fourcc(b"amst");
+
+#[link(name = "CoreAudio", kind = "framework")]
+extern "C" {
+    pub(crate) fn AudioObjectGetPropertyDataSize(
+        object: AudioObjectId,
+        address: *const PropertyAddress,
+        qualifier_size: u32,
+        qualifier: *const c_void,
+        data_size: *mut u32,
+    ) -> OsStatus;
+    pub(crate) fn AudioObjectGetPropertyData(
+        object: AudioObjectId,
+        address: *const PropertyAddress,
+        qualifier_size: u32,
+        qualifier: *const c_void,
+        data_size: *mut u32,
+        data: *mut c_void,
+    ) -> OsStatus;
+    pub(crate) fn AudioObjectSetPropertyData(
+        object: AudioObjectId,
+        address: *const PropertyAddress,
+        qualifier_size: u32,
+        qualifier: *const c_void,
+        data_size: u32,
+        data: *const c_void,
+    ) -> OsStatus;
+    pub(crate) fn AudioHardwareCreateAggregateDevice(
+        description: CFDictionaryRef,
+        device: *mut AudioObjectId,
+    ) -> OsStatus;
+    pub(crate) fn AudioHardwareDestroyAggregateDevice(device: AudioObjectId) -> OsStatus;
+}
+
+/// Owned Core Foundation object, released on drop
+pub(crate) struct CfOwned(pub(crate) CFTypeRef);
+
+impl CfOwned {
+    pub(crate) fn string(text: &str) -> Self {
+        Self(unsafe {
+            CFStringCreateWithBytes(
+                std::ptr::null(),
+                text.as_ptr(),
+                text.len() as _,
+                kCFStringEncodingUTF8,
+                0,
+            )
+        } as CFTypeRef)
+    }
+
+    pub(crate) fn number(value: i32) -> Self {
+        Self(unsafe {
+            CFNumberCreate(
+                std::ptr::null(),
+                kCFNumberSInt32Type,
+                &value as *const i32 as *const c_void,
+            )
+        } as CFTypeRef)
+    }
+
+    pub(crate) fn array(items: &[&CfOwned]) -> Self {
+        let values: Vec<CFTypeRef> = items.iter().map(|item| item.0).collect();
+        Self(unsafe {
+            CFArrayCreate(
+                std::ptr::null(),
+                values.as_ptr() as *const *const c_void,
+                values.len() as _,
+                &kCFTypeArrayCallBacks,
+            )
+        } as CFTypeRef)
+    }
+
+    pub(crate) fn dictionary(entries: &[(&str, &CfOwned)]) -> Self {
+        let keys: Vec<CfOwned> = entries.iter().map(|(key, _)| Self::string(key)).collect();
+        let key_refs: Vec<CFTypeRef> = keys.iter().map(|key| key.0).collect();
+        let values: Vec<CFTypeRef> = entries.iter().map(|(_, value)| value.0).collect();
+        Self(unsafe {
+            CFDictionaryCreate(
+                std::ptr::null(),
+                key_refs.as_ptr() as *const *const c_void,
+                values.as_ptr() as *const *const c_void,
+                entries.len() as _,
+                &kCFTypeDictionaryKeyCallBacks,
+                &kCFTypeDictionaryValueCallBacks,
+            )
+        } as CFTypeRef)
+    }
+
+    pub(crate) fn as_string(&self) -> Option<String> {
+        if self.0.is_null() {
+            return None;
+        }
+        unsafe {
+            let string = self.0 as CFStringRef;
+            let capacity =
+                CFStringGetMaximumSizeForEncoding(CFStringGetLength(string), kCFStringEncodingUTF8)
+                    + 1;
+            let mut buffer = vec![0u8; capacity as usize];
+            if CFStringGetCString(
+                string,
+                buffer.as_mut_ptr() as *mut c_char,
+                capacity,
+                kCFStringEncodingUTF8,
+            ) == 0
+            {
+                return None;
+            }
+            let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+            Some(String::from_utf8_lossy(&buffer[..end]).into_owned())
+        }
+    }
+}
+
+impl Drop for CfOwned {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            unsafe { CFRelease(self.0) };
+        }
+    }
+}
+
+pub(crate) fn address(selector: u32, scope: u32) -> PropertyAddress {
+    PropertyAddress {
+        selector,
+        scope,
+        element: ELEMENT_MAIN,
+    }
+}
+
+pub(crate) fn check(status: OsStatus, what: &str) -> Result<()> {
+    if status == 0 {
+        Ok(())
+    } else {
+        Err(AudioError::Backend(format!(
+            "CoreAudio failed to {} (OSStatus {})",
+            what, status
+        )))
+    }
+}
+
+// A property holding a single value of type `T`
+pub(crate) fn get_property<T: Default>(object: AudioObjectId, selector: u32) -> Result<T> {
+    let mut value = T::default();
+    let mut size = std::mem::size_of::<T>() as u32;
+    let status = unsafe {
+        AudioObjectGetPropertyData(
+            object,
+            &address(selector, SCOPE_GLOBAL),
+            0,
+            std::ptr::null(),
+            &mut size,
+            &mut value as *mut T as *mut c_void,
+        )
+    };
+    check(status, "read a device property")?;
+    Ok(value)
+}
+
+pub(crate) fn get_string(object: AudioObjectId, selector: u32) -> Option<String> {
+    let mut string: CFStringRef = std::ptr::null();
+    let mut size = std::mem::size_of::<CFStringRef>() as u32;
+    let status = unsafe {
+        AudioObjectGetPropertyData(
+            object,
+            &address(selector, SCOPE_GLOBAL),
+            0,
+            std::ptr::null(),
+            &mut size,
+            &mut string as *mut CFStringRef as *mut c_void,
+        )
+    };
+    if status != 0 {
+        return None;
+    }
+    CfOwned(string as CFTypeRef).as_string()
+}
+
+pub(crate) fn devices() -> Result<Vec<AudioObjectId>> {
+    let devices_address = address(HARDWARE_DEVICES, SCOPE_GLOBAL);
+    let mut size = 0u32;
+    let status = unsafe {
+        AudioObjectGetPropertyDataSize(
+            SYSTEM_OBJECT,
+            &devices_address,
+            0,
+            std::ptr::null(),
+            &mut size,
+        )
+    };
+    check(status, "count audio devices")?;
+
+    let mut ids = vec![0 as AudioObjectId; size as usize / std::mem::size_of::<AudioObjectId>()];
+    let status = unsafe {
+        AudioObjectGetPropertyData(
+            SYSTEM_OBJECT,
+            &devices_address,
+            0,
+            std::ptr::null(),
+            &mut size,
+            ids.as_mut_ptr() as *mut c_void,
+        )
+    };
+    check(status, "list audio devices")?;
+    ids.truncate(size as usize / std::mem::size_of::<AudioObjectId>());
+    Ok(ids)
+}
+
+pub(crate) fn has_output_streams(device: AudioObjectId) -> bool {
+    let mut size = 0u32;
+    let status = unsafe {
+        AudioObjectGetPropertyDataSize(
+            device,
+            &address(DEVICE_STREAMS, SCOPE_OUTPUT),
+            0,
+            std::ptr::null(),
+            &mut size,
+        )
+    };
+    status == 0 && size > 0
+}
+
+pub(crate) fn device_for_uid(uid: &str) -> Option<AudioObjectId> {
+    let uid = CfOwned::string(uid);
+    let mut device = UNKNOWN_OBJECT;
+    let mut size = std::mem::size_of::<AudioObjectId>() as u32;
+    let status = unsafe {
+        AudioObjectGetPropertyData(
+            SYSTEM_OBJECT,
+            &address(HARDWARE_TRANSLATE_UID, SCOPE_GLOBAL),
+            std::mem::size_of::<CFStringRef>() as u32,
+            &uid.0 as *const CFTypeRef as *const c_void,
+            &mut size,
+            &mut device as *mut AudioObjectId as *mut c_void,
+        )
+    };
+    (status == 0 && device != UNKNOWN_OBJECT).then_some(device)
+}
@@ -0,0 +1,36 @@
+// Opt-in verbose logging for the capture callbacks
+// Per-callback RMS/sample dumps are useful when diagnosing routing problems but
+// far too noisy for normal sessions, so they are off by default and only ever
+// written to the log file (the console target filters this target out).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Log target used for verbose capture diagnostics
+pub const AUDIO_DEBUG_TARGET: &str = "handy::audio_debug";
+
+static DEBUG_AUDIO_LOGGING: AtomicBool = AtomicBool::new(false);
+
+pub fn set_audio_debug_logging(enabled: bool) {
+    DEBUG_AUDIO_LOGGING.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether capture callbacks should compute and log per-buffer statistics
+pub fn audio_debug_enabled() -> bool {
+    DEBUG_AUDIO_LOGGING.load(Ordering::Relaxed)
+}
+
+/// Root-mean-square and peak amplitude of a block of samples
+pub fn rms_and_peak<I: IntoIterator<Item = f32>>(samples: I) -> (f32, f32) {
+    let mut sum_sq = 0.0f32;
+    let mut peak = 0.0f32;
+    let mut count = 0usize;
+    for s in samples {
+        sum_sq += s * s;
+        peak = peak.max(s.abs());
+        count += 1;
+    }
+    if count == 0 {
+        return (0.0, 0.0);
+    }
+    ((sum_sq / count as f32).sqrt(), peak)
+}
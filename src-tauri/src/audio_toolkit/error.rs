@@ -0,0 +1,211 @@
+// Errors of the audio toolkit
+// Capture, device and decoding failures are sorted into kinds, so commands can
+// tell the user what to do about them and callers can decide whether trying
+// again is worth it.
+
+use std::sync::mpsc;
+use thiserror::Error;
+
+pub type Result<T, E = AudioError> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum AudioError {
+    #[error("Audio device not found: {0}")]
+    DeviceNotFound(String),
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+    #[error("Failed to build audio stream: {0}")]
+    StreamBuildFailed(String),
+    #[error("Unsupported audio format: {0}")]
+    UnsupportedFormat(String),
+    /// The stream or its worker went away while in use
+    #[error("Audio stream closed")]
+    StreamClosed,
+    #[error("Invalid audio configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Failed to load VAD model: {0}")]
+    VadModel(String),
+    #[error("Invalid audio data: {0}")]
+    Decode(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Platform capture failures that fit no other kind
+    #[error("{0}")]
+    Backend(String),
+}
+
+impl AudioError {
+    /// Stable name of the kind, for the frontend and logs
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AudioError::DeviceNotFound(_) => "device_not_found",
+            AudioError::PermissionDenied(_) => "permission_denied",
+            AudioError::StreamBuildFailed(_) => "stream_build_failed",
+            AudioError::UnsupportedFormat(_) => "unsupported_format",
+            AudioError::StreamClosed => "stream_closed",
+            AudioError::InvalidConfig(_) => "invalid_config",
+            AudioError::VadModel(_) => "vad_model",
+            AudioError::Decode(_) => "decode",
+            AudioError::Io(_) => "io",
+            AudioError::Backend(_) => "backend",
+        }
+    }
+
+    /// Whether the same call may succeed a moment later, like opening a
+    /// device another stream was still releasing
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            AudioError::StreamBuildFailed(_) | AudioError::StreamClosed
+        )
+    }
+
+    /// What the user can do about it
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            AudioError::DeviceNotFound(_) => {
+                Some("Check that the device is connected, or pick another one in settings.")
+            }
+            AudioError::PermissionDenied(_) => Some(
+                "Allow Handy to record audio in your system's privacy settings, then try again.",
+            ),
+            AudioError::StreamBuildFailed(_) | AudioError::StreamClosed => {
+                Some("Close other apps using the device and try again.")
+            }
+            AudioError::UnsupportedFormat(_) => Some(
+                "Pick another device, or change this one's format in your system's audio settings.",
+            ),
+            AudioError::VadModel(_) => Some("Reinstall Handy to restore its bundled files."),
+            _ => None,
+        }
+    }
+
+    /// Sort a backend's error description, which is all some platforms give,
+    /// into permission problems and other stream failures
+    fn from_backend(description: String) -> Self {
+        let lower = description.to_lowercase();
+        if lower.contains("permission") || lower.contains("denied") || lower.contains("authoriz") {
+            AudioError::PermissionDenied(description)
+        } else {
+            AudioError::StreamBuildFailed(description)
+        }
+    }
+}
+
+impl From<cpal::BuildStreamError> for AudioError {
+    fn from(e: cpal::BuildStreamError) -> Self {
+        match e {
+            cpal::BuildStreamError::DeviceNotAvailable => {
+                AudioError::DeviceNotFound("the device is no longer available".to_string())
+            }
+            cpal::BuildStreamError::StreamConfigNotSupported => {
+                AudioError::UnsupportedFormat("the device rejected the stream format".to_string())
+            }
+            e => AudioError::from_backend(e.to_string()),
+        }
+    }
+}
+
+impl From<cpal::PlayStreamError> for AudioError {
+    fn from(e: cpal::PlayStreamError) -> Self {
+        match e {
+            cpal::PlayStreamError::DeviceNotAvailable => {
+                AudioError::DeviceNotFound("the device is no longer available".to_string())
+            }
+            e => AudioError::from_backend(e.to_string()),
+        }
+    }
+}
+
+impl From<cpal::DefaultStreamConfigError> for AudioError {
+    fn from(e: cpal::DefaultStreamConfigError) -> Self {
+        match e {
+            cpal::DefaultStreamConfigError::DeviceNotAvailable => {
+                AudioError::DeviceNotFound("the device is no longer available".to_string())
+            }
+            cpal::DefaultStreamConfigError::StreamTypeNotSupported => {
+                AudioError::UnsupportedFormat("the device has no usable stream format".to_string())
+            }
+            e => AudioError::from_backend(e.to_string()),
+        }
+    }
+}
+
+impl From<cpal::SupportedStreamConfigsError> for AudioError {
+    fn from(e: cpal::SupportedStreamConfigsError) -> Self {
+        match e {
+            cpal::SupportedStreamConfigsError::DeviceNotAvailable => {
+                AudioError::DeviceNotFound("the device is no longer available".to_string())
+            }
+            e => AudioError::from_backend(e.to_string()),
+        }
+    }
+}
+
+impl From<cpal::DevicesError> for AudioError {
+    fn from(e: cpal::DevicesError) -> Self {
+        AudioError::from_backend(e.to_string())
+    }
+}
+
+// A recorder's worker thread hung up on its channels
+impl<T> From<mpsc::SendError<T>> for AudioError {
+    fn from(_: mpsc::SendError<T>) -> Self {
+        AudioError::StreamClosed
+    }
+}
+
+impl From<mpsc::RecvError> for AudioError {
+    fn from(_: mpsc::RecvError) -> Self {
+        AudioError::StreamClosed
+    }
+}
+
+impl From<symphonia::core::errors::Error> for AudioError {
+    fn from(e: symphonia::core::errors::Error) -> Self {
+        use symphonia::core::errors::Error as SymphoniaError;
+        match e {
+            SymphoniaError::IoError(e) => AudioError::Io(e),
+            SymphoniaError::Unsupported(what) => AudioError::UnsupportedFormat(what.to_string()),
+            e => AudioError::Decode(e.to_string()),
+        }
+    }
+}
+
+impl From<hound::Error> for AudioError {
+    fn from(e: hound::Error) -> Self {
+        match e {
+            hound::Error::IoError(e) => AudioError::Io(e),
+            e => AudioError::UnsupportedFormat(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_errors_are_sorted_by_description() {
+        let denied = AudioError::from(cpal::BuildStreamError::BackendSpecific {
+            err: cpal::BackendSpecificError {
+                description: "Microphone access was denied by the user".to_string(),
+            },
+        });
+        assert_eq!(denied.kind(), "permission_denied");
+        assert!(!denied.is_retryable());
+
+        let busy = AudioError::from(cpal::BuildStreamError::BackendSpecific {
+            err: cpal::BackendSpecificError {
+                description: "Device or resource busy".to_string(),
+            },
+        });
+        assert_eq!(busy.kind(), "stream_build_failed");
+        assert!(busy.is_retryable());
+
+        let gone = AudioError::from(cpal::BuildStreamError::DeviceNotAvailable);
+        assert_eq!(gone.kind(), "device_not_found");
+        assert!(!gone.is_retryable());
+        assert!(gone.hint().is_some());
+    }
+}
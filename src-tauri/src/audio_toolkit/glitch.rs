@@ -0,0 +1,135 @@
+//! Capture glitches: audio lost between the device and the pipeline.
+//!
+//! Every glitch is counted in the metrics. Once one stream glitches often
+//! enough to be heard, an `audio-glitch-detected` event suggests what to try,
+//! at most once per stream per run.
+
+use crate::events::{self, AudioGlitchDetected};
+use crate::metrics::{self, CaptureStream, Glitch};
+use once_cell::sync::{Lazy, OnceCell};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+/// Glitches are counted towards the warning over this long
+const WINDOW: Duration = Duration::from_secs(60);
+/// Glitches within `WINDOW` that trigger the warning
+const THRESHOLD: usize = 5;
+/// Gaps longer than this are the stream pausing, such as loopback going quiet
+/// while nothing plays or the machine sleeping, rather than audio dropped
+const MAX_DROPOUT: Duration = Duration::from_secs(1);
+
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+// Indexed by `CaptureStream`
+static RATES: Lazy<Mutex<[GlitchRate; 2]>> = Lazy::new(Default::default);
+
+#[derive(Default)]
+struct GlitchRate {
+    recent: VecDeque<Instant>,
+    warned: bool,
+}
+
+impl GlitchRate {
+    /// Count a glitch at `at`. Returns the glitches within the window the
+    /// first time they reach the threshold.
+    fn push(&mut self, at: Instant) -> Option<usize> {
+        while self
+            .recent
+            .front()
+            .is_some_and(|first| at.saturating_duration_since(*first) > WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        if self.warned {
+            return None;
+        }
+        self.recent.push_back(at);
+        if self.recent.len() < THRESHOLD {
+            return None;
+        }
+        self.warned = true;
+        Some(self.recent.len())
+    }
+}
+
+/// Let glitches emit `audio-glitch-detected` events once the app is running
+pub fn attach(app: &AppHandle) {
+    let _ = APP_HANDLE.set(app.clone());
+}
+
+/// Count a glitch on `stream`, warning the frontend if they come too often
+pub fn report(stream: CaptureStream, glitch: Glitch) {
+    metrics::record_glitch(stream, glitch);
+    let Some(glitches) = RATES.lock().unwrap()[stream as usize].push(Instant::now()) else {
+        return;
+    };
+    log::warn!(
+        "{} {:?} capture glitches in the last {:?}",
+        glitches,
+        stream,
+        WINDOW
+    );
+    if let Some(app) = APP_HANDLE.get() {
+        events::emit(
+            app,
+            AudioGlitchDetected {
+                stream,
+                glitches,
+                window_secs: WINDOW.as_secs(),
+                remedies: remedies(stream),
+            },
+        );
+    }
+}
+
+/// Count `gap` of missing audio on `stream` as a glitch, unless it is long
+/// enough to be the stream pausing
+pub fn report_gap(stream: CaptureStream, gap: Duration) {
+    if gap <= MAX_DROPOUT {
+        report(stream, Glitch::Gap);
+    }
+}
+
+fn remedies(stream: CaptureStream) -> Vec<String> {
+    let mut remedies = vec![
+        "Close other apps that are using a lot of CPU".to_string(),
+        "Turn off power saving or plug the computer in".to_string(),
+    ];
+    match stream {
+        CaptureStream::Microphone => {
+            remedies.push("Connect the microphone directly instead of through a hub".to_string());
+            remedies.push("Use a larger audio buffer if the microphone offers one".to_string());
+        }
+        CaptureStream::SystemAudio => {
+            remedies.push("Quit other apps that are recording system audio".to_string());
+            remedies.push("Lower the sample rate of the output device".to_string());
+        }
+    }
+    remedies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warns_once_when_glitches_bunch_up() {
+        let mut rate = GlitchRate::default();
+        let start = Instant::now();
+        // Spread out, they never reach the threshold within a window
+        for n in 0..10 {
+            assert_eq!(rate.push(start + WINDOW / 3 * n), None);
+        }
+
+        let burst = start + WINDOW * 10;
+        for n in 0..THRESHOLD as u32 - 1 {
+            assert_eq!(rate.push(burst + Duration::from_millis(n as u64)), None);
+        }
+        assert_eq!(rate.push(burst + Duration::from_secs(1)), Some(THRESHOLD));
+        // Only the first time
+        for n in 0..THRESHOLD as u32 {
+            assert_eq!(rate.push(burst + Duration::from_secs(2 + n as u64)), None);
+        }
+    }
+}
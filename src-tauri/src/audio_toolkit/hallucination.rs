@@ -0,0 +1,179 @@
+//! Heuristics for text Whisper invents on silence.
+//!
+//! Given near-silent audio, Whisper tends to produce stock phrases from its
+//! training data ("Thanks for watching", subtitle credits) or loop on one
+//! phrase. These checks compare the text against how much speech the audio
+//! it came from actually holds.
+
+use crate::audio_toolkit::debug_log::rms_and_peak;
+
+// 30ms frames at 16kHz
+const FRAME_SAMPLES: usize = 480;
+// Frames quieter than this are counted as silence
+const VOICED_FRAME_RMS: f32 = 0.01;
+// Below these the audio is treated as holding no speech at all
+const MIN_RMS: f32 = 0.003;
+const MIN_VOICED_RATIO: f32 = 0.1;
+// Stock phrases are only believed with clearly more speech than that
+const STOCK_PHRASE_VOICED_RATIO: f32 = 0.3;
+
+/// Phrases Whisper produces on silence, matched against the whole text
+const STOCK_PHRASES: &[&str] = &[
+    "you",
+    "thank you",
+    "thanks for watching",
+    "thank you for watching",
+    "thank you so much for watching",
+    "please subscribe",
+    "bye",
+    "cảm ơn các bạn đã theo dõi",
+    "cảm ơn các bạn đã xem video",
+];
+
+/// Fragments that only ever come from subtitle credits, matched anywhere
+const STOCK_FRAGMENTS: &[&str] = &[
+    "subtitles by",
+    "amaraorg",
+    "like and subscribe",
+    "subscribe cho kênh",
+    "ghiền mì gõ",
+];
+
+/// How much speech a span of 16kHz audio holds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AudioEvidence {
+    pub rms: f32,
+    /// Fraction of 30ms frames loud enough to be speech
+    pub voiced_ratio: f32,
+}
+
+impl AudioEvidence {
+    pub fn measure(samples: &[f32]) -> Self {
+        let (rms, _) = rms_and_peak(samples.iter().copied());
+        let frames = samples.chunks(FRAME_SAMPLES).count();
+        let voiced = samples
+            .chunks(FRAME_SAMPLES)
+            .filter(|frame| rms_and_peak(frame.iter().copied()).0 >= VOICED_FRAME_RMS)
+            .count();
+        Self {
+            rms,
+            voiced_ratio: if frames == 0 {
+                0.0
+            } else {
+                voiced as f32 / frames as f32
+            },
+        }
+    }
+}
+
+/// Lowercase, drop punctuation and collapse whitespace
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_stock_phrase(normalized: &str) -> bool {
+    STOCK_PHRASES.contains(&normalized)
+        || STOCK_FRAGMENTS
+            .iter()
+            .any(|fragment| normalized.contains(fragment))
+}
+
+/// Whether most of the text is one phrase repeated back to back, the loop
+/// Whisper falls into when it runs out of audio
+fn is_repetition_loop(normalized: &str) -> bool {
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+    for n in 1..=(words.len() / 3).min(8) {
+        // Single words need a longer run; "no no no" is real speech
+        let min_repeats = if n == 1 { 4 } else { 3 };
+        for start in 0..n {
+            let mut repeats = 1;
+            let mut i = start;
+            while i + 2 * n <= words.len() {
+                if words[i..i + n] == words[i + n..i + 2 * n] {
+                    repeats += 1;
+                    if repeats >= min_repeats && repeats * n * 4 >= words.len() * 3 {
+                        return true;
+                    }
+                } else {
+                    repeats = 1;
+                }
+                i += n;
+            }
+        }
+    }
+    false
+}
+
+/// Why `text` looks invented given the audio it was transcribed from, or
+/// `None` if it should be kept
+pub fn hallucination_reason(text: &str, evidence: &AudioEvidence) -> Option<&'static str> {
+    let normalized = normalize(text);
+    if normalized.is_empty() {
+        return None;
+    }
+    if evidence.rms < MIN_RMS || evidence.voiced_ratio < MIN_VOICED_RATIO {
+        return Some("no speech in the audio");
+    }
+    if is_stock_phrase(&normalized) && evidence.voiced_ratio < STOCK_PHRASE_VOICED_RATIO {
+        return Some("stock phrase over little speech");
+    }
+    if is_repetition_loop(&normalized) {
+        return Some("repetition loop");
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evidence(rms: f32, voiced_ratio: f32) -> AudioEvidence {
+        AudioEvidence { rms, voiced_ratio }
+    }
+
+    #[test]
+    fn test_measure_counts_voiced_frames() {
+        let mut samples = vec![0.0; FRAME_SAMPLES * 3];
+        samples.extend(std::iter::repeat(0.1).take(FRAME_SAMPLES));
+        let measured = AudioEvidence::measure(&samples);
+        assert!((measured.voiced_ratio - 0.25).abs() < 1e-6);
+        assert!(measured.rms > MIN_RMS);
+    }
+
+    #[test]
+    fn test_stock_phrases_need_real_speech() {
+        let quiet = evidence(0.02, 0.15);
+        let loud = evidence(0.05, 0.8);
+        assert_eq!(
+            hallucination_reason("Thanks for watching!", &quiet),
+            Some("stock phrase over little speech")
+        );
+        assert_eq!(hallucination_reason("Thanks for watching!", &loud), None);
+        assert!(hallucination_reason("Hãy subscribe cho kênh Ghiền Mì Gõ", &quiet).is_some());
+        assert_eq!(hallucination_reason("Thanks, see you there", &quiet), None);
+    }
+
+    #[test]
+    fn test_silence_and_loops_are_dropped() {
+        let loud = evidence(0.05, 0.8);
+        assert_eq!(
+            hallucination_reason("Hello there", &evidence(0.001, 0.0)),
+            Some("no speech in the audio")
+        );
+        assert_eq!(
+            hallucination_reason("I'm going. I'm going. I'm going. I'm going.", &loud),
+            Some("repetition loop")
+        );
+        assert_eq!(hallucination_reason("no no no", &loud), None);
+        assert_eq!(
+            hallucination_reason("we can go now if you want to go", &loud),
+            None
+        );
+    }
+}
@@ -0,0 +1,255 @@
+// Diagnosis for loopback devices (BlackHole and friends) that deliver only silence
+// The capture callback feeds a ChannelProbe with the raw interleaved samples;
+// when the startup check finds nothing but zeros the probe is turned into a
+// LoopbackDiagnosis and emitted once, so the UI can show what is wrong as a
+// checklist instead of the user digging through logs.
+
+use serde::Serialize;
+
+/// Event carrying a `LoopbackDiagnosis`
+pub const LOOPBACK_DIAGNOSIS_EVENT: &str = "loopback-diagnosis";
+
+// Peak amplitude below which a channel counts as digital silence
+const SILENCE_PEAK: f32 = 0.00001;
+
+/// Per-channel signal statistics gathered from the capture callback
+#[derive(Clone, Debug, Default)]
+pub struct ChannelProbe {
+    callbacks: u64,
+    frames: u64,
+    peaks: Vec<f32>,
+    sum_squares: Vec<f64>,
+}
+
+impl ChannelProbe {
+    /// Accumulate one callback's worth of interleaved samples
+    pub fn observe(&mut self, data: impl IntoIterator<Item = f32>, channels: usize) {
+        let channels = channels.max(1);
+        if self.peaks.len() != channels {
+            self.peaks = vec![0.0; channels];
+            self.sum_squares = vec![0.0; channels];
+        }
+
+        self.callbacks += 1;
+        let mut samples = 0u64;
+        for (i, sample) in data.into_iter().enumerate() {
+            let channel = i % channels;
+            self.peaks[channel] = self.peaks[channel].max(sample.abs());
+            self.sum_squares[channel] += (sample as f64) * (sample as f64);
+            samples += 1;
+        }
+        self.frames += samples / channels as u64;
+    }
+
+    pub fn channel_activity(&self) -> Vec<ChannelActivity> {
+        self.peaks
+            .iter()
+            .zip(&self.sum_squares)
+            .enumerate()
+            .map(|(i, (&peak, &sum_squares))| ChannelActivity {
+                channel: i + 1,
+                peak,
+                rms: if self.frames > 0 {
+                    (sum_squares / self.frames as f64).sqrt() as f32
+                } else {
+                    0.0
+                },
+                active: peak >= SILENCE_PEAK,
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ChannelActivity {
+    /// 1-based channel number
+    pub channel: usize,
+    pub peak: f32,
+    pub rms: f32,
+    pub active: bool,
+}
+
+/// Format of the loopback input and the output the OS is playing to
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LoopbackRouting {
+    pub device: String,
+    pub sample_rate: u32,
+    pub channels: usize,
+    pub sample_format: String,
+    /// Current default output device, if it could be read
+    pub default_output: Option<String>,
+    pub output_sample_rate: Option<u32>,
+}
+
+/// One line of the checklist. `hint` says what to do when it failed.
+#[derive(Clone, Debug, Serialize)]
+pub struct DiagnosisCheck {
+    pub id: &'static str,
+    pub label: String,
+    pub passed: bool,
+    pub hint: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LoopbackDiagnosis {
+    #[serde(flatten)]
+    pub routing: LoopbackRouting,
+    pub callbacks: u64,
+    pub frames_received: u64,
+    pub channel_activity: Vec<ChannelActivity>,
+    pub checks: Vec<DiagnosisCheck>,
+}
+
+/// Whether the output device forwards audio into a loopback device
+pub fn routes_to_loopback(output: &str) -> bool {
+    let name = output.to_lowercase();
+    name.contains("blackhole") || name.contains("multi-output")
+}
+
+impl LoopbackDiagnosis {
+    pub fn new(routing: LoopbackRouting, probe: &ChannelProbe) -> Self {
+        let channel_activity = probe.channel_activity();
+        let active: Vec<usize> = channel_activity
+            .iter()
+            .filter(|c| c.active)
+            .map(|c| c.channel)
+            .collect();
+
+        let mut checks = vec![DiagnosisCheck {
+            id: "stream",
+            label: format!("{} is delivering audio callbacks", routing.device),
+            passed: probe.callbacks > 0,
+            hint: Some(
+                "The device opened but macOS sent no audio. Check that Handy is allowed to use the microphone in System Settings > Privacy & Security > Microphone.".to_string(),
+            ),
+        }];
+
+        let routed = routing.default_output.as_deref().map(routes_to_loopback);
+        checks.push(DiagnosisCheck {
+            id: "output_routing",
+            label: match &routing.default_output {
+                Some(output) => format!("System output '{}' feeds {}", output, routing.device),
+                None => "System output device could be read".to_string(),
+            },
+            passed: routed == Some(true),
+            hint: Some(format!(
+                "Click Set Up Routing to create a Multi-Output Device containing {} and your speakers and make it the system output.",
+                routing.device
+            )),
+        });
+
+        if let Some(output_rate) = routing.output_sample_rate {
+            checks.push(DiagnosisCheck {
+                id: "sample_rate",
+                label: format!(
+                    "Output and {} both run at {}Hz",
+                    routing.device, routing.sample_rate
+                ),
+                passed: output_rate == routing.sample_rate,
+                hint: Some(format!(
+                    "The output runs at {}Hz but {} at {}Hz. Set both to the same rate in Audio MIDI Setup.",
+                    output_rate, routing.device, routing.sample_rate
+                )),
+            });
+        }
+
+        checks.push(DiagnosisCheck {
+            id: "signal",
+            label: "Non-silent audio is arriving".to_string(),
+            passed: !active.is_empty(),
+            hint: Some(
+                "Play something in another app and make sure its volume, and the output device's volume, are not muted.".to_string(),
+            ),
+        });
+
+        if !active.is_empty() && active.len() < channel_activity.len() {
+            checks.push(DiagnosisCheck {
+                id: "channels",
+                label: "Every channel carries audio".to_string(),
+                passed: false,
+                hint: Some(format!(
+                    "Only channel {:?} of {} has signal. Check the channel mapping of the Multi-Output Device.",
+                    active,
+                    channel_activity.len()
+                )),
+            });
+        }
+
+        for check in &mut checks {
+            if check.passed {
+                check.hint = None;
+            }
+        }
+
+        Self {
+            routing,
+            callbacks: probe.callbacks,
+            frames_received: probe.frames,
+            channel_activity,
+            checks,
+        }
+    }
+
+    /// The first failed check, for a one-line log summary
+    pub fn first_failure(&self) -> Option<&DiagnosisCheck> {
+        self.checks.iter().find(|check| !check.passed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routing(output: &str, output_rate: u32) -> LoopbackRouting {
+        LoopbackRouting {
+            device: "BlackHole 2ch".to_string(),
+            sample_rate: 48000,
+            channels: 2,
+            sample_format: "F32".to_string(),
+            default_output: Some(output.to_string()),
+            output_sample_rate: Some(output_rate),
+        }
+    }
+
+    #[test]
+    fn test_silent_device_with_speakers_as_output() {
+        let mut probe = ChannelProbe::default();
+        for _ in 0..10 {
+            probe.observe(vec![0.0; 1024], 2);
+        }
+        let diagnosis = LoopbackDiagnosis::new(routing("MacBook Pro Speakers", 44100), &probe);
+
+        assert_eq!(diagnosis.callbacks, 10);
+        assert_eq!(diagnosis.frames_received, 5120);
+        assert!(diagnosis.channel_activity.iter().all(|c| !c.active));
+        let failed: Vec<&str> = diagnosis
+            .checks
+            .iter()
+            .filter(|c| !c.passed)
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(failed, vec!["output_routing", "sample_rate", "signal"]);
+        assert_eq!(diagnosis.first_failure().unwrap().id, "output_routing");
+    }
+
+    #[test]
+    fn test_one_dead_channel() {
+        let mut probe = ChannelProbe::default();
+        // Left carries a tone, right is silent
+        let data: Vec<f32> = (0..2048)
+            .map(|i| if i % 2 == 0 { 0.2 } else { 0.0 })
+            .collect();
+        probe.observe(data, 2);
+        let diagnosis = LoopbackDiagnosis::new(routing("Multi-Output Device", 48000), &probe);
+
+        let activity = &diagnosis.channel_activity;
+        assert!(activity[0].active && !activity[1].active);
+        assert!((activity[0].rms - 0.2).abs() < 1e-6);
+        assert_eq!(diagnosis.first_failure().unwrap().id, "channels");
+        assert!(diagnosis
+            .checks
+            .iter()
+            .filter(|c| c.passed)
+            .all(|c| c.hint.is_none()));
+    }
+}
@@ -1,15 +1,39 @@
 pub mod audio;
+pub mod blackhole;
 pub mod constants;
+pub mod debug_log;
+pub mod error;
+pub mod glitch;
+pub mod hallucination;
+pub mod loopback_diagnosis;
+pub mod numbers;
+pub mod profanity;
+pub mod punctuation;
+pub mod ring_buffer;
+pub mod sample_queue;
+pub mod selftest;
+pub mod simulated;
+pub mod stream;
 pub mod system_audio;
 pub mod text;
+pub mod timed;
 pub mod utils;
 pub mod vad;
+pub mod wasapi;
 
+#[cfg(target_os = "macos")]
+pub mod blackhole_routing;
+#[cfg(target_os = "macos")]
+mod coreaudio;
+#[cfg(target_os = "macos")]
+pub mod process_tap;
 #[cfg(target_os = "macos")]
 pub mod screencapturekit;
 
+pub use error::AudioError;
 pub use audio::{
-    list_input_devices, list_output_devices, save_wav_file, AudioRecorder, CpalDeviceInfo,
+    decode_audio_file, find_device, list_input_devices, list_output_devices, save_flac_file,
+    save_wav_file, waveform_envelope, AudioRecorder, CpalDeviceInfo,
 };
 
 #[cfg(target_os = "macos")]
@@ -23,6 +47,11 @@ pub use system_audio::{SystemAudioCapture, WindowsSystemAudio};
 
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
 pub use system_audio::SystemAudioCapture;
-pub use text::apply_custom_words;
+pub use simulated::{SimulatedAudioCapture, SimulatedSource};
+pub use stream::{AudioFrame, AudioFrameStream};
+pub use timed::TimedSamples;
+pub use numbers::normalize_numbers;
+pub use profanity::filter_profanity;
+pub use text::{apply_casing, apply_custom_words, apply_spoken_formatting, custom_words_prompt};
 pub use utils::get_cpal_host;
 pub use vad::{SileroVad, VoiceActivityDetector};
@@ -0,0 +1,683 @@
+//! Number and date normalization for English transcripts.
+//!
+//! Runs in up to three passes over the words of a transcript: spoken numbers
+//! to digits, then dates into the chosen format, then digits back to words.
+//! Dates are written with digits whatever the number style.
+
+use crate::settings::{DateFormat, NumberFormatting, NumberStyle, ThousandsSeparator};
+
+const UNITS: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const SCALES: [(&str, u64); 3] = [
+    ("billion", 1_000_000_000),
+    ("million", 1_000_000),
+    ("thousand", 1_000),
+];
+const ORDINAL_UNITS: [&str; 20] = [
+    "zeroth",
+    "first",
+    "second",
+    "third",
+    "fourth",
+    "fifth",
+    "sixth",
+    "seventh",
+    "eighth",
+    "ninth",
+    "tenth",
+    "eleventh",
+    "twelfth",
+    "thirteenth",
+    "fourteenth",
+    "fifteenth",
+    "sixteenth",
+    "seventeenth",
+    "eighteenth",
+    "nineteenth",
+];
+const ORDINAL_TENS: [&str; 10] = [
+    "",
+    "",
+    "twentieth",
+    "thirtieth",
+    "fortieth",
+    "fiftieth",
+    "sixtieth",
+    "seventieth",
+    "eightieth",
+    "ninetieth",
+];
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+// Keeps a stray "1234567890123" from being spelled out word by word
+const MAX_SPELLED: u64 = 999_999_999_999;
+
+/// A word of the transcript with the punctuation around it split off
+#[derive(Clone, Debug)]
+struct Token {
+    prefix: String,
+    core: String,
+    suffix: String,
+    /// Already in its final form, e.g. a formatted date
+    protected: bool,
+}
+
+impl Token {
+    fn new(prefix: &str, core: String, suffix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            core,
+            suffix: suffix.to_string(),
+            protected: false,
+        }
+    }
+
+    fn lower(&self) -> String {
+        self.core.to_lowercase()
+    }
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    text.split_whitespace()
+        .map(|word| {
+            let start = word
+                .find(|c: char| c.is_alphanumeric())
+                .unwrap_or(word.len());
+            let end = word
+                .rfind(|c: char| c.is_alphanumeric())
+                .map_or(start, |i| {
+                    i + word[i..].chars().next().map_or(1, char::len_utf8)
+                });
+            Token::new(&word[..start], word[start..end].to_string(), &word[end..])
+        })
+        .collect()
+}
+
+fn render(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .map(|t| format!("{}{}{}", t.prefix, t.core, t.suffix))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Unit,
+    Teen,
+    Tens,
+    Hundred,
+    Scale,
+    And,
+    A,
+}
+
+/// A spoken number read from the tokens at some position
+struct Spoken {
+    value: u64,
+    tokens: usize,
+    ordinal: bool,
+    /// Number words read, counting the parts of "twenty-five" separately
+    words: usize,
+}
+
+fn word_value(word: &str) -> Option<(Kind, u64, bool)> {
+    if let Some(n) = UNITS.iter().position(|w| *w == word) {
+        return Some((
+            if n < 10 { Kind::Unit } else { Kind::Teen },
+            n as u64,
+            false,
+        ));
+    }
+    if let Some(n) = ORDINAL_UNITS.iter().skip(1).position(|w| *w == word) {
+        let n = n + 1;
+        return Some((if n < 10 { Kind::Unit } else { Kind::Teen }, n as u64, true));
+    }
+    if let Some(n) = TENS.iter().position(|w| !w.is_empty() && *w == word) {
+        return Some((Kind::Tens, n as u64 * 10, false));
+    }
+    if let Some(n) = ORDINAL_TENS
+        .iter()
+        .position(|w| !w.is_empty() && *w == word)
+    {
+        return Some((Kind::Tens, n as u64 * 10, true));
+    }
+    if word == "hundred" {
+        return Some((Kind::Hundred, 100, false));
+    }
+    if let Some((_, scale)) = SCALES.iter().find(|(w, _)| *w == word) {
+        return Some((Kind::Scale, *scale, false));
+    }
+    match word {
+        "and" => Some((Kind::And, 0, false)),
+        "a" => Some((Kind::A, 1, false)),
+        _ => None,
+    }
+}
+
+/// Read the longest spoken number starting at `start`, e.g. "one hundred and
+/// five" or "twenty-first". A number never runs past punctuation.
+fn parse_spoken(tokens: &[Token], start: usize) -> Option<Spoken> {
+    let mut total = 0u64;
+    let mut current = 0u64;
+    let mut last: Option<Kind> = None;
+    let mut words = 0;
+    let mut best = None;
+
+    'tokens: for (offset, token) in tokens[start..].iter().enumerate() {
+        if token.protected || (offset > 0 && !token.prefix.is_empty()) {
+            break;
+        }
+        let lower = token.lower();
+        let (mut t, mut c, mut l, mut w) = (total, current, last, words);
+        let mut ordinal = false;
+        for part in lower.split('-') {
+            let Some((kind, value, is_ordinal)) = word_value(part) else {
+                break 'tokens;
+            };
+            if ordinal {
+                break 'tokens;
+            }
+            let allowed = match kind {
+                Kind::Unit => c % 10 == 0 && !matches!(l, Some(Kind::Unit | Kind::Teen | Kind::A)),
+                Kind::Teen | Kind::Tens => {
+                    c % 100 == 0
+                        && !matches!(l, Some(Kind::Unit | Kind::Teen | Kind::Tens | Kind::A))
+                }
+                Kind::Hundred => matches!(l, Some(Kind::Unit | Kind::Teen | Kind::A)) && c < 100,
+                Kind::Scale => c > 0 && c < 1000 && l != Some(Kind::And),
+                Kind::And => matches!(l, Some(Kind::Hundred | Kind::Scale)),
+                Kind::A => l.is_none(),
+            };
+            // "zero" only stands alone
+            if !allowed || (value == 0 && kind == Kind::Unit && l.is_some()) {
+                break 'tokens;
+            }
+            match kind {
+                Kind::Unit | Kind::Teen | Kind::Tens => c += value,
+                Kind::Hundred => c *= 100,
+                Kind::Scale => {
+                    t += c * value;
+                    c = 0;
+                }
+                Kind::And => {}
+                Kind::A => c = 1,
+            }
+            l = Some(kind);
+            w += 1;
+            ordinal = is_ordinal;
+        }
+
+        (total, current, last, words) = (t, c, l, w);
+        if !matches!(last, Some(Kind::And | Kind::A)) {
+            best = Some(Spoken {
+                value: total + current,
+                tokens: offset + 1,
+                ordinal,
+                words,
+            });
+        }
+        if ordinal || !token.suffix.is_empty() {
+            break;
+        }
+    }
+    best
+}
+
+/// The value of a token written in digits, e.g. "25", "1,000" or "5th"
+fn parse_digits(core: &str) -> Option<(u64, bool)> {
+    let lower = core.to_lowercase();
+    let (digits, ordinal) = match ["st", "nd", "rd", "th"]
+        .iter()
+        .find_map(|suffix| lower.strip_suffix(suffix))
+    {
+        Some(digits) => (digits, true),
+        None => (lower.as_str(), false),
+    };
+    if digits.is_empty() || !digits.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    if digits.contains(',') {
+        let mut groups = digits.split(',');
+        let first = groups.next()?;
+        let valid = (1..=3).contains(&first.len())
+            && groups.all(|g| g.len() == 3 && g.bytes().all(|b| b.is_ascii_digit()));
+        if !valid || ordinal {
+            return None;
+        }
+    }
+    let plain: String = digits.chars().filter(|c| *c != ',').collect();
+    if !plain.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    plain.parse().ok().map(|value| (value, ordinal))
+}
+
+fn ordinal_suffix(value: u64) -> &'static str {
+    match (value % 10, value % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+/// Digits grouped by `separator`. Four-digit numbers are left ungrouped so
+/// years read naturally.
+fn format_digits(value: u64, separator: ThousandsSeparator) -> String {
+    let digits = value.to_string();
+    let separator = match separator {
+        ThousandsSeparator::None => return digits,
+        _ if value < 10_000 => return digits,
+        ThousandsSeparator::Comma => ',',
+        ThousandsSeparator::Period => '.',
+        ThousandsSeparator::Space => ' ',
+    };
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(separator);
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+fn below_thousand_words(value: u64) -> Vec<String> {
+    let mut words = Vec::new();
+    let (hundreds, rest) = (value / 100, value % 100);
+    if hundreds > 0 {
+        words.push(format!("{} hundred", UNITS[hundreds as usize]));
+    }
+    if rest >= 20 {
+        let tens = TENS[(rest / 10) as usize];
+        words.push(match rest % 10 {
+            0 => tens.to_string(),
+            unit => format!("{}-{}", tens, UNITS[unit as usize]),
+        });
+    } else if rest > 0 {
+        words.push(UNITS[rest as usize].to_string());
+    }
+    words
+}
+
+/// `value` spelled out, e.g. "one thousand two hundred five"
+fn to_words(value: u64) -> String {
+    if value == 0 {
+        return UNITS[0].to_string();
+    }
+    let mut words = Vec::new();
+    let mut rest = value;
+    for (name, scale) in SCALES {
+        if rest >= scale {
+            words.extend(below_thousand_words(rest / scale));
+            words.push(name.to_string());
+            rest %= scale;
+        }
+    }
+    words.extend(below_thousand_words(rest));
+    words.join(" ")
+}
+
+fn to_ordinal_words(value: u64) -> String {
+    let words = to_words(value);
+    let split = words.rfind([' ', '-']).map_or(0, |i| i + 1);
+    let (head, last) = words.split_at(split);
+    let last = match UNITS.iter().position(|w| *w == last) {
+        Some(n) => ORDINAL_UNITS[n].to_string(),
+        None => match TENS.iter().position(|w| !w.is_empty() && *w == last) {
+            Some(n) => ORDINAL_TENS[n].to_string(),
+            None => format!("{}th", last),
+        },
+    };
+    format!("{}{}", head, last)
+}
+
+fn is_word(token: Option<&Token>, options: &[&str]) -> bool {
+    token.is_some_and(|t| t.prefix.is_empty() && options.contains(&t.lower().as_str()))
+}
+
+/// Spoken numbers to digits, with currency and percentages
+fn to_digits(tokens: Vec<Token>, separator: ThousandsSeparator) -> Vec<Token> {
+    let mut out = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let number = if tokens[i].protected {
+            None
+        } else if let Some((value, ordinal)) = parse_digits(&tokens[i].core) {
+            Some(Spoken {
+                value,
+                tokens: 1,
+                ordinal,
+                words: 0,
+            })
+        } else {
+            parse_spoken(&tokens, i)
+        };
+        let Some(number) = number else {
+            out.push(tokens[i].clone());
+            i += 1;
+            continue;
+        };
+
+        let mut end = i + number.tokens;
+        let open = tokens[end - 1].suffix.is_empty();
+        let digits = format_digits(number.value, separator);
+        let (prefix, core) = if number.ordinal {
+            (
+                String::new(),
+                format!("{}{}", number.value, ordinal_suffix(number.value)),
+            )
+        } else if open && is_word(tokens.get(end), &["dollar", "dollars", "bucks"]) {
+            end += 1;
+            let mut core = digits;
+            // "and fifty cents"
+            if tokens[end - 1].suffix.is_empty() && is_word(tokens.get(end), &["and"]) {
+                if let Some(cents) = parse_spoken(&tokens, end + 1)
+                    .filter(|c| c.value < 100 && !c.ordinal)
+                    .filter(|c| is_word(tokens.get(end + 1 + c.tokens), &["cent", "cents"]))
+                {
+                    core = format!("{}.{:02}", core, cents.value);
+                    end += 2 + cents.tokens;
+                }
+            }
+            ("$".to_string(), core)
+        } else if open && is_word(tokens.get(end), &["percent"]) {
+            end += 1;
+            (String::new(), format!("{}%", digits))
+        } else if number.words == 1 && number.value < 10 {
+            // Small numbers on their own read better as words ("one of them")
+            out.push(tokens[i].clone());
+            i += 1;
+            continue;
+        } else {
+            (String::new(), digits)
+        };
+
+        let first = &tokens[i];
+        let prefix = if first.prefix.ends_with('$') {
+            first.prefix.clone()
+        } else {
+            format!("{}{}", first.prefix, prefix)
+        };
+        out.push(Token::new(&prefix, core, &tokens[end - 1].suffix));
+        i = end;
+    }
+    out
+}
+
+fn month_index(token: Option<&Token>) -> Option<u32> {
+    let token = token?;
+    if !token.prefix.is_empty() && !token.prefix.chars().all(|c| c == '(' || c == '"') {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|m| *m == token.lower())
+        .map(|i| i as u32 + 1)
+}
+
+/// A day of the month at `i`, in digits or words, and the tokens it used
+fn day_at(tokens: &[Token], i: usize) -> Option<(u32, usize)> {
+    let token = tokens.get(i)?;
+    if token.protected || !token.prefix.is_empty() {
+        return None;
+    }
+    let (value, used) = match parse_digits(&token.core) {
+        Some((value, _)) if !token.core.contains(',') => (value, 1),
+        Some(_) => return None,
+        // Spoken days must be ordinals; "may one day" is not a date
+        None => {
+            let spoken = parse_spoken(tokens, i).filter(|s| s.ordinal)?;
+            (spoken.value, spoken.tokens)
+        }
+    };
+    (1..=31).contains(&value).then_some((value as u32, used))
+}
+
+fn year_at(token: Option<&Token>) -> Option<u32> {
+    let token = token?;
+    if !token.prefix.is_empty() || token.core.len() != 4 {
+        return None;
+    }
+    match parse_digits(&token.core) {
+        Some((year, false)) if (1000..=2999).contains(&year) => Some(year as u32),
+        _ => None,
+    }
+}
+
+fn format_date(month: u32, day: u32, year: Option<u32>, format: DateFormat) -> Option<String> {
+    let month_name = |m: u32| {
+        let name = MONTHS[m as usize - 1];
+        name[..1].to_uppercase() + &name[1..]
+    };
+    Some(match (format, year) {
+        (DateFormat::AsTranscribed, _) => return None,
+        (DateFormat::Long, Some(year)) => format!("{} {}, {}", month_name(month), day, year),
+        (DateFormat::Long, None) => format!("{} {}", month_name(month), day),
+        (DateFormat::Iso, Some(year)) => format!("{:04}-{:02}-{:02}", year, month, day),
+        (DateFormat::Iso, None) => return None,
+        (DateFormat::Us, Some(year)) => format!("{}/{}/{}", month, day, year),
+        (DateFormat::Us, None) => format!("{}/{}", month, day),
+        (DateFormat::European, Some(year)) => format!("{}/{}/{}", day, month, year),
+        (DateFormat::European, None) => format!("{}/{}", day, month),
+    })
+}
+
+/// Reformat "March 5th, 2024" and "the fifth of March" style dates
+fn format_dates(tokens: Vec<Token>, format: DateFormat) -> Vec<Token> {
+    let mut out = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        // Month first: "March 5th, 2024"
+        let month_first = month_index(tokens.get(i))
+            .filter(|_| tokens[i].suffix.is_empty())
+            .and_then(|month| day_at(&tokens, i + 1).map(|(day, used)| (month, day, i + 1 + used)));
+        // Day first: "the 5th of March 2024"
+        let day_first = || {
+            let start = if is_word(tokens.get(i), &["the"]) {
+                i + 1
+            } else {
+                i
+            };
+            let (day, used) = day_at(&tokens, start)?;
+            let of = start + used;
+            if !tokens[of - 1].suffix.is_empty() || !is_word(tokens.get(of), &["of"]) {
+                return None;
+            }
+            let month = month_index(tokens.get(of + 1))?;
+            Some((month, day, of + 2))
+        };
+
+        let Some((month, day, mut end)) = month_first.or_else(day_first) else {
+            out.push(tokens[i].clone());
+            i += 1;
+            continue;
+        };
+        let separator = tokens[end - 1].suffix.as_str();
+        let year = if separator.is_empty() || separator == "," {
+            year_at(tokens.get(end))
+        } else {
+            None
+        };
+        if year.is_some() {
+            end += 1;
+        }
+        match format_date(month, day, year, format) {
+            Some(date) => {
+                let mut token = Token::new(&tokens[i].prefix, date, &tokens[end - 1].suffix);
+                token.protected = true;
+                out.push(token);
+                i = end;
+            }
+            None => {
+                out.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Numbers written in digits to words, with currency and percentages
+fn to_words_style(tokens: Vec<Token>) -> Vec<Token> {
+    tokens
+        .into_iter()
+        .map(|mut token| {
+            if token.protected {
+                return token;
+            }
+            let Some((value, ordinal)) = parse_digits(&token.core) else {
+                return token;
+            };
+            if value > MAX_SPELLED {
+                return token;
+            }
+            token.core = if ordinal {
+                to_ordinal_words(value)
+            } else {
+                to_words(value)
+            };
+            if let Some(prefix) = token.prefix.strip_suffix('$') {
+                token.prefix = prefix.to_string();
+                token
+                    .core
+                    .push_str(if value == 1 { " dollar" } else { " dollars" });
+            }
+            if let Some(suffix) = token.suffix.strip_prefix('%') {
+                token.suffix = suffix.to_string();
+                token.core.push_str(" percent");
+            }
+            token
+        })
+        .collect()
+}
+
+/// Rewrite numbers and dates in `text` as `formatting` asks
+pub fn normalize_numbers(text: &str, formatting: &NumberFormatting) -> String {
+    if formatting.style == NumberStyle::AsTranscribed
+        && formatting.date_format == DateFormat::AsTranscribed
+    {
+        return text.to_string();
+    }
+
+    let mut tokens = tokenize(text);
+    if formatting.style == NumberStyle::Digits {
+        tokens = to_digits(tokens, formatting.thousands_separator);
+    }
+    if formatting.date_format != DateFormat::AsTranscribed {
+        tokens = format_dates(tokens, formatting.date_format);
+    }
+    if formatting.style == NumberStyle::Words {
+        tokens = to_words_style(tokens);
+    }
+    render(&tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formatting(style: NumberStyle, date_format: DateFormat) -> NumberFormatting {
+        NumberFormatting {
+            style,
+            thousands_separator: ThousandsSeparator::Comma,
+            date_format,
+        }
+    }
+
+    #[test]
+    fn test_spoken_numbers_to_digits() {
+        let digits = formatting(NumberStyle::Digits, DateFormat::AsTranscribed);
+        assert_eq!(
+            normalize_numbers("It costs twenty five dollars.", &digits),
+            "It costs $25."
+        );
+        assert_eq!(
+            normalize_numbers("one hundred and five people, two of them late", &digits),
+            "105 people, two of them late"
+        );
+        assert_eq!(
+            normalize_numbers("about fifty thousand users or 12 percent", &digits),
+            "about 50,000 users or 12%"
+        );
+        assert_eq!(
+            normalize_numbers("three dollars and fifty cents", &digits),
+            "$3.50"
+        );
+        assert_eq!(
+            normalize_numbers("the twenty-first time in 2024", &digits),
+            "the 21st time in 2024"
+        );
+    }
+
+    #[test]
+    fn test_dates() {
+        let iso = formatting(NumberStyle::AsTranscribed, DateFormat::Iso);
+        assert_eq!(
+            normalize_numbers("due March 5th, 2024.", &iso),
+            "due 2024-03-05."
+        );
+        let us = formatting(NumberStyle::Digits, DateFormat::Us);
+        assert_eq!(
+            normalize_numbers("on the fifth of March 2024", &us),
+            "on 3/5/2024"
+        );
+        let long = formatting(NumberStyle::Words, DateFormat::Long);
+        assert_eq!(
+            normalize_numbers("By march 5 we had 3 cats", &long),
+            "By March 5 we had three cats"
+        );
+        assert_eq!(
+            normalize_numbers("you may one day go", &long),
+            "you may one day go"
+        );
+    }
+
+    #[test]
+    fn test_digits_to_words() {
+        let words = formatting(NumberStyle::Words, DateFormat::AsTranscribed);
+        assert_eq!(
+            normalize_numbers("I paid $1,205 for the 2nd one, 40% off!", &words),
+            "I paid one thousand two hundred five dollars for the second one, forty percent off!"
+        );
+        assert_eq!(to_ordinal_words(21), "twenty-first");
+        assert_eq!(to_ordinal_words(12), "twelfth");
+    }
+}
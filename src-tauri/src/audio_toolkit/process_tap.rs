@@ -0,0 +1,425 @@
+// System audio capture through a CoreAudio process tap (macOS 14.4+)
+// A global tap hears everything the system plays, except Handy itself, with
+// neither BlackHole nor Screen Recording permission. The tap is wrapped in a
+// private aggregate device, whose IO callback delivers the tapped audio.
+// The tap APIs are looked up at runtime so older systems still launch.
+
+use crate::audio_toolkit::audio::Downmix;
+use crate::audio_toolkit::coreaudio::{
+    address, check, fourcc, get_property, get_string, AudioHardwareCreateAggregateDevice,
+    AudioHardwareDestroyAggregateDevice, AudioObjectGetPropertyData, AudioObjectId, CfOwned,
+    OsStatus, DEVICE_UID, HARDWARE_DEFAULT_OUTPUT, SCOPE_GLOBAL, SYSTEM_OBJECT, UNKNOWN_OBJECT,
+};
+use crate::audio_toolkit::error::{AudioError, Result};
+use crate::audio_toolkit::ring_buffer::SampleRingBuffer;
+use crate::audio_toolkit::sample_queue::{self, SampleProducer};
+use crate::audio_toolkit::system_audio::CaptureFormat;
+use crate::audio_toolkit::timed;
+use core_foundation_sys::dictionary::CFDictionaryRef;
+use objc::runtime::{Class, Object, YES};
+use objc::{msg_send, sel, sel_impl};
+use std::ffi::CString;
+use std::os::raw::{c_char, c_void};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// First macOS release with `AudioHardwareCreateProcessTap`
+pub const MIN_MACOS_VERSION: (u32, u32) = (14, 4);
+
+const HARDWARE_TRANSLATE_PID: u32 = fourcc(b"id2p");
+const TAP_UID: u32 = fourcc(b"tuid");
+const TAP_FORMAT: u32 = fourcc(b"tfmt");
+const FORMAT_LINEAR_PCM: u32 = fourcc(b"lpcm");
+const FORMAT_FLAG_IS_FLOAT: u32 = 1;
+const FORMAT_FLAG_NON_INTERLEAVED: u32 = 1 << 5;
+// CATapMuteBehavior: the tapped audio still plays
+const TAP_UNMUTED: isize = 0;
+
+// AudioStreamBasicDescription
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct StreamDescription {
+    sample_rate: f64,
+    format_id: u32,
+    format_flags: u32,
+    bytes_per_packet: u32,
+    frames_per_packet: u32,
+    bytes_per_frame: u32,
+    channels_per_frame: u32,
+    bits_per_channel: u32,
+    reserved: u32,
+}
+
+#[repr(C)]
+struct AudioBuffer {
+    channels: u32,
+    byte_size: u32,
+    data: *mut c_void,
+}
+
+#[repr(C)]
+struct AudioBufferList {
+    count: u32,
+    buffers: [AudioBuffer; 1],
+}
+
+// AudioTimeStamp
+#[repr(C)]
+#[allow(dead_code)]
+struct AudioTimeStamp {
+    sample_time: f64,
+    host_time: u64,
+    rate_scalar: f64,
+    word_clock_time: u64,
+    smpte_time: [u32; 6],
+    flags: u32,
+    reserved: u32,
+}
+
+const TIMESTAMP_HOST_TIME_VALID: u32 = 1 << 1;
+
+#[repr(C)]
+#[derive(Default)]
+struct TimebaseInfo {
+    numer: u32,
+    denom: u32,
+}
+
+type IoProc = unsafe extern "C" fn(
+    device: AudioObjectId,
+    now: *const c_void,
+    input: *const AudioBufferList,
+    input_time: *const AudioTimeStamp,
+    output: *mut AudioBufferList,
+    output_time: *const c_void,
+    context: *mut c_void,
+) -> OsStatus;
+type IoProcId = *mut c_void;
+
+type CreateProcessTap = unsafe extern "C" fn(*mut Object, *mut AudioObjectId) -> OsStatus;
+type DestroyProcessTap = unsafe extern "C" fn(AudioObjectId) -> OsStatus;
+
+#[link(name = "CoreAudio", kind = "framework")]
+extern "C" {
+    fn AudioDeviceCreateIOProcID(
+        device: AudioObjectId,
+        proc_: IoProc,
+        context: *mut c_void,
+        proc_id: *mut IoProcId,
+    ) -> OsStatus;
+    fn AudioDeviceDestroyIOProcID(device: AudioObjectId, proc_id: IoProcId) -> OsStatus;
+    fn AudioDeviceStart(device: AudioObjectId, proc_id: IoProcId) -> OsStatus;
+    fn AudioDeviceStop(device: AudioObjectId, proc_id: IoProcId) -> OsStatus;
+}
+
+extern "C" {
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    fn mach_absolute_time() -> u64;
+    fn mach_timebase_info(info: *mut TimebaseInfo) -> i32;
+}
+
+// Searches every image loaded into the process
+const RTLD_DEFAULT: *mut c_void = -2isize as *mut c_void;
+
+fn symbol(name: &str) -> Option<*mut c_void> {
+    let name = CString::new(name).ok()?;
+    let pointer = unsafe { dlsym(RTLD_DEFAULT, name.as_ptr()) };
+    (!pointer.is_null()).then_some(pointer)
+}
+
+fn create_process_tap() -> Option<CreateProcessTap> {
+    symbol("AudioHardwareCreateProcessTap")
+        .map(|p| unsafe { std::mem::transmute::<*mut c_void, CreateProcessTap>(p) })
+}
+
+fn destroy_process_tap() -> Option<DestroyProcessTap> {
+    symbol("AudioHardwareDestroyProcessTap")
+        .map(|p| unsafe { std::mem::transmute::<*mut c_void, DestroyProcessTap>(p) })
+}
+
+/// Whether `version` (major, minor) has process taps
+fn version_supports_taps(version: Option<(u32, u32)>) -> bool {
+    version.is_some_and(|version| version >= MIN_MACOS_VERSION)
+}
+
+/// Whether this system can capture through a process tap
+pub fn is_supported() -> bool {
+    version_supports_taps(crate::audio_toolkit::screencapturekit::get_macos_version())
+        && create_process_tap().is_some()
+        && Class::get("CATapDescription").is_some()
+}
+
+// Handy's own audio object, so its sounds stay out of the capture
+fn own_process_object() -> Option<AudioObjectId> {
+    let pid = std::process::id() as i32;
+    let mut object = UNKNOWN_OBJECT;
+    let mut size = std::mem::size_of::<AudioObjectId>() as u32;
+    let status = unsafe {
+        AudioObjectGetPropertyData(
+            SYSTEM_OBJECT,
+            &address(HARDWARE_TRANSLATE_PID, SCOPE_GLOBAL),
+            std::mem::size_of::<i32>() as u32,
+            &pid as *const i32 as *const c_void,
+            &mut size,
+            &mut object as *mut AudioObjectId as *mut c_void,
+        )
+    };
+    (status == 0 && object != UNKNOWN_OBJECT).then_some(object)
+}
+
+// A private stereo tap of every process but Handy
+fn create_tap() -> Result<AudioObjectId> {
+    let create = create_process_tap()
+        .ok_or_else(|| AudioError::Backend("process taps need macOS 14.4".to_string()))?;
+    let class = Class::get("CATapDescription")
+        .ok_or_else(|| AudioError::Backend("CATapDescription is unavailable".to_string()))?;
+
+    let excluded: Vec<CfOwned> = own_process_object()
+        .map(|object| CfOwned::number(object as i32))
+        .into_iter()
+        .collect();
+    let excluded = CfOwned::array(&excluded.iter().collect::<Vec<_>>());
+
+    let mut tap = UNKNOWN_OBJECT;
+    let status = unsafe {
+        let description: *mut Object = msg_send![class, alloc];
+        let description: *mut Object = msg_send![description,
+            initStereoGlobalTapButExcludeProcesses: excluded.0 as *mut Object];
+        if description.is_null() {
+            return Err(AudioError::Backend(
+                "could not describe the process tap".to_string(),
+            ));
+        }
+        let _: () = msg_send![description, setPrivateTap: YES];
+        let _: () = msg_send![description, setMuteBehavior: TAP_UNMUTED];
+        let status = create(description, &mut tap);
+        let _: () = msg_send![description, release];
+        status
+    };
+    check(status, "create the process tap")?;
+    Ok(tap)
+}
+
+// A private aggregate device around `tap`, clocked by the current output
+fn create_aggregate(tap: AudioObjectId) -> Result<AudioObjectId> {
+    let tap_uid = get_string(tap, TAP_UID)
+        .ok_or_else(|| AudioError::Backend("the process tap has no UID".to_string()))?;
+    let output: AudioObjectId = get_property(SYSTEM_OBJECT, HARDWARE_DEFAULT_OUTPUT)?;
+    let output_uid = get_string(output, DEVICE_UID)
+        .ok_or_else(|| AudioError::DeviceNotFound("no default output device".to_string()))?;
+
+    let on = CfOwned::number(1);
+    let tap_entry = CfOwned::string(&tap_uid);
+    let output_entry = CfOwned::string(&output_uid);
+    let sub_tap = CfOwned::dictionary(&[("uid", &tap_entry), ("drift", &on)]);
+    let taps = CfOwned::array(&[&sub_tap]);
+    let output_sub = CfOwned::dictionary(&[("uid", &output_entry)]);
+    let subdevices = CfOwned::array(&[&output_sub]);
+    let uid = CfOwned::string(&format!("com.handy.process-tap.{}", tap_uid));
+    let name = CfOwned::string("Handy System Audio Tap");
+    let description = CfOwned::dictionary(&[
+        ("uid", &uid),
+        ("name", &name),
+        ("master", &output_entry),
+        ("subdevices", &subdevices),
+        ("taps", &taps),
+        ("tapautostart", &on),
+        // Hidden from Sound settings and gone when Handy quits
+        ("private", &on),
+    ]);
+
+    let mut device = UNKNOWN_OBJECT;
+    let status = unsafe {
+        AudioHardwareCreateAggregateDevice(description.0 as CFDictionaryRef, &mut device)
+    };
+    check(status, "create the process tap device")?;
+    Ok(device)
+}
+
+struct TapContext {
+    samples: SampleProducer,
+    downmix: Downmix,
+    // One frame gathered from non-interleaved buffers
+    frame: Vec<f32>,
+}
+
+unsafe fn plane(buffer: &AudioBuffer) -> &[f32] {
+    if buffer.data.is_null() {
+        return &[];
+    }
+    std::slice::from_raw_parts(
+        buffer.data as *const f32,
+        buffer.byte_size as usize / std::mem::size_of::<f32>(),
+    )
+}
+
+// When the input stamped `input_time` was captured, from its host time
+unsafe fn capture_time(input_time: *const AudioTimeStamp) -> Instant {
+    let mut timebase = TimebaseInfo::default();
+    if input_time.is_null()
+        || (*input_time).flags & TIMESTAMP_HOST_TIME_VALID == 0
+        || mach_timebase_info(&mut timebase) != 0
+        || timebase.denom == 0
+    {
+        return Instant::now();
+    }
+    let ticks = mach_absolute_time().saturating_sub((*input_time).host_time);
+    let nanos = ticks as u128 * timebase.numer as u128 / timebase.denom as u128;
+    timed::captured_before(Duration::from_nanos(nanos as u64))
+}
+
+unsafe extern "C" fn tap_io_proc(
+    _device: AudioObjectId,
+    _now: *const c_void,
+    input: *const AudioBufferList,
+    input_time: *const AudioTimeStamp,
+    _output: *mut AudioBufferList,
+    _output_time: *const c_void,
+    context: *mut c_void,
+) -> OsStatus {
+    if input.is_null() || context.is_null() {
+        return 0;
+    }
+    let context = &mut *(context as *mut TapContext);
+    let buffers = std::slice::from_raw_parts((*input).buffers.as_ptr(), (*input).count as usize);
+    context.samples.stamp(capture_time(input_time));
+
+    match buffers {
+        [] => {}
+        // Interleaved: every buffer is its own set of frames
+        [buffer] => {
+            let channels = buffer.channels.max(1) as usize;
+            let downmix = context.downmix;
+            context.samples.push_iter(
+                plane(buffer)
+                    .chunks_exact(channels)
+                    .map(|frame| downmix.mix(frame)),
+            );
+        }
+        // Non-interleaved: one buffer per channel
+        planes => {
+            let len = planes.iter().map(|p| plane(p).len()).min().unwrap_or(0);
+            let TapContext {
+                samples,
+                downmix,
+                frame,
+            } = context;
+            for i in 0..len {
+                frame.clear();
+                frame.extend(planes.iter().map(|p| plane(p)[i]));
+                samples.push_iter(std::iter::once(downmix.mix(frame.as_slice())));
+            }
+        }
+    }
+    0
+}
+
+/// A running process tap feeding a sample buffer; stopped and removed on drop
+pub struct ProcessTap {
+    tap: AudioObjectId,
+    device: AudioObjectId,
+    proc_id: IoProcId,
+    context: *mut TapContext,
+}
+
+// The raw pointers are only touched by CoreAudio and on drop
+unsafe impl Send for ProcessTap {}
+
+impl ProcessTap {
+    /// Tap all system audio and push it, reduced to mono with `downmix`,
+    /// into `buffer`
+    pub fn start(buffer: &Arc<Mutex<SampleRingBuffer>>, downmix: Downmix) -> Result<Self> {
+        let tap = create_tap()?;
+        let mut this = Self {
+            tap,
+            device: UNKNOWN_OBJECT,
+            proc_id: std::ptr::null_mut(),
+            context: std::ptr::null_mut(),
+        };
+
+        let format: StreamDescription = get_property(tap, TAP_FORMAT)?;
+        if format.format_id != FORMAT_LINEAR_PCM
+            || format.format_flags & FORMAT_FLAG_IS_FLOAT == 0
+            || format.bits_per_channel != 32
+        {
+            return Err(AudioError::UnsupportedFormat(format!(
+                "process tap delivers format {:#x} with flags {:#x}",
+                format.format_id, format.format_flags
+            )));
+        }
+        log::info!(
+            "🎯 [ProcessTap] Tap format: {}Hz, {} channels, {}",
+            format.sample_rate,
+            format.channels_per_frame,
+            if format.format_flags & FORMAT_FLAG_NON_INTERLEAVED != 0 {
+                "non-interleaved"
+            } else {
+                "interleaved"
+            }
+        );
+        buffer.lock().unwrap().set_format(CaptureFormat {
+            sample_rate: format.sample_rate as u32,
+            channels: format.channels_per_frame as u16,
+        });
+
+        this.device = create_aggregate(tap)?;
+
+        this.context = Box::into_raw(Box::new(TapContext {
+            samples: sample_queue::feed(buffer),
+            downmix,
+            frame: Vec::with_capacity(format.channels_per_frame as usize),
+        }));
+        let status = unsafe {
+            AudioDeviceCreateIOProcID(
+                this.device,
+                tap_io_proc,
+                this.context as *mut c_void,
+                &mut this.proc_id,
+            )
+        };
+        check(status, "register the process tap callback")?;
+        let status = unsafe { AudioDeviceStart(this.device, this.proc_id) };
+        check(status, "start the process tap")?;
+
+        log::info!("✅ [ProcessTap] Capturing system audio through a process tap");
+        Ok(this)
+    }
+}
+
+impl Drop for ProcessTap {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.proc_id.is_null() {
+                AudioDeviceStop(self.device, self.proc_id);
+                AudioDeviceDestroyIOProcID(self.device, self.proc_id);
+            }
+            if self.device != UNKNOWN_OBJECT {
+                AudioHardwareDestroyAggregateDevice(self.device);
+            }
+            if let Some(destroy) = destroy_process_tap() {
+                destroy(self.tap);
+            }
+            // Only after the callback is gone; dropping the producer lets the
+            // feed thread finish
+            if !self.context.is_null() {
+                drop(Box::from_raw(self.context));
+            }
+        }
+        log::info!("🛑 [ProcessTap] Process tap removed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_gate() {
+        assert!(!version_supports_taps(None));
+        assert!(!version_supports_taps(Some((13, 6))));
+        assert!(!version_supports_taps(Some((14, 3))));
+        assert!(version_supports_taps(Some((14, 4))));
+        assert!(version_supports_taps(Some((15, 0))));
+    }
+}
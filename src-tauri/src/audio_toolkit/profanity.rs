@@ -0,0 +1,125 @@
+//! Profanity masking for pasted text and live captions.
+
+use crate::settings::ProfanityFilter;
+
+/// Built-in blocklist; users add their own words in settings
+const BLOCKLIST: &[&str] = &[
+    "asshole",
+    "bastard",
+    "bitch",
+    "bullshit",
+    "cock",
+    "cunt",
+    "damn",
+    "dick",
+    "fuck",
+    "motherfucker",
+    "piss",
+    "prick",
+    "pussy",
+    "shit",
+    "slut",
+    "twat",
+    "whore",
+];
+
+/// Endings a blocked word may carry and still be matched, e.g. "fucking"
+const ENDINGS: &[&str] = &["", "s", "es", "ed", "er", "ers", "ing", "in", "y", "ty"];
+
+fn is_blocked(word: &str, extra: &[String]) -> bool {
+    let lower = word.to_lowercase();
+    BLOCKLIST
+        .iter()
+        .copied()
+        .chain(extra.iter().map(String::as_str))
+        .filter(|blocked| !blocked.is_empty())
+        .any(|blocked| {
+            let blocked = blocked.to_lowercase();
+            lower
+                .strip_prefix(&blocked)
+                .is_some_and(|rest| ENDINGS.contains(&rest))
+        })
+}
+
+/// First letter followed by asterisks, e.g. "s***"
+fn mask(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => std::iter::once(first).chain(chars.map(|_| '*')).collect(),
+        None => String::new(),
+    }
+}
+
+/// Mask or remove blocked words in `text`, keeping the punctuation around them
+pub fn filter_profanity(text: &str, filter: ProfanityFilter, extra: &[String]) -> String {
+    if filter == ProfanityFilter::Keep {
+        return text.to_string();
+    }
+
+    let mut out: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        let start = word
+            .find(|c: char| c.is_alphanumeric())
+            .unwrap_or(word.len());
+        let end = word
+            .rfind(|c: char| c.is_alphanumeric())
+            .map_or(start, |i| {
+                i + word[i..].chars().next().map_or(1, char::len_utf8)
+            });
+        let core = &word[start..end];
+        if core.is_empty() || !is_blocked(core, extra) {
+            out.push(word.to_string());
+            continue;
+        }
+        let (prefix, suffix) = (&word[..start], &word[end..]);
+        match filter {
+            ProfanityFilter::Mask => out.push(format!("{}{}{}", prefix, mask(core), suffix)),
+            // Move trailing punctuation onto the previous word so the clause
+            // still ends where it did
+            ProfanityFilter::Remove => {
+                let previous = out
+                    .last_mut()
+                    .filter(|previous| previous.ends_with(char::is_alphanumeric));
+                if let Some(previous) = previous {
+                    previous.push_str(suffix);
+                }
+            }
+            ProfanityFilter::Keep => out.push(word.to_string()),
+        }
+    }
+    out.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_keeps_first_letter_and_punctuation() {
+        assert_eq!(
+            filter_profanity("Oh shit, that's fucking great!", ProfanityFilter::Mask, &[]),
+            "Oh s***, that's f****** great!"
+        );
+        assert_eq!(
+            filter_profanity("a shiitake and a cocktail", ProfanityFilter::Mask, &[]),
+            "a shiitake and a cocktail"
+        );
+    }
+
+    #[test]
+    fn test_remove_and_custom_words() {
+        let extra = vec!["frick".to_string()];
+        assert_eq!(
+            filter_profanity(
+                "well frick, it broke. Damn.",
+                ProfanityFilter::Remove,
+                &extra
+            ),
+            "well, it broke."
+        );
+        assert_eq!(
+            filter_profanity("well frick", ProfanityFilter::Keep, &extra),
+            "well frick"
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Punctuation and truecasing for models that emit bare lowercase text.
+//!
+//! The model directory holds `model.onnx` and `config.json`. The model takes
+//! word ids as `input_ids` (int64, `[1, words]`) and returns, per word,
+//! `punct_logits` over `config.punctuation` (the mark to put after the word)
+//! and `case_logits` over `config.casing`.
+
+use anyhow::{anyhow, Context, Result};
+use ort::session::builder::GraphOptimizationLevel;
+use ort::session::Session;
+use ort::value::Tensor;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub const MODEL_FILE: &str = "model.onnx";
+pub const CONFIG_FILE: &str = "config.json";
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WordCase {
+    Lower,
+    /// First letter uppercase
+    Capitalize,
+    Upper,
+}
+
+#[derive(Deserialize, Debug)]
+struct PunctuationConfig {
+    vocab: Vec<String>,
+    unk_id: i64,
+    /// Most words the model takes in one run
+    max_words: usize,
+    /// Mark appended after a word for each punctuation label; "" for none
+    punctuation: Vec<String>,
+    casing: Vec<WordCase>,
+}
+
+pub struct PunctuationModel {
+    dir: PathBuf,
+    session: Session,
+    config: PunctuationConfig,
+    ids: HashMap<String, i64>,
+}
+
+/// Whether `text` looks like it came from a model without punctuation:
+/// no sentence marks and no capitals
+pub fn needs_punctuation(text: &str) -> bool {
+    text.chars().any(char::is_alphabetic)
+        && !text
+            .chars()
+            .any(|c| c.is_uppercase() || matches!(c, '.' | '?' | '!' | ','))
+}
+
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+fn argmax(logits: &[f32]) -> usize {
+    logits
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map_or(0, |(i, _)| i)
+}
+
+fn apply_case(word: &str, case: WordCase) -> String {
+    match case {
+        WordCase::Lower => word.to_string(),
+        WordCase::Upper => word.to_uppercase(),
+        WordCase::Capitalize => {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }
+    }
+}
+
+/// Rebuild the text from per-word labels
+fn apply_labels(
+    words: &[&str],
+    punct: &[usize],
+    case: &[usize],
+    config: &PunctuationConfig,
+) -> String {
+    words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            let case = case
+                .get(i)
+                .and_then(|&label| config.casing.get(label))
+                .copied()
+                .unwrap_or(WordCase::Lower);
+            let mark = punct
+                .get(i)
+                .and_then(|&label| config.punctuation.get(label))
+                .map_or("", String::as_str);
+            format!("{}{}", apply_case(word, case), mark)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl PunctuationModel {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let config: PunctuationConfig = serde_json::from_str(
+            &std::fs::read_to_string(dir.join(CONFIG_FILE))
+                .with_context(|| format!("reading {}", CONFIG_FILE))?,
+        )?;
+        if config.max_words == 0 || config.punctuation.is_empty() || config.casing.is_empty() {
+            return Err(anyhow!("{} has no labels or word limit", CONFIG_FILE));
+        }
+        let session = Session::builder()?
+            .with_optimization_level(GraphOptimizationLevel::Level3)?
+            .with_intra_threads(1)?
+            .commit_from_file(dir.join(MODEL_FILE))?;
+        let ids = config
+            .vocab
+            .iter()
+            .enumerate()
+            .map(|(id, word)| (word.clone(), id as i64))
+            .collect();
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            session,
+            config,
+            ids,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Punctuate and case `text`, run over chunks of at most `max_words`
+    pub fn restore(&mut self, text: &str) -> Result<String> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut restored = Vec::new();
+        for chunk in words.chunks(self.config.max_words) {
+            let (punct, case) = self.label(chunk)?;
+            restored.push(apply_labels(chunk, &punct, &case, &self.config));
+        }
+        Ok(restored.join(" "))
+    }
+
+    /// Punctuation and case labels for each word
+    fn label(&mut self, words: &[&str]) -> Result<(Vec<usize>, Vec<usize>)> {
+        let ids: Vec<i64> = words
+            .iter()
+            .map(|word| {
+                self.ids
+                    .get(&normalize(word))
+                    .copied()
+                    .unwrap_or(self.config.unk_id)
+            })
+            .collect();
+        let input = Tensor::from_array(([1usize, ids.len()], ids))?;
+        let outputs = self.session.run(ort::inputs!["input_ids" => input])?;
+
+        let labels = |name: &str, classes: usize| -> Result<Vec<usize>> {
+            let (_, logits) = outputs[name].try_extract_tensor::<f32>()?;
+            if logits.len() != words.len() * classes {
+                return Err(anyhow!("unexpected {} shape", name));
+            }
+            Ok(logits.chunks(classes).map(argmax).collect())
+        };
+        Ok((
+            labels("punct_logits", self.config.punctuation.len())?,
+            labels("case_logits", self.config.casing.len())?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_punctuation() {
+        assert!(needs_punctuation("hello there how are you"));
+        assert!(!needs_punctuation("Hello there. How are you?"));
+        assert!(!needs_punctuation("hello there, friend"));
+        assert!(!needs_punctuation("123 456"));
+    }
+
+    #[test]
+    fn test_apply_labels() {
+        let config = PunctuationConfig {
+            vocab: Vec::new(),
+            unk_id: 0,
+            max_words: 64,
+            punctuation: vec!["".into(), ",".into(), ".".into(), "?".into()],
+            casing: vec![WordCase::Lower, WordCase::Capitalize, WordCase::Upper],
+        };
+        let words = ["hi", "sam", "is", "the", "api", "up"];
+        let punct = [1, 2, 0, 0, 0, 3];
+        let case = [1, 1, 1, 0, 2, 0];
+        assert_eq!(
+            apply_labels(&words, &punct, &case, &config),
+            "Hi, Sam. Is the API up?"
+        );
+    }
+}
@@ -0,0 +1,489 @@
+// Bounded sample storage for system audio capture
+// Overwrites the oldest samples once full so a stalled consumer can't grow memory forever.
+// A consumer can instead take delivery of every chunk on a channel and block on it.
+// Chunks keep their capture stamps, so readers can tell where audio went missing.
+
+use crate::audio_toolkit::error::{AudioError, Result};
+use crate::audio_toolkit::stream::{AudioFrameStream, FrameBroadcaster};
+use crate::audio_toolkit::system_audio::CaptureFormat;
+use crate::audio_toolkit::timed::{self, TimedSamples};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Sample rate assumed for system audio until the backend reports its own
+pub const SYSTEM_AUDIO_SAMPLE_RATE: usize = 48000;
+
+/// Default buffer length when no explicit limit is configured
+pub const DEFAULT_MAX_BUFFER_SECONDS: u32 = 30;
+
+/// Fixed-capacity FIFO of mono samples with an overwrite-oldest policy.
+/// Chunks appended with `extend` are also published to frame subscribers.
+pub struct SampleRingBuffer {
+    samples: VecDeque<f32>,
+    capacity: usize,
+    dropped: u64,
+    format: CaptureFormat,
+    frames: FrameBroadcaster,
+    last_write: Option<Instant>,
+    delivery: Option<Delivery>,
+    // Samples appended since creation; the front of `samples` is number
+    // `written - samples.len()`
+    written: u64,
+    // Capture time of the sample at each position where a chunk began. The
+    // oldest is at or before the front, so every buffered sample has one.
+    marks: VecDeque<(u64, Instant)>,
+    // Where the audio appended last would have carried on
+    next_at: Option<Instant>,
+    // Capture time of the first chunk appended since the rate last changed,
+    // and whether that chunk is still to come
+    format_since: Option<Instant>,
+    format_changed: bool,
+}
+
+// Sending end of `deliver`. `queued` counts samples sent but not yet
+// received, so delivery is held to the same capacity as the buffer.
+struct Delivery {
+    tx: Sender<TimedSamples>,
+    queued: Arc<AtomicUsize>,
+}
+
+/// Receiving end of `SampleRingBuffer::deliver`
+pub struct SampleReceiver {
+    rx: Receiver<TimedSamples>,
+    queued: Arc<AtomicUsize>,
+}
+
+// When a chunk appended without a capture time is taken to be captured
+enum Stamp {
+    At(Instant),
+    // Just arrived: captured over the chunk's length up to now
+    Arrived,
+    // Follows straight on from the previous chunk
+    Continues,
+}
+
+impl SampleReceiver {
+    /// Block until at least `min_samples` have arrived or `timeout` passes,
+    /// and return what arrived, which may be nothing. Fails once the capture
+    /// is gone and everything it sent has been received.
+    pub fn recv(&self, min_samples: usize, timeout: Duration) -> Result<TimedSamples> {
+        let deadline = Instant::now() + timeout;
+        let mut samples = TimedSamples::new();
+        while samples.len() < min_samples {
+            match self.rx.recv_deadline(deadline) {
+                Ok(chunk) => samples.append(chunk),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) if samples.is_empty() => {
+                    return Err(AudioError::StreamClosed)
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        self.queued.fetch_sub(samples.len(), Ordering::Relaxed);
+        Ok(samples)
+    }
+}
+
+impl SampleRingBuffer {
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            dropped: 0,
+            format: CaptureFormat::default(),
+            frames: FrameBroadcaster::new(SYSTEM_AUDIO_SAMPLE_RATE as u32),
+            last_write: None,
+            delivery: None,
+            written: 0,
+            marks: VecDeque::new(),
+            next_at: None,
+            format_since: None,
+            format_changed: false,
+        }
+    }
+
+    /// Create a buffer holding `seconds` of audio at `sample_rate`
+    pub fn with_seconds(seconds: u32, sample_rate: usize) -> Self {
+        Self::with_capacity(seconds as usize * sample_rate)
+    }
+
+    /// Create a buffer holding `seconds` of audio in `format`
+    pub fn with_format(seconds: u32, format: CaptureFormat) -> Self {
+        let mut buffer = Self::with_seconds(seconds, format.sample_rate as usize);
+        buffer.format = format;
+        buffer.frames.set_sample_rate(format.sample_rate);
+        buffer
+    }
+
+    pub fn push_back(&mut self, sample: f32) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+            self.dropped += 1;
+        }
+        self.samples.push_back(sample);
+        self.written += 1;
+    }
+
+    /// Append a chunk that has just arrived from the backend
+    pub fn extend<I: IntoIterator<Item = f32>>(&mut self, iter: I) {
+        self.append(iter, Stamp::Arrived);
+    }
+
+    /// Append a chunk whose first sample was captured at `captured_at`
+    pub fn extend_at<I: IntoIterator<Item = f32>>(&mut self, iter: I, captured_at: Instant) {
+        self.append(iter, Stamp::At(captured_at));
+    }
+
+    /// Append stamped samples. Any before the first mark follow on from the
+    /// audio appended last. Returns the longest stretch of audio missing
+    /// between chunks, judged by their stamps.
+    pub fn extend_timed(&mut self, timed: TimedSamples) -> Option<Duration> {
+        let mut missing = None;
+        for (chunk, at) in timed.chunks() {
+            if let (Some(at), Some(next)) = (at, self.next_at) {
+                missing = missing.max(timed::skipped(next, at));
+            }
+            let stamp = at.map_or(Stamp::Continues, Stamp::At);
+            self.append(chunk.iter().copied(), stamp);
+        }
+        missing
+    }
+
+    fn append<I: IntoIterator<Item = f32>>(&mut self, iter: I, stamp: Stamp) {
+        self.last_write = Some(Instant::now());
+        let start = self.written;
+        if self.delivery.is_none() && !self.frames.has_subscribers() {
+            for sample in iter {
+                self.push_back(sample);
+            }
+            let len = (self.written - start) as usize;
+            if len > 0 {
+                let at = self.resolve(stamp, len);
+                self.mark(start, at);
+            }
+            return;
+        }
+
+        let chunk: Vec<f32> = iter.into_iter().collect();
+        if chunk.is_empty() {
+            return;
+        }
+        let at = self.resolve(stamp, chunk.len());
+        if self.frames.has_subscribers() {
+            self.frames.publish(&chunk, at);
+        }
+        let Some(returned) = self.deliver_chunk(TimedSamples::from_chunk(chunk, at)) else {
+            return;
+        };
+        for &sample in &returned.samples {
+            self.push_back(sample);
+        }
+        self.mark(start, at);
+    }
+
+    // Capture time of a chunk of `len` samples appended with `stamp`, and
+    // remember where the next one would start
+    fn resolve(&mut self, stamp: Stamp, len: usize) -> Instant {
+        let rate = self.format.sample_rate;
+        let at = match (stamp, self.next_at) {
+            (Stamp::At(at), _) => at,
+            (Stamp::Continues, Some(next)) => next,
+            (Stamp::Arrived | Stamp::Continues, _) => {
+                timed::offset_by(Instant::now(), -(len as f64), rate)
+            }
+        };
+        self.next_at = Some(timed::offset_by(at, len as f64, rate));
+        if self.format_changed {
+            self.format_changed = false;
+            self.format_since = Some(at);
+        }
+        at
+    }
+
+    fn mark(&mut self, position: u64, at: Instant) {
+        self.marks.push_back((position, at));
+        let front = self.written - self.samples.len() as u64;
+        while self.marks.len() > 1 && self.marks[1].0 <= front {
+            self.marks.pop_front();
+        }
+    }
+
+    /// Async stream of every chunk appended from now on
+    pub fn subscribe(&self) -> AudioFrameStream {
+        self.frames.subscribe()
+    }
+
+    /// Send every chunk appended from now on, and the samples buffered so
+    /// far, to the returned receiver instead of keeping them for
+    /// `drain_all`. Buffering resumes once the receiver is dropped.
+    pub fn deliver(&mut self) -> SampleReceiver {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let queued = Arc::new(AtomicUsize::new(0));
+        self.delivery = Some(Delivery {
+            tx,
+            queued: Arc::clone(&queued),
+        });
+        let buffered = self.drain_timed();
+        if !buffered.is_empty() {
+            self.deliver_chunk(buffered);
+        }
+        SampleReceiver { rx, queued }
+    }
+
+    // Hand `chunk` to the receiver, giving it back if there is none. Chunks
+    // that would take the receiver's backlog over capacity are dropped.
+    fn deliver_chunk(&mut self, chunk: TimedSamples) -> Option<TimedSamples> {
+        let delivery = self.delivery.as_ref()?;
+        let len = chunk.len();
+        if delivery.queued.load(Ordering::Relaxed) + len > self.capacity {
+            self.dropped += len as u64;
+            return None;
+        }
+        delivery.queued.fetch_add(len, Ordering::Relaxed);
+        match delivery.tx.send(chunk) {
+            Ok(()) => None,
+            Err(returned) => {
+                self.delivery = None;
+                Some(returned.into_inner())
+            }
+        }
+    }
+
+    /// Take every buffered sample, leaving the buffer empty
+    pub fn drain_all(&mut self) -> Vec<f32> {
+        self.drain_timed().samples
+    }
+
+    /// Take every buffered sample with its capture stamps, leaving the
+    /// buffer empty
+    pub fn drain_timed(&mut self) -> TimedSamples {
+        let front = self.written - self.samples.len() as u64;
+        let rate = self.format.sample_rate;
+        let samples: Vec<f32> = self.samples.drain(..).collect();
+        if samples.is_empty() {
+            return TimedSamples::new();
+        }
+
+        let mut marks = Vec::new();
+        for &(position, at) in &self.marks {
+            match position.checked_sub(front) {
+                Some(offset) => marks.push((offset as usize, at)),
+                // Began before the front, which follows on from it
+                None => {
+                    marks.clear();
+                    marks.push((0, timed::offset_by(at, (front - position) as f64, rate)));
+                }
+            }
+        }
+        self.marks.clear();
+
+        TimedSamples { samples, marks }
+    }
+
+    pub fn iter(&self) -> std::collections::vec_deque::Iter<'_, f32> {
+        self.samples.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Format of the audio the backend is appending
+    pub fn format(&self) -> CaptureFormat {
+        self.format
+    }
+
+    /// Record the format the backend captures in. The capacity is rescaled
+    /// so the buffer still holds the same length of audio.
+    pub fn set_format(&mut self, format: CaptureFormat) {
+        if format.sample_rate != self.format.sample_rate {
+            let seconds = self.capacity as f64 / self.format.sample_rate as f64;
+            self.frames.set_sample_rate(format.sample_rate);
+            self.set_capacity((seconds * format.sample_rate as f64) as usize);
+            self.format_changed = true;
+        }
+        self.format = format;
+    }
+
+    /// Capture time of the first chunk appended at the current rate, if it
+    /// replaced another rate. Audio stamped before it is at the old rate.
+    pub fn format_since(&self) -> Option<Instant> {
+        self.format_since
+    }
+
+    /// Audio currently buffered or waiting for the delivery receiver
+    pub fn buffered_duration(&self) -> Duration {
+        let queued = self
+            .delivery
+            .as_ref()
+            .map_or(0, |d| d.queued.load(Ordering::Relaxed));
+        Duration::from_secs_f64(
+            (self.samples.len() + queued) as f64 / self.format.sample_rate as f64,
+        )
+    }
+
+    /// Time since the backend last appended audio, if it ever has
+    pub fn last_write_age(&self) -> Option<Duration> {
+        self.last_write.map(|at| at.elapsed())
+    }
+
+    /// Total number of samples overwritten since creation
+    pub fn dropped_samples(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Count samples lost before they reached the buffer
+    pub fn record_dropped(&mut self, count: u64) {
+        self.dropped += count;
+    }
+
+    /// Change the capacity, discarding the oldest samples if needed
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.samples.len() > self.capacity {
+            self.samples.pop_front();
+            self.dropped += 1;
+        }
+    }
+
+    /// Clear buffered samples; the drop counter is preserved
+    /// Drop the buffered audio and hang up on the delivery receiver, if any
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.marks.clear();
+        self.next_at = None;
+        self.delivery = None;
+    }
+}
+
+impl Default for SampleRingBuffer {
+    fn default() -> Self {
+        Self::with_seconds(DEFAULT_MAX_BUFFER_SECONDS, SYSTEM_AUDIO_SAMPLE_RATE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overwrites_oldest_when_full() {
+        let mut buf = SampleRingBuffer::with_capacity(3);
+        buf.extend([1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        assert_eq!(buf.len(), 3);
+        assert_eq!(buf.dropped_samples(), 2);
+        assert_eq!(buf.drain_all(), vec![3.0, 4.0, 5.0]);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_shrinking_capacity_counts_drops() {
+        let mut buf = SampleRingBuffer::with_capacity(4);
+        buf.extend([1.0, 2.0, 3.0, 4.0]);
+        buf.set_capacity(2);
+
+        assert_eq!(buf.dropped_samples(), 2);
+        assert_eq!(buf.iter().copied().collect::<Vec<_>>(), vec![3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_status_reads_leave_samples_buffered() {
+        let mut buf = SampleRingBuffer::with_capacity(SYSTEM_AUDIO_SAMPLE_RATE);
+        assert!(buf.last_write_age().is_none());
+
+        buf.extend(vec![0.0; SYSTEM_AUDIO_SAMPLE_RATE / 2]);
+        assert_eq!(buf.buffered_duration(), Duration::from_millis(500));
+        assert!(buf.last_write_age().is_some());
+        assert_eq!(buf.len(), SYSTEM_AUDIO_SAMPLE_RATE / 2);
+    }
+
+    #[test]
+    fn test_format_change_keeps_buffer_length() {
+        let mut buf = SampleRingBuffer::with_seconds(2, SYSTEM_AUDIO_SAMPLE_RATE);
+        buf.set_format(CaptureFormat {
+            sample_rate: 44100,
+            channels: 2,
+        });
+
+        assert_eq!(buf.capacity(), 2 * 44100);
+        assert!(buf.format_since().is_none());
+        let switched = Instant::now();
+        buf.extend_at(vec![0.0; 44100], switched);
+        assert_eq!(buf.buffered_duration(), Duration::from_secs(1));
+
+        // The chunk that followed the switch stays its start
+        buf.extend(vec![0.0; 100]);
+        assert_eq!(buf.format_since(), Some(switched));
+    }
+
+    #[test]
+    fn test_delivery_takes_over_from_buffering() {
+        let mut buf = SampleRingBuffer::with_capacity(4);
+        buf.extend([1.0, 2.0]);
+        let rx = buf.deliver();
+        buf.extend([3.0]);
+        assert!(buf.is_empty());
+
+        // Stops at the deadline with what has arrived
+        let samples = rx.recv(10, Duration::from_millis(10)).unwrap();
+        assert_eq!(samples.samples, vec![1.0, 2.0, 3.0]);
+
+        // Chunks the receiver has no room for are dropped
+        buf.extend([4.0, 5.0, 6.0]);
+        buf.extend([7.0, 8.0]);
+        assert_eq!(buf.dropped_samples(), 2);
+        assert_eq!(
+            rx.recv(1, Duration::ZERO).unwrap().samples,
+            vec![4.0, 5.0, 6.0]
+        );
+
+        // Without a receiver the buffer keeps the audio again
+        drop(rx);
+        buf.extend([9.0]);
+        assert_eq!(buf.drain_all(), vec![9.0]);
+    }
+
+    #[test]
+    fn test_drain_keeps_capture_stamps() {
+        let mut buf = SampleRingBuffer::with_format(
+            1,
+            CaptureFormat {
+                sample_rate: 4,
+                channels: 1,
+            },
+        );
+        let start = Instant::now();
+        buf.extend_at([1.0, 2.0], start);
+        // Two samples (half a second) went missing
+        let resumed = start + Duration::from_secs(1);
+        let missing = buf.extend_timed(TimedSamples::from_chunk(vec![3.0, 4.0, 5.0], resumed));
+        assert_eq!(missing, Some(Duration::from_millis(500)));
+        let missing = buf.extend_timed(TimedSamples {
+            samples: vec![6.0],
+            marks: Vec::new(),
+        });
+        assert_eq!(missing, None);
+
+        // The first two were overwritten; the front follows on from its chunk
+        let timed = buf.drain_timed();
+        assert_eq!(timed.samples, vec![3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(timed.marks[0], (0, resumed));
+        assert_eq!(
+            timed.time_at(3, 4),
+            Some(resumed + Duration::from_millis(750))
+        );
+    }
+}
@@ -0,0 +1,221 @@
+// Lock-free hand-off of samples from audio callbacks
+// Callbacks run on the device's realtime thread, where waiting on a lock held
+// by a consumer can make the stream glitch. They push into a single-producer
+// single-consumer queue instead, and samples that don't fit are counted as
+// overflow rather than waited for. Capture stamps travel in a second queue
+// beside the samples, keyed by the position of the sample they stamp.
+
+use crate::audio_toolkit::glitch;
+use crate::audio_toolkit::ring_buffer::{SampleRingBuffer, SYSTEM_AUDIO_SAMPLE_RATE};
+use crate::audio_toolkit::timed::TimedSamples;
+use crate::metrics::{CaptureStream, Glitch};
+use rtrb::RingBuffer;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// A second of system audio, many times what a callback delivers at once
+const FEED_CAPACITY: usize = SYSTEM_AUDIO_SAMPLE_RATE;
+// Stamps queued at once; one per callback, so far more than fit in a second
+const STAMP_CAPACITY: usize = 1024;
+// How often `feed` moves queued samples into the ring buffer
+const PUMP_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A queue holding up to `capacity` samples
+pub fn sample_queue(capacity: usize) -> (SampleProducer, SampleConsumer) {
+    let (producer, consumer) = RingBuffer::new(capacity);
+    let (stamps_tx, stamps_rx) = RingBuffer::new(STAMP_CAPACITY);
+    let overflow = Arc::new(AtomicU64::new(0));
+    (
+        SampleProducer {
+            producer,
+            stamps: stamps_tx,
+            written: 0,
+            overflow: Arc::clone(&overflow),
+        },
+        SampleConsumer {
+            consumer,
+            stamps: stamps_rx,
+            read: 0,
+            overflow,
+        },
+    )
+}
+
+/// The callback's end of the queue. Pushing never blocks or allocates.
+pub struct SampleProducer {
+    producer: rtrb::Producer<f32>,
+    // Capture time of the sample at each position, by samples queued so far
+    stamps: rtrb::Producer<(u64, Instant)>,
+    written: u64,
+    overflow: Arc<AtomicU64>,
+}
+
+impl SampleProducer {
+    /// Record that the next sample pushed was captured at `captured_at`.
+    /// Callbacks stamp each buffer before pushing it.
+    pub fn stamp(&mut self, captured_at: Instant) {
+        // A full stamp queue only costs precision; the samples still follow on
+        let _ = self.stamps.push((self.written, captured_at));
+    }
+
+    /// Queue as many of `samples` as fit, counting the rest as overflow
+    pub fn push_slice(&mut self, samples: &[f32]) {
+        self.push_iter(samples.iter().copied());
+    }
+
+    pub fn push_iter<I: IntoIterator<Item = f32>>(&mut self, samples: I) {
+        let mut samples = samples.into_iter();
+        if let Ok(chunk) = self.producer.write_chunk_uninit(self.producer.slots()) {
+            self.written += chunk.fill_from_iter(&mut samples) as u64;
+        }
+        let dropped = samples.count();
+        if dropped > 0 {
+            self.overflow.fetch_add(dropped as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Room left before samples start to overflow
+    pub fn free(&self) -> usize {
+        self.producer.slots()
+    }
+}
+
+pub struct SampleConsumer {
+    consumer: rtrb::Consumer<f32>,
+    stamps: rtrb::Consumer<(u64, Instant)>,
+    read: u64,
+    overflow: Arc<AtomicU64>,
+}
+
+impl SampleConsumer {
+    /// Move every queued sample to the end of `out`
+    pub fn pop_into(&mut self, out: &mut Vec<f32>) {
+        if let Ok(chunk) = self.consumer.read_chunk(self.consumer.slots()) {
+            let (first, second) = chunk.as_slices();
+            out.extend_from_slice(first);
+            out.extend_from_slice(second);
+            self.read += chunk.len() as u64;
+            chunk.commit_all();
+        }
+    }
+
+    /// Move every queued sample to the end of `out`, marked with the stamps
+    /// the producer gave them
+    pub fn pop_timed(&mut self, out: &mut TimedSamples) {
+        let base = out.samples.len();
+        let first = self.read;
+        self.pop_into(&mut out.samples);
+        while let Ok(&(position, at)) = self.stamps.peek() {
+            if position >= self.read {
+                // Its samples haven't been queued yet
+                break;
+            }
+            let _ = self.stamps.pop();
+            if let Some(offset) = position.checked_sub(first) {
+                out.marks.push((base + offset as usize, at));
+            }
+        }
+    }
+
+    /// Samples dropped because the queue was full since the last call
+    pub fn take_overflow(&self) -> u64 {
+        self.overflow.swap(0, Ordering::Relaxed)
+    }
+
+    /// Whether the producer is gone, so nothing more will be queued
+    pub fn is_abandoned(&self) -> bool {
+        self.consumer.is_abandoned()
+    }
+}
+
+/// A producer for a callback to fill `buffer` through without locking it. A
+/// pump thread moves the samples over every few milliseconds, adding overflow
+/// to the buffer's dropped samples, and exits once the producer is dropped.
+pub fn feed(buffer: &Arc<Mutex<SampleRingBuffer>>) -> SampleProducer {
+    let (producer, mut consumer) = sample_queue(FEED_CAPACITY);
+    let buffer = Arc::clone(buffer);
+    thread::spawn(move || {
+        loop {
+            // Checked before draining so the last samples still get moved
+            let abandoned = consumer.is_abandoned();
+            let mut samples = TimedSamples::new();
+            consumer.pop_timed(&mut samples);
+            let overflow = consumer.take_overflow();
+            if !samples.is_empty() || overflow > 0 {
+                let mut buffer = buffer.lock().unwrap();
+                buffer.record_dropped(overflow);
+                if overflow > 0 {
+                    glitch::report(CaptureStream::SystemAudio, Glitch::Overrun);
+                }
+                if let Some(gap) = buffer.extend_timed(samples) {
+                    glitch::report_gap(CaptureStream::SystemAudio, gap);
+                }
+            }
+            if abandoned {
+                break;
+            }
+            thread::sleep(PUMP_INTERVAL);
+        }
+    });
+    producer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_full_queue_counts_overflow() {
+        let (mut producer, mut consumer) = sample_queue(4);
+        producer.push_slice(&[1.0, 2.0, 3.0]);
+        producer.push_iter([4.0, 5.0, 6.0]);
+        assert_eq!(producer.free(), 0);
+
+        let mut out = Vec::new();
+        consumer.pop_into(&mut out);
+        assert_eq!(out, vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(consumer.take_overflow(), 2);
+        assert_eq!(consumer.take_overflow(), 0);
+
+        assert!(!consumer.is_abandoned());
+        drop(producer);
+        assert!(consumer.is_abandoned());
+    }
+
+    #[test]
+    fn test_stamps_follow_their_samples() {
+        let (mut producer, mut consumer) = sample_queue(8);
+        let first = Instant::now();
+        let second = first + Duration::from_millis(10);
+        producer.stamp(first);
+        producer.push_slice(&[1.0, 2.0]);
+        producer.stamp(second);
+
+        // The second stamp waits for its samples
+        let mut out = TimedSamples::new();
+        consumer.pop_timed(&mut out);
+        assert_eq!(out.marks, vec![(0, first)]);
+
+        producer.push_slice(&[3.0]);
+        consumer.pop_timed(&mut out);
+        assert_eq!(out.samples, vec![1.0, 2.0, 3.0]);
+        assert_eq!(out.marks, vec![(0, first), (2, second)]);
+    }
+
+    #[test]
+    fn test_feed_moves_samples_into_buffer() {
+        let buffer = Arc::new(Mutex::new(SampleRingBuffer::with_capacity(16)));
+        let mut producer = feed(&buffer);
+        producer.push_slice(&[0.5; 8]);
+        drop(producer);
+
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while buffer.lock().unwrap().is_empty() && Instant::now() < deadline {
+            thread::sleep(PUMP_INTERVAL);
+        }
+        assert_eq!(buffer.lock().unwrap().drain_all(), vec![0.5; 8]);
+    }
+}
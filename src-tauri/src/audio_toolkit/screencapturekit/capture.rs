@@ -15,14 +15,27 @@ use screencapturekit::{
 };
 use core_media_rs::cm_sample_buffer::CMSampleBuffer;
 use std::sync::{Arc, Mutex};
-use std::collections::VecDeque;
-use anyhow::Result;
+use std::time::Duration;
 
+use super::CaptureTarget;
+use crate::audio_toolkit::audio::Downmix;
+use crate::audio_toolkit::debug_log::{audio_debug_enabled, AUDIO_DEBUG_TARGET};
+use crate::audio_toolkit::error::{AudioError, Result};
+use crate::audio_toolkit::ring_buffer::{
+    SampleReceiver, SampleRingBuffer, SYSTEM_AUDIO_SAMPLE_RATE,
+};
+use crate::audio_toolkit::sample_queue::{self, SampleProducer};
+use crate::audio_toolkit::stream::AudioFrameStream;
+use crate::audio_toolkit::system_audio::{CaptureFormat, CaptureStatus};
+use crate::audio_toolkit::timed::{self, TimedSamples};
 use crate::audio_toolkit::SystemAudioCapture;
 
 /// Audio output handler for ScreenCaptureKit
 struct AudioStreamOutput {
-    buffer: Arc<Mutex<VecDeque<f32>>>,
+    // Only this handler locks it, so the lock is never contended; it is
+    // there because SCK hands the handler out as `&self`
+    samples: Mutex<SampleProducer>,
+    downmix: Downmix,
 }
 
 impl SCStreamOutputTrait for AudioStreamOutput {
@@ -36,66 +49,45 @@ impl SCStreamOutputTrait for AudioStreamOutput {
             log::trace!("📹 [SCK] Received non-audio buffer type: {:?}", of_type);
             return;
         }
-        
-        
+
         // Extract audio data from CMSampleBuffer
         match sample_buffer.get_audio_buffer_list() {
             Ok(audio_buffer_list) => {
+                // ScreenCaptureKit outputs non-interleaved Float32 PCM, one
+                // buffer per channel
                 let num_buffers = audio_buffer_list.num_buffers();
-                
-                let mut buffer_lock = self.buffer.lock().unwrap();
-                let before_len = buffer_lock.len();
-                
+                let mut planes = Vec::new();
                 for i in 0..num_buffers {
                     if let Some(buffer) = audio_buffer_list.get(i) {
-                        let data = buffer.data();
-                        let data_len = data.len();
-                        
-                        // Convert bytes to f32 samples
-                        // ScreenCaptureKit outputs Float32 PCM
-                        let mut samples_added = 0;
-                        for chunk in data.chunks_exact(4) {
-                            let sample = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-                            buffer_lock.push_back(sample);
-                            samples_added += 1;
-                        }
-                        
+                        planes.push(
+                            buffer
+                                .data()
+                                .chunks_exact(4)
+                                .map(|chunk| {
+                                    f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])
+                                })
+                                .collect::<Vec<f32>>(),
+                        );
                     } else {
                         log::warn!("⚠️ [SCK] Buffer[{}] is None", i);
                     }
                 }
-                
-                let after_len = buffer_lock.len();
-                let added = after_len - before_len;
+                // The buffer was captured over its length up to its arrival
+                let frames = planes.first().map_or(0, Vec::len);
+                let mut samples = self.samples.lock().unwrap();
+                samples.stamp(timed::captured_before(Duration::from_secs_f64(
+                    frames as f64 / SYSTEM_AUDIO_SAMPLE_RATE as f64,
+                )));
+                match planes.len() {
+                    0 => {}
+                    1 => samples.push_slice(&planes[0]),
+                    _ => samples.push_slice(&self.downmix.mix_planar(&planes)),
+                }
             }
             Err(e) => {
                 log::error!("❌ [SCK] Failed to get audio buffer list: {:?}", e);
             }
         }
-            match sample_buffer.get_audio_buffer_list() {
-                Ok(audio_buffer_list) => {
-                    let num_buffers = audio_buffer_list.num_buffers();
-                    let mut buffer_lock = self.buffer.lock().unwrap();
-                    for i in 0..num_buffers {
-                        if let Some(buffer) = audio_buffer_list.get(i) {
-                            let data = buffer.data();
-                            for chunk in data.chunks_exact(4) {
-                                let sample = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-                                buffer_lock.push_back(sample);
-                            }
-                        } else {
-                            log::warn!("⚠️ [SCK] Buffer[{}] is None", i);
-                        }
-                    }
-                    // Only log if buffer is empty or error
-                    if buffer_lock.is_empty() {
-                        log::warn!("⚠️ [SCK] Audio buffer is empty after callback");
-                    }
-                }
-                Err(e) => {
-                    log::error!("❌ [SCK] Failed to get audio buffer list: {:?}", e);
-                }
-            }
     }
 }
 
@@ -105,8 +97,12 @@ impl SCStreamOutputTrait for AudioStreamOutput {
 /// Audio-only mode: captures at 48kHz stereo, minimal CPU/GPU usage.
 pub struct ScreenCaptureKitAudio {
     stream: Arc<Mutex<Option<SCStream>>>,
-    audio_buffer: Arc<Mutex<VecDeque<f32>>>,
+    audio_buffer: Arc<Mutex<SampleRingBuffer>>,
     is_capturing: Arc<Mutex<bool>>,
+    // How the stereo capture is reduced to mono
+    downmix: Downmix,
+    // Picked display, window or app; the main display when None
+    target: Option<CaptureTarget>,
 }
 
 impl ScreenCaptureKitAudio {
@@ -116,16 +112,119 @@ impl ScreenCaptureKitAudio {
         
         Ok(Self {
             stream: Arc::new(Mutex::new(None)),
-            audio_buffer: Arc::new(Mutex::new(VecDeque::new())),
+            audio_buffer: Arc::new(Mutex::new(SampleRingBuffer::default())),
             is_capturing: Arc::new(Mutex::new(false)),
+            downmix: Downmix::default(),
+            target: None,
         })
     }
+
+    /// Limit how much unread audio is kept before the oldest samples are overwritten
+    pub fn with_max_buffer_seconds(self, seconds: u32) -> Self {
+        self.audio_buffer
+            .lock()
+            .unwrap()
+            .set_capacity(seconds as usize * SYSTEM_AUDIO_SAMPLE_RATE);
+        self
+    }
+
+    /// Reduce the stereo capture to mono with `downmix` instead of
+    /// averaging the channels
+    pub fn with_downmix(mut self, downmix: Downmix) -> Self {
+        self.downmix = downmix;
+        self
+    }
+
+    /// Capture into `buffer` instead of a buffer of its own, for a capture
+    /// that uses ScreenCaptureKit as one of its strategies
+    pub fn with_sample_buffer(mut self, buffer: Arc<Mutex<SampleRingBuffer>>) -> Self {
+        self.audio_buffer = buffer;
+        self
+    }
+
+    /// Capture the audio of `target` instead of the whole main display
+    pub fn with_target(mut self, target: Option<CaptureTarget>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Filter for the picked target, or the main display when there is none
+    /// or it has gone away (window closed, app quit, display unplugged)
+    fn content_filter(&self, content: &SCShareableContent) -> Result<SCContentFilter> {
+        let mut displays = content.displays();
+        if displays.is_empty() {
+            log::error!("❌ [SCK] No displays available for capture");
+            return Err(AudioError::DeviceNotFound(
+                "No displays available for capture".to_string(),
+            ));
+        }
+
+        match &self.target {
+            Some(CaptureTarget::Display { display_id }) => {
+                if let Some(display) = displays.iter().find(|d| d.display_id() == *display_id) {
+                    log::info!(
+                        "✅ [SCK] Capturing audio from picked display {}",
+                        display_id
+                    );
+                    return Ok(SCContentFilter::new().with_display_excluding_windows(display, &[]));
+                }
+                log::warn!(
+                    "⚠️ [SCK] Picked display {} is gone, using the main display",
+                    display_id
+                );
+            }
+            Some(CaptureTarget::Window {
+                window_id, title, ..
+            }) => {
+                if let Some(window) = content
+                    .windows()
+                    .iter()
+                    .find(|w| w.window_id() == *window_id)
+                {
+                    log::info!("✅ [SCK] Capturing audio from window {:?}", title);
+                    return Ok(SCContentFilter::new().with_desktop_independent_window(window));
+                }
+                log::warn!(
+                    "⚠️ [SCK] Picked window {:?} is gone, using the main display",
+                    title
+                );
+            }
+            Some(CaptureTarget::Application { bundle_id, .. }) => {
+                let applications = content.applications();
+                if let Some(app) = applications
+                    .iter()
+                    .find(|a| a.bundle_identifier() == *bundle_id)
+                {
+                    log::info!("✅ [SCK] Capturing audio from app {}", bundle_id);
+                    return Ok(SCContentFilter::new()
+                        .with_display_including_application_excluding_windows(
+                            &displays[0],
+                            &[app],
+                            &[],
+                        ));
+                }
+                log::warn!(
+                    "⚠️ [SCK] Picked app {} is not running, using the main display",
+                    bundle_id
+                );
+            }
+            None => {}
+        }
+
+        let display = displays.remove(0);
+        log::info!(
+            "✅ [SCK] Capturing audio from display ID: {}",
+            display.display_id()
+        );
+        // Display capture (not window) gets all system audio
+        Ok(SCContentFilter::new().with_display_excluding_windows(&display, &[]))
+    }
 }
 
 impl SystemAudioCapture for ScreenCaptureKitAudio {
     /// Start capturing system audio
     /// 
-    /// Captures audio-only from the primary display.
+    /// Captures audio-only from the picked target, or the primary display.
     /// Configuration:
     /// - Sample rate: 48kHz
     /// - Channels: 2 (stereo)
@@ -146,40 +245,31 @@ impl SystemAudioCapture for ScreenCaptureKitAudio {
         // Configure stream for audio-only capture
         let config = SCStreamConfiguration::new()
             .set_captures_audio(true)
-            .map_err(|e| anyhow::anyhow!("❌ Failed to enable audio capture: {:?}", e))?
+            .map_err(|e| AudioError::InvalidConfig(format!("❌ Failed to enable audio capture: {:?}", e)))?
             .set_sample_rate(48000)
-            .map_err(|e| anyhow::anyhow!("❌ Failed to set sample rate: {:?}", e))?
+            .map_err(|e| AudioError::InvalidConfig(format!("❌ Failed to set sample rate: {:?}", e)))?
             .set_channel_count(2)
-            .map_err(|e| anyhow::anyhow!("❌ Failed to set channel count: {:?}", e))?;
+            .map_err(|e| AudioError::InvalidConfig(format!("❌ Failed to set channel count: {:?}", e)))?;
         
         log::info!("✅ [SCK] Stream configured: 48kHz, 2 channels, audio-only");
+        self.audio_buffer.lock().unwrap().set_format(CaptureFormat {
+            sample_rate: 48000,
+            channels: 2,
+        });
         
-        // Get the primary display
         let shareable_content = SCShareableContent::get()
-            .map_err(|e| anyhow::anyhow!("❌ Failed to get shareable content: {:?}. Make sure Screen Recording permission is granted.", e))?;
-        
-        let mut displays = shareable_content.displays();
-        if displays.is_empty() {
-            log::error!("❌ [SCK] No displays available for capture");
-            return Err(anyhow::anyhow!("No displays available for capture"));
-        }
-        
-        let display = displays.remove(0);
-        log::info!("✅ [SCK] Capturing audio from display ID: {}", display.display_id());
+            .map_err(|e| AudioError::PermissionDenied(format!("❌ Failed to get shareable content: {:?}. Make sure Screen Recording permission is granted.", e)))?;
+        let filter = self.content_filter(&shareable_content)?;
         
-        // Create content filter - capture all system audio from the display
-        // We use display capture (not window) to get all system audio
-        let filter = SCContentFilter::new()
-            .with_display_excluding_windows(&display, &[]);
-        
-        log::info!("✅ [SCK] Content filter created - capturing all system audio");
+        log::info!("✅ [SCK] Content filter created");
         
         // Create stream with audio output handler
         let mut stream = SCStream::new(&filter, &config);
         
         // Add output handler for audio
         let output_handler = AudioStreamOutput {
-            buffer: self.audio_buffer.clone(),
+            samples: Mutex::new(sample_queue::feed(&self.audio_buffer)),
+            downmix: self.downmix,
         };
         
         stream.add_output_handler(output_handler, SCStreamOutputType::Audio);
@@ -188,7 +278,7 @@ impl SystemAudioCapture for ScreenCaptureKitAudio {
         // Start capturing
         log::info!("▶️ [SCK] Starting capture...");
         stream.start_capture()
-            .map_err(|e| anyhow::anyhow!("❌ Failed to start capture: {:?}", e))?;
+            .map_err(|e| AudioError::StreamBuildFailed(format!("❌ Failed to start capture: {:?}", e)))?;
         
         // Store stream and set capturing flag
         *self.stream.lock().unwrap() = Some(stream);
@@ -207,7 +297,7 @@ impl SystemAudioCapture for ScreenCaptureKitAudio {
         
         if let Some(stream) = stream_lock.take() {
             stream.stop_capture()
-                .map_err(|e| anyhow::anyhow!("Failed to stop capture: {:?}", e))?;
+                .map_err(|e| AudioError::Backend(format!("Failed to stop capture: {:?}", e)))?;
             *self.is_capturing.lock().unwrap() = false;
             log::info!("ScreenCaptureKit audio capture stopped");
         }
@@ -219,22 +309,25 @@ impl SystemAudioCapture for ScreenCaptureKitAudio {
     /// 
     /// Returns samples captured at 48kHz stereo.
     /// Caller should resample to 16kHz mono for Whisper.
-    fn read_samples(&mut self) -> Result<Option<Vec<f32>>> {
+    fn read_samples(&mut self) -> Result<Option<TimedSamples>> {
         let mut buffer = self.audio_buffer.lock().unwrap();
         
         if buffer.is_empty() {
-                // Only log if buffer is empty
-                log::warn!("📭 [SCK] read_samples: Buffer is empty");
+            if audio_debug_enabled() {
+                log::debug!(target: AUDIO_DEBUG_TARGET, "📭 [SCK] read_samples: Buffer is empty");
+            }
             return Ok(None);
         }
         
         // Drain all available samples
-        let samples: Vec<f32> = buffer.drain(..).collect();
+        let samples = buffer.drain_timed();
         let sample_count = samples.len();
         let duration_secs = sample_count as f32 / 48000.0;
         
-        log::info!("📤 [SCK] read_samples: Returning {} samples (~{:.2}s audio @ 48kHz)", 
-            sample_count, duration_secs);
+        if audio_debug_enabled() {
+            log::debug!(target: AUDIO_DEBUG_TARGET, "📤 [SCK] read_samples: Returning {} samples (~{:.2}s audio @ 48kHz)",
+                sample_count, duration_secs);
+        }
         
         Ok(Some(samples))
     }
@@ -243,6 +336,26 @@ impl SystemAudioCapture for ScreenCaptureKitAudio {
     fn is_capturing(&self) -> bool {
         *self.is_capturing.lock().unwrap()
     }
+
+    fn dropped_samples(&self) -> u64 {
+        self.audio_buffer.lock().unwrap().dropped_samples()
+    }
+
+    fn subscribe_frames(&self) -> Option<AudioFrameStream> {
+        Some(self.audio_buffer.lock().unwrap().subscribe())
+    }
+
+    fn deliver_samples(&mut self) -> Option<SampleReceiver> {
+        Some(self.audio_buffer.lock().unwrap().deliver())
+    }
+
+    fn format(&self) -> CaptureFormat {
+        self.audio_buffer.lock().unwrap().format()
+    }
+
+    fn peek_status(&self) -> CaptureStatus {
+        CaptureStatus::from_buffer(&self.audio_buffer.lock().unwrap())
+    }
 }
 
 impl Drop for ScreenCaptureKitAudio {
@@ -9,12 +9,38 @@ pub mod capture;
 #[cfg(target_os = "macos")]
 pub mod permissions;
 
+#[cfg(target_os = "macos")]
+pub mod picker;
+
 #[cfg(target_os = "macos")]
 pub use capture::ScreenCaptureKitAudio;
 
 #[cfg(target_os = "macos")]
 pub use permissions::{check_screen_recording_permission, request_screen_recording_permission};
 
+use serde::{Deserialize, Serialize};
+
+/// What ScreenCaptureKit captures audio from, as picked with the
+/// content-sharing picker. Stored by ID and looked up again when capture
+/// starts; the whole main display is captured when there is none.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CaptureTarget {
+    Display {
+        display_id: u32,
+    },
+    Window {
+        window_id: u32,
+        title: Option<String>,
+        /// Name of the app owning the window
+        app: Option<String>,
+    },
+    Application {
+        bundle_id: String,
+        name: Option<String>,
+    },
+}
+
 #[cfg(not(target_os = "macos"))]
 pub struct ScreenCaptureKitAudio;
 
@@ -32,3 +58,20 @@ impl ScreenCaptureKitAudio {
         Err("ScreenCaptureKit is only available on macOS".to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_target_round_trips() {
+        let target = CaptureTarget::Application {
+            bundle_id: "com.google.Chrome".to_string(),
+            name: Some("Google Chrome".to_string()),
+        };
+        let json = serde_json::to_value(&target).unwrap();
+        assert_eq!(json["kind"], "application");
+        assert_eq!(json["bundle_id"], "com.google.Chrome");
+        assert_eq!(serde_json::from_value::<CaptureTarget>(json).unwrap(), target);
+    }
+}
@@ -0,0 +1,203 @@
+//! Native content-sharing picker (macOS 14+)
+//!
+//! Presents `SCContentSharingPicker` so the user can choose a display, window
+//! or app to capture audio from. The picked filter is turned into a
+//! `CaptureTarget` that can be stored and looked up again when capture
+//! starts. Reading what a filter includes needs macOS 15.2.
+
+use super::CaptureTarget;
+use objc::declare::ClassDecl;
+use objc::runtime::{Class, Object, Protocol, Sel, BOOL, NO, YES};
+use objc::{class, msg_send, sel, sel_impl};
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, Once, OnceLock};
+
+const OBSERVER_CLASS: &str = "HandyContentSharingPickerObserver";
+
+// SCShareableContentStyle
+const STYLE_WINDOW: isize = 1;
+const STYLE_DISPLAY: isize = 2;
+const STYLE_APPLICATION: isize = 3;
+
+/// How the picker was closed
+#[derive(Debug)]
+pub enum PickerOutcome {
+    Picked(CaptureTarget),
+    Cancelled,
+    Failed(String),
+}
+
+// Where the outcome of the picker being shown goes
+static PENDING: Mutex<Option<Sender<PickerOutcome>>> = Mutex::new(None);
+// The observer object, kept for the lifetime of the app
+static OBSERVER: OnceLock<usize> = OnceLock::new();
+
+/// Whether this system has the content-sharing picker
+pub fn is_supported() -> bool {
+    Class::get("SCContentSharingPicker").is_some()
+}
+
+unsafe fn to_string(string: *mut Object) -> Option<String> {
+    if string.is_null() {
+        return None;
+    }
+    let utf8: *const c_char = msg_send![string, UTF8String];
+    if utf8.is_null() {
+        return None;
+    }
+    Some(CStr::from_ptr(utf8).to_string_lossy().into_owned())
+}
+
+unsafe fn first_object(array: *mut Object) -> Option<*mut Object> {
+    if array.is_null() {
+        return None;
+    }
+    let object: *mut Object = msg_send![array, firstObject];
+    (!object.is_null()).then_some(object)
+}
+
+// The display, window or app an `SCContentFilter` from the picker includes
+unsafe fn target_from_filter(filter: *mut Object) -> Option<CaptureTarget> {
+    if filter.is_null() {
+        return None;
+    }
+    let readable: BOOL = msg_send![filter, respondsToSelector: sel!(includedDisplays)];
+    if readable == NO {
+        return None;
+    }
+
+    let style: isize = msg_send![filter, style];
+    match style {
+        STYLE_DISPLAY => {
+            let display = first_object(msg_send![filter, includedDisplays])?;
+            let display_id: u32 = msg_send![display, displayID];
+            Some(CaptureTarget::Display { display_id })
+        }
+        STYLE_WINDOW => {
+            let window = first_object(msg_send![filter, includedWindows])?;
+            let window_id: u32 = msg_send![window, windowID];
+            let app: *mut Object = msg_send![window, owningApplication];
+            Some(CaptureTarget::Window {
+                window_id,
+                title: to_string(msg_send![window, title]),
+                app: if app.is_null() {
+                    None
+                } else {
+                    to_string(msg_send![app, applicationName])
+                },
+            })
+        }
+        STYLE_APPLICATION => {
+            let app = first_object(msg_send![filter, includedApplications])?;
+            Some(CaptureTarget::Application {
+                bundle_id: to_string(msg_send![app, bundleIdentifier])?,
+                name: to_string(msg_send![app, applicationName]),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn finish(outcome: PickerOutcome) {
+    if let Some(sender) = PENDING.lock().unwrap().take() {
+        let _ = sender.send(outcome);
+    }
+    unsafe {
+        if let Some(class) = Class::get("SCContentSharingPicker") {
+            let picker: *mut Object = msg_send![class, sharedPicker];
+            let _: () = msg_send![picker, setActive: NO];
+        }
+    }
+}
+
+extern "C" fn did_update(
+    _this: &Object,
+    _cmd: Sel,
+    _picker: *mut Object,
+    filter: *mut Object,
+    _stream: *mut Object,
+) {
+    let outcome = match unsafe { target_from_filter(filter) } {
+        Some(target) => PickerOutcome::Picked(target),
+        None => PickerOutcome::Failed(
+            "The selection can't be remembered on this macOS version (needs 15.2)".to_string(),
+        ),
+    };
+    finish(outcome);
+}
+
+extern "C" fn did_cancel(_this: &Object, _cmd: Sel, _picker: *mut Object, _stream: *mut Object) {
+    finish(PickerOutcome::Cancelled);
+}
+
+extern "C" fn did_fail(_this: &Object, _cmd: Sel, error: *mut Object) {
+    let message = unsafe {
+        if error.is_null() {
+            None
+        } else {
+            to_string(msg_send![error, localizedDescription])
+        }
+    };
+    finish(PickerOutcome::Failed(message.unwrap_or_else(|| {
+        "The picker could not be shown".to_string()
+    })));
+}
+
+fn observer_class() -> &'static Class {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        let mut decl = ClassDecl::new(OBSERVER_CLASS, class!(NSObject))
+            .expect("picker observer class is registered once");
+        if let Some(protocol) = Protocol::get("SCContentSharingPickerObserver") {
+            decl.add_protocol(protocol);
+        }
+        unsafe {
+            decl.add_method(
+                sel!(contentSharingPicker:didUpdateWithFilter:forStream:),
+                did_update as extern "C" fn(&Object, Sel, *mut Object, *mut Object, *mut Object),
+            );
+            decl.add_method(
+                sel!(contentSharingPicker:didCancelForStream:),
+                did_cancel as extern "C" fn(&Object, Sel, *mut Object, *mut Object),
+            );
+            decl.add_method(
+                sel!(contentSharingPickerStartDidFailWithError:),
+                did_fail as extern "C" fn(&Object, Sel, *mut Object),
+            );
+        }
+        decl.register();
+    });
+    Class::get(OBSERVER_CLASS).expect("picker observer class is registered")
+}
+
+/// Receiver for the outcome of the next `present`. A pick still pending is
+/// abandoned.
+pub fn begin() -> Receiver<PickerOutcome> {
+    let (sender, receiver) = mpsc::channel();
+    *PENDING.lock().unwrap() = Some(sender);
+    receiver
+}
+
+/// Show the picker. Must run on the main thread; the outcome arrives on the
+/// receiver from `begin`.
+pub fn present() {
+    let Some(class) = Class::get("SCContentSharingPicker") else {
+        finish(PickerOutcome::Failed(
+            "The content picker needs macOS 14 or later".to_string(),
+        ));
+        return;
+    };
+    unsafe {
+        let observer = *OBSERVER.get_or_init(|| {
+            let observer: *mut Object = msg_send![observer_class(), new];
+            observer as usize
+        }) as *mut Object;
+        let picker: *mut Object = msg_send![class, sharedPicker];
+        // Adding an observer twice keeps a single entry
+        let _: () = msg_send![picker, addObserver: observer];
+        let _: () = msg_send![picker, setActive: YES];
+        let _: () = msg_send![picker, present];
+    }
+}
@@ -0,0 +1,354 @@
+// Self-test of the capture pipeline
+// The selected source is opened for a few seconds and every chunk it delivers
+// is fed to a SelfTestProbe together with its arrival time. The probe measures
+// the signal, runs the chunks through the same resampler and VAD a recording
+// uses, and turns the result into a checklist the settings UI shows as
+// pass/fail.
+
+use crate::audio_toolkit::audio::FrameResampler;
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::loopback_diagnosis::DiagnosisCheck;
+use crate::audio_toolkit::VoiceActivityDetector;
+use serde::Serialize;
+use std::time::Duration;
+
+/// How long the source is captured for
+pub const SELFTEST_DURATION: Duration = Duration::from_secs(3);
+
+const VAD_FRAME: Duration = Duration::from_millis(30);
+// Delivery may lag the audio it carries by this much before it counts as a dropout
+const DROPOUT_SLACK: Duration = Duration::from_millis(50);
+// Allowed deviation of the measured from the nominal sample rate, and of the
+// resampler output from the expected length
+const RATE_TOLERANCE: f64 = 0.05;
+// Peak below which the source counts as silent
+const SILENCE_PEAK: f32 = 0.001;
+const CLIPPING_PEAK: f32 = 0.99;
+
+/// Accumulates the chunks of one self-test capture
+pub struct SelfTestProbe {
+    sample_rate: u32,
+    vad: Option<Box<dyn VoiceActivityDetector>>,
+    resampler: FrameResampler,
+    chunks: u64,
+    samples: u64,
+    first_chunk: Option<(Duration, u64)>,
+    last_arrival: Option<Duration>,
+    sum_squares: f64,
+    peak: f32,
+    dropouts: u64,
+    resampled_samples: u64,
+    vad_frames: u64,
+    speech_frames: u64,
+}
+
+/// Result of `run_audio_selftest`
+#[derive(Clone, Debug, Serialize)]
+pub struct AudioSelfTest {
+    /// `microphone` or `system_audio`
+    pub source: String,
+    pub device: String,
+    pub duration_ms: u64,
+    /// Rate the stream was opened at
+    pub sample_rate: u32,
+    /// Rate the samples actually arrived at
+    pub measured_sample_rate: f64,
+    pub rms: f32,
+    pub peak: f32,
+    /// Gaps in delivery longer than the audio received before them
+    pub dropouts: u64,
+    /// Samples the capture buffer overwrote before they were read
+    pub dropped_samples: u64,
+    /// Share of 30ms frames the VAD heard speech in, when a VAD ran
+    pub speech_ratio: Option<f32>,
+    /// 16kHz samples the resampler produced, and how many it should have
+    pub resampled_samples: u64,
+    pub expected_resampled_samples: u64,
+    pub checks: Vec<DiagnosisCheck>,
+    pub passed: bool,
+}
+
+impl SelfTestProbe {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            vad: None,
+            resampler: FrameResampler::new(
+                sample_rate as usize,
+                WHISPER_SAMPLE_RATE as usize,
+                VAD_FRAME,
+            ),
+            chunks: 0,
+            samples: 0,
+            first_chunk: None,
+            last_arrival: None,
+            sum_squares: 0.0,
+            peak: 0.0,
+            dropouts: 0,
+            resampled_samples: 0,
+            vad_frames: 0,
+            speech_frames: 0,
+        }
+    }
+
+    pub fn with_vad(mut self, vad: Box<dyn VoiceActivityDetector>) -> Self {
+        self.vad = Some(vad);
+        self
+    }
+
+    /// Accumulate one chunk of mono samples that arrived `at` after the
+    /// capture started
+    pub fn observe(&mut self, samples: &[f32], at: Duration) {
+        if samples.is_empty() {
+            return;
+        }
+
+        let chunk_duration =
+            Duration::from_secs_f64(samples.len() as f64 / self.sample_rate as f64);
+        if let Some(last) = self.last_arrival {
+            if at.saturating_sub(last) > chunk_duration + DROPOUT_SLACK {
+                self.dropouts += 1;
+            }
+        }
+        if self.first_chunk.is_none() {
+            self.first_chunk = Some((at, samples.len() as u64));
+        }
+        self.last_arrival = Some(at);
+        self.chunks += 1;
+        self.samples += samples.len() as u64;
+
+        for &sample in samples {
+            self.peak = self.peak.max(sample.abs());
+            self.sum_squares += (sample as f64) * (sample as f64);
+        }
+
+        let Self {
+            resampler,
+            vad,
+            resampled_samples,
+            vad_frames,
+            speech_frames,
+            ..
+        } = self;
+        resampler.push(samples, |frame| {
+            *resampled_samples += frame.len() as u64;
+            if let Some(vad) = vad.as_mut() {
+                *vad_frames += 1;
+                if vad.is_voice(frame).unwrap_or(false) {
+                    *speech_frames += 1;
+                }
+            }
+        });
+    }
+
+    /// Rate the samples arrived at, from the first chunk to the last. The
+    /// first chunk's samples were captured before it arrived, so they don't
+    /// count.
+    fn measured_sample_rate(&self) -> f64 {
+        match (self.first_chunk, self.last_arrival) {
+            (Some((first_at, first_len)), Some(last_at)) if last_at > first_at => {
+                (self.samples - first_len) as f64 / (last_at - first_at).as_secs_f64()
+            }
+            _ => 0.0,
+        }
+    }
+
+    pub fn finish(
+        mut self,
+        source: &str,
+        device: String,
+        duration: Duration,
+        dropped_samples: u64,
+    ) -> AudioSelfTest {
+        let Self {
+            resampler,
+            resampled_samples,
+            ..
+        } = &mut self;
+        resampler.finish(|frame| *resampled_samples += frame.len() as u64);
+
+        let measured_sample_rate = self.measured_sample_rate();
+        let rms = if self.samples > 0 {
+            (self.sum_squares / self.samples as f64).sqrt() as f32
+        } else {
+            0.0
+        };
+        let speech_ratio = (self.vad.is_some() && self.vad_frames > 0)
+            .then_some(self.speech_frames as f32 / self.vad_frames as f32);
+        let expected_resampled_samples =
+            self.samples * WHISPER_SAMPLE_RATE as u64 / self.sample_rate as u64;
+        let within_tolerance = |actual: f64, expected: f64| {
+            expected > 0.0 && ((actual - expected) / expected).abs() <= RATE_TOLERANCE
+        };
+
+        let mut checks = vec![DiagnosisCheck {
+            id: "stream",
+            label: format!("{} is delivering audio", device),
+            passed: self.chunks > 0,
+            hint: Some(
+                "The source opened but sent no audio. Check that Handy is allowed to record it in your system's privacy settings, or pick another device.".to_string(),
+            ),
+        }];
+
+        checks.push(DiagnosisCheck {
+            id: "sample_rate",
+            label: format!("Audio arrives at {}Hz", self.sample_rate),
+            passed: within_tolerance(measured_sample_rate, self.sample_rate as f64),
+            hint: Some(format!(
+                "Audio arrived at about {:.0}Hz. The device may be misreporting its rate; try another sample rate for it in your system's audio settings.",
+                measured_sample_rate
+            )),
+        });
+
+        checks.push(DiagnosisCheck {
+            id: "dropouts",
+            label: "Audio arrives without gaps".to_string(),
+            passed: self.dropouts == 0 && dropped_samples == 0,
+            hint: Some(format!(
+                "{} gaps in delivery and {} overwritten samples. Close other apps using the device, or pick a device on a different connection.",
+                self.dropouts, dropped_samples
+            )),
+        });
+
+        checks.push(DiagnosisCheck {
+            id: "signal",
+            label: "Non-silent audio is arriving".to_string(),
+            passed: self.peak >= SILENCE_PEAK,
+            hint: Some(match source {
+                "system_audio" => "Play something in another app and make sure its volume is not muted.".to_string(),
+                _ => "Speak during the test and check that the microphone is not muted and its input volume is up.".to_string(),
+            }),
+        });
+
+        checks.push(DiagnosisCheck {
+            id: "clipping",
+            label: "Audio is not clipping".to_string(),
+            passed: self.peak < CLIPPING_PEAK,
+            hint: Some(
+                "The signal reaches full scale. Lower the input volume of the device.".to_string(),
+            ),
+        });
+
+        checks.push(DiagnosisCheck {
+            id: "resampler",
+            label: format!(
+                "Resampling {}Hz to 16kHz keeps every sample",
+                self.sample_rate
+            ),
+            passed: within_tolerance(
+                self.resampled_samples as f64,
+                expected_resampled_samples as f64,
+            ),
+            hint: Some(format!(
+                "The resampler produced {} samples where {} were expected.",
+                self.resampled_samples, expected_resampled_samples
+            )),
+        });
+
+        if let Some(ratio) = speech_ratio.filter(|_| source == "microphone") {
+            checks.push(DiagnosisCheck {
+                id: "speech",
+                label: "Speech is detected".to_string(),
+                passed: ratio > 0.0,
+                hint: Some(
+                    "No speech was heard. Say a few words during the test; if you did, move closer to the microphone.".to_string(),
+                ),
+            });
+        }
+
+        for check in &mut checks {
+            if check.passed {
+                check.hint = None;
+            }
+        }
+
+        AudioSelfTest {
+            source: source.to_string(),
+            device,
+            duration_ms: duration.as_millis() as u64,
+            sample_rate: self.sample_rate,
+            measured_sample_rate,
+            rms,
+            peak: self.peak,
+            dropouts: self.dropouts,
+            dropped_samples,
+            speech_ratio,
+            resampled_samples: self.resampled_samples,
+            expected_resampled_samples,
+            passed: checks.iter().all(|check| check.passed),
+            checks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_toolkit::error::Result;
+    use crate::audio_toolkit::vad::VadFrame;
+
+    // Calls every frame louder than 0.1 speech
+    struct LoudnessVad;
+
+    impl VoiceActivityDetector for LoudnessVad {
+        fn push_frame<'a>(&'a mut self, frame: &'a [f32]) -> Result<VadFrame<'a>> {
+            if frame.iter().any(|s| s.abs() > 0.1) {
+                Ok(VadFrame::Speech(frame))
+            } else {
+                Ok(VadFrame::Noise)
+            }
+        }
+    }
+
+    fn failed(result: &AudioSelfTest) -> Vec<&str> {
+        result
+            .checks
+            .iter()
+            .filter(|c| !c.passed)
+            .map(|c| c.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_steady_stream_passes() {
+        let mut probe = SelfTestProbe::new(16000).with_vad(Box::new(LoudnessVad));
+        // 100 chunks of 10ms, the second half loud
+        for i in 0..100u64 {
+            let level = if i < 50 { 0.01 } else { 0.5 };
+            probe.observe(&[level; 160], Duration::from_millis(10 * (i + 1)));
+        }
+        let result = probe.finish("microphone", "Mic".to_string(), SELFTEST_DURATION, 0);
+
+        assert_eq!(failed(&result), Vec::<&str>::new());
+        assert!(result.passed);
+        assert!((result.measured_sample_rate - 16000.0).abs() < 1.0);
+        assert_eq!(result.peak, 0.5);
+        assert_eq!(result.dropouts, 0);
+        // The last partial 30ms frame is padded out
+        assert_eq!(result.resampled_samples, 16320);
+        assert_eq!(result.expected_resampled_samples, 16000);
+        let ratio = result.speech_ratio.unwrap();
+        assert!((0.45..=0.55).contains(&ratio), "speech ratio {}", ratio);
+    }
+
+    #[test]
+    fn test_gap_and_silence_fail() {
+        let mut probe = SelfTestProbe::new(16000);
+        probe.observe(&[0.0; 160], Duration::from_millis(10));
+        probe.observe(&[0.0; 160], Duration::from_millis(20));
+        // 200ms without audio
+        probe.observe(&[0.0; 160], Duration::from_millis(220));
+        let result = probe.finish(
+            "system_audio",
+            "System audio".to_string(),
+            SELFTEST_DURATION,
+            0,
+        );
+
+        assert_eq!(result.dropouts, 1);
+        assert!(result.speech_ratio.is_none());
+        assert_eq!(failed(&result), vec!["sample_rate", "dropouts", "signal"]);
+        assert!(!result.passed);
+        assert!(result.checks.iter().all(|c| c.passed == c.hint.is_none()));
+    }
+}
@@ -0,0 +1,288 @@
+// Simulated capture for testing without audio hardware
+// Setting HANDY_SIMULATED_AUDIO to an audio file path, or to `tone` or
+// `tone:<hz>`, replaces the microphone and system audio with that signal,
+// looped and delivered in real time, so the recording -> VAD -> transcription
+// pipeline can run on a machine without devices or permissions.
+
+use crate::audio_toolkit::audio::{decode_audio_file, FrameResampler};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::error::{AudioError, Result};
+use crate::audio_toolkit::ring_buffer::{
+    SampleReceiver, SampleRingBuffer, SYSTEM_AUDIO_SAMPLE_RATE,
+};
+use crate::audio_toolkit::stream::AudioFrameStream;
+use crate::audio_toolkit::system_audio::{CaptureFormat, CaptureStatus, SystemAudioCapture};
+use crate::audio_toolkit::timed::TimedSamples;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Environment variable selecting the simulated source
+pub const SIMULATED_AUDIO_ENV: &str = "HANDY_SIMULATED_AUDIO";
+
+// Audio handed over per delivery, like a capture callback
+const CHUNK_DURATION: Duration = Duration::from_millis(10);
+const DEFAULT_TONE_HZ: f32 = 440.0;
+const TONE_AMPLITUDE: f32 = 0.3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimulatedSource {
+    /// Any file `decode_audio_file` reads, played in a loop
+    File(PathBuf),
+    /// Sine wave
+    Tone { frequency: f32 },
+}
+
+impl SimulatedSource {
+    /// `tone`, `tone:<hz>` or a file path
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            return Err(AudioError::InvalidConfig(
+                "empty simulated audio source".to_string(),
+            ));
+        }
+        if spec == "tone" {
+            return Ok(Self::Tone {
+                frequency: DEFAULT_TONE_HZ,
+            });
+        }
+        if let Some(frequency) = spec.strip_prefix("tone:") {
+            let frequency: f32 = frequency.parse().map_err(|_| {
+                AudioError::InvalidConfig(format!("invalid tone frequency '{}'", frequency))
+            })?;
+            if !frequency.is_finite() || frequency <= 0.0 {
+                return Err(AudioError::InvalidConfig(
+                    "tone frequency must be positive".to_string(),
+                ));
+            }
+            return Ok(Self::Tone { frequency });
+        }
+        Ok(Self::File(PathBuf::from(spec)))
+    }
+
+    /// The source selected by `HANDY_SIMULATED_AUDIO`, if any
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var(SIMULATED_AUDIO_ENV).ok()?;
+        match Self::parse(&spec) {
+            Ok(source) => Some(source),
+            Err(e) => {
+                log::warn!("Ignoring {}: {}", SIMULATED_AUDIO_ENV, e);
+                None
+            }
+        }
+    }
+
+    /// One loop of the signal as mono samples at `sample_rate`
+    pub fn signal(&self, sample_rate: u32) -> Result<Vec<f32>> {
+        match self {
+            Self::File(path) => {
+                let samples = decode_audio_file(path)?;
+                if sample_rate == WHISPER_SAMPLE_RATE {
+                    return Ok(samples);
+                }
+                let mut resampler = FrameResampler::new(
+                    WHISPER_SAMPLE_RATE as usize,
+                    sample_rate as usize,
+                    CHUNK_DURATION,
+                );
+                let mut output = Vec::with_capacity(
+                    samples.len() * sample_rate as usize / WHISPER_SAMPLE_RATE as usize,
+                );
+                resampler.push(&samples, |frame| output.extend_from_slice(frame));
+                resampler.finish(|frame| output.extend_from_slice(frame));
+                Ok(output)
+            }
+            // A whole second, so whole-Hz tones loop without a click
+            Self::Tone { frequency } => Ok((0..sample_rate)
+                .map(|i| {
+                    let t = i as f32 / sample_rate as f32;
+                    (2.0 * std::f32::consts::PI * frequency * t).sin() * TONE_AMPLITUDE
+                })
+                .collect()),
+        }
+    }
+}
+
+/// Deliver `signal` to `sink` in a loop, one chunk at a time at the pace a
+/// device running at `sample_rate` would, until `stop` is set. Each chunk
+/// comes with when a device would have captured its first sample.
+pub fn spawn_player(
+    signal: Vec<f32>,
+    sample_rate: u32,
+    stop: Arc<AtomicBool>,
+    mut sink: impl FnMut(&[f32], Instant) + Send + 'static,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        if signal.is_empty() {
+            return;
+        }
+        let chunk_len =
+            ((sample_rate as f64 * CHUNK_DURATION.as_secs_f64()).round() as usize).max(1);
+        let started = Instant::now();
+        let mut position = 0;
+        let mut chunk = Vec::with_capacity(chunk_len);
+        let mut delivered = 0u32;
+
+        while !stop.load(Ordering::Relaxed) {
+            chunk.clear();
+            while chunk.len() < chunk_len {
+                let take = (chunk_len - chunk.len()).min(signal.len() - position);
+                chunk.extend_from_slice(&signal[position..position + take]);
+                position = (position + take) % signal.len();
+            }
+            sink(&chunk, started + CHUNK_DURATION * delivered);
+
+            // Sleep to an absolute deadline so the pace doesn't drift
+            delivered += 1;
+            let deadline = started + CHUNK_DURATION * delivered;
+            if let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+                thread::sleep(wait);
+            }
+        }
+    })
+}
+
+/// `SystemAudioCapture` playing a `SimulatedSource` at the system capture rate
+pub struct SimulatedAudioCapture {
+    source: SimulatedSource,
+    sample_buffer: Arc<Mutex<SampleRingBuffer>>,
+    stop: Arc<AtomicBool>,
+    player: Option<thread::JoinHandle<()>>,
+}
+
+impl SimulatedAudioCapture {
+    pub fn new(source: SimulatedSource) -> Self {
+        Self {
+            source,
+            sample_buffer: Arc::new(Mutex::new(SampleRingBuffer::default())),
+            stop: Arc::new(AtomicBool::new(false)),
+            player: None,
+        }
+    }
+
+    /// Limit how much unread audio is kept before the oldest samples are overwritten
+    pub fn with_max_buffer_seconds(self, seconds: u32) -> Self {
+        self.sample_buffer
+            .lock()
+            .unwrap()
+            .set_capacity(seconds as usize * SYSTEM_AUDIO_SAMPLE_RATE);
+        self
+    }
+}
+
+impl SystemAudioCapture for SimulatedAudioCapture {
+    fn start_capture(&mut self) -> Result<()> {
+        if self.player.is_some() {
+            return Ok(());
+        }
+        let signal = self.source.signal(SYSTEM_AUDIO_SAMPLE_RATE as u32)?;
+        log::info!(
+            "Simulated system audio from {:?} ({} samples per loop)",
+            self.source,
+            signal.len()
+        );
+
+        self.stop.store(false, Ordering::Relaxed);
+        let buffer = Arc::clone(&self.sample_buffer);
+        self.player = Some(spawn_player(
+            signal,
+            SYSTEM_AUDIO_SAMPLE_RATE as u32,
+            Arc::clone(&self.stop),
+            move |chunk, at| buffer.lock().unwrap().extend_at(chunk.iter().copied(), at),
+        ));
+        Ok(())
+    }
+
+    fn stop_capture(&mut self) -> Result<()> {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(player) = self.player.take() {
+            let _ = player.join();
+        }
+        self.sample_buffer.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn read_samples(&mut self) -> Result<Option<TimedSamples>> {
+        let samples = self.sample_buffer.lock().unwrap().drain_timed();
+        Ok((!samples.is_empty()).then_some(samples))
+    }
+
+    fn is_capturing(&self) -> bool {
+        self.player.is_some()
+    }
+
+    fn dropped_samples(&self) -> u64 {
+        self.sample_buffer.lock().unwrap().dropped_samples()
+    }
+
+    fn subscribe_frames(&self) -> Option<AudioFrameStream> {
+        Some(self.sample_buffer.lock().unwrap().subscribe())
+    }
+
+    fn deliver_samples(&mut self) -> Option<SampleReceiver> {
+        Some(self.sample_buffer.lock().unwrap().deliver())
+    }
+
+    fn format(&self) -> CaptureFormat {
+        self.sample_buffer.lock().unwrap().format()
+    }
+
+    fn peek_status(&self) -> CaptureStatus {
+        CaptureStatus::from_buffer(&self.sample_buffer.lock().unwrap())
+    }
+}
+
+impl Drop for SimulatedAudioCapture {
+    fn drop(&mut self) {
+        let _ = self.stop_capture();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(
+            SimulatedSource::parse("tone").unwrap(),
+            SimulatedSource::Tone { frequency: 440.0 }
+        );
+        assert_eq!(
+            SimulatedSource::parse("tone:1000").unwrap(),
+            SimulatedSource::Tone { frequency: 1000.0 }
+        );
+        assert_eq!(
+            SimulatedSource::parse("/tmp/speech.wav").unwrap(),
+            SimulatedSource::File(PathBuf::from("/tmp/speech.wav"))
+        );
+        assert!(SimulatedSource::parse("tone:-5").is_err());
+        assert!(SimulatedSource::parse("tone:abc").is_err());
+        assert!(SimulatedSource::parse(" ").is_err());
+    }
+
+    #[test]
+    fn test_capture_delivers_tone_in_real_time() {
+        let mut capture = SimulatedAudioCapture::new(SimulatedSource::Tone { frequency: 440.0 });
+        capture.start_capture().unwrap();
+        assert!(capture.is_capturing());
+        thread::sleep(Duration::from_millis(200));
+        let samples = capture.read_samples().unwrap().unwrap().samples;
+        capture.stop_capture().unwrap();
+
+        // About 200ms at 48kHz, allowing for scheduling jitter
+        let expected = SYSTEM_AUDIO_SAMPLE_RATE / 5;
+        assert!(
+            samples.len() >= expected / 2 && samples.len() <= expected * 2,
+            "got {} samples",
+            samples.len()
+        );
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!((peak - TONE_AMPLITUDE).abs() < 0.01);
+        assert!(!capture.is_capturing());
+        assert_eq!(capture.read_samples().unwrap(), None);
+    }
+}
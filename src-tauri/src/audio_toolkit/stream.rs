@@ -0,0 +1,124 @@
+//! Push-based fan-out of captured audio.
+//!
+//! Capture backends publish every chunk they receive to a `FrameBroadcaster`;
+//! consumers subscribe to get an independent async `Stream` of frames instead
+//! of polling `read_samples` and competing for the same buffer.
+
+use futures_util::Stream;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+/// Frames a subscriber may fall behind by before new frames are dropped for it
+const SUBSCRIBER_CAPACITY: usize = 256;
+
+/// A chunk of mono audio. Cloning is cheap; all subscribers share the samples.
+#[derive(Clone, Debug)]
+pub struct AudioFrame {
+    pub samples: Arc<[f32]>,
+    pub sample_rate: u32,
+    /// When the first sample was captured
+    pub captured_at: Instant,
+}
+
+pub struct FrameBroadcaster {
+    sample_rate: u32,
+    subscribers: Mutex<Vec<mpsc::Sender<AudioFrame>>>,
+    // Lets the capture callback skip all work while nobody is listening
+    has_subscribers: AtomicBool,
+}
+
+impl FrameBroadcaster {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            subscribers: Mutex::new(Vec::new()),
+            has_subscribers: AtomicBool::new(false),
+        }
+    }
+
+    /// Tag frames published from now on with `sample_rate`
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+    }
+
+    pub fn subscribe(&self) -> AudioFrameStream {
+        let (tx, rx) = mpsc::channel(SUBSCRIBER_CAPACITY);
+        self.subscribers.lock().unwrap().push(tx);
+        self.has_subscribers.store(true, Ordering::Relaxed);
+        AudioFrameStream { rx }
+    }
+
+    pub fn has_subscribers(&self) -> bool {
+        self.has_subscribers.load(Ordering::Relaxed)
+    }
+
+    /// Send `samples`, captured from `captured_at` on, to every subscriber.
+    /// Never blocks: a subscriber whose queue is full misses this frame.
+    pub fn publish(&self, samples: &[f32], captured_at: Instant) {
+        if !self.has_subscribers() || samples.is_empty() {
+            return;
+        }
+
+        let frame = AudioFrame {
+            samples: Arc::from(samples),
+            sample_rate: self.sample_rate,
+            captured_at,
+        };
+
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|tx| match tx.try_send(frame.clone()) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                log::debug!("Audio frame subscriber is lagging, dropping a frame");
+                true
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        });
+        self.has_subscribers
+            .store(!subscribers.is_empty(), Ordering::Relaxed);
+    }
+}
+
+/// Async stream of frames from one capture source. Ends when the source is
+/// dropped.
+pub struct AudioFrameStream {
+    rx: mpsc::Receiver<AudioFrame>,
+}
+
+impl Stream for AudioFrameStream {
+    type Item = AudioFrame;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<AudioFrame>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[test]
+    fn test_every_subscriber_gets_each_frame() {
+        let broadcaster = FrameBroadcaster::new(16000);
+        let mut a = broadcaster.subscribe();
+        let mut b = broadcaster.subscribe();
+
+        let captured_at = Instant::now();
+        broadcaster.publish(&[0.1, 0.2], captured_at);
+        drop(broadcaster);
+
+        tauri::async_runtime::block_on(async {
+            let frame = a.next().await.unwrap();
+            assert_eq!(&*frame.samples, &[0.1, 0.2]);
+            assert_eq!(frame.sample_rate, 16000);
+            assert_eq!(frame.captured_at, captured_at);
+            assert_eq!(&*b.next().await.unwrap().samples, &[0.1, 0.2]);
+            assert!(a.next().await.is_none());
+        });
+    }
+}
@@ -1,7 +1,56 @@
 // System Audio Capture trait
 // Platform-specific implementations provide system audio capture functionality
 
-use anyhow::Result;
+use crate::audio_toolkit::error::Result;
+use crate::audio_toolkit::ring_buffer::{
+    SampleReceiver, SampleRingBuffer, SYSTEM_AUDIO_SAMPLE_RATE,
+};
+use crate::audio_toolkit::stream::AudioFrameStream;
+use crate::audio_toolkit::timed::TimedSamples;
+use std::time::{Duration, Instant};
+
+/// Format of the device a capture records from. Samples are always handed
+/// out downmixed to mono, at `sample_rate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CaptureFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl Default for CaptureFormat {
+    fn default() -> Self {
+        Self {
+            sample_rate: SYSTEM_AUDIO_SAMPLE_RATE as u32,
+            channels: 1,
+        }
+    }
+}
+
+/// Snapshot of a capture's buffer, taken without consuming any samples
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CaptureStatus {
+    /// Audio waiting to be read
+    pub buffered: Duration,
+    /// Time since the backend last delivered audio, if it ever has
+    pub last_callback_age: Option<Duration>,
+    pub dropped_samples: u64,
+    pub format: CaptureFormat,
+    /// Capture time of the first audio in `format`, if the capture switched
+    /// to it from another rate
+    pub format_since: Option<Instant>,
+}
+
+impl CaptureStatus {
+    pub fn from_buffer(buffer: &SampleRingBuffer) -> Self {
+        Self {
+            buffered: buffer.buffered_duration(),
+            last_callback_age: buffer.last_write_age(),
+            dropped_samples: buffer.dropped_samples(),
+            format: buffer.format(),
+            format_since: buffer.format_since(),
+        }
+    }
+}
 
 /// Trait for system audio capture implementations
 pub trait SystemAudioCapture: Send + Sync {
@@ -12,11 +61,47 @@ pub trait SystemAudioCapture: Send + Sync {
     fn stop_capture(&mut self) -> Result<()>;
     
     /// Read available audio samples (non-blocking)
-    /// Returns None if no samples available, Some(Vec<f32>) with samples otherwise
-    fn read_samples(&mut self) -> Result<Option<Vec<f32>>>;
+    /// Returns None if no samples available, otherwise the samples with the
+    /// capture time of each chunk, so gaps from dropped buffers are visible
+    fn read_samples(&mut self) -> Result<Option<TimedSamples>>;
     
     /// Check if currently capturing
     fn is_capturing(&self) -> bool;
+
+    /// Number of samples overwritten because the consumer fell behind
+    fn dropped_samples(&self) -> u64 {
+        0
+    }
+
+    /// Sample rate and channel count of the device being captured. May change
+    /// while capturing, e.g. when the capture follows a new default output.
+    fn format(&self) -> CaptureFormat {
+        CaptureFormat::default()
+    }
+
+    /// Subscribe to captured audio as an async stream of mono frames at the
+    /// capture rate. Subscribers don't consume samples from `read_samples`.
+    fn subscribe_frames(&self) -> Option<AudioFrameStream> {
+        None
+    }
+
+    /// Take delivery of captured mono audio on a channel, so the
+    /// consumer can block on it instead of polling `read_samples`. Audio
+    /// buffered so far goes to the receiver first, and `read_samples` gets
+    /// nothing until the receiver is dropped. None if the backend can't.
+    fn deliver_samples(&mut self) -> Option<SampleReceiver> {
+        None
+    }
+
+    /// Buffer state for status queries. Unlike `read_samples` this leaves the
+    /// buffered audio for the caption loop.
+    fn peek_status(&self) -> CaptureStatus {
+        CaptureStatus {
+            dropped_samples: self.dropped_samples(),
+            format: self.format(),
+            ..CaptureStatus::default()
+        }
+    }
 }
 
 // macOS implementation
@@ -42,14 +127,16 @@ pub struct DummySystemAudio;
 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
 impl SystemAudioCapture for DummySystemAudio {
     fn start_capture(&mut self) -> Result<()> {
-        Err(anyhow::anyhow!("System audio capture not supported on this platform"))
+        Err(crate::audio_toolkit::AudioError::Backend(
+            "System audio capture not supported on this platform".to_string(),
+        ))
     }
     
     fn stop_capture(&mut self) -> Result<()> {
         Ok(())
     }
     
-    fn read_samples(&mut self) -> Result<Option<Vec<f32>>> {
+    fn read_samples(&mut self) -> Result<Option<TimedSamples>> {
         Ok(None)
     }
     
@@ -1,16 +1,30 @@
 // macOS System Audio Capture
-// Strategy 1: Try BlackHole virtual audio device (recommended - more reliable)
-// Strategy 2: Fallback to ScreenCaptureKit (requires macOS 13+ and Screen Recording permission)
+// Strategy 1: CoreAudio process tap (macOS 14.4+, needs no extra setup)
+// Strategy 2: Try BlackHole virtual audio device (reliable on older systems)
+// Strategy 3: Fallback to ScreenCaptureKit, in process (requires macOS 13+ and Screen Recording permission)
 
-use anyhow::{anyhow, Result};
-use std::collections::VecDeque;
-use std::io::{BufRead, Read};
-use std::process::{Child, Command, Stdio};
 use std::sync::{Arc, Mutex};
 use std::thread;
 
-use crate::audio_toolkit::system_audio::SystemAudioCapture;
-use crate::utils;
+use crate::audio_toolkit::audio::Downmix;
+use crate::audio_toolkit::blackhole;
+use crate::audio_toolkit::debug_log::{audio_debug_enabled, rms_and_peak, AUDIO_DEBUG_TARGET};
+use crate::audio_toolkit::error::{AudioError, Result};
+use crate::audio_toolkit::loopback_diagnosis::{
+    routes_to_loopback, ChannelProbe, LoopbackDiagnosis, LoopbackRouting, LOOPBACK_DIAGNOSIS_EVENT,
+};
+use crate::audio_toolkit::process_tap::{self, ProcessTap};
+use crate::audio_toolkit::ring_buffer::{
+    SampleReceiver, SampleRingBuffer, SYSTEM_AUDIO_SAMPLE_RATE,
+};
+use crate::audio_toolkit::sample_queue::{self, SampleProducer};
+use crate::audio_toolkit::screencapturekit::permissions::{
+    request_screen_recording_permission, supports_screencapturekit,
+};
+use crate::audio_toolkit::screencapturekit::{CaptureTarget, ScreenCaptureKitAudio};
+use crate::audio_toolkit::stream::AudioFrameStream;
+use crate::audio_toolkit::system_audio::{CaptureFormat, CaptureStatus, SystemAudioCapture};
+use crate::audio_toolkit::timed::{self, TimedSamples};
 use tauri::{AppHandle, Emitter};
 
 use cpal::{
@@ -18,16 +32,19 @@ use cpal::{
     Device, Sample, SizedSample,
 };
 
-/// macOS implementation - tries BlackHole first, then ScreenCaptureKit
+/// macOS implementation - tries a process tap first, then BlackHole, then ScreenCaptureKit
 pub struct MacOSSystemAudio {
     is_capturing: bool,
     permission_denied: bool, // Track if permission was denied
-    sample_buffer: Arc<Mutex<VecDeque<f32>>>,
-    capture_process: Option<Child>,
+    sample_buffer: Arc<Mutex<SampleRingBuffer>>,
+    screencapturekit: Option<ScreenCaptureKitAudio>, // Running ScreenCaptureKit stream, when that strategy is in use
     app_handle: AppHandle,
     use_blackhole: bool, // Whether we're using BlackHole or ScreenCaptureKit
     blackhole_thread: Option<thread::JoinHandle<()>>, // Thread that keeps BlackHole stream alive
     blackhole_stop_tx: Option<std::sync::mpsc::Sender<()>>, // Channel to signal stop
+    downmix: Downmix,    // How BlackHole's channels are reduced to mono
+    process_tap: Option<ProcessTap>, // Running process tap, when that strategy is in use
+    capture_target: Option<CaptureTarget>, // Picked display, window or app for ScreenCaptureKit
 }
 
 impl MacOSSystemAudio {
@@ -35,116 +52,86 @@ impl MacOSSystemAudio {
         Ok(Self {
             is_capturing: false,
             permission_denied: false,
-            sample_buffer: Arc::new(Mutex::new(VecDeque::new())),
-            capture_process: None,
+            sample_buffer: Arc::new(Mutex::new(SampleRingBuffer::default())),
+            screencapturekit: None,
             app_handle: app.clone(),
             use_blackhole: false,
             blackhole_thread: None,
             blackhole_stop_tx: None,
+            downmix: Downmix::default(),
+            process_tap: None,
+            capture_target: None,
         })
     }
     
+    /// Limit how much unread audio is kept before the oldest samples are overwritten
+    pub fn with_max_buffer_seconds(self, seconds: u32) -> Self {
+        self.sample_buffer
+            .lock()
+            .unwrap()
+            .set_capacity(seconds as usize * SYSTEM_AUDIO_SAMPLE_RATE);
+        self
+    }
+
+    /// Reduce BlackHole's or the process tap's channels to mono with
+    /// `downmix` instead of averaging them
+    pub fn with_downmix(mut self, downmix: Downmix) -> Self {
+        self.downmix = downmix;
+        self
+    }
+
+    /// Capture only `target`, picked with the content-sharing picker. Only
+    /// ScreenCaptureKit can narrow capture down, so the other strategies are
+    /// skipped while a target is set
+    pub fn with_capture_target(mut self, target: Option<CaptureTarget>) -> Self {
+        self.capture_target = target;
+        self
+    }
+
     pub fn is_permission_denied(&self) -> bool {
         self.permission_denied
     }
+
+    /// Name of the strategy in use, for logs
+    fn capture_method(&self) -> &'static str {
+        if self.process_tap.is_some() {
+            "Process Tap"
+        } else if self.use_blackhole {
+            "BlackHole"
+        } else {
+            "ScreenCaptureKit"
+        }
+    }
     
-    /// Try to find BlackHole device
-    /// Also tries to find any input device that might have system audio
+    /// Find the BlackHole input device. When it is missing the settings UI
+    /// offers a guided install instead of instructions in the log
     fn find_blackhole_device() -> Option<Device> {
-        let host = crate::audio_toolkit::get_cpal_host();
-        log::info!("🔍 [SystemAudio] Enumerating input devices to find system audio source...");
-        
-        // Also check default input device
-        if let Some(default_input) = host.default_input_device() {
-            if let Ok(name) = default_input.name() {
-                log::info!("🔍 [SystemAudio] Default input device: {}", name);
-            }
-        }
-        
-        // Also check output devices (for debugging)
-        log::info!("🔍 [BlackHole] Enumerating output devices...");
-        if let Ok(output_devices) = host.output_devices() {
-            let mut output_list = Vec::new();
-            for device in output_devices {
-                if let Ok(name) = device.name() {
-                    output_list.push(name.clone());
-                    log::info!("🔍 [BlackHole] Found output device: {}", name);
-                }
+        // Where the OS is playing to decides whether the loopback device hears
+        // anything; problems are reported as a LoopbackDiagnosis if capture is silent
+        match Self::default_output() {
+            Some((name, _)) if routes_to_loopback(&name) => {
+                log::info!("✅ [SystemAudio] Default OUTPUT is '{}' - audio should be routed correctly", name);
             }
-            log::info!("📋 [BlackHole] All output devices: {:?}", output_list);
-            
-            // Check default output - CRITICAL: Audio must be routed here for capture to work
-            if let Some(default_output) = host.default_output_device() {
-                if let Ok(name) = default_output.name() {
-                    log::info!("🔍 [SystemAudio] Default OUTPUT device: {}", name);
-                    if !name.contains("BlackHole") && !name.contains("blackhole") && !name.contains("Multi-Output") {
-                        log::error!("❌ [SystemAudio] ⚠️⚠️⚠️ CRITICAL: Default OUTPUT is NOT BlackHole or Multi-Output Device!");
-                        log::error!("❌ [SystemAudio] Current output: '{}'", name);
-                        log::error!("❌ [SystemAudio] Audio from Chrome/system will NOT be routed to BlackHole!");
-                        log::error!("❌ [SystemAudio] This is why you're seeing RMS: 0.000000 (silence)");
-                        log::warn!("💡 [SystemAudio] ⭐ RECOMMENDED SOLUTION: Create Multi-Output Device (giữ audio từ speakers + capture)");
-                        log::warn!("   📋 Steps:");
-                        log::warn!("   1. Mở Audio MIDI Setup (Applications > Utilities > Audio MIDI Setup)");
-                        log::warn!("   2. Click '+' button ở bottom-left, chọn 'Create Multi-Output Device'");
-                        log::warn!("   3. Trong Multi-Output Device:");
-                        log::warn!("      ✅ Check box cho 'BlackHole 2ch'");
-                        log::warn!("      ✅ Check box cho '{}' (speakers hiện tại)", name);
-                        log::warn!("      ⚠️ Đảm bảo Master Device là '{}' (để volume control hoạt động)", name);
-                        log::warn!("   4. System Settings > Sound > Output: Chọn Multi-Output Device vừa tạo");
-                        log::warn!("   ✅ Kết quả: Audio vẫn phát từ speakers VÀ được capture bởi app!");
-                        log::warn!("");
-                        log::warn!("   ⚠️ Alternative (KHÔNG KHUYẾN NGHỊ): Chọn 'BlackHole 2ch' trực tiếp");
-                        log::warn!("      ❌ Bạn sẽ KHÔNG nghe được âm thanh từ speakers!");
-                        log::warn!("      ✅ Chỉ dùng nếu bạn không cần nghe audio (ví dụ: chỉ cần transcription)");
-                    } else {
-                        log::info!("✅ [SystemAudio] Default OUTPUT is '{}' - audio should be routed correctly", name);
-                        if name.contains("Multi-Output") {
-                            log::info!("✅ [SystemAudio] Multi-Output Device detected - audio will route to both speakers and BlackHole");
-                        }
-                    }
-                }
-            } else {
-                log::warn!("⚠️ [SystemAudio] Could not get default output device");
+            Some((name, _)) => {
+                log::warn!("⚠️ [SystemAudio] Default OUTPUT '{}' is not BlackHole or a Multi-Output Device - capture will be silent", name);
             }
+            None => log::warn!("⚠️ [SystemAudio] Could not get default output device"),
         }
-        
-        if let Ok(devices) = host.input_devices() {
-            let mut device_list = Vec::new();
-            let mut blackhole_device: Option<Device> = None;
-            
-            for device in devices {
-                if let Ok(name) = device.name() {
-                    device_list.push(name.clone());
-                    log::info!("🔍 [SystemAudio] Found input device: {}", name);
-                    
-                    // Priority 1: BlackHole (best for system audio)
-                    if name.contains("BlackHole") || name.contains("blackhole") {
-                        log::info!("✅ [SystemAudio] Found BlackHole device: {}", name);
-                        blackhole_device = Some(device);
-                        break; // Use first BlackHole found
-                    }
-                }
-            }
-            
-            log::info!("📋 [SystemAudio] All input devices: {:?}", device_list);
-            
-            if blackhole_device.is_some() {
-                return blackhole_device;
-            }
-            
-            // If no BlackHole found, log all devices for debugging
-            log::warn!("⚠️ [SystemAudio] BlackHole device not found in input devices.");
-            log::info!("💡 [SystemAudio] Available input devices: {:?}", device_list);
-            log::info!("💡 [SystemAudio] To capture system audio, you need:");
-            log::info!("   1. Install BlackHole: brew install blackhole-2ch");
-            log::info!("   2. Configure Sound Output to route audio to BlackHole");
-            log::info!("   3. Or create Multi-Output Device (BlackHole + Speakers)");
-        } else {
-            log::warn!("⚠️ [SystemAudio] Failed to enumerate input devices");
+
+        let device = blackhole::find_blackhole_input();
+        match device.as_ref().and_then(|d| d.name().ok()) {
+            Some(name) => log::info!("✅ [SystemAudio] Found BlackHole device: {}", name),
+            None => log::info!("⚠️ [SystemAudio] BlackHole is not installed. Will try ScreenCaptureKit."),
         }
-        
-        log::info!("⚠️ [SystemAudio] No suitable input device found. Will try ScreenCaptureKit.");
-        None
+        device
+    }
+    
+    /// Name and sample rate of the current default output device
+    fn default_output() -> Option<(String, Option<u32>)> {
+        let device = crate::audio_toolkit::get_cpal_host().default_output_device()?;
+        let name = device.name().ok()?;
+        let rate = device.default_output_config().ok().map(|c| c.sample_rate().0);
+        Some((name, rate))
     }
     
     /// Start capture from BlackHole device
@@ -153,36 +140,39 @@ impl MacOSSystemAudio {
         let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
         log::info!("🎯 Starting capture from device: {}", device_name);
         
-        let config = device.default_input_config()
-            .map_err(|e| anyhow!("Failed to get device config for {}: {}", device_name, e))?;
+        let config = device.default_input_config()?;
         
         let sample_rate = config.sample_rate().0;
         let channels = config.channels() as usize;
         
         log::info!("📊 Device config ({}): sample_rate={}, channels={}, format={:?}", 
             device_name, sample_rate, channels, config.sample_format());
-        let _ = self.app_handle.emit("log-update", format!(
-            "📊 [BlackHole] Device: {}, Rate: {}Hz, Channels: {}, Format: {:?}", 
-            device_name, sample_rate, channels, config.sample_format()
-        ));
         
+        self.sample_buffer.lock().unwrap().set_format(CaptureFormat {
+            sample_rate,
+            channels: config.channels(),
+        });
         let buffer = self.sample_buffer.clone();
-        let app_handle = self.app_handle.clone();
+        let probe = Arc::new(Mutex::new(ChannelProbe::default()));
+        let stream_probe = probe.clone();
+        let sample_format = config.sample_format();
+        let downmix = self.downmix;
         
         // Create stream in thread worker (like AudioRecorder does)
         // This avoids Send issues since stream stays in the thread
         let (tx, rx) = std::sync::mpsc::channel();
         let stream_handle = thread::spawn(move || {
             // Build and start stream in this thread
+            let samples = sample_queue::feed(&buffer);
             let stream_result: Result<cpal::Stream, cpal::BuildStreamError> = match config.sample_format() {
                 cpal::SampleFormat::F32 => {
-                    Self::build_blackhole_stream_in_thread::<f32>(&device, &config, buffer.clone(), channels, app_handle.clone())
+                    Self::build_blackhole_stream_in_thread::<f32>(&device, &config, samples, stream_probe.clone(), channels, downmix)
                 }
                 cpal::SampleFormat::I16 => {
-                    Self::build_blackhole_stream_in_thread::<i16>(&device, &config, buffer.clone(), channels, app_handle.clone())
+                    Self::build_blackhole_stream_in_thread::<i16>(&device, &config, samples, stream_probe.clone(), channels, downmix)
                 }
                 cpal::SampleFormat::I32 => {
-                    Self::build_blackhole_stream_in_thread::<i32>(&device, &config, buffer.clone(), channels, app_handle.clone())
+                    Self::build_blackhole_stream_in_thread::<i32>(&device, &config, samples, stream_probe.clone(), channels, downmix)
                 }
                 _ => {
                     log::error!("Unsupported BlackHole sample format: {:?}", config.sample_format());
@@ -193,14 +183,11 @@ impl MacOSSystemAudio {
             match stream_result {
                 Ok(stream) => {
                     log::info!("✅✅✅ [BlackHole] Stream created successfully! Waiting for callbacks...");
-                    let _ = app_handle.emit("log-update", "✅ [BlackHole] Stream created - waiting for audio callbacks...");
                     if let Err(e) = stream.play() {
                         log::error!("❌ [BlackHole] Failed to play stream: {}", e);
-                        let _ = app_handle.emit("log-update", format!("❌ [BlackHole] Failed to play stream: {}", e));
                         return;
                     }
                     log::info!("✅✅✅ [BlackHole] Stream started (playing) - callbacks should start now!");
-                    let _ = app_handle.emit("log-update", "✅ [BlackHole] Stream playing - callbacks should start!");
                     
                     // Keep stream alive - wait for stop signal
                     let _stream = stream; // Stream stays alive as long as this variable exists
@@ -253,9 +240,6 @@ impl MacOSSystemAudio {
                     
                     if rms > 0.00001 {
                         log::info!("✅ [SystemAudio] ✅✅✅ AUDIO DETECTED! RMS: {:.6}, Max: {:.6}", rms, max_amp);
-                        let _ = self.app_handle.emit("log-update", format!(
-                            "✅✅✅ [SystemAudio] AUDIO DETECTED! RMS: {:.6}, Max: {:.6} - Live caption will start working now!", rms, max_amp
-                        ));
                         audio_detected = true;
                         break;
                     }
@@ -267,16 +251,27 @@ impl MacOSSystemAudio {
         }
         
         if !audio_detected {
-            log::warn!("⚠️ [SystemAudio] No audio detected after 5s from device: {}", device_name);
-            log::warn!("⚠️ [SystemAudio] Max RMS seen: {:.6}, Max amplitude seen: {:.6}", max_rms_seen, max_amp_seen);
-            log::warn!("⚠️ [SystemAudio] User may need to configure Sound Output to route audio to this device");
-            log::warn!("⚠️ [SystemAudio] Will continue monitoring - audio may start later when user configures output");
+            log::warn!("⚠️ [SystemAudio] No audio detected after 5s from device: {} (max RMS: {:.6}, max amplitude: {:.6})",
+                device_name, max_rms_seen, max_amp_seen);
             
-            // Emit detailed log to frontend
-            let _ = self.app_handle.emit("log-update", format!(
-                "⚠️ [SystemAudio] No audio detected from {}. Max RMS: {:.6}. Please configure Sound Output to route audio to this device.", 
-                device_name, max_rms_seen
-            ));
+            let (default_output, output_sample_rate) = match Self::default_output() {
+                Some((name, rate)) => (Some(name), rate),
+                None => (None, None),
+            };
+            let routing = LoopbackRouting {
+                device: device_name.clone(),
+                sample_rate,
+                channels,
+                sample_format: format!("{:?}", sample_format),
+                default_output,
+                output_sample_rate,
+            };
+            let diagnosis = LoopbackDiagnosis::new(routing, &probe.lock().unwrap());
+            if let Some(check) = diagnosis.first_failure() {
+                log::warn!("⚠️ [SystemAudio] Loopback diagnosis: '{}' failed - {}", 
+                    check.label, check.hint.as_deref().unwrap_or_default());
+            }
+            let _ = self.app_handle.emit(LOOPBACK_DIAGNOSIS_EVENT, diagnosis);
         }
         
         Ok(audio_detected)
@@ -285,9 +280,10 @@ impl MacOSSystemAudio {
     fn build_blackhole_stream_in_thread<T>(
         device: &Device,
         config: &cpal::SupportedStreamConfig,
-        buffer: Arc<Mutex<VecDeque<f32>>>,
+        mut samples: SampleProducer,
+        probe: Arc<Mutex<ChannelProbe>>,
         channels: usize,
-        app_handle: AppHandle,
+        downmix: Downmix,
     ) -> Result<cpal::Stream, cpal::BuildStreamError>
     where
         T: Sample + SizedSample + Send + 'static,
@@ -295,106 +291,46 @@ impl MacOSSystemAudio {
     {
         let mut callback_count = 0u64;
         log::info!("🔧 [BlackHole] Creating stream callback function...");
-        let stream_cb = move |data: &[T], _info: &cpal::InputCallbackInfo| {
+        let stream_cb = move |data: &[T], info: &cpal::InputCallbackInfo| {
             callback_count += 1;
             
             // CRITICAL: Always log first callback to confirm it's being called
             if callback_count == 1 {
                 log::info!("🎉 [BlackHole] ✅✅✅ FIRST CALLBACK RECEIVED! Callback #1: {} samples", data.len());
-                let _ = app_handle.emit("log-update", format!("🎉 [BlackHole] First callback received: {} samples", data.len()));
             }
             
-            let mut buf = buffer.lock().unwrap();
-            
-            // CRITICAL: Log EVERY callback for first 50 to catch any issues
-            // Then log every 50th callback for continuous monitoring
-            let should_log = callback_count <= 50 || callback_count % 50 == 0;
-            
-            if should_log {
-                let rms = if data.is_empty() {
-                    0.0
-                } else {
-                    let sum_sq: f32 = data.iter()
-                        .map(|&s| {
-                            let f: f32 = s.to_sample();
-                            f * f
-                        })
-                        .sum();
-                    (sum_sq / data.len() as f32).sqrt()
-                };
-                let max_amp = data.iter()
-                    .map(|&s| s.to_sample::<f32>().abs())
-                    .fold(0.0f32, |a, b| a.max(b));
-                
-                // Debug: Check first few raw samples to see what we're getting
+            probe
+                .lock()
+                .unwrap()
+                .observe(data.iter().map(|&s| s.to_sample::<f32>()), channels);
+
+            // Per-callback statistics only when debug audio logging is on
+            let debug = audio_debug_enabled();
+            if debug && (callback_count <= 50 || callback_count % 50 == 0) {
+                let (rms, max_amp) = rms_and_peak(data.iter().map(|&s| s.to_sample::<f32>()));
                 let first_samples: Vec<f32> = data.iter().take(10).map(|&s| s.to_sample::<f32>()).collect();
-                let min_sample = first_samples.iter().fold(0.0f32, |a, &b| a.min(b.abs()));
-                let max_sample = first_samples.iter().fold(0.0f32, |a, &b| a.max(b.abs()));
-                
-                // Log with timestamp and detailed info
-                let timestamp = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis();
-                log::info!("🎵 [BlackHole] Callback #{} (t={}ms): {} samples, RMS: {:.9}, Max: {:.9}, Range: [{:.9}, {:.9}], First 10: {:?}", 
-                    callback_count, timestamp, data.len(), rms, max_amp, min_sample, max_sample, first_samples);
-                
-                // Also emit to frontend for first 10 callbacks
-                if callback_count <= 10 {
-                    let _ = app_handle.emit("log-update", format!(
-                        "🎵 [BlackHole] Callback #{}: {} samples, RMS: {:.6}, Max: {:.6}", 
-                        callback_count, data.len(), rms, max_amp
-                    ));
-                }
-                
-                // If all samples are zero, log warnings at key points
-                if max_amp < 0.00001 {
-                    if callback_count == 5 {
-                        log::warn!("⚠️ [BlackHole] ⚠️⚠️⚠️ All samples are ZERO at callback #5!");
-                        log::warn!("⚠️ [BlackHole] This means BlackHole is NOT receiving audio from system.");
-                        log::warn!("⚠️ [BlackHole] Check: 1) System Settings > Sound > Output = Multi-Output Device?");
-                        log::warn!("⚠️ [BlackHole] Check: 2) Is audio actually playing from Chrome/app?");
-                        log::warn!("⚠️ [BlackHole] Check: 3) Multi-Output Device includes BlackHole 2ch?");
-                    } else if callback_count == 20 {
-                        log::error!("❌ [BlackHole] ❌❌❌ Still ZERO after 20 callbacks! BlackHole definitely not receiving audio!");
-                        log::error!("❌ [BlackHole] ACTION REQUIRED: Configure Sound Output to Multi-Output Device with BlackHole");
-                    }
-                } else {
-                    // Audio detected!
-                    if callback_count <= 5 {
-                        log::info!("✅✅✅ [BlackHole] AUDIO DETECTED! RMS: {:.9}, Max: {:.9}", rms, max_amp);
-                    }
-                }
+                log::debug!(
+                    target: AUDIO_DEBUG_TARGET,
+                    "🎵 [BlackHole] Callback #{}: {} samples, RMS: {:.9}, Max: {:.9}, First 10: {:?}",
+                    callback_count, data.len(), rms, max_amp, first_samples
+                );
             }
-            
+
+            samples.stamp(timed::cpal_capture_time(info));
             if channels == 1 {
-                buf.extend(data.iter().map(|&sample| sample.to_sample::<f32>()));
+                samples.push_iter(data.iter().map(|&sample| sample.to_sample::<f32>()));
             } else {
                 // Convert to mono
-                for frame in data.chunks_exact(channels) {
-                    let mono_sample = frame
-                        .iter()
-                        .map(|&sample| sample.to_sample::<f32>())
-                        .sum::<f32>()
-                        / channels as f32;
-                    buf.push_back(mono_sample);
-                }
+                samples.push_iter(data.chunks_exact(channels).map(|frame| downmix.mix(frame)));
             }
-            
-            // Log periodically (every 1000 callbacks = ~20 seconds at 48kHz)
-            if callback_count % 1000 == 0 {
-                let buf_size = buf.len();
-                // Calculate RMS of recent samples for logging
-                let recent_samples: Vec<f32> = buf.iter().rev().take(48000).cloned().collect(); // Last 1 second
-                let rms = if !recent_samples.is_empty() {
-                    let sum_sq: f32 = recent_samples.iter().map(|&s| s * s).sum();
-                    (sum_sq / recent_samples.len() as f32).sqrt()
-                } else {
-                    0.0
-                };
-                let max_amp = recent_samples.iter().map(|&s| s.abs()).fold(0.0f32, |a, b| a.max(b));
-                log::info!("📊 [BlackHole] Callback #{}: Buffer size: {} samples ({}s), RMS: {:.6}, Max: {:.6}", 
-                    callback_count, buf_size, buf_size as f32 / 48000.0, rms, max_amp);
+
+            // Queue status every 1000 callbacks (~20 seconds at 48kHz)
+            if debug && callback_count % 1000 == 0 {
+                log::debug!(
+                    target: AUDIO_DEBUG_TARGET,
+                    "📊 [BlackHole] Callback #{}: Queue has room for {} samples",
+                    callback_count, samples.free()
+                );
             }
         };
         
@@ -417,8 +353,35 @@ impl SystemAudioCapture for MacOSSystemAudio {
             std::thread::sleep(std::time::Duration::from_millis(200));
         }
 
-        // Strategy 1: Try BlackHole first (more reliable)
-        if let Some(blackhole_device) = Self::find_blackhole_device() {
+        // A picked target can only be captured with ScreenCaptureKit
+        let whole_system = self.capture_target.is_none();
+
+        // Strategy 1: Process tap, on macOS 14.4+
+        if whole_system && process_tap::is_supported() {
+            match ProcessTap::start(&self.sample_buffer, self.downmix) {
+                Ok(tap) => {
+                    self.process_tap = Some(tap);
+                    self.is_capturing = true;
+                    log::info!("✅ Using a CoreAudio process tap for system audio capture");
+                    return Ok(());
+                }
+                Err(e) => {
+                    log::warn!(
+                        "⚠️  Failed to start the process tap: {}. Falling back to BlackHole.",
+                        e
+                    );
+                }
+            }
+        }
+
+        // Strategy 2: Try BlackHole (more reliable than ScreenCaptureKit), unless
+        // nothing plays into it and ScreenCaptureKit can hear the output directly
+        let blackhole_device = Self::find_blackhole_device().filter(|_| {
+            whole_system
+                && (Self::default_output().is_some_and(|(name, _)| routes_to_loopback(&name))
+                    || !supports_screencapturekit())
+        });
+        if let Some(blackhole_device) = blackhole_device {
             match self.start_blackhole_capture(blackhole_device) {
                 Ok(true) => {
                     log::info!("✅ Using BlackHole for system audio capture (audio detected)");
@@ -426,8 +389,7 @@ impl SystemAudioCapture for MacOSSystemAudio {
                 }
                 Ok(false) => {
                     log::warn!("⚠️  BlackHole started but no audio detected initially.");
-                    log::info!("💡 [BlackHole] Keeping BlackHole running - will monitor for audio");
-                    log::info!("💡 [BlackHole] User can configure Sound Output to 'BlackHole 2ch' and audio will start flowing");
+                    log::info!("💡 [BlackHole] Keeping BlackHole running - see the loopback diagnosis for what to fix");
                     
                     // Try to open System Settings to help user configure
                     log::info!("💡 [BlackHole] Attempting to open System Settings > Sound...");
@@ -435,11 +397,6 @@ impl SystemAudioCapture for MacOSSystemAudio {
                         .args(["-b", "com.apple.systempreferences", "com.apple.preference.sound"])
                         .output();
                     
-                    // Emit log event to frontend
-                    let _ = self.app_handle.emit("log-update", format!(
-                        "⚠️ [BlackHole] No audio detected. Please set Sound Output to 'BlackHole 2ch' in System Settings > Sound > Output. App will continue monitoring for audio."
-                    ));
-                    
                     // Keep BlackHole running - don't stop it
                     // Audio may start when user configures Sound Output
                     log::info!("✅ [BlackHole] Keeping capture active - monitoring for audio...");
@@ -451,219 +408,37 @@ impl SystemAudioCapture for MacOSSystemAudio {
             }
         }
         
-        // Strategy 2: Fallback to ScreenCaptureKit
+        // Strategy 3: Fallback to ScreenCaptureKit, captured in this process
+        if !supports_screencapturekit() {
+            log::warn!("⚠️ ScreenCaptureKit needs macOS 13+ - install BlackHole for system audio");
+            return Err(AudioError::DeviceNotFound(
+                "System Audio Capture failed. Please install BlackHole and set up its Multi-Output Device, or upgrade to macOS 13+ for ScreenCaptureKit support.".to_string()
+            ));
+        }
         log::info!("🔄 Falling back to ScreenCaptureKit...");
-        
-        // Try to start ScreenCaptureKit helper binary
-        // First check in app bundle Resources (for production builds)
-        let exe_path = std::env::current_exe()?;
-        let mut possible_path = exe_path.clone();
-        possible_path.pop(); // MacOS/
-        possible_path.pop(); // Contents/
-        possible_path.push("Resources/bin/macos-audio-capture");
-        
-        if !possible_path.exists() {
-            // Try from current directory (for dev builds)
-            let bin_path = std::env::current_dir()?;
-            possible_path = bin_path.clone();
-            possible_path.push("bin/macos-audio-capture");
-
-            if !possible_path.exists() {
-                // Try src-tauri/bin (if running from root)
-                possible_path = bin_path.clone();
-                possible_path.push("src-tauri/bin/macos-audio-capture");
-            }
 
-            if !possible_path.exists() {
-                // Try one level up (if running from target/debug/...)
-                let mut bin_path2 = bin_path.clone();
-                bin_path2.pop();
-                possible_path = bin_path2;
-                possible_path.push("src-tauri/bin/macos-audio-capture");
+        let mut capture = ScreenCaptureKitAudio::new(&self.app_handle)?
+            .with_downmix(self.downmix)
+            .with_sample_buffer(self.sample_buffer.clone())
+            .with_target(self.capture_target.clone());
+        match capture.start_capture() {
+            Ok(()) => {
+                // SystemAudioStatus looks for "PERMISSION GRANTED" in the log
+                log::info!("✅ PERMISSION GRANTED - Using ScreenCaptureKit for system audio capture");
+                self.screencapturekit = Some(capture);
+                self.is_capturing = true;
+                self.permission_denied = false;
+                Ok(())
             }
-        }
-
-        if possible_path.exists() {
-            log::info!("Starting ScreenCaptureKit helper: {:?}", possible_path);
-
-            match Command::new(&possible_path)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped()) // Capture stderr for logs
-                .spawn()
-            {
-                Ok(mut child) => {
-                    let stdout = child.stdout.take().unwrap();
-                    let stderr = child.stderr.take().unwrap();
-                    let buffer = self.sample_buffer.clone();
-                    let app_handle_audio = self.app_handle.clone();
-                    let app_handle_log = self.app_handle.clone();
-
-                    // Thread to read audio data
-                    thread::spawn(move || {
-                        let mut reader = std::io::BufReader::new(stdout);
-                        let mut bytes = [0u8; 4096];
-                        let mut chunks_read = 0;
-
-                        loop {
-                            match reader.read(&mut bytes) {
-                                Ok(n) if n > 0 => {
-                                    chunks_read += 1;
-                                    // Log first chunk and periodically
-                                    if chunks_read == 1 {
-                                        log::info!("🎉 [SystemCapture] ✅ First audio data received from SCK helper! ({} bytes)", n);
-                                    }
-                                    // Log every 100 chunks for debugging (more frequent)
-                                    if chunks_read % 100 == 0 {
-                                        log::info!("📥 [SystemCapture] Received {} chunks from helper (last chunk: {} bytes)", chunks_read, n);
-                                    }
-
-                                           // Convert bytes to f32 (Little Endian)
-                                           let float_count = n / 4;
-                                           let mut floats = Vec::with_capacity(float_count);
-                                           let mut sum_sq = 0.0;
-
-                                           for i in 0..float_count {
-                                               let start = i * 4;
-                                               let end = start + 4;
-                                               // Safety: bytes buffer is large enough
-                                               if end <= n {
-                                                   let val = f32::from_le_bytes(
-                                                       bytes[start..end].try_into().unwrap(),
-                                                   );
-                                                   floats.push(val);
-                                                   sum_sq += val * val;
-                                               }
-                                           }
-                                           
-                                           // Log first chunk and periodically
-                                           if chunks_read == 1 {
-                                               log::info!("📊 [SystemCapture] First chunk: {} floats, buffer size: {}", float_count, buffer.lock().unwrap().len());
-                                           }
-                                           // Log every 100 chunks for debugging
-                                           if chunks_read % 100 == 0 {
-                                               let buffer_size = buffer.lock().unwrap().len();
-                                               log::info!("📊 [SystemCapture] Added {} floats to buffer (total: {}, chunks: {})", float_count, buffer_size, chunks_read);
-                                           }
-
-                                    // Emit levels for visualization
-                                    if float_count > 0 {
-                                        let rms = (sum_sq / float_count as f32).sqrt();
-                                        // Scale up a bit for better visibility
-                                        let level = (rms * 5.0).min(1.0);
-                                        utils::emit_levels(&app_handle_audio, &vec![level]);
-                                    }
-
-                                    let mut guard = buffer.lock().unwrap();
-                                    guard.extend(floats);
-                                }
-                                Ok(_) => break, // EOF
-                                Err(_) => break,
-                            }
-                        }
-                        log::info!("SCK helper stdout closed");
-                    });
-
-                    // Thread to read logs from stderr
-                    let app_handle_open_settings = self.app_handle.clone();
-                    let app_handle_log_clone = app_handle_log.clone(); // Clone for this thread
-                    thread::spawn(move || {
-                        let mut reader = std::io::BufReader::new(stderr);
-                        let mut line = String::new();
-                        let mut permission_denied_detected = false;
-                        while reader.read_line(&mut line).unwrap_or(0) > 0 {
-                            let log_line = format!("[SCK Helper] {}", line.trim());
-                            log::info!("{}", log_line);
-                            
-                            // Check for permission denied and auto-open System Settings
-                            if (log_line.contains("PERMISSION DENIED") || 
-                                log_line.contains("❌ PERMISSION DENIED") ||
-                                log_line.contains("declined TCCs")) && !permission_denied_detected {
-                                permission_denied_detected = true;
-                                log::warn!("⚠️ Permission denied detected! Auto-opening System Settings...");
-                                
-                                // Mark permission as denied in the struct (need to use Arc<Mutex> for this)
-                                // For now, we'll handle this in get_system_audio_status by checking process state
-                                
-                                // Auto-open System Settings on macOS
-                                #[cfg(target_os = "macos")]
-                                {
-                                    use std::process::Command;
-                                    let _ = Command::new("open")
-                                        .arg("x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture")
-                                        .spawn();
-                                    log::info!("✅ Opened System Settings > Privacy & Security > Screen Recording");
-                                    let _ = app_handle_open_settings.emit("log-update", "✅ [System] Opened System Settings - Please grant Screen Recording permission for Terminal or Handy".to_string());
-                                }
-                            }
-                            
-                            // Emit log to frontend for SystemAudioStatus component
-                            // Use clone to ensure we can emit from this thread
-                            if let Err(e) = app_handle_log_clone.emit("log-update", log_line.clone()) {
-                                log::warn!("Failed to emit log-update event: {}", e);
-                            }
-                            line.clear();
-                        }
-                    });
-
-                    // Store process first
-                    self.capture_process = Some(child);
-                    
-                    // Check if process exited immediately (likely permission denied)
-                    // Give it a moment to start, then check
-                    std::thread::sleep(std::time::Duration::from_millis(2000));
-                    
-                    // Check process status
-                    if let Some(ref mut proc) = self.capture_process {
-                        match proc.try_wait() {
-                            Ok(Some(status)) => {
-                                // Process exited - likely permission denied
-                                log::warn!("❌ SCK helper process exited with status: {:?} - Permission likely denied", status);
-                                self.permission_denied = true;
-                                self.is_capturing = false;
-                                self.capture_process = None;
-                                return Err(anyhow!("Screen Recording permission denied. Please grant permission in System Settings > Privacy & Security > Screen Recording for Terminal (dev) or Handy (production)."));
-                            },
-                            Ok(None) => {
-                                // Process still running - good!
-                                log::info!("✅ SCK helper process is running - permission granted");
-                                self.is_capturing = true;
-                                self.permission_denied = false;
-                                return Ok(());
-                            },
-                            Err(e) => {
-                                log::error!("Failed to check process status: {}", e);
-                                // Assume it's running if we can't check
-                                self.is_capturing = true;
-                                self.permission_denied = false;
-                                return Ok(());
-                            }
-                        }
-                    } else {
-                        return Err(anyhow!("Failed to store SCK helper process"));
-                    }
-                }
-                Err(e) => {
-                    log::error!("Failed to spawn SCK helper: {}", e);
-                    // Check if it's architecture mismatch (os error 86)
-                    if e.to_string().contains("86") || e.to_string().contains("Bad CPU type") {
-                        log::warn!("⚠️ SCK helper binary architecture mismatch - binary may be compiled for different CPU architecture");
-                        log::warn!("💡 This is normal on macOS < 13 which doesn't support ScreenCaptureKit anyway");
-                        log::warn!("💡 Please use BlackHole instead by installing: brew install blackhole-2ch");
-                    }
+            Err(e) => {
+                if let AudioError::PermissionDenied(_) = e {
+                    log::warn!("❌ PERMISSION DENIED - Screen Recording permission is required, opening System Settings");
+                    self.permission_denied = true;
+                    request_screen_recording_permission();
                 }
+                Err(e)
             }
-        } else {
-            let current_dir = std::env::current_dir().unwrap_or_default();
-            log::warn!(
-                "SCK helper binary not found. Searched paths relative to: {:?}",
-                current_dir
-            );
-            log::warn!("💡 If you're on macOS < 13, please install BlackHole: brew install blackhole-2ch");
         }
-
-        Err(anyhow!(
-            "System Audio Capture failed. Please install BlackHole (brew install blackhole-2ch) and configure Multi-Output Device, or upgrade to macOS 13+ for ScreenCaptureKit support."
-        ))
     }
 
     fn stop_capture(&mut self) -> Result<()> {
@@ -672,10 +447,15 @@ impl SystemAudioCapture for MacOSSystemAudio {
             return Ok(());
         }
 
-        log::info!("🛑 [SystemAudio] Stopping capture (method: {})", 
-            if self.use_blackhole { "BlackHole" } else { "ScreenCaptureKit" });
+        log::info!(
+            "🛑 [SystemAudio] Stopping capture (method: {})",
+            self.capture_method()
+        );
 
-        if self.use_blackhole {
+        if let Some(tap) = self.process_tap.take() {
+            // Stops the callback and removes the tap and its device
+            drop(tap);
+        } else if self.use_blackhole {
             // Stop BlackHole stream by signaling stop
             if let Some(tx) = self.blackhole_stop_tx.take() {
                 log::info!("🛑 [SystemAudio] Signaling BlackHole thread to stop...");
@@ -692,13 +472,10 @@ impl SystemAudioCapture for MacOSSystemAudio {
                     log::info!("✅ [SystemAudio] BlackHole thread finished (background cleanup)");
                 });
             }
-        } else {
-            // Stop ScreenCaptureKit helper
-            if let Some(mut child) = self.capture_process.take() {
-                log::info!("🛑 [SystemAudio] Stopping SCK helper process...");
-                let _ = child.kill();
-                let _ = child.wait();
-                log::info!("✅ [SystemAudio] SCK helper process stopped");
+        } else if let Some(mut capture) = self.screencapturekit.take() {
+            log::info!("🛑 [SystemAudio] Stopping ScreenCaptureKit stream...");
+            if let Err(e) = capture.stop_capture() {
+                log::warn!("⚠️ [SystemAudio] Failed to stop ScreenCaptureKit: {}", e);
             }
         }
 
@@ -715,42 +492,36 @@ impl SystemAudioCapture for MacOSSystemAudio {
         Ok(())
     }
 
-    fn read_samples(&mut self) -> Result<Option<Vec<f32>>> {
+    fn read_samples(&mut self) -> Result<Option<TimedSamples>> {
         let mut buffer = self.sample_buffer.lock().unwrap();
 
         if buffer.is_empty() {
             // Log periodically to confirm we're checking (every 10 calls)
             static CALL_COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
             let count = CALL_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-            if count % 10 == 0 {
-                log::info!("🔍 [SystemCapture] Buffer is empty (checked {} times) - SCStream may not be sending audio buffers. Please ensure audio is playing from Chrome or another app.", count + 1);
-                // Check if process is still running (without mutable borrow)
-                let process_running = if let Some(ref mut proc) = self.capture_process {
-                    proc.try_wait().ok().flatten().is_none()
-                } else {
-                    false
-                };
-                log::info!("🔍 [SystemCapture] Debug: is_capturing={}, process running={}", 
-                    self.is_capturing, 
-                    process_running);
+            if audio_debug_enabled() && count % 10 == 0 {
+                log::debug!(target: AUDIO_DEBUG_TARGET, "🔍 [SystemCapture] Buffer is empty (checked {} times) - SCStream may not be sending audio buffers. Please ensure audio is playing from Chrome or another app.", count + 1);
+                log::debug!(target: AUDIO_DEBUG_TARGET, "🔍 [SystemCapture] Debug: is_capturing={}, method={}",
+                    self.is_capturing,
+                    self.capture_method());
             }
             return Ok(None);
         }
 
         // Drain all samples
         let sample_count = buffer.len();
-        let samples: Vec<f32> = buffer.drain(..).collect();
+        let samples = buffer.drain_timed();
         
         // Log periodically (every 100 reads) to avoid spam
         static READ_COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
         let count = READ_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        if count % 100 == 0 {
-            let sample_rate = if self.use_blackhole { 48000 } else { 48000 }; // Both are 48kHz typically
-            log::info!("✅ [SystemCapture] Read {} samples from buffer ({}s audio at {}kHz) - method: {}", 
+        if audio_debug_enabled() && count % 100 == 0 {
+            let sample_rate = buffer.format().sample_rate;
+            log::debug!(target: AUDIO_DEBUG_TARGET, "✅ [SystemCapture] Read {} samples from buffer ({}s audio at {}kHz) - method: {}", 
                 sample_count, 
                 sample_count as f32 / sample_rate as f32,
                 sample_rate,
-                if self.use_blackhole { "BlackHole" } else { "ScreenCaptureKit" }
+                self.capture_method()
             );
         }
         Ok(Some(samples))
@@ -759,5 +530,25 @@ impl SystemAudioCapture for MacOSSystemAudio {
     fn is_capturing(&self) -> bool {
         self.is_capturing
     }
+
+    fn dropped_samples(&self) -> u64 {
+        self.sample_buffer.lock().unwrap().dropped_samples()
+    }
+
+    fn subscribe_frames(&self) -> Option<AudioFrameStream> {
+        Some(self.sample_buffer.lock().unwrap().subscribe())
+    }
+
+    fn deliver_samples(&mut self) -> Option<SampleReceiver> {
+        Some(self.sample_buffer.lock().unwrap().deliver())
+    }
+
+    fn format(&self) -> CaptureFormat {
+        self.sample_buffer.lock().unwrap().format()
+    }
+
+    fn peek_status(&self) -> CaptureStatus {
+        CaptureStatus::from_buffer(&self.sample_buffer.lock().unwrap())
+    }
 }
 
@@ -2,38 +2,184 @@
 // This captures the audio that is being played by the system (e.g., Chrome, Spotify, etc.)
 // Uses Windows Audio Session API (WASAPI) in loopback mode
 
-use anyhow::{anyhow, Result};
-use std::collections::VecDeque;
+use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
 use std::thread;
 
-use crate::audio_toolkit::system_audio::SystemAudioCapture;
-use tauri::{AppHandle, Emitter};
+use crate::audio_toolkit::audio::Downmix;
+use crate::audio_toolkit::debug_log::{audio_debug_enabled, rms_and_peak, AUDIO_DEBUG_TARGET};
+use crate::audio_toolkit::error::{AudioError, Result};
+use crate::audio_toolkit::ring_buffer::{
+    SampleReceiver, SampleRingBuffer, SYSTEM_AUDIO_SAMPLE_RATE,
+};
+use crate::audio_toolkit::sample_queue::{self, SampleProducer};
+use crate::audio_toolkit::stream::AudioFrameStream;
+use crate::audio_toolkit::system_audio::{CaptureFormat, CaptureStatus, SystemAudioCapture};
+use crate::audio_toolkit::timed::{self, TimedSamples};
+use crate::audio_toolkit::wasapi::{self, Flow, WasapiOptions, WasapiStream};
+use tauri::AppHandle;
 
 use cpal::{
     traits::{DeviceTrait, HostTrait, StreamTrait},
     Device, Sample, SizedSample,
 };
+use windows::core::{implement, PCWSTR};
+use windows::Win32::Foundation::PROPERTYKEY;
+use windows::Win32::Media::Audio::{
+    eConsole, eRender, EDataFlow, ERole, IMMDeviceEnumerator, IMMNotificationClient,
+    IMMNotificationClient_Impl, MMDeviceEnumerator, DEVICE_STATE,
+};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED,
+};
+
+/// Tells the capture thread what to do with its stream
+enum LoopbackCmd {
+    /// The default output changed: capture from the new one instead
+    SwitchDevice,
+    Stop,
+}
+
+/// Passes default output changes on to the capture thread. Windows calls it
+/// on its own threads, where it mustn't block, so it only sends a command.
+#[implement(IMMNotificationClient)]
+struct DefaultOutputListener {
+    commands: Sender<LoopbackCmd>,
+}
+
+impl IMMNotificationClient_Impl for DefaultOutputListener_Impl {
+    fn OnDefaultDeviceChanged(
+        &self,
+        flow: EDataFlow,
+        role: ERole,
+        _default_device_id: &PCWSTR,
+    ) -> windows::core::Result<()> {
+        // Sent once per role; the console role stands for all of them
+        if flow == eRender && role == eConsole {
+            let _ = self.commands.send(LoopbackCmd::SwitchDevice);
+        }
+        Ok(())
+    }
+
+    fn OnDeviceStateChanged(
+        &self,
+        _device_id: &PCWSTR,
+        _new_state: DEVICE_STATE,
+    ) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn OnDeviceAdded(&self, _device_id: &PCWSTR) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn OnDeviceRemoved(&self, _device_id: &PCWSTR) -> windows::core::Result<()> {
+        Ok(())
+    }
+
+    fn OnPropertyValueChanged(
+        &self,
+        _device_id: &PCWSTR,
+        _key: &PROPERTYKEY,
+    ) -> windows::core::Result<()> {
+        Ok(())
+    }
+}
+
+/// A registered `DefaultOutputListener`, unregistered on drop
+struct DefaultOutputNotifications {
+    enumerator: IMMDeviceEnumerator,
+    client: IMMNotificationClient,
+}
+
+// The device enumerator is free-threaded, and the listener only holds a
+// channel sender
+unsafe impl Send for DefaultOutputNotifications {}
+unsafe impl Sync for DefaultOutputNotifications {}
+
+impl DefaultOutputNotifications {
+    fn register(commands: Sender<LoopbackCmd>) -> windows::core::Result<Self> {
+        unsafe {
+            // Does nothing if COM is already initialized on this thread
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+            let client: IMMNotificationClient = DefaultOutputListener { commands }.into();
+            enumerator.RegisterEndpointNotificationCallback(&client)?;
+            Ok(Self { enumerator, client })
+        }
+    }
+}
+
+impl Drop for DefaultOutputNotifications {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = self
+                .enumerator
+                .UnregisterEndpointNotificationCallback(&self.client);
+        }
+    }
+}
+
+/// A running loopback stream, held by the capture thread to keep it alive
+#[allow(dead_code)]
+enum LoopbackStream {
+    Cpal(cpal::Stream),
+    Wasapi(WasapiStream),
+}
 
 /// Windows implementation using WASAPI loopback capture
 pub struct WindowsSystemAudio {
     is_capturing: bool,
-    sample_buffer: Arc<Mutex<VecDeque<f32>>>,
-    app_handle: AppHandle,
+    sample_buffer: Arc<Mutex<SampleRingBuffer>>,
     capture_thread: Option<thread::JoinHandle<()>>,
-    stop_tx: Option<std::sync::mpsc::Sender<()>>,
+    stop_tx: Option<Sender<LoopbackCmd>>,
+    // Moves capture to a new default output, e.g. headphones plugged in
+    default_output_notifications: Option<DefaultOutputNotifications>,
+    // How the output's channels are reduced to mono
+    downmix: Downmix,
+    // Buffer, polling and raw mode for the loopback stream
+    wasapi: WasapiOptions,
 }
 
 impl WindowsSystemAudio {
-    pub fn new(app: &AppHandle) -> Result<Self> {
+    pub fn new(_app: &AppHandle) -> Result<Self> {
         Ok(Self {
             is_capturing: false,
-            sample_buffer: Arc::new(Mutex::new(VecDeque::new())),
-            app_handle: app.clone(),
+            sample_buffer: Arc::new(Mutex::new(SampleRingBuffer::default())),
             capture_thread: None,
             stop_tx: None,
+            default_output_notifications: None,
+            downmix: Downmix::default(),
+            wasapi: WasapiOptions::default(),
         })
     }
+
+    /// Limit how much unread audio is kept before the oldest samples are overwritten
+    pub fn with_max_buffer_seconds(self, seconds: u32) -> Self {
+        self.sample_buffer
+            .lock()
+            .unwrap()
+            .set_capacity(seconds as usize * SYSTEM_AUDIO_SAMPLE_RATE);
+        self
+    }
+
+    /// Reduce the output's channels to mono with `downmix` instead of
+    /// averaging them
+    pub fn with_downmix(mut self, downmix: Downmix) -> Self {
+        self.downmix = downmix;
+        self
+    }
+
+    /// Open the loopback stream with `options`. Loopback capture is always
+    /// shared, so exclusive mode is left out.
+    pub fn with_wasapi_options(mut self, options: WasapiOptions) -> Self {
+        self.wasapi = WasapiOptions {
+            exclusive: false,
+            ..options
+        };
+        self
+    }
     
     /// Find the default loopback device (what system is playing)
     /// On Windows, this is typically called "Stereo Mix" or the default output device in loopback mode
@@ -62,75 +208,57 @@ impl WindowsSystemAudio {
         log::info!("🎯 [WindowsSystemAudio] Starting loopback capture from: {}", device_name);
         
         // Get default config for the output device
-        let config = device.default_output_config()
-            .map_err(|e| anyhow!("Failed to get device config for {}: {}", device_name, e))?;
+        let config = device.default_output_config()?;
         
         let sample_rate = config.sample_rate().0;
         let channels = config.channels() as usize;
         
-        log::info!("📊 [WindowsSystemAudio] Device config: sample_rate={}Hz, channels={}, format={:?}", 
-            sample_rate, channels, config.sample_format());
-        
-        let _ = self.app_handle.emit("log-update", format!(
-            "📊 [WindowsSystemAudio] Loopback: {}, Rate: {}Hz, Channels: {}", 
-            device_name, sample_rate, channels
-        ));
+        log::info!("📊 [WindowsSystemAudio] Device config ({}): sample_rate={}Hz, channels={}, format={:?}", 
+            device_name, sample_rate, channels, config.sample_format());
         
         let buffer = self.sample_buffer.clone();
-        let app_handle = self.app_handle.clone();
+        let downmix = self.downmix;
+        let options = self.wasapi;
         
-        // Create channel for stopping the thread
+        // Create channel for stopping the thread or moving its stream
         let (tx, rx) = std::sync::mpsc::channel();
         
-        // Spawn thread to run the audio stream
+        // Spawn thread to run the audio stream, moving it to whichever device
+        // becomes the default output
         let stream_handle = thread::spawn(move || {
-            log::info!("🔧 [WindowsSystemAudio] Building loopback stream...");
-            
-            // Build input stream in loopback mode
-            // Note: On Windows, we need to use the input stream API but with a loopback device
-            let stream_result = match config.sample_format() {
-                cpal::SampleFormat::F32 => {
-                    Self::build_loopback_stream::<f32>(&device, &config, buffer.clone(), channels, app_handle.clone())
-                }
-                cpal::SampleFormat::I16 => {
-                    Self::build_loopback_stream::<i16>(&device, &config, buffer.clone(), channels, app_handle.clone())
-                }
-                cpal::SampleFormat::I32 => {
-                    Self::build_loopback_stream::<i32>(&device, &config, buffer.clone(), channels, app_handle.clone())
-                }
-                _ => {
-                    log::error!("❌ [WindowsSystemAudio] Unsupported sample format: {:?}", config.sample_format());
-                    return;
-                }
-            };
-            
-            match stream_result {
-                Ok(stream) => {
-                    log::info!("✅ [WindowsSystemAudio] Stream created successfully!");
-                    let _ = app_handle.emit("log-update", "✅ [WindowsSystemAudio] Loopback stream created".to_string());
-                    
-                    if let Err(e) = stream.play() {
-                        log::error!("❌ [WindowsSystemAudio] Failed to start stream: {}", e);
-                        let _ = app_handle.emit("log-update", format!("❌ [WindowsSystemAudio] Failed to start: {}", e));
-                        return;
+            let mut output = Some((device, config));
+            loop {
+                // Keep stream alive until told to stop or switch
+                let _stream = output.as_ref().and_then(|(device, config)| {
+                    Self::open_loopback_stream(device, config, &buffer, downmix, options)
+                });
+                match rx.recv() {
+                    Ok(LoopbackCmd::SwitchDevice) => {
+                        log::info!("🔄 [WindowsSystemAudio] Default output changed, switching loopback device...");
+                        output = Self::find_loopback_device().and_then(|device| {
+                            match device.default_output_config() {
+                                Ok(config) => Some((device, config)),
+                                Err(e) => {
+                                    log::error!("❌ [WindowsSystemAudio] Failed to read new output config: {}", e);
+                                    None
+                                }
+                            }
+                        });
                     }
-                    
-                    log::info!("✅ [WindowsSystemAudio] Stream started - capturing system audio!");
-                    let _ = app_handle.emit("log-update", "✅ [WindowsSystemAudio] Capturing system audio".to_string());
-                    
-                    // Keep stream alive until stop signal
-                    let _stream = stream;
-                    let _ = rx.recv(); // Wait for stop signal
-                    
-                    log::info!("🛑 [WindowsSystemAudio] Stream stopped");
-                }
-                Err(e) => {
-                    log::error!("❌ [WindowsSystemAudio] Failed to build stream: {}", e);
-                    let _ = app_handle.emit("log-update", format!("❌ [WindowsSystemAudio] Failed to build stream: {}", e));
+                    Ok(LoopbackCmd::Stop) | Err(_) => break,
                 }
             }
+            log::info!("🛑 [WindowsSystemAudio] Stream stopped");
         });
-        
+
+        match DefaultOutputNotifications::register(tx.clone()) {
+            Ok(notifications) => self.default_output_notifications = Some(notifications),
+            Err(e) => log::warn!(
+                "⚠️ [WindowsSystemAudio] Capture won't follow default output changes: {}",
+                e
+            ),
+        }
+
         self.capture_thread = Some(stream_handle);
         self.stop_tx = Some(tx);
         self.is_capturing = true;
@@ -164,9 +292,6 @@ impl WindowsSystemAudio {
                     
                     if rms > 0.00001 {
                         log::info!("✅ [WindowsSystemAudio] Audio detected! RMS: {:.6}", rms);
-                        let _ = self.app_handle.emit("log-update", format!(
-                            "✅ [WindowsSystemAudio] Audio detected! RMS: {:.6}", rms
-                        ));
                         audio_detected = true;
                         break;
                     }
@@ -181,21 +306,113 @@ impl WindowsSystemAudio {
             log::warn!("⚠️ [WindowsSystemAudio] No audio detected after 5s");
             log::warn!("⚠️ [WindowsSystemAudio] Max RMS seen: {:.6}", max_rms_seen);
             log::warn!("💡 [WindowsSystemAudio] Please ensure audio is playing (Chrome, Spotify, etc.)");
-            
-            let _ = self.app_handle.emit("log-update", format!(
-                "⚠️ [WindowsSystemAudio] No audio detected. Please play audio from Chrome, Spotify, etc."
-            ));
         }
         
         Ok(audio_detected)
     }
     
+    /// Build and start a loopback stream on `device` feeding the sample
+    /// buffer, through WASAPI directly when `options` ask for more than cpal
+    /// does; None if that failed
+    fn open_loopback_stream(
+        device: &Device,
+        config: &cpal::SupportedStreamConfig,
+        buffer: &Arc<Mutex<SampleRingBuffer>>,
+        downmix: Downmix,
+        options: WasapiOptions,
+    ) -> Option<LoopbackStream> {
+        if !options.is_default() {
+            let mut samples = sample_queue::feed(buffer);
+            let opened = wasapi::open(
+                device.name().ok(),
+                Flow::Loopback,
+                options,
+                move |data, channels, captured_at| {
+                    samples.stamp(captured_at);
+                    if channels == 1 {
+                        samples.push_slice(data);
+                    } else {
+                        samples
+                            .push_iter(data.chunks_exact(channels).map(|frame| downmix.mix(frame)));
+                    }
+                },
+            );
+            match opened {
+                Ok((stream, format)) => {
+                    buffer.lock().unwrap().set_format(format);
+                    log::info!(
+                        "✅ [WindowsSystemAudio] Loopback stream opened with {:?}",
+                        options
+                    );
+                    return Some(LoopbackStream::Wasapi(stream));
+                }
+                Err(e) => log::warn!(
+                    "⚠️ [WindowsSystemAudio] Opening loopback with {:?} failed, using defaults: {}",
+                    options,
+                    e
+                ),
+            }
+        }
+
+        let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+        let channels = config.channels() as usize;
+        log::info!(
+            "🔧 [WindowsSystemAudio] Building loopback stream on {}...",
+            device_name
+        );
+
+        buffer.lock().unwrap().set_format(CaptureFormat {
+            sample_rate: config.sample_rate().0,
+            channels: config.channels(),
+        });
+
+        // Build input stream in loopback mode
+        // Note: On Windows, we need to use the input stream API but with a loopback device
+        let samples = sample_queue::feed(buffer);
+        let stream_result = match config.sample_format() {
+            cpal::SampleFormat::F32 => {
+                Self::build_loopback_stream::<f32>(device, config, samples, channels, downmix)
+            }
+            cpal::SampleFormat::I16 => {
+                Self::build_loopback_stream::<i16>(device, config, samples, channels, downmix)
+            }
+            cpal::SampleFormat::I32 => {
+                Self::build_loopback_stream::<i32>(device, config, samples, channels, downmix)
+            }
+            _ => {
+                log::error!(
+                    "❌ [WindowsSystemAudio] Unsupported sample format: {:?}",
+                    config.sample_format()
+                );
+                return None;
+            }
+        };
+
+        match stream_result {
+            Ok(stream) => {
+                log::info!("✅ [WindowsSystemAudio] Stream created successfully!");
+
+                if let Err(e) = stream.play() {
+                    log::error!("❌ [WindowsSystemAudio] Failed to start stream: {}", e);
+                    return None;
+                }
+
+                log::info!("✅ [WindowsSystemAudio] Stream started - capturing system audio!");
+                Some(LoopbackStream::Cpal(stream))
+            }
+            Err(e) => {
+                log::error!("❌ [WindowsSystemAudio] Failed to build stream: {}", e);
+                None
+            }
+        }
+    }
+
     fn build_loopback_stream<T>(
         device: &Device,
         config: &cpal::SupportedStreamConfig,
-        buffer: Arc<Mutex<VecDeque<f32>>>,
+        mut samples: SampleProducer,
         channels: usize,
-        app_handle: AppHandle,
+        downmix: Downmix,
     ) -> Result<cpal::Stream, cpal::BuildStreamError>
     where
         T: Sample + SizedSample + Send + 'static,
@@ -205,76 +422,45 @@ impl WindowsSystemAudio {
         
         log::info!("🔧 [WindowsSystemAudio] Creating stream callback...");
         
-        let stream_cb = move |data: &[T], _info: &cpal::InputCallbackInfo| {
+        let stream_cb = move |data: &[T], info: &cpal::InputCallbackInfo| {
             callback_count += 1;
             
             // Log first callback
             if callback_count == 1 {
                 log::info!("🎉 [WindowsSystemAudio] First callback received! {} samples", data.len());
-                let _ = app_handle.emit("log-update", format!(
-                    "🎉 [WindowsSystemAudio] First callback: {} samples", data.len()
-                ));
             }
             
-            // Log periodically
-            let should_log = callback_count <= 50 || callback_count % 100 == 0;
-            
-            if should_log {
-                let rms = if data.is_empty() {
-                    0.0
-                } else {
-                    let sum_sq: f32 = data.iter()
-                        .map(|&s| {
-                            let f: f32 = s.to_sample();
-                            f * f
-                        })
-                        .sum();
-                    (sum_sq / data.len() as f32).sqrt()
-                };
-                let max_amp = data.iter()
-                    .map(|&s| s.to_sample::<f32>().abs())
-                    .fold(0.0f32, |a, b| a.max(b));
-                
-                log::info!("🎵 [WindowsSystemAudio] Callback #{}: {} samples, RMS: {:.6}, Max: {:.6}", 
-                    callback_count, data.len(), rms, max_amp);
-                
-                if callback_count <= 10 {
-                    let _ = app_handle.emit("log-update", format!(
-                        "🎵 [WindowsSystemAudio] Callback #{}: {} samples, RMS: {:.6}", 
-                        callback_count, data.len(), rms
-                    ));
-                }
-                
-                // Warn if no audio detected
+            // Silence warning is actionable, so it is always emitted once
+            if callback_count == 10 {
+                let (_, max_amp) = rms_and_peak(data.iter().map(|&s| s.to_sample::<f32>()));
                 if max_amp < 0.00001 {
-                    if callback_count == 10 {
-                        log::warn!("⚠️ [WindowsSystemAudio] No audio after 10 callbacks - ensure audio is playing");
-                    }
+                    log::warn!("⚠️ [WindowsSystemAudio] No audio after 10 callbacks - ensure audio is playing");
                 }
             }
-            
-            // Convert to mono and store
-            let mut buf = buffer.lock().unwrap();
-            
+
+            // Per-callback statistics only when debug audio logging is on
+            let debug = audio_debug_enabled();
+            if debug && (callback_count <= 50 || callback_count % 100 == 0) {
+                let (rms, max_amp) = rms_and_peak(data.iter().map(|&s| s.to_sample::<f32>()));
+                log::debug!(
+                    target: AUDIO_DEBUG_TARGET,
+                    "🎵 [WindowsSystemAudio] Callback #{}: {} samples, RMS: {:.6}, Max: {:.6}",
+                    callback_count, data.len(), rms, max_amp
+                );
+            }
+
+            // Convert to mono and queue
+            samples.stamp(timed::cpal_capture_time(info));
             if channels == 1 {
-                buf.extend(data.iter().map(|&s| s.to_sample::<f32>()));
+                samples.push_iter(data.iter().map(|&s| s.to_sample::<f32>()));
             } else {
-                // Convert to mono by averaging channels
-                for frame in data.chunks_exact(channels) {
-                    let mono_sample = frame
-                        .iter()
-                        .map(|&s| s.to_sample::<f32>())
-                        .sum::<f32>()
-                        / channels as f32;
-                    buf.push_back(mono_sample);
-                }
+                samples.push_iter(data.chunks_exact(channels).map(|frame| downmix.mix(frame)));
             }
             
-            // Log buffer status periodically
-            if callback_count % 1000 == 0 {
-                let buf_size = buf.len();
-                log::info!("📊 [WindowsSystemAudio] Buffer: {} samples ({:.1}s)", 
-                    buf_size, buf_size as f32 / 48000.0);
+            // Log queue status periodically
+            if debug && callback_count % 1000 == 0 {
+                log::debug!(target: AUDIO_DEBUG_TARGET, "📊 [WindowsSystemAudio] Queue has room for {} samples",
+                    samples.free());
             }
         };
         
@@ -317,7 +503,9 @@ impl SystemAudioCapture for WindowsSystemAudio {
             }
         }
         
-        Err(anyhow!("Failed to find audio output device for loopback capture"))
+        Err(AudioError::DeviceNotFound(
+            "no output device for loopback capture".to_string(),
+        ))
     }
     
     fn stop_capture(&mut self) -> Result<()> {
@@ -327,9 +515,10 @@ impl SystemAudioCapture for WindowsSystemAudio {
         
         log::info!("🛑 [WindowsSystemAudio] Stopping capture...");
         
-        // Signal thread to stop
+        // Stop following the default output, then signal thread to stop
+        self.default_output_notifications = None;
         if let Some(tx) = self.stop_tx.take() {
-            let _ = tx.send(());
+            let _ = tx.send(LoopbackCmd::Stop);
         }
         
         // Wait for thread to finish (in background to avoid blocking)
@@ -351,26 +540,26 @@ impl SystemAudioCapture for WindowsSystemAudio {
         Ok(())
     }
     
-    fn read_samples(&mut self) -> Result<Option<Vec<f32>>> {
+    fn read_samples(&mut self) -> Result<Option<TimedSamples>> {
         let mut buffer = self.sample_buffer.lock().unwrap();
         
         if buffer.is_empty() {
             static CALL_COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
             let count = CALL_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-            if count % 10 == 0 {
-                log::debug!("🔍 [WindowsSystemAudio] Buffer empty (checked {} times)", count + 1);
+            if audio_debug_enabled() && count % 10 == 0 {
+                log::debug!(target: AUDIO_DEBUG_TARGET, "🔍 [WindowsSystemAudio] Buffer empty (checked {} times)", count + 1);
             }
             return Ok(None);
         }
         
         // Drain all samples
         let sample_count = buffer.len();
-        let samples: Vec<f32> = buffer.drain(..).collect();
+        let samples = buffer.drain_timed();
         
         static READ_COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
         let count = READ_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        if count % 100 == 0 {
-            log::info!("✅ [WindowsSystemAudio] Read {} samples ({:.1}s audio)", 
+        if audio_debug_enabled() && count % 100 == 0 {
+            log::debug!(target: AUDIO_DEBUG_TARGET, "✅ [WindowsSystemAudio] Read {} samples ({:.1}s audio)", 
                 sample_count, sample_count as f32 / 48000.0);
         }
         
@@ -380,4 +569,24 @@ impl SystemAudioCapture for WindowsSystemAudio {
     fn is_capturing(&self) -> bool {
         self.is_capturing
     }
+
+    fn dropped_samples(&self) -> u64 {
+        self.sample_buffer.lock().unwrap().dropped_samples()
+    }
+
+    fn subscribe_frames(&self) -> Option<AudioFrameStream> {
+        Some(self.sample_buffer.lock().unwrap().subscribe())
+    }
+
+    fn deliver_samples(&mut self) -> Option<SampleReceiver> {
+        Some(self.sample_buffer.lock().unwrap().deliver())
+    }
+
+    fn format(&self) -> CaptureFormat {
+        self.sample_buffer.lock().unwrap().format()
+    }
+
+    fn peek_status(&self) -> CaptureStatus {
+        CaptureStatus::from_buffer(&self.sample_buffer.lock().unwrap())
+    }
 }
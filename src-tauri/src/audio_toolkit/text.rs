@@ -1,6 +1,11 @@
+use crate::settings::{SpokenCommand, TextCasing};
 use natural::phonetics::soundex;
+use std::collections::HashMap;
 use strsim::levenshtein;
 
+// Whisper keeps at most 224 prompt tokens, and the caption context shares them
+const MAX_PROMPT_CHARS: usize = 400;
+
 /// Applies custom word corrections to transcribed text using fuzzy matching
 ///
 /// This function corrects words in the input text by finding the best matches
@@ -8,14 +13,24 @@ use strsim::levenshtein;
 /// - Levenshtein distance for string similarity
 /// - Soundex phonetic matching for pronunciation similarity
 ///
+/// Before that, spoken aliases of a custom word are replaced outright, for
+/// words that sound nothing like their spelling (e.g. "cube cuttle" for
+/// "kubectl").
+///
 /// # Arguments
 /// * `text` - The input text to correct
 /// * `custom_words` - List of custom words to match against
+/// * `aliases` - Phrases to replace with each custom word, keyed by the word
 /// * `threshold` - Maximum similarity score to accept (0.0 = exact match, 1.0 = any match)
 ///
 /// # Returns
 /// The corrected text with custom words applied
-pub fn apply_custom_words(text: &str, custom_words: &[String], threshold: f64) -> String {
+pub fn apply_custom_words(
+    text: &str,
+    custom_words: &[String],
+    aliases: &HashMap<String, Vec<String>>,
+    threshold: f64,
+) -> String {
     if custom_words.is_empty() {
         return text.to_string();
     }
@@ -23,10 +38,10 @@ pub fn apply_custom_words(text: &str, custom_words: &[String], threshold: f64) -
     // Pre-compute lowercase versions to avoid repeated allocations
     let custom_words_lower: Vec<String> = custom_words.iter().map(|w| w.to_lowercase()).collect();
 
-    let words: Vec<&str> = text.split_whitespace().collect();
+    let words = apply_aliases(text, custom_words, aliases);
     let mut corrected_words = Vec::new();
 
-    for word in words {
+    for word in &words {
         let cleaned_word = word
             .trim_matches(|c: char| !c.is_alphabetic())
             .to_lowercase();
@@ -93,6 +108,223 @@ pub fn apply_custom_words(text: &str, custom_words: &[String], threshold: f64) -
     corrected_words.join(" ")
 }
 
+/// Replaces each alias in `text` with its custom word, keeping the
+/// punctuation around it. Aliases may span several words; the longest one
+/// matching at a position wins.
+fn apply_aliases(
+    text: &str,
+    custom_words: &[String],
+    aliases: &HashMap<String, Vec<String>>,
+) -> Vec<String> {
+    let clean = |word: &str| {
+        word.trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase()
+    };
+    let mut alias_words: Vec<(Vec<String>, &String)> = Vec::new();
+    for word in custom_words {
+        for alias in aliases.get(word).into_iter().flatten() {
+            let spoken: Vec<String> = alias.split_whitespace().map(clean).collect();
+            if !spoken.is_empty() && spoken.iter().all(|w| !w.is_empty()) {
+                alias_words.push((spoken, word));
+            }
+        }
+    }
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if alias_words.is_empty() {
+        return words.into_iter().map(str::to_string).collect();
+    }
+
+    let mut result = Vec::with_capacity(words.len());
+    let mut i = 0;
+    while i < words.len() {
+        let matched = alias_words
+            .iter()
+            .filter(|(alias, _)| {
+                alias.len() <= words.len() - i
+                    && alias.iter().zip(&words[i..]).all(|(a, w)| *a == clean(w))
+            })
+            .max_by_key(|(alias, _)| alias.len());
+
+        match matched {
+            Some((alias, replacement)) => {
+                let (prefix, _) = extract_punctuation(words[i]);
+                let (_, suffix) = extract_punctuation(words[i + alias.len() - 1]);
+                let corrected = preserve_case_pattern(words[i], replacement);
+                result.push(format!("{}{}{}", prefix, corrected, suffix));
+                i += alias.len();
+            }
+            None => {
+                result.push(words[i].to_string());
+                i += 1;
+            }
+        }
+    }
+    result
+}
+
+/// Glossary of the custom words for the decoder's initial prompt, biasing it
+/// toward spelling them as given rather than only correcting them after the
+/// fact. Words past the prompt budget are left to `apply_custom_words`.
+pub fn custom_words_prompt(custom_words: &[String]) -> Option<String> {
+    let mut prompt = String::from("Glossary:");
+    let mut listed = 0;
+    for word in custom_words
+        .iter()
+        .map(|w| w.trim())
+        .filter(|w| !w.is_empty())
+    {
+        if prompt.len() + word.len() + 3 > MAX_PROMPT_CHARS {
+            break;
+        }
+        if listed > 0 {
+            prompt.push(',');
+        }
+        prompt.push(' ');
+        prompt.push_str(word);
+        listed += 1;
+    }
+    if listed == 0 {
+        return None;
+    }
+    prompt.push('.');
+    Some(prompt)
+}
+
+/// Turns spoken formatting commands in dictated text into the formatting they
+/// name, e.g. "hello new line world" into "hello\nworld". Commands are
+/// matched case-insensitively, ignoring the punctuation the model put around
+/// them, and the longest phrase matching at a position wins.
+pub fn apply_spoken_formatting(text: &str, commands: &HashMap<String, SpokenCommand>) -> String {
+    let clean = |word: &str| {
+        word.trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase()
+    };
+    let mut phrases: Vec<(Vec<String>, &SpokenCommand)> = commands
+        .iter()
+        .map(|(phrase, command)| {
+            let words: Vec<String> = phrase.split_whitespace().map(clean).collect();
+            (words, command)
+        })
+        .filter(|(words, _)| !words.is_empty())
+        .collect();
+    phrases.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut out = String::with_capacity(text.len());
+    // Whether the next word goes straight after the previous output
+    let mut glued = true;
+    let mut caps = false;
+    let mut capitalize_next = false;
+
+    let mut i = 0;
+    while i < words.len() {
+        let matched = phrases.iter().find(|(phrase, _)| {
+            phrase.len() <= words.len() - i
+                && phrase.iter().zip(&words[i..]).all(|(p, w)| *p == clean(w))
+        });
+        let Some((phrase, command)) = matched else {
+            if !glued {
+                out.push(' ');
+            }
+            let word = words[i];
+            if caps {
+                out.push_str(&word.to_uppercase());
+            } else if capitalize_next {
+                let mut chars = word.chars();
+                if let Some(first) = chars.next() {
+                    out.extend(first.to_uppercase());
+                    out.push_str(chars.as_str());
+                }
+            } else {
+                out.push_str(word);
+            }
+            glued = false;
+            capitalize_next = false;
+            i += 1;
+            continue;
+        };
+
+        match command {
+            SpokenCommand::Punctuation(mark) => {
+                // Replace the punctuation the model guessed with the one spoken
+                let kept = out.trim_end_matches([',', '.', ';', ':']).len();
+                out.truncate(kept);
+                out.push_str(mark);
+                glued = false;
+            }
+            SpokenCommand::Opening(mark) => {
+                if !glued {
+                    out.push(' ');
+                }
+                out.push_str(mark);
+                glued = true;
+            }
+            SpokenCommand::Break(text) => {
+                out.push_str(text);
+                glued = true;
+            }
+            SpokenCommand::Text(text) => {
+                if !glued {
+                    out.push(' ');
+                }
+                out.push_str(text);
+                glued = false;
+            }
+            SpokenCommand::CapsOn => caps = true,
+            SpokenCommand::CapsOff => caps = false,
+            SpokenCommand::CapitalizeNext => capitalize_next = true,
+        }
+        i += phrase.len();
+    }
+    out
+}
+
+/// Forces `casing` onto the whole text
+pub fn apply_casing(text: &str, casing: TextCasing) -> String {
+    match casing {
+        TextCasing::Original => text.to_string(),
+        TextCasing::Lowercase => text.to_lowercase(),
+        TextCasing::Uppercase => text.to_uppercase(),
+        TextCasing::Sentence => sentence_case(text),
+    }
+}
+
+/// Lowercases `text`, then capitalizes the start of each sentence and the
+/// pronoun "I". A sentence starts after `.`, `!` or `?` followed by whitespace,
+/// so decimals and dotted names like "v2.5" don't start one.
+fn sentence_case(text: &str) -> String {
+    let chars: Vec<char> = text.to_lowercase().chars().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut sentence_start = true;
+    let mut after_terminator = false;
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_whitespace() {
+            sentence_start |= after_terminator;
+            after_terminator = false;
+            result.push(c);
+            continue;
+        }
+        after_terminator = matches!(c, '.' | '!' | '?');
+
+        let standalone_i = c == 'i'
+            && (i == 0 || !chars[i - 1].is_alphanumeric())
+            && chars
+                .get(i + 1)
+                .map_or(true, |&next| !next.is_alphanumeric() || next == '\'');
+        if (sentence_start && c.is_alphanumeric()) || standalone_i {
+            result.extend(c.to_uppercase());
+        } else {
+            result.push(c);
+        }
+        if c.is_alphanumeric() {
+            sentence_start = false;
+        }
+    }
+    result
+}
+
 /// Preserves the case pattern of the original word when applying a replacement
 fn preserve_case_pattern(original: &str, replacement: &str) -> String {
     if original.chars().all(|c| c.is_uppercase()) {
@@ -140,7 +372,7 @@ mod tests {
     fn test_apply_custom_words_exact_match() {
         let text = "hello world";
         let custom_words = vec!["Hello".to_string(), "World".to_string()];
-        let result = apply_custom_words(text, &custom_words, 0.5);
+        let result = apply_custom_words(text, &custom_words, &HashMap::new(), 0.5);
         assert_eq!(result, "Hello World");
     }
 
@@ -148,7 +380,7 @@ mod tests {
     fn test_apply_custom_words_fuzzy_match() {
         let text = "helo wrold";
         let custom_words = vec!["hello".to_string(), "world".to_string()];
-        let result = apply_custom_words(text, &custom_words, 0.5);
+        let result = apply_custom_words(text, &custom_words, &HashMap::new(), 0.5);
         assert_eq!(result, "hello world");
     }
 
@@ -166,11 +398,86 @@ mod tests {
         assert_eq!(extract_punctuation("...hello..."), ("...", "..."));
     }
 
+    #[test]
+    fn test_apply_casing() {
+        let text = "Hello World. i think v2.5 is FINE! ok";
+        assert_eq!(apply_casing(text, TextCasing::Original), text);
+        assert_eq!(
+            apply_casing(text, TextCasing::Lowercase),
+            "hello world. i think v2.5 is fine! ok"
+        );
+        assert_eq!(
+            apply_casing(text, TextCasing::Uppercase),
+            "HELLO WORLD. I THINK V2.5 IS FINE! OK"
+        );
+        assert_eq!(
+            apply_casing(text, TextCasing::Sentence),
+            "Hello world. I think v2.5 is fine! Ok"
+        );
+        assert_eq!(
+            apply_casing("  what? i'm in.", TextCasing::Sentence),
+            "  What? I'm in."
+        );
+    }
+
+    #[test]
+    fn test_apply_custom_words_aliases() {
+        let custom_words = vec!["kubectl".to_string(), "Handy".to_string()];
+        let aliases = HashMap::from([(
+            "kubectl".to_string(),
+            vec!["cube cuttle".to_string(), "coob control".to_string()],
+        )]);
+        let result = apply_custom_words(
+            "Run cube cuttle, then coob control apply",
+            &custom_words,
+            &aliases,
+            0.18,
+        );
+        assert_eq!(result, "Run kubectl, then kubectl apply");
+    }
+
+    #[test]
+    fn test_custom_words_prompt() {
+        assert_eq!(custom_words_prompt(&[]), None);
+        assert_eq!(
+            custom_words_prompt(&["Kubernetes".to_string(), "Handy".to_string()]),
+            Some("Glossary: Kubernetes, Handy.".to_string())
+        );
+        let many: Vec<String> = (0..200).map(|i| format!("word{}", i)).collect();
+        let prompt = custom_words_prompt(&many).unwrap();
+        assert!(prompt.len() <= MAX_PROMPT_CHARS);
+        assert!(prompt.ends_with('.'));
+    }
+
+    #[test]
+    fn test_apply_spoken_formatting() {
+        let commands = crate::settings::default_spoken_commands();
+        assert_eq!(
+            apply_spoken_formatting("Dear Sam, new line. Thanks period", &commands),
+            "Dear Sam,\nThanks."
+        );
+        assert_eq!(
+            apply_spoken_formatting(
+                "she said open quote all caps stop end caps close quote",
+                &commands
+            ),
+            "she said \"STOP\""
+        );
+        assert_eq!(
+            apply_spoken_formatting("Is it ready, question mark?", &commands),
+            "Is it ready?"
+        );
+        assert_eq!(
+            apply_spoken_formatting("ask cap bob first", &commands),
+            "ask Bob first"
+        );
+    }
+
     #[test]
     fn test_empty_custom_words() {
         let text = "hello world";
         let custom_words = vec![];
-        let result = apply_custom_words(text, &custom_words, 0.5);
+        let result = apply_custom_words(text, &custom_words, &HashMap::new(), 0.5);
         assert_eq!(result, "hello world");
     }
 }
@@ -0,0 +1,253 @@
+//! Capture timestamps carried alongside audio.
+//!
+//! Backends stamp each chunk with when its first sample was captured, from
+//! the device's callback info where there is one. Consumers place audio on a
+//! timeline from the stamps instead of assuming it arrived contiguously, so a
+//! dropped buffer shows up as a gap rather than shifting everything after it.
+
+use std::time::{Duration, Instant};
+
+/// Mono samples plus the capture time of each chunk they were assembled from
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TimedSamples {
+    pub samples: Vec<f32>,
+    /// Index into `samples` where a chunk starts, and when that sample was
+    /// captured. Ascending; samples up to the next mark follow on at the
+    /// capture rate.
+    pub marks: Vec<(usize, Instant)>,
+}
+
+impl TimedSamples {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A single chunk whose first sample was captured at `captured_at`
+    pub fn from_chunk(samples: Vec<f32>, captured_at: Instant) -> Self {
+        let marks = if samples.is_empty() {
+            Vec::new()
+        } else {
+            vec![(0, captured_at)]
+        };
+        Self { samples, marks }
+    }
+
+    pub fn push(&mut self, chunk: &[f32], captured_at: Instant) {
+        if chunk.is_empty() {
+            return;
+        }
+        self.marks.push((self.samples.len(), captured_at));
+        self.samples.extend_from_slice(chunk);
+    }
+
+    pub fn append(&mut self, other: TimedSamples) {
+        let offset = self.samples.len();
+        self.marks
+            .extend(other.marks.into_iter().map(|(i, at)| (offset + i, at)));
+        self.samples.extend(other.samples);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.marks.clear();
+    }
+
+    /// The samples split at each mark, with its capture time. Any before the
+    /// first mark come first, without one.
+    pub fn chunks(&self) -> impl Iterator<Item = (&[f32], Option<Instant>)> + '_ {
+        let lead = self.marks.first().map_or(self.samples.len(), |&(i, _)| i);
+        let head = (lead > 0).then(|| (&self.samples[..lead], None));
+        let marked = self.marks.iter().enumerate().map(move |(n, &(start, at))| {
+            let end = self
+                .marks
+                .get(n + 1)
+                .map_or(self.samples.len(), |&(i, _)| i);
+            (&self.samples[start..end], Some(at))
+        });
+        head.into_iter().chain(marked)
+    }
+
+    /// Take the samples that came before the chunk stamped `at`, leaving
+    /// that chunk and those after it. Takes everything if no chunk is.
+    pub fn split_before(&mut self, at: Instant) -> TimedSamples {
+        let Some(n) = self.marks.iter().rposition(|&(_, t)| t == at) else {
+            return std::mem::take(self);
+        };
+        let index = self.marks[n].0;
+        let mut later = TimedSamples {
+            samples: self.samples.split_off(index),
+            marks: self.marks.split_off(n),
+        };
+        later.marks.iter_mut().for_each(|(i, _)| *i -= index);
+        std::mem::replace(self, later)
+    }
+
+    /// When the sample at `index` was captured, following on from the
+    /// nearest mark at or before it, or back from the first one
+    pub fn time_at(&self, index: usize, sample_rate: u32) -> Option<Instant> {
+        let (mark, at) = match self.marks.iter().rev().find(|(i, _)| *i <= index) {
+            Some(&mark) => mark,
+            None => *self.marks.first()?,
+        };
+        Some(offset_by(at, index as f64 - mark as f64, sample_rate))
+    }
+}
+
+/// `at` moved by `samples` (negative for earlier) at `sample_rate`
+pub fn offset_by(at: Instant, samples: f64, sample_rate: u32) -> Instant {
+    let shift = Duration::from_secs_f64(samples.abs() / sample_rate.max(1) as f64);
+    if samples >= 0.0 {
+        at + shift
+    } else {
+        at.checked_sub(shift).unwrap_or(at)
+    }
+}
+
+/// When audio that reached us `latency` ago was captured
+pub fn captured_before(latency: Duration) -> Instant {
+    let now = Instant::now();
+    now.checked_sub(latency).unwrap_or(now)
+}
+
+/// Capture time of the first sample of a cpal input callback, from the
+/// stream's capture and callback timestamps
+pub fn cpal_capture_time(info: &cpal::InputCallbackInfo) -> Instant {
+    let stamp = info.timestamp();
+    captured_before(
+        stamp
+            .callback
+            .duration_since(&stamp.capture)
+            .unwrap_or_default(),
+    )
+}
+
+// Capture stamps further apart than this from where the frame count puts
+// them mean audio went missing; closer ones are callback jitter
+const JITTER_TOLERANCE: Duration = Duration::from_millis(100);
+
+/// Audio missing between where the stream had got to, `expected`, and a chunk
+/// stamped `at`, if that is more than callback jitter
+pub fn skipped(expected: Instant, at: Instant) -> Option<Duration> {
+    at.checked_duration_since(expected)
+        .filter(|gap| *gap > JITTER_TOLERANCE)
+}
+
+/// Hands out capture times for back-to-back frames cut from stamped input.
+/// Times advance by the frame length and only jump to the input's stamps
+/// when those disagree by more than callback jitter.
+#[derive(Debug)]
+pub struct FrameClock {
+    frame: Duration,
+    next: Option<Instant>,
+}
+
+impl FrameClock {
+    pub fn new(frame: Duration) -> Self {
+        Self { frame, next: None }
+    }
+
+    /// Input captured from `at` on is about to be framed. Returns how much
+    /// audio went missing before it, if any.
+    pub fn observe(&mut self, at: Instant) -> Option<Duration> {
+        let next = match self.next {
+            Some(next) if abs_diff(next, at) <= JITTER_TOLERANCE => return None,
+            Some(next) => next,
+            None => {
+                self.next = Some(at);
+                return None;
+            }
+        };
+        self.next = Some(at);
+        at.checked_duration_since(next)
+    }
+
+    /// Capture time of the next frame, advancing past it
+    pub fn tick(&mut self) -> Instant {
+        let at = self.next.unwrap_or_else(Instant::now);
+        self.next = Some(at + self.frame);
+        at
+    }
+
+    /// Capture time the next frame will get, if there has been any input
+    pub fn peek(&self) -> Option<Instant> {
+        self.next
+    }
+}
+
+fn abs_diff(a: Instant, b: Instant) -> Duration {
+    a.checked_duration_since(b)
+        .unwrap_or_else(|| b.duration_since(a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_time_lookup() {
+        let start = Instant::now();
+        let mut timed = TimedSamples::from_chunk(vec![0.0; 100], start);
+        let later = start + Duration::from_secs(1);
+        timed.append(TimedSamples::from_chunk(vec![0.0; 100], later));
+
+        assert_eq!(timed.marks, vec![(0, start), (100, later)]);
+        let chunks: Vec<_> = timed.chunks().map(|(c, at)| (c.len(), at)).collect();
+        assert_eq!(chunks, vec![(100, Some(start)), (100, Some(later))]);
+        assert_eq!(
+            timed.time_at(50, 100),
+            Some(start + Duration::from_millis(500))
+        );
+        assert_eq!(
+            timed.time_at(150, 100),
+            Some(later + Duration::from_millis(500))
+        );
+    }
+
+    #[test]
+    fn test_split_before_a_chunk() {
+        let start = Instant::now();
+        let switch = start + Duration::from_secs(1);
+        let mut timed = TimedSamples::from_chunk(vec![1.0; 100], start);
+        timed.push(&[2.0; 50], switch);
+        timed.push(&[3.0; 20], switch + Duration::from_secs(1));
+
+        let earlier = timed.split_before(switch);
+        assert_eq!(earlier.samples, vec![1.0; 100]);
+        assert_eq!(earlier.marks, vec![(0, start)]);
+        assert_eq!(timed.len(), 70);
+        assert_eq!(
+            timed.marks,
+            vec![(0, switch), (50, switch + Duration::from_secs(1))]
+        );
+
+        let all = timed.split_before(start);
+        assert_eq!(all.len(), 70);
+        assert!(timed.is_empty());
+    }
+
+    #[test]
+    fn test_frame_clock_ignores_jitter_and_reports_gaps() {
+        let start = Instant::now();
+        let frame = Duration::from_millis(30);
+        let mut clock = FrameClock::new(frame);
+        assert_eq!(clock.observe(start), None);
+        assert_eq!(clock.tick(), start);
+
+        // A late callback is jitter; the clock keeps counting frames
+        assert_eq!(clock.observe(start + Duration::from_millis(40)), None);
+        assert_eq!(clock.tick(), start + frame);
+
+        // Half a second missing
+        let resumed = start + Duration::from_millis(560);
+        assert_eq!(clock.observe(resumed), Some(Duration::from_millis(500)));
+        assert_eq!(clock.tick(), resumed);
+    }
+}
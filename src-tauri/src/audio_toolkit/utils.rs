@@ -1,8 +1,45 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Sound server cpal talks to on Linux. Ignored elsewhere.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LinuxAudioHost {
+    /// ALSA, which also reaches PulseAudio and PipeWire through their ALSA
+    /// plugins
+    #[default]
+    Alsa,
+    /// JACK, or PipeWire through pipewire-jack. The recorder shows up as a
+    /// client whose input ports can be wired to any port in the graph.
+    Jack,
+}
+
+static LINUX_AUDIO_HOST: AtomicU8 = AtomicU8::new(LinuxAudioHost::Alsa as u8);
+
+pub fn set_linux_audio_host(host: LinuxAudioHost) {
+    LINUX_AUDIO_HOST.store(host as u8, Ordering::Relaxed);
+}
+
+/// The host `get_cpal_host` uses on Linux
+pub fn linux_audio_host() -> LinuxAudioHost {
+    match LINUX_AUDIO_HOST.load(Ordering::Relaxed) {
+        x if x == LinuxAudioHost::Jack as u8 => LinuxAudioHost::Jack,
+        _ => LinuxAudioHost::Alsa,
+    }
+}
+
 /// Returns the appropriate CPAL host for the current platform.
-/// On Linux, uses ALSA host. On other platforms, uses the default host.
+/// On Linux, uses the ALSA host, or JACK when set with
+/// `set_linux_audio_host`. On other platforms, uses the default host.
 pub fn get_cpal_host() -> cpal::Host {
     #[cfg(target_os = "linux")]
     {
+        if linux_audio_host() == LinuxAudioHost::Jack {
+            match cpal::host_from_id(cpal::HostId::Jack) {
+                Ok(host) => return host,
+                Err(e) => log::warn!("JACK host unavailable, using ALSA: {}", e),
+            }
+        }
         cpal::host_from_id(cpal::HostId::Alsa).unwrap_or_else(|_| cpal::default_host())
     }
     #[cfg(not(target_os = "linux"))]
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use crate::audio_toolkit::error::Result;
 
 pub enum VadFrame<'a> {
     /// Speech – may aggregate several frames (prefill + current + hangover)
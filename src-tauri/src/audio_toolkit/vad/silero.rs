@@ -1,10 +1,10 @@
-use anyhow::Result;
 use std::path::Path;
 
 use vad_rs::Vad;
 
 use super::{VadFrame, VoiceActivityDetector};
 use crate::audio_toolkit::constants;
+use crate::audio_toolkit::error::{AudioError, Result};
 
 const SILERO_FRAME_MS: u32 = 30;
 const SILERO_FRAME_SAMPLES: usize =
@@ -18,12 +18,14 @@ pub struct SileroVad {
 impl SileroVad {
     pub fn new<P: AsRef<Path>>(model_path: P, threshold: f32) -> Result<Self> {
         if !(0.0..=1.0).contains(&threshold) {
-            anyhow::bail!("threshold must be between 0.0 and 1.0");
+            return Err(AudioError::InvalidConfig(
+                "threshold must be between 0.0 and 1.0".to_string(),
+            ));
         }
 
         Ok(Self {
             engine: Vad::new(&model_path, constants::WHISPER_SAMPLE_RATE as usize)
-                .map_err(|e| anyhow::anyhow!("Failed to create VAD: {e}"))?,
+                .map_err(|e| AudioError::VadModel(e.to_string()))?,
             threshold,
         })
     }
@@ -32,16 +34,16 @@ impl SileroVad {
 impl VoiceActivityDetector for SileroVad {
     fn push_frame<'a>(&'a mut self, frame: &'a [f32]) -> Result<VadFrame<'a>> {
         if frame.len() != SILERO_FRAME_SAMPLES {
-            anyhow::bail!(
+            return Err(AudioError::InvalidConfig(format!(
                 "expected {SILERO_FRAME_SAMPLES} samples, got {}",
                 frame.len()
-            );
+            )));
         }
 
         let result = self
             .engine
             .compute(frame)
-            .map_err(|e| anyhow::anyhow!("Silero VAD error: {e}"))?;
+            .map_err(|e| AudioError::VadModel(e.to_string()))?;
 
         if result.prob > self.threshold {
             Ok(VadFrame::Speech(frame))
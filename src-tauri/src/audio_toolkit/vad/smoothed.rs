@@ -1,5 +1,5 @@
 use super::{VadFrame, VoiceActivityDetector};
-use anyhow::Result;
+use crate::audio_toolkit::error::Result;
 use std::collections::VecDeque;
 
 pub struct SmoothedVad {
@@ -0,0 +1,593 @@
+// Advanced WASAPI capture options
+// cpal opens every Windows stream in shared, event-driven mode with the
+// device's default buffer and the full signal processing chain. When the
+// options ask for anything else, the recorder and the loopback capture open
+// the endpoint here instead: exclusive mode, a buffer of a chosen length,
+// polling, or raw mode, which skips the driver's and Windows' effects.
+
+use serde::{Deserialize, Serialize};
+
+/// Longest device buffer the options accept
+pub const MAX_BUFFER_MS: u32 = 500;
+
+/// How a WASAPI endpoint is opened. The default matches what cpal does.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct WasapiOptions {
+    /// Take the microphone for Handy alone, bypassing the Windows mixer.
+    /// Loopback capture is always shared.
+    pub exclusive: bool,
+    /// Wake when the device signals a full buffer instead of polling
+    pub event_driven: bool,
+    /// Length of the device buffer; 0 keeps the device default
+    pub buffer_ms: u32,
+    /// Skip signal processing like automatic gain and noise suppression
+    pub raw: bool,
+}
+
+impl Default for WasapiOptions {
+    fn default() -> Self {
+        Self {
+            exclusive: false,
+            event_driven: true,
+            buffer_ms: 0,
+            raw: false,
+        }
+    }
+}
+
+impl WasapiOptions {
+    /// Whether cpal can open the stream as asked
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The requested buffer in 100ns units, 0 for the device default
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    fn buffer_duration(&self) -> i64 {
+        self.buffer_ms.min(MAX_BUFFER_MS) as i64 * 10_000
+    }
+}
+
+/// Duration in 100ns units of `frames` at `sample_rate`, rounded the way
+/// WASAPI expects when realigning an exclusive-mode buffer
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn aligned_duration(frames: u32, sample_rate: u32) -> i64 {
+    (10_000_000.0 * frames as f64 / sample_rate as f64 + 0.5) as i64
+}
+
+#[cfg(target_os = "windows")]
+pub use native::{open, Flow, WasapiStream};
+
+#[cfg(target_os = "windows")]
+mod native {
+    use super::{aligned_duration, WasapiOptions};
+    use crate::audio_toolkit::error::{AudioError, Result};
+    use crate::audio_toolkit::glitch;
+    use crate::audio_toolkit::system_audio::CaptureFormat;
+    use crate::audio_toolkit::timed;
+    use crate::metrics::{CaptureStream, Glitch};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use windows::core::{Interface, PCWSTR};
+    use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName;
+    use windows::Win32::Foundation::{CloseHandle, HANDLE, S_OK};
+    use windows::Win32::Media::Audio::{
+        eCapture, eConsole, eRender, AudioCategory_Other, AudioClientProperties, EDataFlow,
+        IAudioCaptureClient, IAudioClient, IAudioClient2, IMMDevice, IMMDeviceEnumerator,
+        MMDeviceEnumerator, AUDCLNT_BUFFERFLAGS_DATA_DISCONTINUITY, AUDCLNT_BUFFERFLAGS_SILENT,
+        AUDCLNT_BUFFERFLAGS_TIMESTAMP_ERROR, AUDCLNT_E_BUFFER_SIZE_NOT_ALIGNED,
+        AUDCLNT_E_DEVICE_INVALIDATED, AUDCLNT_E_DEVICE_IN_USE,
+        AUDCLNT_E_EXCLUSIVE_MODE_NOT_ALLOWED, AUDCLNT_E_UNSUPPORTED_FORMAT, AUDCLNT_SHAREMODE,
+        AUDCLNT_SHAREMODE_EXCLUSIVE, AUDCLNT_SHAREMODE_SHARED, AUDCLNT_STREAMFLAGS_EVENTCALLBACK,
+        AUDCLNT_STREAMFLAGS_LOOPBACK, AUDCLNT_STREAMOPTIONS_RAW, DEVICE_STATE_ACTIVE, WAVEFORMATEX,
+        WAVEFORMATEXTENSIBLE, WAVE_FORMAT_PCM,
+    };
+    use windows::Win32::Media::KernelStreaming::WAVE_FORMAT_EXTENSIBLE;
+    use windows::Win32::Media::Multimedia::{
+        KSDATAFORMAT_SUBTYPE_IEEE_FLOAT, WAVE_FORMAT_IEEE_FLOAT,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoTaskMemFree, CLSCTX_ALL, COINIT_MULTITHREADED,
+        STGM_READ,
+    };
+    use windows::Win32::System::Performance::{QueryPerformanceCounter, QueryPerformanceFrequency};
+    use windows::Win32::System::Threading::{CreateEventW, WaitForSingleObject};
+
+    // How long a wait for the buffer event lasts before the buffer is read
+    // anyway; loopback streams on older Windows never signal it
+    const EVENT_TIMEOUT_MS: u32 = 100;
+
+    /// Which side of an endpoint is captured
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Flow {
+        /// A microphone or other input
+        Capture,
+        /// What an output device plays
+        Loopback,
+    }
+
+    /// A running capture, stopped on drop
+    pub struct WasapiStream {
+        stop: Arc<AtomicBool>,
+        thread: Option<thread::JoinHandle<()>>,
+    }
+
+    impl Drop for WasapiStream {
+        fn drop(&mut self) {
+            self.stop.store(true, Ordering::Relaxed);
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    /// Open the endpoint named `device`, or the default one, with `options`
+    /// and hand its interleaved samples, channel count and capture time to
+    /// `sink` on the capture thread. Returns the stream and its format.
+    pub fn open<F>(
+        device: Option<String>,
+        flow: Flow,
+        options: WasapiOptions,
+        sink: F,
+    ) -> Result<(WasapiStream, CaptureFormat)>
+    where
+        F: FnMut(&[f32], usize, Instant) + Send + 'static,
+    {
+        if options.exclusive && flow == Flow::Loopback {
+            return Err(AudioError::InvalidConfig(
+                "loopback capture can't use exclusive mode".to_string(),
+            ));
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let thread_stop = Arc::clone(&stop);
+        // COM objects stay on the thread that created them
+        let thread = thread::spawn(move || {
+            let session = match unsafe { Session::open(device.as_deref(), flow, options) } {
+                Ok(session) => {
+                    let _ = ready_tx.send(Ok(session.format));
+                    session
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            if let Err(e) = unsafe { session.pump(&thread_stop, sink) } {
+                log::error!("WASAPI capture stopped: {}", e);
+            }
+        });
+
+        match ready_rx.recv() {
+            Ok(Ok(format)) => Ok((
+                WasapiStream {
+                    stop,
+                    thread: Some(thread),
+                },
+                format,
+            )),
+            Ok(Err(e)) => {
+                let _ = thread.join();
+                Err(e)
+            }
+            Err(_) => {
+                let _ = thread.join();
+                Err(AudioError::StreamClosed)
+            }
+        }
+    }
+
+    fn wasapi_error(context: &str, e: windows::core::Error) -> AudioError {
+        let message = format!("{}: {}", context, e);
+        match e.code() {
+            AUDCLNT_E_DEVICE_IN_USE => AudioError::StreamBuildFailed(message),
+            AUDCLNT_E_UNSUPPORTED_FORMAT => AudioError::UnsupportedFormat(message),
+            AUDCLNT_E_DEVICE_INVALIDATED => AudioError::DeviceNotFound(message),
+            AUDCLNT_E_EXCLUSIVE_MODE_NOT_ALLOWED => AudioError::InvalidConfig(format!(
+                "{} (allow exclusive control in the device's Sound properties)",
+                message
+            )),
+            _ => AudioError::Backend(message),
+        }
+    }
+
+    /// Sample layout of the buffers the endpoint delivers
+    #[derive(Debug, Clone, Copy)]
+    enum SampleKind {
+        F32,
+        I16,
+        I32,
+    }
+
+    impl SampleKind {
+        fn of(format: &WAVEFORMATEXTENSIBLE) -> Option<Self> {
+            let tag = format.Format.wFormatTag as u32;
+            let bits = format.Format.wBitsPerSample;
+            let sub_format = format.SubFormat;
+            let float = tag == WAVE_FORMAT_IEEE_FLOAT
+                || (tag == WAVE_FORMAT_EXTENSIBLE && sub_format == KSDATAFORMAT_SUBTYPE_IEEE_FLOAT);
+            match (float, bits) {
+                (true, 32) => Some(Self::F32),
+                (false, 16) => Some(Self::I16),
+                (false, 32) => Some(Self::I32),
+                _ => None,
+            }
+        }
+
+        unsafe fn convert(self, data: *const u8, count: usize, out: &mut Vec<f32>) {
+            match self {
+                Self::F32 => {
+                    out.extend_from_slice(std::slice::from_raw_parts(data as *const f32, count))
+                }
+                Self::I16 => out.extend(
+                    std::slice::from_raw_parts(data as *const i16, count)
+                        .iter()
+                        .map(|&s| s as f32 / 32_768.0),
+                ),
+                Self::I32 => out.extend(
+                    std::slice::from_raw_parts(data as *const i32, count)
+                        .iter()
+                        .map(|&s| s as f32 / 2_147_483_648.0),
+                ),
+            }
+        }
+    }
+
+    unsafe fn friendly_name(device: &IMMDevice) -> Option<String> {
+        let store = device.OpenPropertyStore(STGM_READ).ok()?;
+        let value = store.GetValue(&PKEY_Device_FriendlyName).ok()?;
+        Some(value.to_string())
+    }
+
+    // The active endpoint cpal calls `name`, or the default one
+    unsafe fn find_endpoint(
+        enumerator: &IMMDeviceEnumerator,
+        flow: EDataFlow,
+        name: Option<&str>,
+    ) -> Result<IMMDevice> {
+        let Some(name) = name else {
+            return enumerator
+                .GetDefaultAudioEndpoint(flow, eConsole)
+                .map_err(|e| AudioError::DeviceNotFound(format!("no default endpoint: {}", e)));
+        };
+        let endpoints = enumerator
+            .EnumAudioEndpoints(flow, DEVICE_STATE_ACTIVE)
+            .map_err(|e| wasapi_error("Failed to list endpoints", e))?;
+        let count = endpoints
+            .GetCount()
+            .map_err(|e| wasapi_error("Failed to list endpoints", e))?;
+        (0..count)
+            .filter_map(|i| endpoints.Item(i).ok())
+            .find(|device| friendly_name(device).as_deref() == Some(name))
+            .ok_or_else(|| AudioError::DeviceNotFound(name.to_string()))
+    }
+
+    unsafe fn activate(device: &IMMDevice, raw: bool) -> Result<IAudioClient> {
+        let client: IAudioClient = device
+            .Activate(CLSCTX_ALL, None)
+            .map_err(|e| wasapi_error("Failed to activate the endpoint", e))?;
+        if raw {
+            let properties = AudioClientProperties {
+                cbSize: std::mem::size_of::<AudioClientProperties>() as u32,
+                bIsOffload: false.into(),
+                eCategory: AudioCategory_Other,
+                Options: AUDCLNT_STREAMOPTIONS_RAW,
+            };
+            // Endpoints without raw support keep their processing
+            let set = client
+                .cast::<IAudioClient2>()
+                .and_then(|client| client.SetClientProperties(&properties));
+            if let Err(e) = set {
+                log::warn!("Endpoint doesn't support raw mode: {}", e);
+            }
+        }
+        Ok(client)
+    }
+
+    // The mix format, or for exclusive mode the first of it and 16-bit PCM
+    // at the same rate the endpoint accepts
+    unsafe fn pick_format(
+        client: &IAudioClient,
+        share_mode: AUDCLNT_SHAREMODE,
+    ) -> Result<WAVEFORMATEXTENSIBLE> {
+        let mix = client
+            .GetMixFormat()
+            .map_err(|e| wasapi_error("Failed to read the mix format", e))?;
+        let mut format = WAVEFORMATEXTENSIBLE::default();
+        if (*mix).wFormatTag as u32 == WAVE_FORMAT_EXTENSIBLE {
+            format = *(mix as *const WAVEFORMATEXTENSIBLE);
+        } else {
+            format.Format = *mix;
+        }
+        CoTaskMemFree(Some(mix as *const _));
+
+        if share_mode == AUDCLNT_SHAREMODE_SHARED
+            || client.IsFormatSupported(share_mode, &format.Format, None) == S_OK
+        {
+            return Ok(format);
+        }
+
+        let channels = format.Format.nChannels;
+        let sample_rate = format.Format.nSamplesPerSec;
+        let mut pcm = WAVEFORMATEXTENSIBLE::default();
+        pcm.Format = WAVEFORMATEX {
+            wFormatTag: WAVE_FORMAT_PCM as u16,
+            nChannels: channels,
+            nSamplesPerSec: sample_rate,
+            nAvgBytesPerSec: sample_rate * channels as u32 * 2,
+            nBlockAlign: channels * 2,
+            wBitsPerSample: 16,
+            cbSize: 0,
+        };
+        if client.IsFormatSupported(share_mode, &pcm.Format, None) == S_OK {
+            return Ok(pcm);
+        }
+        Err(AudioError::UnsupportedFormat(format!(
+            "the endpoint takes neither its mix format nor 16-bit PCM at {}Hz in exclusive mode",
+            sample_rate
+        )))
+    }
+
+    /// An initialized, started endpoint; stopped on drop
+    struct Session {
+        client: IAudioClient,
+        capture: IAudioCaptureClient,
+        event: Option<HANDLE>,
+        kind: SampleKind,
+        format: CaptureFormat,
+        poll_interval: Duration,
+        flow: Flow,
+    }
+
+    impl Session {
+        unsafe fn open(name: Option<&str>, flow: Flow, options: WasapiOptions) -> Result<Self> {
+            // Does nothing if COM is already initialized on this thread
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                    .map_err(|e| wasapi_error("Failed to create the device enumerator", e))?;
+            let data_flow = match flow {
+                Flow::Capture => eCapture,
+                Flow::Loopback => eRender,
+            };
+            let device = find_endpoint(&enumerator, data_flow, name)?;
+
+            let share_mode = if options.exclusive {
+                AUDCLNT_SHAREMODE_EXCLUSIVE
+            } else {
+                AUDCLNT_SHAREMODE_SHARED
+            };
+            let mut client = activate(&device, options.raw)?;
+            let format = pick_format(&client, share_mode)?;
+            let kind = SampleKind::of(&format).ok_or_else(|| {
+                AudioError::UnsupportedFormat(format!(
+                    "{}-bit samples with format tag {}",
+                    { format.Format.wBitsPerSample },
+                    { format.Format.wFormatTag }
+                ))
+            })?;
+            let sample_rate = format.Format.nSamplesPerSec;
+            let channels = format.Format.nChannels;
+
+            let mut flags = 0;
+            if flow == Flow::Loopback {
+                flags |= AUDCLNT_STREAMFLAGS_LOOPBACK;
+            }
+            if options.event_driven {
+                flags |= AUDCLNT_STREAMFLAGS_EVENTCALLBACK;
+            }
+
+            // Exclusive streams need a buffer of at least the minimum period,
+            // and their period equals the buffer
+            let mut buffer = options.buffer_duration();
+            if options.exclusive {
+                let (mut default_period, mut min_period) = (0i64, 0i64);
+                client
+                    .GetDevicePeriod(
+                        Some(&mut default_period as *mut _),
+                        Some(&mut min_period as *mut _),
+                    )
+                    .map_err(|e| wasapi_error("Failed to read the device period", e))?;
+                buffer = if buffer == 0 {
+                    default_period
+                } else {
+                    buffer.max(min_period)
+                };
+            }
+            let period = if options.exclusive { buffer } else { 0 };
+
+            let initialized =
+                client.Initialize(share_mode, flags, buffer, period, &format.Format, None);
+            match initialized {
+                Err(e) if e.code() == AUDCLNT_E_BUFFER_SIZE_NOT_ALIGNED => {
+                    // Ask again for the nearest buffer the device can do
+                    let frames = client
+                        .GetBufferSize()
+                        .map_err(|e| wasapi_error("Failed to read the buffer size", e))?;
+                    let buffer = aligned_duration(frames, sample_rate);
+                    client = activate(&device, options.raw)?;
+                    client
+                        .Initialize(share_mode, flags, buffer, buffer, &format.Format, None)
+                        .map_err(|e| wasapi_error("Failed to open the endpoint", e))?;
+                }
+                result => result.map_err(|e| wasapi_error("Failed to open the endpoint", e))?,
+            }
+
+            let event = if options.event_driven {
+                let event = CreateEventW(None, false, false, PCWSTR::null())
+                    .map_err(|e| wasapi_error("Failed to create the buffer event", e))?;
+                if let Err(e) = client.SetEventHandle(event) {
+                    let _ = CloseHandle(event);
+                    return Err(wasapi_error("Failed to set the buffer event", e));
+                }
+                Some(event)
+            } else {
+                None
+            };
+
+            let capture: IAudioCaptureClient = client
+                .GetService()
+                .map_err(|e| wasapi_error("Failed to get the capture client", e))?;
+            let buffer_frames = client
+                .GetBufferSize()
+                .map_err(|e| wasapi_error("Failed to read the buffer size", e))?;
+            // Polling reads the buffer twice per length of it
+            let poll_interval =
+                Duration::from_secs_f64(buffer_frames as f64 / sample_rate as f64 / 2.0)
+                    .max(Duration::from_millis(1));
+
+            client
+                .Start()
+                .map_err(|e| wasapi_error("Failed to start the endpoint", e))?;
+            log::info!(
+                "WASAPI {:?} capture on {:?}: {}Hz, {} channels, {:?}, buffer {} frames, {}, {}{}",
+                flow,
+                name.unwrap_or("default endpoint"),
+                sample_rate,
+                channels,
+                kind,
+                buffer_frames,
+                if options.exclusive {
+                    "exclusive"
+                } else {
+                    "shared"
+                },
+                if options.event_driven {
+                    "event-driven"
+                } else {
+                    "polled"
+                },
+                if options.raw { ", raw" } else { "" }
+            );
+
+            Ok(Self {
+                client,
+                capture,
+                event,
+                kind,
+                format: CaptureFormat {
+                    sample_rate,
+                    channels,
+                },
+                poll_interval,
+                flow,
+            })
+        }
+
+        /// Hand every buffer to `sink` until `stop` is set
+        unsafe fn pump<F>(&self, stop: &AtomicBool, mut sink: F) -> windows::core::Result<()>
+        where
+            F: FnMut(&[f32], usize, Instant),
+        {
+            let channels = self.format.channels as usize;
+            let stream = match self.flow {
+                Flow::Capture => CaptureStream::Microphone,
+                Flow::Loopback => CaptureStream::SystemAudio,
+            };
+            let mut samples = Vec::new();
+            // The first packet after Start is flagged as a break as well
+            let mut started = false;
+            while !stop.load(Ordering::Relaxed) {
+                match self.event {
+                    Some(event) => {
+                        WaitForSingleObject(event, EVENT_TIMEOUT_MS);
+                    }
+                    None => thread::sleep(self.poll_interval),
+                }
+                while self.capture.GetNextPacketSize()? > 0 {
+                    let mut data = std::ptr::null_mut();
+                    let mut frames = 0u32;
+                    let mut flags = 0u32;
+                    let mut qpc_position = 0u64;
+                    self.capture.GetBuffer(
+                        &mut data,
+                        &mut frames,
+                        &mut flags,
+                        None,
+                        Some(&mut qpc_position),
+                    )?;
+                    let timestamp_error = flags & AUDCLNT_BUFFERFLAGS_TIMESTAMP_ERROR.0 as u32 != 0;
+                    if flags & AUDCLNT_BUFFERFLAGS_DATA_DISCONTINUITY.0 as u32 != 0 && started {
+                        glitch::report(stream, Glitch::Discontinuity);
+                    }
+                    started = true;
+                    let captured_at = if timestamp_error {
+                        Instant::now()
+                    } else {
+                        qpc_instant(qpc_position)
+                    };
+                    let count = frames as usize * channels;
+                    samples.clear();
+                    if data.is_null() || flags & AUDCLNT_BUFFERFLAGS_SILENT.0 as u32 != 0 {
+                        samples.resize(count, 0.0);
+                    } else {
+                        self.kind.convert(data, count, &mut samples);
+                    }
+                    self.capture.ReleaseBuffer(frames)?;
+                    sink(&samples, channels, captured_at);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    // When the buffer WASAPI stamped at `qpc_position`, in 100ns units of the
+    // performance counter, was captured
+    unsafe fn qpc_instant(qpc_position: u64) -> Instant {
+        let mut counter = 0i64;
+        let mut frequency = 0i64;
+        if QueryPerformanceCounter(&mut counter).is_err()
+            || QueryPerformanceFrequency(&mut frequency).is_err()
+            || frequency <= 0
+        {
+            return Instant::now();
+        }
+        let now = (counter as i128 * 10_000_000 / frequency as i128) as u64;
+        timed::captured_before(Duration::from_nanos(now.saturating_sub(qpc_position) * 100))
+    }
+
+    impl Drop for Session {
+        fn drop(&mut self) {
+            unsafe {
+                let _ = self.client.Stop();
+                if let Some(event) = self.event {
+                    let _ = CloseHandle(event);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_options_and_durations() {
+        assert!(WasapiOptions::default().is_default());
+        let raw = WasapiOptions {
+            raw: true,
+            ..Default::default()
+        };
+        assert!(!raw.is_default());
+
+        let options = WasapiOptions {
+            buffer_ms: 20,
+            ..Default::default()
+        };
+        assert_eq!(options.buffer_duration(), 200_000);
+        let options = WasapiOptions {
+            buffer_ms: 5_000,
+            ..Default::default()
+        };
+        assert_eq!(options.buffer_duration(), MAX_BUFFER_MS as i64 * 10_000);
+
+        // 480 frames at 48kHz are 10ms
+        assert_eq!(aligned_duration(480, 48_000), 100_000);
+        assert_eq!(aligned_duration(441, 44_100), 100_000);
+    }
+}
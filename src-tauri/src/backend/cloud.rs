@@ -0,0 +1,442 @@
+use super::{
+    load_local_backend, BackendCapabilities, ComputeBackend, Granularity, InferenceRequest,
+    TimedSegment, Transcript, TranscriptionBackend,
+};
+use crate::cloud_quota;
+use crate::managers::model::EngineType;
+use crate::settings::{get_settings, AppSettings};
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+use serde::Deserialize;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::AppHandle;
+
+const SAMPLE_RATE: u32 = 16000;
+// OpenAI rejects uploads over 25MB; ten minutes of 16-bit mono is ~19MB
+const CHUNK_SECS: usize = 600;
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Why an upload failed, which decides whether it is retried or falls back
+#[derive(Debug)]
+enum CloudError {
+    /// The endpoint couldn't be reached at all
+    Offline(String),
+    /// The endpoint answered with an error status
+    Status(u16, String),
+    Other(String),
+}
+
+impl CloudError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            CloudError::Offline(_) => true,
+            CloudError::Status(status, _) => *status == 429 || *status >= 500,
+            CloudError::Other(_) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for CloudError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CloudError::Offline(e) => write!(f, "endpoint unreachable: {}", e),
+            CloudError::Status(status, body) => write!(f, "HTTP {}: {}", status, body),
+            CloudError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for CloudError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_connect() || e.is_timeout() {
+            CloudError::Offline(e.to_string())
+        } else {
+            CloudError::Other(e.to_string())
+        }
+    }
+}
+
+/// `verbose_json` response of the OpenAI-compatible audio endpoints
+#[derive(Debug, Default, Deserialize)]
+struct VerboseTranscription {
+    text: String,
+    #[serde(default)]
+    segments: Vec<ApiSegment>,
+    #[serde(default)]
+    words: Vec<ApiWord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiSegment {
+    start: f32,
+    end: f32,
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiWord {
+    word: String,
+    start: f32,
+    end: f32,
+}
+
+impl VerboseTranscription {
+    /// Word timings when asked for and available, otherwise segment timings,
+    /// shifted by `offset` seconds
+    fn timed_segments(self, granularity: Granularity, offset: f32) -> Vec<TimedSegment> {
+        if granularity == Granularity::Word && !self.words.is_empty() {
+            self.words
+                .into_iter()
+                .map(|w| TimedSegment {
+                    start: w.start + offset,
+                    end: w.end + offset,
+                    text: w.word,
+                })
+                .collect()
+        } else {
+            self.segments
+                .into_iter()
+                .map(|s| TimedSegment {
+                    start: s.start + offset,
+                    end: s.end + offset,
+                    text: s.text,
+                })
+                .collect()
+        }
+    }
+}
+
+/// Encode 16kHz mono samples as a 16-bit PCM WAV file
+fn encode_wav(samples: &[f32]) -> Result<Vec<u8>> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut cursor = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut cursor, spec)?;
+    for &sample in samples {
+        writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+    }
+    writer.finalize()?;
+    Ok(cursor.into_inner())
+}
+
+/// multipart/form-data body with text `fields` and the WAV as `file`
+fn multipart_body(boundary: &str, fields: &[(&str, String)], wav: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(wav.len() + 512);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                boundary, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"audio.wav\"\r\nContent-Type: audio/wav\r\n\r\n",
+            boundary
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(wav);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// Run a future to completion on its own thread. Backends are called both
+/// from blocking threads and from inside async tasks, and blocking on the
+/// runtime from a worker thread would panic.
+fn block_on_thread<T: Send + 'static>(
+    future: impl std::future::Future<Output = T> + Send + 'static,
+) -> Result<T> {
+    std::thread::spawn(move || tauri::async_runtime::block_on(future))
+        .join()
+        .map_err(|_| anyhow!("Cloud request thread panicked"))
+}
+
+/// Downloaded model used while the cloud endpoint is unreachable. Loaded on
+/// first use so a working connection costs no memory.
+pub struct LocalFallback {
+    engine_type: EngineType,
+    model_path: PathBuf,
+    compute: ComputeBackend,
+    backend: Option<Box<dyn TranscriptionBackend>>,
+}
+
+impl LocalFallback {
+    pub fn new(engine_type: EngineType, model_path: PathBuf, compute: ComputeBackend) -> Self {
+        Self {
+            engine_type,
+            model_path,
+            compute,
+            backend: None,
+        }
+    }
+
+    fn transcribe(&mut self, audio: Vec<f32>, request: &InferenceRequest) -> Result<Transcript> {
+        if self.backend.is_none() {
+            info!("☁️ [Cloud] Loading local model as offline fallback");
+            self.backend = Some(load_local_backend(
+                &self.engine_type,
+                &self.model_path,
+                self.compute,
+            )?);
+        }
+        self.backend
+            .as_mut()
+            .expect("Fallback backend loaded above")
+            .transcribe(audio, request)
+    }
+}
+
+/// OpenAI-compatible speech-to-text endpoints (OpenAI, Groq, ...)
+pub struct CloudBackend {
+    app: AppHandle,
+    client: reqwest::Client,
+    fallback: Option<LocalFallback>,
+}
+
+impl CloudBackend {
+    pub fn new(app: &AppHandle, fallback: Option<LocalFallback>) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| anyhow!("Failed to build HTTP client: {}", e))?;
+        Ok(Self {
+            app: app.clone(),
+            client,
+            fallback,
+        })
+    }
+
+    /// Upload one chunk, retrying transient failures with exponential backoff
+    fn upload(
+        &self,
+        settings: &AppSettings,
+        chunk: &[f32],
+        request: &InferenceRequest,
+    ) -> Result<VerboseTranscription, CloudError> {
+        let task = if request.translate {
+            "translations"
+        } else {
+            "transcriptions"
+        };
+        let url = format!(
+            "{}/audio/{}",
+            settings.cloud_api_endpoint.trim_end_matches('/'),
+            task
+        );
+
+        let mut fields = vec![
+            ("model", settings.cloud_model.clone()),
+            ("response_format", "verbose_json".to_string()),
+            ("temperature", request.temperature.to_string()),
+        ];
+        if !request.translate {
+            if let Some(language) = &request.language {
+                fields.push(("language", language.clone()));
+            }
+            if let Some(prompt) = &request.initial_prompt {
+                fields.push(("prompt", prompt.clone()));
+            }
+            if request.granularity == Granularity::Word {
+                fields.push(("timestamp_granularities[]", "word".to_string()));
+                fields.push(("timestamp_granularities[]", "segment".to_string()));
+            }
+        }
+
+        let wav = encode_wav(chunk).map_err(|e| CloudError::Other(e.to_string()))?;
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let boundary = format!("handy-{:x}", nanos);
+        let body = multipart_body(&boundary, &fields, &wav);
+
+        let mut attempt = 1;
+        loop {
+            let client = self.client.clone();
+            let url = url.clone();
+            let api_key = settings.cloud_api_key.clone();
+            let content_type = format!("multipart/form-data; boundary={}", boundary);
+            let body = body.clone();
+            let result = block_on_thread(async move {
+                let response = client
+                    .post(url)
+                    .bearer_auth(api_key)
+                    .header(reqwest::header::CONTENT_TYPE, content_type)
+                    .body(body)
+                    .send()
+                    .await?;
+                let status = response.status();
+                if !status.is_success() {
+                    let text = response.text().await.unwrap_or_default();
+                    return Err(CloudError::Status(status.as_u16(), text));
+                }
+                response
+                    .json::<VerboseTranscription>()
+                    .await
+                    .map_err(|e| CloudError::Other(format!("Invalid response: {}", e)))
+            })
+            .map_err(|e| CloudError::Other(e.to_string()))
+            .and_then(|result| result);
+
+            match result {
+                Err(e) if e.is_retryable() && attempt < MAX_ATTEMPTS => {
+                    let delay = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+                    warn!(
+                        "☁️ [Cloud] Attempt {} failed ({}), retrying in {}ms",
+                        attempt,
+                        e,
+                        delay.as_millis()
+                    );
+                    std::thread::sleep(delay);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn transcribe_cloud(
+        &self,
+        settings: &AppSettings,
+        audio: &[f32],
+        request: &InferenceRequest,
+    ) -> Result<Transcript, CloudError> {
+        let mut transcript = Transcript::default();
+        for (i, chunk) in audio.chunks(CHUNK_SECS * SAMPLE_RATE as usize).enumerate() {
+            let secs = chunk.len() as f64 / SAMPLE_RATE as f64;
+            cloud_quota::check_quota(&self.app, secs).map_err(|exceeded| {
+                CloudError::Other(format!(
+                    "Daily cloud quota of {} minutes reached",
+                    exceeded.daily_limit_minutes
+                ))
+            })?;
+
+            let response = self.upload(settings, chunk, request)?;
+            cloud_quota::record_usage(&self.app, secs);
+
+            let offset = (i * CHUNK_SECS) as f32;
+            if !transcript.text.is_empty() {
+                transcript.text.push(' ');
+            }
+            transcript.text.push_str(response.text.trim());
+            transcript
+                .segments
+                .extend(response.timed_segments(request.granularity, offset));
+        }
+        Ok(transcript)
+    }
+}
+
+impl TranscriptionBackend for CloudBackend {
+    fn name(&self) -> &'static str {
+        "cloud"
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            streaming: false,
+            segment_timestamps: true,
+            word_timestamps: true,
+            translation: true,
+            language_selection: true,
+            language_detection: true,
+            local: false,
+        }
+    }
+
+    fn transcribe(&mut self, audio: Vec<f32>, request: &InferenceRequest) -> Result<Transcript> {
+        let settings = get_settings(&self.app);
+        if settings.cloud_api_key.trim().is_empty() {
+            return Err(anyhow!("No API key is set for cloud transcription"));
+        }
+
+        let st = std::time::Instant::now();
+        match self.transcribe_cloud(&settings, &audio, request) {
+            Ok(transcript) => {
+                debug!(
+                    "☁️ [Cloud] Transcribed {:.1}s of audio in {}ms",
+                    audio.len() as f32 / SAMPLE_RATE as f32,
+                    st.elapsed().as_millis()
+                );
+                Ok(transcript)
+            }
+            Err(CloudError::Offline(e)) if settings.cloud_fallback_to_local => {
+                match self.fallback.as_mut() {
+                    Some(fallback) => {
+                        warn!("☁️ [Cloud] Offline ({}), transcribing locally", e);
+                        fallback.transcribe(audio, request)
+                    }
+                    None => Err(anyhow!(
+                        "Cloud endpoint unreachable ({}) and no local model is downloaded",
+                        e
+                    )),
+                }
+            }
+            Err(e) => Err(anyhow!("Cloud transcription failed: {}", e)),
+        }
+    }
+
+    fn unload(&mut self) {
+        if let Some(backend) = self.fallback.as_mut().and_then(|f| f.backend.as_mut()) {
+            backend.unload();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wav_and_multipart_encoding() {
+        let wav = encode_wav(&vec![0.5; 1600]).unwrap();
+        let reader = hound::WavReader::new(Cursor::new(wav.clone())).unwrap();
+        assert_eq!(reader.spec().sample_rate, 16000);
+        assert_eq!(reader.len(), 1600);
+
+        let body = multipart_body("b", &[("model", "whisper-1".to_string())], &wav);
+        let text = String::from_utf8_lossy(&body);
+        assert!(text.starts_with(
+            "--b\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n"
+        ));
+        assert!(text.contains("name=\"file\"; filename=\"audio.wav\""));
+        assert!(text.ends_with("\r\n--b--\r\n"));
+    }
+
+    #[test]
+    fn test_response_timings_are_offset() {
+        let json = r#"{
+            "text": "hello world",
+            "segments": [{"id": 0, "start": 0.0, "end": 1.2, "text": " hello world"}],
+            "words": [
+                {"word": "hello", "start": 0.1, "end": 0.5},
+                {"word": "world", "start": 0.6, "end": 1.1}
+            ]
+        }"#;
+        let parse = || serde_json::from_str::<VerboseTranscription>(json).unwrap();
+
+        let words = parse().timed_segments(Granularity::Word, 600.0);
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[1].text, "world");
+        assert!((words[1].start - 600.6).abs() < 1e-3);
+
+        let segments = parse().timed_segments(Granularity::Segment, 0.0);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].end, 1.2);
+
+        assert!(CloudError::Status(503, String::new()).is_retryable());
+        assert!(CloudError::Status(429, String::new()).is_retryable());
+        assert!(!CloudError::Status(401, String::new()).is_retryable());
+    }
+}
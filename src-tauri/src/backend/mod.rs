@@ -0,0 +1,226 @@
+// Speech-to-text engines behind a common interface
+// `TranscriptionManager` owns one loaded backend at a time and talks to it only
+// through `TranscriptionBackend`, so adding an engine means adding a module
+// here rather than touching the caption or recording code.
+
+mod cloud;
+mod parakeet;
+mod whisper;
+
+pub use cloud::{CloudBackend, LocalFallback};
+pub use parakeet::ParakeetBackend;
+pub use whisper::WhisperBackend;
+
+use crate::helpers::hardware::{hardware_profile, GpuAcceleration};
+use crate::managers::model::EngineType;
+use anyhow::Result;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A piece of transcribed text with its position in the input audio
+#[derive(Clone, Debug, Serialize)]
+pub struct TimedSegment {
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+}
+
+/// Output of one inference run
+#[derive(Clone, Debug, Default)]
+pub struct Transcript {
+    pub text: String,
+    /// Empty when the backend doesn't report timings
+    pub segments: Vec<TimedSegment>,
+}
+
+/// How finely segment timings should be reported
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Granularity {
+    Segment,
+    Word,
+}
+
+/// Per-call decoding options, resolved from settings by the manager
+#[derive(Clone, Debug)]
+pub struct InferenceRequest {
+    /// ISO 639-1 code, or `None` to auto-detect
+    pub language: Option<String>,
+    /// Translate the speech into English instead of transcribing it
+    pub translate: bool,
+    pub granularity: Granularity,
+    /// Beam search width, or `None` for the engine's default
+    pub beam_size: Option<u32>,
+    pub temperature: f32,
+    pub no_speech_threshold: f32,
+    /// Text the audio follows on from, so names and terms stay consistent
+    pub initial_prompt: Option<String>,
+    /// Words and names to favour while decoding. Backends that can't bias
+    /// their decoder ignore them.
+    pub keywords: Vec<String>,
+    /// Added to the logits of keyword tokens
+    pub keyword_bias: f32,
+}
+
+/// What a backend can do, so callers can adapt instead of assuming Whisper
+#[derive(Clone, Debug, Serialize)]
+pub struct BackendCapabilities {
+    /// Accepts audio incrementally rather than whole windows
+    pub streaming: bool,
+    pub segment_timestamps: bool,
+    pub word_timestamps: bool,
+    /// Supports `InferenceRequest::translate`
+    pub translation: bool,
+    /// Honours `InferenceRequest::language`
+    pub language_selection: bool,
+    /// Identifies the spoken language by itself
+    pub language_detection: bool,
+    /// Audio never leaves the machine
+    pub local: bool,
+}
+
+/// A loaded speech-to-text engine
+pub trait TranscriptionBackend: Send {
+    /// Short identifier for logs and the UI
+    fn name(&self) -> &'static str;
+
+    fn capabilities(&self) -> BackendCapabilities;
+
+    /// Transcribe 16kHz mono audio
+    fn transcribe(&mut self, audio: Vec<f32>, request: &InferenceRequest) -> Result<Transcript>;
+
+    /// Release the model's memory; the backend is dropped afterwards
+    fn unload(&mut self);
+
+    /// The device the model runs on, or `None` when it runs remotely
+    fn compute_backend(&self) -> Option<ComputeBackend> {
+        None
+    }
+}
+
+/// Where transcription runs
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    /// The selected downloaded model, on this machine
+    Local,
+    /// An OpenAI-compatible speech-to-text endpoint
+    Cloud,
+}
+
+impl Default for BackendKind {
+    fn default() -> Self {
+        BackendKind::Local
+    }
+}
+
+/// Device local models run on. `Auto` is only ever requested; a loaded
+/// backend reports the device it settled on.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ComputeBackend {
+    Auto,
+    Cpu,
+    Metal,
+    Cuda,
+    Vulkan,
+}
+
+impl Default for ComputeBackend {
+    fn default() -> Self {
+        ComputeBackend::Auto
+    }
+}
+
+impl ComputeBackend {
+    /// GPU backends this build of whisper.cpp includes
+    pub fn compiled_gpus() -> Vec<ComputeBackend> {
+        let mut gpus = Vec::new();
+        if cfg!(target_os = "macos") {
+            gpus.push(ComputeBackend::Metal);
+        }
+        if cfg!(feature = "cuda") {
+            gpus.push(ComputeBackend::Cuda);
+        }
+        if cfg!(feature = "vulkan") {
+            gpus.push(ComputeBackend::Vulkan);
+        }
+        gpus
+    }
+
+    /// The device to load on when this backend is requested, given the GPU
+    /// backends `compiled` in and the GPU the hardware probe found
+    pub fn resolve(
+        self,
+        compiled: &[ComputeBackend],
+        probed: Option<GpuAcceleration>,
+    ) -> ComputeBackend {
+        match self {
+            ComputeBackend::Cpu => ComputeBackend::Cpu,
+            ComputeBackend::Auto => compiled
+                .iter()
+                .copied()
+                .find(|gpu| match gpu {
+                    ComputeBackend::Metal => probed == Some(GpuAcceleration::Metal),
+                    ComputeBackend::Cuda => probed == Some(GpuAcceleration::Cuda),
+                    // Vulkan drivers can't be probed cheaply; whisper.cpp
+                    // falls back to the CPU when there is no device
+                    _ => true,
+                })
+                .unwrap_or(ComputeBackend::Cpu),
+            requested if compiled.contains(&requested) => requested,
+            requested => {
+                warn!(
+                    "{:?} isn't included in this build, running on the CPU",
+                    requested
+                );
+                ComputeBackend::Cpu
+            }
+        }
+    }
+}
+
+/// Load the local backend matching a downloaded model, on `compute` or the
+/// CPU when that isn't available
+pub fn load_local_backend(
+    engine_type: &EngineType,
+    model_path: &Path,
+    compute: ComputeBackend,
+) -> Result<Box<dyn TranscriptionBackend>> {
+    let compute = compute.resolve(&ComputeBackend::compiled_gpus(), hardware_profile().gpu);
+    Ok(match engine_type {
+        EngineType::Whisper => Box::new(WhisperBackend::load(model_path, compute)?),
+        // transcribe-rs builds Parakeet's ONNX session on the CPU provider
+        EngineType::Parakeet => Box::new(ParakeetBackend::load(model_path)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_backend_resolve() {
+        let compiled = [ComputeBackend::Metal, ComputeBackend::Vulkan];
+
+        assert_eq!(
+            ComputeBackend::Auto.resolve(&compiled, Some(GpuAcceleration::Metal)),
+            ComputeBackend::Metal
+        );
+        // No Metal GPU found, so Auto moves on to Vulkan
+        assert_eq!(
+            ComputeBackend::Auto.resolve(&compiled, None),
+            ComputeBackend::Vulkan
+        );
+        assert_eq!(ComputeBackend::Auto.resolve(&[], None), ComputeBackend::Cpu);
+        assert_eq!(
+            ComputeBackend::Cpu.resolve(&compiled, Some(GpuAcceleration::Metal)),
+            ComputeBackend::Cpu
+        );
+        // Asking for a backend the build lacks runs on the CPU
+        assert_eq!(
+            ComputeBackend::Cuda.resolve(&compiled, Some(GpuAcceleration::Cuda)),
+            ComputeBackend::Cpu
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use super::{
+    BackendCapabilities, ComputeBackend, Granularity, InferenceRequest, TimedSegment, Transcript,
+    TranscriptionBackend,
+};
+use anyhow::{anyhow, Result};
+use log::debug;
+use std::path::Path;
+use transcribe_rs::{
+    engines::parakeet::{
+        ParakeetEngine, ParakeetInferenceParams, ParakeetModelParams, TimestampGranularity,
+    },
+    TranscriptionEngine, TranscriptionResult,
+};
+
+/// NVIDIA Parakeet TDT models (int8 ONNX), run locally
+pub struct ParakeetBackend {
+    engine: ParakeetEngine,
+}
+
+impl ParakeetBackend {
+    pub fn load(model_path: &Path) -> Result<Self> {
+        let mut engine = ParakeetEngine::new();
+        engine
+            .load_model_with_params(model_path, ParakeetModelParams::int8())
+            .map_err(|e| anyhow!("Failed to load parakeet model: {}", e))?;
+        Ok(Self { engine })
+    }
+}
+
+impl TranscriptionBackend for ParakeetBackend {
+    fn name(&self) -> &'static str {
+        "parakeet"
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            streaming: false,
+            segment_timestamps: true,
+            word_timestamps: true,
+            translation: false,
+            language_selection: false,
+            language_detection: true,
+            local: true,
+        }
+    }
+
+    fn transcribe(&mut self, audio: Vec<f32>, request: &InferenceRequest) -> Result<Transcript> {
+        debug!(
+            "Parakeet transcription (language hint {:?} is ignored)",
+            request.language
+        );
+
+        let params = ParakeetInferenceParams {
+            timestamp_granularity: match request.granularity {
+                Granularity::Segment => TimestampGranularity::Segment,
+                Granularity::Word => TimestampGranularity::Word,
+            },
+            ..Default::default()
+        };
+
+        let result = self
+            .engine
+            .transcribe_samples(audio, Some(params))
+            .map_err(|e| anyhow!("Parakeet transcription failed: {}", e))?;
+        Ok(to_transcript(result))
+    }
+
+    fn unload(&mut self) {
+        self.engine.unload_model();
+    }
+
+    fn compute_backend(&self) -> Option<ComputeBackend> {
+        Some(ComputeBackend::Cpu)
+    }
+}
+
+/// Convert transcribe-rs output
+fn to_transcript(result: TranscriptionResult) -> Transcript {
+    Transcript {
+        text: result.text,
+        segments: result
+            .segments
+            .unwrap_or_default()
+            .into_iter()
+            .map(|segment| TimedSegment {
+                start: segment.start,
+                end: segment.end,
+                text: segment.text,
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,243 @@
+use super::{
+    BackendCapabilities, ComputeBackend, InferenceRequest, TimedSegment, Transcript,
+    TranscriptionBackend,
+};
+use anyhow::{anyhow, Result};
+use log::warn;
+use std::ffi::{c_int, c_void};
+use std::path::Path;
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+    WhisperSysContext, WhisperSysState, WhisperToken, WhisperTokenData,
+};
+
+/// Longest keyword, in tokens, that is boosted
+const MAX_KEYWORD_TOKENS: usize = 32;
+
+/// whisper.cpp models, run locally. Talks to whisper-rs directly rather than
+/// through transcribe-rs, which has no way to choose the compute device.
+pub struct WhisperBackend {
+    // Both `None` once unloaded
+    context: Option<WhisperContext>,
+    state: Option<WhisperState>,
+    compute: ComputeBackend,
+}
+
+impl WhisperBackend {
+    /// Load the model on `compute`, which must already be resolved from `Auto`
+    pub fn load(model_path: &Path, compute: ComputeBackend) -> Result<Self> {
+        let path = model_path
+            .to_str()
+            .ok_or_else(|| anyhow!("Model path is not valid UTF-8: {:?}", model_path))?;
+        let mut params = WhisperContextParameters::default();
+        params.use_gpu(compute != ComputeBackend::Cpu);
+
+        let context = WhisperContext::new_with_params(path, params)
+            .map_err(|e| anyhow!("Failed to load whisper model: {}", e))?;
+        let state = context
+            .create_state()
+            .map_err(|e| anyhow!("Failed to create whisper state: {}", e))?;
+        Ok(Self {
+            context: Some(context),
+            state: Some(state),
+            compute,
+        })
+    }
+}
+
+/// Keywords as token sequences, and the bias their tokens get while decoding
+struct KeywordBias {
+    keywords: Vec<Vec<WhisperToken>>,
+    bias: f32,
+    n_vocab: usize,
+}
+
+impl KeywordBias {
+    fn new(context: &WhisperContext, keywords: &[String], bias: f32) -> Option<Self> {
+        if bias <= 0.0 {
+            return None;
+        }
+        let keywords: Vec<_> = keywords
+            .iter()
+            .map(|keyword| keyword.trim())
+            .filter(|keyword| !keyword.is_empty())
+            // Mid-sentence form; whisper's words start with a space
+            .filter_map(|keyword| {
+                match context.tokenize(&format!(" {}", keyword), MAX_KEYWORD_TOKENS) {
+                    Ok(tokens) => Some(tokens),
+                    Err(e) => {
+                        warn!("Not boosting keyword '{}': {}", keyword, e);
+                        None
+                    }
+                }
+            })
+            .filter(|tokens| !tokens.is_empty())
+            .collect();
+        (!keywords.is_empty()).then(|| Self {
+            keywords,
+            bias,
+            n_vocab: context.n_vocab() as usize,
+        })
+    }
+
+    /// Raise the logits of the token that starts each keyword, or that
+    /// continues one the decoded tokens have begun
+    fn apply(&self, decoded: &[WhisperToken], logits: &mut [f32]) {
+        for keyword in &self.keywords {
+            let matched = (1..keyword.len())
+                .rev()
+                .find(|&n| decoded.ends_with(&keyword[..n]))
+                .unwrap_or(0);
+            if let Some(logit) = logits.get_mut(keyword[matched] as usize) {
+                *logit += self.bias;
+            }
+        }
+    }
+}
+
+/// whisper.cpp logits filter, called for every token each decoder samples.
+/// `user_data` is the `KeywordBias` for the call.
+unsafe extern "C" fn bias_keywords(
+    _ctx: *mut WhisperSysContext,
+    _state: *mut WhisperSysState,
+    tokens: *const WhisperTokenData,
+    n_tokens: c_int,
+    logits: *mut f32,
+    user_data: *mut c_void,
+) {
+    if logits.is_null() || user_data.is_null() {
+        return;
+    }
+    let bias = &*(user_data as *const KeywordBias);
+    let decoded: Vec<WhisperToken> = if tokens.is_null() || n_tokens <= 0 {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(tokens, n_tokens as usize)
+            .iter()
+            .map(|token| token.id)
+            .collect()
+    };
+    let logits = std::slice::from_raw_parts_mut(logits, bias.n_vocab);
+    bias.apply(&decoded, logits);
+}
+
+impl TranscriptionBackend for WhisperBackend {
+    fn name(&self) -> &'static str {
+        "whisper"
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            streaming: false,
+            segment_timestamps: true,
+            word_timestamps: false,
+            translation: true,
+            language_selection: true,
+            language_detection: true,
+            local: true,
+        }
+    }
+
+    fn transcribe(&mut self, audio: Vec<f32>, request: &InferenceRequest) -> Result<Transcript> {
+        let (Some(context), Some(state)) = (&self.context, self.state.as_mut()) else {
+            return Err(anyhow!("Whisper model is unloaded"));
+        };
+        // Outlives `state.full`, the only place the filter reads it
+        let keyword_bias = KeywordBias::new(context, &request.keywords, request.keyword_bias);
+
+        let strategy = match request.beam_size {
+            Some(beam_size) => SamplingStrategy::BeamSearch {
+                beam_size: beam_size as i32,
+                patience: -1.0,
+            },
+            None => SamplingStrategy::Greedy { best_of: 1 },
+        };
+        let mut params = FullParams::new(strategy);
+        params.set_language(request.language.as_deref());
+        params.set_translate(request.translate);
+        params.set_temperature(request.temperature);
+        params.set_no_speech_thold(request.no_speech_threshold);
+        if let Some(prompt) = &request.initial_prompt {
+            params.set_initial_prompt(prompt);
+        }
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        if let Some(keyword_bias) = &keyword_bias {
+            // SAFETY: the filter only reads `keyword_bias`, which lives until
+            // the end of this call
+            unsafe {
+                params.set_filter_logits_callback(Some(bias_keywords));
+                params.set_filter_logits_callback_user_data(
+                    keyword_bias as *const KeywordBias as *mut c_void,
+                );
+            }
+        }
+
+        state
+            .full(params, &audio)
+            .map_err(|e| anyhow!("Whisper transcription failed: {}", e))?;
+
+        let count = state
+            .full_n_segments()
+            .map_err(|e| anyhow!("Failed to read whisper segments: {}", e))?;
+        let mut segments = Vec::with_capacity(count.max(0) as usize);
+        for i in 0..count {
+            let read = |e| anyhow!("Failed to read whisper segment {}: {}", i, e);
+            // whisper.cpp reports times in centiseconds
+            segments.push(TimedSegment {
+                start: state.full_get_segment_t0(i).map_err(read)? as f32 / 100.0,
+                end: state.full_get_segment_t1(i).map_err(read)? as f32 / 100.0,
+                text: state.full_get_segment_text(i).map_err(read)?,
+            });
+        }
+
+        Ok(Transcript {
+            text: segments
+                .iter()
+                .map(|segment| segment.text.as_str())
+                .collect::<String>()
+                .trim()
+                .to_string(),
+            segments,
+        })
+    }
+
+    fn unload(&mut self) {
+        self.state = None;
+        self.context = None;
+    }
+
+    fn compute_backend(&self) -> Option<ComputeBackend> {
+        Some(self.compute)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_bias_follows_partial_keywords() {
+        let bias = KeywordBias {
+            keywords: vec![vec![5, 6, 7], vec![9]],
+            bias: 2.0,
+            n_vocab: 10,
+        };
+        let boosted = |decoded: &[WhisperToken]| {
+            let mut logits = [0.0; 10];
+            bias.apply(decoded, &mut logits);
+            (0..10).filter(|&t| logits[t] > 0.0).collect::<Vec<_>>()
+        };
+
+        // Keywords can always start
+        assert_eq!(boosted(&[]), [5, 9]);
+        assert_eq!(boosted(&[1, 2]), [5, 9]);
+        // Once one has begun, its next token is favoured instead
+        assert_eq!(boosted(&[1, 5]), [6, 9]);
+        assert_eq!(boosted(&[5, 6]), [7, 9]);
+        // Finished keywords don't push for a repeat of their tail
+        assert_eq!(boosted(&[5, 6, 7]), [5, 9]);
+    }
+}
@@ -0,0 +1,123 @@
+/// Most words two hypotheses are compared over at a window boundary
+const MAX_MATCH_WORDS: usize = 12;
+
+/// Lowercase and strip punctuation so "Hello," and "hello" agree
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Length of the longest run of words that ends `previous` and starts `next`
+fn overlap_len<S: AsRef<str>>(previous: &[S], next: &[S]) -> usize {
+    let max = previous.len().min(next.len()).min(MAX_MATCH_WORDS);
+    (1..=max)
+        .rev()
+        .find(|&k| {
+            previous[previous.len() - k..]
+                .iter()
+                .zip(&next[..k])
+                .all(|(a, b)| {
+                    let a = normalize(a.as_ref());
+                    !a.is_empty() && a == normalize(b.as_ref())
+                })
+        })
+        .unwrap_or(0)
+}
+
+/// LocalAgreement-2 merging of overlapping caption windows.
+///
+/// The words a window heard in the audio it shares with the next one are held
+/// back. They're emitted once the next window transcribes the same words at
+/// its start; if it doesn't, the newer hypothesis wins since it heard that
+/// audio with more context after it.
+#[derive(Default)]
+pub struct LocalAgreement {
+    // Emitted words, kept only as far back as a boundary match can reach
+    committed: Vec<String>,
+    held: Vec<String>,
+}
+
+impl LocalAgreement {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept the words of one window, the last `hold` of which were heard in
+    /// the overlap with the next window. Returns the words to emit now.
+    pub fn push(&mut self, words: Vec<String>, hold: usize) -> Vec<String> {
+        let mut candidate = self.committed.clone();
+        candidate.extend(self.held.iter().cloned());
+
+        // Words at the start of this window that the previous one already
+        // heard. Nothing held means there was no overlap to re-hear.
+        let matched = if self.held.is_empty() {
+            0
+        } else {
+            overlap_len(&candidate, &words)
+        };
+        let confirmed_held = matched.min(self.held.len());
+        let skip_committed = matched - confirmed_held;
+        // Only the held words this window agreed with are emitted; an
+        // unconfirmed tail is replaced by this window's version of it
+        let mut emit: Vec<String> = self.held.drain(..).take(confirmed_held).collect();
+        let fresh = &words[(skip_committed + confirmed_held).min(words.len())..];
+        let keep = fresh.len().saturating_sub(hold);
+        emit.extend(fresh[..keep].iter().cloned());
+        self.held = fresh[keep..].to_vec();
+
+        self.committed.extend(emit.iter().cloned());
+        let excess = self.committed.len().saturating_sub(MAX_MATCH_WORDS);
+        self.committed.drain(..excess);
+        emit
+    }
+
+    /// Release the held words, e.g. when the stream ends
+    pub fn flush(&mut self) -> Vec<String> {
+        let held = std::mem::take(&mut self.held);
+        self.committed.extend(held.iter().cloned());
+        let excess = self.committed.len().saturating_sub(MAX_MATCH_WORDS);
+        self.committed.drain(..excess);
+        held
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_overlap_is_emitted_once() {
+        let mut agreement = LocalAgreement::new();
+
+        let first = agreement.push(words("the quick brown fox"), 1);
+        assert_eq!(first, words("the quick brown"));
+
+        // The next window re-hears "fox" in the overlap
+        let second = agreement.push(words("Fox, jumps over the"), 1);
+        assert_eq!(second, words("fox jumps over"));
+        assert_eq!(agreement.flush(), words("the"));
+    }
+
+    #[test]
+    fn test_unconfirmed_tail_is_replaced_by_newer_hypothesis() {
+        let mut agreement = LocalAgreement::new();
+
+        agreement.push(words("see you at the bus"), 1);
+        // "bus" was the start of "business" cut at the window edge
+        let second = agreement.push(words("business meeting"), 0);
+        assert_eq!(second, words("business meeting"));
+    }
+
+    #[test]
+    fn test_overlap_len_compares_normalized_words() {
+        assert_eq!(overlap_len(&words("we met Anna."), &words("anna said")), 1);
+        assert_eq!(overlap_len(&words("a b c"), &words("b c d")), 2);
+        assert_eq!(overlap_len(&words("a b"), &words("c d")), 0);
+    }
+}
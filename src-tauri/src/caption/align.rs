@@ -0,0 +1,137 @@
+use crate::managers::transcription::TimedSegment;
+use serde::Serialize;
+
+/// A single word placed on the caption stream's timeline (seconds since the
+/// caption loop started)
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TimedWord {
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl TimedWord {
+    fn midpoint(&self) -> f64 {
+        (self.start + self.end) / 2.0
+    }
+}
+
+/// Convert window-relative segments to absolute words. Segments holding more
+/// than one word (Whisper) have their span shared out by character count.
+pub fn segments_to_words(segments: &[TimedSegment], window_start: f64) -> Vec<TimedWord> {
+    let mut words = Vec::new();
+
+    for segment in segments {
+        let parts: Vec<&str> = segment.text.split_whitespace().collect();
+        if parts.is_empty() {
+            continue;
+        }
+
+        let seg_start = window_start + segment.start as f64;
+        let seg_end = (window_start + segment.end as f64).max(seg_start);
+        let total_chars: usize = parts.iter().map(|p| p.chars().count()).sum();
+        let span = seg_end - seg_start;
+
+        let mut cursor = seg_start;
+        for part in parts {
+            let share = part.chars().count() as f64 / total_chars.max(1) as f64;
+            let end = cursor + span * share;
+            words.push(TimedWord {
+                text: part.to_string(),
+                start: cursor,
+                end,
+            });
+            cursor = end;
+        }
+    }
+
+    words
+}
+
+pub fn join_words(words: &[TimedWord]) -> String {
+    words
+        .iter()
+        .map(|w| w.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Stitches overlapping caption windows together using word timestamps.
+///
+/// Each window is transcribed in full, including the overlap it shares with the
+/// previous one. Words whose midpoint falls before the end of the last emitted
+/// word were already emitted and are dropped.
+#[derive(Default)]
+pub struct CaptionAligner {
+    committed_until: f64,
+    transcript: Vec<TimedWord>,
+}
+
+impl CaptionAligner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept the words of one window and return those not emitted before
+    pub fn push(&mut self, words: Vec<TimedWord>) -> Vec<TimedWord> {
+        let fresh: Vec<TimedWord> = words
+            .into_iter()
+            .filter(|w| w.midpoint() >= self.committed_until)
+            .collect();
+
+        if let Some(last) = fresh.last() {
+            self.committed_until = self.committed_until.max(last.end);
+        }
+        self.transcript.extend(fresh.iter().cloned());
+        fresh
+    }
+
+    /// Every word emitted so far, in stream order
+    pub fn transcript(&self) -> &[TimedWord] {
+        &self.transcript
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, start: f64, end: f64) -> TimedWord {
+        TimedWord {
+            text: text.to_string(),
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn test_drops_words_repeated_in_overlap() {
+        let mut aligner = CaptionAligner::new();
+
+        let first = aligner.push(vec![
+            word("the", 0.0, 0.3),
+            word("quick", 0.3, 0.8),
+            word("brown", 1.6, 2.0),
+        ]);
+        assert_eq!(join_words(&first), "the quick brown");
+
+        // Second window starts at 1.5s and re-hears "brown"
+        let second = aligner.push(vec![word("brown", 1.55, 1.95), word("fox", 2.1, 2.5)]);
+        assert_eq!(join_words(&second), "fox");
+        assert_eq!(join_words(aligner.transcript()), "the quick brown fox");
+    }
+
+    #[test]
+    fn test_segments_are_split_by_length() {
+        let segments = vec![TimedSegment {
+            start: 0.0,
+            end: 1.0,
+            text: "ab abcd".to_string(),
+        }];
+
+        let words = segments_to_words(&segments, 10.0);
+        assert_eq!(words.len(), 2);
+        assert!((words[0].end - (10.0 + 2.0 / 6.0)).abs() < 1e-9);
+        assert!((words[1].end - 11.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,159 @@
+use super::align::TimedWord;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+// Speaking rate is reported per bucket of this many seconds
+const BUCKET_SECS: f64 = 30.0;
+const FILLERS: &[&str] = &[
+    "um",
+    "umm",
+    "uh",
+    "uhm",
+    "er",
+    "erm",
+    "ah",
+    "hmm",
+    "like",
+    "basically",
+    "literally",
+];
+const FILLER_PHRASES: &[(&str, &str)] = &[("you", "know"), ("i", "mean"), ("sort", "of")];
+
+/// Speaking rate over one bucket of the session timeline
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RatePoint {
+    /// Seconds since the session started
+    pub start: f64,
+    pub words_per_minute: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SessionAnalytics {
+    pub total_words: usize,
+    /// Seconds from the first word to the last
+    pub duration_secs: f64,
+    pub average_wpm: f64,
+    pub rate: Vec<RatePoint>,
+    pub filler_counts: BTreeMap<String, usize>,
+    pub total_fillers: usize,
+}
+
+/// Running word statistics for a caption session, fed as captions are published
+#[derive(Clone, Debug, Default)]
+pub struct SpeechStats {
+    words: usize,
+    first_start: Option<f64>,
+    last_end: f64,
+    buckets: Vec<usize>,
+    fillers: BTreeMap<String, usize>,
+    previous: Option<String>,
+}
+
+impl SpeechStats {
+    pub fn push_words(&mut self, words: &[TimedWord]) {
+        for word in words {
+            let normalized: String = word
+                .text
+                .chars()
+                .filter(|c| c.is_alphanumeric() || *c == '\'')
+                .collect::<String>()
+                .to_lowercase();
+            if normalized.is_empty() {
+                continue;
+            }
+
+            self.words += 1;
+            self.first_start.get_or_insert(word.start);
+            self.last_end = self.last_end.max(word.end);
+            let bucket = ((word.start + word.end) / 2.0 / BUCKET_SECS).max(0.0) as usize;
+            if self.buckets.len() <= bucket {
+                self.buckets.resize(bucket + 1, 0);
+            }
+            self.buckets[bucket] += 1;
+
+            let phrase = self.previous.as_deref().and_then(|previous| {
+                FILLER_PHRASES
+                    .iter()
+                    .find(|(first, second)| *first == previous && *second == normalized)
+            });
+            if let Some((first, second)) = phrase {
+                *self
+                    .fillers
+                    .entry(format!("{} {}", first, second))
+                    .or_default() += 1;
+            } else if FILLERS.contains(&normalized.as_str()) {
+                *self.fillers.entry(normalized.clone()).or_default() += 1;
+            }
+            self.previous = Some(normalized);
+        }
+    }
+
+    pub fn analytics(&self) -> SessionAnalytics {
+        let Some(first_start) = self.first_start else {
+            return SessionAnalytics::default();
+        };
+        let duration_secs = (self.last_end - first_start).max(0.0);
+
+        let rate = self
+            .buckets
+            .iter()
+            .enumerate()
+            .skip_while(|(_, count)| **count == 0)
+            .map(|(i, count)| {
+                let start = i as f64 * BUCKET_SECS;
+                // Only the part of a bucket the session has reached counts
+                let covered = (self.last_end.min(start + BUCKET_SECS) - start.max(first_start))
+                    .clamp(1.0, BUCKET_SECS);
+                RatePoint {
+                    start,
+                    words_per_minute: *count as f64 * 60.0 / covered,
+                }
+            })
+            .collect();
+
+        SessionAnalytics {
+            total_words: self.words,
+            duration_secs,
+            average_wpm: if duration_secs > 0.0 {
+                self.words as f64 * 60.0 / duration_secs
+            } else {
+                0.0
+            },
+            rate,
+            total_fillers: self.fillers.values().sum(),
+            filler_counts: self.fillers.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_and_fillers() {
+        let mut stats = SpeechStats::default();
+        // Two words a second for the first minute
+        let text = "so um you know the plan is like this ".repeat(12);
+        let words: Vec<TimedWord> = text
+            .split_whitespace()
+            .enumerate()
+            .map(|(i, w)| TimedWord {
+                text: w.to_string(),
+                start: i as f64 * 0.5,
+                end: i as f64 * 0.5 + 0.4,
+            })
+            .collect();
+        stats.push_words(&words);
+
+        let analytics = stats.analytics();
+        assert_eq!(analytics.total_words, 108);
+        assert_eq!(analytics.rate.len(), 2);
+        assert!((analytics.rate[0].words_per_minute - 120.0).abs() < 1e-9);
+        assert!((analytics.average_wpm - 120.0).abs() < 2.0);
+        assert_eq!(analytics.filler_counts["um"], 12);
+        assert_eq!(analytics.filler_counts["you know"], 12);
+        assert_eq!(analytics.filler_counts["like"], 12);
+        assert_eq!(analytics.total_fillers, 36);
+    }
+}
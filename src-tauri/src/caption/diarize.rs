@@ -0,0 +1,291 @@
+// Speaker labels for multi-speaker system audio
+// Each caption segment is reduced to a voiceprint (the mean and spread of its
+// MFCCs over voiced frames) and matched against the speakers heard so far in
+// the session. A new voiceprint that matches nobody becomes the next speaker.
+//
+// This is heuristic clustering, not a trained speaker-embedding model. MFCC
+// statistics mostly capture pitch range and timbre, so similar-sounding voices
+// get merged, one voice can split when the mic, codec or tone changes, and
+// overlapping speech goes to whoever is loudest. Speakers are numbered greedily
+// as they're first heard, so an early mistake sticks for the whole session.
+
+use rustfft::{num_complex::Complex32, Fft, FftPlanner};
+use std::sync::Arc;
+
+// 32ms frames with 50% overlap at 16kHz
+const FRAME_SIZE: usize = 512;
+const HOP_SIZE: usize = 256;
+const MEL_BANDS: usize = 24;
+const MEL_MIN_HZ: f32 = 80.0;
+const MEL_MAX_HZ: f32 = 7600.0;
+// Cepstral coefficients kept, skipping c0 (overall loudness)
+const CEPSTRA: usize = 12;
+const LIFTER: f32 = 22.0;
+// Frames quieter than this fraction of the loudest frame are pauses, not voice
+const VOICED_ENERGY_RATIO: f32 = 0.01;
+// A segment needs ~0.5s of voice to say anything about who is talking
+const MIN_VOICED_FRAMES: usize = 30;
+// Cosine similarity above which a voiceprint is attributed to a known speaker.
+// Tuned by hand on synthetic voices, not measured on real meetings.
+const SAME_SPEAKER_SIMILARITY: f32 = 0.85;
+// Further voices are folded into the closest known speaker
+const MAX_SPEAKERS: usize = 8;
+
+/// Reduces a segment of 16kHz audio to a fixed-size, unit-length voiceprint
+/// from its MFCC statistics
+pub struct MfccVoiceprinter {
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    // Triangular mel filters as (first FFT bin, weights)
+    filters: Vec<(usize, Vec<f32>)>,
+    fft_buffer: Vec<Complex32>,
+}
+
+impl MfccVoiceprinter {
+    pub fn new(sample_rate: usize) -> Self {
+        let fft = FftPlanner::<f32>::new().plan_fft_forward(FRAME_SIZE);
+        let window = (0..FRAME_SIZE)
+            .map(|i| {
+                0.5 * (1.0 - (2.0 * std::f32::consts::PI * i as f32 / FRAME_SIZE as f32).cos())
+            })
+            .collect();
+
+        Self {
+            fft,
+            window,
+            filters: mel_filters(sample_rate),
+            fft_buffer: vec![Complex32::new(0.0, 0.0); FRAME_SIZE],
+        }
+    }
+
+    /// Voiceprint of `samples`, or `None` if there isn't enough voice in it
+    pub fn voiceprint(&mut self, samples: &[f32]) -> Option<Vec<f32>> {
+        if samples.len() < FRAME_SIZE {
+            return None;
+        }
+
+        let frames: Vec<(f32, Vec<f32>)> = samples
+            .windows(FRAME_SIZE)
+            .step_by(HOP_SIZE)
+            .map(|frame| self.frame_cepstra(frame))
+            .collect();
+        let loudest = frames.iter().fold(0.0f32, |m, (e, _)| m.max(*e));
+        let voiced: Vec<&Vec<f32>> = frames
+            .iter()
+            .filter(|(energy, _)| *energy > 0.0 && *energy >= loudest * VOICED_ENERGY_RATIO)
+            .map(|(_, cepstra)| cepstra)
+            .collect();
+        if voiced.len() < MIN_VOICED_FRAMES {
+            return None;
+        }
+
+        let count = voiced.len() as f32;
+        let mut mean = vec![0.0f32; CEPSTRA];
+        for cepstra in &voiced {
+            for (m, c) in mean.iter_mut().zip(cepstra.iter()) {
+                *m += c / count;
+            }
+        }
+        let mut spread = vec![0.0f32; CEPSTRA];
+        for cepstra in &voiced {
+            for ((s, c), m) in spread.iter_mut().zip(cepstra.iter()).zip(mean.iter()) {
+                *s += (c - m) * (c - m) / count;
+            }
+        }
+
+        let mut voiceprint: Vec<f32> = mean
+            .into_iter()
+            .chain(spread.into_iter().map(f32::sqrt))
+            .collect();
+        let norm = voiceprint.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm == 0.0 {
+            return None;
+        }
+        voiceprint.iter_mut().for_each(|x| *x /= norm);
+        Some(voiceprint)
+    }
+
+    /// Energy and MFCCs (c1..=CEPSTRA) of one frame
+    fn frame_cepstra(&mut self, frame: &[f32]) -> (f32, Vec<f32>) {
+        for ((slot, &sample), &w) in self.fft_buffer.iter_mut().zip(frame).zip(&self.window) {
+            *slot = Complex32::new(sample * w, 0.0);
+        }
+        self.fft.process(&mut self.fft_buffer);
+
+        let power: Vec<f32> = self.fft_buffer[..FRAME_SIZE / 2 + 1]
+            .iter()
+            .map(|c| c.norm_sqr())
+            .collect();
+        let energy = power.iter().sum();
+
+        let log_mel: Vec<f32> = self
+            .filters
+            .iter()
+            .map(|(first, weights)| {
+                let band: f32 = weights
+                    .iter()
+                    .zip(&power[*first..])
+                    .map(|(w, p)| w * p)
+                    .sum();
+                (band + 1e-10).ln()
+            })
+            .collect();
+
+        // DCT-II of the log mel energies
+        let cepstra = (1..=CEPSTRA)
+            .map(|k| {
+                log_mel
+                    .iter()
+                    .enumerate()
+                    .map(|(n, e)| {
+                        e * (std::f32::consts::PI * k as f32 * (n as f32 + 0.5) / MEL_BANDS as f32)
+                            .cos()
+                    })
+                    .sum::<f32>()
+                    * lifter(k)
+            })
+            .collect();
+        (energy, cepstra)
+    }
+}
+
+/// HTK-style sinusoidal lifter, so the low coefficients (mostly spectral
+/// tilt) don't drown out the finer shape that tells voices apart
+fn lifter(k: usize) -> f32 {
+    1.0 + LIFTER / 2.0 * (std::f32::consts::PI * k as f32 / LIFTER).sin()
+}
+
+fn hz_to_mel(hz: f32) -> f32 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+fn mel_filters(sample_rate: usize) -> Vec<(usize, Vec<f32>)> {
+    let max_hz = MEL_MAX_HZ.min(sample_rate as f32 / 2.0);
+    let (low, high) = (hz_to_mel(MEL_MIN_HZ), hz_to_mel(max_hz));
+    let bin_of = |mel: f32| mel_to_hz(mel) * FRAME_SIZE as f32 / sample_rate as f32;
+    let edges: Vec<f32> = (0..MEL_BANDS + 2)
+        .map(|i| bin_of(low + (high - low) * i as f32 / (MEL_BANDS + 1) as f32))
+        .collect();
+
+    edges
+        .windows(3)
+        .map(|edge| {
+            let (left, centre, right) = (edge[0], edge[1], edge[2]);
+            let first = left.ceil() as usize;
+            let last = (right.floor() as usize).min(FRAME_SIZE / 2);
+            let weights = (first..=last)
+                .map(|bin| {
+                    let bin = bin as f32;
+                    if bin <= centre {
+                        (bin - left) / (centre - left).max(f32::EPSILON)
+                    } else {
+                        (right - bin) / (right - centre).max(f32::EPSILON)
+                    }
+                })
+                .collect();
+            (first, weights)
+        })
+        .collect()
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    dot / (norm(a) * norm(b)).max(f32::EPSILON)
+}
+
+struct Speaker {
+    centroid: Vec<f32>,
+    segments: usize,
+}
+
+/// Greedy online clustering of voiceprints into numbered speakers for one
+/// session, by cosine similarity to each speaker's running mean
+#[derive(Default)]
+pub struct SpeakerTracker {
+    speakers: Vec<Speaker>,
+}
+
+impl SpeakerTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 1-based number of the speaker `voiceprint` belongs to, registering a
+    /// new speaker if it matches none closely enough
+    pub fn assign(&mut self, voiceprint: &[f32]) -> usize {
+        let closest = self
+            .speakers
+            .iter()
+            .enumerate()
+            .map(|(i, speaker)| (i, cosine(&speaker.centroid, voiceprint)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        let index = match closest {
+            Some((i, similarity))
+                if similarity >= SAME_SPEAKER_SIMILARITY || self.speakers.len() >= MAX_SPEAKERS =>
+            {
+                i
+            }
+            _ => {
+                self.speakers.push(Speaker {
+                    centroid: vec![0.0; voiceprint.len()],
+                    segments: 0,
+                });
+                self.speakers.len() - 1
+            }
+        };
+
+        // Running mean, so a speaker's voiceprint settles as they keep talking
+        let speaker = &mut self.speakers[index];
+        speaker.segments += 1;
+        let weight = 1.0 / speaker.segments as f32;
+        for (c, v) in speaker.centroid.iter_mut().zip(voiceprint) {
+            *c += (v - *c) * weight;
+        }
+        index + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Harmonic "voice": pitch sets the spacing, rolloff the timbre
+    fn voice(pitch: f32, rolloff: f32, seconds: f32, phase: f32) -> Vec<f32> {
+        let rate = 16000.0;
+        (0..(seconds * rate) as usize)
+            .map(|i| {
+                let t = i as f32 / rate;
+                (1..30)
+                    .map(|h| {
+                        let freq = pitch * h as f32;
+                        let amplitude = rolloff.powi(h);
+                        amplitude * (2.0 * std::f32::consts::PI * freq * t + phase * h as f32).sin()
+                    })
+                    .sum::<f32>()
+                    * 0.1
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tracker_separates_voices() {
+        let mut voiceprinter = MfccVoiceprinter::new(16000);
+        let mut tracker = SpeakerTracker::new();
+        let low = |phase| voice(110.0, 0.9, 2.0, phase);
+        let high = |phase| voice(230.0, 0.55, 1.5, phase);
+
+        let labels: Vec<usize> = [low(0.0), high(0.3), low(1.1), high(2.0)]
+            .iter()
+            .map(|segment| tracker.assign(&voiceprinter.voiceprint(segment).unwrap()))
+            .collect();
+        assert_eq!(labels, vec![1, 2, 1, 2]);
+
+        assert!(voiceprinter.voiceprint(&vec![0.0; 16000]).is_none());
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+
+// Length of a saved snippet, centred on the caption that mentioned the phrase
+const SNIPPET_SECS: f64 = 30.0;
+// Audio kept for snippets: the snippet itself plus headroom for captions
+// arriving late because of transcription lag
+const RETAINED_SECS: usize = 60;
+
+/// Audio and transcript around a hotword, ready to be saved to history
+pub struct Snippet {
+    pub phrase: String,
+    pub samples: Vec<f32>,
+    pub transcript: String,
+}
+
+struct PendingSnippet {
+    phrase: String,
+    from: f64,
+    to: f64,
+}
+
+struct Cue {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+/// Watches captions for configured phrases and cuts the surrounding audio and
+/// transcript out of a rolling window once the audio after the phrase is in.
+pub struct HotwordSnippets {
+    sample_rate: usize,
+    audio: VecDeque<f32>,
+    // Position of `audio[0]` on the caption timeline, in samples
+    audio_start: usize,
+    cues: VecDeque<Cue>,
+    pending: Vec<PendingSnippet>,
+}
+
+impl HotwordSnippets {
+    pub fn new(sample_rate: usize) -> Self {
+        Self {
+            sample_rate,
+            audio: VecDeque::new(),
+            audio_start: 0,
+            cues: VecDeque::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Append newly captured audio; must be called for every sample on the timeline
+    pub fn push_audio(&mut self, samples: impl IntoIterator<Item = f32>) {
+        self.audio.extend(samples);
+        let limit = RETAINED_SECS * self.sample_rate;
+        if self.audio.len() > limit {
+            let excess = self.audio.len() - limit;
+            self.audio.drain(..excess);
+            self.audio_start += excess;
+        }
+
+        let retained_from = self.seconds(self.audio_start);
+        while self.cues.front().is_some_and(|c| c.end < retained_from) {
+            self.cues.pop_front();
+        }
+    }
+
+    /// Record a published caption, arming a snippet if it contains a phrase
+    pub fn push_caption(&mut self, phrases: &[String], start: f64, end: f64, text: &str) {
+        self.cues.push_back(Cue {
+            start,
+            end,
+            text: text.to_string(),
+        });
+
+        let Some(phrase) = find_phrase(phrases, text) else {
+            return;
+        };
+        // A phrase repeated inside a snippet that is still filling is one moment
+        if self
+            .pending
+            .iter()
+            .any(|p| p.phrase == phrase && start < p.to)
+        {
+            return;
+        }
+
+        let centre = (start + end) / 2.0;
+        self.pending.push(PendingSnippet {
+            phrase: phrase.to_string(),
+            from: (centre - SNIPPET_SECS / 2.0).max(0.0),
+            to: centre + SNIPPET_SECS / 2.0,
+        });
+    }
+
+    /// Snippets whose trailing audio has been captured. With `flush`, every
+    /// pending snippet is returned with whatever audio is available.
+    pub fn take_ready(&mut self, flush: bool) -> Vec<Snippet> {
+        let captured_until = self.seconds(self.audio_start + self.audio.len());
+        let (ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|p| flush || p.to <= captured_until);
+        self.pending = waiting;
+
+        ready.into_iter().map(|p| self.cut(p)).collect()
+    }
+
+    fn cut(&self, snippet: PendingSnippet) -> Snippet {
+        let to_index = |secs: f64| {
+            ((secs * self.sample_rate as f64) as usize)
+                .saturating_sub(self.audio_start)
+                .min(self.audio.len())
+        };
+        let samples = self
+            .audio
+            .range(to_index(snippet.from)..to_index(snippet.to))
+            .copied()
+            .collect();
+        let transcript = self
+            .cues
+            .iter()
+            .filter(|c| c.end > snippet.from && c.start < snippet.to)
+            .map(|c| c.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Snippet {
+            phrase: snippet.phrase,
+            samples,
+            transcript,
+        }
+    }
+
+    fn seconds(&self, samples: usize) -> f64 {
+        samples as f64 / self.sample_rate as f64
+    }
+}
+
+/// First configured phrase contained in `text`, ignoring case
+fn find_phrase<'a>(phrases: &'a [String], text: &str) -> Option<&'a str> {
+    let text = text.to_lowercase();
+    phrases
+        .iter()
+        .map(|p| p.trim())
+        .find(|p| !p.is_empty() && text.contains(&p.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_waits_for_trailing_audio() {
+        let phrases = vec!["Action Item".to_string()];
+        let mut snippets = HotwordSnippets::new(10);
+
+        snippets.push_audio(vec![0.0; 10 * 40]);
+        snippets.push_caption(&phrases, 1.0, 2.0, "hello everyone");
+        snippets.push_caption(&phrases, 39.0, 41.0, "next action item is yours");
+        assert!(snippets.take_ready(false).is_empty());
+
+        snippets.push_audio(vec![0.0; 10 * 20]);
+        let ready = snippets.take_ready(false);
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].phrase, "Action Item");
+        assert_eq!(ready[0].samples.len(), 10 * 30);
+        assert_eq!(ready[0].transcript, "next action item is yours");
+    }
+}
@@ -0,0 +1,1151 @@
+// Continuous live-caption pipeline used in always-on mode
+// Reads audio from the active source in a sliding window (no audio loss), transcribes it,
+// then emits `live-caption-update`, saves to history and pastes the result.
+// With a finalization delay, captions are first emitted as `live-caption-provisional`
+// and only reach history, sinks and paste once the next window confirms them.
+
+mod agreement;
+mod align;
+pub mod analytics;
+mod diarize;
+mod hotword;
+pub mod server;
+pub mod session;
+mod sink;
+mod timeline;
+mod utterance;
+
+pub use sink::append_to_transcript_file;
+
+use agreement::LocalAgreement;
+use align::{join_words, segments_to_words, CaptionAligner, TimedWord};
+use diarize::{MfccVoiceprinter, SpeakerTracker};
+use hotword::HotwordSnippets;
+use server::CaptionServer;
+use sink::{FileSinkConfig, FileTranscriptSink, TranscriptSink};
+use timeline::Timeline;
+use utterance::{Segment, UtteranceSegmenter};
+
+use crate::audio_toolkit::audio::{Denoiser, FrameResampler, PreprocessChain};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::debug_log::{audio_debug_enabled, AUDIO_DEBUG_TARGET};
+use crate::audio_toolkit::ring_buffer::SampleReceiver;
+use crate::audio_toolkit::{apply_casing, filter_profanity, normalize_numbers};
+use crate::audio_toolkit::{SileroVad, TimedSamples};
+use crate::events::{self, CaptionSegment};
+use crate::managers::audio::{resolve_vad_model_path, AudioRecordingManager};
+use crate::managers::history::{HistoryManager, TranscriptionMetadata};
+use crate::managers::power::{PowerManager, PowerThrottle};
+use crate::managers::transcription::{TimedSegment, TranscriptionManager};
+use crate::managers::workers::JobPriority;
+use crate::settings::{get_settings, AppSettings, AudioSource, CaptionStrategy, OutputMode};
+use crate::utils::emit_throttled;
+use log::{debug, error, info, trace, warn};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+const TRANSCRIBE_INTERVAL_SECS: u64 = 3; // Transcribe every 3 seconds for real-time
+const MIN_AUDIO_SECS: usize = 2; // Minimum 2 seconds of audio before transcribing
+const TARGET_SAMPLE_RATE: usize = WHISPER_SAMPLE_RATE as usize;
+const MIN_SAMPLES: usize = MIN_AUDIO_SECS * TARGET_SAMPLE_RATE;
+const MAX_MODEL_WAIT: u32 = 20; // Max 10 seconds (20 * 500ms)
+
+// The VAD strategy polls often so utterances are transcribed soon after they end
+const VAD_POLL_INTERVAL: Duration = Duration::from_millis(250);
+// With delivered samples it wakes as soon as this much audio has arrived
+const VAD_DELIVERY_BATCH: Duration = Duration::from_millis(100);
+const VAD_THRESHOLD: f32 = 0.3;
+// Interval windows are this many times longer while power saving
+const THROTTLED_INTERVAL_FACTOR: u64 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptionSource {
+    SystemAudio,
+    Microphone,
+}
+
+impl CaptionSource {
+    fn audio_source(self) -> AudioSource {
+        match self {
+            CaptionSource::SystemAudio => AudioSource::SystemAudio,
+            CaptionSource::Microphone => AudioSource::Microphone,
+        }
+    }
+
+    fn tag(self) -> &'static str {
+        match self {
+            CaptionSource::SystemAudio => "[Auto-transcription]",
+            CaptionSource::Microphone => "[Mic Auto-transcription]",
+        }
+    }
+
+    /// Name history sessions from this source are titled with
+    fn session_label(self) -> &'static str {
+        match self {
+            CaptionSource::SystemAudio => "System audio captions",
+            CaptionSource::Microphone => "Microphone captions",
+        }
+    }
+
+    /// History tag and caption label used when both sources are captioned
+    fn speaker(self) -> (&'static str, &'static str) {
+        match self {
+            CaptionSource::SystemAudio => ("others", "Others"),
+            CaptionSource::Microphone => ("me", "Me"),
+        }
+    }
+}
+
+/// How long the loop waits for audio between windows. Power saving stretches
+/// interval windows; utterances are still captioned as they end.
+fn poll_interval(strategy: CaptionStrategy, throttle: PowerThrottle) -> Duration {
+    match strategy {
+        CaptionStrategy::Interval if throttle > PowerThrottle::Full => {
+            Duration::from_secs(TRANSCRIBE_INTERVAL_SECS * THROTTLED_INTERVAL_FACTOR)
+        }
+        CaptionStrategy::Interval => Duration::from_secs(TRANSCRIBE_INTERVAL_SECS),
+        CaptionStrategy::Vad => VAD_POLL_INTERVAL,
+    }
+}
+
+/// Rate the caption samples from `source` arrive at
+fn source_sample_rate(rm: &AudioRecordingManager, source: CaptionSource) -> usize {
+    match source {
+        CaptionSource::SystemAudio => rm.system_audio_format().sample_rate as usize,
+        // The recorder already resamples the microphone to 16kHz
+        CaptionSource::Microphone => TARGET_SAMPLE_RATE,
+    }
+}
+
+/// Resampler taking `input_rate` audio to 16kHz, None if it already is
+fn input_resampler(input_rate: usize) -> Option<FrameResampler> {
+    (input_rate != TARGET_SAMPLE_RATE)
+        .then(|| FrameResampler::new(input_rate, TARGET_SAMPLE_RATE, Duration::from_millis(30)))
+}
+
+/// Start the caption loop on its own thread. It exits when auto-captioning is
+/// turned off or the audio source no longer matches `source`.
+pub fn spawn_caption_loop(
+    app_handle: AppHandle,
+    rm: Arc<AudioRecordingManager>,
+    binding_id: String,
+    source: CaptionSource,
+) {
+    std::thread::spawn(move || {
+        CaptionLoop::new(app_handle, rm, binding_id, source).run();
+    });
+}
+
+/// Tracks silence so the user gets one clear hint instead of a log flood
+#[derive(Default)]
+struct SilenceMonitor {
+    previous_rms: Option<f32>,
+    silent_checks: u64,
+}
+
+impl SilenceMonitor {
+    fn observe(&mut self, source: CaptionSource, samples: &[f32]) {
+        let tag = source.tag();
+        let rms = (samples.iter().map(|&s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+        let max_amplitude = samples
+            .iter()
+            .map(|&s| s.abs())
+            .fold(0.0f32, |a, b| a.max(b));
+
+        debug!(
+            "🎙️ {} Processing {} samples ({}s audio) - RMS: {:.6}, Max: {:.6}",
+            tag,
+            samples.len(),
+            samples.len() / TARGET_SAMPLE_RATE,
+            rms,
+            max_amplitude
+        );
+
+        // Detect transition from silence to non-silence
+        let was_silent = self.previous_rms.map(|pr| pr < 0.00001).unwrap_or(true);
+        if was_silent && rms > 0.00001 {
+            info!(
+                "🎉 {} ✅ AUDIO DETECTED! RMS: {:.6}, Max: {:.6}",
+                tag, rms, max_amplitude
+            );
+        }
+
+        if rms < 0.00001 && max_amplitude < 0.01 {
+            self.silent_checks += 1;
+            if self.silent_checks == 1 {
+                warn!(
+                    "⚠️ {} Audio is SILENT (RMS: {:.6}, Max: {:.6})",
+                    tag, rms, max_amplitude
+                );
+                if source == CaptionSource::SystemAudio {
+                    Self::log_silence_hint();
+                }
+            } else if self.silent_checks % 10 == 0 {
+                warn!(
+                    "⚠️ {} Audio still silent (checked {} times)",
+                    tag, self.silent_checks
+                );
+            }
+        } else if self.silent_checks > 0 {
+            info!(
+                "🎉 {} ✅ AUDIO DETECTED after {} silent checks!",
+                tag, self.silent_checks
+            );
+            self.silent_checks = 0;
+        }
+
+        self.previous_rms = Some(rms);
+    }
+
+    fn last_rms(&self) -> f32 {
+        self.previous_rms.unwrap_or(0.0)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn log_silence_hint() {
+        warn!("⚠️ [Config] Audio is SILENT! Please configure Sound Output:");
+        warn!("   1. Open System Settings > Sound");
+        warn!("   2. Set Output to 'BlackHole 2ch' OR use Set Up Routing in the setup dialog");
+        warn!("   3. See HUONG_DAN_CAI_DAT_BLACKHOLE.md for details");
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn log_silence_hint() {
+        warn!("⚠️ [Config] Audio is SILENT! Please play audio from Chrome/Spotify");
+    }
+}
+
+/// A transcribed caption and where it sits on the session timeline (seconds)
+struct Caption {
+    text: String,
+    start: f64,
+    end: f64,
+    translation: Option<String>,
+    // Word timings from alignment; empty when captions aren't aligned
+    words: Vec<TimedWord>,
+}
+
+/// Payload of `live-caption-update`. `text` carries the speaker label too, so
+/// listeners that only show text still say who is talking.
+#[derive(Clone, Serialize)]
+struct LiveCaptionUpdate {
+    text: String,
+    speaker: Option<String>,
+    translation: Option<String>,
+}
+
+/// A caption shown as provisional, waiting for the next window or the delay
+struct PendingCaption {
+    caption: Caption,
+    samples: Vec<f32>,
+    since: Instant,
+}
+
+struct CaptionLoop {
+    app_handle: AppHandle,
+    rm: Arc<AudioRecordingManager>,
+    binding_id: String,
+    source: CaptionSource,
+    // Rate the source captures at, and the resampler taking it to 16kHz
+    input_rate: usize,
+    resampler: Option<FrameResampler>,
+    // Captured audio pushed to the loop, when the source can deliver it
+    delivery: Option<SampleReceiver>,
+    // Accumulation buffer to avoid missing any audio (stores resampled 16kHz samples)
+    buffer: VecDeque<f32>,
+    // Number of 16kHz samples dropped from the front of `buffer` so far
+    consumed: usize,
+    // Session time of every sample, from the capture stamps
+    timeline: Timeline,
+    silence: SilenceMonitor,
+    aligner: CaptionAligner,
+    // De-duplicates the overlap between windows when they aren't aligned
+    agreement: LocalAgreement,
+    // Created on first use of the "vad" strategy
+    segmenter: Option<UtteranceSegmenter>,
+    // Rebuilt whenever the transcript file settings change
+    sink_config: Option<FileSinkConfig>,
+    sinks: Vec<Box<dyn TranscriptSink>>,
+    pending: Option<PendingCaption>,
+    // Only system-audio captions are watched for hotwords
+    hotwords: Option<HotwordSnippets>,
+    // Speakers heard in this session, for heuristic system-audio diarization
+    voiceprinter: Option<MfccVoiceprinter>,
+    speakers: SpeakerTracker,
+    // Last words captioned, passed to the model as context for the next window
+    context: String,
+    // History session this run's captions are saved under
+    history_session: Option<i64>,
+    // Power saving level, read once per pass of the loop
+    throttle: PowerThrottle,
+}
+
+impl CaptionLoop {
+    fn new(
+        app_handle: AppHandle,
+        rm: Arc<AudioRecordingManager>,
+        binding_id: String,
+        source: CaptionSource,
+    ) -> Self {
+        let input_rate = source_sample_rate(&rm, source);
+
+        Self {
+            app_handle,
+            rm,
+            binding_id,
+            source,
+            input_rate,
+            resampler: input_resampler(input_rate),
+            delivery: None,
+            buffer: VecDeque::new(),
+            consumed: 0,
+            timeline: Timeline::new(Instant::now(), TARGET_SAMPLE_RATE),
+            silence: SilenceMonitor::default(),
+            aligner: CaptionAligner::new(),
+            agreement: LocalAgreement::new(),
+            segmenter: None,
+            sink_config: None,
+            sinks: Vec::new(),
+            pending: None,
+            hotwords: (source == CaptionSource::SystemAudio)
+                .then(|| HotwordSnippets::new(TARGET_SAMPLE_RATE)),
+            voiceprinter: (source == CaptionSource::SystemAudio)
+                .then(|| MfccVoiceprinter::new(TARGET_SAMPLE_RATE)),
+            speakers: SpeakerTracker::new(),
+            context: String::new(),
+            history_session: None,
+            throttle: PowerThrottle::Full,
+        }
+    }
+
+    fn run(&mut self) {
+        let tag = self.source.tag();
+        info!(
+            "{} Thread started, interval: {}s (real-time mode, no audio loss)",
+            tag, TRANSCRIBE_INTERVAL_SECS
+        );
+
+        let started = session::begin_session();
+        self.timeline = Timeline::new(started, TARGET_SAMPLE_RATE);
+        let hm = Arc::clone(&self.app_handle.state::<Arc<HistoryManager>>());
+        self.history_session = match hm.start_session(self.source.session_label()) {
+            Ok(id) => Some(id),
+            Err(e) => {
+                error!("{} Failed to start history session: {}", tag, e);
+                None
+            }
+        };
+        let mut strategy = get_settings(&self.app_handle).caption_strategy;
+
+        loop {
+            self.throttle = self
+                .app_handle
+                .try_state::<Arc<PowerManager>>()
+                .map_or(PowerThrottle::Full, |power| power.throttle());
+
+            // Delivered samples pace the loop in pull_samples; polling sleeps
+            if self.delivery.is_none() {
+                self.delivery = self.rm.deliver_caption_samples(self.source);
+            }
+            if self.delivery.is_none() {
+                std::thread::sleep(poll_interval(strategy, self.throttle));
+            }
+
+            let settings = get_settings(&self.app_handle);
+            if settings.caption_strategy != strategy {
+                info!(
+                    "{} Caption strategy changed to {:?}",
+                    tag, settings.caption_strategy
+                );
+                strategy = settings.caption_strategy;
+                self.segmenter = None;
+            }
+            if !settings.auto_caption_enabled() {
+                info!("Auto-captioning disabled, stopping {}", tag);
+                // Release the stream so dictation can use it again
+                self.rm.cancel_recording();
+                break;
+            }
+
+            // Check if audio source still matches (may have changed). In
+            // dual-channel mode the mic is captioned next to system audio.
+            let audio_source = settings.audio_source.unwrap_or(AudioSource::Microphone);
+            let second_channel =
+                self.source == CaptionSource::Microphone && settings.dual_channel_active();
+            if audio_source != self.source.audio_source() && !second_channel {
+                info!(
+                    "Audio source changed to {:?}, stopping {}",
+                    audio_source, tag
+                );
+                break;
+            }
+            // The power manager reopens the always-on microphone, and with it
+            // this loop, once power saving eases off
+            if self.throttle == PowerThrottle::Paused
+                && self.source == CaptionSource::Microphone
+                && !second_channel
+            {
+                info!("Power saving paused always-on capture, stopping {}", tag);
+                self.rm.cancel_recording();
+                break;
+            }
+
+            self.sync_sinks(&settings);
+            self.finalize_expired(&settings);
+
+            // Ensure recording is active (for system audio, this just ensures buffer is ready)
+            if !self.rm.is_recording() && !self.rm.try_start_recording(&self.binding_id) {
+                warn!("Failed to restart recording in always-on mode");
+                break;
+            }
+
+            self.pull_samples(strategy);
+            self.save_snippets(false);
+
+            let window = match strategy {
+                CaptionStrategy::Interval => self.next_interval_window(&settings),
+                CaptionStrategy::Vad => self.next_utterance(&settings),
+            };
+            let (window_start, samples) = match window {
+                Some(window) => window,
+                None => continue,
+            };
+
+            self.silence.observe(self.source, &samples);
+
+            if !self.wait_for_model() {
+                continue;
+            }
+
+            if let Some(caption) = self.transcribe_window(&settings, window_start, &samples) {
+                self.stage(&settings, caption, samples);
+            }
+            // Continue loop - accumulation buffer keeps growing, no audio loss
+        }
+
+        // Nothing will confirm a provisional caption or held words any more
+        let held = self.agreement.flush();
+        let settings = get_settings(&self.app_handle);
+        if let Some(mut pending) = self.pending.take() {
+            if !held.is_empty() {
+                let text = format!("{} {}", pending.caption.text, held.join(" "));
+                pending.caption.text = self.finish_text(&settings, &text);
+            }
+            self.publish(pending.caption, pending.samples);
+        } else if !held.is_empty() {
+            // Held words were heard in the overlap still left in the buffer
+            let samples: Vec<f32> = self.buffer.iter().copied().collect();
+            let caption = Caption {
+                text: self.finish_text(&settings, &held.join(" ")),
+                start: self.timeline.seconds_at(self.consumed),
+                end: self.timeline.seconds_at(self.consumed + samples.len()),
+                translation: None,
+                words: Vec::new(),
+            };
+            self.publish(caption, samples);
+        }
+        self.save_snippets(true);
+
+        if let Some(id) = self.history_session.take() {
+            if let Err(e) = hm.end_session(id) {
+                error!("{} Failed to end history session: {}", tag, e);
+            }
+        }
+    }
+
+    /// Read new samples from the source and append them (at 16kHz) to the buffer
+    fn pull_samples(&mut self, strategy: CaptionStrategy) {
+        let tag = self.source.tag();
+
+        let received = self.delivery.as_ref().map(|rx| {
+            let batch = match strategy {
+                CaptionStrategy::Interval => poll_interval(strategy, self.throttle),
+                CaptionStrategy::Vad => VAD_DELIVERY_BATCH,
+            };
+            let min_samples = self.input_rate * batch.as_millis() as usize / 1000;
+            rx.recv(min_samples, poll_interval(strategy, self.throttle))
+        });
+        let read = match received {
+            Some(Ok(samples)) => Ok(Some(samples)),
+            Some(Err(e)) => {
+                // Capture closed; poll until it can deliver again
+                warn!("{} Sample delivery ended: {}", tag, e);
+                self.delivery = None;
+                return;
+            }
+            None => self.rm.read_caption_samples(self.source),
+        };
+
+        let new_samples = match read {
+            Ok(Some(s)) if !s.is_empty() => s,
+            Ok(_) => {
+                // Buffer is empty - this is normal if no audio is playing
+                static EMPTY_COUNT: AtomicU64 = AtomicU64::new(0);
+                let count = EMPTY_COUNT.fetch_add(1, Ordering::Relaxed);
+                if count % 20 == 0 {
+                    if self.source == CaptionSource::SystemAudio {
+                        debug!(
+                            "🔍 {} Buffer empty (checked {} times) - Please ensure audio is playing",
+                            tag,
+                            count + 1
+                        );
+                    } else {
+                        debug!(
+                            "{} No audio samples available (checked {} times)",
+                            tag,
+                            count + 1
+                        );
+                    }
+                }
+                return;
+            }
+            Err(e) => {
+                error!("❌ {} Failed to read samples: {}", tag, e);
+                return;
+            }
+        };
+
+        let input_count = new_samples.len();
+        let previous_len = self.buffer.len();
+        let mut new_samples = new_samples;
+
+        // The system capture can change rate, e.g. when it follows a new
+        // default output device
+        let input_rate = source_sample_rate(&self.rm, self.source);
+        if input_rate != self.input_rate {
+            info!(
+                "{} Capture rate changed from {}Hz to {}Hz",
+                tag, self.input_rate, input_rate
+            );
+            // Audio read along with the switch may have been captured before
+            // it, and the old resampler still holds the end of that audio.
+            // Until audio at the new rate arrives, all of it is at the old one.
+            let earlier = match self.rm.system_audio_format_since() {
+                Some(since) => new_samples.split_before(since),
+                None => std::mem::take(&mut new_samples),
+            };
+            self.push_input(earlier);
+            if let Some(mut resampler) = self.resampler.take() {
+                let buffer = &mut self.buffer;
+                resampler.flush(|chunk| buffer.extend(chunk.iter().copied()));
+            }
+            self.input_rate = input_rate;
+            self.resampler = input_resampler(input_rate);
+        }
+        self.push_input(new_samples);
+
+        if let Some(hotwords) = self.hotwords.as_mut() {
+            hotwords.push_audio(self.buffer.range(previous_len..).copied());
+        }
+
+        if audio_debug_enabled() {
+            debug!(
+                target: AUDIO_DEBUG_TARGET,
+                "📥 {} Read {} samples, total buffer: {} samples ({}s)",
+                tag,
+                input_count,
+                self.buffer.len(),
+                self.buffer.len() / TARGET_SAMPLE_RATE
+            );
+        }
+    }
+
+    /// Append samples read at `input_rate` to the buffer, resampled to 16kHz
+    fn push_input(&mut self, samples: TimedSamples) {
+        if samples.is_empty() {
+            return;
+        }
+        self.place(&samples);
+        match self.resampler.as_mut() {
+            Some(resampler) => {
+                let buffer = &mut self.buffer;
+                resampler.push(&samples.samples, |chunk| {
+                    buffer.extend(chunk.iter().copied())
+                });
+            }
+            None => self.buffer.extend(samples.samples),
+        }
+    }
+
+    /// Put the stamps of newly read samples on the timeline, noting any audio
+    /// that went missing before them
+    fn place(&mut self, new_samples: &TimedSamples) {
+        let base = self.consumed + self.buffer.len();
+        for &(i, at) in &new_samples.marks {
+            let index = base + i * TARGET_SAMPLE_RATE / self.input_rate;
+            if let Some(gap) = self.timeline.observe(index, at) {
+                warn!(
+                    "{} {:.2}s of audio missing at {:.2}s",
+                    self.source.tag(),
+                    gap.duration,
+                    gap.start
+                );
+                session::record_gap(gap);
+            }
+        }
+    }
+
+    /// "interval" strategy: transcribe whatever has accumulated once there is enough
+    fn next_interval_window(&mut self, settings: &AppSettings) -> Option<(usize, Vec<f32>)> {
+        let current_buffer_size = self.buffer.len();
+        if current_buffer_size < MIN_SAMPLES {
+            return None;
+        }
+
+        debug!(
+            "✅ {} Buffer has {} samples ({}s), ready to transcribe!",
+            self.source.tag(),
+            current_buffer_size,
+            current_buffer_size / TARGET_SAMPLE_RATE
+        );
+
+        let (window_start, samples) = self.take_window(settings);
+        (!samples.is_empty()).then_some((window_start, samples))
+    }
+
+    /// "vad" strategy: transcribe once per utterance, dropping silence in between
+    fn next_utterance(&mut self, settings: &AppSettings) -> Option<(usize, Vec<f32>)> {
+        if self.segmenter.is_none() {
+            match create_segmenter(&self.app_handle) {
+                Ok(segmenter) => self.segmenter = Some(segmenter),
+                Err(e) => {
+                    error!(
+                        "{} Failed to create VAD, using interval strategy: {}",
+                        self.source.tag(),
+                        e
+                    );
+                    return self.next_interval_window(settings);
+                }
+            }
+        }
+        let segmenter = self.segmenter.as_mut()?;
+
+        loop {
+            match segmenter.scan(&self.buffer) {
+                Segment::Wait => return None,
+                Segment::Discard(n) => {
+                    self.buffer.drain(..n);
+                    self.consumed += n;
+                    self.timeline.forget_before(self.consumed);
+                    segmenter.drained(n);
+                }
+                Segment::Utterance(range) => {
+                    let window_start = self.consumed + range.start;
+                    let samples: Vec<f32> = self.buffer.range(range.clone()).copied().collect();
+                    self.buffer.drain(..range.end);
+                    self.consumed += range.end;
+                    self.timeline.forget_before(window_start);
+                    segmenter.drained(range.end);
+
+                    debug!(
+                        "✅ {} Utterance of {:.1}s ready to transcribe",
+                        self.source.tag(),
+                        samples.len() as f32 / TARGET_SAMPLE_RATE as f32
+                    );
+                    return Some((window_start, samples));
+                }
+            }
+        }
+    }
+
+    /// Take the next window to transcribe, returning its start (in 16kHz samples
+    /// since the loop started) and the samples.
+    ///
+    /// The whole buffer is transcribed and the overlap is kept for the next
+    /// window, so words at the boundary are heard twice. With alignment they are
+    /// de-duplicated by word timestamps, otherwise by `LocalAgreement`.
+    fn take_window(&mut self, settings: &AppSettings) -> (usize, Vec<f32>) {
+        let window_start = self.consumed;
+        let overlap = settings.caption_overlap_ms as usize * TARGET_SAMPLE_RATE / 1000;
+        let samples: Vec<f32> = self.buffer.iter().copied().collect();
+        let advance = self.buffer.len().saturating_sub(overlap);
+        self.buffer.drain(..advance);
+        self.consumed += advance;
+        self.timeline.forget_before(window_start);
+        (window_start, samples)
+    }
+
+    /// Ensure the model is loaded before transcribing (with timeout)
+    fn wait_for_model(&self) -> bool {
+        let tm = self.app_handle.state::<Arc<TranscriptionManager>>();
+        tm.initiate_model_load();
+
+        let mut wait_count = 0;
+        while !tm.is_model_loaded() && wait_count < MAX_MODEL_WAIT {
+            std::thread::sleep(Duration::from_millis(500));
+            wait_count += 1;
+        }
+
+        if !tm.is_model_loaded() {
+            warn!(
+                "⚠️ {} Model still not loaded after waiting, skipping transcription",
+                self.source.tag()
+            );
+            return false;
+        }
+        true
+    }
+
+    fn transcribe_window(
+        &mut self,
+        settings: &AppSettings,
+        window_start: usize,
+        samples: &[f32],
+    ) -> Option<Caption> {
+        let tag = self.source.tag();
+        let tm = self.app_handle.state::<Arc<TranscriptionManager>>();
+
+        // Window bounds; aligned captions narrow this to the emitted words
+        let mut start = self.timeline.seconds_at(window_start);
+        let mut end = self.timeline.seconds_at(window_start + samples.len());
+        let window_secs = samples.len() as f64 / TARGET_SAMPLE_RATE as f64;
+        let mut words = Vec::new();
+
+        // Apply audio preprocessing to improve transcription quality
+        // Similar to what Google Translate does: normalize, remove DC offset, high-pass filter
+        let mut audio = samples.to_vec();
+        let mut chain = PreprocessChain::standard();
+        if settings.denoise_enabled {
+            // Denoise first so the filter and normalization only see speech
+            chain.insert(0, Denoiser::new());
+        }
+        chain.process(&mut audio, TARGET_SAMPLE_RATE);
+        let translation_audio = settings
+            .caption_translation_language
+            .is_some()
+            .then(|| audio.clone());
+
+        debug!(
+            "🔄 {} Starting transcription for {} samples",
+            tag,
+            audio.len()
+        );
+
+        let context = (settings.caption_context_words > 0 && !self.context.is_empty())
+            .then(|| self.context.clone());
+        let prompt = context.as_deref();
+
+        let result = if settings.caption_alignment_enabled {
+            tm.transcribe_segments(audio, prompt, JobPriority::Caption)
+                .map(|segments| {
+                    // Word times within the window, placed on the timeline
+                    // so a gap inside it doesn't shift the words after it
+                    let mut timed = segments_to_words(&segments, 0.0);
+                    for word in timed.iter_mut() {
+                        let at = |secs: f64| {
+                            let offset = (secs * TARGET_SAMPLE_RATE as f64) as usize;
+                            self.timeline.seconds_at(window_start + offset)
+                        };
+                        word.start = at(word.start);
+                        word.end = at(word.end);
+                    }
+                    let fresh = self.aligner.push(timed);
+                    debug!(
+                        "{} Aligned {} new words ({} total)",
+                        tag,
+                        fresh.len(),
+                        self.aligner.transcript().len()
+                    );
+                    if let (Some(first), Some(last)) = (fresh.first(), fresh.last()) {
+                        start = first.start;
+                        end = last.end;
+                    }
+                    let text = join_words(&fresh);
+                    words = fresh;
+                    text
+                })
+        } else {
+            tm.transcribe_with_prompt(audio, prompt, JobPriority::Caption)
+                .map(|text| {
+                    let words: Vec<String> = text.split_whitespace().map(str::to_string).collect();
+                    let merged = if settings.caption_strategy == CaptionStrategy::Interval {
+                        // Share of the words heard in the overlap with the next window
+                        let overlap = settings.caption_overlap_ms as f64 / 1000.0;
+                        let hold = (words.len() as f64 * overlap / window_secs).ceil() as usize;
+                        self.agreement.push(words, hold)
+                    } else {
+                        // Utterances don't overlap; release anything still held
+                        let mut merged = self.agreement.flush();
+                        merged.extend(words);
+                        merged
+                    };
+                    merged.join(" ")
+                })
+        };
+
+        match result {
+            Ok(transcription) => {
+                let trimmed = transcription.trim();
+                debug!("📝 {} Raw transcription (len={})", tag, transcription.len());
+                trace!("📝 {} Raw transcription: '{}'", tag, transcription);
+
+                if trimmed.is_empty() {
+                    debug!("{} Empty result (RMS: {:.6})", tag, self.silence.last_rms());
+                    return None;
+                }
+
+                // Only process if transcription has meaningful content (more than 1 char)
+                if trimmed.len() <= 1 {
+                    return None;
+                }
+                self.context = context_tail(&self.context, trimmed, settings.caption_context_words);
+                let translation = translation_audio.and_then(|audio| {
+                    self.translate_caption(settings, &audio, window_start, start, end)
+                });
+                Some(Caption {
+                    text: trimmed.to_string(),
+                    start,
+                    end,
+                    translation,
+                    words,
+                })
+            }
+            Err(e) => {
+                error!("{} Transcription failed: {}", tag, e);
+                None
+            }
+        }
+    }
+
+    /// Translate the part of the window the caption covers, so overlap that
+    /// was already captioned isn't translated twice
+    fn translate_caption(
+        &self,
+        settings: &AppSettings,
+        audio: &[f32],
+        window_start: usize,
+        start: f64,
+        end: f64,
+    ) -> Option<String> {
+        let target = settings.caption_translation_language.as_deref()?;
+        if settings.translate_to_english {
+            // The caption itself is already English
+            return None;
+        }
+
+        let to_index = |secs: f64| {
+            let index = self.timeline.index_at(secs).saturating_sub(window_start);
+            index.min(audio.len())
+        };
+        let span = audio[to_index(start)..to_index(end)].to_vec();
+
+        let tm = self.app_handle.state::<Arc<TranscriptionManager>>();
+        match tm.translate(span, target, JobPriority::Caption) {
+            Ok(translation) if !translation.is_empty() => Some(translation),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("{} Caption translation failed: {}", self.source.tag(), e);
+                None
+            }
+        }
+    }
+
+    fn sync_sinks(&mut self, settings: &AppSettings) {
+        let config = FileSinkConfig::from_settings(settings);
+        if config == self.sink_config {
+            return;
+        }
+
+        self.sinks.clear();
+        if let Some(config) = config.clone() {
+            info!("{} Writing captions to transcript file", self.source.tag());
+            self.sinks.push(Box::new(FileTranscriptSink::new(config)));
+        }
+        self.sink_config = config;
+    }
+
+    /// Text stages every caption goes through before it is shown. Each is a
+    /// no-op on text it already produced, so finished text can be re-run.
+    fn finish_text(&self, settings: &AppSettings, text: &str) -> String {
+        let text = normalize_numbers(text, &settings.number_formatting);
+        let text = filter_profanity(&text, settings.profanity_filter, &settings.profanity_words);
+        apply_casing(&text, settings.text_casing_for(&self.binding_id))
+    }
+
+    /// Publish a new caption, or hold it as provisional when a finalization delay
+    /// is set. A new window confirms the caption before it, since its audio has
+    /// now been heard with more context.
+    fn stage(&mut self, settings: &AppSettings, mut caption: Caption, samples: Vec<f32>) {
+        caption.text = self.finish_text(settings, &caption.text);
+        if let Some(pending) = self.pending.take() {
+            self.publish(pending.caption, pending.samples);
+        }
+
+        if settings.caption_finalization_delay_ms == 0 {
+            self.publish(caption, samples);
+            return;
+        }
+
+        emit_throttled(
+            &self.app_handle,
+            "live-caption-provisional",
+            caption.text.clone(),
+        );
+        self.pending = Some(PendingCaption {
+            caption,
+            samples,
+            since: Instant::now(),
+        });
+    }
+
+    /// Finalize a provisional caption that no window confirmed within the delay.
+    /// Checked once per loop iteration, so the effective delay rounds up to it.
+    fn finalize_expired(&mut self, settings: &AppSettings) {
+        let delay = Duration::from_millis(settings.caption_finalization_delay_ms);
+        let expired = self
+            .pending
+            .as_ref()
+            .is_some_and(|pending| pending.since.elapsed() >= delay);
+        if expired {
+            if let Some(pending) = self.pending.take() {
+                self.publish(pending.caption, pending.samples);
+            }
+        }
+    }
+
+    fn publish(&mut self, caption: Caption, samples: Vec<f32>) {
+        let tag = self.source.tag();
+        let settings = get_settings(&self.app_handle);
+        let speaker = self.identify_speaker(&settings, &samples);
+        let text = match &speaker {
+            Some((_, label)) => format!("{}: {}", label, caption.text),
+            None => caption.text.clone(),
+        };
+
+        let cue = session::record_cue(caption.start, caption.end, &text);
+        session::record_words(&caption_words(&caption));
+        for sink in self.sinks.iter_mut() {
+            if let Err(e) = sink.write(&cue) {
+                error!("{} Failed to write caption to transcript sink: {}", tag, e);
+            }
+        }
+        self.app_handle
+            .state::<Arc<CaptionServer>>()
+            .broadcast(&cue.text, cue.start, cue.end);
+        if let Some(hotwords) = self.hotwords.as_mut() {
+            // Snippets are cut by sample position, which gaps don't advance
+            let position =
+                |secs: f64| self.timeline.index_at(secs) as f64 / TARGET_SAMPLE_RATE as f64;
+            hotwords.push_caption(
+                &settings.caption_hotwords,
+                position(caption.start),
+                position(caption.end),
+                &caption.text,
+            );
+        }
+        debug!("🎯 {} Result (len={})", tag, text.len());
+        trace!("🎯 {} Result: '{}'", tag, text);
+
+        let speaker_label = speaker.as_ref().map(|(_, label)| label.clone());
+        let session_id = self.history_session;
+        events::emit(
+            &self.app_handle,
+            CaptionSegment {
+                session_id,
+                source: self.source.audio_source().into(),
+                text: caption.text.clone(),
+                speaker: speaker_label.clone(),
+                start: caption.start,
+                end: caption.end,
+                emitted_at: events::now_ms(),
+            },
+        );
+
+        // Save to history (async)
+        let hm = Arc::clone(&self.app_handle.state::<Arc<HistoryManager>>());
+        let history_text = caption.text;
+        let metadata = self.history_metadata();
+        tauri::async_runtime::spawn(async move {
+            let speaker_tag = speaker.map(|(speaker_tag, _)| speaker_tag);
+            let saved = hm
+                .save_caption(session_id, samples, history_text, speaker_tag, metadata)
+                .await;
+            if let Err(e) = saved {
+                error!("Failed to save auto-transcription to history: {}", e);
+            }
+        });
+
+        // Emit live caption event to frontend
+        let update = LiveCaptionUpdate {
+            text: text.clone(),
+            speaker: speaker_label,
+            translation: caption.translation,
+        };
+        if let Err(e) = self.app_handle.emit("live-caption-update", update) {
+            error!(
+                "❌ [LiveCaption] Failed to emit live-caption-update event: {}",
+                e
+            );
+        } else {
+            debug!("✅ [LiveCaption] Emitted live-caption-update event");
+        }
+
+        let Some(mode) = caption_output_mode(&settings, &self.binding_id) else {
+            return;
+        };
+        if let Err(e) = crate::utils::output(text, mode, self.app_handle.clone()) {
+            error!("Failed to paste auto-transcription: {}", e);
+        }
+    }
+
+    /// History tag and label for the caption's speaker, if captions are being
+    /// attributed. Diarized system audio is split into numbered speakers; in
+    /// dual-channel mode the two channels are otherwise "Me" and "Others".
+    fn identify_speaker(
+        &mut self,
+        settings: &AppSettings,
+        samples: &[f32],
+    ) -> Option<(String, String)> {
+        if settings.speaker_diarization_enabled {
+            let voiceprint = self
+                .voiceprinter
+                .as_mut()
+                .and_then(|v| v.voiceprint(samples));
+            if let Some(voiceprint) = voiceprint {
+                let speaker = self.speakers.assign(&voiceprint);
+                debug!(
+                    "{} Caption attributed to speaker {}",
+                    self.source.tag(),
+                    speaker
+                );
+                return Some((
+                    format!("speaker-{}", speaker),
+                    format!("Speaker {}", speaker),
+                ));
+            }
+        }
+
+        // Both channels share the caption stream, so label who is speaking
+        settings.dual_channel_active().then(|| {
+            let (tag, label) = self.source.speaker();
+            (tag.to_string(), label.to_string())
+        })
+    }
+
+    /// Metadata saved with this stream's captions; they run in the background,
+    /// so no focused app is recorded
+    fn history_metadata(&self) -> TranscriptionMetadata {
+        let tm = self.app_handle.state::<Arc<TranscriptionManager>>();
+        TranscriptionMetadata {
+            model: tm.get_current_model(),
+            source: Some(self.source.audio_source().into()),
+            latency_ms: None,
+            language: Some(get_settings(&self.app_handle).selected_language),
+            app_name: None,
+        }
+    }
+
+    /// Save hotword snippets whose surrounding audio is complete as tagged
+    /// history entries
+    fn save_snippets(&mut self, flush: bool) {
+        let Some(hotwords) = self.hotwords.as_mut() else {
+            return;
+        };
+
+        for snippet in hotwords.take_ready(flush) {
+            info!(
+                "🔖 {} Saving snippet for hotword '{}'",
+                self.source.tag(),
+                snippet.phrase
+            );
+            let _ = self
+                .app_handle
+                .emit("hotword-snippet-saved", snippet.phrase.clone());
+            let hm = Arc::clone(&self.app_handle.state::<Arc<HistoryManager>>());
+            let session_id = self.history_session;
+            let metadata = self.history_metadata();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = hm
+                    .save_caption(
+                        session_id,
+                        snippet.samples,
+                        snippet.transcript,
+                        Some(snippet.phrase),
+                        metadata,
+                    )
+                    .await
+                {
+                    error!("Failed to save hotword snippet to history: {}", e);
+                }
+            });
+        }
+    }
+}
+
+/// The caption's words, spreading its text over its span when alignment
+/// didn't provide word timings
+fn caption_words(caption: &Caption) -> Vec<TimedWord> {
+    if !caption.words.is_empty() {
+        return caption.words.clone();
+    }
+    let span = TimedSegment {
+        start: 0.0,
+        end: (caption.end - caption.start) as f32,
+        text: caption.text.clone(),
+    };
+    segments_to_words(&[span], caption.start)
+}
+
+/// How a finished caption is output for `binding_id`, if at all. Captions stay
+/// in the overlay, history and transcript file unless pasting is opted into,
+/// and a binding set to output nothing gets nothing beyond that.
+fn caption_output_mode(settings: &AppSettings, binding_id: &str) -> Option<OutputMode> {
+    if !settings.caption_paste_enabled {
+        return None;
+    }
+    match settings.output_mode_for(binding_id) {
+        OutputMode::None => None,
+        mode => Some(mode),
+    }
+}
+
+/// The last `words` words of `previous` followed by `text`
+fn context_tail(previous: &str, text: &str, words: usize) -> String {
+    let all: Vec<&str> = previous
+        .split_whitespace()
+        .chain(text.split_whitespace())
+        .collect();
+    all[all.len().saturating_sub(words)..].join(" ")
+}
+
+fn create_segmenter(app_handle: &AppHandle) -> Result<UtteranceSegmenter, anyhow::Error> {
+    let vad_path = resolve_vad_model_path(app_handle)?;
+    let vad = SileroVad::new(vad_path, VAD_THRESHOLD)?;
+    Ok(UtteranceSegmenter::new(Box::new(vad)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::get_default_settings;
+
+    #[test]
+    fn test_caption_output_follows_binding_mode() {
+        let mut settings = get_default_settings();
+        settings.caption_paste_enabled = true;
+        settings.output_mode = OutputMode::Paste;
+        settings
+            .binding_output_mode
+            .insert("note".to_string(), OutputMode::None);
+        settings
+            .binding_output_mode
+            .insert("copy".to_string(), OutputMode::ClipboardOnly);
+
+        assert_eq!(caption_output_mode(&settings, "note"), None);
+        assert_eq!(
+            caption_output_mode(&settings, "copy"),
+            Some(OutputMode::ClipboardOnly)
+        );
+        assert_eq!(
+            caption_output_mode(&settings, "transcribe"),
+            Some(OutputMode::Paste)
+        );
+
+        settings.caption_paste_enabled = false;
+        assert_eq!(caption_output_mode(&settings, "transcribe"), None);
+    }
+}
@@ -0,0 +1,156 @@
+// Optional WebSocket server that mirrors `live-caption-update` events, so OBS
+// browser sources and other local tools can show captions outside the webview.
+
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, oneshot};
+use tokio_tungstenite::tungstenite::Message;
+
+// Captions are small and infrequent; a slow client only loses the oldest ones
+const CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Clone, Debug, Serialize)]
+struct CaptionMessage<'a> {
+    event: &'static str,
+    payload: &'a str,
+    start: f64,
+    end: f64,
+}
+
+struct RunningServer {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+}
+
+pub struct CaptionServer {
+    tx: broadcast::Sender<String>,
+    running: Mutex<Option<RunningServer>>,
+}
+
+impl CaptionServer {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            tx,
+            running: Mutex::new(None),
+        }
+    }
+
+    /// Start, stop or move the server to match the settings
+    pub fn apply_settings(&self, enabled: bool, port: u16) -> Result<(), String> {
+        let mut running = self.running.lock().unwrap();
+
+        if let Some(server) = running.as_ref() {
+            if enabled && server.port == port {
+                return Ok(());
+            }
+        }
+        if let Some(server) = running.take() {
+            let _ = server.shutdown.send(());
+            info!("🔌 [CaptionServer] Stopped server on port {}", server.port);
+        }
+        if !enabled {
+            return Ok(());
+        }
+
+        // Bind synchronously so a busy port is reported to the caller
+        let listener = std::net::TcpListener::bind(("127.0.0.1", port))
+            .map_err(|e| format!("Failed to bind caption server to port {}: {}", port, e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure caption server socket: {}", e))?;
+
+        let (shutdown, shutdown_rx) = oneshot::channel();
+        let tx = self.tx.clone();
+        tauri::async_runtime::spawn(async move {
+            match TcpListener::from_std(listener) {
+                Ok(listener) => accept_loop(listener, tx, shutdown_rx).await,
+                Err(e) => error!("❌ [CaptionServer] Failed to start listener: {}", e),
+            }
+        });
+
+        info!(
+            "🔌 [CaptionServer] Serving live captions on ws://127.0.0.1:{}",
+            port
+        );
+        *running = Some(RunningServer { port, shutdown });
+        Ok(())
+    }
+
+    /// Send a caption to every connected client
+    pub fn broadcast(&self, text: &str, start: f64, end: f64) {
+        if self.tx.receiver_count() == 0 {
+            return;
+        }
+
+        let message = CaptionMessage {
+            event: "live-caption-update",
+            payload: text,
+            start,
+            end,
+        };
+        match serde_json::to_string(&message) {
+            Ok(json) => {
+                let _ = self.tx.send(json);
+            }
+            Err(e) => error!("❌ [CaptionServer] Failed to serialize caption: {}", e),
+        }
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    tx: broadcast::Sender<String>,
+    mut shutdown: oneshot::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, addr)) => {
+                    tauri::async_runtime::spawn(serve_client(stream, addr, tx.subscribe()));
+                }
+                Err(e) => warn!("⚠️ [CaptionServer] Failed to accept connection: {}", e),
+            },
+        }
+    }
+}
+
+async fn serve_client(stream: TcpStream, addr: SocketAddr, mut rx: broadcast::Receiver<String>) {
+    let ws = match tokio_tungstenite::accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            debug!("[CaptionServer] Handshake with {} failed: {}", addr, e);
+            return;
+        }
+    };
+    info!("🔌 [CaptionServer] Client connected: {}", addr);
+
+    let (mut sink, mut incoming) = ws.split();
+    loop {
+        tokio::select! {
+            caption = rx.recv() => match caption {
+                Ok(json) => {
+                    if sink.send(Message::text(json)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("⚠️ [CaptionServer] {} fell behind, skipped {} captions", addr, skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            // Clients only listen; anything else is a ping or a close
+            message = incoming.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    info!("🔌 [CaptionServer] Client disconnected: {}", addr);
+}
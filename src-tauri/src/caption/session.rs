@@ -0,0 +1,180 @@
+use super::align::TimedWord;
+use super::analytics::{SessionAnalytics, SpeechStats};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// One published caption, timed against the start of its session
+#[derive(Clone, Debug, Serialize)]
+pub struct CaptionCue {
+    /// Seconds since the session started
+    pub start: f64,
+    pub end: f64,
+    /// Unix time in milliseconds when the cue's audio began
+    pub wall_clock_ms: i64,
+    pub text: String,
+}
+
+/// Audio that went missing during a session, e.g. buffers dropped by the
+/// device or a capture that stalled
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct CaptionGap {
+    /// Seconds since the session started
+    pub start: f64,
+    pub duration: f64,
+}
+
+/// Captions from one run of the caption loop
+#[derive(Clone, Debug, Serialize)]
+pub struct CaptionSession {
+    /// Unix time in milliseconds when capture started
+    pub started_at_ms: i64,
+    pub cues: Vec<CaptionCue>,
+    pub gaps: Vec<CaptionGap>,
+    #[serde(skip)]
+    pub stats: SpeechStats,
+}
+
+// The current session, or the last one once the loop has stopped
+static SESSION: Lazy<Mutex<Option<CaptionSession>>> = Lazy::new(|| Mutex::new(None));
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Start a new session, replacing the previous one. Returns when it started,
+/// which cue times count from.
+pub fn begin_session() -> Instant {
+    let started = Instant::now();
+    *SESSION.lock().unwrap() = Some(CaptionSession {
+        started_at_ms: now_ms(),
+        cues: Vec::new(),
+        gaps: Vec::new(),
+        stats: SpeechStats::default(),
+    });
+    started
+}
+
+/// Add a cue to the current session and return it
+pub fn record_cue(start: f64, end: f64, text: &str) -> CaptionCue {
+    let mut session = SESSION.lock().unwrap();
+    let started_at_ms = session
+        .as_ref()
+        .map(|s| s.started_at_ms)
+        .unwrap_or_else(now_ms);
+    let cue = CaptionCue {
+        start,
+        end: end.max(start),
+        wall_clock_ms: started_at_ms + (start * 1000.0) as i64,
+        text: text.to_string(),
+    };
+    if let Some(session) = session.as_mut() {
+        session.cues.push(cue.clone());
+    }
+    cue
+}
+
+/// Note audio missing from the current session
+pub fn record_gap(gap: CaptionGap) {
+    if let Some(session) = SESSION.lock().unwrap().as_mut() {
+        session.gaps.push(gap);
+    }
+}
+
+/// Feed the words of a published caption into the session's speech statistics
+pub fn record_words(words: &[TimedWord]) {
+    if let Some(session) = SESSION.lock().unwrap().as_mut() {
+        session.stats.push_words(words);
+    }
+}
+
+pub fn session_analytics() -> Option<SessionAnalytics> {
+    SESSION
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|session| session.stats.analytics())
+}
+
+pub fn current_session() -> Option<CaptionSession> {
+    SESSION.lock().unwrap().clone()
+}
+
+/// `HH:MM:SS<sep>mmm`; SRT uses ',' and WebVTT '.'
+fn format_timestamp(seconds: f64, separator: char) -> String {
+    let total_ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        total_ms / 3_600_000,
+        (total_ms / 60_000) % 60,
+        (total_ms / 1000) % 60,
+        separator,
+        total_ms % 1000
+    )
+}
+
+pub fn to_srt(cues: &[CaptionCue]) -> String {
+    let mut out = String::new();
+    for (i, cue) in cues.iter().enumerate() {
+        let _ = write!(
+            out,
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_timestamp(cue.start, ','),
+            format_timestamp(cue.end, ','),
+            cue.text.trim()
+        );
+    }
+    out
+}
+
+pub fn to_vtt(cues: &[CaptionCue]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for cue in cues {
+        let _ = write!(
+            out,
+            "{} --> {}\n{}\n\n",
+            format_timestamp(cue.start, '.'),
+            format_timestamp(cue.end, '.'),
+            cue.text.trim()
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue(start: f64, end: f64, text: &str) -> CaptionCue {
+        CaptionCue {
+            start,
+            end,
+            wall_clock_ms: 0,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_srt_and_vtt_formatting() {
+        let cues = vec![
+            cue(1.5, 3.25, "Hello there"),
+            cue(3661.0, 3662.001, "Later"),
+        ];
+
+        assert_eq!(
+            to_srt(&cues),
+            "1\n00:00:01,500 --> 00:00:03,250\nHello there\n\n\
+             2\n01:01:01,000 --> 01:01:02,001\nLater\n\n"
+        );
+        assert_eq!(
+            to_vtt(&cues[..1]),
+            "WEBVTT\n\n00:00:01.500 --> 00:00:03.250\nHello there\n\n"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use super::session::CaptionCue;
+use crate::settings::AppSettings;
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Receives every finalized caption from the caption loop
+pub trait TranscriptSink: Send {
+    fn write(&mut self, cue: &CaptionCue) -> Result<()>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileSinkConfig {
+    path: PathBuf,
+    timestamps: bool,
+    daily_rotation: bool,
+}
+
+impl FileSinkConfig {
+    pub fn from_settings(settings: &AppSettings) -> Option<Self> {
+        if !settings.transcript_file_enabled {
+            return None;
+        }
+        let path = settings.transcript_file_path.as_deref()?.trim();
+        if path.is_empty() {
+            return None;
+        }
+        Some(Self {
+            path: PathBuf::from(path),
+            timestamps: settings.transcript_file_timestamps,
+            daily_rotation: settings.transcript_file_daily_rotation,
+        })
+    }
+}
+
+/// Appends captions to a text file, or a Markdown list if the file ends in `.md`
+pub struct FileTranscriptSink {
+    config: FileSinkConfig,
+}
+
+impl FileTranscriptSink {
+    pub fn new(config: FileSinkConfig) -> Self {
+        Self { config }
+    }
+
+    /// With daily rotation `notes.md` becomes `notes-2024-05-01.md`
+    fn target_path(&self, date: NaiveDate) -> PathBuf {
+        if !self.config.daily_rotation {
+            return self.config.path.clone();
+        }
+        let stem = self
+            .config
+            .path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "transcript".to_string());
+        let file_name = match self.config.path.extension() {
+            Some(ext) => format!("{}-{}.{}", stem, date, ext.to_string_lossy()),
+            None => format!("{}-{}", stem, date),
+        };
+        self.config.path.with_file_name(file_name)
+    }
+
+    fn format_line(&self, path: &Path, time: &DateTime<Local>, text: &str) -> String {
+        let markdown = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("md"))
+            .unwrap_or(false);
+        let text = text.trim();
+
+        match (markdown, self.config.timestamps) {
+            (true, true) => format!("- **{}** {}\n", time.format("%H:%M:%S"), text),
+            (true, false) => format!("- {}\n", text),
+            (false, true) => format!("[{}] {}\n", time.format("%H:%M:%S"), text),
+            (false, false) => format!("{}\n", text),
+        }
+    }
+}
+
+impl TranscriptSink for FileTranscriptSink {
+    fn write(&mut self, cue: &CaptionCue) -> Result<()> {
+        let time = DateTime::from_timestamp_millis(cue.wall_clock_ms)
+            .map(|t| t.with_timezone(&Local))
+            .unwrap_or_else(Local::now);
+        let path = self.target_path(time.date_naive());
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        file.write_all(self.format_line(&path, &time, &cue.text).as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Append text finished outside the caption loop, e.g. a hotkey recording
+/// whose binding outputs nothing, to the transcript file if one is set up
+pub fn append_to_transcript_file(settings: &AppSettings, text: &str) -> Result<()> {
+    let Some(config) = FileSinkConfig::from_settings(settings) else {
+        return Ok(());
+    };
+    let cue = CaptionCue {
+        start: 0.0,
+        end: 0.0,
+        wall_clock_ms: Local::now().timestamp_millis(),
+        text: text.to_string(),
+    };
+    FileTranscriptSink::new(config).write(&cue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_rotated_markdown_lines() {
+        let sink = FileTranscriptSink::new(FileSinkConfig {
+            path: PathBuf::from("/notes/meeting.md"),
+            timestamps: true,
+            daily_rotation: true,
+        });
+
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let path = sink.target_path(date);
+        assert_eq!(path, PathBuf::from("/notes/meeting-2024-05-01.md"));
+
+        let time = Local.with_ymd_and_hms(2024, 5, 1, 9, 30, 5).unwrap();
+        assert_eq!(
+            sink.format_line(&path, &time, " hello "),
+            "- **09:30:05** hello\n"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use super::session::CaptionGap;
+use std::time::Instant;
+
+/// Capture stamps further than this from where the sample count puts them
+/// move the timeline; closer ones are callback jitter
+const TOLERANCE_SECS: f64 = 0.25;
+
+/// Places the loop's 16kHz samples on the session timeline from their capture
+/// stamps. Audio runs on contiguously between anchors; a stamp that disagrees
+/// with the running position sets a new one, so time lost to dropped buffers
+/// shows up as a gap instead of pulling every later caption early.
+pub struct Timeline {
+    started: Instant,
+    sample_rate: usize,
+    // Sample position and its session time, ascending
+    anchors: Vec<(usize, f64)>,
+}
+
+impl Timeline {
+    pub fn new(started: Instant, sample_rate: usize) -> Self {
+        Self {
+            started,
+            sample_rate,
+            anchors: Vec::new(),
+        }
+    }
+
+    /// The sample at `index` was captured at `at`. Returns the gap before it
+    /// if audio went missing.
+    pub fn observe(&mut self, index: usize, at: Instant) -> Option<CaptionGap> {
+        let secs = match at.checked_duration_since(self.started) {
+            Some(since) => since.as_secs_f64(),
+            // Buffered before the session began
+            None => -self.started.duration_since(at).as_secs_f64(),
+        };
+        if self.anchors.is_empty() {
+            self.anchors.push((index, secs));
+            return None;
+        }
+
+        let expected = self.position(index);
+        if (secs - expected).abs() <= TOLERANCE_SECS {
+            return None;
+        }
+        self.anchors.retain(|&(i, _)| i < index);
+        self.anchors.push((index, secs));
+        // Stamps running early only correct the position
+        (secs > expected).then(|| CaptionGap {
+            start: expected.max(0.0),
+            duration: secs - expected.max(0.0),
+        })
+    }
+
+    /// Session time of the sample at `index`
+    pub fn seconds_at(&self, index: usize) -> f64 {
+        self.position(index).max(0.0)
+    }
+
+    /// Position of the sample heard `secs` into the session; the first one
+    /// after a gap when `secs` falls in it
+    pub fn index_at(&self, secs: f64) -> usize {
+        let n = self
+            .anchors
+            .iter()
+            .rposition(|&(_, s)| s <= secs)
+            .unwrap_or(0);
+        let Some(&(index, start)) = self.anchors.get(n) else {
+            return (secs.max(0.0) * self.sample_rate as f64) as usize;
+        };
+        let index = index + ((secs - start).max(0.0) * self.sample_rate as f64) as usize;
+        match self.anchors.get(n + 1) {
+            Some(&(next, _)) => index.min(next),
+            None => index,
+        }
+    }
+
+    /// Drop what is only needed for samples before `index`
+    pub fn forget_before(&mut self, index: usize) {
+        let keep = self
+            .anchors
+            .iter()
+            .rposition(|&(i, _)| i <= index)
+            .unwrap_or(0);
+        self.anchors.drain(..keep);
+    }
+
+    fn position(&self, index: usize) -> f64 {
+        let anchor = self
+            .anchors
+            .iter()
+            .rev()
+            .find(|&&(i, _)| i <= index)
+            .or(self.anchors.first());
+        match anchor {
+            Some(&(i, secs)) => secs + (index as f64 - i as f64) / self.sample_rate as f64,
+            None => index as f64 / self.sample_rate as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_dropped_audio_becomes_a_gap() {
+        let started = Instant::now();
+        let mut timeline = Timeline::new(started, 100);
+        assert_eq!(timeline.observe(0, started + Duration::from_secs(1)), None);
+        // Jitter doesn't move anything
+        assert_eq!(
+            timeline.observe(100, started + Duration::from_millis(2100)),
+            None
+        );
+        assert_eq!(timeline.seconds_at(150), 2.5);
+
+        // The second second of audio never arrived
+        let gap = timeline.observe(200, started + Duration::from_secs(4));
+        assert_eq!(
+            gap,
+            Some(CaptionGap {
+                start: 3.0,
+                duration: 1.0
+            })
+        );
+        assert_eq!(timeline.seconds_at(250), 4.5);
+        assert_eq!(timeline.index_at(4.5), 250);
+        // Inside the gap maps to the first sample after it
+        assert_eq!(timeline.index_at(3.5), 200);
+
+        timeline.forget_before(220);
+        assert_eq!(timeline.seconds_at(250), 4.5);
+    }
+}
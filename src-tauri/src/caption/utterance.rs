@@ -0,0 +1,165 @@
+use crate::audio_toolkit::VoiceActivityDetector;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+// 30ms frames at 16kHz, the size SileroVad expects
+const FRAME_SAMPLES: usize = 480;
+// ~500ms of silence closes an utterance
+const END_SILENCE_FRAMES: usize = 17;
+// Keep ~300ms before the first speech frame so word onsets aren't clipped
+const PRE_ROLL_SAMPLES: usize = 10 * FRAME_SAMPLES;
+// Utterances shorter than ~240ms of speech are clicks/noise
+const MIN_SPEECH_FRAMES: usize = 8;
+// Force a cut on long monologues so captions keep flowing
+const MAX_UTTERANCE_SAMPLES: usize = 15 * 16000;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Segment {
+    /// Not enough audio to decide yet
+    Wait,
+    /// `buffer[range]` holds a complete utterance; drain up to `range.end`
+    Utterance(Range<usize>),
+    /// The first `n` samples hold nothing worth transcribing; drain them
+    Discard(usize),
+}
+
+/// Finds end-of-utterance boundaries in the caption buffer with a VAD, so
+/// transcription runs once per utterance instead of on a fixed timer.
+pub struct UtteranceSegmenter {
+    vad: Box<dyn VoiceActivityDetector>,
+    scanned: usize,
+    speech_start: Option<usize>,
+    speech_frames: usize,
+    silence_frames: usize,
+    frame: Vec<f32>,
+}
+
+impl UtteranceSegmenter {
+    pub fn new(vad: Box<dyn VoiceActivityDetector>) -> Self {
+        Self {
+            vad,
+            scanned: 0,
+            speech_start: None,
+            speech_frames: 0,
+            silence_frames: 0,
+            frame: Vec::with_capacity(FRAME_SAMPLES),
+        }
+    }
+
+    /// Classify any unscanned frames of `buffer` and report the next boundary
+    pub fn scan(&mut self, buffer: &VecDeque<f32>) -> Segment {
+        while self.scanned + FRAME_SAMPLES <= buffer.len() {
+            let frame_start = self.scanned;
+            let frame_end = frame_start + FRAME_SAMPLES;
+            self.frame.clear();
+            self.frame.extend(buffer.range(frame_start..frame_end));
+            self.scanned = frame_end;
+
+            let is_speech = self.vad.is_voice(&self.frame).unwrap_or(false);
+
+            let start = match self.speech_start {
+                Some(start) => start,
+                None => {
+                    if is_speech {
+                        self.speech_start = Some(frame_start.saturating_sub(PRE_ROLL_SAMPLES));
+                        self.speech_frames = 1;
+                        self.silence_frames = 0;
+                    }
+                    continue;
+                }
+            };
+
+            if is_speech {
+                self.speech_frames += 1;
+                self.silence_frames = 0;
+            } else {
+                self.silence_frames += 1;
+            }
+
+            let ended = self.silence_frames >= END_SILENCE_FRAMES;
+            let too_long = frame_end - start >= MAX_UTTERANCE_SAMPLES;
+            if ended || too_long {
+                let speech_frames = self.speech_frames;
+                self.speech_start = None;
+                self.speech_frames = 0;
+                self.silence_frames = 0;
+
+                if speech_frames < MIN_SPEECH_FRAMES {
+                    return Segment::Discard(frame_end);
+                }
+                return Segment::Utterance(start..frame_end);
+            }
+        }
+
+        // Nothing in progress: leading silence only needs to keep the pre-roll
+        if self.speech_start.is_none() && self.scanned > PRE_ROLL_SAMPLES {
+            return Segment::Discard(self.scanned - PRE_ROLL_SAMPLES);
+        }
+        Segment::Wait
+    }
+
+    /// The caller removed `n` samples from the front of the buffer
+    pub fn drained(&mut self, n: usize) {
+        self.scanned = self.scanned.saturating_sub(n);
+        self.speech_start = self.speech_start.map(|s| s.saturating_sub(n));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_toolkit::error::Result;
+    use crate::audio_toolkit::vad::VadFrame;
+
+    /// Treats any frame with a loud first sample as speech
+    struct LevelVad;
+
+    impl VoiceActivityDetector for LevelVad {
+        fn push_frame<'a>(&'a mut self, frame: &'a [f32]) -> Result<VadFrame<'a>> {
+            if frame[0] > 0.5 {
+                Ok(VadFrame::Speech(frame))
+            } else {
+                Ok(VadFrame::Noise)
+            }
+        }
+    }
+
+    fn frames(buffer: &mut VecDeque<f32>, count: usize, level: f32) {
+        buffer.extend(std::iter::repeat(level).take(count * FRAME_SAMPLES));
+    }
+
+    #[test]
+    fn test_emits_utterance_after_trailing_silence() {
+        let mut seg = UtteranceSegmenter::new(Box::new(LevelVad));
+        let mut buffer = VecDeque::new();
+
+        frames(&mut buffer, 20, 0.0);
+        frames(&mut buffer, 10, 1.0);
+        frames(&mut buffer, 5, 0.0);
+        // Speech has started, so nothing is released until it ends
+        assert_eq!(seg.scan(&buffer), Segment::Wait);
+
+        frames(&mut buffer, END_SILENCE_FRAMES, 0.0);
+        let start = 20 * FRAME_SAMPLES - PRE_ROLL_SAMPLES;
+        let end = (35 + END_SILENCE_FRAMES - 5) * FRAME_SAMPLES;
+        assert_eq!(seg.scan(&buffer), Segment::Utterance(start..end));
+    }
+
+    #[test]
+    fn test_discards_silence_and_short_blips() {
+        let mut seg = UtteranceSegmenter::new(Box::new(LevelVad));
+        let mut buffer = VecDeque::new();
+
+        frames(&mut buffer, 30, 0.0);
+        assert_eq!(
+            seg.scan(&buffer),
+            Segment::Discard(30 * FRAME_SAMPLES - PRE_ROLL_SAMPLES)
+        );
+        buffer.drain(..30 * FRAME_SAMPLES - PRE_ROLL_SAMPLES);
+        seg.drained(30 * FRAME_SAMPLES - PRE_ROLL_SAMPLES);
+
+        frames(&mut buffer, 2, 1.0);
+        frames(&mut buffer, END_SILENCE_FRAMES + 1, 0.0);
+        assert!(matches!(seg.scan(&buffer), Segment::Discard(_)));
+    }
+}
@@ -1,12 +1,18 @@
-use crate::settings::{get_settings, ClipboardHandling, PasteMethod};
+use crate::metrics::{self, Stage};
+use crate::settings::{get_settings, ClipboardHandling, OutputMode, PasteMethod};
 use enigo::Enigo;
 use enigo::Key;
 use enigo::Keyboard;
 use enigo::Settings;
 use log::info;
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter};
 use tauri_plugin_clipboard_manager::ClipboardExt;
 
+#[cfg(target_os = "linux")]
+pub(crate) mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+
 /// Sends a Ctrl+V or Cmd+V paste command using platform-specific virtual key codes.
 /// This ensures the paste works regardless of keyboard layout (e.g., Russian, AZERTY, DVORAK).
 fn send_paste_ctrl_v() -> Result<(), String> {
@@ -80,6 +86,17 @@ fn paste_via_direct_input(text: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Inserts text through the macOS Accessibility API.
+/// Falls back to direct input for fields that don't expose their text.
+#[cfg(target_os = "macos")]
+fn paste_via_accessibility(text: &str) -> Result<(), String> {
+    if let Err(e) = macos::insert_text(text) {
+        log::warn!("Accessibility insertion failed ({}), typing instead", e);
+        paste_via_direct_input(text)?;
+    }
+    Ok(())
+}
+
 /// Pastes text using the clipboard method with Ctrl+V/Cmd+V.
 /// Saves the current clipboard, writes the text, sends paste command, then restores the clipboard.
 fn paste_via_clipboard_ctrl_v(text: &str, app_handle: &AppHandle) -> Result<(), String> {
@@ -135,18 +152,86 @@ fn paste_via_clipboard_shift_insert(text: &str, app_handle: &AppHandle) -> Resul
     Ok(())
 }
 
+/// Copies instead of pasting when the focused app is on the paste blacklist,
+/// and tells the frontend why nothing was pasted. Returns true if it did.
+fn copy_if_blacklisted(
+    text: &str,
+    blacklist: &[String],
+    app_handle: &AppHandle,
+) -> Result<bool, String> {
+    if blacklist.is_empty() {
+        return Ok(false);
+    }
+    let app = match crate::utils::frontmost_app() {
+        Some(app) if app.matches_any(blacklist) => app,
+        _ => return Ok(false),
+    };
+
+    info!(
+        "Not pasting into blacklisted app {} ({}), copying instead",
+        app.name, app.identifier
+    );
+    app_handle
+        .clipboard()
+        .write_text(text)
+        .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
+    let _ = app_handle.emit("paste-blocked", &app.name);
+    Ok(true)
+}
+
 pub fn paste(text: String, app_handle: AppHandle) -> Result<(), String> {
+    let paste_method = get_settings(&app_handle).paste_method;
+    metrics::time(Stage::Paste, || paste_with(text, paste_method, app_handle))
+}
+
+/// Send `text` wherever `mode` says finished transcriptions go
+pub fn output(text: String, mode: OutputMode, app_handle: AppHandle) -> Result<(), String> {
+    match mode {
+        OutputMode::Paste => paste(text, app_handle),
+        OutputMode::Type => metrics::time(Stage::Paste, || {
+            paste_with(text, PasteMethod::Direct, app_handle)
+        }),
+        OutputMode::ClipboardOnly => app_handle
+            .clipboard()
+            .write_text(&text)
+            .map_err(|e| format!("Failed to copy to clipboard: {}", e)),
+        // History already has it; the transcript file gets it too if one is set up
+        OutputMode::None => {
+            crate::caption::append_to_transcript_file(&get_settings(&app_handle), &text)
+                .map_err(|e| format!("Failed to append to transcript file: {}", e))
+        }
+    }
+}
+
+fn paste_with(
+    text: String,
+    paste_method: PasteMethod,
+    app_handle: AppHandle,
+) -> Result<(), String> {
     let settings = get_settings(&app_handle);
-    let paste_method = settings.paste_method;
 
-    info!("Using paste method: {:?}", paste_method);
+    if copy_if_blacklisted(&text, &settings.paste_blacklist, &app_handle)? {
+        return Ok(());
+    }
 
-    // Perform the paste operation
-    match paste_method {
-        PasteMethod::CtrlV => paste_via_clipboard_ctrl_v(&text, &app_handle)?,
-        PasteMethod::Direct => paste_via_direct_input(&text)?,
-        #[cfg(not(target_os = "macos"))]
-        PasteMethod::ShiftInsert => paste_via_clipboard_shift_insert(&text, &app_handle)?,
+    // On Linux a dedicated typing backend may take over (e.g. on Wayland)
+    #[cfg(target_os = "linux")]
+    let handled = linux::paste(settings.linux_typing_backend, &text, &app_handle)?;
+    #[cfg(not(target_os = "linux"))]
+    let handled = false;
+
+    if !handled {
+        info!("Using paste method: {:?}", paste_method);
+
+        // Perform the paste operation
+        match paste_method {
+            PasteMethod::CtrlV => paste_via_clipboard_ctrl_v(&text, &app_handle)?,
+            PasteMethod::Direct => paste_via_direct_input(&text)?,
+            #[cfg(not(target_os = "macos"))]
+            PasteMethod::ShiftInsert => paste_via_clipboard_shift_insert(&text, &app_handle)?,
+            #[cfg(target_os = "macos")]
+            PasteMethod::Accessibility => paste_via_accessibility(&text)?,
+        }
     }
 
     // After pasting, optionally copy to clipboard based on settings
@@ -0,0 +1,133 @@
+//! Linux text output backends.
+//!
+//! Enigo only drives X11, so on Wayland compositors the regular paste methods
+//! silently do nothing. These backends shell out to the usual input tools
+//! instead: wtype (wlroots compositors), ydotool (any compositor, needs the
+//! ydotoold daemon) and xdotool (X11/XWayland).
+
+use crate::settings::LinuxTypingBackend;
+use log::{info, warn};
+use std::path::Path;
+use std::process::Command;
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SessionType {
+    Wayland,
+    X11,
+    Unknown,
+}
+
+pub(crate) fn detect_session() -> SessionType {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        return SessionType::Wayland;
+    }
+    match std::env::var("XDG_SESSION_TYPE").as_deref() {
+        Ok("wayland") => SessionType::Wayland,
+        Ok("x11") => SessionType::X11,
+        _ if std::env::var_os("DISPLAY").is_some() => SessionType::X11,
+        _ => SessionType::Unknown,
+    }
+}
+
+/// Whether `binary` can be found on PATH
+pub(crate) fn command_exists(binary: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| {
+            std::env::split_paths(&paths).any(|dir| Path::new(&dir).join(binary).is_file())
+        })
+        .unwrap_or(false)
+}
+
+/// Pick a concrete backend for the configured preference.
+///
+/// Returns `None` when the regular enigo-based paste methods should be used,
+/// which is what `Auto` does on X11.
+pub(crate) fn resolve_backend(preference: LinuxTypingBackend) -> Option<LinuxTypingBackend> {
+    match preference {
+        LinuxTypingBackend::Auto => {
+            match detect_session() {
+                SessionType::Wayland => {
+                    if command_exists("wtype") {
+                        Some(LinuxTypingBackend::Wtype)
+                    } else if command_exists("ydotool") {
+                        Some(LinuxTypingBackend::Ydotool)
+                    } else {
+                        warn!("Wayland session without wtype or ydotool, falling back to clipboard only");
+                        Some(LinuxTypingBackend::ClipboardOnly)
+                    }
+                }
+                SessionType::X11 | SessionType::Unknown => None,
+            }
+        }
+        other => Some(other),
+    }
+}
+
+fn run_tool(binary: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new(binary)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", binary, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "{} exited with {}: {}",
+            binary,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+fn type_text(backend: LinuxTypingBackend, text: &str) -> Result<(), String> {
+    match backend {
+        LinuxTypingBackend::Wtype => run_tool("wtype", &["--", text]),
+        LinuxTypingBackend::Ydotool => run_tool("ydotool", &["type", "--", text]),
+        LinuxTypingBackend::Xdotool => {
+            run_tool("xdotool", &["type", "--clearmodifiers", "--", text])
+        }
+        LinuxTypingBackend::Auto | LinuxTypingBackend::ClipboardOnly => Ok(()),
+    }
+}
+
+fn copy_to_clipboard(text: &str, app_handle: &AppHandle) -> Result<(), String> {
+    app_handle
+        .clipboard()
+        .write_text(text)
+        .map_err(|e| format!("Failed to write to clipboard: {}", e))
+}
+
+/// Output `text` through the configured Linux backend.
+///
+/// Returns `Ok(false)` if no backend applies and the caller should use the
+/// regular paste method. If a typing tool fails the text is left on the
+/// clipboard so the transcription isn't lost.
+pub fn paste(
+    preference: LinuxTypingBackend,
+    text: &str,
+    app_handle: &AppHandle,
+) -> Result<bool, String> {
+    let backend = match resolve_backend(preference) {
+        Some(backend) => backend,
+        None => return Ok(false),
+    };
+
+    info!("Using Linux typing backend: {:?}", backend);
+
+    if backend == LinuxTypingBackend::ClipboardOnly {
+        copy_to_clipboard(text, app_handle)?;
+        return Ok(true);
+    }
+
+    if let Err(e) = type_text(backend, text) {
+        warn!(
+            "{:?} backend failed ({}), leaving text on the clipboard",
+            backend, e
+        );
+        copy_to_clipboard(text, app_handle)?;
+    }
+    Ok(true)
+}
@@ -0,0 +1,106 @@
+//! Accessibility (AXUIElement) text insertion on macOS.
+//!
+//! Sets the focused element's `AXSelectedText`, which replaces the selection
+//! or inserts at the caret. Nothing goes through the clipboard and no
+//! keystrokes are sent, so it also works in fields that block paste. Needs the
+//! same Accessibility permission as the keystroke-based methods.
+
+use core_foundation_sys::base::{CFRelease, CFTypeRef};
+use core_foundation_sys::string::{kCFStringEncodingUTF8, CFStringCreateWithBytes, CFStringRef};
+use std::os::raw::c_void;
+
+type AXUIElementRef = *const c_void;
+type AXError = i32;
+
+const AX_ERROR_SUCCESS: AXError = 0;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
+    fn AXUIElementCreateSystemWide() -> AXUIElementRef;
+    fn AXUIElementCopyAttributeValue(
+        element: AXUIElementRef,
+        attribute: CFStringRef,
+        value: *mut CFTypeRef,
+    ) -> AXError;
+    fn AXUIElementIsAttributeSettable(
+        element: AXUIElementRef,
+        attribute: CFStringRef,
+        settable: *mut u8,
+    ) -> AXError;
+    fn AXUIElementSetAttributeValue(
+        element: AXUIElementRef,
+        attribute: CFStringRef,
+        value: CFTypeRef,
+    ) -> AXError;
+}
+
+/// Owned Core Foundation object, released on drop
+struct CfOwned(CFTypeRef);
+
+impl CfOwned {
+    fn string(text: &str) -> Result<Self, String> {
+        let string = unsafe {
+            CFStringCreateWithBytes(
+                std::ptr::null(),
+                text.as_ptr(),
+                text.len() as _,
+                kCFStringEncodingUTF8,
+                0,
+            )
+        };
+        if string.is_null() {
+            return Err("Failed to create CFString".to_string());
+        }
+        Ok(Self(string as CFTypeRef))
+    }
+}
+
+impl Drop for CfOwned {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            unsafe { CFRelease(self.0) };
+        }
+    }
+}
+
+/// Insert `text` into the focused text field through the Accessibility API
+pub fn insert_text(text: &str) -> Result<(), String> {
+    unsafe {
+        if !AXIsProcessTrusted() {
+            return Err("Accessibility permission not granted".to_string());
+        }
+
+        let system = CfOwned(AXUIElementCreateSystemWide());
+        let focused_attribute = CfOwned::string("AXFocusedUIElement")?;
+        let mut focused: CFTypeRef = std::ptr::null();
+        let error = AXUIElementCopyAttributeValue(
+            system.0,
+            focused_attribute.0 as CFStringRef,
+            &mut focused,
+        );
+        if error != AX_ERROR_SUCCESS || focused.is_null() {
+            return Err(format!("No focused element (AXError {})", error));
+        }
+        let focused = CfOwned(focused);
+
+        let selected_text = CfOwned::string("AXSelectedText")?;
+        let mut settable = 0u8;
+        let error = AXUIElementIsAttributeSettable(
+            focused.0,
+            selected_text.0 as CFStringRef,
+            &mut settable,
+        );
+        if error != AX_ERROR_SUCCESS || settable == 0 {
+            return Err("Focused element does not accept text insertion".to_string());
+        }
+
+        let value = CfOwned::string(text)?;
+        let error =
+            AXUIElementSetAttributeValue(focused.0, selected_text.0 as CFStringRef, value.0);
+        if error != AX_ERROR_SUCCESS {
+            return Err(format!("Failed to insert text (AXError {})", error));
+        }
+    }
+    Ok(())
+}
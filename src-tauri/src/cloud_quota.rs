@@ -0,0 +1,149 @@
+// Daily minute budget for cloud speech-to-text providers
+// Always-on captioning can send hours of audio a day, so usage is tracked
+// here and requests that would go over the configured budget are refused.
+
+use crate::settings;
+use chrono::Local;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+pub const CLOUD_USAGE_STORE_PATH: &str = "cloud_usage.json";
+
+/// Audio sent to cloud providers on a single local day
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    /// Local date as YYYY-MM-DD
+    pub date: String,
+    pub seconds: f64,
+}
+
+impl DailyUsage {
+    /// Usage for `date`, starting from zero if the stored day is older
+    fn for_date(self, date: &str) -> Self {
+        if self.date == date {
+            self
+        } else {
+            Self {
+                date: date.to_string(),
+                seconds: 0.0,
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CloudUsage {
+    pub date: String,
+    pub minutes_used: f64,
+    /// `None` when no daily quota is configured
+    pub daily_limit_minutes: Option<u32>,
+    pub cost_per_minute: f64,
+    pub estimated_cost: f64,
+}
+
+/// Payload of the `cloud-quota-exceeded` event
+#[derive(Clone, Debug, Serialize)]
+pub struct QuotaExceeded {
+    pub minutes_used: f64,
+    pub requested_minutes: f64,
+    pub daily_limit_minutes: u32,
+}
+
+/// Whether sending `requested_secs` more would go over `limit_minutes`.
+/// A limit of 0 means unlimited.
+fn exceeds_quota(used_secs: f64, requested_secs: f64, limit_minutes: u32) -> bool {
+    limit_minutes > 0 && used_secs + requested_secs > limit_minutes as f64 * 60.0
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+fn load_usage(app: &AppHandle) -> DailyUsage {
+    let today = today();
+    let usage = app
+        .store(CLOUD_USAGE_STORE_PATH)
+        .ok()
+        .and_then(|store| store.get("usage"))
+        .and_then(|value| serde_json::from_value::<DailyUsage>(value).ok())
+        .unwrap_or_default();
+    usage.for_date(&today)
+}
+
+fn save_usage(app: &AppHandle, usage: &DailyUsage) {
+    match app.store(CLOUD_USAGE_STORE_PATH) {
+        Ok(store) => store.set("usage", serde_json::to_value(usage).unwrap()),
+        Err(e) => warn!("Failed to open cloud usage store: {}", e),
+    }
+}
+
+/// Check that `audio_secs` of audio fits in today's budget before sending it.
+/// Emits `cloud-quota-exceeded` and returns an error if it doesn't.
+pub fn check_quota(app: &AppHandle, audio_secs: f64) -> Result<(), QuotaExceeded> {
+    let limit = settings::get_settings(app).cloud_daily_quota_minutes;
+    let usage = load_usage(app);
+
+    if !exceeds_quota(usage.seconds, audio_secs, limit) {
+        return Ok(());
+    }
+
+    let exceeded = QuotaExceeded {
+        minutes_used: usage.seconds / 60.0,
+        requested_minutes: audio_secs / 60.0,
+        daily_limit_minutes: limit,
+    };
+    warn!(
+        "☁️ [Quota] Refusing cloud request: {:.1} of {} minutes used today",
+        exceeded.minutes_used, limit
+    );
+    if let Err(e) = app.emit("cloud-quota-exceeded", exceeded.clone()) {
+        warn!("Failed to emit cloud-quota-exceeded event: {}", e);
+    }
+    Err(exceeded)
+}
+
+/// Add audio that was sent to a cloud provider to today's usage
+pub fn record_usage(app: &AppHandle, audio_secs: f64) {
+    let mut usage = load_usage(app);
+    usage.seconds += audio_secs.max(0.0);
+    save_usage(app, &usage);
+    info!(
+        "☁️ [Quota] {:.1} cloud minutes used today",
+        usage.seconds / 60.0
+    );
+}
+
+pub fn get_usage(app: &AppHandle) -> CloudUsage {
+    let settings = settings::get_settings(app);
+    let usage = load_usage(app);
+    let minutes_used = usage.seconds / 60.0;
+
+    CloudUsage {
+        date: usage.date,
+        minutes_used,
+        daily_limit_minutes: Some(settings.cloud_daily_quota_minutes).filter(|&m| m > 0),
+        cost_per_minute: settings.cloud_cost_per_minute,
+        estimated_cost: minutes_used * settings.cloud_cost_per_minute,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_limits_and_daily_reset() {
+        assert!(!exceeds_quota(3000.0, 600.0, 0));
+        assert!(!exceeds_quota(3000.0, 600.0, 60));
+        assert!(exceeds_quota(3000.0, 601.0, 60));
+
+        let yesterday = DailyUsage {
+            date: "2024-01-01".to_string(),
+            seconds: 90.0,
+        };
+        assert_eq!(yesterday.clone().for_date("2024-01-01").seconds, 90.0);
+        assert_eq!(yesterday.for_date("2024-01-02").seconds, 0.0);
+    }
+}
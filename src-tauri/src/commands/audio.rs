@@ -1,10 +1,23 @@
 use crate::audio_feedback;
-use crate::audio_toolkit::audio::{list_input_devices, list_output_devices};
+use crate::audio_toolkit::audio::{
+    find_device, input_capabilities, list_input_devices, list_output_devices, output_capabilities,
+    request_spectrum, CpalDeviceInfo, DeviceCapabilities, Downmix, MAX_GAIN_DB, MAX_SPECTRUM_BANDS,
+    MIN_GAIN_DB, RECORDABLE_FORMATS,
+};
+use crate::audio_toolkit::blackhole::{self, BlackHoleStatus, RoutingCheck};
+use crate::audio_toolkit::screencapturekit::CaptureTarget;
+use crate::audio_toolkit::selftest::AudioSelfTest;
+use crate::audio_toolkit::utils::{self, LinuxAudioHost};
+use crate::audio_toolkit::wasapi::{self, WasapiOptions};
+use crate::audio_toolkit::AudioError;
 use crate::managers::audio::{AudioRecordingManager, MicrophoneMode};
-use crate::settings::{get_settings, write_settings, AudioSource};
+use crate::settings::{get_settings, write_settings, AudioSource, ChannelDownmix, InputGain};
+use cpal::traits::HostTrait;
 use log::warn;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
 
 #[derive(Serialize)]
@@ -22,6 +35,16 @@ fn custom_sound_exists(app: &AppHandle, sound_type: &str) -> bool {
         .map_or(false, |path| path.exists())
 }
 
+/// `e` for the frontend: the whole chain, plus what the user can do about it
+/// when the audio toolkit said what went wrong
+fn audio_error_message(context: &str, e: &anyhow::Error) -> String {
+    let message = format!("{}: {:#}", context, e);
+    match e.downcast_ref::<AudioError>().and_then(AudioError::hint) {
+        Some(hint) => format!("{} {}", message, hint),
+        None => message,
+    }
+}
+
 #[tauri::command]
 pub fn check_custom_sounds(app: AppHandle) -> CustomSounds {
     CustomSounds {
@@ -32,11 +55,49 @@ pub fn check_custom_sounds(app: AppHandle) -> CustomSounds {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AudioDevice {
+    /// Stable id to save as the selected device
+    pub id: String,
     pub index: String,
     pub name: String,
+    pub alias: Option<String>,
     pub is_default: bool,
 }
 
+impl AudioDevice {
+    fn default_entry() -> Self {
+        // Matches the value the settings UI uses for "no explicit device"
+        AudioDevice {
+            id: "Default".to_string(),
+            index: "default".to_string(),
+            name: "Default".to_string(),
+            alias: None,
+            is_default: true,
+        }
+    }
+
+    fn from_cpal(device: CpalDeviceInfo, aliases: &HashMap<String, String>) -> Self {
+        AudioDevice {
+            alias: aliases.get(&device.id).cloned(),
+            id: device.id,
+            index: device.index,
+            name: device.name,
+            is_default: false, // The explicit default is handled separately
+        }
+    }
+}
+
+/// Settings saved before device ids existed hold a display name; report the
+/// matching device's id so the selectors show it as selected
+fn saved_device_id(saved: Option<String>, devices: Option<Vec<CpalDeviceInfo>>) -> String {
+    let Some(saved) = saved else {
+        return "default".to_string();
+    };
+    devices
+        .and_then(|devices| find_device(devices, &saved))
+        .map(|device| device.id)
+        .unwrap_or(saved)
+}
+
 #[tauri::command]
 pub fn update_microphone_mode(app: AppHandle, always_on: bool) -> Result<(), String> {
     // Update settings
@@ -59,7 +120,49 @@ pub fn update_microphone_mode(app: AppHandle, always_on: bool) -> Result<(), Str
     };
 
     rm.update_mode(new_mode)
-        .map_err(|e| format!("Failed to update microphone mode: {}", e))
+        .map_err(|e| audio_error_message("Failed to update microphone mode", &e))
+}
+
+#[tauri::command]
+pub fn change_auto_caption_system_audio_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.auto_caption_system_audio = enabled;
+    write_settings(&app, settings);
+
+    if !enabled {
+        // The caption loop stops itself once it sees the setting
+        return Ok(());
+    }
+    match app.try_state::<Arc<AudioRecordingManager>>() {
+        Some(rm) => rm
+            .start_auto_caption()
+            .map_err(|e| audio_error_message("Failed to start captioning", &e)),
+        None => {
+            warn!("Recording manager not available - captioning starts on next launch");
+            Ok(())
+        }
+    }
+}
+
+#[tauri::command]
+pub fn change_dual_channel_captions_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.dual_channel_captions = enabled;
+    write_settings(&app, settings);
+
+    let Some(rm) = app.try_state::<Arc<AudioRecordingManager>>() else {
+        return Ok(());
+    };
+    if enabled {
+        rm.start_dual_channel_mic()
+            .map_err(|e| audio_error_message("Failed to open microphone channel", &e))
+    } else {
+        rm.stop_dual_channel_mic();
+        Ok(())
+    }
 }
 
 #[tauri::command]
@@ -69,21 +172,17 @@ pub fn get_microphone_mode(app: AppHandle) -> Result<bool, String> {
 }
 
 #[tauri::command]
-pub fn get_available_microphones() -> Result<Vec<AudioDevice>, String> {
+pub fn get_available_microphones(app: AppHandle) -> Result<Vec<AudioDevice>, String> {
     let devices =
         list_input_devices().map_err(|e| format!("Failed to list audio devices: {}", e))?;
+    let aliases = get_settings(&app).device_aliases;
 
-    let mut result = vec![AudioDevice {
-        index: "default".to_string(),
-        name: "Default".to_string(),
-        is_default: true,
-    }];
-
-    result.extend(devices.into_iter().map(|d| AudioDevice {
-        index: d.index,
-        name: d.name,
-        is_default: false, // The explicit default is handled separately
-    }));
+    let mut result = vec![AudioDevice::default_entry()];
+    result.extend(
+        devices
+            .into_iter()
+            .map(|d| AudioDevice::from_cpal(d, &aliases)),
+    );
 
     Ok(result)
 }
@@ -107,7 +206,7 @@ pub fn set_selected_microphone(app: AppHandle, device_name: String) -> Result<()
         }
     };
     rm.update_selected_device()
-        .map_err(|e| format!("Failed to update selected device: {}", e))?;
+        .map_err(|e| audio_error_message("Failed to update selected device", &e))?;
 
     Ok(())
 }
@@ -115,31 +214,51 @@ pub fn set_selected_microphone(app: AppHandle, device_name: String) -> Result<()
 #[tauri::command]
 pub fn get_selected_microphone(app: AppHandle) -> Result<String, String> {
     let settings = get_settings(&app);
-    Ok(settings
-        .selected_microphone
-        .unwrap_or_else(|| "default".to_string()))
+    Ok(saved_device_id(
+        settings.selected_microphone,
+        list_input_devices().ok(),
+    ))
 }
 
 #[tauri::command]
-pub fn get_available_output_devices() -> Result<Vec<AudioDevice>, String> {
+pub fn get_available_output_devices(app: AppHandle) -> Result<Vec<AudioDevice>, String> {
     let devices =
         list_output_devices().map_err(|e| format!("Failed to list output devices: {}", e))?;
+    let aliases = get_settings(&app).device_aliases;
 
-    let mut result = vec![AudioDevice {
-        index: "default".to_string(),
-        name: "Default".to_string(),
-        is_default: true,
-    }];
-
-    result.extend(devices.into_iter().map(|d| AudioDevice {
-        index: d.index,
-        name: d.name,
-        is_default: false, // The explicit default is handled separately
-    }));
+    let mut result = vec![AudioDevice::default_entry()];
+    result.extend(
+        devices
+            .into_iter()
+            .map(|d| AudioDevice::from_cpal(d, &aliases)),
+    );
 
     Ok(result)
 }
 
+/// Give a device a friendly name; an empty alias removes it
+#[tauri::command]
+pub fn set_device_alias(
+    app: AppHandle,
+    device_id: String,
+    alias: Option<String>,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    let alias = alias
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty());
+    match alias {
+        Some(alias) => {
+            settings.device_aliases.insert(device_id, alias);
+        }
+        None => {
+            settings.device_aliases.remove(&device_id);
+        }
+    }
+    write_settings(&app, settings);
+    Ok(())
+}
+
 #[tauri::command]
 pub fn set_selected_output_device(app: AppHandle, device_name: String) -> Result<(), String> {
     let mut settings = get_settings(&app);
@@ -155,9 +274,102 @@ pub fn set_selected_output_device(app: AppHandle, device_name: String) -> Result
 #[tauri::command]
 pub fn get_selected_output_device(app: AppHandle) -> Result<String, String> {
     let settings = get_settings(&app);
-    Ok(settings
-        .selected_output_device
-        .unwrap_or_else(|| "default".to_string()))
+    Ok(saved_device_id(
+        settings.selected_output_device,
+        list_output_devices().ok(),
+    ))
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceKind {
+    Input,
+    Output,
+}
+
+#[derive(Serialize)]
+pub struct DeviceCapabilitiesReport {
+    #[serde(flatten)]
+    pub capabilities: DeviceCapabilities,
+    /// Problems the current settings would run into on this device
+    pub warnings: Vec<String>,
+}
+
+/// Sample rates, channel counts and formats `device_id` supports, with
+/// warnings for settings it can't satisfy
+#[tauri::command]
+pub fn get_device_capabilities(
+    app: AppHandle,
+    device_id: String,
+    kind: DeviceKind,
+) -> Result<DeviceCapabilitiesReport, String> {
+    let device = if device_id.eq_ignore_ascii_case("default") {
+        let host = crate::audio_toolkit::get_cpal_host();
+        match kind {
+            DeviceKind::Input => host.default_input_device(),
+            DeviceKind::Output => host.default_output_device(),
+        }
+    } else {
+        let devices = match kind {
+            DeviceKind::Input => list_input_devices(),
+            DeviceKind::Output => list_output_devices(),
+        }
+        .map_err(|e| format!("Failed to list audio devices: {}", e))?;
+        find_device(devices, &device_id).map(|d| d.device)
+    }
+    .ok_or_else(|| format!("Audio device not found: {}", device_id))?;
+
+    let capabilities = match kind {
+        DeviceKind::Input => input_capabilities(&device),
+        DeviceKind::Output => output_capabilities(&device),
+    }
+    .map_err(|e| format!("Failed to read device capabilities: {}", e))?;
+
+    let settings = get_settings(&app);
+    let downmix = match kind {
+        DeviceKind::Input => settings.microphone_downmix.downmix(),
+        DeviceKind::Output => settings.system_audio_downmix.downmix(),
+    };
+    let warnings = capability_warnings(&capabilities, kind, downmix);
+
+    Ok(DeviceCapabilitiesReport {
+        capabilities,
+        warnings,
+    })
+}
+
+fn capability_warnings(
+    capabilities: &DeviceCapabilities,
+    kind: DeviceKind,
+    downmix: Downmix,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if capabilities.ranges.is_empty() {
+        warnings.push("The device reports no supported formats.".to_string());
+        return warnings;
+    }
+    if matches!(kind, DeviceKind::Input)
+        && !RECORDABLE_FORMATS
+            .into_iter()
+            .any(|format| capabilities.supports_format(format))
+    {
+        warnings.push(format!(
+            "Handy can't record from this device's formats ({}).",
+            capabilities.sample_formats.join(", ")
+        ));
+    }
+    let max_channels = capabilities.max_channels() as usize;
+    if let Downmix::Channel(index) = downmix {
+        if index >= max_channels {
+            warnings.push(format!(
+                "Channel {} is selected but the device has {} channel{}; all channels will be averaged.",
+                index + 1,
+                max_channels,
+                if max_channels == 1 { "" } else { "s" }
+            ));
+        }
+    }
+    warnings
 }
 
 #[tauri::command]
@@ -188,9 +400,10 @@ pub fn set_clamshell_microphone(app: AppHandle, device_name: String) -> Result<(
 #[tauri::command]
 pub fn get_clamshell_microphone(app: AppHandle) -> Result<String, String> {
     let settings = get_settings(&app);
-    Ok(settings
-        .clamshell_microphone
-        .unwrap_or_else(|| "default".to_string()))
+    Ok(saved_device_id(
+        settings.clamshell_microphone,
+        list_input_devices().ok(),
+    ))
 }
 
 #[tauri::command]
@@ -214,13 +427,10 @@ pub async fn set_audio_source(app: AppHandle, source: String) -> Result<(), Stri
         }
     };
     let rm_clone = Arc::clone(&rm);
-    let app_clone = app.clone();
-    
+
     tauri::async_runtime::spawn(async move {
         if let Err(e) = rm_clone.update_selected_device() {
-            log::error!("Failed to update audio source: {}", e);
-            // Emit error event to frontend
-            let _ = app_clone.emit("log-update", format!("❌ [AudioSource] Failed to update: {}", e));
+            log::error!("❌ [AudioSource] Failed to update audio source: {}", e);
         }
     });
 
@@ -237,11 +447,326 @@ pub fn get_audio_source(app: AppHandle) -> Result<String, String> {
     })
 }
 
+/// Change how `source`'s channels are reduced to mono. An open stream is
+/// reopened so the change is heard right away.
+#[tauri::command]
+pub async fn set_channel_downmix(
+    app: AppHandle,
+    source: AudioSource,
+    downmix: ChannelDownmix,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    match source {
+        AudioSource::Microphone => settings.microphone_downmix = downmix,
+        AudioSource::SystemAudio => settings.system_audio_downmix = downmix,
+    }
+    write_settings(&app, settings);
+
+    let Some(rm) = app.try_state::<Arc<AudioRecordingManager>>() else {
+        return Ok(());
+    };
+    let rm = Arc::clone(&rm);
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = rm.update_selected_device() {
+            log::error!("Failed to reopen audio after a downmix change: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// Boost or cut `device_id`'s input by `gain_db`, optionally with a soft
+/// limiter; unity gain without the limiter removes the setting. The open
+/// microphone is reopened so the change is heard right away.
+#[tauri::command]
+pub async fn set_input_gain(
+    app: AppHandle,
+    device_id: String,
+    gain_db: f32,
+    limiter: bool,
+) -> Result<(), String> {
+    if !gain_db.is_finite() {
+        return Err(format!("Invalid gain: {}", gain_db));
+    }
+    let gain_db = gain_db.clamp(MIN_GAIN_DB, MAX_GAIN_DB);
+    let device_id = if device_id.eq_ignore_ascii_case("default") {
+        "default".to_string()
+    } else {
+        device_id
+    };
+
+    let mut settings = get_settings(&app);
+    if gain_db == 0.0 && !limiter {
+        settings.input_gains.remove(&device_id);
+    } else {
+        settings
+            .input_gains
+            .insert(device_id, InputGain { gain_db, limiter });
+    }
+    write_settings(&app, settings);
+
+    let Some(rm) = app.try_state::<Arc<AudioRecordingManager>>() else {
+        return Ok(());
+    };
+    let rm = Arc::clone(&rm);
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = rm.update_selected_device() {
+            log::error!("Failed to reopen the microphone after a gain change: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// Emit the microphone's spectrum in `bands` bands as `audio-spectrum` events
+/// until `unsubscribe_spectrum`. Calling it again changes the band count.
+#[tauri::command]
+pub fn subscribe_spectrum(bands: usize) -> Result<(), String> {
+    if bands == 0 || bands > MAX_SPECTRUM_BANDS {
+        return Err(format!(
+            "Spectrum bands must be between 1 and {}",
+            MAX_SPECTRUM_BANDS
+        ));
+    }
+    request_spectrum(bands);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unsubscribe_spectrum() {
+    request_spectrum(0);
+}
+
+/// Change how microphones and loopback capture are opened on Windows. The
+/// open stream is reopened so the change takes effect right away.
+#[tauri::command]
+pub async fn set_wasapi_options(app: AppHandle, options: WasapiOptions) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.wasapi = WasapiOptions {
+        buffer_ms: options.buffer_ms.min(wasapi::MAX_BUFFER_MS),
+        ..options
+    };
+    write_settings(&app, settings);
+
+    let Some(rm) = app.try_state::<Arc<AudioRecordingManager>>() else {
+        return Ok(());
+    };
+    let rm = Arc::clone(&rm);
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = rm.update_selected_device() {
+            log::error!("Failed to reopen audio after a WASAPI change: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// Switch the Linux sound server devices are listed and opened through, and
+/// reopen the microphone on it
+#[tauri::command]
+pub async fn set_linux_audio_host(app: AppHandle, host: LinuxAudioHost) -> Result<(), String> {
+    utils::set_linux_audio_host(host);
+
+    let mut settings = get_settings(&app);
+    settings.linux_audio_host = host;
+    write_settings(&app, settings);
+
+    let Some(rm) = app.try_state::<Arc<AudioRecordingManager>>() else {
+        return Ok(());
+    };
+    let rm = Arc::clone(&rm);
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = rm.update_selected_device() {
+            log::error!("Failed to reopen audio on the new host: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// Create a multi-output device playing through the current speakers and
+/// into BlackHole and make it the system output, or with `enabled` false
+/// switch back to the speakers and remove it. Returns the speakers' name
+/// when enabling.
+#[tauri::command]
+pub fn setup_blackhole_routing(enabled: bool) -> Result<Option<String>, String> {
+    #[cfg(target_os = "macos")]
+    {
+        use crate::audio_toolkit::blackhole_routing;
+
+        if enabled {
+            blackhole_routing::setup_blackhole_routing()
+                .map(Some)
+                .map_err(|e| format!("Failed to set up BlackHole routing: {}", e))
+        } else {
+            blackhole_routing::teardown_blackhole_routing()
+                .map(|()| None)
+                .map_err(|e| format!("Failed to remove BlackHole routing: {}", e))
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = enabled;
+        Err("BlackHole routing is only available on macOS".to_string())
+    }
+}
+
+/// Whether BlackHole is installed, its channel variant, and whether the
+/// system output plays into it
+#[tauri::command]
+pub fn get_blackhole_status() -> BlackHoleStatus {
+    blackhole::detect_blackhole()
+}
+
+/// What `install_blackhole` opened
+#[derive(Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BlackHoleInstall {
+    /// A downloaded installer package, opened in Installer
+    Installer { path: String },
+    /// The download page, as no package was found
+    Download { url: String },
+}
+
+/// Open the newest BlackHole installer package in the Downloads folder, of
+/// the `channels` variant if given, or the download page if there is none
+#[tauri::command]
+pub fn install_blackhole(
+    app: AppHandle,
+    channels: Option<u16>,
+) -> Result<BlackHoleInstall, String> {
+    #[cfg(target_os = "macos")]
+    {
+        use tauri_plugin_opener::OpenerExt;
+
+        let installer = app
+            .path()
+            .download_dir()
+            .ok()
+            .and_then(|dir| blackhole::find_installer(&dir, channels));
+        match installer {
+            Some(path) => {
+                let path = path.to_string_lossy().into_owned();
+                app.opener()
+                    .open_path(path.clone(), None::<String>)
+                    .map_err(|e| format!("Failed to open the BlackHole installer: {}", e))?;
+                Ok(BlackHoleInstall::Installer { path })
+            }
+            None => {
+                app.opener()
+                    .open_url(blackhole::BLACKHOLE_DOWNLOAD_URL, None::<String>)
+                    .map_err(|e| format!("Failed to open the BlackHole download page: {}", e))?;
+                Ok(BlackHoleInstall::Download {
+                    url: blackhole::BLACKHOLE_DOWNLOAD_URL.to_string(),
+                })
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, channels);
+        Err("BlackHole is only available on macOS".to_string())
+    }
+}
+
+/// Play a test tone through the selected output device and check that it
+/// arrives at the BlackHole input
+#[tauri::command]
+pub async fn verify_blackhole_routing(app: AppHandle) -> Result<RoutingCheck, String> {
+    let output = get_settings(&app)
+        .selected_output_device
+        .and_then(|name| find_device(list_output_devices().ok()?, &name))
+        .map(|info| info.device);
+    tauri::async_runtime::spawn_blocking(move || blackhole::verify_routing(output))
+        .await
+        .map_err(|e| format!("Routing check failed: {}", e))?
+        .map_err(|e| audio_error_message("Routing check failed", &anyhow::Error::from(e)))
+}
+
+// How long the content-sharing picker waits for the user
+#[cfg(target_os = "macos")]
+const PICKER_TIMEOUT: Duration = Duration::from_secs(300);
+
+// Reopen the audio stream so system audio capture starts over with the
+// current capture target
+fn reopen_system_audio(app: &AppHandle) {
+    if get_settings(app).audio_source != Some(AudioSource::SystemAudio) {
+        return;
+    }
+    let Some(rm) = app.try_state::<Arc<AudioRecordingManager>>() else {
+        return;
+    };
+    let rm = Arc::clone(&rm);
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = rm.update_selected_device() {
+            log::error!("Failed to reopen system audio after a target change: {}", e);
+        }
+    });
+}
+
+/// Show macOS's content-sharing picker and capture audio from the display,
+/// window or app the user picks from now on. Returns None if the picker was
+/// cancelled, leaving the previous target in place.
+#[tauri::command]
+pub async fn pick_capture_target(app: AppHandle) -> Result<Option<CaptureTarget>, String> {
+    #[cfg(target_os = "macos")]
+    {
+        use crate::audio_toolkit::screencapturekit::picker::{self, PickerOutcome};
+
+        if !picker::is_supported() {
+            return Err("The content picker needs macOS 14 or later".to_string());
+        }
+        let outcome = picker::begin();
+        app.run_on_main_thread(picker::present)
+            .map_err(|e| format!("Failed to show the content picker: {}", e))?;
+        let outcome =
+            tauri::async_runtime::spawn_blocking(move || outcome.recv_timeout(PICKER_TIMEOUT))
+                .await
+                .map_err(|e| format!("Content picker failed: {}", e))?
+                .map_err(|_| "No selection was made in the content picker".to_string())?;
+
+        match outcome {
+            PickerOutcome::Picked(target) => {
+                let mut settings = get_settings(&app);
+                settings.screen_capture_target = Some(target.clone());
+                write_settings(&app, settings);
+                reopen_system_audio(&app);
+                Ok(Some(target))
+            }
+            PickerOutcome::Cancelled => Ok(None),
+            PickerOutcome::Failed(message) => Err(message),
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = app;
+        Err("The content picker is only available on macOS".to_string())
+    }
+}
+
+/// Forget the picked capture target and capture all system audio again
+#[tauri::command]
+pub fn clear_capture_target(app: AppHandle) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    if settings.screen_capture_target.take().is_some() {
+        write_settings(&app, settings);
+        reopen_system_audio(&app);
+    }
+    Ok(())
+}
+
+// Capture counts as receiving audio if a callback arrived this recently
+const AUDIO_ACTIVE_WINDOW: Duration = Duration::from_secs(1);
+
 #[derive(Serialize)]
 pub struct SystemAudioStatus {
     pub permission: String, // "unknown" | "granted" | "denied"
     pub capture: String,    // "unknown" | "active" | "waiting" | "error"
     pub audio_detection: String, // "unknown" | "active" | "waiting"
+    pub dropped_samples: u64, // samples overwritten because the consumer fell behind
+    pub buffered_ms: u64,        // audio waiting for the caption loop
+    pub last_callback_age_ms: Option<u64>, // None until the backend delivers audio
+    pub sample_rate: u32, // rate of the captured device
+    pub channels: u16,    // downmixed to mono for the caption loop
 }
 
 #[tauri::command]
@@ -254,10 +779,19 @@ pub fn get_system_audio_status(app: AppHandle) -> Result<SystemAudioStatus, Stri
                 permission: "unknown".to_string(),
                 capture: "not_initialized".to_string(),
                 audio_detection: "unknown".to_string(),
+                dropped_samples: 0,
+                buffered_ms: 0,
+                last_callback_age_ms: None,
+                sample_rate: 0,
+                channels: 0,
             });
         }
     };
-    let (is_open, has_audio) = rm.get_system_audio_status();
+    let (is_open, status) = rm.get_system_audio_status();
+    // Backends deliver audio (even silence) continuously while capture works
+    let has_audio = status
+        .last_callback_age
+        .is_some_and(|age| age < AUDIO_ACTIVE_WINDOW);
     
     // Check if permission was denied by checking if capture failed to start
     // If is_open is false, it could mean permission denied or just not started
@@ -297,9 +831,28 @@ pub fn get_system_audio_status(app: AppHandle) -> Result<SystemAudioStatus, Stri
         permission: permission_status.to_string(),
         capture: capture_status.to_string(),
         audio_detection: audio_detection_status.to_string(),
+        dropped_samples: status.dropped_samples,
+        buffered_ms: status.buffered.as_millis() as u64,
+        last_callback_age_ms: status.last_callback_age.map(|age| age.as_millis() as u64),
+        sample_rate: status.format.sample_rate,
+        channels: status.format.channels,
     })
 }
 
+/// Capture the selected source for a few seconds and report how the pipeline
+/// handled it, as a pass/fail checklist
+#[tauri::command]
+pub async fn run_audio_selftest(app: AppHandle) -> Result<AudioSelfTest, String> {
+    let rm = match app.try_state::<Arc<AudioRecordingManager>>() {
+        Some(manager) => Arc::clone(&manager),
+        None => return Err("Audio system not initialized".to_string()),
+    };
+    tauri::async_runtime::spawn_blocking(move || rm.run_selftest())
+        .await
+        .map_err(|e| format!("Self-test task failed: {}", e))?
+        .map_err(|e| audio_error_message("Self-test failed", &e))
+}
+
 #[tauri::command]
 pub fn check_audio_initialization_status(app: AppHandle) -> Result<String, String> {
     // Check if recording manager exists
@@ -323,6 +876,28 @@ pub fn check_audio_initialization_status(app: AppHandle) -> Result<String, Strin
     }
 }
 
+/// Pause the microphone recording in progress; the `recording-state` event
+/// reports the change
+#[tauri::command]
+pub fn pause_recording(app: AppHandle) -> Result<(), String> {
+    let rm = app.state::<Arc<AudioRecordingManager>>();
+    if rm.pause_recording() {
+        Ok(())
+    } else {
+        Err("No microphone recording to pause".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn resume_recording(app: AppHandle) -> Result<(), String> {
+    let rm = app.state::<Arc<AudioRecordingManager>>();
+    if rm.resume_recording() {
+        Ok(())
+    } else {
+        Err("No paused recording to resume".to_string())
+    }
+}
+
 #[tauri::command]
 pub fn restart_audio_stream(app: AppHandle) -> Result<(), String> {
     log::info!("🔄 Attempting to restart audio stream after setup...");
@@ -351,7 +926,7 @@ pub fn restart_audio_stream(app: AppHandle) -> Result<(), String> {
         },
         Err(e) => {
             log::error!("❌ Failed to restart audio stream: {}", e);
-            Err(format!("Failed to start audio stream: {}", e))
+            Err(audio_error_message("Failed to start audio stream", &e))
         }
     }
 }
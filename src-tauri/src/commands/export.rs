@@ -0,0 +1,64 @@
+use crate::caption::analytics::SessionAnalytics;
+use crate::caption::session::{self, CaptionSession};
+use crate::history_export::{self, HistoryExportFormat};
+use crate::managers::history::HistoryManager;
+use std::fs;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+fn session_or_err() -> Result<CaptionSession, String> {
+    match session::current_session() {
+        Some(session) if !session.cues.is_empty() => Ok(session),
+        _ => Err("No live caption session to export".to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn get_caption_session() -> Option<CaptionSession> {
+    session::current_session()
+}
+
+/// Speaking rate and filler words for the current or last caption session
+#[tauri::command]
+pub fn get_session_analytics() -> Option<SessionAnalytics> {
+    session::session_analytics()
+}
+
+#[tauri::command]
+pub fn export_session_srt(path: String) -> Result<(), String> {
+    let session = session_or_err()?;
+    fs::write(&path, session::to_srt(&session.cues))
+        .map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+#[tauri::command]
+pub fn export_session_vtt(path: String) -> Result<(), String> {
+    let session = session_or_err()?;
+    fs::write(&path, session::to_vtt(&session.cues))
+        .map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// Write history entries between `from` and `to` (unix seconds, inclusive) to
+/// `path`. Returns how many entries were written.
+#[tauri::command]
+pub fn export_history(
+    app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    path: String,
+    format: HistoryExportFormat,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<usize, String> {
+    let entries = history_manager
+        .get_entries_between(from, to)
+        .map_err(|e| e.to_string())?;
+    if entries.is_empty() {
+        return Err("No history entries in that range".to_string());
+    }
+
+    let version = app.package_info().version.to_string();
+    let contents = history_export::render(&entries, format, &chrono::Local, &version, from, to)
+        .map_err(|e| e.to_string())?;
+    fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(entries.len())
+}
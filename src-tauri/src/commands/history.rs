@@ -1,5 +1,10 @@
-use crate::managers::history::{HistoryEntry, HistoryManager};
+use crate::managers::history::{
+    HistoryEntry, HistoryManager, HistoryPage, HistoryRevision, HistorySession, UsageStats,
+};
+use crate::managers::playback::PlaybackManager;
+use crate::settings::HistoryRetention;
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{AppHandle, State};
 
 #[tauri::command]
@@ -13,6 +18,20 @@ pub async fn get_history_entries(
         .map_err(|e| e.to_string())
 }
 
+/// Newest-first page of entries matching `query`; an empty query matches all
+#[tauri::command]
+pub async fn search_history_entries(
+    _app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    query: String,
+    limit: usize,
+    offset: usize,
+) -> Result<HistoryPage, String> {
+    history_manager
+        .search_entries(&query, limit, offset)
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn toggle_history_entry_saved(
     _app: AppHandle,
@@ -49,42 +68,122 @@ pub async fn delete_history_entry(
         .map_err(|e| e.to_string())
 }
 
+/// Correct an entry's text; the text it replaces is kept as a revision
 #[tauri::command]
-pub async fn update_history_limit(
-    app: AppHandle,
+pub async fn update_history_text(
+    _app: AppHandle,
     history_manager: State<'_, Arc<HistoryManager>>,
-    limit: usize,
+    id: i64,
+    text: String,
 ) -> Result<(), String> {
-    let mut settings = crate::settings::get_settings(&app);
-    settings.history_limit = limit;
-    crate::settings::write_settings(&app, settings);
-
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Transcription text cannot be empty".to_string());
+    }
     history_manager
-        .cleanup_old_entries()
-        .map_err(|e| e.to_string())?;
+        .update_transcription_text(id, text)
+        .map_err(|e| e.to_string())
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn get_history_revisions(
+    _app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    id: i64,
+) -> Result<Vec<HistoryRevision>, String> {
+    history_manager.get_revisions(id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn update_recording_retention_period(
-    app: AppHandle,
+pub async fn get_usage_stats(
+    _app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+) -> Result<UsageStats, String> {
+    history_manager.usage_stats().map_err(|e| e.to_string())
+}
+
+/// Play an entry's recording on the selected output device, or resume it if
+/// it's paused. Progress arrives as `history-playback` events.
+#[tauri::command]
+pub async fn play_history_audio(
+    _app: AppHandle,
     history_manager: State<'_, Arc<HistoryManager>>,
-    period: String,
+    playback_manager: State<'_, Arc<PlaybackManager>>,
+    id: i64,
+) -> Result<(), String> {
+    let entry = history_manager
+        .get_entry_by_id(id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("History entry {} not found", id))?;
+    playback_manager
+        .play(id, &history_manager.get_audio_file_path(&entry.file_name))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pause_history_audio(
+    playback_manager: State<'_, Arc<PlaybackManager>>,
+) -> Result<(), String> {
+    playback_manager.pause().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn seek_history_audio(
+    playback_manager: State<'_, Arc<PlaybackManager>>,
+    position_ms: u64,
+) -> Result<(), String> {
+    playback_manager
+        .seek(Duration::from_millis(position_ms))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn stop_history_audio(
+    playback_manager: State<'_, Arc<PlaybackManager>>,
 ) -> Result<(), String> {
-    use crate::settings::RecordingRetentionPeriod;
+    playback_manager.stop();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_history_sessions(
+    _app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+) -> Result<Vec<HistorySession>, String> {
+    history_manager.get_sessions().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_history_session_entries(
+    _app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    session_id: i64,
+) -> Result<Vec<HistoryEntry>, String> {
+    history_manager
+        .get_session_entries(session_id)
+        .map_err(|e| e.to_string())
+}
 
-    let retention_period = match period.as_str() {
-        "never" => RecordingRetentionPeriod::Never,
-        "preserve_limit" => RecordingRetentionPeriod::PreserveLimit,
-        "days3" => RecordingRetentionPeriod::Days3,
-        "weeks2" => RecordingRetentionPeriod::Weeks2,
-        "months3" => RecordingRetentionPeriod::Months3,
-        _ => return Err(format!("Invalid retention period: {}", period)),
-    };
+#[tauri::command]
+pub async fn delete_history_session(
+    _app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    session_id: i64,
+) -> Result<(), String> {
+    history_manager
+        .delete_session(session_id)
+        .map_err(|e| e.to_string())
+}
 
+#[tauri::command]
+pub async fn set_history_retention(
+    app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    retention: HistoryRetention,
+) -> Result<(), String> {
     let mut settings = crate::settings::get_settings(&app);
-    settings.recording_retention_period = retention_period;
+    settings.history_retention = retention;
     crate::settings::write_settings(&app, settings);
 
     history_manager
@@ -93,3 +192,15 @@ pub async fn update_recording_retention_period(
 
     Ok(())
 }
+
+/// Delete all history now; saved entries are kept unless `include_saved`
+#[tauri::command]
+pub async fn purge_history(
+    _app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    include_saved: bool,
+) -> Result<usize, String> {
+    history_manager
+        .purge(include_saved)
+        .map_err(|e| e.to_string())
+}
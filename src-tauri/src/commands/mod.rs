@@ -1,10 +1,23 @@
 pub mod audio;
+pub mod export;
 pub mod history;
 pub mod models;
 pub mod transcription;
 pub mod permissions;
-
-use crate::{settings, utils::cancel_current_operation};
+pub mod platform;
+pub mod power;
+
+use crate::{
+    audio_toolkit::system_audio::CaptureStatus,
+    events::TranscriptionQueueDepth,
+    log_buffer::{self, LogEntry},
+    managers::{audio::AudioRecordingManager, transcription::TranscriptionManager},
+    metrics::{self, CaptureStream, GlitchMetrics, StageMetrics},
+    settings,
+    utils::{cancel_current_operation, retake_last_recording},
+};
+use serde::Serialize;
+use std::sync::Arc;
 use tauri::{AppHandle, Manager};
 use tauri_plugin_log::LogLevel;
 use tauri_plugin_opener::OpenerExt;
@@ -14,6 +27,15 @@ pub fn cancel_operation(app: AppHandle) {
     cancel_current_operation(&app);
 }
 
+#[tauri::command]
+pub fn retake_recording(app: AppHandle) -> Result<(), String> {
+    if retake_last_recording(&app) {
+        Ok(())
+    } else {
+        Err("No transcription is waiting to be pasted".to_string())
+    }
+}
+
 #[tauri::command]
 pub fn get_app_dir_path(app: AppHandle) -> Result<String, String> {
     let app_data_dir = app
@@ -50,6 +72,89 @@ pub fn set_log_level(app: AppHandle, level: LogLevel) -> Result<(), String> {
     Ok(())
 }
 
+/// The newest `limit` log entries (200 by default), oldest first, at `level`
+/// or more severe and from modules starting with `module`
+#[tauri::command]
+pub fn get_recent_logs(
+    limit: Option<usize>,
+    level: Option<LogLevel>,
+    module: Option<String>,
+) -> Vec<LogEntry> {
+    log_buffer::recent(
+        limit.unwrap_or(200),
+        level.map(Into::into),
+        module.as_deref(),
+    )
+}
+
+/// Emit every new log entry as a `log-entry` event until the matching
+/// `unsubscribe_logs`
+#[tauri::command]
+pub fn subscribe_logs() {
+    log_buffer::subscribe();
+}
+
+#[tauri::command]
+pub fn unsubscribe_logs() {
+    log_buffer::unsubscribe();
+}
+
+#[tauri::command]
+pub fn set_debug_audio_logging(app: AppHandle, enabled: bool) -> Result<(), String> {
+    // Capture callbacks check this flag on every buffer, so it takes effect immediately
+    crate::audio_toolkit::debug_log::set_audio_debug_logging(enabled);
+
+    let mut settings = settings::get_settings(&app);
+    settings.debug_audio_logging = enabled;
+    settings::write_settings(&app, settings);
+
+    Ok(())
+}
+
+/// Pipeline timings and audio health for the diagnostics panel
+#[derive(Clone, Debug, Serialize)]
+pub struct PerformanceMetrics {
+    pub stages: Vec<StageMetrics>,
+    pub microphone_dropped_samples: u64,
+    /// Unread system audio, 0 while system capture is closed
+    pub system_audio_buffered_ms: u64,
+    pub system_audio_dropped_samples: u64,
+    pub microphone_glitches: GlitchMetrics,
+    pub system_audio_glitches: GlitchMetrics,
+    pub transcription_queue: TranscriptionQueueDepth,
+}
+
+#[tauri::command]
+pub fn get_performance_metrics(app: AppHandle) -> PerformanceMetrics {
+    let (microphone_dropped_samples, system_audio) =
+        match app.try_state::<Arc<AudioRecordingManager>>() {
+            Some(rm) => (
+                rm.microphone_dropped_samples(),
+                rm.get_system_audio_status().1,
+            ),
+            None => (0, CaptureStatus::default()),
+        };
+    let transcription_queue = app
+        .try_state::<Arc<TranscriptionManager>>()
+        .map(|tm| tm.queue_depth())
+        .unwrap_or_default();
+
+    PerformanceMetrics {
+        stages: metrics::snapshot(),
+        microphone_dropped_samples,
+        system_audio_buffered_ms: system_audio.buffered.as_millis() as u64,
+        system_audio_dropped_samples: system_audio.dropped_samples,
+        microphone_glitches: metrics::glitches(CaptureStream::Microphone),
+        system_audio_glitches: metrics::glitches(CaptureStream::SystemAudio),
+        transcription_queue,
+    }
+}
+
+#[tauri::command]
+pub fn get_cloud_usage(app: AppHandle) -> crate::cloud_quota::CloudUsage {
+    crate::cloud_quota::get_usage(&app)
+}
+
 #[tauri::command]
 pub fn open_recordings_folder(app: AppHandle) -> Result<(), String> {
     let app_data_dir = app
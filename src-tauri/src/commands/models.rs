@@ -1,8 +1,10 @@
+use crate::audio_toolkit::decode_audio_file;
 use crate::managers::model::{ModelInfo, ModelManager};
-use crate::managers::transcription::TranscriptionManager;
-use crate::settings::{get_settings, write_settings};
+use crate::managers::transcription::{ModelBenchmark, TranscriptionManager};
+use crate::settings::{get_settings, write_settings, ModelSelection};
 use std::sync::Arc;
-use tauri::{AppHandle, State};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager, State};
 
 #[tauri::command]
 pub async fn get_available_models(
@@ -43,6 +45,19 @@ pub async fn delete_model(
         .map_err(|e| e.to_string())
 }
 
+/// Re-hash a downloaded model; `false` means its files changed since install
+#[tauri::command]
+pub async fn verify_model(
+    model_manager: State<'_, Arc<ModelManager>>,
+    model_id: String,
+) -> Result<bool, String> {
+    let model_manager = Arc::clone(&model_manager);
+    tauri::async_runtime::spawn_blocking(move || model_manager.verify_model(&model_id))
+        .await
+        .map_err(|e| format!("Verification task failed: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn set_active_model(
     app_handle: AppHandle,
@@ -125,7 +140,86 @@ pub async fn cancel_download(
 }
 
 #[tauri::command]
-pub async fn get_recommended_first_model() -> Result<String, String> {
-    // Recommend Parakeet V3 model for first-time users - fastest and most accurate
-    Ok("parakeet-tdt-0.6b-v3".to_string())
+pub async fn get_recommended_first_model(
+    model_manager: State<'_, Arc<ModelManager>>,
+) -> Result<String, String> {
+    // Parakeet V3 is fast and accurate anywhere, for when nothing else fits
+    Ok(model_manager
+        .recommended_model(false)
+        .map(|model| model.id)
+        .unwrap_or_else(|| "parakeet-tdt-0.6b-v3".to_string()))
+}
+
+/// Switch between picking models by hand and following the hardware
+/// recommendation. Returns the model selected afterwards.
+#[tauri::command]
+pub async fn set_model_selection(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    selection: ModelSelection,
+) -> Result<String, String> {
+    let mut settings = get_settings(&app_handle);
+    settings.model_selection = selection;
+
+    if selection == ModelSelection::Recommended {
+        if let Some(recommended) = model_manager.recommended_model(true) {
+            if recommended.id != settings.selected_model {
+                transcription_manager
+                    .load_model(&recommended.id)
+                    .map_err(|e| e.to_string())?;
+                settings.selected_model = recommended.id;
+            }
+        }
+    }
+
+    let selected_model = settings.selected_model.clone();
+    write_settings(&app_handle, settings);
+    Ok(selected_model)
+}
+
+/// Time every downloaded model on the bundled sample clip
+#[tauri::command]
+pub async fn benchmark_models(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> Result<Vec<ModelBenchmark>, String> {
+    let clip_path = app_handle
+        .path()
+        .resolve("resources/benchmark_clip.wav", BaseDirectory::Resource)
+        .map_err(|e| format!("Failed to resolve benchmark clip: {}", e))?;
+    let model_manager = Arc::clone(&model_manager);
+    let transcription_manager = Arc::clone(&transcription_manager);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let clip = decode_audio_file(&clip_path)
+            .map_err(|e| format!("Failed to read benchmark clip: {}", e))?;
+
+        let mut models: Vec<ModelInfo> = model_manager
+            .get_available_models()
+            .into_iter()
+            .filter(|model| model.is_downloaded)
+            .collect();
+        models.sort_by_key(|model| model.size_mb);
+
+        let mut results = Vec::new();
+        for model in models {
+            match transcription_manager.benchmark_model(&model.id, clip.clone()) {
+                Ok(result) => {
+                    log::info!(
+                        "Benchmarked {}: RTF {:.2} (load {}ms)",
+                        model.id,
+                        result.real_time_factor,
+                        result.load_ms
+                    );
+                    results.push(result);
+                }
+                Err(e) => log::warn!("Failed to benchmark {}: {}", model.id, e),
+            }
+        }
+        Ok(results)
+    })
+    .await
+    .map_err(|e| format!("Benchmark task failed: {}", e))?
 }
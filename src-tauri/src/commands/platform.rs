@@ -0,0 +1,187 @@
+// What actually works on this platform and session, so the UI can hide or
+// annotate options instead of letting them fail at runtime
+
+use crate::settings;
+use serde::Serialize;
+use tauri::AppHandle;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FeatureSupport {
+    pub supported: bool,
+    /// Why the feature is unavailable, or what it needs to work
+    pub note: Option<String>,
+}
+
+impl FeatureSupport {
+    fn yes() -> Self {
+        Self {
+            supported: true,
+            note: None,
+        }
+    }
+
+    fn with_note(supported: bool, note: &str) -> Self {
+        Self {
+            supported,
+            note: Some(note.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PlatformCapabilities {
+    pub os: String,
+    /// Display server on Linux: "x11", "wayland" or "unknown"
+    pub session: Option<String>,
+    /// Sound server on Linux: "pipewire", "pulseaudio" or "alsa"
+    pub audio_server: Option<String>,
+    pub system_audio: FeatureSupport,
+    pub mute: FeatureSupport,
+    pub clamshell: FeatureSupport,
+    pub global_shortcuts: FeatureSupport,
+    /// Name of the mechanism used to insert text
+    pub paste_backend: String,
+    pub paste: FeatureSupport,
+}
+
+#[cfg(target_os = "linux")]
+fn linux_audio_server() -> &'static str {
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_default();
+    if runtime_dir.join("pipewire-0").exists() {
+        "pipewire"
+    } else if runtime_dir.join("pulse").join("native").exists() {
+        "pulseaudio"
+    } else {
+        "alsa"
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn capabilities(app: &AppHandle) -> PlatformCapabilities {
+    use crate::clipboard::linux::{command_exists, detect_session, resolve_backend, SessionType};
+    use crate::settings::LinuxTypingBackend;
+
+    let session = detect_session();
+    let audio_server = linux_audio_server();
+
+    let mute_tool = match audio_server {
+        "pipewire" => "wpctl",
+        "pulseaudio" => "pactl",
+        _ => "amixer",
+    };
+    let mute = if command_exists(mute_tool) {
+        FeatureSupport::yes()
+    } else {
+        FeatureSupport::with_note(false, &format!("Install {} to mute output", mute_tool))
+    };
+
+    let global_shortcuts = match session {
+        SessionType::Wayland => FeatureSupport::with_note(
+            false,
+            "Wayland does not allow global shortcuts; bind a compositor shortcut to `pkill -USR2 handy` instead",
+        ),
+        _ => FeatureSupport::yes(),
+    };
+
+    let preference = settings::get_settings(app).linux_typing_backend;
+    let (paste_backend, paste) = match resolve_backend(preference) {
+        None => ("enigo", FeatureSupport::yes()),
+        Some(LinuxTypingBackend::ClipboardOnly) => (
+            "clipboard_only",
+            FeatureSupport::with_note(
+                false,
+                "Install wtype or ydotool to type text; it is only copied to the clipboard",
+            ),
+        ),
+        Some(LinuxTypingBackend::Wtype) => ("wtype", tool_support("wtype")),
+        Some(LinuxTypingBackend::Ydotool) => ("ydotool", tool_support("ydotool")),
+        Some(LinuxTypingBackend::Xdotool) => ("xdotool", tool_support("xdotool")),
+        Some(LinuxTypingBackend::Auto) => ("enigo", FeatureSupport::yes()),
+    };
+
+    PlatformCapabilities {
+        os: "linux".to_string(),
+        session: Some(
+            match session {
+                SessionType::Wayland => "wayland",
+                SessionType::X11 => "x11",
+                SessionType::Unknown => "unknown",
+            }
+            .to_string(),
+        ),
+        audio_server: Some(audio_server.to_string()),
+        system_audio: FeatureSupport::with_note(
+            false,
+            "System audio capture is only available on macOS and Windows",
+        ),
+        mute,
+        clamshell: FeatureSupport::with_note(false, "Clamshell mode is macOS only"),
+        global_shortcuts,
+        paste_backend: paste_backend.to_string(),
+        paste,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn tool_support(binary: &str) -> FeatureSupport {
+    use crate::clipboard::linux::command_exists;
+
+    if command_exists(binary) {
+        FeatureSupport::yes()
+    } else {
+        FeatureSupport::with_note(false, &format!("{} is not installed", binary))
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn capabilities(app: &AppHandle) -> PlatformCapabilities {
+    use crate::audio_toolkit::screencapturekit::permissions::supports_screencapturekit;
+
+    let system_audio = if supports_screencapturekit() {
+        FeatureSupport::with_note(true, "Requires Screen Recording permission")
+    } else {
+        FeatureSupport::with_note(true, "Requires the BlackHole loopback driver")
+    };
+
+    PlatformCapabilities {
+        os: "macos".to_string(),
+        session: None,
+        audio_server: None,
+        system_audio,
+        mute: FeatureSupport::yes(),
+        clamshell: FeatureSupport::yes(),
+        global_shortcuts: FeatureSupport::yes(),
+        paste_backend: paste_method_name(app),
+        paste: FeatureSupport::with_note(true, "Requires Accessibility permission"),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn capabilities(app: &AppHandle) -> PlatformCapabilities {
+    PlatformCapabilities {
+        os: "windows".to_string(),
+        session: None,
+        audio_server: None,
+        system_audio: FeatureSupport::yes(),
+        mute: FeatureSupport::yes(),
+        clamshell: FeatureSupport::with_note(false, "Clamshell mode is macOS only"),
+        global_shortcuts: FeatureSupport::yes(),
+        paste_backend: paste_method_name(app),
+        paste: FeatureSupport::yes(),
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn paste_method_name(app: &AppHandle) -> String {
+    serde_json::to_value(settings::get_settings(app).paste_method)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_else(|| "enigo".to_string())
+}
+
+#[tauri::command]
+pub fn get_platform_capabilities(app: AppHandle) -> PlatformCapabilities {
+    capabilities(&app)
+}
@@ -0,0 +1,45 @@
+use crate::events::PowerThrottleChanged;
+use crate::managers::power::PowerManager;
+use crate::settings::{get_settings, write_settings};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+
+/// Turn power-aware throttling on or off, applying it right away
+#[tauri::command]
+pub fn change_power_saving_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.power_saving_enabled = enabled;
+    write_settings(&app, settings);
+    recheck(&app);
+    Ok(())
+}
+
+/// Battery charge, in percent, below which transcription is throttled
+#[tauri::command]
+pub fn change_power_saving_battery_percent_setting(
+    app: AppHandle,
+    percent: u8,
+) -> Result<(), String> {
+    if percent > 100 {
+        return Err(format!("Battery threshold out of range: {}%", percent));
+    }
+    let mut settings = get_settings(&app);
+    settings.power_saving_battery_percent = percent;
+    write_settings(&app, settings);
+    recheck(&app);
+    Ok(())
+}
+
+/// The current power saving level and the readings behind it
+#[tauri::command]
+pub fn get_power_state(power_manager: State<'_, Arc<PowerManager>>) -> PowerThrottleChanged {
+    power_manager.state()
+}
+
+fn recheck(app: &AppHandle) {
+    if let Some(power_manager) = app.try_state::<Arc<PowerManager>>() {
+        let power_manager = Arc::clone(&power_manager);
+        // Switching models can take a while
+        std::thread::spawn(move || power_manager.check());
+    }
+}
@@ -1,5 +1,14 @@
-use crate::managers::transcription::TranscriptionManager;
+use crate::audio_toolkit::decode_audio_file;
+use crate::backend::{BackendKind, ComputeBackend};
+use crate::managers::history::{CaptureSource, HistoryManager, TranscriptionMetadata};
+use crate::managers::journal::{self, OrphanedRecording};
+use crate::managers::queue::{QueueProgress, QueueSource, TranscriptionQueue};
+use crate::managers::transcription::{BackendInfo, ComputeBackendInfo, TranscriptionManager};
+use crate::managers::workers::JobPriority;
 use crate::settings::{get_settings, write_settings, ModelUnloadTimeout};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, State};
 
 #[tauri::command]
@@ -30,3 +39,215 @@ pub fn unload_model_manually(
         .unload_model()
         .map_err(|e| format!("Failed to unload model: {}", e))
 }
+
+/// Switch backends. The loaded model is released and the next transcription
+/// loads it again through the new backend.
+#[tauri::command]
+pub fn change_transcription_backend_setting(
+    app: AppHandle,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    backend: BackendKind,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    if settings.transcription_backend == backend {
+        return Ok(());
+    }
+    settings.transcription_backend = backend;
+    write_settings(&app, settings);
+
+    if transcription_manager.is_model_loaded() {
+        transcription_manager
+            .unload_model()
+            .map_err(|e| format!("Failed to unload model: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Choose the device local models run on. The loaded model is unloaded so the
+/// next transcription loads it there.
+#[tauri::command]
+pub fn change_compute_backend_setting(
+    app: AppHandle,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    backend: ComputeBackend,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    if settings.compute_backend == backend {
+        return Ok(());
+    }
+    settings.compute_backend = backend;
+    write_settings(&app, settings);
+
+    if transcription_manager.is_model_loaded() {
+        transcription_manager
+            .unload_model()
+            .map_err(|e| format!("Failed to unload model: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Which compute backend the loaded model actually runs on and its memory use
+#[tauri::command]
+pub fn get_compute_backend_info(
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> ComputeBackendInfo {
+    transcription_manager.compute_info()
+}
+
+/// Name and capabilities of the loaded transcription backend, or `None` while
+/// no model is loaded
+#[tauri::command]
+pub fn get_transcription_backend_info(
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> Option<BackendInfo> {
+    transcription_manager.backend_info()
+}
+
+/// Metadata for text transcribed from a file or journal rather than live
+fn file_metadata(
+    app: &AppHandle,
+    tm: &TranscriptionManager,
+    source: CaptureSource,
+    latency: Duration,
+) -> TranscriptionMetadata {
+    TranscriptionMetadata {
+        model: tm.get_current_model(),
+        source: Some(source),
+        latency_ms: Some(latency.as_millis() as u64),
+        language: Some(get_settings(app).selected_language),
+        app_name: None,
+    }
+}
+
+/// Decode an audio file, transcribe it and save the result to history
+#[tauri::command]
+pub async fn transcribe_file(
+    app: AppHandle,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    path: String,
+) -> Result<String, String> {
+    let tm = Arc::clone(&transcription_manager);
+    tm.initiate_model_load();
+
+    let path = PathBuf::from(path);
+    let (samples, transcription, latency) = tauri::async_runtime::spawn_blocking(move || {
+        let samples = decode_audio_file(&path)
+            .map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?;
+        log::info!(
+            "Decoded {} ({:.1}s of audio)",
+            path.display(),
+            samples.len() as f32 / 16000.0
+        );
+        let started = Instant::now();
+        let transcription = tm
+            .transcribe_with_prompt(samples.clone(), None, JobPriority::Background)
+            .map_err(|e| format!("Transcription failed: {}", e))?;
+        Ok::<_, String>((samples, transcription, started.elapsed()))
+    })
+    .await
+    .map_err(|e| format!("Transcription task failed: {}", e))??;
+
+    let metadata = file_metadata(&app, &transcription_manager, CaptureSource::File, latency);
+    history_manager
+        .save_transcription(samples, transcription.clone(), None, None, metadata)
+        .await
+        .map_err(|e| format!("Failed to save transcription to history: {}", e))?;
+
+    Ok(transcription)
+}
+
+#[tauri::command]
+pub fn list_orphaned_recordings(app: AppHandle) -> Result<Vec<OrphanedRecording>, String> {
+    let dir = journal::journal_dir(&app).map_err(|e| e.to_string())?;
+    Ok(journal::list_orphaned(&dir))
+}
+
+fn orphaned_journal_path(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    let dir = journal::journal_dir(app).map_err(|e| e.to_string())?;
+    journal::journal_path(&dir, id)
+        .filter(|path| path.exists())
+        .ok_or_else(|| format!("No interrupted recording with id '{}'", id))
+}
+
+/// Transcribe an interrupted recording's journal, save it to history and
+/// delete the journal
+#[tauri::command]
+pub async fn recover_orphaned_recording(
+    app: AppHandle,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    id: String,
+) -> Result<String, String> {
+    let path = orphaned_journal_path(&app, &id)?;
+    let tm = Arc::clone(&transcription_manager);
+    tm.initiate_model_load();
+
+    let journal_path = path.clone();
+    let (samples, transcription, latency) = tauri::async_runtime::spawn_blocking(move || {
+        let samples = journal::read_journal(&journal_path)
+            .map_err(|e| format!("Failed to read recording journal: {}", e))?;
+        let started = Instant::now();
+        let transcription = tm
+            .transcribe_with_prompt(samples.clone(), None, JobPriority::Background)
+            .map_err(|e| format!("Transcription failed: {}", e))?;
+        Ok::<_, String>((samples, transcription, started.elapsed()))
+    })
+    .await
+    .map_err(|e| format!("Transcription task failed: {}", e))??;
+
+    let metadata = file_metadata(
+        &app,
+        &transcription_manager,
+        CaptureSource::Recovered,
+        latency,
+    );
+    history_manager
+        .save_transcription(samples, transcription.clone(), None, None, metadata)
+        .await
+        .map_err(|e| format!("Failed to save transcription to history: {}", e))?;
+
+    if let Err(e) = std::fs::remove_file(&path) {
+        log::warn!("Failed to remove recovered journal {:?}: {}", path, e);
+    }
+    Ok(transcription)
+}
+
+#[tauri::command]
+pub fn discard_orphaned_recording(app: AppHandle, id: String) -> Result<(), String> {
+    let path = orphaned_journal_path(&app, &id)?;
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete recording journal: {}", e))
+}
+
+#[tauri::command]
+pub fn enqueue_transcriptions(
+    queue: State<'_, Arc<TranscriptionQueue>>,
+    sources: Vec<QueueSource>,
+) -> Result<Vec<u64>, String> {
+    Ok(queue.enqueue(sources))
+}
+
+#[tauri::command]
+pub fn get_transcription_queue(
+    queue: State<'_, Arc<TranscriptionQueue>>,
+) -> Result<QueueProgress, String> {
+    Ok(queue.progress())
+}
+
+#[tauri::command]
+pub fn pause_transcription_queue(queue: State<'_, Arc<TranscriptionQueue>>) -> Result<(), String> {
+    queue.pause();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn resume_transcription_queue(queue: State<'_, Arc<TranscriptionQueue>>) -> Result<(), String> {
+    queue.resume();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn cancel_transcription_queue(queue: State<'_, Arc<TranscriptionQueue>>) -> Result<(), String> {
+    queue.cancel();
+    Ok(())
+}
@@ -0,0 +1,196 @@
+//! Typed events describing the recording and transcription lifecycle.
+//!
+//! Each payload names the event it is sent as, so the frontend can rely on
+//! one schema per event instead of parsing log strings. Timestamps
+//! are unix milliseconds.
+
+use crate::helpers::power::PowerStatus;
+use crate::managers::history::CaptureSource;
+use crate::managers::power::PowerThrottle;
+use crate::metrics::CaptureStream;
+use log::error;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+pub trait LifecycleEvent: Serialize + Clone {
+    const NAME: &'static str;
+}
+
+/// Send `event` to every window, logging instead of failing
+pub fn emit<E: LifecycleEvent>(app: &AppHandle, event: E) {
+    if let Err(e) = app.emit(E::NAME, event) {
+        error!("Failed to emit {} event: {}", E::NAME, e);
+    }
+}
+
+pub fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RecordingStarted {
+    /// Shared by the events of one recording and its transcription
+    pub recording_id: u64,
+    pub binding_id: String,
+    pub source: CaptureSource,
+    pub started_at: i64,
+}
+
+impl LifecycleEvent for RecordingStarted {
+    const NAME: &'static str = "recording-started";
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RecordingStopped {
+    pub recording_id: u64,
+    pub binding_id: String,
+    pub stopped_at: i64,
+    /// Wall-clock time from start to stop, including any time paused
+    pub duration_ms: u64,
+    /// Discarded rather than handed on for transcription
+    pub cancelled: bool,
+}
+
+impl LifecycleEvent for RecordingStopped {
+    const NAME: &'static str = "recording-stopped";
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TranscriptionStarted {
+    pub recording_id: u64,
+    pub binding_id: String,
+    pub started_at: i64,
+    /// Length of the recording being transcribed
+    pub audio_ms: u64,
+}
+
+impl LifecycleEvent for TranscriptionStarted {
+    const NAME: &'static str = "transcription-started";
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TranscriptionFinished {
+    pub recording_id: u64,
+    pub binding_id: String,
+    pub finished_at: i64,
+    pub elapsed_ms: u64,
+    /// Raw model output, before any formatting or post-processing
+    pub text: String,
+    /// Set when transcription failed, leaving `text` empty
+    pub error: Option<String>,
+}
+
+impl LifecycleEvent for TranscriptionFinished {
+    const NAME: &'static str = "transcription-finished";
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CaptionSegment {
+    /// History session the caption is saved in
+    pub session_id: Option<i64>,
+    pub source: CaptureSource,
+    pub text: String,
+    pub speaker: Option<String>,
+    /// Position on the caption session timeline, in seconds
+    pub start: f64,
+    pub end: f64,
+    pub emitted_at: i64,
+}
+
+impl LifecycleEvent for CaptionSegment {
+    const NAME: &'static str = "caption-segment";
+}
+
+/// Transcriptions waiting for a worker, by priority, and those running
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct TranscriptionQueueDepth {
+    pub dictation: usize,
+    pub caption: usize,
+    pub background: usize,
+    pub running: usize,
+}
+
+impl LifecycleEvent for TranscriptionQueueDepth {
+    const NAME: &'static str = "transcription-queue-depth";
+}
+
+/// Power saving started, changed level or ended
+#[derive(Clone, Debug, Serialize)]
+pub struct PowerThrottleChanged {
+    pub throttle: PowerThrottle,
+    /// The readings that led to `throttle`
+    pub status: PowerStatus,
+    /// Model used in place of the selected one while throttled
+    pub model_override: Option<String>,
+}
+
+impl LifecycleEvent for PowerThrottleChanged {
+    const NAME: &'static str = "power-throttle-changed";
+}
+
+/// An input device appeared
+#[derive(Clone, Debug, Serialize)]
+pub struct DeviceAdded {
+    /// Stable id, as saved for the selected microphone
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+}
+
+impl LifecycleEvent for DeviceAdded {
+    const NAME: &'static str = "device-added";
+}
+
+/// An input device went away
+#[derive(Clone, Debug, Serialize)]
+pub struct DeviceRemoved {
+    pub id: String,
+    pub name: String,
+    /// Whether it was the default input until it went
+    pub was_default: bool,
+}
+
+impl LifecycleEvent for DeviceRemoved {
+    const NAME: &'static str = "device-removed";
+}
+
+/// The microphone in use went away and was reopened on the default input
+#[derive(Clone, Debug, Serialize)]
+pub struct MicrophoneFailover {
+    /// Name of the device that went away
+    pub from: String,
+    /// A recording was in progress and carried on on the default input
+    pub recording: bool,
+    /// Set when the default input couldn't be opened either
+    pub error: Option<String>,
+}
+
+impl LifecycleEvent for MicrophoneFailover {
+    const NAME: &'static str = "microphone-failover";
+}
+
+/// A capture stream kept losing audio; sent once per stream per run
+#[derive(Clone, Debug, Serialize)]
+pub struct AudioGlitchDetected {
+    pub stream: CaptureStream,
+    /// Glitches counted within the last `window_secs`
+    pub glitches: usize,
+    pub window_secs: u64,
+    /// Things to try, most likely to help first
+    pub remedies: Vec<String>,
+}
+
+impl LifecycleEvent for AudioGlitchDetected {
+    const NAME: &'static str = "audio-glitch-detected";
+}
+
+/// The microphone kept hitting full scale; sent at most once a minute
+#[derive(Clone, Debug, Serialize)]
+pub struct AudioClipping {
+    /// Clips counted within the last few seconds
+    pub clips: usize,
+}
+
+impl LifecycleEvent for AudioClipping {
+    const NAME: &'static str = "audio-clipping";
+}
@@ -0,0 +1,114 @@
+// External command for transforming the final transcript
+// The transcript is written to the command's stdin and whatever it prints to
+// stdout replaces it. The command runs without a shell, with a cleared
+// environment, and is killed if it overruns its time or output budget. It is
+// not sandboxed: it runs with the user's own privileges, so only point this at
+// programs you trust.
+
+use anyhow::{anyhow, bail, Result};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+
+const MAX_INPUT_BYTES: usize = 64 * 1024;
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Pipe `text` through `program args...` and return its output
+pub async fn run_external_command(
+    program: &str,
+    args: &[String],
+    timeout: Duration,
+    text: &str,
+) -> Result<String> {
+    if text.len() > MAX_INPUT_BYTES {
+        bail!(
+            "transcript is larger than the {} byte command limit",
+            MAX_INPUT_BYTES
+        );
+    }
+
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .env_clear()
+        .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+        .current_dir(std::env::temp_dir())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    #[cfg(target_os = "windows")]
+    {
+        // CREATE_NO_WINDOW: don't flash a console for every transcription
+        command.creation_flags(0x0800_0000);
+    }
+
+    let mut child = command.spawn()?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("no command stdin"))?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("no command stdout"))?;
+    let input = text.as_bytes().to_vec();
+
+    // Dropping this future (on timeout or error) drops the child, which kills it
+    let exchange = async move {
+        // Write and read concurrently so a command that streams output can't
+        // deadlock against a full pipe
+        let write = async move {
+            stdin.write_all(&input).await?;
+            // Closing stdin tells the command the transcript is complete
+            drop(stdin);
+            Ok::<_, std::io::Error>(())
+        };
+        let read = async move {
+            let mut output = Vec::new();
+            stdout
+                .take(MAX_OUTPUT_BYTES as u64 + 1)
+                .read_to_end(&mut output)
+                .await?;
+            Ok::<_, std::io::Error>(output)
+        };
+        let ((), output) = tokio::try_join!(write, read)?;
+        check_output_size(&output)?;
+
+        let status = child.wait().await?;
+        if !status.success() {
+            bail!("command exited with {}", status);
+        }
+        decode_output(output)
+    };
+
+    tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| anyhow!("command timed out after {:?}", timeout))?
+}
+
+fn check_output_size(output: &[u8]) -> Result<()> {
+    if output.len() > MAX_OUTPUT_BYTES {
+        bail!("command output exceeded {} bytes", MAX_OUTPUT_BYTES);
+    }
+    Ok(())
+}
+
+/// Command output as text, without the trailing newline most tools print
+fn decode_output(output: Vec<u8>) -> Result<String> {
+    let text = String::from_utf8(output).map_err(|_| anyhow!("command output is not UTF-8"))?;
+    Ok(text.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_is_validated() {
+        assert_eq!(decode_output(b"Hello.\r\n".to_vec()).unwrap(), "Hello.");
+        assert!(decode_output(vec![0xff, 0xfe]).is_err());
+        assert!(check_output_size(&vec![b'a'; MAX_OUTPUT_BYTES + 1]).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use log::{debug, info};
+use serde::Serialize;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// GPU acceleration the transcription engines can use on this machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GpuAcceleration {
+    Metal,
+    Cuda,
+}
+
+/// What the machine has to run models with, probed once at startup
+#[derive(Debug, Clone, Serialize)]
+pub struct HardwareProfile {
+    pub cpu_cores: usize,
+    /// Installed memory, or `None` where it couldn't be read
+    pub total_memory_mb: Option<u64>,
+    pub gpu: Option<GpuAcceleration>,
+}
+
+static PROFILE: OnceLock<HardwareProfile> = OnceLock::new();
+
+/// The probed hardware; the first call runs the probe, later ones reuse it
+pub fn hardware_profile() -> &'static HardwareProfile {
+    PROFILE.get_or_init(|| {
+        let profile = HardwareProfile {
+            cpu_cores: std::thread::available_parallelism()
+                .map(|cores| cores.get())
+                .unwrap_or(1),
+            total_memory_mb: total_memory_mb(),
+            gpu: gpu_acceleration(),
+        };
+        info!(
+            "Hardware: {} cores, {} MB memory, GPU: {:?}",
+            profile.cpu_cores,
+            profile
+                .total_memory_mb
+                .map_or("unknown".to_string(), |mb| mb.to_string()),
+            profile.gpu
+        );
+        profile
+    })
+}
+
+#[tauri::command]
+pub fn get_hardware_profile() -> HardwareProfile {
+    hardware_profile().clone()
+}
+
+#[cfg(target_os = "linux")]
+fn total_memory_mb() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    parse_kb_field(&meminfo, "MemTotal:")
+}
+
+#[cfg(target_os = "macos")]
+fn total_memory_mb() -> Option<u64> {
+    let output = Command::new("sysctl")
+        .args(["-n", "hw.memsize"])
+        .output()
+        .ok()?;
+    let bytes: u64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(bytes / (1024 * 1024))
+}
+
+#[cfg(target_os = "windows")]
+fn total_memory_mb() -> Option<u64> {
+    use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    let mut status = MEMORYSTATUSEX {
+        dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
+        ..Default::default()
+    };
+    unsafe { GlobalMemoryStatusEx(&mut status) }.ok()?;
+    Some(status.ullTotalPhys / (1024 * 1024))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn total_memory_mb() -> Option<u64> {
+    None
+}
+
+/// Memory this process holds in RAM right now
+#[cfg(target_os = "linux")]
+pub fn resident_memory_mb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_kb_field(&status, "VmRSS:")
+}
+
+#[cfg(target_os = "macos")]
+pub fn resident_memory_mb() -> Option<u64> {
+    let output = Command::new("ps")
+        .args(["-o", "rss=", "-p", &std::process::id().to_string()])
+        .output()
+        .ok()?;
+    let kb: u64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kb / 1024)
+}
+
+#[cfg(target_os = "windows")]
+pub fn resident_memory_mb() -> Option<u64> {
+    use windows::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    let mut counters = PROCESS_MEMORY_COUNTERS::default();
+    let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+    unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) }.ok()?;
+    Some(counters.WorkingSetSize as u64 / (1024 * 1024))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn resident_memory_mb() -> Option<u64> {
+    None
+}
+
+/// A `Name:   1234 kB` line from /proc/meminfo or /proc/self/status, in MB
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_kb_field(text: &str, field: &str) -> Option<u64> {
+    let line = text.lines().find(|line| line.starts_with(field))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb / 1024)
+}
+
+fn gpu_acceleration() -> Option<GpuAcceleration> {
+    // Every Apple Silicon GPU runs the engines' Metal kernels
+    if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        return Some(GpuAcceleration::Metal);
+    }
+    if has_cuda_device() {
+        return Some(GpuAcceleration::Cuda);
+    }
+    None
+}
+
+/// Whether an NVIDIA driver reports at least one GPU
+fn has_cuda_device() -> bool {
+    if cfg!(target_os = "macos") {
+        return false;
+    }
+    let mut command = Command::new("nvidia-smi");
+    command.args(["--query-gpu=name", "--format=csv,noheader"]);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW: don't flash a console during startup
+        command.creation_flags(0x0800_0000);
+    }
+    match command.output() {
+        Ok(output) if output.status.success() => {
+            let gpus = String::from_utf8_lossy(&output.stdout);
+            debug!("nvidia-smi reports: {}", gpus.trim());
+            !gpus.trim().is_empty()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_kb_field() {
+        let meminfo = "MemTotal:       16303540 kB\nMemFree:         1234567 kB\n";
+        assert_eq!(parse_kb_field(meminfo, "MemTotal:"), Some(15921));
+        assert_eq!(parse_kb_field(meminfo, "MemFree:"), Some(1205));
+        assert_eq!(parse_kb_field(meminfo, "VmRSS:"), None);
+    }
+}
@@ -1 +1,4 @@
 pub mod clamshell;
+pub mod hardware;
+pub mod output_volume;
+pub mod power;
@@ -0,0 +1,257 @@
+// Volume and mute control for the default output device
+// Expected behavior:
+// - Windows: works on most systems using standard audio drivers.
+// - Linux: works on many systems (PipeWire, PulseAudio, ALSA),
+//   but some distros may lack the tools used.
+// - macOS: works on most standard setups via AppleScript.
+// If unsupported, reads return `None` and changes fail silently.
+
+/// Volume and mute state of the default output device
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutputVolume {
+    /// 0.0 to 1.0; PipeWire allows boosting past 1.0
+    pub volume: f32,
+    pub muted: bool,
+}
+
+/// Put back a state read with `output_volume`
+pub fn restore_output_volume(saved: OutputVolume) {
+    set_output_volume(saved.volume);
+    set_output_mute(saved.muted);
+}
+
+#[cfg(target_os = "windows")]
+fn endpoint_volume() -> Option<windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume> {
+    use windows::Win32::{
+        Media::Audio::{
+            eMultimedia, eRender, Endpoints::IAudioEndpointVolume, IMMDeviceEnumerator,
+            MMDeviceEnumerator,
+        },
+        System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED},
+    };
+
+    unsafe {
+        // Initialize the COM library for this thread.
+        // If already initialized (e.g., by another library like Tauri), this does nothing.
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+        let all_devices: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).ok()?;
+        let default_device = all_devices
+            .GetDefaultAudioEndpoint(eRender, eMultimedia)
+            .ok()?;
+        default_device
+            .Activate::<IAudioEndpointVolume>(CLSCTX_ALL, None)
+            .ok()
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn output_volume() -> Option<OutputVolume> {
+    let endpoint = endpoint_volume()?;
+    unsafe {
+        Some(OutputVolume {
+            volume: endpoint.GetMasterVolumeLevelScalar().ok()?,
+            muted: endpoint.GetMute().ok()?.as_bool(),
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_output_volume(volume: f32) {
+    if let Some(endpoint) = endpoint_volume() {
+        let _ = unsafe {
+            endpoint.SetMasterVolumeLevelScalar(volume.clamp(0.0, 1.0), std::ptr::null())
+        };
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_output_mute(mute: bool) {
+    if let Some(endpoint) = endpoint_volume() {
+        let _ = unsafe { endpoint.SetMute(mute, std::ptr::null()) };
+    }
+}
+
+// Stdout of `program`, if it ran and succeeded
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+}
+
+// Try multiple backends to increase compatibility: PipeWire (wpctl),
+// PulseAudio (pactl), then ALSA (amixer)
+#[cfg(target_os = "linux")]
+pub fn output_volume() -> Option<OutputVolume> {
+    if let Some(volume) =
+        run("wpctl", &["get-volume", "@DEFAULT_AUDIO_SINK@"]).and_then(|o| parse_wpctl(&o))
+    {
+        return Some(volume);
+    }
+    if let Some(volume) =
+        run("pactl", &["get-sink-volume", "@DEFAULT_SINK@"]).and_then(|o| parse_percent(&o))
+    {
+        let muted =
+            run("pactl", &["get-sink-mute", "@DEFAULT_SINK@"]).is_some_and(|o| o.contains("yes"));
+        return Some(OutputVolume { volume, muted });
+    }
+    run("amixer", &["get", "Master"]).and_then(|o| {
+        Some(OutputVolume {
+            volume: parse_percent(&o)?,
+            muted: o.contains("[off]"),
+        })
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_output_volume(volume: f32) {
+    let volume = volume.max(0.0);
+    let percent = format!("{}%", (volume * 100.0).round());
+    if run(
+        "wpctl",
+        &[
+            "set-volume",
+            "@DEFAULT_AUDIO_SINK@",
+            &format!("{:.2}", volume),
+        ],
+    )
+    .is_some()
+        || run("pactl", &["set-sink-volume", "@DEFAULT_SINK@", &percent]).is_some()
+    {
+        return;
+    }
+    let _ = run("amixer", &["set", "Master", &percent]);
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_output_mute(mute: bool) {
+    let mute_val = if mute { "1" } else { "0" };
+    let amixer_state = if mute { "mute" } else { "unmute" };
+
+    if run("wpctl", &["set-mute", "@DEFAULT_AUDIO_SINK@", mute_val]).is_some()
+        || run("pactl", &["set-sink-mute", "@DEFAULT_SINK@", mute_val]).is_some()
+    {
+        return;
+    }
+    let _ = run("amixer", &["set", "Master", amixer_state]);
+}
+
+#[cfg(target_os = "macos")]
+pub fn output_volume() -> Option<OutputVolume> {
+    run("osascript", &["-e", "get volume settings"]).and_then(|o| parse_volume_settings(&o))
+}
+
+#[cfg(target_os = "macos")]
+pub fn set_output_volume(volume: f32) {
+    let script = format!(
+        "set volume output volume {}",
+        (volume.clamp(0.0, 1.0) * 100.0).round()
+    );
+    let _ = run("osascript", &["-e", &script]);
+}
+
+#[cfg(target_os = "macos")]
+pub fn set_output_mute(mute: bool) {
+    let script = format!(
+        "set volume output muted {}",
+        if mute { "true" } else { "false" }
+    );
+    let _ = run("osascript", &["-e", &script]);
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn output_volume() -> Option<OutputVolume> {
+    None
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn set_output_volume(_volume: f32) {}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn set_output_mute(_mute: bool) {}
+
+/// `wpctl get-volume` prints e.g. "Volume: 0.40 [MUTED]"
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_wpctl(output: &str) -> Option<OutputVolume> {
+    let volume = output
+        .trim()
+        .strip_prefix("Volume:")?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    Some(OutputVolume {
+        volume,
+        muted: output.contains("[MUTED]"),
+    })
+}
+
+/// First "NN%" in pactl or amixer output, as a fraction
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_percent(output: &str) -> Option<f32> {
+    output.split_whitespace().find_map(|word| {
+        let percent = word.trim_matches(['[', ']']).strip_suffix('%')?;
+        percent.parse::<f32>().ok().map(|p| p / 100.0)
+    })
+}
+
+/// `get volume settings` prints e.g. "output volume:40, input volume:75,
+/// alert volume:100, output muted:false"; the volume is "missing value" for
+/// devices without one
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_volume_settings(output: &str) -> Option<OutputVolume> {
+    let field = |name: &str| {
+        output
+            .split(',')
+            .find_map(|part| part.trim().strip_prefix(name)?.strip_prefix(':'))
+            .map(str::trim)
+    };
+    Some(OutputVolume {
+        volume: field("output volume")?.parse::<f32>().ok()? / 100.0,
+        muted: field("output muted")? == "true",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_volume_outputs() {
+        assert_eq!(
+            parse_wpctl("Volume: 0.40 [MUTED]\n"),
+            Some(OutputVolume {
+                volume: 0.4,
+                muted: true
+            })
+        );
+        assert_eq!(
+            parse_percent(
+                "Volume: front-left: 26214 /  40% / -23.88 dB,   front-right: 26214 /  40%"
+            ),
+            Some(0.4)
+        );
+        assert_eq!(
+            parse_percent("  Front Left: Playback 39321 [60%] [-13.00dB] [on]"),
+            Some(0.6)
+        );
+        assert_eq!(
+            parse_volume_settings(
+                "output volume:25, input volume:75, alert volume:100, output muted:false\n"
+            ),
+            Some(OutputVolume {
+                volume: 0.25,
+                muted: false
+            })
+        );
+        assert_eq!(
+            parse_volume_settings("output volume:missing value, output muted:missing value"),
+            None
+        );
+    }
+}
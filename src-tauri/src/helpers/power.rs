@@ -0,0 +1,141 @@
+use serde::Serialize;
+
+/// How hard the OS says the machine is running, following macOS's
+/// `NSProcessInfoThermalState`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThermalState {
+    Nominal,
+    Fair,
+    Serious,
+    Critical,
+}
+
+/// Power source and thermal readings, as far as the platform reports them
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PowerStatus {
+    pub on_battery: bool,
+    /// `None` without a battery or where the charge can't be read
+    pub battery_percent: Option<u8>,
+    /// macOS Low Power Mode or Windows battery saver
+    pub power_saver: bool,
+    pub thermal: ThermalState,
+}
+
+impl Default for PowerStatus {
+    fn default() -> Self {
+        Self {
+            on_battery: false,
+            battery_percent: None,
+            power_saver: false,
+            thermal: ThermalState::Nominal,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn power_status() -> PowerStatus {
+    use cocoa::base::id;
+    use objc::runtime::{BOOL, NO};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::process::Command;
+
+    let mut status = Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|output| parse_pmset_batt(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default();
+
+    unsafe {
+        let info: id = msg_send![class!(NSProcessInfo), processInfo];
+        let thermal: i64 = msg_send![info, thermalState];
+        status.thermal = match thermal {
+            0 => ThermalState::Nominal,
+            1 => ThermalState::Fair,
+            2 => ThermalState::Serious,
+            _ => ThermalState::Critical,
+        };
+        // Low Power Mode exists on macOS 12 and later
+        let responds: BOOL = msg_send![info, respondsToSelector: sel!(isLowPowerModeEnabled)];
+        if responds != NO {
+            let low_power: BOOL = msg_send![info, isLowPowerModeEnabled];
+            status.power_saver = low_power != NO;
+        }
+    }
+    status
+}
+
+#[cfg(target_os = "windows")]
+pub fn power_status() -> PowerStatus {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut raw = SYSTEM_POWER_STATUS::default();
+    if unsafe { GetSystemPowerStatus(&mut raw) }.is_err() {
+        return PowerStatus::default();
+    }
+    // Windows has no thermal state for applications to read
+    PowerStatus {
+        on_battery: raw.ACLineStatus == 0,
+        // 255 means the charge is unknown
+        battery_percent: (raw.BatteryLifePercent <= 100).then_some(raw.BatteryLifePercent),
+        power_saver: raw.SystemStatusFlag == 1,
+        thermal: ThermalState::Nominal,
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn power_status() -> PowerStatus {
+    let mut status = PowerStatus::default();
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return status;
+    };
+    for supply in supplies.flatten() {
+        let read = |name: &str| {
+            std::fs::read_to_string(supply.path().join(name))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+        if read("type") == "Battery" {
+            status.battery_percent = read("capacity").parse().ok();
+            status.on_battery = read("status") == "Discharging";
+        }
+    }
+    status
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn power_status() -> PowerStatus {
+    PowerStatus::default()
+}
+
+/// Power source and charge from `pmset -g batt`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset_batt(output: &str) -> PowerStatus {
+    let battery_percent = output
+        .split_whitespace()
+        .find_map(|word| word.strip_suffix("%;"))
+        .and_then(|percent| percent.parse().ok());
+    PowerStatus {
+        on_battery: output.contains("'Battery Power'"),
+        battery_percent,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pmset_batt() {
+        let output = "Now drawing from 'Battery Power'\n \
+            -InternalBattery-0 (id=4653155)\t18%; discharging; 1:02 remaining present: true\n";
+        let status = parse_pmset_batt(output);
+        assert!(status.on_battery);
+        assert_eq!(status.battery_percent, Some(18));
+
+        let status = parse_pmset_batt("Now drawing from 'AC Power'\n");
+        assert!(!status.on_battery);
+        assert_eq!(status.battery_percent, None);
+    }
+}
@@ -0,0 +1,199 @@
+//! Rendering history entries for export.
+
+use crate::managers::history::HistoryEntry;
+use chrono::{DateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Write};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryExportFormat {
+    /// Entries with all their metadata
+    Json,
+    Csv,
+    /// Entries grouped under a heading per day
+    Markdown,
+    Text,
+}
+
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    exported_at: String,
+    app_version: &'a str,
+    from: Option<i64>,
+    to: Option<i64>,
+    count: usize,
+    entries: &'a [HistoryEntry],
+}
+
+/// Text as the user last saw it: post-processed when there is one
+fn final_text(entry: &HistoryEntry) -> &str {
+    entry
+        .post_processed_text
+        .as_deref()
+        .unwrap_or(&entry.transcription_text)
+        .trim()
+}
+
+fn local_time<Tz: TimeZone>(timestamp: i64, tz: &Tz) -> Option<DateTime<Tz>> {
+    DateTime::from_timestamp(timestamp, 0).map(|utc| utc.with_timezone(tz))
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_csv<Tz: TimeZone>(entries: &[HistoryEntry], tz: &Tz) -> String
+where
+    Tz::Offset: Display,
+{
+    let mut out = String::from(
+        "id,timestamp,title,transcription_text,post_processed_text,duration_ms,saved,tag\n",
+    );
+    for entry in entries {
+        let time = local_time(entry.timestamp, tz)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            entry.id,
+            time,
+            csv_field(&entry.title),
+            csv_field(&entry.transcription_text),
+            csv_field(entry.post_processed_text.as_deref().unwrap_or("")),
+            entry.duration_ms.map(|d| d.to_string()).unwrap_or_default(),
+            entry.saved,
+            csv_field(entry.tag.as_deref().unwrap_or("")),
+        );
+    }
+    out
+}
+
+fn to_markdown<Tz: TimeZone>(entries: &[HistoryEntry], tz: &Tz) -> String
+where
+    Tz::Offset: Display,
+{
+    let mut out = String::from("# Transcription History\n");
+    let mut current_day = None;
+    for entry in entries {
+        let Some(time) = local_time(entry.timestamp, tz) else {
+            continue;
+        };
+        let day = time.date_naive();
+        if current_day != Some(day) {
+            let _ = write!(out, "\n## {}\n\n", time.format("%A, %B %-d, %Y"));
+            current_day = Some(day);
+        }
+        let _ = writeln!(out, "- **{}** {}", time.format("%H:%M"), final_text(entry));
+    }
+    out
+}
+
+fn to_text<Tz: TimeZone>(entries: &[HistoryEntry], tz: &Tz) -> String
+where
+    Tz::Offset: Display,
+{
+    let mut out = String::new();
+    for entry in entries {
+        let Some(time) = local_time(entry.timestamp, tz) else {
+            continue;
+        };
+        let _ = write!(
+            out,
+            "[{}]\n{}\n\n",
+            time.format("%Y-%m-%d %H:%M"),
+            final_text(entry)
+        );
+    }
+    out
+}
+
+/// Render `entries`, oldest first, with times in `tz`. `from` and `to` are the
+/// range the entries were picked from and only end up in JSON metadata.
+pub fn render<Tz: TimeZone>(
+    entries: &[HistoryEntry],
+    format: HistoryExportFormat,
+    tz: &Tz,
+    app_version: &str,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<String, serde_json::Error>
+where
+    Tz::Offset: Display,
+{
+    match format {
+        HistoryExportFormat::Json => serde_json::to_string_pretty(&JsonExport {
+            exported_at: chrono::Utc::now().with_timezone(tz).to_rfc3339(),
+            app_version,
+            from,
+            to,
+            count: entries.len(),
+            entries,
+        }),
+        HistoryExportFormat::Csv => Ok(to_csv(entries, tz)),
+        HistoryExportFormat::Markdown => Ok(to_markdown(entries, tz)),
+        HistoryExportFormat::Text => Ok(to_text(entries, tz)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn entry(id: i64, timestamp: i64, text: &str, post_processed: Option<&str>) -> HistoryEntry {
+        HistoryEntry {
+            id,
+            file_name: format!("handy-{}.flac", timestamp),
+            timestamp,
+            saved: false,
+            title: "Title".to_string(),
+            transcription_text: text.to_string(),
+            post_processed_text: post_processed.map(str::to_string),
+            post_process_prompt: None,
+            waveform: None,
+            tag: None,
+            session_id: None,
+            duration_ms: Some(1500),
+            revision_count: 0,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let entries = [entry(1, 0, "hi, \"you\"\nthere", None)];
+        assert_eq!(
+            to_csv(&entries, &Utc),
+            "id,timestamp,title,transcription_text,post_processed_text,duration_ms,saved,tag\n\
+             1,1970-01-01T00:00:00+00:00,Title,\"hi, \"\"you\"\"\nthere\",,1500,false,\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_groups_by_day() {
+        let entries = [
+            entry(1, 60, "first", None),
+            entry(2, 120, "raw", Some("Second.")),
+            entry(3, 86_400 + 3600, "next day", None),
+        ];
+        assert_eq!(
+            to_markdown(&entries, &Utc),
+            "# Transcription History\n\
+             \n## Thursday, January 1, 1970\n\n\
+             - **00:01** first\n\
+             - **00:02** Second.\n\
+             \n## Friday, January 2, 1970\n\n\
+             - **01:00** next day\n"
+        );
+        assert_eq!(
+            to_text(&entries[..1], &Utc),
+            "[1970-01-01 00:01]\nfirst\n\n"
+        );
+    }
+}
@@ -1,11 +1,19 @@
 mod actions;
 mod audio_feedback;
+mod backend;
 pub mod audio_toolkit;
+mod caption;
 mod clipboard;
+mod cloud_quota;
 mod commands;
+mod events;
+mod external_command;
 mod helpers;
+mod history_export;
 mod llm_client;
+mod log_buffer;
 mod managers;
+mod metrics;
 mod overlay;
 mod settings;
 mod shortcut;
@@ -13,10 +21,16 @@ mod signal_handle;
 mod tray;
 mod utils;
 
+use audio_toolkit::debug_log::AUDIO_DEBUG_TARGET;
+use caption::server::CaptionServer;
 use env_filter::Builder as EnvFilterBuilder;
 use managers::audio::AudioRecordingManager;
+use managers::devices::DeviceWatcher;
 use managers::history::HistoryManager;
 use managers::model::ModelManager;
+use managers::playback::PlaybackManager;
+use managers::power::PowerManager;
+use managers::queue::TranscriptionQueue;
 use managers::transcription::TranscriptionManager;
 #[cfg(unix)]
 use signal_hook::consts::SIGUSR2;
@@ -102,6 +116,13 @@ fn show_main_window(app: &AppHandle) {
 }
 
 fn initialize_core_logic(app_handle: &AppHandle) {
+    app_handle.manage(Arc::new(PlaybackManager::new(app_handle)));
+
+    // Probe the hardware off the main thread; model selection reads it later
+    std::thread::spawn(|| {
+        helpers::hardware::hardware_profile();
+    });
+
     // First, initialize the managers
     let recording_manager = match AudioRecordingManager::new(app_handle) {
         Ok(manager) => Arc::new(manager),
@@ -126,13 +147,30 @@ fn initialize_core_logic(app_handle: &AppHandle) {
                 TranscriptionManager::new(app_handle, model_manager.clone())
                     .expect("Failed to initialize transcription manager"),
             );
+            transcription_manager.start_workers();
+            transcription_manager.start_idle_timer();
             let history_manager =
                 Arc::new(HistoryManager::new(app_handle).expect("Failed to initialize history manager"));
 
+            let transcription_queue = Arc::new(TranscriptionQueue::new(
+                app_handle,
+                transcription_manager.clone(),
+                history_manager.clone(),
+            ));
+
             // Add managers to Tauri's managed state (without recording manager)
             app_handle.manage(model_manager.clone());
             app_handle.manage(transcription_manager.clone());
             app_handle.manage(history_manager.clone());
+            app_handle.manage(transcription_queue);
+
+            let power_manager = Arc::new(PowerManager::new(app_handle));
+            app_handle.manage(power_manager.clone());
+            power_manager.start_monitor();
+
+            let device_watcher = Arc::new(DeviceWatcher::new(app_handle));
+            app_handle.manage(device_watcher.clone());
+            device_watcher.start_monitor();
             return;
         }
     };
@@ -142,14 +180,32 @@ fn initialize_core_logic(app_handle: &AppHandle) {
         TranscriptionManager::new(app_handle, model_manager.clone())
             .expect("Failed to initialize transcription manager"),
     );
+    transcription_manager.start_workers();
+    transcription_manager.start_idle_timer();
     let history_manager =
         Arc::new(HistoryManager::new(app_handle).expect("Failed to initialize history manager"));
 
+    let transcription_queue = Arc::new(TranscriptionQueue::new(
+        app_handle,
+        transcription_manager.clone(),
+        history_manager.clone(),
+    ));
+
     // Add managers to Tauri's managed state
     app_handle.manage(recording_manager.clone());
     app_handle.manage(model_manager.clone());
     app_handle.manage(transcription_manager.clone());
     app_handle.manage(history_manager.clone());
+    app_handle.manage(transcription_queue);
+
+    // Started once the managers it throttles are in place
+    let power_manager = Arc::new(PowerManager::new(app_handle));
+    app_handle.manage(power_manager.clone());
+    power_manager.start_monitor();
+
+    let device_watcher = Arc::new(DeviceWatcher::new(app_handle));
+    app_handle.manage(device_watcher.clone());
+    device_watcher.start_monitor();
     
     // Initialize system audio capture if configured
     #[cfg(any(target_os = "macos", target_os = "windows"))]
@@ -271,18 +327,36 @@ pub fn run() {
                 .clear_targets()
                 .targets([
                     // Console output respects RUST_LOG environment variable
+                    // Verbose capture diagnostics never go to the console
                     Target::new(TargetKind::Stdout).filter({
                         let console_filter = console_filter.clone();
-                        move |metadata| console_filter.enabled(metadata)
+                        move |metadata| {
+                            metadata.target() != AUDIO_DEBUG_TARGET
+                                && console_filter.enabled(metadata)
+                        }
                     }),
                     // File logs respect the user's settings (stored in FILE_LOG_LEVEL atomic)
                     Target::new(TargetKind::LogDir {
                         file_name: Some("handy".into()),
                     })
                     .filter(|metadata| {
+                        // Already gated at the call site by the debug_audio_logging setting
+                        if metadata.target() == AUDIO_DEBUG_TARGET {
+                            return true;
+                        }
                         let file_level = FILE_LOG_LEVEL.load(Ordering::Relaxed);
                         metadata.level() <= level_filter_from_u8(file_level)
                     }),
+                    // Recent logs for the frontend, at the file log level but
+                    // without the per-buffer capture diagnostics
+                    Target::new(TargetKind::Dispatch(
+                        fern::Dispatch::new().chain(log_buffer::output()),
+                    ))
+                    .filter(|metadata| {
+                        let file_level = FILE_LOG_LEVEL.load(Ordering::Relaxed);
+                        metadata.target() != AUDIO_DEBUG_TARGET
+                            && metadata.level() <= level_filter_from_u8(file_level)
+                    }),
                 ])
                 .build(),
         );
@@ -304,29 +378,41 @@ pub fn run() {
         .plugin(tauri_plugin_macos_permissions::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_store::Builder::default().build())
-        .plugin(
-            tauri_plugin_sql::Builder::default()
-                .add_migrations(
-                    "sqlite:history.db",
-                    managers::history::HistoryManager::get_migrations(),
-                )
-                .build(),
-        )
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_autostart::init(
             MacosLauncher::LaunchAgent,
             Some(vec![]),
         ))
         .manage(Mutex::new(ShortcutToggleStates::default()))
+        .manage(Arc::new(CaptionServer::new()))
         .setup(move |app| {
+            log_buffer::attach(app.handle());
+            audio_toolkit::glitch::attach(app.handle());
             let settings = settings::get_settings(&app.handle());
             let file_log_level: log::Level = settings.log_level.clone().into();
             // Store the file log level in the atomic for the filter to use
             FILE_LOG_LEVEL.store(file_log_level.to_level_filter() as u8, Ordering::Relaxed);
+            audio_toolkit::debug_log::set_audio_debug_logging(settings.debug_audio_logging);
+            audio_toolkit::utils::set_linux_audio_host(settings.linux_audio_host);
+            if let Err(e) = app.state::<Arc<CaptionServer>>().apply_settings(
+                settings.caption_server_enabled,
+                settings.caption_server_port,
+            ) {
+                log::error!("{}", e);
+            }
             let app_handle = app.handle().clone();
 
             initialize_core_logic(&app_handle);
 
+            // Recordings interrupted by a crash can be recovered from their journals
+            if let Ok(dir) = managers::journal::journal_dir(&app_handle) {
+                let orphans = managers::journal::list_orphaned(&dir);
+                if !orphans.is_empty() {
+                    log::warn!("Found {} interrupted recording(s)", orphans.len());
+                    let _ = app_handle.emit("orphaned-recordings-found", orphans);
+                }
+            }
+
             // Show main window only if not starting hidden
             if !settings.start_hidden {
                 if let Some(main_window) = app_handle.get_webview_window("main") {
@@ -374,6 +460,35 @@ pub fn run() {
             shortcut::change_debug_mode_setting,
             shortcut::change_word_correction_threshold_setting,
             shortcut::change_paste_method_setting,
+            shortcut::change_linux_typing_backend_setting,
+            shortcut::update_keyword_boosts,
+            shortcut::change_keyword_boost_strength_setting,
+            shortcut::change_retake_window_setting,
+            shortcut::change_dictation_stitch_window_setting,
+            shortcut::change_transcript_file_enabled_setting,
+            shortcut::change_transcript_file_path_setting,
+            shortcut::change_transcript_file_timestamps_setting,
+            shortcut::change_transcript_file_rotation_setting,
+            shortcut::change_cloud_quota_setting,
+            shortcut::change_cloud_cost_per_minute_setting,
+            shortcut::change_cloud_api_endpoint_setting,
+            shortcut::change_cloud_api_key_setting,
+            shortcut::change_cloud_model_setting,
+            shortcut::change_cloud_fallback_setting,
+            shortcut::change_long_recording_segment_setting,
+            shortcut::set_transcription_params,
+            shortcut::change_hallucination_filter_setting,
+            shortcut::change_caption_server_enabled_setting,
+            shortcut::change_caption_server_port_setting,
+            shortcut::update_language_cycle,
+            shortcut::change_caption_finalization_delay_setting,
+            shortcut::change_denoise_setting,
+            shortcut::change_echo_cancellation_setting,
+            shortcut::update_caption_hotwords,
+            shortcut::change_external_command_enabled_setting,
+            shortcut::change_external_command_setting,
+            shortcut::change_external_command_timeout_setting,
+            shortcut::change_speaker_diarization_setting,
             shortcut::change_clipboard_handling_setting,
             shortcut::change_post_process_enabled_setting,
             shortcut::change_post_process_base_url_setting,
@@ -386,14 +501,47 @@ pub fn run() {
             shortcut::delete_post_process_prompt,
             shortcut::set_post_process_selected_prompt,
             shortcut::update_custom_words,
+            shortcut::update_custom_word_aliases,
             shortcut::suspend_binding,
             shortcut::resume_binding,
             shortcut::change_mute_while_recording_setting,
+            shortcut::change_mute_mode_setting,
+            shortcut::change_duck_percent_setting,
+            shortcut::change_silence_trim_padding_setting,
+            shortcut::change_pre_roll_setting,
+            shortcut::change_max_recording_setting,
+            shortcut::change_caption_alignment_setting,
+            shortcut::change_caption_overlap_setting,
+            shortcut::change_caption_context_setting,
+            shortcut::change_caption_strategy_setting,
+            shortcut::change_caption_paste_setting,
+            shortcut::set_caption_style,
+            shortcut::change_text_casing_setting,
+            shortcut::change_binding_text_casing_setting,
+            shortcut::change_spoken_formatting_setting,
+            shortcut::change_binding_spoken_formatting_setting,
+            shortcut::set_spoken_commands,
+            shortcut::change_punctuation_model_setting,
+            shortcut::change_punctuation_languages_setting,
+            shortcut::set_number_formatting,
+            shortcut::change_profanity_filter_setting,
+            shortcut::update_profanity_words,
+            shortcut::update_paste_blacklist,
+            shortcut::change_output_mode_setting,
+            shortcut::change_binding_output_mode_setting,
+            shortcut::change_caption_translation_setting,
             trigger_update_check,
             commands::cancel_operation,
+            commands::retake_recording,
             commands::get_app_dir_path,
             commands::get_log_dir_path,
             commands::set_log_level,
+            commands::set_debug_audio_logging,
+            commands::get_recent_logs,
+            commands::subscribe_logs,
+            commands::unsubscribe_logs,
+            commands::get_cloud_usage,
+            commands::get_performance_metrics,
             commands::open_recordings_folder,
             commands::open_log_dir,
             commands::open_app_data_dir,
@@ -401,6 +549,7 @@ pub fn run() {
             commands::models::get_model_info,
             commands::models::download_model,
             commands::models::delete_model,
+            commands::models::verify_model,
             commands::models::cancel_download,
             commands::models::set_active_model,
             commands::models::get_current_model,
@@ -409,38 +558,93 @@ pub fn run() {
             commands::models::has_any_models_available,
             commands::models::has_any_models_or_downloads,
             commands::models::get_recommended_first_model,
+            commands::models::set_model_selection,
+            commands::models::benchmark_models,
             commands::audio::update_microphone_mode,
             commands::audio::get_microphone_mode,
+            commands::audio::change_auto_caption_system_audio_setting,
+            commands::audio::change_dual_channel_captions_setting,
             commands::audio::get_available_microphones,
             commands::audio::set_selected_microphone,
             commands::audio::get_selected_microphone,
             commands::audio::set_audio_source,
             commands::audio::get_audio_source,
+            commands::audio::set_channel_downmix,
+            commands::audio::set_input_gain,
+            commands::audio::subscribe_spectrum,
+            commands::audio::unsubscribe_spectrum,
+            commands::audio::set_wasapi_options,
+            commands::audio::set_linux_audio_host,
             commands::audio::get_available_output_devices,
             commands::audio::set_selected_output_device,
             commands::audio::get_selected_output_device,
+            commands::audio::get_device_capabilities,
+            commands::audio::setup_blackhole_routing,
+            commands::audio::get_blackhole_status,
+            commands::audio::install_blackhole,
+            commands::audio::verify_blackhole_routing,
+            commands::audio::pick_capture_target,
+            commands::audio::clear_capture_target,
+            commands::audio::set_device_alias,
             commands::audio::play_test_sound,
             commands::audio::check_custom_sounds,
             commands::audio::set_clamshell_microphone,
             commands::audio::get_clamshell_microphone,
             commands::audio::get_system_audio_status,
+            commands::audio::run_audio_selftest,
             commands::audio::check_audio_initialization_status,
             commands::audio::restart_audio_stream,
+            commands::audio::pause_recording,
+            commands::audio::resume_recording,
             helpers::clamshell::is_clamshell,
             helpers::clamshell::is_laptop,
+            helpers::hardware::get_hardware_profile,
             commands::permissions::get_macos_version,
+            commands::platform::get_platform_capabilities,
             commands::permissions::supports_screencapturekit,
             commands::permissions::check_screen_recording_permission,
             commands::permissions::request_screen_recording_permission,
             commands::transcription::set_model_unload_timeout,
             commands::transcription::get_model_load_status,
             commands::transcription::unload_model_manually,
+            commands::transcription::change_transcription_backend_setting,
+            commands::transcription::get_transcription_backend_info,
+            commands::transcription::change_compute_backend_setting,
+            commands::transcription::get_compute_backend_info,
+            commands::power::change_power_saving_setting,
+            commands::power::change_power_saving_battery_percent_setting,
+            commands::power::get_power_state,
+            commands::transcription::transcribe_file,
+            commands::transcription::list_orphaned_recordings,
+            commands::transcription::recover_orphaned_recording,
+            commands::transcription::discard_orphaned_recording,
+            commands::transcription::enqueue_transcriptions,
+            commands::transcription::get_transcription_queue,
+            commands::transcription::pause_transcription_queue,
+            commands::transcription::resume_transcription_queue,
+            commands::transcription::cancel_transcription_queue,
+            commands::export::get_caption_session,
+            commands::export::get_session_analytics,
+            commands::export::export_session_srt,
+            commands::export::export_session_vtt,
+            commands::export::export_history,
             commands::history::get_history_entries,
+            commands::history::search_history_entries,
             commands::history::toggle_history_entry_saved,
             commands::history::get_audio_file_path,
             commands::history::delete_history_entry,
-            commands::history::update_history_limit,
-            commands::history::update_recording_retention_period
+            commands::history::update_history_text,
+            commands::history::get_history_revisions,
+            commands::history::get_usage_stats,
+            commands::history::play_history_audio,
+            commands::history::pause_history_audio,
+            commands::history::seek_history_audio,
+            commands::history::stop_history_audio,
+            commands::history::get_history_sessions,
+            commands::history::get_history_session_entries,
+            commands::history::delete_history_session,
+            commands::history::set_history_retention,
+            commands::history::purge_history
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,187 @@
+//! In-memory ring buffer of recent log records.
+//!
+//! A log target next to the console and file ones feeds it, so the frontend
+//! can query recent logs and follow new ones as `log-entry` events instead of
+//! capture threads emitting strings at it.
+
+use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+/// Records kept before the oldest are dropped
+const CAPACITY: usize = 2000;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LogEntry {
+    /// Increases by one per record, so gaps show where entries were dropped
+    pub id: u64,
+    /// Unix milliseconds
+    pub timestamp: i64,
+    /// `error`, `warn`, `info`, `debug` or `trace`
+    pub level: String,
+    /// Log target, the module path unless the call site set one
+    pub module: String,
+    pub message: String,
+}
+
+struct LogBuffer {
+    entries: Mutex<VecDeque<LogEntry>>,
+    next_id: AtomicU64,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+            next_id: AtomicU64::new(0),
+            capacity,
+        }
+    }
+
+    fn push(&self, level: log::Level, module: &str, message: &str) -> LogEntry {
+        let entry = LogEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            level: level.as_str().to_lowercase(),
+            module: module.to_string(),
+            message: message.to_string(),
+        };
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry.clone());
+        entry
+    }
+
+    /// The newest `limit` entries at `min_level` or more severe whose module
+    /// starts with `module`, oldest first
+    fn recent(
+        &self,
+        limit: usize,
+        min_level: Option<log::Level>,
+        module: Option<&str>,
+    ) -> Vec<LogEntry> {
+        let entries = self.entries.lock().unwrap();
+        let mut recent: Vec<LogEntry> = entries
+            .iter()
+            .rev()
+            .filter(|entry| {
+                min_level.is_none_or(|min| {
+                    entry
+                        .level
+                        .parse::<log::Level>()
+                        .is_ok_and(|level| level <= min)
+                })
+            })
+            .filter(|entry| module.is_none_or(|prefix| entry.module.starts_with(prefix)))
+            .take(limit)
+            .cloned()
+            .collect();
+        recent.reverse();
+        recent
+    }
+}
+
+static BUFFER: Lazy<LogBuffer> = Lazy::new(|| LogBuffer::new(CAPACITY));
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+/// Open `subscribe_logs` calls; entries are only emitted while there are any
+static SUBSCRIBERS: AtomicUsize = AtomicUsize::new(0);
+
+/// The message as logged, without the `[date][level][target] ` prefix the log
+/// plugin formats every record with before handing it to targets
+fn raw_message<'a>(formatted: &'a str, level: log::Level, target: &str) -> &'a str {
+    let prefix = format!("[{}][{}] ", level, target);
+    match formatted.find(&prefix) {
+        Some(start) => &formatted[start + prefix.len()..],
+        None => formatted,
+    }
+}
+
+/// Log target output that records into the buffer
+pub fn output() -> fern::Output {
+    fern::Output::call(|record| {
+        let formatted = record.args().to_string();
+        let message = raw_message(&formatted, record.level(), record.target());
+        let entry = BUFFER.push(record.level(), record.target(), message);
+
+        if SUBSCRIBERS.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        // Batched, and emitted off the logging thread: emitting can log,
+        // which would land back here
+        if let Some(app) = APP_HANDLE.get() {
+            crate::utils::emit_throttled(app, "log-entry", entry);
+        }
+    })
+}
+
+/// Let the buffer emit `log-entry` events once the app is running
+pub fn attach(app: &AppHandle) {
+    let _ = APP_HANDLE.set(app.clone());
+}
+
+pub fn subscribe() {
+    SUBSCRIBERS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn unsubscribe() {
+    let _ = SUBSCRIBERS.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+        count.checked_sub(1)
+    });
+}
+
+pub fn recent(limit: usize, min_level: Option<log::Level>, module: Option<&str>) -> Vec<LogEntry> {
+    BUFFER.recent(limit, min_level, module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_message_strips_plugin_prefix() {
+        assert_eq!(
+            raw_message(
+                "[2024-01-01][12:00:00][INFO][handy_app_lib::caption] hello [x]",
+                log::Level::Info,
+                "handy_app_lib::caption"
+            ),
+            "hello [x]"
+        );
+        assert_eq!(
+            raw_message("unformatted", log::Level::Warn, "a"),
+            "unformatted"
+        );
+    }
+
+    #[test]
+    fn test_recent_filters_and_drops_oldest() {
+        let buffer = LogBuffer::new(3);
+        buffer.push(log::Level::Info, "app::audio", "dropped");
+        buffer.push(log::Level::Debug, "app::audio", "debug");
+        buffer.push(log::Level::Warn, "app::caption", "warn");
+        buffer.push(log::Level::Error, "app::audio", "error");
+
+        let messages = |entries: Vec<LogEntry>| -> Vec<String> {
+            entries.into_iter().map(|entry| entry.message).collect()
+        };
+        assert_eq!(
+            messages(buffer.recent(10, None, None)),
+            ["debug", "warn", "error"]
+        );
+        assert_eq!(messages(buffer.recent(1, None, None)), ["error"]);
+        assert_eq!(
+            messages(buffer.recent(10, Some(log::Level::Warn), None)),
+            ["warn", "error"]
+        );
+        assert_eq!(
+            messages(buffer.recent(10, None, Some("app::audio"))),
+            ["debug", "error"]
+        );
+    }
+}
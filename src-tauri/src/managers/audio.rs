@@ -1,122 +1,54 @@
+use crate::audio_toolkit::audio::{Denoiser, EchoReference, FrameResampler, PreprocessChain};
+use crate::audio_toolkit::error::Result as AudioResult;
+use crate::audio_toolkit::ring_buffer::SampleReceiver;
+use crate::audio_toolkit::selftest::{AudioSelfTest, SelfTestProbe, SELFTEST_DURATION};
+use crate::audio_toolkit::system_audio::{CaptureFormat, CaptureStatus};
 use crate::audio_toolkit::{
-    audio::{FrameResampler, preprocess_audio},
-    list_input_devices, vad::SmoothedVad, AudioRecorder, SileroVad,
-    SystemAudioCapture,
+    AudioError, AudioRecorder, SystemAudioCapture, TimedSamples, VoiceActivityDetector,
+};
+use crate::caption::{self, CaptionSource};
+use crate::events::{
+    self, DeviceRemoved, LifecycleEvent, MicrophoneFailover, RecordingStarted, RecordingStopped,
 };
-
-#[cfg(target_os = "macos")]
-use crate::audio_toolkit::{MacOSSystemAudio, ScreenCaptureKitAudio};
-
-#[cfg(target_os = "macos")]
-use crate::audio_toolkit::screencapturekit::permissions::{supports_screencapturekit, get_macos_version};
-
-#[cfg(target_os = "windows")]
-use crate::audio_toolkit::WindowsSystemAudio;
 use crate::helpers::clamshell;
-use crate::settings::{get_settings, AppSettings, AudioSource};
+use crate::helpers::output_volume::{self, OutputVolume};
+use crate::managers::audio_backend::{
+    find_input_device, AudioHost, DeviceRecorderFactory, PlatformCaptureFactory, Recorder,
+    RecorderFactory, SystemCaptureFactory, TranscriberFactory,
+};
+use crate::managers::journal::{self, RecordingJournal};
+use crate::managers::segments::SegmentedTranscription;
+use crate::metrics::{self, Stage};
+use crate::settings::{AppSettings, AudioSource, MuteMode};
 use crate::utils;
+use anyhow::Context;
+use cpal::traits::DeviceTrait;
+use futures_util::StreamExt;
 use log::{debug, error, info, warn};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
-use tauri::{Emitter, Manager};
-
-fn set_mute(mute: bool) {
-    // Expected behavior:
-    // - Windows: works on most systems using standard audio drivers.
-    // - Linux: works on many systems (PipeWire, PulseAudio, ALSA),
-    //   but some distros may lack the tools used.
-    // - macOS: works on most standard setups via AppleScript.
-    // If unsupported, fails silently.
-
-    #[cfg(target_os = "windows")]
-    {
-        unsafe {
-            use windows::Win32::{
-                Media::Audio::{
-                    eMultimedia, eRender, Endpoints::IAudioEndpointVolume, IMMDeviceEnumerator,
-                    MMDeviceEnumerator,
-                },
-                System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED},
-            };
-
-            macro_rules! unwrap_or_return {
-                ($expr:expr) => {
-                    match $expr {
-                        Ok(val) => val,
-                        Err(_) => return,
-                    }
-                };
-            }
-
-            // Initialize the COM library for this thread.
-            // If already initialized (e.g., by another library like Tauri), this does nothing.
-            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
-
-            let all_devices: IMMDeviceEnumerator =
-                unwrap_or_return!(CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL));
-            let default_device =
-                unwrap_or_return!(all_devices.GetDefaultAudioEndpoint(eRender, eMultimedia));
-            let volume_interface = unwrap_or_return!(
-                default_device.Activate::<IAudioEndpointVolume>(CLSCTX_ALL, None)
-            );
-
-            let _ = volume_interface.SetMute(mute, std::ptr::null());
-        }
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        use std::process::Command;
-
-        let mute_val = if mute { "1" } else { "0" };
-        let amixer_state = if mute { "mute" } else { "unmute" };
-
-        // Try multiple backends to increase compatibility
-        // 1. PipeWire (wpctl)
-        if Command::new("wpctl")
-            .args(["set-mute", "@DEFAULT_AUDIO_SINK@", mute_val])
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false)
-        {
-            return;
-        }
-
-        // 2. PulseAudio (pactl)
-        if Command::new("pactl")
-            .args(["set-sink-mute", "@DEFAULT_SINK@", mute_val])
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false)
-        {
-            return;
-        }
-
-        // 3. ALSA (amixer)
-        let _ = Command::new("amixer")
-            .args(["set", "Master", amixer_state])
-            .output();
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        use std::process::Command;
-        let script = format!(
-            "set volume output muted {}",
-            if mute { "true" } else { "false" }
-        );
-        let _ = Command::new("osascript").args(["-e", &script]).output();
-    }
-}
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::Manager;
 
 const WHISPER_SAMPLE_RATE: usize = 16000;
+/// How often a recording is checked against `max_recording_secs`
+const DURATION_GUARD_INTERVAL: Duration = Duration::from_millis(250);
+/// How often the system audio self-test drains its capture
+const SELFTEST_POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// Tries at opening a microphone that reports itself busy
+const OPEN_ATTEMPTS: u32 = 3;
+const OPEN_RETRY_DELAY: Duration = Duration::from_millis(200);
 
 /* ──────────────────────────────────────────────────────────────── */
 
-#[derive(Clone, Debug)]
+/// Payload of the `recording-state` event
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
 pub enum RecordingState {
     Idle,
-    Recording { binding_id: String },
+    Recording { binding_id: String, paused: bool },
 }
 
 #[derive(Clone, Debug)]
@@ -127,27 +59,46 @@ pub enum MicrophoneMode {
 
 /* ──────────────────────────────────────────────────────────────── */
 
-fn create_audio_recorder(
-    vad_path: &str,
+/// Location of the bundled Silero VAD model
+pub fn resolve_vad_model_path(
     app_handle: &tauri::AppHandle,
-) -> Result<AudioRecorder, anyhow::Error> {
-    let silero = SileroVad::new(vad_path, 0.3)
-        .map_err(|e| anyhow::anyhow!("Failed to create SileroVad: {}", e))?;
-    let smoothed_vad = SmoothedVad::new(Box::new(silero), 15, 15, 2);
-
-    // Recorder with VAD plus a spectrum-level callback that forwards updates to
-    // the frontend.
-    let recorder = AudioRecorder::new()
-        .map_err(|e| anyhow::anyhow!("Failed to create AudioRecorder: {}", e))?
-        .with_vad(Box::new(smoothed_vad))
-        .with_level_callback({
-            let app_handle = app_handle.clone();
-            move |levels| {
-                utils::emit_levels(&app_handle, &levels);
-            }
-        });
+) -> Result<std::path::PathBuf, anyhow::Error> {
+    app_handle
+        .path()
+        .resolve(
+            "resources/models/silero_vad_v4.onnx",
+            tauri::path::BaseDirectory::Resource,
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to resolve VAD path: {}", e))
+}
 
-    Ok(recorder)
+/// Open `rec` on `device` with its downmix and gain settings, trying again
+/// while the error says the device may free up, as it can just after another
+/// stream released it
+fn open_with_retry(
+    rec: &mut dyn Recorder,
+    device: Option<&str>,
+    settings: &AppSettings,
+) -> AudioResult<()> {
+    rec.set_downmix(settings.microphone_downmix.downmix());
+    rec.set_gain(settings.input_gain(device));
+    rec.set_wasapi_options(settings.wasapi);
+    let mut attempt = 1;
+    loop {
+        match rec.open_device(device) {
+            Err(e) if e.is_retryable() && attempt < OPEN_ATTEMPTS => {
+                warn!(
+                    "Opening microphone failed on attempt {} ({}), retrying: {}",
+                    attempt,
+                    e.kind(),
+                    e
+                );
+                thread::sleep(OPEN_RETRY_DELAY);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
 }
 
 /* ──────────────────────────────────────────────────────────────── */
@@ -156,21 +107,58 @@ fn create_audio_recorder(
 pub struct AudioRecordingManager {
     state: Arc<Mutex<RecordingState>>,
     mode: Arc<Mutex<MicrophoneMode>>,
-    app_handle: tauri::AppHandle,
+    host: Arc<dyn AudioHost>,
+    recorders: Arc<dyn RecorderFactory>,
+    captures: Arc<dyn SystemCaptureFactory>,
+    transcribers: Arc<dyn TranscriberFactory>,
 
-    recorder: Arc<Mutex<Option<AudioRecorder>>>,
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    recorder: Arc<Mutex<Option<Box<dyn Recorder>>>>,
     system_capture: Arc<Mutex<Option<Box<dyn SystemAudioCapture>>>>,
     is_open: Arc<Mutex<bool>>,
     is_recording: Arc<Mutex<bool>>,
     did_mute: Arc<Mutex<bool>>,
+    // Output state from before muting or ducking, put back afterwards
+    saved_output: Arc<Mutex<Option<OutputVolume>>>,
+    journal: Arc<Mutex<Option<RecordingJournal>>>,
+    // Early transcription of long takes, handed to the caller on stop
+    segments: Arc<Mutex<Option<SegmentedTranscription>>>,
+    // Loopback feeding echo cancellation, and the reference it fills
+    echo_capture: Arc<Mutex<Option<(Box<dyn SystemAudioCapture>, EchoReference)>>>,
+    // Mic opened as the second channel of dual-channel captions
+    dual_channel_mic: Arc<Mutex<bool>>,
+    // Bumped by every start: the id of the latest recording, so a duration
+    // guard only stops its own
+    recording_generation: Arc<AtomicU64>,
+    recording_started_at: Arc<Mutex<Option<Instant>>>,
+    // Binding whose recording the duration guard stopped; its own stop is
+    // then ignored
+    stopped_at_limit: Arc<Mutex<Option<String>>>,
+    // Audio recorded before a failover reopened the microphone, put in front
+    // of the rest on stop
+    failover_samples: Arc<Mutex<Vec<f32>>>,
 }
 
 impl AudioRecordingManager {
     /* ---------- construction ------------------------------------------------ */
 
     pub fn new(app: &tauri::AppHandle) -> Result<Self, anyhow::Error> {
-        let settings = get_settings(app);
+        Self::with_backends(
+            Arc::new(app.clone()),
+            Arc::new(DeviceRecorderFactory::new(app)),
+            Arc::new(PlatformCaptureFactory::new(app)),
+            Arc::new(app.clone()),
+        )
+    }
+
+    /// A manager that records through the given backends and reports to
+    /// `host`. `new` wires in the real ones.
+    pub fn with_backends(
+        host: Arc<dyn AudioHost>,
+        recorders: Arc<dyn RecorderFactory>,
+        captures: Arc<dyn SystemCaptureFactory>,
+        transcribers: Arc<dyn TranscriberFactory>,
+    ) -> Result<Self, anyhow::Error> {
+        let settings = host.settings();
         let mode = if settings.always_on_microphone {
             MicrophoneMode::AlwaysOn
         } else {
@@ -180,18 +168,29 @@ impl AudioRecordingManager {
         let manager = Self {
             state: Arc::new(Mutex::new(RecordingState::Idle)),
             mode: Arc::new(Mutex::new(mode.clone())),
-            app_handle: app.clone(),
+            host,
+            recorders,
+            captures,
+            transcribers,
 
             recorder: Arc::new(Mutex::new(None)),
-            #[cfg(any(target_os = "macos", target_os = "windows"))]
             system_capture: Arc::new(Mutex::new(None)),
             is_open: Arc::new(Mutex::new(false)),
             is_recording: Arc::new(Mutex::new(false)),
             did_mute: Arc::new(Mutex::new(false)),
+            saved_output: Arc::new(Mutex::new(None)),
+            journal: Arc::new(Mutex::new(None)),
+            segments: Arc::new(Mutex::new(None)),
+            echo_capture: Arc::new(Mutex::new(None)),
+            dual_channel_mic: Arc::new(Mutex::new(false)),
+            recording_generation: Arc::new(AtomicU64::new(0)),
+            recording_started_at: Arc::new(Mutex::new(None)),
+            stopped_at_limit: Arc::new(Mutex::new(None)),
+            failover_samples: Arc::new(Mutex::new(Vec::new())),
         };
 
-        // Always-on?  Open immediately.
-        if matches!(mode, MicrophoneMode::AlwaysOn) {
+        // Always-on or captioning on launch?  Open immediately.
+        if matches!(mode, MicrophoneMode::AlwaysOn) || settings.auto_caption_enabled() {
             manager.start_microphone_stream()?;
         }
 
@@ -200,64 +199,91 @@ impl AudioRecordingManager {
 
     /* ---------- helper methods --------------------------------------------- */
 
-    fn get_effective_microphone_device(&self, settings: &AppSettings) -> Option<cpal::Device> {
+    /// Key of the microphone to open, considering clamshell mode; None for
+    /// the default input
+    fn get_effective_microphone(&self, settings: &AppSettings) -> Option<String> {
         // Check if we're in clamshell mode and have a clamshell microphone configured
-        let use_clamshell_mic = if let Ok(is_clamshell) = clamshell::is_clamshell() {
-            is_clamshell && settings.clamshell_microphone.is_some()
-        } else {
-            false
-        };
+        let use_clamshell_mic =
+            settings.clamshell_microphone.is_some() && clamshell::is_clamshell().unwrap_or(false);
 
-        let device_name = if use_clamshell_mic {
-            settings.clamshell_microphone.as_ref().unwrap()
+        if use_clamshell_mic {
+            settings.clamshell_microphone.clone()
         } else {
-            settings.selected_microphone.as_ref()?
-        };
+            settings.selected_microphone.clone()
+        }
+    }
 
-        // Find the device by name
-        match list_input_devices() {
-            Ok(devices) => devices
-                .into_iter()
-                .find(|d| d.name == *device_name)
-                .map(|d| d.device),
-            Err(e) => {
-                debug!("Failed to list devices, using default: {}", e);
-                None
-            }
+    /// Send an event to the frontend through the host
+    fn emit<P: Serialize>(&self, event: &str, payload: P) {
+        match serde_json::to_value(payload) {
+            Ok(payload) => self.host.emit_event(event, payload),
+            Err(e) => error!("Failed to serialize {event} event: {e}"),
+        }
+    }
+
+    /// Run a caption loop over `source`; needs the app, so nothing happens
+    /// without one
+    fn spawn_caption_loop(&self, binding_id: String, source: CaptionSource) {
+        if let Some(app) = self.host.app() {
+            caption::spawn_caption_loop(app.clone(), Arc::new(self.clone()), binding_id, source);
         }
     }
 
     /* ---------- microphone life-cycle -------------------------------------- */
 
-    /// Applies mute if mute_while_recording is enabled and stream is open
+    /// Mutes or ducks the output if mute_while_recording is enabled and
+    /// stream is open, remembering the volume and mute state to restore
     pub fn apply_mute(&self) {
-        let settings = get_settings(&self.app_handle);
+        let settings = self.host.settings();
         let mut did_mute_guard = self.did_mute.lock().unwrap();
 
-        if settings.mute_while_recording && *self.is_open.lock().unwrap() {
-            set_mute(true);
-            *did_mute_guard = true;
-            debug!("Mute applied");
+        // Already applied; reading the state again would save our own change
+        if *did_mute_guard || !settings.mute_while_recording || !*self.is_open.lock().unwrap() {
+            return;
+        }
+        let saved = output_volume::output_volume();
+        match (settings.mute_mode, saved) {
+            (MuteMode::Mute, _) => output_volume::set_output_mute(true),
+            (MuteMode::Duck, Some(saved)) => {
+                let keep = 1.0 - f32::from(settings.duck_percent.min(100)) / 100.0;
+                output_volume::set_output_volume(saved.volume * keep);
+            }
+            (MuteMode::Duck, None) => {
+                warn!("Can't read the output volume, not ducking");
+                return;
+            }
         }
+        *self.saved_output.lock().unwrap() = saved;
+        *did_mute_guard = true;
+        debug!("Mute applied ({:?})", settings.mute_mode);
     }
 
     /// Removes mute if it was applied
     pub fn remove_mute(&self) {
         let mut did_mute_guard = self.did_mute.lock().unwrap();
         if *did_mute_guard {
-            set_mute(false);
+            self.restore_output();
             *did_mute_guard = false;
             debug!("Mute removed");
         }
     }
 
+    // Put back the output state saved by `apply_mute`. Without one, unmuting
+    // is the best guess.
+    fn restore_output(&self) {
+        match self.saved_output.lock().unwrap().take() {
+            Some(saved) => output_volume::restore_output_volume(saved),
+            None => output_volume::set_output_mute(false),
+        }
+    }
+
     pub fn start_microphone_stream(&self) -> Result<(), anyhow::Error> {
         let mut open_flag = self.is_open.lock().unwrap();
         if *open_flag {
             debug!("Microphone stream already active");
             // Even if already open, ensure auto-transcription is started for SystemAudio
-            let settings = get_settings(&self.app_handle);
-            if settings.always_on_microphone {
+            let settings = self.host.settings();
+            if settings.auto_caption_system_audio {
                 let audio_source = settings.audio_source.unwrap_or(AudioSource::Microphone);
                 if audio_source == AudioSource::SystemAudio {
                     let is_recording = *self.is_recording.lock().unwrap();
@@ -277,7 +303,7 @@ impl AudioRecordingManager {
         }
 
         let start_time = Instant::now();
-        let settings = get_settings(&self.app_handle);
+        let settings = self.host.settings();
         let audio_source = settings.audio_source.unwrap_or(AudioSource::Microphone);
 
         // Don't mute immediately - caller will handle muting after audio feedback
@@ -285,690 +311,24 @@ impl AudioRecordingManager {
         *did_mute_guard = false;
 
         if audio_source == AudioSource::SystemAudio {
-            // System Audio Capture - macOS
-            #[cfg(target_os = "macos")]
-            {
-                // Check macOS version and use ScreenCaptureKit if available (macOS 13+)
-                let use_screencapturekit = supports_screencapturekit();
-                
-                if use_screencapturekit {
-                    // Use ScreenCaptureKit (macOS 13+)
-                    info!("Initializing ScreenCaptureKit system audio capture (macOS 13+)");
-                    if let Some((major, minor)) = get_macos_version() {
-                        info!("Detected macOS {}.{} - using native ScreenCaptureKit", major, minor);
-                    }
-                    
-                    let mut capture = match ScreenCaptureKitAudio::new(&self.app_handle) {
-                        Ok(c) => c,
-                        Err(e) => {
-                            error!("Failed to create ScreenCaptureKitAudio: {}", e);
-                            error!("Screen Recording permission may be required.");
-                            // Emit event to show setup instructions - frontend will show persistent dialog
-                            let _ = self.app_handle.emit("screencapture-permission-required", format!("Screen Recording permission required: {}", e));
-                            *open_flag = false;
-                            return Err(e);
-                        }
-                    };
-                    match capture.start_capture() {
-                        Ok(()) => {
-                            *self.system_capture.lock().unwrap() = Some(Box::new(capture));
-                            *open_flag = true;
-                            info!(
-                                "ScreenCaptureKit audio capture initialized in {:?}",
-                                start_time.elapsed()
-                            );
-                        },
-                        Err(e) => {
-                            error!("Failed to start ScreenCaptureKit audio capture: {}", e);
-                            error!("Please ensure Screen Recording permission is granted in System Preferences > Privacy & Security > Screen Recording");
-                            // Emit event to show setup instructions - frontend will show persistent dialog
-                            let _ = self.app_handle.emit("screencapture-permission-required", format!("Screen Recording permission not granted: {}", e));
-                            *open_flag = false;
-                            return Err(e);
-                        }
-                    }
-                } else {
-                    // Fallback to BlackHole (macOS < 13)
-                    if let Some((major, minor)) = get_macos_version() {
-                        info!("Detected macOS {}.{} - ScreenCaptureKit not available, using BlackHole", major, minor);
-                    } else {
-                        info!("macOS version < 13 - ScreenCaptureKit not available, using BlackHole");
-                    }
-                    info!("Initializing BlackHole system audio capture (legacy mode)");
-                    
-                    let mut capture = match MacOSSystemAudio::new(&self.app_handle) {
-                        Ok(c) => c,
-                        Err(e) => {
-                            error!("Failed to create MacOSSystemAudio: {}", e);
-                            error!("System audio not available. Please install BlackHole and configure Multi-Output Device.");
-                            // Emit event to show setup instructions - frontend will show persistent dialog
-                            let _ = self.app_handle.emit("system-audio-setup-required", format!("BlackHole setup required: {}", e));
-                            *open_flag = false;
-                            return Err(e);
-                        }
-                    };
-                    match capture.start_capture() {
-                        Ok(()) => {
-                            *self.system_capture.lock().unwrap() = Some(Box::new(capture));
-                            *open_flag = true;
-                            info!(
-                                "BlackHole system audio capture initialized in {:?}",
-                                start_time.elapsed()
-                            );
-                        },
-                        Err(e) => {
-                            error!("Failed to start BlackHole system audio capture: {}", e);
-                            error!("System audio not available. Please follow setup instructions.");
-                            // Emit event to show setup instructions - frontend will show persistent dialog
-                            let _ = self.app_handle.emit("system-audio-setup-required", format!("BlackHole not configured: {}", e));
-                            *open_flag = false;
-                            return Err(e);
-                        }
-                    }
-                }
-                
-                // Auto-start recording in always-on mode with system audio
-                let settings = get_settings(&self.app_handle);
-                if settings.always_on_microphone {
-                    info!("Always-on mode: Auto-starting continuous system audio transcription");
-                    let binding_id = "transcribe".to_string();
-                    if self.try_start_recording(&binding_id) {
-                        info!("Auto-started recording in always-on mode");
-                        
-                        // Start continuous transcription loop with sliding window (no audio loss like Google Translate)
-                        let app_handle = self.app_handle.clone();
-                        let rm = Arc::new(self.clone());
-                        std::thread::spawn(move || {
-                            use std::time::Duration;
-                            use std::collections::VecDeque;
-                            
-                            const TRANSCRIBE_INTERVAL_SECS: u64 = 3; // Transcribe every 3 seconds for real-time
-                            const MIN_AUDIO_SECS: usize = 2; // Minimum 2 seconds of audio before transcribing
-                            const OVERLAP_SECS: usize = 1; // Keep 1 second overlap to avoid missing audio
-                            const MIN_SAMPLES: usize = MIN_AUDIO_SECS * 16000;
-                            const OVERLAP_SAMPLES: usize = OVERLAP_SECS * 16000;
-                            
-                            // System audio from SCK is 48kHz, need to resample to 16kHz for Whisper
-                            const SYSTEM_AUDIO_SAMPLE_RATE: usize = 48000;
-                            const TARGET_SAMPLE_RATE: usize = 16000;
-                            let mut resampler = FrameResampler::new(
-                                SYSTEM_AUDIO_SAMPLE_RATE,
-                                TARGET_SAMPLE_RATE,
-                                Duration::from_millis(30),
-                            );
-                            
-                            // Accumulation buffer to avoid missing any audio (stores resampled 16kHz samples)
-                            let mut accumulated_buffer: VecDeque<f32> = VecDeque::new();
-                            
-                            // Track previous RMS to detect when audio starts (transitions from silence to non-silence)
-                            let mut previous_rms: Option<f32> = None;
-                            let mut silence_detected_count = 0u64;
-                            
-                            info!("Auto-transcription thread started, interval: {}s (real-time mode, no audio loss)", TRANSCRIBE_INTERVAL_SECS);
-                            info!("📊 [Auto-transcription] Resampler initialized: {}kHz -> {}kHz", SYSTEM_AUDIO_SAMPLE_RATE, TARGET_SAMPLE_RATE);
-                            let _ = app_handle.emit("log-update", format!("✅ [Auto-transcription] Thread started - waiting for audio samples..."));
-                            
-                            loop {
-                                std::thread::sleep(Duration::from_secs(TRANSCRIBE_INTERVAL_SECS));
-                                
-                                // Check if still in always-on mode
-                                let settings = crate::settings::get_settings(&app_handle);
-                                if !settings.always_on_microphone {
-                                    info!("Always-on mode disabled, stopping auto-transcription");
-                                    break;
-                                }
-                                
-                                // Check if audio source is still SystemAudio (may have changed)
-                                let audio_source = settings.audio_source.unwrap_or(crate::settings::AudioSource::Microphone);
-                                if audio_source != crate::settings::AudioSource::SystemAudio {
-                                    info!("Audio source changed from SystemAudio to {:?}, stopping auto-transcription", audio_source);
-                                    break;
-                                }
-                                
-                                // Ensure recording is active (for system audio, this just ensures buffer is ready)
-                                if !*rm.is_recording.lock().unwrap() {
-                                    if !rm.try_start_recording(&binding_id) {
-                                        warn!("Failed to restart recording in always-on mode");
-                                        break;
-                                    }
-                                }
-                                
-                                // Read new samples from system capture buffer and add to accumulation buffer
-                                let new_samples = {
-                                    #[cfg(any(target_os = "macos", target_os = "windows"))]
-                                    {
-                                        if let Some(capture) = rm.system_capture.lock().unwrap().as_mut() {
-                                                match capture.read_samples() {
-                                                Ok(Some(s)) => {
-                                                    if !s.is_empty() {
-                                                        info!("🎙️ [Auto-transcription] ✅ Read {} new samples from system capture ({}s audio)", s.len(), s.len() / 16000);
-                                                        // Don't emit log-update for every read - too frequent, causes UI lag
-                                                        // Only log to backend, frontend doesn't need to know every read
-                                                        Some(s)
-                                                    } else {
-                                                        debug!("Auto-transcription: System capture returned empty samples");
-                                                        None
-                                                    }
-                                                },
-                                                Ok(None) => {
-                                                    // Buffer is empty - this is normal if no audio is playing
-                                                    // Log periodically to show we're checking
-                                                    static EMPTY_COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
-                                                    let count = EMPTY_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                                                    if count % 10 == 0 {
-                                                        info!("🔍 [Auto-transcription] System capture buffer is empty (checked {} times) - SCStream may not be sending audio buffers", count + 1);
-                                                        let _ = app_handle.emit("log-update", format!("🔍 [Auto-transcription] Buffer empty (checked {} times) - Please ensure audio is playing from Chrome or another app", count + 1));
-                                                    }
-                                                    None
-                                                },
-                                                Err(e) => {
-                                                    error!("❌ [Auto-transcription] Failed to read samples from system capture: {}", e);
-                                                    let _ = app_handle.emit("log-update", format!("❌ [Auto-transcription] Failed to read samples: {}", e));
-                                                    None
-                                                }
-                                            }
-                                        } else {
-                                            warn!("⚠️ [Auto-transcription] System capture not available");
-                                            let _ = app_handle.emit("log-update", "⚠️ [Auto-transcription] System capture not available");
-                                            None
-                                        }
-                                    }
-                                    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-                                    {
-                                        None
-                                    }
-                                };
-                                
-                                // Resample and add new samples to accumulation buffer
-                                if let Some(new_samples) = new_samples {
-                                    let input_count = new_samples.len();
-                                    
-                                    // Resample from 48kHz to 16kHz
-                                    let mut resampled_samples = Vec::new();
-                                    resampler.push(&new_samples, |chunk| {
-                                        resampled_samples.extend_from_slice(chunk);
-                                    });
-                                    
-                                    let resampled_count = resampled_samples.len();
-                                    accumulated_buffer.extend(resampled_samples);
-                                    let total_count = accumulated_buffer.len();
-                                    
-                                    info!("📥 [Auto-transcription] Resampled {} samples (48kHz) -> {} samples (16kHz), total buffer: {} samples ({}s)", 
-                                        input_count,
-                                        resampled_count,
-                                        total_count, 
-                                        total_count / 16000);
-                                    
-                                    // Don't emit log-update for resampling - too frequent, causes UI lag
-                                    // Only log to backend
-                                } else {
-                                    // Log periodically when no samples are available
-                                    static NO_SAMPLES_COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
-                                    let count = NO_SAMPLES_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                                    if count % 20 == 0 {
-                                        warn!("Auto-transcription: No audio samples available (checked {} times). Please check Screen Recording permission!", count + 1);
-                                        let _ = app_handle.emit("log-update", format!("⚠️ [Auto-transcription] No audio samples available (checked {} times)", count + 1));
-                                    }
-                                }
-                                
-                                // Only transcribe if we have enough audio (minimum 2 seconds)
-                                let current_buffer_size = accumulated_buffer.len();
-                                if current_buffer_size >= MIN_SAMPLES {
-                                    info!("✅ [Auto-transcription] Buffer has {} samples ({}s), MIN_SAMPLES={}, ready to transcribe!", 
-                                        current_buffer_size, 
-                                        current_buffer_size / 16000,
-                                        MIN_SAMPLES);
-                                    let _ = app_handle.emit("log-update", format!("🔄 [Auto-transcription] Buffer ready: {}s audio, starting transcription...", current_buffer_size / 16000));
-                                    // Take samples for transcription (keep overlap for next iteration)
-                                    let mut samples_to_transcribe: Vec<f32> = if accumulated_buffer.len() > OVERLAP_SAMPLES {
-                                        // Take all except overlap samples
-                                        let take_count = accumulated_buffer.len() - OVERLAP_SAMPLES;
-                                        accumulated_buffer.drain(..take_count).collect()
-                                    } else {
-                                        // Not enough for overlap, take all
-                                        accumulated_buffer.drain(..).collect()
-                                    };
-                                    
-                                        if !samples_to_transcribe.is_empty() {
-                                            // Calculate RMS level to check if audio has actual sound
-                                            let rms = (samples_to_transcribe.iter()
-                                                .map(|&s| s * s)
-                                                .sum::<f32>() / samples_to_transcribe.len() as f32)
-                                                .sqrt();
-                                            let max_amplitude = samples_to_transcribe.iter()
-                                                .map(|&s| s.abs())
-                                                .fold(0.0f32, |a, b| a.max(b));
-                                            
-                                            info!("🎙️ [Auto-transcription] Processing {} samples ({}s audio, {}s overlap kept) - RMS: {:.6}, Max: {:.6}",
-                                                samples_to_transcribe.len(),
-                                                samples_to_transcribe.len() / 16000,
-                                                accumulated_buffer.len() / 16000,
-                                                rms,
-                                                max_amplitude);
-                                            
-                                            // Detect transition from silence to non-silence
-                                            let was_silent = previous_rms.map(|pr| pr < 0.00001).unwrap_or(true);
-                                            let is_now_audio = rms > 0.00001;
-                                            
-                                            if was_silent && is_now_audio {
-                                                info!("🎉 [Auto-transcription] ✅✅✅ AUDIO DETECTED! Audio transitioned from silence to active! RMS: {:.6}, Max: {:.6}", rms, max_amplitude);
-                                                let _ = app_handle.emit("log-update", format!("🎉 [Auto-transcription] ✅✅✅ AUDIO DETECTED! RMS: {:.6}, Max: {:.6} - Live caption will start working now!", rms, max_amplitude));
-                                            }
-                                            
-                                            // Warn if audio seems silent
-                                            if rms < 0.00001 && max_amplitude < 0.01 {
-                                                silence_detected_count += 1;
-                                                if silence_detected_count == 1 {
-                                                    // First detection - emit clear instructions
-                                                    warn!("⚠️ [Auto-transcription] Audio is SILENT (RMS: {:.6}, Max: {:.6}). BlackHole is capturing but no audio is flowing.", rms, max_amplitude);
-                                                    let _ = app_handle.emit("log-update", "⚠️ [Config] Audio is SILENT! Please configure Sound Output:");
-                                                    let _ = app_handle.emit("log-update", "   1. Open System Settings > Sound");
-                                                    let _ = app_handle.emit("log-update", "   2. Set Output to 'BlackHole 2ch' OR create Multi-Output Device");
-                                                    let _ = app_handle.emit("log-update", "   3. See HUONG_DAN_CAI_DAT_BLACKHOLE.md for details");
-                                                } else if silence_detected_count % 10 == 0 {
-                                                    // Periodic reminder
-                                                    warn!("⚠️ [Auto-transcription] Audio still silent (checked {} times). RMS: {:.6}, Max: {:.6}", silence_detected_count, rms, max_amplitude);
-                                                    let _ = app_handle.emit("log-update", format!("⚠️ [Config] Still silent ({} checks). Set Sound Output to BlackHole 2ch!", silence_detected_count));
-                                                }
-                                            } else {
-                                                // Reset silence counter when audio is detected
-                                                if silence_detected_count > 0 {
-                                                    info!("🎉 [Auto-transcription] ✅✅✅ AUDIO DETECTED after {} silent checks! RMS: {:.6}, Max: {:.6}", silence_detected_count, rms, max_amplitude);
-                                                    let _ = app_handle.emit("log-update", format!("🎉 [Auto-transcription] ✅✅✅ AUDIO DETECTED! RMS: {:.6} - Live caption will work now!", rms));
-                                                    silence_detected_count = 0;
-                                                }
-                                            }
-                                            
-                                            // Update previous RMS for next iteration
-                                            previous_rms = Some(rms);
-                                        
-                                        // Don't emit log-update for processing - too frequent, causes UI lag
-                                        // Only log to backend
-                                
-                                        // Trigger transcription
-                                        let tm = app_handle.state::<Arc<crate::managers::transcription::TranscriptionManager>>();
-                                        let hm = app_handle.state::<Arc<crate::managers::history::HistoryManager>>();
-                                        let samples_clone = samples_to_transcribe.clone();
-                                    
-                                        // Ensure model is loaded before transcribing
-                                        tm.initiate_model_load();
-                                        
-                                        // Wait for model to load (with timeout)
-                                        let mut wait_count = 0;
-                                        const MAX_WAIT: u32 = 20; // Max 10 seconds (20 * 500ms)
-                                        while !tm.is_model_loaded() && wait_count < MAX_WAIT {
-                                            std::thread::sleep(Duration::from_millis(500));
-                                            wait_count += 1;
-                                        }
-                                        
-                                        if !tm.is_model_loaded() {
-                                            warn!("Model still not loaded after waiting, skipping transcription");
-                                            let _ = app_handle.emit("log-update", "⚠️ [Auto-transcription] Model still not loaded after waiting, skipping transcription");
-                                            continue;
-                                        }
-                                        
-                                        info!("🔄 [Auto-transcription] Starting transcription for {} samples ({}s)", 
-                                            samples_to_transcribe.len(),
-                                            samples_to_transcribe.len() / 16000);
-                                        
-                                        // Apply audio preprocessing to improve transcription quality
-                                        // Similar to what Google Translate does: normalize, remove DC offset, high-pass filter
-                                        preprocess_audio(&mut samples_to_transcribe, TARGET_SAMPLE_RATE);
-                                        
-                                        // Don't emit log-update for starting transcription - too frequent, causes UI lag
-                                        // Only log to backend
-                                        
-                                        match tm.transcribe(samples_to_transcribe) {
-                                            Ok(transcription) => {
-                                                let trimmed = transcription.trim();
-                                                info!("📝 [Auto-transcription] Raw transcription received (len={}): '{}'", transcription.len(), transcription);
-                                                
-                                                // Emit log for debugging - short and smart
-                                                if !trimmed.is_empty() {
-                                                    let _ = app_handle.emit("log-update", format!("📝 [Transcription] Result ({} chars): {}", trimmed.len(), trimmed.chars().take(50).collect::<String>()));
-                                                } else {
-                                                    let _ = app_handle.emit("log-update", format!("⚠️ [Transcription] Empty result (RMS: {:.6})", previous_rms.unwrap_or(0.0)));
-                                                }
-                                                
-                                                // Always log transcription results - this is important!
-                                                if !trimmed.is_empty() && trimmed.len() > 1 {
-                                                    // Only process if transcription has meaningful content (more than 1 char)
-                                                    info!("🎯 [Auto-transcription] Result (len={}): '{}'", trimmed.len(), trimmed);
-                                                    
-                                                    // Emit log event
-                                                    // Don't emit log-update for result - already emitted via live-caption-update
-                                                    // Only log to backend
-                                                    
-                                                    // Save to history (async)
-                                                    let hm_clone = Arc::clone(&hm);
-                                                    let transcription_clone = trimmed.to_string();
-                                                    let samples_clone2 = samples_clone.clone();
-                                                    tauri::async_runtime::spawn(async move {
-                                                        if let Err(e) = hm_clone.save_transcription(
-                                                            samples_clone2,
-                                                            transcription_clone.clone(),
-                                                            None,
-                                                            None,
-                                                        ).await {
-                                                            error!("Failed to save auto-transcription to history: {}", e);
-                                                        }
-                                                    });
-                                                    
-                                                    // Emit live caption event to frontend
-                                                    info!("📤 [LiveCaption] Emitting event with caption ({} chars): '{}'", trimmed.len(), trimmed);
-                                                    
-                                                    // Emit log for debugging - short and smart
-                                                    let _ = app_handle.emit("log-update", format!("✅ [LiveCaption] Caption ({} chars): {}", trimmed.len(), trimmed.chars().take(50).collect::<String>()));
-                                                    
-                                                    // Don't emit log-update for every caption - too frequent, causes UI lag
-                                                    // Only emit the actual caption event
-                                                    if let Err(e) = app_handle.emit("live-caption-update", trimmed.to_string()) {
-                                                        error!("❌ [LiveCaption] Failed to emit live-caption-update event: {}", e);
-                                                        let _ = app_handle.emit("log-update", format!("❌ [LiveCaption] Failed to emit: {}", e));
-                                                    } else {
-                                                        info!("✅ [LiveCaption] Successfully emitted live-caption-update event");
-                                                    }
-                                                    
-                                                    // Paste the transcription
-                                                    if let Err(e) = crate::utils::paste(trimmed.to_string(), app_handle.clone()) {
-                                                        error!("Failed to paste auto-transcription: {}", e);
-                                                    }
-                                                }
-                                            }
-                                           Err(e) => {
-                                               error!("Auto-transcription failed: {}", e);
-                                           }
-                                       }
-                                    }
-                                }
-                                // Continue loop - accumulation buffer keeps growing, no audio loss
-                            }
-                        });
-                    }
-                }
-                
-                return Ok(());
-            }
-            
-            // System Audio Capture - Windows
-            #[cfg(target_os = "windows")]
-            {
-                info!("Initializing system audio capture (Windows WASAPI)");
-                let mut capture = WindowsSystemAudio::new(&self.app_handle)?;
-                match capture.start_capture() {
-                    Ok(()) => {
-                        *self.system_capture.lock().unwrap() = Some(Box::new(capture));
-                        *open_flag = true;
-                        info!(
-                            "System audio capture initialized in {:?}",
-                            start_time.elapsed()
-                        );
-                    },
-                    Err(e) => {
-                        error!("Failed to start system audio capture: {}", e);
-                        *open_flag = false;
-                        return Err(e);
-                    }
-                }
-                
-                // Auto-start recording in always-on mode with system audio
-                let settings = get_settings(&self.app_handle);
-                if settings.always_on_microphone {
-                    info!("Always-on mode: Auto-starting continuous system audio transcription");
-                    let binding_id = "transcribe".to_string();
-                    if self.try_start_recording(&binding_id) {
-                        info!("Auto-started recording in always-on mode");
-                        
-                        // Start continuous transcription loop with sliding window (no audio loss like Google Translate)
-                        // This is the same implementation as macOS
-                        let app_handle = self.app_handle.clone();
-                        let rm = Arc::new(self.clone());
-                        std::thread::spawn(move || {
-                            use std::time::Duration;
-                            use std::collections::VecDeque;
-                            use crate::audio_toolkit::audio::FrameResampler;
-                            
-                            const TRANSCRIBE_INTERVAL_SECS: u64 = 3;
-                            const MIN_AUDIO_SECS: usize = 2;
-                            const OVERLAP_SECS: usize = 1;
-                            const MIN_SAMPLES: usize = MIN_AUDIO_SECS * 16000;
-                            const OVERLAP_SAMPLES: usize = OVERLAP_SECS * 16000;
-                            const SYSTEM_AUDIO_SAMPLE_RATE: usize = 48000;
-                            const TARGET_SAMPLE_RATE: usize = 16000;
-                            
-                            let mut resampler = FrameResampler::new(
-                                SYSTEM_AUDIO_SAMPLE_RATE,
-                                TARGET_SAMPLE_RATE,
-                                Duration::from_millis(30),
-                            );
-                            
-                            let mut accumulated_buffer: VecDeque<f32> = VecDeque::new();
-                            let mut previous_rms: Option<f32> = None;
-                            let mut silence_detected_count = 0u64;
-                            
-                            info!("Windows auto-transcription thread started, interval: {}s", TRANSCRIBE_INTERVAL_SECS);
-                            info!("📊 [Auto-transcription] Resampler initialized: {}kHz -> {}kHz", SYSTEM_AUDIO_SAMPLE_RATE, TARGET_SAMPLE_RATE);
-                            let _ = app_handle.emit("log-update", format!("✅ [Auto-transcription] Thread started - waiting for audio samples..."));
-                            
-                            loop {
-                                std::thread::sleep(Duration::from_secs(TRANSCRIBE_INTERVAL_SECS));
-                                
-                                let settings = crate::settings::get_settings(&app_handle);
-                                if !settings.always_on_microphone {
-                                    info!("Always-on mode disabled, stopping auto-transcription");
-                                    break;
-                                }
-                                
-                                let audio_source = settings.audio_source.unwrap_or(crate::settings::AudioSource::Microphone);
-                                if audio_source != crate::settings::AudioSource::SystemAudio {
-                                    info!("Audio source changed from SystemAudio, stopping auto-transcription");
-                                    break;
-                                }
-                                
-                                if !*rm.is_recording.lock().unwrap() {
-                                    if !rm.try_start_recording(&binding_id) {
-                                        warn!("Failed to restart recording in always-on mode");
-                                        break;
-                                    }
-                                }
-                                
-                                let new_samples = {
-                                    if let Some(capture) = rm.system_capture.lock().unwrap().as_mut() {
-                                        match capture.read_samples() {
-                                            Ok(Some(s)) => {
-                                                if !s.is_empty() {
-                                                    info!("🎙️ [Auto-transcription] ✅ Read {} new samples from system capture ({}s audio)", s.len(), s.len() / 16000);
-                                                    Some(s)
-                                                } else {
-                                                    debug!("Auto-transcription: System capture returned empty samples");
-                                                    None
-                                                }
-                                            },
-                                            Ok(None) => {
-                                                static EMPTY_COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
-                                                let count = EMPTY_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                                                if count % 10 == 0 {
-                                                    info!("🔍 [Auto-transcription] System capture buffer is empty (checked {} times)", count + 1);
-                                                    let _ = app_handle.emit("log-update", format!("🔍 [Auto-transcription] Buffer empty (checked {} times) - Please ensure audio is playing", count + 1));
-                                                }
-                                                None
-                                            },
-                                            Err(e) => {
-                                                error!("❌ [Auto-transcription] Failed to read samples: {}", e);
-                                                let _ = app_handle.emit("log-update", format!("❌ [Auto-transcription] Failed to read samples: {}", e));
-                                                None
-                                            }
-                                        }
-                                    } else {
-                                        warn!("⚠️ [Auto-transcription] System capture not available");
-                                        None
-                                    }
-                                };
-                                
-                                if let Some(new_samples) = new_samples {
-                                    let input_count = new_samples.len();
-                                    let mut resampled_samples = Vec::new();
-                                    resampler.push(&new_samples, |chunk| {
-                                        resampled_samples.extend_from_slice(chunk);
-                                    });
-                                    
-                                    let resampled_count = resampled_samples.len();
-                                    accumulated_buffer.extend(resampled_samples);
-                                    let total_count = accumulated_buffer.len();
-                                    
-                                    info!("📥 [Auto-transcription] Resampled {} samples (48kHz) -> {} samples (16kHz), total buffer: {} samples ({}s)", 
-                                        input_count, resampled_count, total_count, total_count / 16000);
-                                } else {
-                                    static NO_SAMPLES_COUNT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
-                                    let count = NO_SAMPLES_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                                    if count % 20 == 0 {
-                                        warn!("Auto-transcription: No audio samples available (checked {} times)", count + 1);
-                                        let _ = app_handle.emit("log-update", format!("⚠️ [Auto-transcription] No audio samples available (checked {} times)", count + 1));
-                                    }
-                                }
-                                
-                                let current_buffer_size = accumulated_buffer.len();
-                                if current_buffer_size >= MIN_SAMPLES {
-                                    info!("✅ [Auto-transcription] Buffer has {} samples ({}s), ready to transcribe!", 
-                                        current_buffer_size, current_buffer_size / 16000);
-                                    let _ = app_handle.emit("log-update", format!("🔄 [Auto-transcription] Buffer ready: {}s audio", current_buffer_size / 16000));
-                                    
-                                    let mut samples_to_transcribe: Vec<f32> = if accumulated_buffer.len() > OVERLAP_SAMPLES {
-                                        let take_count = accumulated_buffer.len() - OVERLAP_SAMPLES;
-                                        accumulated_buffer.drain(..take_count).collect()
-                                    } else {
-                                        accumulated_buffer.drain(..).collect()
-                                    };
-                                    
-                                    if !samples_to_transcribe.is_empty() {
-                                        let rms = (samples_to_transcribe.iter()
-                                            .map(|&s| s * s)
-                                            .sum::<f32>() / samples_to_transcribe.len() as f32)
-                                            .sqrt();
-                                        let max_amplitude = samples_to_transcribe.iter()
-                                            .map(|&s| s.abs())
-                                            .fold(0.0f32, |a, b| a.max(b));
-                                        
-                                        info!("🎙️ [Auto-transcription] Processing {} samples ({}s audio) - RMS: {:.6}, Max: {:.6}",
-                                            samples_to_transcribe.len(), samples_to_transcribe.len() / 16000, rms, max_amplitude);
-                                        
-                                        let was_silent = previous_rms.map(|pr| pr < 0.00001).unwrap_or(true);
-                                        let is_now_audio = rms > 0.00001;
-                                        
-                                        if was_silent && is_now_audio {
-                                            info!("🎉 [Auto-transcription] ✅ AUDIO DETECTED! RMS: {:.6}, Max: {:.6}", rms, max_amplitude);
-                                            let _ = app_handle.emit("log-update", format!("🎉 [Auto-transcription] ✅ AUDIO DETECTED! RMS: {:.6}", rms));
-                                        }
-                                        
-                                        if rms < 0.00001 && max_amplitude < 0.01 {
-                                            silence_detected_count += 1;
-                                            if silence_detected_count == 1 {
-                                                warn!("⚠️ [Auto-transcription] Audio is SILENT (RMS: {:.6})", rms);
-                                                let _ = app_handle.emit("log-update", "⚠️ [Config] Audio is SILENT! Please play audio from Chrome/Spotify");
-                                            }
-                                        } else {
-                                            if silence_detected_count > 0 {
-                                                info!("🎉 [Auto-transcription] ✅ AUDIO DETECTED after {} silent checks!", silence_detected_count);
-                                                let _ = app_handle.emit("log-update", format!("🎉 [Auto-transcription] ✅ AUDIO DETECTED! RMS: {:.6}", rms));
-                                                silence_detected_count = 0;
-                                            }
-                                        }
-                                        
-                                        previous_rms = Some(rms);
-                                        
-                                        let tm = app_handle.state::<Arc<crate::managers::transcription::TranscriptionManager>>();
-                                        let hm = app_handle.state::<Arc<crate::managers::history::HistoryManager>>();
-                                        let samples_clone = samples_to_transcribe.clone();
-                                        
-                                        tm.initiate_model_load();
-                                        
-                                        let mut wait_count = 0;
-                                        const MAX_WAIT: u32 = 20;
-                                        while !tm.is_model_loaded() && wait_count < MAX_WAIT {
-                                            std::thread::sleep(Duration::from_millis(500));
-                                            wait_count += 1;
-                                        }
-                                        
-                                        if !tm.is_model_loaded() {
-                                            warn!("Model still not loaded after waiting, skipping transcription");
-                                            let _ = app_handle.emit("log-update", "⚠️ [Auto-transcription] Model not loaded, skipping");
-                                            continue;
-                                        }
-                                        
-                                        info!("🔄 [Auto-transcription] Starting transcription for {} samples", samples_to_transcribe.len());
-                                        
-                                        // Apply audio preprocessing to improve transcription quality
-                                        preprocess_audio(&mut samples_to_transcribe, TARGET_SAMPLE_RATE);
-                                        
-                                        match tm.transcribe(samples_to_transcribe) {
-                                            Ok(transcription) => {
-                                                let trimmed = transcription.trim();
-                                                info!("📝 [Auto-transcription] Raw transcription (len={}): '{}'", transcription.len(), transcription);
-                                                
-                                                if !trimmed.is_empty() {
-                                                    let _ = app_handle.emit("log-update", format!("📝 [Transcription] Result: {}", trimmed.chars().take(50).collect::<String>()));
-                                                }
-                                                
-                                                if !trimmed.is_empty() && trimmed.len() > 1 {
-                                                    info!("🎯 [Auto-transcription] Result: '{}'", trimmed);
-                                                    
-                                                    let hm_clone = Arc::clone(&hm);
-                                                    let transcription_clone = trimmed.to_string();
-                                                    let samples_clone2 = samples_clone.clone();
-                                                    tauri::async_runtime::spawn(async move {
-                                                        if let Err(e) = hm_clone.save_transcription(
-                                                            samples_clone2,
-                                                            transcription_clone.clone(),
-                                                            None,
-                                                            None,
-                                                        ).await {
-                                                            error!("Failed to save auto-transcription to history: {}", e);
-                                                        }
-                                                    });
-                                                    
-                                                    info!("📤 [LiveCaption] Emitting event with caption: '{}'", trimmed);
-                                                    let _ = app_handle.emit("log-update", format!("✅ [LiveCaption] Caption: {}", trimmed.chars().take(50).collect::<String>()));
-                                                    
-                                                    if let Err(e) = app_handle.emit("live-caption-update", trimmed.to_string()) {
-                                                        error!("❌ [LiveCaption] Failed to emit: {}", e);
-                                                    } else {
-                                                        info!("✅ [LiveCaption] Successfully emitted live-caption-update event");
-                                                    }
-                                                    
-                                                    if let Err(e) = crate::utils::paste(trimmed.to_string(), app_handle.clone()) {
-                                                        error!("Failed to paste auto-transcription: {}", e);
-                                                    }
-                                                }
-                                            }
-                                            Err(e) => {
-                                                error!("Auto-transcription failed: {}", e);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        });
-                    }
-                }
-                
-                return Ok(());
-            }
-            
-            #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-            {
-                return Err(anyhow::anyhow!("System audio capture not supported on this platform"));
-            }
+            let capture = self.captures.open(
+                settings.system_audio_buffer_seconds,
+                settings.system_audio_downmix.downmix(),
+            )?;
+            *self.system_capture.lock().unwrap() = Some(capture);
+            *open_flag = true;
+            info!(
+                "System audio capture initialized in {:?}",
+                start_time.elapsed()
+            );
+            // Auto-captioning starts a recording, which checks the flag
+            drop(open_flag);
+
+            self.start_system_audio_captions();
+            return Ok(());
         }
 
         // Regular Microphone Capture
-        let vad_path = self
-            .app_handle
-            .path()
-            .resolve(
-                "resources/models/silero_vad_v4.onnx",
-                tauri::path::BaseDirectory::Resource,
-            )
-            .map_err(|e| anyhow::anyhow!("Failed to resolve VAD path: {}", e))?;
         // Lazy load VAD model - only create recorder when needed to avoid blocking
         // This prevents UI lag when switching audio sources
         let mut recorder_opt = self.recorder.lock().unwrap();
@@ -976,229 +336,61 @@ impl AudioRecordingManager {
         if recorder_opt.is_none() {
             info!("🔄 [AudioSource] Loading VAD model (this may take a moment)...");
             let start_vad = Instant::now();
-            *recorder_opt = Some(create_audio_recorder(
-                vad_path.to_str().unwrap(),
-                &self.app_handle,
-            )?);
+            *recorder_opt = Some(self.recorders.create()?);
             info!("✅ [AudioSource] VAD model loaded in {:?}", start_vad.elapsed());
         }
 
         // Get the selected device from settings, considering clamshell mode
-        let selected_device = self.get_effective_microphone_device(&settings);
+        let selected_device = self.get_effective_microphone(&settings);
 
         if let Some(rec) = recorder_opt.as_mut() {
-            rec.open(selected_device)
-                .map_err(|e| anyhow::anyhow!("Failed to open recorder: {}", e))?;
+            open_with_retry(rec.as_mut(), selected_device.as_deref(), &settings)?;
         }
+        // Release the recorder before try_start_recording() locks it again
+        drop(recorder_opt);
 
         *open_flag = true;
         info!(
             "Microphone stream initialized in {:?}",
             start_time.elapsed()
         );
-        
+        drop(open_flag);
+
         // Auto-start recording in always-on mode with microphone
-        let settings = get_settings(&self.app_handle);
+        let settings = self.host.settings();
         if settings.always_on_microphone {
             info!("Always-on mode: Auto-starting continuous microphone transcription");
             let binding_id = "transcribe".to_string();
             if self.try_start_recording(&binding_id) {
                 info!("Auto-started microphone recording in always-on mode");
-                
+
                 // Start continuous transcription loop for microphone (similar to system audio)
-                let app_handle = self.app_handle.clone();
-                let rm = Arc::new(self.clone());
-                std::thread::spawn(move || {
-                    use std::time::Duration;
-                    use std::collections::VecDeque;
-                    
-                    const TRANSCRIBE_INTERVAL_SECS: u64 = 3;
-                    const MIN_AUDIO_SECS: usize = 2;
-                    const OVERLAP_SECS: usize = 1;
-                    const MIN_SAMPLES: usize = MIN_AUDIO_SECS * 16000;
-                    const OVERLAP_SAMPLES: usize = OVERLAP_SECS * 16000;
-                    
-                    let mut accumulated_buffer: VecDeque<f32> = VecDeque::new();
-                    let mut previous_rms: Option<f32> = None;
-                    let mut silence_detected_count = 0u64;
-                    
-                    info!("🎤 [Mic Auto-transcription] Thread started, interval: {}s", TRANSCRIBE_INTERVAL_SECS);
-                    let _ = app_handle.emit("log-update", "✅ [Mic Auto-transcription] Thread started - waiting for audio...".to_string());
-                    
-                    loop {
-                        std::thread::sleep(Duration::from_secs(TRANSCRIBE_INTERVAL_SECS));
-                        
-                        let settings = crate::settings::get_settings(&app_handle);
-                        if !settings.always_on_microphone {
-                            info!("Always-on mode disabled, stopping mic auto-transcription");
-                            break;
-                        }
-                        
-                        let audio_source = settings.audio_source.unwrap_or(crate::settings::AudioSource::Microphone);
-                        if audio_source != crate::settings::AudioSource::Microphone {
-                            info!("Audio source changed from Microphone, stopping auto-transcription");
-                            break;
-                        }
-                        
-                        if !*rm.is_recording.lock().unwrap() {
-                            if !rm.try_start_recording(&binding_id) {
-                                warn!("Failed to restart microphone recording in always-on mode");
-                                break;
-                            }
-                        }
-                        
-                        // Read samples from microphone recorder
-                        let new_samples = {
-                            if let Some(rec) = rm.recorder.lock().unwrap().as_mut() {
-                                // Use read_samples() to get continuous buffer without stopping
-                                // This ensures no audio loss (like system audio)
-                                match rec.read_samples() {
-                                    Ok(samples) => {
-                                        if !samples.is_empty() {
-                                            info!("🎤 [Mic Auto-transcription] ✅ Read {} samples ({}s audio)", samples.len(), samples.len() / 16000);
-                                            Some(samples)
-                                        } else {
-                                            debug!("Mic recorder returned empty samples");
-                                            None
-                                        }
-                                    },
-                                    Err(e) => {
-                                        error!("❌ [Mic Auto-transcription] Failed to read samples: {}", e);
-                                        None
-                                    }
-                                }
-                            } else {
-                                warn!("⚠️ [Mic Auto-transcription] Recorder not available");
-                                None
-                            }
-                        };
-                        
-                        // Microphone samples are already at 16kHz, no resampling needed
-                        if let Some(new_samples) = new_samples {
-                            accumulated_buffer.extend(new_samples);
-                            let total_count = accumulated_buffer.len();
-                            info!("📥 [Mic Auto-transcription] Accumulated {} samples ({}s)", total_count, total_count / 16000);
-                        }
-                        
-                        let current_buffer_size = accumulated_buffer.len();
-                        if current_buffer_size >= MIN_SAMPLES {
-                            info!("✅ [Mic Auto-transcription] Buffer has {} samples ({}s), ready to transcribe!", 
-                                current_buffer_size, current_buffer_size / 16000);
-                            
-                            let mut samples_to_transcribe: Vec<f32> = if accumulated_buffer.len() > OVERLAP_SAMPLES {
-                                let take_count = accumulated_buffer.len() - OVERLAP_SAMPLES;
-                                accumulated_buffer.drain(..take_count).collect()
-                            } else {
-                                accumulated_buffer.drain(..).collect()
-                            };
-                            
-                            if !samples_to_transcribe.is_empty() {
-                                let rms = (samples_to_transcribe.iter()
-                                    .map(|&s| s * s)
-                                    .sum::<f32>() / samples_to_transcribe.len() as f32)
-                                    .sqrt();
-                                let max_amplitude = samples_to_transcribe.iter()
-                                    .map(|&s| s.abs())
-                                    .fold(0.0f32, |a, b| a.max(b));
-                                
-                                info!("🎤 [Mic Auto-transcription] Processing {} samples ({}s) - RMS: {:.6}, Max: {:.6}",
-                                    samples_to_transcribe.len(), samples_to_transcribe.len() / 16000, rms, max_amplitude);
-                                
-                                let was_silent = previous_rms.map(|pr| pr < 0.00001).unwrap_or(true);
-                                let is_now_audio = rms > 0.00001;
-                                
-                                if was_silent && is_now_audio {
-                                    info!("🎉 [Mic Auto-transcription] ✅ AUDIO DETECTED! RMS: {:.6}", rms);
-                                    let _ = app_handle.emit("log-update", format!("🎉 [Mic] AUDIO DETECTED! RMS: {:.6}", rms));
-                                }
-                                
-                                if rms < 0.00001 && max_amplitude < 0.01 {
-                                    silence_detected_count += 1;
-                                    if silence_detected_count == 1 {
-                                        warn!("⚠️ [Mic Auto-transcription] Audio is SILENT (RMS: {:.6})", rms);
-                                    }
-                                } else {
-                                    if silence_detected_count > 0 {
-                                        info!("🎉 [Mic Auto-transcription] ✅ AUDIO DETECTED after {} silent checks!", silence_detected_count);
-                                        silence_detected_count = 0;
-                                    }
-                                }
-                                
-                                previous_rms = Some(rms);
-                                
-                                let tm = app_handle.state::<Arc<crate::managers::transcription::TranscriptionManager>>();
-                                let hm = app_handle.state::<Arc<crate::managers::history::HistoryManager>>();
-                                let samples_clone = samples_to_transcribe.clone();
-                                
-                                tm.initiate_model_load();
-                                
-                                let mut wait_count = 0;
-                                const MAX_WAIT: u32 = 20;
-                                while !tm.is_model_loaded() && wait_count < MAX_WAIT {
-                                    std::thread::sleep(Duration::from_millis(500));
-                                    wait_count += 1;
-                                }
-                                
-                                if !tm.is_model_loaded() {
-                                    warn!("Model still not loaded after waiting, skipping transcription");
-                                    continue;
-                                }
-                                
-                                info!("🔄 [Mic Auto-transcription] Starting transcription for {} samples", samples_to_transcribe.len());
-                                
-                                // Apply audio preprocessing to improve transcription quality
-                                // Mic already at 16kHz (same as Whisper requirement)
-                                preprocess_audio(&mut samples_to_transcribe, 16000);
-                                
-                                match tm.transcribe(samples_to_transcribe) {
-                                    Ok(transcription) => {
-                                        let trimmed = transcription.trim();
-                                        info!("📝 [Mic Auto-transcription] Raw transcription (len={}): '{}'", transcription.len(), transcription);
-                                        
-                                        if !trimmed.is_empty() && trimmed.len() > 1 {
-                                            info!("🎯 [Mic Auto-transcription] Result: '{}'", trimmed);
-                                            
-                                            let hm_clone = Arc::clone(&hm);
-                                            let transcription_clone = trimmed.to_string();
-                                            let samples_clone2 = samples_clone.clone();
-                                            tauri::async_runtime::spawn(async move {
-                                                if let Err(e) = hm_clone.save_transcription(
-                                                    samples_clone2,
-                                                    transcription_clone.clone(),
-                                                    None,
-                                                    None,
-                                                ).await {
-                                                    error!("Failed to save mic auto-transcription to history: {}", e);
-                                                }
-                                            });
-                                            
-                                            info!("📤 [Mic LiveCaption] Emitting event with caption: '{}'", trimmed);
-                                            
-                                            if let Err(e) = app_handle.emit("live-caption-update", trimmed.to_string()) {
-                                                error!("❌ [Mic LiveCaption] Failed to emit: {}", e);
-                                            } else {
-                                                info!("✅ [Mic LiveCaption] Successfully emitted live-caption-update event");
-                                            }
-                                            
-                                            if let Err(e) = crate::utils::paste(trimmed.to_string(), app_handle.clone()) {
-                                                error!("Failed to paste mic auto-transcription: {}", e);
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        error!("Mic auto-transcription failed: {}", e);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                });
+                self.spawn_caption_loop(binding_id, CaptionSource::Microphone);
             }
         }
-        
+
         Ok(())
     }
 
+    /// Start the system audio caption loop, and the microphone channel of
+    /// dual-channel captions, if auto-captioning is on
+    fn start_system_audio_captions(&self) {
+        let settings = self.host.settings();
+        if settings.auto_caption_system_audio {
+            info!("Auto-caption: Auto-starting continuous system audio transcription");
+            let binding_id = "transcribe".to_string();
+            if self.try_start_recording(&binding_id) {
+                info!("Auto-started recording in always-on mode");
+
+                // Start continuous transcription loop with sliding window (no audio loss like Google Translate)
+                self.spawn_caption_loop(binding_id, CaptionSource::SystemAudio);
+            }
+            if let Err(e) = self.start_dual_channel_mic() {
+                warn!("Dual-channel captions unavailable: {}", e);
+            }
+        }
+    }
+
     pub fn stop_microphone_stream(&self) {
         let mut open_flag = self.is_open.lock().unwrap();
         if !*open_flag {
@@ -1207,16 +399,13 @@ impl AudioRecordingManager {
 
         let mut did_mute_guard = self.did_mute.lock().unwrap();
         if *did_mute_guard {
-            set_mute(false);
+            self.restore_output();
         }
         *did_mute_guard = false;
 
         // Stop System Capture
-        #[cfg(target_os = "macos")]
-        {
-            if let Some(mut capture) = self.system_capture.lock().unwrap().take() {
-                let _ = capture.stop_capture();
-            }
+        if let Some(mut capture) = self.system_capture.lock().unwrap().take() {
+            let _ = capture.stop_capture();
         }
 
         if let Some(rec) = self.recorder.lock().unwrap().as_mut() {
@@ -1227,11 +416,14 @@ impl AudioRecordingManager {
             }
             let _ = rec.close();
         }
+        *self.dual_channel_mic.lock().unwrap() = false;
 
         // Reset recording state to Idle so we can start recording again later
         {
             let mut state = self.state.lock().unwrap();
-            *state = RecordingState::Idle;
+            if !matches!(*state, RecordingState::Idle) {
+                self.set_state(&mut state, RecordingState::Idle);
+            }
         }
 
         *open_flag = false;
@@ -1262,12 +454,87 @@ impl AudioRecordingManager {
         Ok(())
     }
 
+    /* ---------- auto-captioning -------------------------------------------- */
+
+    // Captioning state follows the settings: a caption loop runs while the
+    // active source has auto-captioning on (`auto_caption_system_audio` for
+    // system audio, `always_on_microphone` for the mic). Turning it off needs
+    // no call here; the loop sees the setting, cancels its recording and exits.
+
+    /// Start system-audio captioning after it was switched on
+    pub fn start_auto_caption(&self) -> Result<(), anyhow::Error> {
+        let settings = self.host.settings();
+        let audio_source = settings.audio_source.unwrap_or(AudioSource::Microphone);
+        if audio_source != AudioSource::SystemAudio || !settings.auto_caption_system_audio {
+            return Ok(());
+        }
+
+        if !*self.is_open.lock().unwrap() {
+            // Opening system audio starts the caption loop
+            return self.start_microphone_stream();
+        }
+
+        let binding_id = "transcribe".to_string();
+        if self.try_start_recording(&binding_id) {
+            info!("Auto-caption: Starting system audio transcription on an open stream");
+            self.spawn_caption_loop(binding_id, CaptionSource::SystemAudio);
+        }
+        Ok(())
+    }
+
+    /// Open the microphone next to system audio and caption it as a second
+    /// channel. The recorder is only opened, not started: the caption loop
+    /// reads its continuous buffer.
+    pub fn start_dual_channel_mic(&self) -> Result<(), anyhow::Error> {
+        let settings = self.host.settings();
+        if !settings.dual_channel_active() {
+            return Ok(());
+        }
+        let mut dual_mic = self.dual_channel_mic.lock().unwrap();
+        if *dual_mic {
+            return Ok(());
+        }
+
+        let mut recorder_opt = self.recorder.lock().unwrap();
+        if recorder_opt.is_none() {
+            *recorder_opt = Some(self.recorders.create()?);
+        }
+        if let Some(rec) = recorder_opt.as_mut() {
+            open_with_retry(
+                rec.as_mut(),
+                self.get_effective_microphone(&settings).as_deref(),
+                &settings,
+            )?;
+        }
+        drop(recorder_opt);
+        *dual_mic = true;
+
+        info!("🎙️ [DualChannel] Captioning microphone alongside system audio");
+        self.spawn_caption_loop("transcribe".to_string(), CaptionSource::Microphone);
+        Ok(())
+    }
+
+    /// Close the second-channel microphone; its caption loop exits on its own
+    pub fn stop_dual_channel_mic(&self) {
+        let mut dual_mic = self.dual_channel_mic.lock().unwrap();
+        if !*dual_mic {
+            return;
+        }
+        if let Some(rec) = self.recorder.lock().unwrap().as_mut() {
+            let _ = rec.close();
+        }
+        *dual_mic = false;
+        info!("🎙️ [DualChannel] Microphone channel closed");
+    }
+
     /* ---------- recording --------------------------------------------------- */
 
     pub fn try_start_recording(&self, binding_id: &str) -> bool {
         let mut state = self.state.lock().unwrap();
 
         if let RecordingState::Idle = *state {
+            self.stopped_at_limit.lock().unwrap().take();
+
             // Ensure microphone is open in on-demand mode
             if matches!(*self.mode.lock().unwrap(), MicrophoneMode::OnDemand) {
                 if let Err(e) = self.start_microphone_stream() {
@@ -1276,23 +543,25 @@ impl AudioRecordingManager {
                 }
             }
 
-            let settings = get_settings(&self.app_handle);
+            let settings = self.host.settings();
             let audio_source = settings.audio_source.unwrap_or(AudioSource::Microphone);
 
             if audio_source == AudioSource::SystemAudio {
                 // System capture is continuous, so we just mark state.
                 // Clear any old buffer data before starting "recording" segment.
-                #[cfg(any(target_os = "macos", target_os = "windows"))]
-                {
-                    if let Some(capture) = self.system_capture.lock().unwrap().as_mut() {
-                        let _ = capture.read_samples(); // Clear buffer
-                        *self.is_recording.lock().unwrap() = true;
-                        *state = RecordingState::Recording {
+                if let Some(capture) = self.system_capture.lock().unwrap().as_mut() {
+                    let _ = capture.read_samples(); // Clear buffer
+                    *self.is_recording.lock().unwrap() = true;
+                    self.set_state(
+                        &mut state,
+                        RecordingState::Recording {
                             binding_id: binding_id.to_string(),
-                        };
-                        debug!("System recording started for binding {binding_id}");
-                        return true;
-                    }
+                            paused: false,
+                        },
+                    );
+                    self.recording_started(binding_id, &settings);
+                    debug!("System recording started for binding {binding_id}");
+                    return true;
                 }
                 error!("System capture not available");
                 return false;
@@ -1300,11 +569,31 @@ impl AudioRecordingManager {
 
             // Regular microphone recording
             if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
-                if rec.start().is_ok() {
+                let pre_roll = Duration::from_millis(settings.pre_roll_ms);
+                if rec.start_with_pre_roll(pre_roll).is_ok() {
+                    // Segments a previous stop never collected
+                    self.segments.lock().unwrap().take();
+                    self.failover_samples.lock().unwrap().clear();
+                    // Always-on captions are saved per window, so only
+                    // dictation takes need a journal
+                    if !settings.always_on_microphone {
+                        self.start_journal(rec);
+                        if settings.long_recording_segment_secs > 0 {
+                            self.start_segments(rec, &settings);
+                        }
+                    }
+                    if settings.echo_cancellation_enabled {
+                        self.start_echo_reference(rec, &settings);
+                    }
                     *self.is_recording.lock().unwrap() = true;
-                    *state = RecordingState::Recording {
-                        binding_id: binding_id.to_string(),
-                    };
+                    self.set_state(
+                        &mut state,
+                        RecordingState::Recording {
+                            binding_id: binding_id.to_string(),
+                            paused: false,
+                        },
+                    );
+                    self.recording_started(binding_id, &settings);
                     debug!("Recording started for binding {binding_id}");
                     return true;
                 }
@@ -1316,29 +605,493 @@ impl AudioRecordingManager {
         }
     }
 
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
-    pub fn get_system_audio_status(&self) -> (bool, bool) {
-        // Returns (is_open, has_audio_samples)
-        let is_open = *self.is_open.lock().unwrap();
-        let has_audio = if is_open {
-            if let Some(capture) = self.system_capture.lock().unwrap().as_mut() {
-                match capture.read_samples() {
-                    Ok(Some(samples)) => !samples.is_empty(),
-                    Ok(None) => false,
-                    Err(_) => false,
+    /// Id of the current or most recent recording, as sent with its
+    /// lifecycle events
+    pub fn recording_id(&self) -> u64 {
+        self.recording_generation.load(Ordering::SeqCst)
+    }
+
+    fn recording_started(&self, binding_id: &str, settings: &AppSettings) {
+        let recording_id = self.recording_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        *self.recording_started_at.lock().unwrap() = Some(Instant::now());
+        self.emit(
+            RecordingStarted::NAME,
+            RecordingStarted {
+                recording_id,
+                binding_id: binding_id.to_string(),
+                source: settings
+                    .audio_source
+                    .unwrap_or(AudioSource::Microphone)
+                    .into(),
+                started_at: events::now_ms(),
+            },
+        );
+        self.start_duration_guard(recording_id, binding_id, settings);
+    }
+
+    fn recording_stopped(&self, binding_id: &str, cancelled: bool) {
+        let duration = self
+            .recording_started_at
+            .lock()
+            .unwrap()
+            .take()
+            .map(|started| started.elapsed())
+            .unwrap_or_default();
+        self.emit(
+            RecordingStopped::NAME,
+            RecordingStopped {
+                recording_id: self.recording_id(),
+                binding_id: binding_id.to_string(),
+                stopped_at: events::now_ms(),
+                duration_ms: duration.as_millis() as u64,
+                cancelled,
+            },
+        );
+    }
+
+    /// Stop and transcribe the recording once it has run for
+    /// `max_recording_secs`, not counting time spent paused, so a forgotten
+    /// take can't grow into a buffer the model chokes on
+    fn start_duration_guard(&self, generation: u64, binding_id: &str, settings: &AppSettings) {
+        if settings.max_recording_secs == 0 {
+            return;
+        }
+
+        let limit = Duration::from_secs(settings.max_recording_secs);
+        let manager = self.clone();
+        let binding_id = binding_id.to_string();
+        thread::spawn(move || {
+            let mut recorded = Duration::ZERO;
+            while recorded < limit {
+                thread::sleep(DURATION_GUARD_INTERVAL);
+                if manager.recording_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                match &*manager.state.lock().unwrap() {
+                    RecordingState::Recording {
+                        binding_id: active,
+                        paused,
+                    } if *active == binding_id => {
+                        if !paused {
+                            recorded += DURATION_GUARD_INTERVAL;
+                        }
+                    }
+                    _ => return,
                 }
-            } else {
-                false
             }
+
+            warn!(
+                "Recording for {binding_id} reached the {}s limit, stopping it",
+                limit.as_secs()
+            );
+            manager.emit("recording-limit-reached", limit.as_secs());
+            if let Some(app) = manager.host.app() {
+                utils::stop_binding(app, &binding_id);
+            }
+            *manager.stopped_at_limit.lock().unwrap() = Some(binding_id);
+        });
+    }
+
+    /// Whether the duration guard already stopped `binding_id`'s recording,
+    /// so a later release or press of its shortcut has nothing to stop
+    pub fn take_stopped_at_limit(&self, binding_id: &str) -> bool {
+        let mut stopped = self.stopped_at_limit.lock().unwrap();
+        if stopped.as_deref() == Some(binding_id) {
+            *stopped = None;
+            true
         } else {
             false
+        }
+    }
+
+    fn start_journal(&self, rec: &dyn Recorder) {
+        let Some(app) = self.host.app() else {
+            return;
         };
-        (is_open, has_audio)
+        let started = journal::journal_dir(app)
+            .and_then(|dir| Ok(RecordingJournal::start(&dir, rec.recorded_frames())?));
+        match started {
+            Ok(journal) => *self.journal.lock().unwrap() = Some(journal),
+            Err(e) => warn!("Recording without a crash journal: {e}"),
+        }
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-    pub fn get_system_audio_status(&self) -> (bool, bool) {
-        (false, false)
+    fn start_segments(&self, rec: &dyn Recorder, settings: &AppSettings) {
+        let vad = match self.recorders.create_vad() {
+            Ok(vad) => vad,
+            Err(e) => {
+                warn!("Long recordings will be transcribed in one pass: {e}");
+                return;
+            }
+        };
+        let Some(transcriber) = self.transcribers.transcriber() else {
+            warn!("Long recordings will be transcribed in one pass: no transcriber yet");
+            return;
+        };
+
+        let host = Arc::clone(&self.host);
+        let segment_samples = settings.long_recording_segment_secs as usize * WHISPER_SAMPLE_RATE;
+        let segments = SegmentedTranscription::start(
+            rec.recorded_frames(),
+            vad,
+            segment_samples,
+            move |mut samples| {
+                if host.settings().denoise_enabled {
+                    let mut chain = PreprocessChain::new();
+                    chain.push(Denoiser::new());
+                    chain.process(&mut samples, WHISPER_SAMPLE_RATE);
+                }
+                transcriber.transcribe(samples)
+            },
+        );
+        *self.segments.lock().unwrap() = Some(segments);
+    }
+
+    /// Segments of the last recording transcribed while it was running
+    pub fn take_segments(&self) -> Option<SegmentedTranscription> {
+        self.segments.lock().unwrap().take()
+    }
+
+    fn finish_journal(&self) {
+        if let Some(journal) = self.journal.lock().unwrap().take() {
+            journal.finish();
+        }
+    }
+
+    /// Capture system audio alongside the microphone so the recorder can
+    /// cancel speaker feedback picked up by the mic
+    fn start_echo_reference(&self, rec: &dyn Recorder, settings: &AppSettings) {
+        let capture = match self.captures.open(
+            settings.system_audio_buffer_seconds,
+            settings.system_audio_downmix.downmix(),
+        ) {
+            Ok(capture) => capture,
+            Err(e) => {
+                warn!("Recording without echo cancellation: {e}");
+                return;
+            }
+        };
+        let Some(mut frames) = capture.subscribe_frames() else {
+            warn!("System audio capture doesn't stream frames; echo cancellation disabled");
+            return;
+        };
+
+        let reference = EchoReference::new();
+        let sink = reference.clone();
+        // Ends once the capture is dropped in stop_echo_reference
+        tauri::async_runtime::spawn(async move {
+            let mut resampler: Option<FrameResampler> = None;
+            while let Some(frame) = frames.next().await {
+                resampler
+                    .get_or_insert_with(|| {
+                        FrameResampler::new(
+                            frame.sample_rate as usize,
+                            WHISPER_SAMPLE_RATE,
+                            Duration::from_millis(30),
+                        )
+                    })
+                    .push(&frame.samples, |chunk| sink.push(chunk));
+            }
+        });
+
+        if let Err(e) = rec.set_echo_reference(Some(reference.clone())) {
+            warn!("Failed to enable echo cancellation: {e}");
+            return;
+        }
+        *self.echo_capture.lock().unwrap() = Some((capture, reference));
+        debug!("Echo cancellation active for this recording");
+    }
+
+    fn stop_echo_reference(&self, rec: &dyn Recorder) {
+        let _ = rec.set_echo_reference(None);
+        if let Some((mut capture, reference)) = self.echo_capture.lock().unwrap().take() {
+            let _ = capture.stop_capture();
+            debug!("Echo reference clock drift: {:+.0} ppm", reference.drift_ppm());
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        *self.is_recording.lock().unwrap()
+    }
+
+    fn set_state(&self, state: &mut RecordingState, new_state: RecordingState) {
+        *state = new_state;
+        self.emit("recording-state", state.clone());
+    }
+
+    /// Discard microphone audio until `resume_recording`, keeping the mic open
+    /// and what was recorded so far. False if nothing could be paused.
+    pub fn pause_recording(&self) -> bool {
+        self.set_paused(true)
+    }
+
+    pub fn resume_recording(&self) -> bool {
+        self.set_paused(false)
+    }
+
+    fn set_paused(&self, pause: bool) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let RecordingState::Recording { binding_id, paused } = &*state else {
+            return false;
+        };
+        if *paused == pause {
+            return true;
+        }
+        let settings = self.host.settings();
+        if settings.audio_source.unwrap_or(AudioSource::Microphone) != AudioSource::Microphone {
+            warn!("Only microphone recordings can be paused");
+            return false;
+        }
+
+        let result = match self.recorder.lock().unwrap().as_ref() {
+            Some(rec) if pause => rec.pause(),
+            Some(rec) => rec.resume(),
+            None => {
+                error!("Recorder not available");
+                return false;
+            }
+        };
+        if let Err(e) = result {
+            error!("Failed to pause or resume recording: {e}");
+            return false;
+        }
+
+        let binding_id = binding_id.clone();
+        self.set_state(
+            &mut state,
+            RecordingState::Recording {
+                binding_id,
+                paused: pause,
+            },
+        );
+        debug!("Recording {}", if pause { "paused" } else { "resumed" });
+        true
+    }
+
+    /// Whether the user is talking into the recording in progress
+    pub fn is_speech_active(&self) -> bool {
+        self.is_recording()
+            && self
+                .recorder
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|rec| rec.is_speaking())
+    }
+
+    /// Drain audio captured since the last call, for the always-on caption loop.
+    /// Microphone samples are 16kHz; system audio is at `system_audio_format`.
+    /// Either carries the capture time of each chunk.
+    pub fn read_caption_samples(
+        &self,
+        source: CaptionSource,
+    ) -> AudioResult<Option<TimedSamples>> {
+        match source {
+            CaptionSource::Microphone => match self.recorder.lock().unwrap().as_ref() {
+                // Use read_samples() to get continuous buffer without stopping
+                Some(rec) => rec.read_samples().map(Some),
+                None => Err(AudioError::StreamClosed),
+            },
+            CaptionSource::SystemAudio => match self.system_capture.lock().unwrap().as_mut() {
+                Some(capture) => capture.read_samples(),
+                None => Err(AudioError::StreamClosed),
+            },
+        }
+    }
+
+    /// Take delivery of system audio as it is captured, so the caption loop
+    /// can block on it instead of polling `read_caption_samples`. None for the
+    /// microphone, when capture is closed, or when the backend can't deliver.
+    pub fn deliver_caption_samples(&self, source: CaptionSource) -> Option<SampleReceiver> {
+        match source {
+            CaptionSource::Microphone => None,
+            CaptionSource::SystemAudio => self
+                .system_capture
+                .lock()
+                .unwrap()
+                .as_mut()?
+                .deliver_samples(),
+        }
+    }
+
+    /// Rate and channel count of the system audio device, or the default
+    /// format while capture is closed
+    pub fn system_audio_format(&self) -> CaptureFormat {
+        self.system_capture
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or_else(CaptureFormat::default, |capture| capture.format())
+    }
+
+    /// Capture time of the first system audio at the current rate, if the
+    /// capture switched to it from another one
+    pub fn system_audio_format_since(&self) -> Option<Instant> {
+        self.system_capture
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|capture| capture.peek_status().format_since)
+    }
+
+    /// Microphone samples lost to a full input queue
+    pub fn microphone_dropped_samples(&self) -> u64 {
+        self.recorder
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(0, |rec| rec.dropped_samples())
+    }
+
+    /// Whether system capture is open, plus a snapshot of its buffer. Doesn't
+    /// consume samples, so polling it can't open gaps in the captions.
+    pub fn get_system_audio_status(&self) -> (bool, CaptureStatus) {
+        let is_open = *self.is_open.lock().unwrap();
+        let status = match self.system_capture.lock().unwrap().as_ref() {
+            Some(capture) if is_open => capture.peek_status(),
+            _ => CaptureStatus::default(),
+        };
+        (is_open, status)
+    }
+
+    /* ---------- self-test --------------------------------------------------- */
+
+    /// Capture the selected source for `SELFTEST_DURATION` and measure it.
+    /// Opens its own stream next to any already open, and blocks until done.
+    pub fn run_selftest(&self) -> Result<AudioSelfTest, anyhow::Error> {
+        let settings = self.host.settings();
+        let vad = self.recorders.create_vad()?;
+
+        match settings.audio_source.unwrap_or(AudioSource::Microphone) {
+            AudioSource::Microphone => {
+                let device = self
+                    .get_effective_microphone(&settings)
+                    .as_deref()
+                    .and_then(find_input_device);
+                let name = device
+                    .as_ref()
+                    .and_then(|d| d.name().ok())
+                    .unwrap_or_else(|| "Default microphone".to_string());
+                let downmix = settings.microphone_downmix.downmix();
+                let (sample_rate, chunks) =
+                    AudioRecorder::capture_raw(device, downmix, SELFTEST_DURATION)
+                        .with_context(|| format!("Failed to open {}", name))?;
+
+                let mut probe = SelfTestProbe::new(sample_rate).with_vad(vad);
+                for (at, samples) in &chunks {
+                    probe.observe(samples, *at);
+                }
+                Ok(probe.finish("microphone", name, SELFTEST_DURATION, 0))
+            }
+            AudioSource::SystemAudio => self.run_system_audio_selftest(vad, &settings),
+        }
+    }
+
+    fn run_system_audio_selftest(
+        &self,
+        vad: Box<dyn VoiceActivityDetector>,
+        settings: &AppSettings,
+    ) -> Result<AudioSelfTest, anyhow::Error> {
+        let mut capture = self.captures.open(
+            settings.system_audio_buffer_seconds,
+            settings.system_audio_downmix.downmix(),
+        )?;
+        let mut probe = SelfTestProbe::new(capture.format().sample_rate).with_vad(vad);
+
+        let started = Instant::now();
+        while started.elapsed() < SELFTEST_DURATION {
+            thread::sleep(SELFTEST_POLL_INTERVAL);
+            match capture.read_samples() {
+                Ok(Some(timed)) => probe.observe(&timed.samples, started.elapsed()),
+                Ok(None) => {}
+                Err(e) => {
+                    let _ = capture.stop_capture();
+                    return Err(e.into());
+                }
+            }
+        }
+        let dropped_samples = capture.dropped_samples();
+        let _ = capture.stop_capture();
+
+        Ok(probe.finish(
+            "system_audio",
+            "System audio".to_string(),
+            SELFTEST_DURATION,
+            dropped_samples,
+        ))
+    }
+
+    /* ---------- device failover -------------------------------------------- */
+
+    /// Move the microphone to the default input if `removed` is the one it
+    /// was open on, instead of recording nothing. A recording in progress
+    /// carries on there and keeps what it had before the switch.
+    pub fn handle_device_removed(&self, removed: &DeviceRemoved) {
+        let state = self.state.lock().unwrap();
+        let settings = self.host.settings();
+        let on_microphone =
+            settings.audio_source.unwrap_or(AudioSource::Microphone) == AudioSource::Microphone;
+        let microphone_open = (on_microphone && *self.is_open.lock().unwrap())
+            || *self.dual_channel_mic.lock().unwrap();
+        let in_use = match self.get_effective_microphone(&settings) {
+            Some(key) => key == removed.id || key == removed.name,
+            None => removed.was_default,
+        };
+        if !microphone_open || !in_use {
+            return;
+        }
+
+        let mut recorder = self.recorder.lock().unwrap();
+        let Some(rec) = recorder.as_mut() else {
+            return;
+        };
+        // Some(paused) while a microphone recording is in progress
+        let recording = match &*state {
+            RecordingState::Recording { paused, .. } if on_microphone => Some(*paused),
+            _ => None,
+        };
+        warn!(
+            "Microphone {} went away, switching to the default input",
+            removed.name
+        );
+
+        if recording.is_some() {
+            let padding = Duration::from_millis(settings.silence_trim_padding_ms);
+            match rec.stop_with_padding(padding) {
+                Ok(samples) => self.failover_samples.lock().unwrap().extend(samples),
+                Err(e) => warn!("Lost the audio recorded before the switch: {e}"),
+            }
+            self.stop_echo_reference(rec.as_ref());
+        }
+        let _ = rec.close();
+        let reopened =
+            open_with_retry(rec.as_mut(), None, &settings).and_then(|()| match recording {
+                Some(paused) => {
+                    rec.start_with_pre_roll(Duration::ZERO)?;
+                    if paused {
+                        rec.pause()?;
+                    }
+                    if settings.echo_cancellation_enabled {
+                        self.start_echo_reference(rec.as_ref(), &settings);
+                    }
+                    Ok(())
+                }
+                None => Ok(()),
+            });
+        drop(recorder);
+        drop(state);
+
+        if let Err(e) = &reopened {
+            error!("Failed to open the default input after the microphone went away: {e}");
+        }
+        self.emit(
+            MicrophoneFailover::NAME,
+            MicrophoneFailover {
+                from: removed.name.clone(),
+                recording: recording.is_some(),
+                error: reopened.err().map(|e| e.to_string()),
+            },
+        );
     }
 
     pub fn update_selected_device(&self) -> Result<(), anyhow::Error> {
@@ -1353,8 +1106,8 @@ impl AudioRecordingManager {
             std::thread::sleep(std::time::Duration::from_millis(500));
             
             // Check if we're in SystemAudio mode and always-on, and ensure auto-transcription is running
-            let settings = get_settings(&self.app_handle);
-            if settings.always_on_microphone {
+            let settings = self.host.settings();
+            if settings.auto_caption_system_audio {
                 let audio_source = settings.audio_source.unwrap_or(AudioSource::Microphone);
                 if audio_source == AudioSource::SystemAudio {
                     let is_open = *self.is_open.lock().unwrap();
@@ -1409,43 +1162,45 @@ impl AudioRecordingManager {
         match *state {
             RecordingState::Recording {
                 binding_id: ref active,
+                ..
             } if active == binding_id => {
-                *state = RecordingState::Idle;
+                self.set_state(&mut state, RecordingState::Idle);
                 drop(state);
+                self.recording_stopped(binding_id, false);
 
-                let settings = get_settings(&self.app_handle);
+                let settings = self.host.settings();
                 let audio_source = settings.audio_source.unwrap_or(AudioSource::Microphone);
 
                 let samples = if audio_source == AudioSource::SystemAudio {
                     // Read samples from system capture
-                    #[cfg(target_os = "macos")]
-                    {
-                        if let Some(capture) = self.system_capture.lock().unwrap().as_mut() {
-                            match capture.read_samples() {
-                                Ok(Some(s)) => s,
-                                Ok(None) => Vec::new(),
-                                Err(e) => {
-                                    error!("System capture read failed: {e}");
-                                    Vec::new()
-                                }
+                    if let Some(capture) = self.system_capture.lock().unwrap().as_mut() {
+                        match capture.read_samples() {
+                            Ok(Some(timed)) => timed.samples,
+                            Ok(None) => Vec::new(),
+                            Err(e) => {
+                                error!("System capture read failed: {e}");
+                                Vec::new()
                             }
-                        } else {
-                            error!("System capture not available");
-                            Vec::new()
                         }
-                    }
-                    #[cfg(not(target_os = "macos"))]
-                    {
+                    } else {
+                        error!("System capture not available");
                         Vec::new()
                     }
                 } else if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
-                    match rec.stop() {
+                    let padding = Duration::from_millis(settings.silence_trim_padding_ms);
+                    let stopped = metrics::time(Stage::Capture, || rec.stop_with_padding(padding));
+                    let samples = match stopped {
                         Ok(buf) => buf,
                         Err(e) => {
                             error!("stop() failed: {e}");
                             Vec::new()
                         }
-                    }
+                    };
+                    self.stop_echo_reference(rec);
+                    self.finish_journal();
+                    let mut recorded = std::mem::take(&mut *self.failover_samples.lock().unwrap());
+                    recorded.extend(samples);
+                    recorded
                 } else {
                     error!("Recorder not available");
                     Vec::new()
@@ -1477,13 +1232,19 @@ impl AudioRecordingManager {
     pub fn cancel_recording(&self) {
         let mut state = self.state.lock().unwrap();
 
-        if let RecordingState::Recording { .. } = *state {
-            *state = RecordingState::Idle;
+        if let RecordingState::Recording { binding_id, .. } = &*state {
+            let binding_id = binding_id.clone();
+            self.set_state(&mut state, RecordingState::Idle);
             drop(state);
+            self.recording_stopped(&binding_id, true);
 
             if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
                 let _ = rec.stop(); // Discard the result
+                self.stop_echo_reference(rec);
             }
+            self.finish_journal();
+            self.segments.lock().unwrap().take();
+            self.failover_samples.lock().unwrap().clear();
 
             *self.is_recording.lock().unwrap() = false;
 
@@ -1494,3 +1255,438 @@ impl AudioRecordingManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_toolkit::audio::Downmix;
+    use crate::audio_toolkit::stream::{AudioFrameStream, FrameBroadcaster};
+    use crate::managers::audio_backend::Transcriber;
+    use crate::settings::get_default_settings;
+
+    // Calls made on the mock recorder and capture, in order
+    type CallLog = Arc<Mutex<Vec<String>>>;
+
+    struct TestHost {
+        settings: Mutex<AppSettings>,
+        events: Mutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    impl TestHost {
+        fn events(&self, name: &str) -> Vec<serde_json::Value> {
+            self.events
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(event, _)| event == name)
+                .map(|(_, payload)| payload.clone())
+                .collect()
+        }
+    }
+
+    impl AudioHost for TestHost {
+        fn settings(&self) -> AppSettings {
+            self.settings.lock().unwrap().clone()
+        }
+
+        fn emit_event(&self, event: &str, payload: serde_json::Value) {
+            self.events
+                .lock()
+                .unwrap()
+                .push((event.to_string(), payload));
+        }
+
+        fn app(&self) -> Option<&tauri::AppHandle> {
+            None
+        }
+    }
+
+    // Returns `samples` samples on stop; "unplugged" fails to open, "busy"
+    // fails the first time
+    struct MockRecorder {
+        calls: CallLog,
+        samples: usize,
+        frames: FrameBroadcaster,
+        busy: bool,
+    }
+
+    impl MockRecorder {
+        fn log(&self, call: &str) -> AudioResult<()> {
+            self.calls.lock().unwrap().push(call.to_string());
+            Ok(())
+        }
+    }
+
+    impl Recorder for MockRecorder {
+        fn open_device(&mut self, device: Option<&str>) -> AudioResult<()> {
+            self.log(&format!("open {}", device.unwrap_or("default")))?;
+            match device {
+                Some("unplugged") => Err(AudioError::DeviceNotFound("unplugged".to_string())),
+                Some("busy") if self.busy => {
+                    self.busy = false;
+                    Err(AudioError::StreamBuildFailed("device busy".to_string()))
+                }
+                _ => Ok(()),
+            }
+        }
+
+        fn close(&mut self) -> AudioResult<()> {
+            self.log("close")
+        }
+
+        fn start_with_pre_roll(&self, _pre_roll: Duration) -> AudioResult<()> {
+            self.log("start")
+        }
+
+        fn pause(&self) -> AudioResult<()> {
+            self.log("pause")
+        }
+
+        fn resume(&self) -> AudioResult<()> {
+            self.log("resume")
+        }
+
+        fn stop(&self) -> AudioResult<Vec<f32>> {
+            self.stop_with_padding(Duration::ZERO)
+        }
+
+        fn stop_with_padding(&self, _padding: Duration) -> AudioResult<Vec<f32>> {
+            self.log("stop")?;
+            Ok(vec![0.1; self.samples])
+        }
+
+        fn read_samples(&self) -> AudioResult<TimedSamples> {
+            Ok(TimedSamples::new())
+        }
+
+        fn is_speaking(&self) -> bool {
+            false
+        }
+
+        fn recorded_frames(&self) -> AudioFrameStream {
+            self.frames.subscribe()
+        }
+
+        fn set_echo_reference(&self, _reference: Option<EchoReference>) -> AudioResult<()> {
+            Ok(())
+        }
+    }
+
+    struct MockRecorders {
+        calls: CallLog,
+        samples: usize,
+    }
+
+    impl RecorderFactory for MockRecorders {
+        fn create(&self) -> anyhow::Result<Box<dyn Recorder>> {
+            self.calls.lock().unwrap().push("create".to_string());
+            Ok(Box::new(MockRecorder {
+                calls: Arc::clone(&self.calls),
+                samples: self.samples,
+                frames: FrameBroadcaster::new(WHISPER_SAMPLE_RATE as u32),
+                busy: true,
+            }))
+        }
+
+        fn create_vad(&self) -> anyhow::Result<Box<dyn VoiceActivityDetector>> {
+            Err(anyhow::anyhow!("No VAD in tests"))
+        }
+    }
+
+    // Hands out whatever the test queued in `pending`
+    struct MockCapture {
+        calls: CallLog,
+        pending: Arc<Mutex<Vec<f32>>>,
+        capturing: bool,
+    }
+
+    impl SystemAudioCapture for MockCapture {
+        fn start_capture(&mut self) -> AudioResult<()> {
+            self.capturing = true;
+            Ok(())
+        }
+
+        fn stop_capture(&mut self) -> AudioResult<()> {
+            self.calls.lock().unwrap().push("stop capture".to_string());
+            self.capturing = false;
+            Ok(())
+        }
+
+        fn read_samples(&mut self) -> AudioResult<Option<TimedSamples>> {
+            let samples = std::mem::take(&mut *self.pending.lock().unwrap());
+            Ok((!samples.is_empty()).then(|| TimedSamples::from_chunk(samples, Instant::now())))
+        }
+
+        fn is_capturing(&self) -> bool {
+            self.capturing
+        }
+    }
+
+    struct MockCaptures {
+        calls: CallLog,
+        pending: Arc<Mutex<Vec<f32>>>,
+    }
+
+    impl SystemCaptureFactory for MockCaptures {
+        fn open(
+            &self,
+            _max_buffer_seconds: u32,
+            _downmix: Downmix,
+        ) -> AudioResult<Box<dyn SystemAudioCapture>> {
+            self.calls.lock().unwrap().push("open capture".to_string());
+            let mut capture = MockCapture {
+                calls: Arc::clone(&self.calls),
+                pending: Arc::clone(&self.pending),
+                capturing: false,
+            };
+            capture.start_capture()?;
+            Ok(Box::new(capture))
+        }
+    }
+
+    struct NoTranscriber;
+
+    impl TranscriberFactory for NoTranscriber {
+        fn transcriber(&self) -> Option<Arc<dyn Transcriber>> {
+            None
+        }
+    }
+
+    struct Fixture {
+        host: Arc<TestHost>,
+        calls: CallLog,
+        system_audio: Arc<Mutex<Vec<f32>>>,
+        manager: AudioRecordingManager,
+    }
+
+    impl Fixture {
+        fn take_calls(&self) -> Vec<String> {
+            std::mem::take(&mut *self.calls.lock().unwrap())
+        }
+    }
+
+    /// A manager on mocks whose recorder returns `samples` samples, with
+    /// on-demand microphone settings adjusted by `configure`
+    fn fixture(samples: usize, configure: impl FnOnce(&mut AppSettings)) -> Fixture {
+        let mut settings = get_default_settings();
+        settings.audio_source = Some(AudioSource::Microphone);
+        settings.always_on_microphone = false;
+        settings.auto_caption_system_audio = false;
+        settings.long_recording_segment_secs = 0;
+        settings.max_recording_secs = 0;
+        configure(&mut settings);
+
+        let host = Arc::new(TestHost {
+            settings: Mutex::new(settings),
+            events: Mutex::new(Vec::new()),
+        });
+        let calls = CallLog::default();
+        let system_audio = Arc::new(Mutex::new(Vec::new()));
+        let manager = AudioRecordingManager::with_backends(
+            host.clone(),
+            Arc::new(MockRecorders {
+                calls: Arc::clone(&calls),
+                samples,
+            }),
+            Arc::new(MockCaptures {
+                calls: Arc::clone(&calls),
+                pending: Arc::clone(&system_audio),
+            }),
+            Arc::new(NoTranscriber),
+        )
+        .unwrap();
+
+        Fixture {
+            host,
+            calls,
+            system_audio,
+            manager,
+        }
+    }
+
+    #[test]
+    fn test_on_demand_opens_microphone_per_recording() {
+        let f = fixture(32000, |_| {});
+        assert!(f.take_calls().is_empty());
+
+        assert!(f.manager.try_start_recording("transcribe"));
+        assert!(f.manager.is_recording());
+        assert!(!f.manager.try_start_recording("transcribe"));
+        assert_eq!(f.take_calls(), ["create", "open default", "start"]);
+
+        // Another binding can't stop it
+        assert!(f.manager.stop_recording("other").is_none());
+        let samples = f.manager.stop_recording("transcribe").unwrap();
+        assert_eq!(samples.len(), 32000);
+        assert!(!f.manager.is_recording());
+        assert_eq!(f.take_calls(), ["stop", "close"]);
+
+        let states: Vec<_> = f
+            .host
+            .events("recording-state")
+            .iter()
+            .map(|state| state["state"].clone())
+            .collect();
+        assert_eq!(states, ["recording", "idle"]);
+        assert_eq!(f.host.events(RecordingStarted::NAME).len(), 1);
+        assert_eq!(
+            f.host.events(RecordingStopped::NAME)[0]["cancelled"].as_bool(),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_short_recording_is_padded() {
+        let f = fixture(8000, |_| {});
+        assert!(f.manager.try_start_recording("transcribe"));
+        let samples = f.manager.stop_recording("transcribe").unwrap();
+        assert_eq!(samples.len(), WHISPER_SAMPLE_RATE * 5 / 4);
+    }
+
+    #[test]
+    fn test_always_on_starts_recording_at_launch() {
+        let f = fixture(16000, |s| s.always_on_microphone = true);
+        assert!(f.manager.is_recording());
+        assert_eq!(f.take_calls(), ["create", "open default", "start"]);
+    }
+
+    #[test]
+    fn test_mode_switch_opens_and_closes_microphone() {
+        let f = fixture(16000, |_| {});
+        f.manager.update_mode(MicrophoneMode::AlwaysOn).unwrap();
+        assert_eq!(f.take_calls(), ["create", "open default"]);
+
+        // An always-open stream stays open across recordings
+        assert!(f.manager.try_start_recording("transcribe"));
+        f.manager.stop_recording("transcribe").unwrap();
+        assert_eq!(f.take_calls(), ["start", "stop"]);
+
+        // Switching back doesn't cut off a recording in progress
+        assert!(f.manager.try_start_recording("transcribe"));
+        f.manager.update_mode(MicrophoneMode::OnDemand).unwrap();
+        assert_eq!(f.take_calls(), ["start"]);
+        f.manager.stop_recording("transcribe").unwrap();
+        assert_eq!(f.take_calls(), ["stop", "close"]);
+
+        f.manager.update_mode(MicrophoneMode::AlwaysOn).unwrap();
+        f.manager.update_mode(MicrophoneMode::OnDemand).unwrap();
+        assert_eq!(f.take_calls(), ["open default", "close"]);
+    }
+
+    #[test]
+    fn test_device_change_reopens_stream() {
+        let f = fixture(16000, |s| {
+            s.selected_microphone = Some("usb-mic".to_string())
+        });
+        // Nothing to reopen while the stream is closed
+        f.manager.update_selected_device().unwrap();
+        assert!(f.take_calls().is_empty());
+
+        f.manager.update_mode(MicrophoneMode::AlwaysOn).unwrap();
+        f.host.settings.lock().unwrap().selected_microphone = Some("headset".to_string());
+        f.manager.update_selected_device().unwrap();
+        assert_eq!(
+            f.take_calls(),
+            ["create", "open usb-mic", "close", "open headset"]
+        );
+    }
+
+    #[test]
+    fn test_removed_microphone_fails_over_to_default() {
+        let f = fixture(16000, |s| {
+            s.selected_microphone = Some("usb-mic".to_string())
+        });
+        let removed = |name: &str| DeviceRemoved {
+            id: format!("{}|1ch@48000", name),
+            name: name.to_string(),
+            was_default: false,
+        };
+        assert!(f.manager.try_start_recording("transcribe"));
+        f.take_calls();
+
+        // Another device going away changes nothing
+        f.manager.handle_device_removed(&removed("headset"));
+        assert!(f.take_calls().is_empty());
+
+        f.manager.handle_device_removed(&removed("usb-mic"));
+        assert_eq!(f.take_calls(), ["stop", "close", "open default", "start"]);
+        let failovers = f.host.events(MicrophoneFailover::NAME);
+        assert_eq!(failovers.len(), 1);
+        assert_eq!(failovers[0]["recording"].as_bool(), Some(true));
+
+        // The recording keeps what it had before the switch
+        let samples = f.manager.stop_recording("transcribe").unwrap();
+        assert_eq!(samples.len(), 32000);
+    }
+
+    #[test]
+    fn test_failed_open_leaves_manager_idle() {
+        let f = fixture(16000, |s| {
+            s.selected_microphone = Some("unplugged".to_string())
+        });
+        assert!(!f.manager.try_start_recording("transcribe"));
+        assert!(!f.manager.is_recording());
+        assert!(f.host.events(RecordingStarted::NAME).is_empty());
+        // A missing device isn't worth trying again
+        assert_eq!(f.take_calls(), ["create", "open unplugged"]);
+
+        // Recording works again once the device is back
+        f.host.settings.lock().unwrap().selected_microphone = None;
+        assert!(f.manager.try_start_recording("transcribe"));
+    }
+
+    #[test]
+    fn test_busy_device_is_retried() {
+        let f = fixture(16000, |s| s.selected_microphone = Some("busy".to_string()));
+        assert!(f.manager.try_start_recording("transcribe"));
+        assert_eq!(
+            f.take_calls(),
+            ["create", "open busy", "open busy", "start"]
+        );
+    }
+
+    #[test]
+    fn test_pause_resume_and_cancel() {
+        let f = fixture(16000, |_| {});
+        assert!(!f.manager.pause_recording());
+
+        assert!(f.manager.try_start_recording("transcribe"));
+        assert!(f.manager.pause_recording());
+        assert!(f.manager.resume_recording());
+        f.manager.cancel_recording();
+        assert!(!f.manager.is_recording());
+        assert_eq!(
+            f.take_calls(),
+            [
+                "create",
+                "open default",
+                "start",
+                "pause",
+                "resume",
+                "stop",
+                "close"
+            ]
+        );
+        assert_eq!(
+            f.host.events(RecordingStopped::NAME)[0]["cancelled"].as_bool(),
+            Some(true)
+        );
+
+        // Idle again, so the next recording can start
+        assert!(f.manager.try_start_recording("transcribe"));
+    }
+
+    #[test]
+    fn test_system_audio_records_from_capture() {
+        let f = fixture(0, |s| s.audio_source = Some(AudioSource::SystemAudio));
+        // Audio from before the recording is discarded when it starts
+        f.system_audio.lock().unwrap().extend([0.5; 100]);
+        assert!(f.manager.try_start_recording("transcribe"));
+        f.system_audio.lock().unwrap().extend([0.25; 48000]);
+
+        let samples = f.manager.stop_recording("transcribe").unwrap();
+        assert_eq!(samples, vec![0.25; 48000]);
+        assert_eq!(f.take_calls(), ["open capture", "stop capture"]);
+        assert!(!f.manager.get_system_audio_status().0);
+    }
+}
@@ -0,0 +1,361 @@
+// Seams between AudioRecordingManager and what it drives
+// The manager reaches the microphone, system audio, transcription and the app
+// itself (settings, events) only through the traits below. The app plugs in
+// the implementations here; tests drive the manager's state machine with
+// mocks instead of devices and a running Tauri app.
+
+use crate::audio_toolkit::audio::{Downmix, EchoReference, Gain};
+use crate::audio_toolkit::error::Result as AudioResult;
+#[cfg(target_os = "macos")]
+use crate::audio_toolkit::screencapturekit::permissions::get_macos_version;
+use crate::audio_toolkit::stream::AudioFrameStream;
+use crate::audio_toolkit::wasapi::WasapiOptions;
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+use crate::audio_toolkit::AudioError;
+#[cfg(target_os = "macos")]
+use crate::audio_toolkit::MacOSSystemAudio;
+#[cfg(target_os = "windows")]
+use crate::audio_toolkit::WindowsSystemAudio;
+use crate::audio_toolkit::{
+    find_device, list_input_devices, vad::SmoothedVad, AudioRecorder, SileroVad,
+    SimulatedAudioCapture, SimulatedSource, SystemAudioCapture, TimedSamples,
+    VoiceActivityDetector,
+};
+use crate::events::{self, AudioClipping};
+use crate::managers::audio::resolve_vad_model_path;
+use crate::managers::transcription::TranscriptionManager;
+use crate::settings::{get_settings, AppSettings};
+use crate::utils;
+use anyhow::Result;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use log::info;
+use log::{debug, error};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// What the manager needs from the running app
+pub trait AudioHost: Send + Sync {
+    /// Current settings, read fresh on every call
+    fn settings(&self) -> AppSettings;
+
+    /// Send an event to the frontend, logging instead of failing
+    fn emit_event(&self, event: &str, payload: serde_json::Value);
+
+    /// The app itself, for the caption loop, crash journal and shortcuts.
+    /// None when the manager runs without one, as in tests.
+    fn app(&self) -> Option<&AppHandle>;
+}
+
+impl AudioHost for AppHandle {
+    fn settings(&self) -> AppSettings {
+        get_settings(self)
+    }
+
+    fn emit_event(&self, event: &str, payload: serde_json::Value) {
+        if let Err(e) = self.emit(event, payload) {
+            error!("Failed to emit {} event: {}", event, e);
+        }
+    }
+
+    fn app(&self) -> Option<&AppHandle> {
+        Some(self)
+    }
+}
+
+/// A microphone recorder; see `AudioRecorder` for what each call does
+pub trait Recorder: Send {
+    /// Open `device`, an id or name as saved in settings, or the default
+    /// input when None
+    fn open_device(&mut self, device: Option<&str>) -> AudioResult<()>;
+    fn close(&mut self) -> AudioResult<()>;
+    fn start_with_pre_roll(&self, pre_roll: Duration) -> AudioResult<()>;
+    fn pause(&self) -> AudioResult<()>;
+    fn resume(&self) -> AudioResult<()>;
+    fn stop(&self) -> AudioResult<Vec<f32>>;
+    fn stop_with_padding(&self, padding: Duration) -> AudioResult<Vec<f32>>;
+    fn read_samples(&self) -> AudioResult<TimedSamples>;
+    fn is_speaking(&self) -> bool;
+    fn recorded_frames(&self) -> AudioFrameStream;
+    fn set_echo_reference(&self, reference: Option<EchoReference>) -> AudioResult<()>;
+
+    /// How multi-channel input is reduced to mono from the next `open_device` on
+    fn set_downmix(&mut self, _downmix: Downmix) {}
+
+    /// Software gain applied to the input from the next `open_device` on
+    fn set_gain(&mut self, _gain: Gain) {}
+
+    /// How the device is opened on Windows from the next `open_device` on
+    fn set_wasapi_options(&mut self, _options: WasapiOptions) {}
+
+    /// Samples lost to a full input queue since the device was opened
+    fn dropped_samples(&self) -> u64 {
+        0
+    }
+}
+
+pub trait RecorderFactory: Send + Sync {
+    /// A new recorder, not yet opened
+    fn create(&self) -> Result<Box<dyn Recorder>>;
+
+    /// A VAD for work alongside the recorder, like cutting long takes into
+    /// segments
+    fn create_vad(&self) -> Result<Box<dyn VoiceActivityDetector>>;
+}
+
+pub trait SystemCaptureFactory: Send + Sync {
+    /// Create and start the capture behind the system audio source, keeping
+    /// up to `max_buffer_seconds` of unread audio and reducing multi-channel
+    /// audio to mono with `downmix`
+    fn open(
+        &self,
+        max_buffer_seconds: u32,
+        downmix: Downmix,
+    ) -> AudioResult<Box<dyn SystemAudioCapture>>;
+}
+
+pub trait Transcriber: Send + Sync {
+    fn transcribe(&self, samples: Vec<f32>) -> Result<String>;
+}
+
+pub trait TranscriberFactory: Send + Sync {
+    /// The transcriber for segments of long recordings, once there is one
+    fn transcriber(&self) -> Option<Arc<dyn Transcriber>>;
+}
+
+/* ──────────────────────────────────────────────────────────────── */
+
+/// The input device saved in settings as `key`: its id, or its name for
+/// settings saved before ids existed
+pub fn find_input_device(key: &str) -> Option<cpal::Device> {
+    match list_input_devices() {
+        Ok(devices) => find_device(devices, key).map(|d| d.device),
+        Err(e) => {
+            debug!("Failed to list devices, using default: {}", e);
+            None
+        }
+    }
+}
+
+impl Recorder for AudioRecorder {
+    fn open_device(&mut self, device: Option<&str>) -> AudioResult<()> {
+        // Simulated audio stands in for the device
+        match SimulatedSource::from_env() {
+            Some(source) => self.open_simulated(&source),
+            None => AudioRecorder::open(self, device.and_then(find_input_device)),
+        }
+    }
+
+    fn close(&mut self) -> AudioResult<()> {
+        AudioRecorder::close(self)
+    }
+
+    fn start_with_pre_roll(&self, pre_roll: Duration) -> AudioResult<()> {
+        AudioRecorder::start_with_pre_roll(self, pre_roll)
+    }
+
+    fn pause(&self) -> AudioResult<()> {
+        AudioRecorder::pause(self)
+    }
+
+    fn resume(&self) -> AudioResult<()> {
+        AudioRecorder::resume(self)
+    }
+
+    fn stop(&self) -> AudioResult<Vec<f32>> {
+        AudioRecorder::stop(self)
+    }
+
+    fn stop_with_padding(&self, padding: Duration) -> AudioResult<Vec<f32>> {
+        AudioRecorder::stop_with_padding(self, padding)
+    }
+
+    fn read_samples(&self) -> AudioResult<TimedSamples> {
+        AudioRecorder::read_samples(self)
+    }
+
+    fn is_speaking(&self) -> bool {
+        AudioRecorder::is_speaking(self)
+    }
+
+    fn dropped_samples(&self) -> u64 {
+        AudioRecorder::dropped_samples(self)
+    }
+
+    fn recorded_frames(&self) -> AudioFrameStream {
+        AudioRecorder::recorded_frames(self)
+    }
+
+    fn set_echo_reference(&self, reference: Option<EchoReference>) -> AudioResult<()> {
+        AudioRecorder::set_echo_reference(self, reference)
+    }
+
+    fn set_downmix(&mut self, downmix: Downmix) {
+        AudioRecorder::set_downmix(self, downmix)
+    }
+
+    fn set_gain(&mut self, gain: Gain) {
+        AudioRecorder::set_gain(self, gain)
+    }
+
+    fn set_wasapi_options(&mut self, options: WasapiOptions) {
+        AudioRecorder::set_wasapi_options(self, options)
+    }
+}
+
+/// Recorders on the input devices, with the bundled Silero VAD and a level
+/// meter and spectrum feeding the frontend
+pub struct DeviceRecorderFactory {
+    app: AppHandle,
+}
+
+impl DeviceRecorderFactory {
+    pub fn new(app: &AppHandle) -> Self {
+        Self { app: app.clone() }
+    }
+
+    fn silero(&self) -> Result<SileroVad> {
+        let vad_path = resolve_vad_model_path(&self.app)?;
+        Ok(SileroVad::new(vad_path, 0.3)?)
+    }
+}
+
+impl RecorderFactory for DeviceRecorderFactory {
+    fn create(&self) -> Result<Box<dyn Recorder>> {
+        let smoothed_vad = SmoothedVad::new(Box::new(self.silero()?), 15, 15, 2);
+        let app = self.app.clone();
+        let spectrum_app = self.app.clone();
+        let clipping_app = self.app.clone();
+        let recorder = AudioRecorder::new()?
+            .with_vad(Box::new(smoothed_vad))
+            .with_level_callback(move |levels| utils::emit_levels(&app, &levels))
+            .with_spectrum_callback(move |frame| utils::emit_spectrum(&spectrum_app, &frame))
+            .with_clipping_callback(move |clips| {
+                events::emit(&clipping_app, AudioClipping { clips });
+            });
+        Ok(Box::new(recorder))
+    }
+
+    fn create_vad(&self) -> Result<Box<dyn VoiceActivityDetector>> {
+        Ok(Box::new(self.silero()?))
+    }
+}
+
+/// The simulated source when `HANDY_SIMULATED_AUDIO` is set, otherwise the
+/// platform's loopback capture
+pub struct PlatformCaptureFactory {
+    app: AppHandle,
+}
+
+impl PlatformCaptureFactory {
+    pub fn new(app: &AppHandle) -> Self {
+        Self { app: app.clone() }
+    }
+}
+
+impl SystemCaptureFactory for PlatformCaptureFactory {
+    fn open(
+        &self,
+        max_buffer_seconds: u32,
+        downmix: Downmix,
+    ) -> AudioResult<Box<dyn SystemAudioCapture>> {
+        // The simulated source is already mono
+        if let Some(source) = SimulatedSource::from_env() {
+            let mut capture =
+                SimulatedAudioCapture::new(source).with_max_buffer_seconds(max_buffer_seconds);
+            capture.start_capture()?;
+            return Ok(Box::new(capture));
+        }
+        open_platform_capture(&self.app, max_buffer_seconds, downmix)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn open_platform_capture(
+    app: &AppHandle,
+    max_buffer_seconds: u32,
+    downmix: Downmix,
+) -> AudioResult<Box<dyn SystemAudioCapture>> {
+    // MacOSSystemAudio picks a process tap, BlackHole or ScreenCaptureKit,
+    // whichever this system supports; the frontend answers the setup events
+    // below with a persistent dialog
+    if let Some((major, minor)) = get_macos_version() {
+        info!(
+            "Initializing system audio capture on macOS {}.{}",
+            major, minor
+        );
+    }
+
+    let mut capture = match MacOSSystemAudio::new(app) {
+        Ok(c) => c
+            .with_max_buffer_seconds(max_buffer_seconds)
+            .with_downmix(downmix)
+            .with_capture_target(get_settings(app).screen_capture_target),
+        Err(e) => {
+            error!("Failed to create MacOSSystemAudio: {}", e);
+            let _ = app.emit(
+                "system-audio-setup-required",
+                format!("System audio setup required: {}", e),
+            );
+            return Err(e);
+        }
+    };
+    if let Err(e) = capture.start_capture() {
+        error!("Failed to start system audio capture: {}", e);
+        if capture.is_permission_denied() {
+            let _ = app.emit(
+                "screencapture-permission-required",
+                format!("Screen Recording permission not granted: {}", e),
+            );
+        } else {
+            let _ = app.emit(
+                "system-audio-setup-required",
+                format!("BlackHole not configured: {}", e),
+            );
+        }
+        return Err(e);
+    }
+    Ok(Box::new(capture))
+}
+
+#[cfg(target_os = "windows")]
+fn open_platform_capture(
+    app: &AppHandle,
+    max_buffer_seconds: u32,
+    downmix: Downmix,
+) -> AudioResult<Box<dyn SystemAudioCapture>> {
+    info!("Initializing system audio capture (Windows WASAPI)");
+    let mut capture = WindowsSystemAudio::new(app)?
+        .with_max_buffer_seconds(max_buffer_seconds)
+        .with_downmix(downmix)
+        .with_wasapi_options(get_settings(app).wasapi);
+    if let Err(e) = capture.start_capture() {
+        error!("Failed to start system audio capture: {}", e);
+        return Err(e);
+    }
+    Ok(Box::new(capture))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn open_platform_capture(
+    _app: &AppHandle,
+    _max_buffer_seconds: u32,
+    _downmix: Downmix,
+) -> AudioResult<Box<dyn SystemAudioCapture>> {
+    Err(AudioError::Backend(
+        "System audio capture not supported on this platform".to_string(),
+    ))
+}
+
+impl Transcriber for TranscriptionManager {
+    fn transcribe(&self, samples: Vec<f32>) -> Result<String> {
+        TranscriptionManager::transcribe(self, samples)
+    }
+}
+
+impl TranscriberFactory for AppHandle {
+    fn transcriber(&self) -> Option<Arc<dyn Transcriber>> {
+        let manager = self.try_state::<Arc<TranscriptionManager>>()?;
+        Some(Arc::clone(manager.inner()) as Arc<dyn Transcriber>)
+    }
+}
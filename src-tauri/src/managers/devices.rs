@@ -0,0 +1,141 @@
+use crate::audio_toolkit::list_input_devices;
+use crate::events::{self, DeviceAdded, DeviceRemoved};
+use crate::managers::audio::AudioRecordingManager;
+use log::{debug, info};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+// How often the input devices are listed again
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, PartialEq)]
+struct InputDevice {
+    id: String,
+    name: String,
+    is_default: bool,
+}
+
+/// Devices in `after` but not in `before`, and in `before` but not in
+/// `after`, by id
+fn diff_devices<'a>(
+    before: &'a [InputDevice],
+    after: &'a [InputDevice],
+) -> (Vec<&'a InputDevice>, Vec<&'a InputDevice>) {
+    let added = after
+        .iter()
+        .filter(|device| !before.iter().any(|d| d.id == device.id))
+        .collect();
+    let removed = before
+        .iter()
+        .filter(|device| !after.iter().any(|d| d.id == device.id))
+        .collect();
+    (added, removed)
+}
+
+/// Polls the input devices, announcing each one that appears or goes away
+/// and moving the microphone off one that goes away while in use
+pub struct DeviceWatcher {
+    app_handle: AppHandle,
+    // None until the first poll
+    known: Mutex<Option<Vec<InputDevice>>>,
+}
+
+impl DeviceWatcher {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        Self {
+            app_handle: app_handle.clone(),
+            known: Mutex::new(None),
+        }
+    }
+
+    /// List the devices now and then every `DEVICE_POLL_INTERVAL`. The polls
+    /// stop when the watcher is dropped.
+    pub fn start_monitor(self: &Arc<Self>) {
+        let watcher = Arc::downgrade(self);
+        thread::spawn(move || loop {
+            let Some(watcher) = watcher.upgrade() else {
+                debug!("Device watcher stopping, watcher is gone");
+                return;
+            };
+            watcher.check();
+            drop(watcher);
+            thread::sleep(DEVICE_POLL_INTERVAL);
+        });
+    }
+
+    /// List the input devices and act on any change since the last check
+    pub fn check(&self) {
+        let devices: Vec<InputDevice> = match list_input_devices() {
+            Ok(devices) => devices
+                .into_iter()
+                .map(|d| InputDevice {
+                    id: d.id,
+                    name: d.name,
+                    is_default: d.is_default,
+                })
+                .collect(),
+            Err(e) => {
+                debug!("Failed to list input devices: {}", e);
+                return;
+            }
+        };
+        let Some(before) = self.known.lock().unwrap().replace(devices.clone()) else {
+            return;
+        };
+
+        let (added, removed) = diff_devices(&before, &devices);
+        for device in added {
+            info!("Input device added: {}", device.name);
+            events::emit(
+                &self.app_handle,
+                DeviceAdded {
+                    id: device.id.clone(),
+                    name: device.name.clone(),
+                    is_default: device.is_default,
+                },
+            );
+        }
+        for device in removed {
+            info!("Input device removed: {}", device.name);
+            let event = DeviceRemoved {
+                id: device.id.clone(),
+                name: device.name.clone(),
+                was_default: device.is_default,
+            };
+            // A device whose format changed is listed under a new id, but is
+            // still there to record from
+            let gone = !devices.iter().any(|d| d.name == device.name);
+            if gone {
+                if let Some(rm) = self.app_handle.try_state::<Arc<AudioRecordingManager>>() {
+                    rm.handle_device_removed(&event);
+                }
+            }
+            events::emit(&self.app_handle, event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_devices() {
+        let device = |id: &str| InputDevice {
+            id: id.to_string(),
+            name: id.to_string(),
+            is_default: false,
+        };
+        let before = vec![device("built-in"), device("usb-mic")];
+        let after = vec![device("built-in"), device("headset")];
+
+        let (added, removed) = diff_devices(&before, &after);
+        assert_eq!(added, vec![&device("headset")]);
+        assert_eq!(removed, vec![&device("usb-mic")]);
+
+        let (added, removed) = diff_devices(&after, &after);
+        assert!(added.is_empty() && removed.is_empty());
+    }
+}
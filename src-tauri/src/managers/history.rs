@@ -1,14 +1,158 @@
 use anyhow::Result;
-use chrono::{DateTime, Local, Utc};
-use log::{debug, error};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use log::{debug, error, info};
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
-use tauri_plugin_sql::{Migration, MigrationKind};
 
-use crate::audio_toolkit::save_wav_file;
+use crate::audio_toolkit::{save_flac_file, waveform_envelope};
+use crate::settings::{AudioSource, HistoryRetention};
+
+/// Number of points stored per entry for the history waveform preview
+const WAVEFORM_POINTS: usize = 200;
+
+/// Rate of the samples handed to the save functions
+const SAMPLE_RATE: usize = 16000;
+
+/// How often the background task applies the retention limits
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Days covered by `UsageStats::daily`, ending today
+const STATS_DAYS: i64 = 30;
+/// Apps listed in `UsageStats::top_apps`
+const STATS_TOP_APPS: usize = 5;
+
+/// Columns `row_to_entry` reads
+const ENTRY_COLUMNS: &str = "id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt, waveform, tag, session_id, duration_ms, metadata,
+    (SELECT COUNT(*) FROM history_revisions WHERE entry_id = transcription_history.id) AS revision_count";
+
+/// A schema change, applied once and in order of `version`
+struct Migration {
+    version: i64,
+    description: &'static str,
+    sql: &'static str,
+}
+
+/// The schema's history. Append new migrations; never edit applied ones.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create_transcription_history_table",
+        sql: "CREATE TABLE IF NOT EXISTS transcription_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_name TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            saved BOOLEAN NOT NULL DEFAULT 0,
+            title TEXT NOT NULL,
+            transcription_text TEXT NOT NULL
+        );",
+    },
+    Migration {
+        version: 2,
+        description: "add_post_processed_text_column",
+        sql: "ALTER TABLE transcription_history ADD COLUMN post_processed_text TEXT;",
+    },
+    Migration {
+        version: 3,
+        description: "add_post_process_prompt_column",
+        sql: "ALTER TABLE transcription_history ADD COLUMN post_process_prompt TEXT;",
+    },
+    Migration {
+        version: 4,
+        description: "add_waveform_column",
+        sql: "ALTER TABLE transcription_history ADD COLUMN waveform TEXT;",
+    },
+    Migration {
+        version: 5,
+        description: "add_tag_column",
+        sql: "ALTER TABLE transcription_history ADD COLUMN tag TEXT;",
+    },
+    Migration {
+        version: 6,
+        description: "create_history_sessions_table",
+        sql: "CREATE TABLE IF NOT EXISTS history_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            started_at INTEGER NOT NULL,
+            ended_at INTEGER
+        );
+        ALTER TABLE transcription_history ADD COLUMN session_id INTEGER;
+        ALTER TABLE transcription_history ADD COLUMN duration_ms INTEGER;",
+    },
+    Migration {
+        version: 7,
+        description: "add_history_indexes",
+        sql: "CREATE INDEX IF NOT EXISTS idx_history_timestamp
+            ON transcription_history (timestamp);
+        CREATE INDEX IF NOT EXISTS idx_history_session
+            ON transcription_history (session_id);",
+    },
+    Migration {
+        version: 8,
+        description: "create_history_revisions_table",
+        sql: "CREATE TABLE IF NOT EXISTS history_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entry_id INTEGER NOT NULL,
+            text TEXT NOT NULL,
+            replaced_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_history_revisions_entry
+            ON history_revisions (entry_id);",
+    },
+    Migration {
+        version: 9,
+        description: "add_metadata_column",
+        sql: "ALTER TABLE transcription_history ADD COLUMN metadata TEXT;",
+    },
+];
+
+/// Schema version reached under tauri-plugin-sql, which ran the migrations
+/// before the backend did and tracked them in its own table
+fn imported_version(conn: &Connection) -> Result<i64> {
+    let has_table: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_table {
+        return Ok(0);
+    }
+    Ok(conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations WHERE success = 1",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+/// Bring the schema up to date, tracking the version in `PRAGMA user_version`
+fn migrate(conn: &mut Connection) -> Result<()> {
+    let mut version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version == 0 {
+        version = imported_version(conn)?;
+        if version > 0 {
+            info!("Imported history schema at version {}", version);
+            conn.execute_batch(&format!("PRAGMA user_version = {}", version))?;
+        }
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+        debug!(
+            "Applying history migration {}: {}",
+            migration.version, migration.description
+        );
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration.sql)?;
+        tx.execute_batch(&format!("PRAGMA user_version = {}", migration.version))?;
+        tx.commit()?;
+    }
+    Ok(())
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HistoryEntry {
@@ -20,12 +164,228 @@ pub struct HistoryEntry {
     pub transcription_text: String,
     pub post_processed_text: Option<String>,
     pub post_process_prompt: Option<String>,
+    /// Peak envelope of the recording, `WAVEFORM_POINTS` values in 0.0..=1.0
+    pub waveform: Option<Vec<f32>>,
+    /// Set on entries saved automatically, e.g. the hotword that bookmarked it
+    pub tag: Option<String>,
+    /// Caption session the entry was captured in
+    pub session_id: Option<i64>,
+    /// Length of the recording; entries saved before this was tracked have none
+    pub duration_ms: Option<i64>,
+    /// Earlier versions of `transcription_text` kept by edits
+    pub revision_count: i64,
+    pub metadata: Option<TranscriptionMetadata>,
+}
+
+/// Where an entry's audio came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureSource {
+    Microphone,
+    SystemAudio,
+    /// A transcribed audio file
+    File,
+    /// A recording recovered after a crash
+    Recovered,
+}
+
+impl From<AudioSource> for CaptureSource {
+    fn from(source: AudioSource) -> Self {
+        match source {
+            AudioSource::Microphone => CaptureSource::Microphone,
+            AudioSource::SystemAudio => CaptureSource::SystemAudio,
+        }
+    }
+}
+
+/// How an entry was captured and transcribed. Fields are left out where the
+/// capture path can't know them.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TranscriptionMetadata {
+    pub model: Option<String>,
+    pub source: Option<CaptureSource>,
+    /// Time from the end of the recording until the text was ready
+    pub latency_ms: Option<u64>,
+    pub language: Option<String>,
+    /// Frontmost application when the recording stopped
+    pub app_name: Option<String>,
+}
+
+/// Text an entry had before an edit replaced it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryRevision {
+    pub id: i64,
+    pub entry_id: i64,
+    pub text: String,
+    pub replaced_at: i64,
+}
+
+/// One page of history entries matching a search
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    /// Matching entries across all pages
+    pub total: i64,
+}
+
+/// Captions saved from one run of a caption stream
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistorySession {
+    pub id: i64,
+    pub title: String,
+    pub started_at: i64,
+    /// `None` while the stream is still running
+    pub ended_at: Option<i64>,
+    pub entry_count: i64,
+    /// Total length of the session's recordings
+    pub duration_ms: i64,
 }
 
+/// Dictation on one local day
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub words: usize,
+    pub entries: usize,
+    pub duration_ms: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AppUsage {
+    pub name: String,
+    pub entries: usize,
+    pub words: usize,
+}
+
+/// Totals over the whole history plus a per-day breakdown of recent days
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UsageStats {
+    pub total_entries: usize,
+    pub total_words: usize,
+    /// Combined length of all recordings
+    pub total_duration_ms: i64,
+    /// Over entries that recorded a latency
+    pub average_latency_ms: Option<u64>,
+    /// The last `STATS_DAYS` days, oldest first, including days without entries
+    pub daily: Vec<DailyUsage>,
+    /// Most dictated-into apps by entry count
+    pub top_apps: Vec<AppUsage>,
+}
+
+/// What the stats need from one entry
+struct UsageRecord {
+    day: NaiveDate,
+    words: usize,
+    duration_ms: i64,
+    latency_ms: Option<u64>,
+    app_name: Option<String>,
+}
+
+fn aggregate_usage(records: &[UsageRecord], today: NaiveDate) -> UsageStats {
+    let first_day = today - chrono::Duration::days(STATS_DAYS - 1);
+    let mut daily: Vec<DailyUsage> = first_day
+        .iter_days()
+        .take(STATS_DAYS as usize)
+        .map(|day| DailyUsage {
+            date: day.format("%Y-%m-%d").to_string(),
+            words: 0,
+            entries: 0,
+            duration_ms: 0,
+        })
+        .collect();
+
+    let mut apps: HashMap<&str, AppUsage> = HashMap::new();
+    let mut latencies = Vec::new();
+    for record in records {
+        if record.day >= first_day && record.day <= today {
+            let day = &mut daily[(record.day - first_day).num_days() as usize];
+            day.words += record.words;
+            day.entries += 1;
+            day.duration_ms += record.duration_ms;
+        }
+        if let Some(name) = &record.app_name {
+            let app = apps.entry(name).or_insert_with(|| AppUsage {
+                name: name.clone(),
+                entries: 0,
+                words: 0,
+            });
+            app.entries += 1;
+            app.words += record.words;
+        }
+        latencies.extend(record.latency_ms);
+    }
+
+    let mut top_apps: Vec<AppUsage> = apps.into_values().collect();
+    top_apps.sort_by(|a, b| b.entries.cmp(&a.entries).then_with(|| a.name.cmp(&b.name)));
+    top_apps.truncate(STATS_TOP_APPS);
+
+    UsageStats {
+        total_entries: records.len(),
+        total_words: records.iter().map(|r| r.words).sum(),
+        total_duration_ms: records.iter().map(|r| r.duration_ms).sum(),
+        average_latency_ms: (!latencies.is_empty())
+            .then(|| latencies.iter().sum::<u64>() / latencies.len() as u64),
+        daily,
+        top_apps,
+    }
+}
+
+/// Unsaved entries, given newest first as (id, file name, timestamp, audio
+/// bytes), that fall outside `retention` at `now`
+fn expired_entries(
+    entries: Vec<(i64, String, i64, u64)>,
+    retention: &HistoryRetention,
+    now: i64,
+) -> Vec<(i64, String)> {
+    let cutoff = now - retention.max_age_days as i64 * 24 * 60 * 60;
+    let max_audio_bytes = retention.max_audio_mb * 1024 * 1024;
+
+    let mut audio_bytes = 0u64;
+    let mut expired = Vec::new();
+    for (index, (id, file_name, timestamp, bytes)) in entries.into_iter().enumerate() {
+        audio_bytes += bytes;
+        let too_old = retention.max_age_days > 0 && timestamp < cutoff;
+        let too_many = retention.max_entries > 0 && index >= retention.max_entries;
+        let too_big = retention.max_audio_mb > 0 && audio_bytes > max_audio_bytes;
+        if too_old || too_many || too_big {
+            expired.push((id, file_name));
+        }
+    }
+    expired
+}
+
+/// Create an empty recording file for an entry saved at `timestamp` and
+/// return its name. Several entries can be saved within one second, such as
+/// captions from both channels, so later ones get a counter instead of
+/// overwriting the audio of the first.
+fn reserve_recording(dir: &Path, timestamp: i64, suffix: &str) -> Result<String> {
+    let mut n = 1;
+    loop {
+        let file_name = match n {
+            1 => format!("handy-{}{}.flac", timestamp, suffix),
+            _ => format!("handy-{}{}-{}.flac", timestamp, suffix, n),
+        };
+        let reserved = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dir.join(&file_name));
+        match reserved {
+            Ok(_) => return Ok(file_name),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct HistoryManager {
     app_handle: AppHandle,
     recordings_dir: PathBuf,
     db_path: PathBuf,
+    /// Dropped whenever history changes; kept with the day it was computed on
+    /// since the daily window moves at midnight
+    stats_cache: Arc<Mutex<Option<(NaiveDate, UsageStats)>>>,
 }
 
 impl HistoryManager {
@@ -45,70 +405,136 @@ impl HistoryManager {
             app_handle: app_handle.clone(),
             recordings_dir,
             db_path,
+            stats_cache: Arc::new(Mutex::new(None)),
         };
 
         // Initialize database
         manager.init_database()?;
 
-        Ok(manager)
-    }
+        // Apply the retention limits now and then periodically
+        let pruner = manager.clone();
+        thread::spawn(move || loop {
+            if let Err(e) = pruner.cleanup_old_entries() {
+                error!("Failed to prune history: {}", e);
+            }
+            thread::sleep(PRUNE_INTERVAL);
+        });
 
-    pub fn get_migrations() -> Vec<Migration> {
-        vec![
-            Migration {
-                version: 1,
-                description: "create_transcription_history_table",
-                sql: "CREATE TABLE IF NOT EXISTS transcription_history (
-                    id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    file_name TEXT NOT NULL,
-                    timestamp INTEGER NOT NULL,
-                    saved BOOLEAN NOT NULL DEFAULT 0,
-                    title TEXT NOT NULL,
-                    transcription_text TEXT NOT NULL
-                );",
-                kind: MigrationKind::Up,
-            },
-            Migration {
-                version: 2,
-                description: "add_post_processed_text_column",
-                sql: "ALTER TABLE transcription_history ADD COLUMN post_processed_text TEXT;",
-                kind: MigrationKind::Up,
-            },
-            Migration {
-                version: 3,
-                description: "add_post_process_prompt_column",
-                sql: "ALTER TABLE transcription_history ADD COLUMN post_process_prompt TEXT;",
-                kind: MigrationKind::Up,
-            },
-        ]
+        Ok(manager)
     }
 
     fn init_database(&self) -> Result<()> {
-        // Database initialization and migrations are handled by tauri-plugin-sql
-        // via the preload configuration in tauri.conf.json
         debug!("Database path: {:?}", self.db_path);
-        Ok(())
+        let mut conn = self.get_connection()?;
+        migrate(&mut conn)
     }
 
     fn get_connection(&self) -> Result<Connection> {
         Ok(Connection::open(&self.db_path)?)
     }
 
-    /// Save a transcription to history (both database and WAV file)
+    /// Drop cached stats and tell the frontend history changed
+    fn notify_changed(&self) {
+        *self.stats_cache.lock().unwrap() = None;
+        if let Err(e) = self.app_handle.emit("history-updated", ()) {
+            error!("Failed to emit history-updated event: {}", e);
+        }
+    }
+
+    /// Save a transcription to history (both database and FLAC file)
     pub async fn save_transcription(
         &self,
         audio_samples: Vec<f32>,
         transcription_text: String,
         post_processed_text: Option<String>,
         post_process_prompt: Option<String>,
+        metadata: TranscriptionMetadata,
+    ) -> Result<()> {
+        self.save_entry(
+            Utc::now().timestamp(),
+            audio_samples,
+            transcription_text,
+            post_processed_text,
+            post_process_prompt,
+            None,
+            None,
+            metadata,
+        )
+        .await
+    }
+
+    /// Open a session that the captions of one caption stream are grouped into
+    pub fn start_session(&self, label: &str) -> Result<i64> {
+        let started_at = Utc::now().timestamp();
+        let title = format!("{} - {}", label, self.format_timestamp_title(started_at));
+        let conn = self.get_connection()?;
+        conn.execute(
+            "INSERT INTO history_sessions (title, started_at) VALUES (?1, ?2)",
+            params![title, started_at],
+        )?;
+        let id = conn.last_insert_rowid();
+        debug!("Started history session {}: {}", id, title);
+        Ok(id)
+    }
+
+    /// Close a session once its caption stream stops
+    pub fn end_session(&self, id: i64) -> Result<()> {
+        let conn = self.get_connection()?;
+        conn.execute(
+            "UPDATE history_sessions SET ended_at = ?1 WHERE id = ?2",
+            params![Utc::now().timestamp(), id],
+        )?;
+
+        self.notify_changed();
+        Ok(())
+    }
+
+    /// Save a live caption, grouped into `session_id` when there is one
+    pub async fn save_caption(
+        &self,
+        session_id: Option<i64>,
+        audio_samples: Vec<f32>,
+        transcription_text: String,
+        tag: Option<String>,
+        metadata: TranscriptionMetadata,
+    ) -> Result<()> {
+        self.save_entry(
+            Utc::now().timestamp(),
+            audio_samples,
+            transcription_text,
+            None,
+            None,
+            tag,
+            session_id,
+            metadata,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn save_entry(
+        &self,
+        timestamp: i64,
+        audio_samples: Vec<f32>,
+        transcription_text: String,
+        post_processed_text: Option<String>,
+        post_process_prompt: Option<String>,
+        tag: Option<String>,
+        session_id: Option<i64>,
+        metadata: TranscriptionMetadata,
     ) -> Result<()> {
-        let timestamp = Utc::now().timestamp();
-        let file_name = format!("handy-{}.wav", timestamp);
         let title = self.format_timestamp_title(timestamp);
+        let waveform = waveform_envelope(&audio_samples, WAVEFORM_POINTS);
+        let duration_ms = (audio_samples.len() * 1000 / SAMPLE_RATE) as i64;
 
-        // Save WAV file
+        // Save FLAC file; tagged clips are named like hotword snippets
+        let suffix = if tag.is_some() { "-snippet" } else { "" };
+        let file_name = reserve_recording(&self.recordings_dir, timestamp, suffix)?;
         let file_path = self.recordings_dir.join(&file_name);
-        save_wav_file(file_path, &audio_samples).await?;
+        if let Err(e) = save_flac_file(&file_path, &audio_samples).await {
+            let _ = fs::remove_file(&file_path);
+            return Err(e);
+        }
 
         // Save to database
         self.save_to_database(
@@ -118,19 +544,22 @@ impl HistoryManager {
             transcription_text,
             post_processed_text,
             post_process_prompt,
+            &waveform,
+            tag,
+            session_id,
+            duration_ms,
+            &metadata,
         )?;
 
         // Clean up old entries
         self.cleanup_old_entries()?;
 
-        // Emit history updated event
-        if let Err(e) = self.app_handle.emit("history-updated", ()) {
-            error!("Failed to emit history-updated event: {}", e);
-        }
+        self.notify_changed();
 
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn save_to_database(
         &self,
         file_name: String,
@@ -139,37 +568,24 @@ impl HistoryManager {
         transcription_text: String,
         post_processed_text: Option<String>,
         post_process_prompt: Option<String>,
+        waveform: &[f32],
+        tag: Option<String>,
+        session_id: Option<i64>,
+        duration_ms: i64,
+        metadata: &TranscriptionMetadata,
     ) -> Result<()> {
+        let waveform = serde_json::to_string(waveform)?;
+        let metadata = serde_json::to_string(metadata)?;
         let conn = self.get_connection()?;
         conn.execute(
-            "INSERT INTO transcription_history (file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![file_name, timestamp, false, title, transcription_text, post_processed_text, post_process_prompt],
+            "INSERT INTO transcription_history (file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt, waveform, tag, session_id, duration_ms, metadata) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![file_name, timestamp, false, title, transcription_text, post_processed_text, post_process_prompt, waveform, tag, session_id, duration_ms, metadata],
         )?;
 
         debug!("Saved transcription to database");
         Ok(())
     }
 
-    pub fn cleanup_old_entries(&self) -> Result<()> {
-        let retention_period = crate::settings::get_recording_retention_period(&self.app_handle);
-
-        match retention_period {
-            crate::settings::RecordingRetentionPeriod::Never => {
-                // Don't delete anything
-                return Ok(());
-            }
-            crate::settings::RecordingRetentionPeriod::PreserveLimit => {
-                // Use the old count-based logic with history_limit
-                let limit = crate::settings::get_history_limit(&self.app_handle);
-                return self.cleanup_by_count(limit);
-            }
-            _ => {
-                // Use time-based logic
-                return self.cleanup_by_time(retention_period);
-            }
-        }
-    }
-
     fn delete_entries_and_files(&self, entries: &[(i64, String)]) -> Result<usize> {
         if entries.is_empty() {
             return Ok(0);
@@ -184,14 +600,18 @@ impl HistoryManager {
                 "DELETE FROM transcription_history WHERE id = ?1",
                 params![id],
             )?;
+            conn.execute(
+                "DELETE FROM history_revisions WHERE entry_id = ?1",
+                params![id],
+            )?;
 
-            // Delete WAV file
+            // Delete audio file
             let file_path = self.recordings_dir.join(file_name);
             if file_path.exists() {
                 if let Err(e) = fs::remove_file(&file_path) {
-                    error!("Failed to delete WAV file {}: {}", file_name, e);
+                    error!("Failed to delete audio file {}: {}", file_name, e);
                 } else {
-                    debug!("Deleted old WAV file: {}", file_name);
+                    debug!("Deleted old audio file: {}", file_name);
                     deleted_count += 1;
                 }
             }
@@ -200,101 +620,193 @@ impl HistoryManager {
         Ok(deleted_count)
     }
 
-    fn cleanup_by_count(&self, limit: usize) -> Result<()> {
+    /// Unsaved entries, newest first
+    fn unsaved_entries(&self) -> Result<Vec<(i64, String, i64)>> {
         let conn = self.get_connection()?;
-
-        // Get all entries that are not saved, ordered by timestamp desc
         let mut stmt = conn.prepare(
-            "SELECT id, file_name FROM transcription_history WHERE saved = 0 ORDER BY timestamp DESC"
+            "SELECT id, file_name, timestamp FROM transcription_history WHERE saved = 0 ORDER BY timestamp DESC, id DESC",
         )?;
-
         let rows = stmt.query_map([], |row| {
-            Ok((row.get::<_, i64>("id")?, row.get::<_, String>("file_name")?))
+            Ok((
+                row.get::<_, i64>("id")?,
+                row.get::<_, String>("file_name")?,
+                row.get::<_, i64>("timestamp")?,
+            ))
         })?;
 
-        let mut entries: Vec<(i64, String)> = Vec::new();
+        let mut entries = Vec::new();
         for row in rows {
             entries.push(row?);
         }
+        Ok(entries)
+    }
 
-        if entries.len() > limit {
-            let entries_to_delete = &entries[limit..];
-            let deleted_count = self.delete_entries_and_files(entries_to_delete)?;
+    /// Delete unsaved entries beyond the age, count and audio size limits in
+    /// the history retention settings. Returns how many entries were removed.
+    pub fn cleanup_old_entries(&self) -> Result<usize> {
+        let retention = crate::settings::get_settings(&self.app_handle).history_retention;
+        let entries: Vec<_> = self
+            .unsaved_entries()?
+            .into_iter()
+            .map(|(id, file_name, timestamp)| {
+                let bytes = fs::metadata(self.recordings_dir.join(&file_name))
+                    .map(|m| m.len())
+                    .unwrap_or(0);
+                (id, file_name, timestamp, bytes)
+            })
+            .collect();
+        let to_delete = expired_entries(entries, &retention, Utc::now().timestamp());
 
-            if deleted_count > 0 {
-                debug!("Cleaned up {} old history entries by count", deleted_count);
-            }
+        if to_delete.is_empty() {
+            return Ok(0);
         }
+        self.delete_entries_and_files(&to_delete)?;
+        self.delete_empty_sessions()?;
+        info!("Pruned {} history entries", to_delete.len());
 
-        Ok(())
+        self.notify_changed();
+        Ok(to_delete.len())
     }
 
-    fn cleanup_by_time(
-        &self,
-        retention_period: crate::settings::RecordingRetentionPeriod,
-    ) -> Result<()> {
-        let conn = self.get_connection()?;
-
-        // Calculate cutoff timestamp (current time minus retention period)
-        let now = Utc::now().timestamp();
-        let cutoff_timestamp = match retention_period {
-            crate::settings::RecordingRetentionPeriod::Days3 => now - (3 * 24 * 60 * 60), // 3 days in seconds
-            crate::settings::RecordingRetentionPeriod::Weeks2 => now - (2 * 7 * 24 * 60 * 60), // 2 weeks in seconds
-            crate::settings::RecordingRetentionPeriod::Months3 => now - (3 * 30 * 24 * 60 * 60), // 3 months in seconds (approximate)
-            _ => unreachable!("Should not reach here"),
+    /// Delete every entry, keeping saved ones unless `include_saved` is set.
+    /// Returns how many entries were removed.
+    pub fn purge(&self, include_saved: bool) -> Result<usize> {
+        let entries: Vec<(i64, String)> = if include_saved {
+            let conn = self.get_connection()?;
+            let mut stmt = conn.prepare("SELECT id, file_name FROM transcription_history")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, i64>("id")?, row.get::<_, String>("file_name")?))
+            })?;
+            let mut entries = Vec::new();
+            for row in rows {
+                entries.push(row?);
+            }
+            entries
+        } else {
+            self.unsaved_entries()?
+                .into_iter()
+                .map(|(id, file_name, _)| (id, file_name))
+                .collect()
         };
 
-        // Get all unsaved entries older than the cutoff timestamp
-        let mut stmt = conn.prepare(
-            "SELECT id, file_name FROM transcription_history WHERE saved = 0 AND timestamp < ?1",
+        self.delete_entries_and_files(&entries)?;
+        self.delete_empty_sessions()?;
+        info!("Purged {} history entries", entries.len());
+
+        self.notify_changed();
+        Ok(entries.len())
+    }
+
+    /// Sessions whose entries were all pruned; ones still running are kept
+    fn delete_empty_sessions(&self) -> Result<()> {
+        let conn = self.get_connection()?;
+        conn.execute(
+            "DELETE FROM history_sessions WHERE ended_at IS NOT NULL AND id NOT IN
+             (SELECT session_id FROM transcription_history WHERE session_id IS NOT NULL)",
+            [],
         )?;
+        Ok(())
+    }
 
-        let rows = stmt.query_map(params![cutoff_timestamp], |row| {
-            Ok((row.get::<_, i64>("id")?, row.get::<_, String>("file_name")?))
-        })?;
+    fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
+        // Stored as JSON; entries saved before the columns existed have none
+        let waveform: Option<String> = row.get("waveform")?;
+        let metadata: Option<String> = row.get("metadata")?;
+        Ok(HistoryEntry {
+            id: row.get("id")?,
+            file_name: row.get("file_name")?,
+            timestamp: row.get("timestamp")?,
+            saved: row.get("saved")?,
+            title: row.get("title")?,
+            transcription_text: row.get("transcription_text")?,
+            post_processed_text: row.get("post_processed_text")?,
+            post_process_prompt: row.get("post_process_prompt")?,
+            waveform: waveform.and_then(|w| serde_json::from_str(&w).ok()),
+            tag: row.get("tag")?,
+            session_id: row.get("session_id")?,
+            duration_ms: row.get("duration_ms")?,
+            revision_count: row.get("revision_count")?,
+            metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
+        })
+    }
+
+    pub async fn get_history_entries(&self) -> Result<Vec<HistoryEntry>> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM transcription_history ORDER BY timestamp DESC",
+            ENTRY_COLUMNS
+        ))?;
+
+        let rows = stmt.query_map([], Self::row_to_entry)?;
 
-        let mut entries_to_delete: Vec<(i64, String)> = Vec::new();
+        let mut entries = Vec::new();
         for row in rows {
-            entries_to_delete.push(row?);
+            entries.push(row?);
         }
 
-        let deleted_count = self.delete_entries_and_files(&entries_to_delete)?;
+        Ok(entries)
+    }
+
+    /// Entries recorded between `from` and `to` (inclusive, either open), oldest first
+    pub fn get_entries_between(
+        &self,
+        from: Option<i64>,
+        to: Option<i64>,
+    ) -> Result<Vec<HistoryEntry>> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM transcription_history WHERE timestamp >= ?1 AND timestamp <= ?2 ORDER BY timestamp ASC, id ASC",
+            ENTRY_COLUMNS
+        ))?;
 
-        if deleted_count > 0 {
-            debug!(
-                "Cleaned up {} old history entries based on retention period",
-                deleted_count
-            );
-        }
+        let rows = stmt.query_map(
+            params![from.unwrap_or(i64::MIN), to.unwrap_or(i64::MAX)],
+            Self::row_to_entry,
+        )?;
 
-        Ok(())
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row?);
+        }
+        Ok(entries)
     }
 
-    pub async fn get_history_entries(&self) -> Result<Vec<HistoryEntry>> {
+    /// Newest-first entries whose text or title contains `query`
+    pub fn search_entries(&self, query: &str, limit: usize, offset: usize) -> Result<HistoryPage> {
         let conn = self.get_connection()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt FROM transcription_history ORDER BY timestamp DESC"
+        // Escape LIKE wildcards so the query matches literally
+        let pattern = format!(
+            "%{}%",
+            query
+                .trim()
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let filter = "WHERE transcription_text LIKE ?1 ESCAPE '\\'
+            OR post_processed_text LIKE ?1 ESCAPE '\\'
+            OR title LIKE ?1 ESCAPE '\\'";
+
+        let total: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM transcription_history {}", filter),
+            params![pattern],
+            |row| row.get(0),
         )?;
 
-        let rows = stmt.query_map([], |row| {
-            Ok(HistoryEntry {
-                id: row.get("id")?,
-                file_name: row.get("file_name")?,
-                timestamp: row.get("timestamp")?,
-                saved: row.get("saved")?,
-                title: row.get("title")?,
-                transcription_text: row.get("transcription_text")?,
-                post_processed_text: row.get("post_processed_text")?,
-                post_process_prompt: row.get("post_process_prompt")?,
-            })
-        })?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM transcription_history {} ORDER BY timestamp DESC, id DESC LIMIT ?2 OFFSET ?3",
+            ENTRY_COLUMNS, filter
+        ))?;
+        let rows = stmt.query_map(
+            params![pattern, limit as i64, offset as i64],
+            Self::row_to_entry,
+        )?;
 
         let mut entries = Vec::new();
         for row in rows {
             entries.push(row?);
         }
-
-        Ok(entries)
+        Ok(HistoryPage { entries, total })
     }
 
     pub async fn toggle_saved_status(&self, id: i64) -> Result<()> {
@@ -316,39 +828,128 @@ impl HistoryManager {
 
         debug!("Toggled saved status for entry {}: {}", id, new_saved);
 
-        // Emit history updated event
-        if let Err(e) = self.app_handle.emit("history-updated", ()) {
-            error!("Failed to emit history-updated event: {}", e);
+        self.notify_changed();
+
+        Ok(())
+    }
+
+    /// Replace an entry's transcription, e.g. after re-transcribing or
+    /// correcting it. The previous text is kept as a revision.
+    pub fn update_transcription_text(&self, id: i64, text: &str) -> Result<()> {
+        let mut conn = self.get_connection()?;
+        let tx = conn.transaction()?;
+        let current: String = tx
+            .query_row(
+                "SELECT transcription_text FROM transcription_history WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| anyhow::anyhow!("History entry {} not found", id))?;
+        if current == text {
+            return Ok(());
         }
 
+        tx.execute(
+            "INSERT INTO history_revisions (entry_id, text, replaced_at) VALUES (?1, ?2, ?3)",
+            params![id, current, Utc::now().timestamp()],
+        )?;
+        tx.execute(
+            "UPDATE transcription_history SET transcription_text = ?1 WHERE id = ?2",
+            params![text, id],
+        )?;
+        tx.commit()?;
+
+        debug!("Updated transcription text for entry {}", id);
+
+        self.notify_changed();
+
         Ok(())
     }
 
+    /// Earlier texts of an entry, newest first
+    pub fn get_revisions(&self, id: i64) -> Result<Vec<HistoryRevision>> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, entry_id, text, replaced_at FROM history_revisions
+             WHERE entry_id = ?1 ORDER BY replaced_at DESC, id DESC",
+        )?;
+
+        let rows = stmt.query_map([id], |row| {
+            Ok(HistoryRevision {
+                id: row.get("id")?,
+                entry_id: row.get("entry_id")?,
+                text: row.get("text")?,
+                replaced_at: row.get("replaced_at")?,
+            })
+        })?;
+
+        let mut revisions = Vec::new();
+        for row in rows {
+            revisions.push(row?);
+        }
+        Ok(revisions)
+    }
+
+    /// Usage across the whole history, cached until it next changes
+    pub fn usage_stats(&self) -> Result<UsageStats> {
+        let today = Local::now().date_naive();
+        if let Some((day, stats)) = self.stats_cache.lock().unwrap().as_ref() {
+            if *day == today {
+                return Ok(stats.clone());
+            }
+        }
+
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, transcription_text, post_processed_text, duration_ms, metadata FROM transcription_history",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let timestamp: i64 = row.get(0)?;
+            let text: String = row.get(1)?;
+            let post_processed: Option<String> = row.get(2)?;
+            let duration_ms: Option<i64> = row.get(3)?;
+            let metadata: Option<String> = row.get(4)?;
+            let metadata: TranscriptionMetadata = metadata
+                .and_then(|m| serde_json::from_str(&m).ok())
+                .unwrap_or_default();
+            Ok(UsageRecord {
+                day: DateTime::from_timestamp(timestamp, 0)
+                    .unwrap_or_default()
+                    .with_timezone(&Local)
+                    .date_naive(),
+                words: post_processed
+                    .as_deref()
+                    .unwrap_or(&text)
+                    .split_whitespace()
+                    .count(),
+                duration_ms: duration_ms.unwrap_or(0),
+                latency_ms: metadata.latency_ms,
+                app_name: metadata.app_name,
+            })
+        })?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            records.push(row?);
+        }
+        let stats = aggregate_usage(&records, today);
+        *self.stats_cache.lock().unwrap() = Some((today, stats.clone()));
+        Ok(stats)
+    }
+
     pub fn get_audio_file_path(&self, file_name: &str) -> PathBuf {
         self.recordings_dir.join(file_name)
     }
 
     pub async fn get_entry_by_id(&self, id: i64) -> Result<Option<HistoryEntry>> {
         let conn = self.get_connection()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt
-             FROM transcription_history WHERE id = ?1",
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM transcription_history WHERE id = ?1",
+            ENTRY_COLUMNS
+        ))?;
 
-        let entry = stmt
-            .query_row([id], |row| {
-                Ok(HistoryEntry {
-                    id: row.get("id")?,
-                    file_name: row.get("file_name")?,
-                    timestamp: row.get("timestamp")?,
-                    saved: row.get("saved")?,
-                    title: row.get("title")?,
-                    transcription_text: row.get("transcription_text")?,
-                    post_processed_text: row.get("post_processed_text")?,
-                    post_process_prompt: row.get("post_process_prompt")?,
-                })
-            })
-            .optional()?;
+        let entry = stmt.query_row([id], Self::row_to_entry).optional()?;
 
         Ok(entry)
     }
@@ -373,13 +974,85 @@ impl HistoryManager {
             "DELETE FROM transcription_history WHERE id = ?1",
             params![id],
         )?;
+        conn.execute(
+            "DELETE FROM history_revisions WHERE entry_id = ?1",
+            params![id],
+        )?;
 
         debug!("Deleted history entry with id: {}", id);
 
-        // Emit history updated event
-        if let Err(e) = self.app_handle.emit("history-updated", ()) {
-            error!("Failed to emit history-updated event: {}", e);
+        self.notify_changed();
+
+        Ok(())
+    }
+
+    pub fn get_sessions(&self) -> Result<Vec<HistorySession>> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT s.id, s.title, s.started_at, s.ended_at, COUNT(h.id) AS entry_count, COALESCE(SUM(h.duration_ms), 0) AS duration_ms
+             FROM history_sessions s LEFT JOIN transcription_history h ON h.session_id = s.id
+             GROUP BY s.id ORDER BY s.started_at DESC",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(HistorySession {
+                id: row.get("id")?,
+                title: row.get("title")?,
+                started_at: row.get("started_at")?,
+                ended_at: row.get("ended_at")?,
+                entry_count: row.get("entry_count")?,
+                duration_ms: row.get("duration_ms")?,
+            })
+        })?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            sessions.push(row?);
         }
+        Ok(sessions)
+    }
+
+    /// A session's captions, oldest first
+    pub fn get_session_entries(&self, session_id: i64) -> Result<Vec<HistoryEntry>> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            &format!(
+            "SELECT {} FROM transcription_history WHERE session_id = ?1 ORDER BY timestamp ASC, id ASC",
+            ENTRY_COLUMNS
+        ),
+        )?;
+
+        let rows = stmt.query_map([session_id], Self::row_to_entry)?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row?);
+        }
+        Ok(entries)
+    }
+
+    /// Delete a session together with all of its entries and recordings
+    pub fn delete_session(&self, session_id: i64) -> Result<()> {
+        let entries: Vec<(i64, String)> = self
+            .get_session_entries(session_id)?
+            .into_iter()
+            .map(|entry| (entry.id, entry.file_name))
+            .collect();
+        self.delete_entries_and_files(&entries)?;
+
+        let conn = self.get_connection()?;
+        conn.execute(
+            "DELETE FROM history_sessions WHERE id = ?1",
+            params![session_id],
+        )?;
+
+        debug!(
+            "Deleted history session {} with {} entries",
+            session_id,
+            entries.len()
+        );
+
+        self.notify_changed();
 
         Ok(())
     }
@@ -394,3 +1067,150 @@ impl HistoryManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_version(conn: &Connection) -> i64 {
+        conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_migrate_fresh_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        assert_eq!(user_version(&conn), MIGRATIONS.last().unwrap().version);
+
+        // Running again is a no-op
+        migrate(&mut conn).unwrap();
+        conn.execute(
+            "INSERT INTO transcription_history (file_name, timestamp, title, transcription_text, session_id, duration_ms) VALUES ('a.wav', 1, 't', 'hi', 2, 3000)",
+            [],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_migrate_imports_plugin_schema() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE _sqlx_migrations (version BIGINT PRIMARY KEY, success BOOLEAN NOT NULL);
+             INSERT INTO _sqlx_migrations VALUES (1, 1), (2, 1), (3, 1), (4, 1), (5, 1);",
+        )
+        .unwrap();
+        for migration in MIGRATIONS.iter().filter(|m| m.version <= 5) {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+
+        // Re-running the imported migrations would fail on duplicate columns
+        migrate(&mut conn).unwrap();
+        assert_eq!(user_version(&conn), MIGRATIONS.last().unwrap().version);
+        let sessions: i64 = conn
+            .query_row("SELECT COUNT(*) FROM history_sessions", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(sessions, 0);
+    }
+
+    #[test]
+    fn test_recordings_saved_in_one_second_get_their_own_files() {
+        let dir = std::env::temp_dir().join(format!("handy-history-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let first = reserve_recording(&dir, 1_700_000_000, "").unwrap();
+        fs::write(dir.join(&first), b"first").unwrap();
+        let second = reserve_recording(&dir, 1_700_000_000, "").unwrap();
+        let snippet = reserve_recording(&dir, 1_700_000_000, "-snippet").unwrap();
+        assert_eq!(first, "handy-1700000000.flac");
+        assert_eq!(second, "handy-1700000000-2.flac");
+        assert_eq!(snippet, "handy-1700000000-snippet.flac");
+        assert_eq!(fs::read(dir.join(&first)).unwrap(), b"first");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_expired_entries_apply_every_limit() {
+        let day = 24 * 60 * 60;
+        let now = 100 * day;
+        let mb = 1024 * 1024;
+        // Newest first, one a day
+        let entries: Vec<_> = (0..6)
+            .map(|n| (n, format!("{}.flac", n), now - n * day, mb))
+            .collect();
+        let ids = |retention: &HistoryRetention| -> Vec<i64> {
+            expired_entries(entries.clone(), retention, now)
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect()
+        };
+
+        let off = HistoryRetention {
+            max_age_days: 0,
+            max_entries: 0,
+            max_audio_mb: 0,
+        };
+        assert!(ids(&off).is_empty());
+        // Age and count both set: whichever is stricter wins
+        let both = HistoryRetention {
+            max_age_days: 4,
+            max_entries: 2,
+            ..off.clone()
+        };
+        assert_eq!(ids(&both), [2, 3, 4, 5]);
+        let both = HistoryRetention {
+            max_age_days: 2,
+            max_entries: 5,
+            ..off.clone()
+        };
+        assert_eq!(ids(&both), [3, 4, 5]);
+        let size = HistoryRetention {
+            max_audio_mb: 3,
+            ..off
+        };
+        assert_eq!(ids(&size), [3, 4, 5]);
+    }
+
+    #[test]
+    fn test_aggregate_usage() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let record = |days_ago: i64, words, latency_ms, app: Option<&str>| UsageRecord {
+            day: today - chrono::Duration::days(days_ago),
+            words,
+            duration_ms: 1000,
+            latency_ms,
+            app_name: app.map(str::to_string),
+        };
+        let stats = aggregate_usage(
+            &[
+                record(0, 10, Some(200), Some("Slack")),
+                record(0, 5, Some(400), Some("Notes")),
+                record(1, 3, None, Some("Slack")),
+                record(STATS_DAYS, 7, None, None),
+            ],
+            today,
+        );
+
+        assert_eq!(stats.total_entries, 4);
+        assert_eq!(stats.total_words, 25);
+        assert_eq!(stats.total_duration_ms, 4000);
+        assert_eq!(stats.average_latency_ms, Some(300));
+        assert_eq!(stats.daily.len(), STATS_DAYS as usize);
+        assert_eq!(stats.daily[0].date, "2024-02-10");
+        let last = stats.daily.last().unwrap();
+        assert_eq!(
+            (last.date.as_str(), last.words, last.entries),
+            ("2024-03-10", 15, 2)
+        );
+        assert_eq!(stats.daily[STATS_DAYS as usize - 2].words, 3);
+        let apps: Vec<_> = stats
+            .top_apps
+            .iter()
+            .map(|a| (a.name.as_str(), a.entries))
+            .collect();
+        assert_eq!(apps, [("Slack", 2), ("Notes", 1)]);
+    }
+}
@@ -0,0 +1,172 @@
+// Crash-safe journal for in-progress recordings
+// While recording, every 16kHz frame is appended to a file in the journal
+// directory. A clean stop deletes it, so any journal left on startup belongs to
+// a take that was interrupted by a crash and can be recovered.
+
+use crate::audio_toolkit::stream::AudioFrameStream;
+use chrono::Utc;
+use futures_util::StreamExt;
+use log::{debug, error, warn};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::oneshot;
+
+const JOURNAL_EXTENSION: &str = "journal";
+const JOURNAL_SAMPLE_RATE: f64 = 16000.0;
+// Bound on how long a stop waits for the writer to close the file
+const WRITER_SHUTDOWN_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OrphanedRecording {
+    pub id: String,
+    /// Unix time in milliseconds when the recording started
+    pub started_at: i64,
+    pub duration_secs: f64,
+}
+
+pub fn journal_dir(app: &AppHandle) -> anyhow::Result<PathBuf> {
+    Ok(app
+        .path()
+        .app_data_dir()?
+        .join("recordings")
+        .join("journal"))
+}
+
+/// Path of the journal with `id`, rejecting ids that could escape the directory
+pub fn journal_path(dir: &Path, id: &str) -> Option<PathBuf> {
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        .then(|| dir.join(format!("{}.{}", id, JOURNAL_EXTENSION)))
+}
+
+/// Journals left behind by recordings that never stopped cleanly
+pub fn list_orphaned(dir: &Path) -> Vec<OrphanedRecording> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut orphans: Vec<OrphanedRecording> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != JOURNAL_EXTENSION {
+                return None;
+            }
+            let id = path.file_stem()?.to_str()?.to_string();
+            let bytes = entry.metadata().ok()?.len();
+            Some(OrphanedRecording {
+                started_at: id.parse().ok()?,
+                id,
+                duration_secs: (bytes / 4) as f64 / JOURNAL_SAMPLE_RATE,
+            })
+        })
+        .collect();
+    orphans.sort_by_key(|o| o.started_at);
+    orphans
+}
+
+fn encode_samples(samples: &[f32]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+/// Read a journal back as 16kHz mono samples. A partially written trailing
+/// sample from the crash is ignored.
+pub fn read_journal(path: &Path) -> io::Result<Vec<f32>> {
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+/// Journal for the recording in progress. Dropping it without calling
+/// `finish` leaves the file on disk, as a crash would.
+pub struct RecordingJournal {
+    path: PathBuf,
+    stop: Option<oneshot::Sender<()>>,
+    done: mpsc::Receiver<()>,
+}
+
+impl RecordingJournal {
+    /// Start appending every frame from `frames` to a new journal in `dir`
+    pub fn start(dir: &Path, mut frames: AudioFrameStream) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "{}.{}",
+            Utc::now().timestamp_millis(),
+            JOURNAL_EXTENSION
+        ));
+        let mut file = File::create(&path)?;
+        debug!("Recording journal started at {:?}", path);
+
+        let (stop, mut stop_rx) = oneshot::channel();
+        let (done_tx, done) = mpsc::channel();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = &mut stop_rx => break,
+                    frame = frames.next() => match frame {
+                        // Unbuffered so everything captured so far survives a crash
+                        Some(frame) => {
+                            if let Err(e) = file.write_all(&encode_samples(&frame.samples)) {
+                                error!("Failed to write recording journal: {}", e);
+                                break;
+                            }
+                        }
+                        None => break,
+                    },
+                }
+            }
+            drop(file);
+            let _ = done_tx.send(());
+        });
+
+        Ok(Self {
+            path,
+            stop: Some(stop),
+            done,
+        })
+    }
+
+    /// The recording stopped cleanly; stop writing and delete the journal
+    pub fn finish(mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if self.done.recv_timeout(WRITER_SHUTDOWN_TIMEOUT).is_err() {
+            warn!("Recording journal writer did not stop in time");
+        }
+        if let Err(e) = fs::remove_file(&self.path) {
+            warn!("Failed to remove recording journal {:?}: {}", self.path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journal_round_trip_ignores_torn_sample() {
+        let dir = std::env::temp_dir().join(format!("handy-journal-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = journal_path(&dir, "1700000000000").unwrap();
+
+        let mut bytes = encode_samples(&[0.5, -0.25, 1.0]);
+        bytes.extend_from_slice(&[0, 0]);
+        fs::write(&path, bytes).unwrap();
+
+        assert_eq!(read_journal(&path).unwrap(), vec![0.5, -0.25, 1.0]);
+        let orphans = list_orphaned(&dir);
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].started_at, 1_700_000_000_000);
+        assert!(journal_path(&dir, "../history").is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,4 +1,12 @@
 pub mod audio;
+pub mod audio_backend;
+pub mod devices;
 pub mod history;
+pub mod journal;
 pub mod model;
+pub mod playback;
+pub mod power;
+pub mod queue;
+pub mod segments;
 pub mod transcription;
+pub mod workers;
@@ -1,18 +1,26 @@
-use crate::settings::{get_settings, write_settings};
+use crate::helpers::hardware::{hardware_profile, HardwareProfile};
+use crate::settings::{get_settings, write_settings, ModelSelection};
 use anyhow::Result;
 use flate2::read::GzDecoder;
 use futures_util::StreamExt;
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tar::Archive;
 use tauri::{AppHandle, Emitter, Manager};
 
+// Share of installed memory a model may take and still leave the rest of the
+// system comfortable
+const MODEL_MEMORY_SHARE: f64 = 0.25;
+// Assumed when the probe can't read installed memory
+const FALLBACK_MEMORY_MB: u64 = 8 * 1024;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EngineType {
     Whisper,
@@ -34,6 +42,8 @@ pub struct ModelInfo {
     pub engine_type: EngineType,
     pub accuracy_score: f32, // 0.0 to 1.0, higher is more accurate
     pub speed_score: f32,    // 0.0 to 1.0, higher is faster
+    /// SHA-256 of the file or archive at `url`, checked after download
+    pub sha256: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +92,7 @@ impl ModelManager {
                 engine_type: EngineType::Whisper,
                 accuracy_score: 0.60,
                 speed_score: 0.85,
+                sha256: None,
             },
         );
 
@@ -102,6 +113,7 @@ impl ModelManager {
                 engine_type: EngineType::Whisper,
                 accuracy_score: 0.75,
                 speed_score: 0.60,
+                sha256: None,
             },
         );
 
@@ -121,6 +133,7 @@ impl ModelManager {
                 engine_type: EngineType::Whisper,
                 accuracy_score: 0.80,
                 speed_score: 0.40,
+                sha256: None,
             },
         );
 
@@ -140,6 +153,7 @@ impl ModelManager {
                 engine_type: EngineType::Whisper,
                 accuracy_score: 0.85,
                 speed_score: 0.30,
+                sha256: None,
             },
         );
 
@@ -160,6 +174,7 @@ impl ModelManager {
                 engine_type: EngineType::Parakeet,
                 accuracy_score: 0.85,
                 speed_score: 0.85,
+                sha256: None,
             },
         );
 
@@ -179,6 +194,7 @@ impl ModelManager {
                 engine_type: EngineType::Parakeet,
                 accuracy_score: 0.80,
                 speed_score: 0.85,
+                sha256: None,
             },
         );
 
@@ -284,10 +300,36 @@ impl ModelManager {
         Ok(())
     }
 
+    /// The largest model this machine runs comfortably, among the downloaded
+    /// ones when `downloaded_only`
+    pub fn recommended_model(&self, downloaded_only: bool) -> Option<ModelInfo> {
+        let models = self.available_models.lock().unwrap();
+        let candidates: Vec<&ModelInfo> = models
+            .values()
+            .filter(|model| model.is_downloaded || !downloaded_only)
+            .collect();
+        recommend_model(&candidates, hardware_profile()).cloned()
+    }
+
     fn auto_select_model_if_needed(&self) -> Result<()> {
         // Check if we have a selected model in settings
         let settings = get_settings(&self.app_handle);
 
+        if settings.model_selection == ModelSelection::Recommended {
+            if let Some(recommended) = self.recommended_model(true) {
+                if recommended.id != settings.selected_model {
+                    info!(
+                        "Selecting recommended model: {} ({})",
+                        recommended.id, recommended.name
+                    );
+                    let mut updated_settings = settings;
+                    updated_settings.selected_model = recommended.id;
+                    write_settings(&self.app_handle, updated_settings);
+                }
+                return Ok(());
+            }
+        }
+
         // If no model is selected or selected model is empty
         if settings.selected_model.is_empty() {
             // Find the first available (downloaded) model
@@ -402,6 +444,12 @@ impl ModelManager {
             std::fs::File::create(&partial_path)?
         };
 
+        // Hash as the bytes arrive; a resumed download hashes what it already has
+        let mut hasher = Sha256::new();
+        if resume_from > 0 {
+            hash_reader(File::open(&partial_path)?, &mut hasher)?;
+        }
+
         // Emit initial progress
         let initial_progress = DownloadProgress {
             model_id: model_id.to_string(),
@@ -431,6 +479,7 @@ impl ModelManager {
             })?;
 
             file.write_all(&chunk)?;
+            hasher.update(&chunk);
             downloaded += chunk.len() as u64;
 
             let percentage = if total_size > 0 {
@@ -453,6 +502,34 @@ impl ModelManager {
         file.flush()?;
         drop(file); // Ensure file is closed before moving
 
+        let digest = format!("{:x}", hasher.finalize());
+        if let Some(expected) = &model_info.sha256 {
+            if !digest.eq_ignore_ascii_case(expected) {
+                // A corrupt partial can't be resumed into a good file
+                let _ = fs::remove_file(&partial_path);
+                {
+                    let mut models = self.available_models.lock().unwrap();
+                    if let Some(model) = models.get_mut(model_id) {
+                        model.is_downloading = false;
+                        model.partial_size = 0;
+                    }
+                }
+                let error_msg = format!(
+                    "Checksum mismatch for {}: expected {}, got {}",
+                    model_id, expected, digest
+                );
+                let _ = self.app_handle.emit(
+                    "model-verification-failed",
+                    &serde_json::json!({
+                        "model_id": model_id,
+                        "error": error_msg
+                    }),
+                );
+                return Err(anyhow::anyhow!(error_msg));
+            }
+            info!("Checksum verified for model {}", model_id);
+        }
+
         // Handle directory-based models (extract tar.gz) vs file-based models
         if model_info.is_directory {
             // Emit extraction started event
@@ -527,6 +604,16 @@ impl ModelManager {
             fs::rename(&partial_path, &model_path)?;
         }
 
+        // Record what was installed so `verify_model` can detect later corruption
+        let installed_hash = if model_info.is_directory {
+            content_hash(&model_path)?
+        } else {
+            digest
+        };
+        if let Err(e) = fs::write(self.checksum_path(&model_info), installed_hash) {
+            warn!("Failed to record checksum for {}: {}", model_id, e);
+        }
+
         // Update download status
         {
             let mut models = self.available_models.lock().unwrap();
@@ -599,6 +686,7 @@ impl ModelManager {
         if !deleted_something {
             return Err(anyhow::anyhow!("No model files found to delete"));
         }
+        let _ = fs::remove_file(self.checksum_path(&model_info));
 
         // Update download status
         self.update_download_status()?;
@@ -652,6 +740,41 @@ impl ModelManager {
         }
     }
 
+    fn checksum_path(&self, model_info: &ModelInfo) -> PathBuf {
+        self.models_dir
+            .join(format!("{}.sha256", &model_info.filename))
+    }
+
+    /// Re-hash a downloaded model and compare it with the checksum recorded
+    /// when it was installed. Models installed before checksums were recorded
+    /// have theirs recorded now and pass.
+    pub fn verify_model(&self, model_id: &str) -> Result<bool> {
+        let model_path = self.get_model_path(model_id)?;
+        let model_info = self
+            .get_model_info(model_id)
+            .ok_or_else(|| anyhow::anyhow!("Model not found: {}", model_id))?;
+        let checksum_path = self.checksum_path(&model_info);
+
+        let actual = content_hash(&model_path)?;
+        let expected = match fs::read_to_string(&checksum_path) {
+            Ok(recorded) => recorded.trim().to_string(),
+            Err(_) => {
+                info!("No recorded checksum for {}, recording it now", model_id);
+                fs::write(&checksum_path, &actual)?;
+                return Ok(true);
+            }
+        };
+
+        let ok = actual.eq_ignore_ascii_case(&expected);
+        if !ok {
+            warn!(
+                "Model {} failed verification: expected {}, got {}",
+                model_id, expected, actual
+            );
+        }
+        Ok(ok)
+    }
+
     pub fn cancel_download(&self, model_id: &str) -> Result<()> {
         debug!("ModelManager: cancel_download called for: {}", model_id);
 
@@ -682,3 +805,161 @@ impl ModelManager {
         Ok(())
     }
 }
+
+fn hash_reader(mut reader: impl Read, hasher: &mut Sha256) -> Result<()> {
+    let mut buffer = vec![0u8; 1 << 16];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(());
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+/// SHA-256 of a model file, or for a model directory of every file in it
+/// (relative path and contents, in path order)
+fn content_hash(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    if path.is_dir() {
+        let mut files = Vec::new();
+        let mut pending = vec![path.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let entry_path = entry?.path();
+                if entry_path.is_dir() {
+                    pending.push(entry_path);
+                } else {
+                    files.push(entry_path);
+                }
+            }
+        }
+        files.sort();
+        for file in files {
+            let relative = file.strip_prefix(path).unwrap_or(&file);
+            hasher.update(relative.to_string_lossy().replace('\\', "/").as_bytes());
+            hasher.update([0u8]);
+            hash_reader(File::open(&file)?, &mut hasher)?;
+        }
+    } else {
+        hash_reader(File::open(path)?, &mut hasher)?;
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Memory a loaded model takes: its weights plus the engine's working buffers
+fn estimated_memory_mb(model: &ModelInfo) -> u64 {
+    model.size_mb * 3 / 2
+}
+
+/// Pick the largest of `models` that fits in `hardware`'s memory budget and,
+/// without a GPU, is fast enough for its cores to keep up with dictation
+fn recommend_model<'a>(
+    models: &[&'a ModelInfo],
+    hardware: &HardwareProfile,
+) -> Option<&'a ModelInfo> {
+    let memory_mb = hardware.total_memory_mb.unwrap_or(FALLBACK_MEMORY_MB);
+    let budget_mb = (memory_mb as f64 * MODEL_MEMORY_SHARE) as u64;
+    let min_speed = if hardware.gpu.is_some() {
+        0.0
+    } else if hardware.cpu_cores >= 8 {
+        0.4
+    } else {
+        0.6
+    };
+
+    models
+        .iter()
+        .copied()
+        .filter(|model| estimated_memory_mb(model) <= budget_mb)
+        .filter(|model| model.speed_score >= min_speed)
+        .max_by(|a, b| {
+            a.size_mb
+                .cmp(&b.size_mb)
+                .then(a.accuracy_score.total_cmp(&b.accuracy_score))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::hardware::GpuAcceleration;
+
+    fn model(id: &str, size_mb: u64, speed_score: f32) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            filename: format!("{}.bin", id),
+            url: None,
+            size_mb,
+            is_downloaded: true,
+            is_downloading: false,
+            partial_size: 0,
+            is_directory: false,
+            engine_type: EngineType::Whisper,
+            accuracy_score: 0.8,
+            speed_score,
+            sha256: None,
+        }
+    }
+
+    #[test]
+    fn test_recommend_model_fits_hardware() {
+        let small = model("small", 487, 0.85);
+        let medium = model("medium", 492, 0.6);
+        let large = model("large", 1100, 0.3);
+        let turbo = model("turbo", 1600, 0.4);
+        let models = [&small, &medium, &large, &turbo];
+        let pick = |cpu_cores, total_memory_mb, gpu| {
+            let hardware = HardwareProfile {
+                cpu_cores,
+                total_memory_mb,
+                gpu,
+            };
+            recommend_model(&models, &hardware).map(|model| model.id.as_str())
+        };
+
+        // A big GPU machine takes the largest model
+        assert_eq!(
+            pick(8, Some(32 * 1024), Some(GpuAcceleration::Cuda)),
+            Some("turbo")
+        );
+        // 8 GB leaves room for large but not turbo
+        assert_eq!(
+            pick(4, Some(8 * 1024), Some(GpuAcceleration::Metal)),
+            Some("large")
+        );
+        // A small CPU-only machine needs a fast model
+        assert_eq!(pick(4, Some(8 * 1024), None), Some("medium"));
+        assert_eq!(pick(8, Some(16 * 1024), None), Some("turbo"));
+        // Nothing fits in 2 GB
+        assert_eq!(pick(2, Some(2 * 1024), None), None);
+    }
+
+    #[test]
+    fn test_content_hash_covers_names_and_contents() {
+        let dir = std::env::temp_dir().join(format!("handy-models-{}", std::process::id()));
+        fs::create_dir_all(dir.join("model/sub")).unwrap();
+        fs::write(dir.join("model/a.onnx"), b"weights").unwrap();
+        fs::write(dir.join("model/sub/vocab.txt"), b"tokens").unwrap();
+        fs::write(dir.join("empty.bin"), b"").unwrap();
+
+        // SHA-256 of the empty string
+        assert_eq!(
+            content_hash(&dir.join("empty.bin")).unwrap(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        let before = content_hash(&dir.join("model")).unwrap();
+        assert_eq!(before, content_hash(&dir.join("model")).unwrap());
+        fs::rename(
+            dir.join("model/sub/vocab.txt"),
+            dir.join("model/sub/vocab2.txt"),
+        )
+        .unwrap();
+        assert_ne!(before, content_hash(&dir.join("model")).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
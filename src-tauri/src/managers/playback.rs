@@ -0,0 +1,193 @@
+use crate::audio_feedback::open_output_stream;
+use crate::audio_toolkit::decode_audio_file;
+use crate::settings;
+use anyhow::{anyhow, Result};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use log::{debug, error, warn};
+use rodio::buffer::SamplesBuffer;
+use rodio::Sink;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Rate `decode_audio_file` produces
+const SAMPLE_RATE: u32 = 16000;
+/// How often position events are sent while playing
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+enum PlaybackCommand {
+    Pause,
+    Resume,
+    Seek(Duration),
+    Stop,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackStatus {
+    Playing,
+    Paused,
+    /// Reached the end or was stopped. The position goes back to 0 and a new
+    /// play starts from the beginning.
+    Ended,
+}
+
+/// Payload of the `history-playback` event
+#[derive(Clone, Debug, Serialize)]
+pub struct PlaybackProgress {
+    pub entry_id: i64,
+    pub position_ms: u64,
+    pub duration_ms: u64,
+    pub status: PlaybackStatus,
+}
+
+struct ActivePlayback {
+    entry_id: i64,
+    control: Sender<PlaybackCommand>,
+}
+
+/// Plays history recordings on the selected output device, one at a time
+pub struct PlaybackManager {
+    app_handle: AppHandle,
+    active: Mutex<Option<ActivePlayback>>,
+}
+
+impl PlaybackManager {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        Self {
+            app_handle: app_handle.clone(),
+            active: Mutex::new(None),
+        }
+    }
+
+    /// Play an entry's recording, resuming it if it's the one paused and
+    /// stopping anything else that's playing
+    pub fn play(&self, entry_id: i64, path: &Path) -> Result<()> {
+        let mut active = self.active.lock().unwrap();
+        if let Some(current) = active.take() {
+            // Fails once the playback thread has finished
+            if current.entry_id == entry_id && current.control.send(PlaybackCommand::Resume).is_ok()
+            {
+                *active = Some(current);
+                return Ok(());
+            }
+            let _ = current.control.send(PlaybackCommand::Stop);
+        }
+
+        let samples = decode_audio_file(path)?;
+        let (control, commands) = crossbeam_channel::unbounded();
+        let app = self.app_handle.clone();
+        thread::spawn(move || {
+            if let Err(e) = run_playback(&app, entry_id, samples, commands) {
+                error!("Playback of history entry {} failed: {}", entry_id, e);
+                let _ = app.emit(
+                    "history-playback",
+                    PlaybackProgress {
+                        entry_id,
+                        position_ms: 0,
+                        duration_ms: 0,
+                        status: PlaybackStatus::Ended,
+                    },
+                );
+            }
+        });
+        *active = Some(ActivePlayback { entry_id, control });
+        debug!("Playing history entry {}", entry_id);
+        Ok(())
+    }
+
+    pub fn pause(&self) -> Result<()> {
+        self.send(PlaybackCommand::Pause)
+    }
+
+    pub fn seek(&self, position: Duration) -> Result<()> {
+        self.send(PlaybackCommand::Seek(position))
+    }
+
+    pub fn stop(&self) {
+        if let Some(current) = self.active.lock().unwrap().take() {
+            let _ = current.control.send(PlaybackCommand::Stop);
+        }
+    }
+
+    fn send(&self, command: PlaybackCommand) -> Result<()> {
+        self.active
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|current| current.control.send(command).ok())
+            .ok_or_else(|| anyhow!("Nothing is playing"))
+    }
+}
+
+/// Own the output stream for one recording, reporting progress until it ends
+/// or is stopped
+fn run_playback(
+    app: &AppHandle,
+    entry_id: i64,
+    samples: Vec<f32>,
+    commands: Receiver<PlaybackCommand>,
+) -> Result<()> {
+    let selected_device = settings::get_settings(app).selected_output_device;
+    let stream = open_output_stream(selected_device).map_err(|e| anyhow!("{}", e))?;
+    let sink = Sink::connect_new(stream.mixer());
+    let duration = Duration::from_secs_f64(samples.len() as f64 / SAMPLE_RATE as f64);
+    sink.append(SamplesBuffer::new(1, SAMPLE_RATE, samples));
+
+    let emit = |status: PlaybackStatus| {
+        let position = match status {
+            PlaybackStatus::Ended => Duration::ZERO,
+            _ => sink.get_pos().min(duration),
+        };
+        let progress = PlaybackProgress {
+            entry_id,
+            position_ms: position.as_millis() as u64,
+            duration_ms: duration.as_millis() as u64,
+            status,
+        };
+        if let Err(e) = app.emit("history-playback", progress) {
+            error!("Failed to emit history-playback event: {}", e);
+        }
+    };
+
+    loop {
+        let commanded = match commands.recv_timeout(PROGRESS_INTERVAL) {
+            Ok(PlaybackCommand::Stop) | Err(RecvTimeoutError::Disconnected) => {
+                sink.stop();
+                emit(PlaybackStatus::Ended);
+                return Ok(());
+            }
+            Ok(PlaybackCommand::Pause) => {
+                sink.pause();
+                true
+            }
+            Ok(PlaybackCommand::Resume) => {
+                sink.play();
+                true
+            }
+            Ok(PlaybackCommand::Seek(position)) => {
+                if let Err(e) = sink.try_seek(position.min(duration)) {
+                    warn!("Failed to seek history playback: {}", e);
+                }
+                true
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+        };
+
+        if sink.empty() {
+            emit(PlaybackStatus::Ended);
+            return Ok(());
+        }
+        // Paused playback only reports the change itself
+        if sink.is_paused() {
+            if commanded {
+                emit(PlaybackStatus::Paused);
+            }
+        } else {
+            emit(PlaybackStatus::Playing);
+        }
+    }
+}
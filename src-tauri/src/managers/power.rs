@@ -0,0 +1,220 @@
+use crate::backend::BackendKind;
+use crate::events::{self, PowerThrottleChanged};
+use crate::helpers::power::{power_status, PowerStatus, ThermalState};
+use crate::managers::audio::{AudioRecordingManager, MicrophoneMode};
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::TranscriptionManager;
+use crate::settings::{get_settings, AppSettings};
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+// How often the power source and thermal state are read again
+const POWER_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How far transcription is cut back to save power, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerThrottle {
+    Full,
+    /// Captions wait longer between windows and a smaller model is used
+    Reduced,
+    /// As `Reduced`, and always-on microphone capture stops
+    Paused,
+}
+
+/// The throttle `status` calls for. Running low means below
+/// `battery_threshold` percent on battery; below half of it capture pauses.
+pub fn throttle_for(status: &PowerStatus, battery_threshold: u8) -> PowerThrottle {
+    let battery = status
+        .battery_percent
+        .filter(|_| status.on_battery)
+        .unwrap_or(100);
+    if status.thermal >= ThermalState::Critical || battery < battery_threshold / 2 {
+        PowerThrottle::Paused
+    } else if status.thermal >= ThermalState::Serious
+        || status.power_saver
+        || battery < battery_threshold
+    {
+        PowerThrottle::Reduced
+    } else {
+        PowerThrottle::Full
+    }
+}
+
+/// Watches the battery and thermal state and throttles transcription while
+/// power saving is on and the machine is low on power or running hot
+pub struct PowerManager {
+    app_handle: AppHandle,
+    state: Mutex<PowerThrottleChanged>,
+}
+
+impl PowerManager {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        Self {
+            app_handle: app_handle.clone(),
+            state: Mutex::new(PowerThrottleChanged {
+                throttle: PowerThrottle::Full,
+                status: PowerStatus::default(),
+                model_override: None,
+            }),
+        }
+    }
+
+    /// Check the power state now and then every `POWER_CHECK_INTERVAL`. The
+    /// checks stop when the manager is dropped.
+    pub fn start_monitor(self: &Arc<Self>) {
+        let manager = Arc::downgrade(self);
+        thread::spawn(move || loop {
+            let Some(manager) = manager.upgrade() else {
+                debug!("Power monitor stopping, power manager is gone");
+                return;
+            };
+            manager.check();
+            drop(manager);
+            thread::sleep(POWER_CHECK_INTERVAL);
+        });
+    }
+
+    pub fn throttle(&self) -> PowerThrottle {
+        self.state.lock().unwrap().throttle
+    }
+
+    /// The current throttle with the readings behind it
+    pub fn state(&self) -> PowerThrottleChanged {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// Read the power state and apply the throttle it calls for, announcing
+    /// `power-throttle-changed` when that differs from before
+    pub fn check(&self) {
+        let settings = get_settings(&self.app_handle);
+        let status = power_status();
+        let throttle = if settings.power_saving_enabled {
+            throttle_for(&status, settings.power_saving_battery_percent)
+        } else {
+            PowerThrottle::Full
+        };
+
+        let previous = {
+            let mut state = self.state.lock().unwrap();
+            state.status = status.clone();
+            std::mem::replace(&mut state.throttle, throttle)
+        };
+        if previous == throttle {
+            return;
+        }
+        info!(
+            "Power throttle {:?} -> {:?} ({:?})",
+            previous, throttle, status
+        );
+
+        let model_override = self.apply_model(throttle, &settings);
+        self.apply_capture(throttle, &settings);
+
+        let event = {
+            let mut state = self.state.lock().unwrap();
+            state.model_override = model_override;
+            state.clone()
+        };
+        events::emit(&self.app_handle, event);
+    }
+
+    /// Run on the smallest downloaded model while throttled; returns its id
+    fn apply_model(&self, throttle: PowerThrottle, settings: &AppSettings) -> Option<String> {
+        let transcription_manager = self.app_handle.try_state::<Arc<TranscriptionManager>>()?;
+        let model_override = if throttle == PowerThrottle::Full
+            || settings.transcription_backend != BackendKind::Local
+        {
+            None
+        } else {
+            self.smaller_model(&settings.selected_model)
+        };
+
+        if let Err(e) = transcription_manager.set_model_override(model_override.clone()) {
+            warn!("Failed to switch model for power saving: {}", e);
+            return None;
+        }
+        model_override
+    }
+
+    /// The smallest downloaded model below `selected_model` in size
+    fn smaller_model(&self, selected_model: &str) -> Option<String> {
+        let model_manager = self.app_handle.try_state::<Arc<ModelManager>>()?;
+        let selected_size = model_manager.get_model_info(selected_model)?.size_mb;
+        model_manager
+            .get_available_models()
+            .into_iter()
+            .filter(|model| model.is_downloaded && model.size_mb < selected_size)
+            .min_by_key(|model| model.size_mb)
+            .map(|model| model.id)
+    }
+
+    /// Close the always-on microphone while paused and reopen it after. The
+    /// microphone caption loop stops by itself once it sees the pause.
+    fn apply_capture(&self, throttle: PowerThrottle, settings: &AppSettings) {
+        if !settings.always_on_microphone {
+            return;
+        }
+        let Some(recording_manager) = self.app_handle.try_state::<Arc<AudioRecordingManager>>()
+        else {
+            return;
+        };
+        let mode = if throttle == PowerThrottle::Paused {
+            MicrophoneMode::OnDemand
+        } else {
+            MicrophoneMode::AlwaysOn
+        };
+        if let Err(e) = recording_manager.update_mode(mode) {
+            warn!("Failed to switch microphone mode for power saving: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_for() {
+        let status = |on_battery, battery_percent, thermal| PowerStatus {
+            on_battery,
+            battery_percent,
+            power_saver: false,
+            thermal,
+        };
+
+        assert_eq!(
+            throttle_for(&status(false, Some(5), ThermalState::Nominal), 20),
+            PowerThrottle::Full
+        );
+        assert_eq!(
+            throttle_for(&status(true, Some(50), ThermalState::Nominal), 20),
+            PowerThrottle::Full
+        );
+        assert_eq!(
+            throttle_for(&status(true, Some(15), ThermalState::Nominal), 20),
+            PowerThrottle::Reduced
+        );
+        assert_eq!(
+            throttle_for(&status(true, Some(9), ThermalState::Nominal), 20),
+            PowerThrottle::Paused
+        );
+        assert_eq!(
+            throttle_for(&status(false, None, ThermalState::Serious), 20),
+            PowerThrottle::Reduced
+        );
+        assert_eq!(
+            throttle_for(&status(false, None, ThermalState::Critical), 20),
+            PowerThrottle::Paused
+        );
+        // Unknown charge on battery isn't treated as low
+        assert_eq!(
+            throttle_for(&status(true, None, ThermalState::Fair), 20),
+            PowerThrottle::Full
+        );
+    }
+}
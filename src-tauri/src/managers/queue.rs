@@ -0,0 +1,322 @@
+use crate::audio_toolkit::decode_audio_file;
+use crate::managers::history::{CaptureSource, HistoryManager, TranscriptionMetadata};
+use crate::managers::transcription::TranscriptionManager;
+use crate::managers::workers::JobPriority;
+use crate::settings::get_settings;
+use anyhow::{anyhow, Result};
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Something the queue can transcribe
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QueueSource {
+    /// An audio file on disk; the result is saved as a new history entry
+    File { path: String },
+    /// An existing history recording; its transcription is replaced
+    History { id: i64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueItemStatus {
+    Pending,
+    Processing,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct QueueItem {
+    pub id: u64,
+    pub source: QueueSource,
+    pub status: QueueItemStatus,
+    pub text: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct QueueProgress {
+    /// Number of items finished (done, failed or cancelled)
+    pub position: usize,
+    pub total: usize,
+    pub paused: bool,
+    /// Estimated seconds until every pending item is processed
+    pub eta_secs: Option<f64>,
+    pub items: Vec<QueueItem>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    items: Vec<QueueItem>,
+    next_id: u64,
+    paused: bool,
+    /// Time spent on completed items, for the ETA
+    processed_count: u32,
+    processed_time: Duration,
+}
+
+impl QueueState {
+    fn progress(&self) -> QueueProgress {
+        let finished = self
+            .items
+            .iter()
+            .filter(|i| {
+                !matches!(
+                    i.status,
+                    QueueItemStatus::Pending | QueueItemStatus::Processing
+                )
+            })
+            .count();
+        let remaining = self.items.len() - finished;
+
+        let eta_secs = (self.processed_count > 0).then(|| {
+            let per_item = self.processed_time.as_secs_f64() / self.processed_count as f64;
+            per_item * remaining as f64
+        });
+
+        QueueProgress {
+            position: finished,
+            total: self.items.len(),
+            paused: self.paused,
+            eta_secs,
+            items: self.items.clone(),
+        }
+    }
+
+    fn has_active(&self) -> bool {
+        self.items.iter().any(|i| {
+            matches!(
+                i.status,
+                QueueItemStatus::Pending | QueueItemStatus::Processing
+            )
+        })
+    }
+
+    fn item_mut(&mut self, id: u64) -> Option<&mut QueueItem> {
+        self.items.iter_mut().find(|i| i.id == id)
+    }
+}
+
+/// Sequential batch transcription of files and history entries on a worker
+/// thread. Progress is reported through `queue-progress` events.
+pub struct TranscriptionQueue {
+    app_handle: AppHandle,
+    state: Arc<(Mutex<QueueState>, Condvar)>,
+}
+
+impl TranscriptionQueue {
+    pub fn new(
+        app_handle: &AppHandle,
+        transcription_manager: Arc<TranscriptionManager>,
+        history_manager: Arc<HistoryManager>,
+    ) -> Self {
+        let state = Arc::new((Mutex::new(QueueState::default()), Condvar::new()));
+
+        let worker_state = state.clone();
+        let worker_app = app_handle.clone();
+        thread::spawn(move || {
+            run_worker(
+                worker_app,
+                worker_state,
+                transcription_manager,
+                history_manager,
+            )
+        });
+
+        Self {
+            app_handle: app_handle.clone(),
+            state,
+        }
+    }
+
+    /// Add items to the queue, returning their ids
+    pub fn enqueue(&self, sources: Vec<QueueSource>) -> Vec<u64> {
+        let (lock, cvar) = &*self.state;
+        let mut state = lock.lock().unwrap();
+
+        // Start a fresh batch once the previous one has finished
+        if !state.has_active() {
+            state.items.clear();
+            state.processed_count = 0;
+            state.processed_time = Duration::ZERO;
+        }
+
+        let mut ids = Vec::with_capacity(sources.len());
+        for source in sources {
+            let id = state.next_id;
+            state.next_id += 1;
+            state.items.push(QueueItem {
+                id,
+                source,
+                status: QueueItemStatus::Pending,
+                text: None,
+                error: None,
+            });
+            ids.push(id);
+        }
+
+        info!("Queued {} item(s) for transcription", ids.len());
+        emit_progress(&self.app_handle, &state);
+        cvar.notify_all();
+        ids
+    }
+
+    /// Stop picking up new items; the one in progress still finishes
+    pub fn pause(&self) {
+        self.set_paused(true);
+    }
+
+    pub fn resume(&self) {
+        self.set_paused(false);
+    }
+
+    fn set_paused(&self, paused: bool) {
+        let (lock, cvar) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.paused = paused;
+        emit_progress(&self.app_handle, &state);
+        cvar.notify_all();
+    }
+
+    /// Cancel every pending item. The item currently being transcribed runs to
+    /// completion but its result is discarded.
+    pub fn cancel(&self) {
+        let (lock, _) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        for item in state.items.iter_mut() {
+            if matches!(
+                item.status,
+                QueueItemStatus::Pending | QueueItemStatus::Processing
+            ) {
+                item.status = QueueItemStatus::Cancelled;
+            }
+        }
+        state.paused = false;
+        emit_progress(&self.app_handle, &state);
+    }
+
+    pub fn progress(&self) -> QueueProgress {
+        let (lock, _) = &*self.state;
+        lock.lock().unwrap().progress()
+    }
+}
+
+fn emit_progress(app_handle: &AppHandle, state: &QueueState) {
+    if let Err(e) = app_handle.emit("queue-progress", state.progress()) {
+        error!("Failed to emit queue-progress event: {}", e);
+    }
+}
+
+fn run_worker(
+    app_handle: AppHandle,
+    state: Arc<(Mutex<QueueState>, Condvar)>,
+    tm: Arc<TranscriptionManager>,
+    hm: Arc<HistoryManager>,
+) {
+    let (lock, cvar) = &*state;
+
+    loop {
+        // Wait for the next pending item
+        let (id, source) = {
+            let mut guard = lock.lock().unwrap();
+            loop {
+                if !guard.paused {
+                    if let Some(item) = guard
+                        .items
+                        .iter_mut()
+                        .find(|i| i.status == QueueItemStatus::Pending)
+                    {
+                        item.status = QueueItemStatus::Processing;
+                        let next = (item.id, item.source.clone());
+                        emit_progress(&app_handle, &guard);
+                        break next;
+                    }
+                }
+                guard = cvar.wait(guard).unwrap();
+            }
+        };
+
+        debug!("Queue processing item {}: {:?}", id, source);
+        tm.initiate_model_load();
+        let started = Instant::now();
+        let language = get_settings(&app_handle).selected_language;
+        let result = process_item(&tm, &hm, &source, &language, || {
+            // Skip saving if the item was cancelled while transcribing
+            let guard = lock.lock().unwrap();
+            guard
+                .items
+                .iter()
+                .any(|i| i.id == id && i.status == QueueItemStatus::Processing)
+        });
+
+        let mut guard = lock.lock().unwrap();
+        guard.processed_count += 1;
+        guard.processed_time += started.elapsed();
+        if let Some(item) = guard.item_mut(id) {
+            if item.status == QueueItemStatus::Processing {
+                match result {
+                    Ok(text) => {
+                        item.status = QueueItemStatus::Done;
+                        item.text = Some(text);
+                    }
+                    Err(e) => {
+                        error!("Queue item {} failed: {}", id, e);
+                        item.status = QueueItemStatus::Failed;
+                        item.error = Some(e.to_string());
+                    }
+                }
+            }
+        }
+        emit_progress(&app_handle, &guard);
+    }
+}
+
+fn process_item(
+    tm: &TranscriptionManager,
+    hm: &HistoryManager,
+    source: &QueueSource,
+    language: &str,
+    still_wanted: impl Fn() -> bool,
+) -> Result<String> {
+    match source {
+        QueueSource::File { path } => {
+            let samples = decode_audio_file(&PathBuf::from(path))?;
+            let started = Instant::now();
+            let text = tm.transcribe_with_prompt(samples.clone(), None, JobPriority::Background)?;
+            if still_wanted() {
+                let metadata = TranscriptionMetadata {
+                    model: tm.get_current_model(),
+                    source: Some(CaptureSource::File),
+                    latency_ms: Some(started.elapsed().as_millis() as u64),
+                    language: Some(language.to_string()),
+                    app_name: None,
+                };
+                tauri::async_runtime::block_on(hm.save_transcription(
+                    samples,
+                    text.clone(),
+                    None,
+                    None,
+                    metadata,
+                ))?;
+            }
+            Ok(text)
+        }
+        QueueSource::History { id } => {
+            let entry = tauri::async_runtime::block_on(hm.get_entry_by_id(*id))?
+                .ok_or_else(|| anyhow!("History entry {} not found", id))?;
+            let samples = decode_audio_file(&hm.get_audio_file_path(&entry.file_name))?;
+            let text = tm.transcribe_with_prompt(samples, None, JobPriority::Background)?;
+            if still_wanted() {
+                hm.update_transcription_text(*id, &text)?;
+            }
+            Ok(text)
+        }
+    }
+}
@@ -0,0 +1,208 @@
+// Pipelined transcription for long dictations
+// Once a take grows past the configured segment length, it is cut at the next
+// VAD pause and the finished segment is transcribed on a worker thread while
+// recording continues. Stopping then only has to transcribe the tail.
+
+use crate::audio_toolkit::stream::AudioFrameStream;
+use crate::audio_toolkit::VoiceActivityDetector;
+use futures_util::{FutureExt, StreamExt};
+use log::{debug, error, warn};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+use tokio::sync::oneshot;
+
+// 30ms frames at 16kHz, the size SileroVad expects
+const FRAME_SAMPLES: usize = 480;
+// ~300ms of silence counts as a pause worth cutting at
+const PAUSE_FRAMES: usize = 10;
+
+/// Decides where to cut a growing take, one VAD frame at a time
+struct SegmentCutter {
+    target: usize,
+    len: usize,
+    silence_frames: usize,
+    last_pause: Option<usize>,
+}
+
+impl SegmentCutter {
+    fn new(target_samples: usize) -> Self {
+        Self {
+            target: target_samples.max(FRAME_SAMPLES),
+            len: 0,
+            silence_frames: 0,
+            last_pause: None,
+        }
+    }
+
+    /// Account for one more frame and return how many buffered samples
+    /// should be cut off as a finished segment, if any
+    fn push_frame(&mut self, is_speech: bool) -> Option<usize> {
+        self.len += FRAME_SAMPLES;
+        if is_speech {
+            self.silence_frames = 0;
+        } else {
+            self.silence_frames += 1;
+            if self.silence_frames >= PAUSE_FRAMES {
+                self.last_pause = Some(self.len);
+            }
+        }
+
+        let cut = if self.len >= self.target && self.silence_frames >= PAUSE_FRAMES {
+            self.len
+        } else if self.len >= self.target * 3 / 2 {
+            // No pause since the target; fall back to the last one, or cut
+            // mid-speech rather than letting the segment grow without bound
+            self.last_pause.unwrap_or(self.len)
+        } else {
+            return None;
+        };
+
+        self.len -= cut;
+        self.last_pause = None;
+        Some(cut)
+    }
+}
+
+/// Segments of the recording in progress that are transcribed while it
+/// continues. Dropping it without calling `finish` discards their text.
+pub struct SegmentedTranscription {
+    stop: Option<oneshot::Sender<()>>,
+    tail: mpsc::Receiver<Vec<f32>>,
+    texts: Arc<Mutex<Vec<String>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl SegmentedTranscription {
+    /// Watch `frames` and pass every segment longer than `segment_samples`
+    /// to `transcribe`, cutting at VAD pauses
+    pub fn start<F>(
+        mut frames: AudioFrameStream,
+        mut vad: Box<dyn VoiceActivityDetector>,
+        segment_samples: usize,
+        mut transcribe: F,
+    ) -> Self
+    where
+        F: FnMut(Vec<f32>) -> anyhow::Result<String> + Send + 'static,
+    {
+        let texts = Arc::new(Mutex::new(Vec::new()));
+        let (segment_tx, segment_rx) = mpsc::channel::<Vec<f32>>();
+        let worker = {
+            let texts = Arc::clone(&texts);
+            std::thread::spawn(move || {
+                for segment in segment_rx {
+                    let samples = segment.len();
+                    match transcribe(segment) {
+                        Ok(text) => {
+                            debug!("Transcribed {} sample segment early", samples);
+                            texts.lock().unwrap().push(text);
+                        }
+                        Err(e) => error!("Failed to transcribe recording segment: {}", e),
+                    }
+                }
+            })
+        };
+
+        let (stop, mut stop_rx) = oneshot::channel();
+        let (tail_tx, tail) = mpsc::channel();
+        tauri::async_runtime::spawn(async move {
+            let mut cutter = SegmentCutter::new(segment_samples);
+            let mut buffer: Vec<f32> = Vec::new();
+            let mut scanned = 0;
+
+            let mut push = |buffer: &mut Vec<f32>, scanned: &mut usize, samples: &[f32]| {
+                buffer.extend_from_slice(samples);
+                while *scanned + FRAME_SAMPLES <= buffer.len() {
+                    let frame = &buffer[*scanned..*scanned + FRAME_SAMPLES];
+                    let is_speech = vad.is_voice(frame).unwrap_or(true);
+                    *scanned += FRAME_SAMPLES;
+                    if let Some(cut) = cutter.push_frame(is_speech) {
+                        // The cutter only counts scanned samples, so the cut
+                        // is always measured from the start of the buffer
+                        let segment: Vec<f32> = buffer.drain(..cut).collect();
+                        *scanned -= cut;
+                        if segment_tx.send(segment).is_err() {
+                            warn!("Segment transcription worker stopped early");
+                        }
+                    }
+                }
+            };
+
+            loop {
+                tokio::select! {
+                    _ = &mut stop_rx => break,
+                    frame = frames.next() => match frame {
+                        Some(frame) => push(&mut buffer, &mut scanned, &frame.samples),
+                        None => break,
+                    },
+                }
+            }
+            // Frames published before the recorder stopped may still be queued
+            while let Some(Some(frame)) = frames.next().now_or_never() {
+                push(&mut buffer, &mut scanned, &frame.samples);
+            }
+            drop(segment_tx);
+            let _ = tail_tx.send(buffer);
+        });
+
+        Self {
+            stop: Some(stop),
+            tail,
+            texts,
+            worker: Some(worker),
+        }
+    }
+
+    /// The recording has stopped. Wait for the segments already cut to be
+    /// transcribed and return their texts in order, along with the audio
+    /// after the last cut that still needs transcribing.
+    pub fn finish(mut self) -> (Vec<String>, Vec<f32>) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        let tail = self.tail.recv().unwrap_or_default();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        let texts = std::mem::take(&mut *self.texts.lock().unwrap());
+        (texts, tail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_frames(cutter: &mut SegmentCutter, count: usize, is_speech: bool) -> Vec<usize> {
+        (0..count)
+            .filter_map(|_| cutter.push_frame(is_speech))
+            .collect()
+    }
+
+    #[test]
+    fn test_cuts_at_first_pause_after_target() {
+        let mut cutter = SegmentCutter::new(100 * FRAME_SAMPLES);
+
+        // A pause before the target is not enough on its own
+        assert!(push_frames(&mut cutter, 50, true).is_empty());
+        assert!(push_frames(&mut cutter, PAUSE_FRAMES, false).is_empty());
+        assert!(push_frames(&mut cutter, 60, true).is_empty());
+
+        let cuts = push_frames(&mut cutter, PAUSE_FRAMES, false);
+        assert_eq!(cuts, vec![(120 + PAUSE_FRAMES) * FRAME_SAMPLES]);
+    }
+
+    #[test]
+    fn test_forces_cut_at_last_pause_without_one_after_target() {
+        let mut cutter = SegmentCutter::new(100 * FRAME_SAMPLES);
+
+        push_frames(&mut cutter, 80, true);
+        push_frames(&mut cutter, PAUSE_FRAMES, false);
+        let cuts = push_frames(&mut cutter, 60, true);
+        assert_eq!(cuts, vec![(80 + PAUSE_FRAMES) * FRAME_SAMPLES]);
+
+        // Continuous speech with no pause at all is cut at 1.5x the target
+        let mut cutter = SegmentCutter::new(100 * FRAME_SAMPLES);
+        let cuts = push_frames(&mut cutter, 160, true);
+        assert_eq!(cuts, vec![150 * FRAME_SAMPLES]);
+    }
+}
@@ -1,23 +1,37 @@
-use crate::audio_toolkit::apply_custom_words;
-use crate::managers::model::{EngineType, ModelManager};
-use crate::settings::{get_settings, ModelUnloadTimeout};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::hallucination::{hallucination_reason, AudioEvidence};
+use crate::audio_toolkit::punctuation::{needs_punctuation, PunctuationModel};
+use crate::audio_toolkit::{apply_custom_words, custom_words_prompt};
+use crate::backend::{
+    load_local_backend, BackendCapabilities, BackendKind, CloudBackend, ComputeBackend,
+    Granularity, InferenceRequest, LocalFallback, Transcript, TranscriptionBackend,
+};
+use crate::events::{self, TranscriptionQueueDepth};
+use crate::helpers::hardware::resident_memory_mb;
+use crate::managers::model::ModelManager;
+use crate::managers::workers::{JobPriority, WorkerPool};
+use crate::metrics::{self, Stage};
+use crate::settings::{get_settings, AppSettings, ModelUnloadTimeout};
 use anyhow::Result;
 use log::{debug, error, info, warn};
 use serde::Serialize;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::{Arc, Condvar, Mutex};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
 use std::thread;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use tauri::{AppHandle, Emitter};
-use transcribe_rs::{
-    engines::{
-        parakeet::{
-            ParakeetEngine, ParakeetInferenceParams, ParakeetModelParams, TimestampGranularity,
-        },
-        whisper::{WhisperEngine, WhisperInferenceParams},
-    },
-    TranscriptionEngine,
-};
+
+pub use crate::backend::TimedSegment;
+
+// Shorter segments (Parakeet's single words) are only judged as part of the
+// whole window; a word's few frames say little about whether it was spoken
+const MIN_JUDGED_SEGMENT_SECS: f32 = 1.0;
+// How often the idle timer compares the time since the last transcription
+// against the unload timeout
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+// The engine runs one inference at a time; more workers would only wait on its lock
+const TRANSCRIPTION_WORKERS: usize = 1;
 
 #[derive(Clone, Debug, Serialize)]
 pub struct ModelStateEvent {
@@ -27,104 +41,137 @@ pub struct ModelStateEvent {
     pub error: Option<String>,
 }
 
-enum LoadedEngine {
-    Whisper(WhisperEngine),
-    Parakeet(ParakeetEngine),
+/// The device the loaded model runs on, for `get_compute_backend_info`
+#[derive(Clone, Debug, Serialize)]
+pub struct ComputeBackendInfo {
+    /// From settings, so possibly `auto`
+    pub requested: ComputeBackend,
+    /// `None` while no model is loaded, and for the cloud backend
+    pub active: Option<ComputeBackend>,
+    /// GPU backends this build includes
+    pub available: Vec<ComputeBackend>,
+    /// Resident memory the process gained loading the model. GPU memory
+    /// outside the host's RAM isn't counted.
+    pub model_memory_mb: Option<u64>,
+    pub process_memory_mb: Option<u64>,
+}
+
+/// How fast a model transcribes on this machine, for `benchmark_models`
+#[derive(Clone, Debug, Serialize)]
+pub struct ModelBenchmark {
+    pub model_id: String,
+    pub load_ms: u64,
+    pub transcribe_ms: u64,
+    /// Transcription time over audio duration; below 1.0 keeps up with speech
+    pub real_time_factor: f32,
+}
+
+/// The loaded backend and what it supports, for `get_transcription_backend_info`
+#[derive(Clone, Debug, Serialize)]
+pub struct BackendInfo {
+    pub name: String,
+    pub capabilities: BackendCapabilities,
 }
 
 #[derive(Clone)]
 pub struct TranscriptionManager {
-    engine: Arc<Mutex<Option<LoadedEngine>>>,
+    engine: Arc<Mutex<Option<Box<dyn TranscriptionBackend>>>>,
     model_manager: Arc<ModelManager>,
     app_handle: AppHandle,
     current_model_id: Arc<Mutex<Option<String>>>,
     last_activity: Arc<AtomicU64>,
-    shutdown_signal: Arc<AtomicBool>,
-    watcher_handle: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
     is_loading: Arc<Mutex<bool>>,
     loading_condvar: Arc<Condvar>,
+    punctuation: Arc<Mutex<Option<PunctuationModel>>>,
+    // What the process grew by loading the current model
+    model_memory_mb: Arc<Mutex<Option<u64>>>,
+    // Used instead of the selected model while set, as when saving power
+    model_override: Arc<Mutex<Option<String>>>,
+    // Set by `start_workers`; until then jobs run on the calling thread
+    workers: Arc<OnceLock<WorkerPool<TranscriptionManager>>>,
 }
 
 impl TranscriptionManager {
     pub fn new(app_handle: &AppHandle, model_manager: Arc<ModelManager>) -> Result<Self> {
-        let manager = Self {
+        Ok(Self {
             engine: Arc::new(Mutex::new(None)),
             model_manager,
             app_handle: app_handle.clone(),
             current_model_id: Arc::new(Mutex::new(None)),
-            last_activity: Arc::new(AtomicU64::new(
-                SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64,
-            )),
-            shutdown_signal: Arc::new(AtomicBool::new(false)),
-            watcher_handle: Arc::new(Mutex::new(None)),
+            last_activity: Arc::new(AtomicU64::new(now_ms())),
             is_loading: Arc::new(Mutex::new(false)),
             loading_condvar: Arc::new(Condvar::new()),
+            punctuation: Arc::new(Mutex::new(None)),
+            model_memory_mb: Arc::new(Mutex::new(None)),
+            model_override: Arc::new(Mutex::new(None)),
+            workers: Arc::new(OnceLock::new()),
+        })
+    }
+
+    /// Unload the model once it has gone unused for the `model_unload_timeout`
+    /// setting. The next transcription loads it again. The timer stops when
+    /// the manager is dropped.
+    pub fn start_idle_timer(self: &Arc<Self>) {
+        let manager = Arc::downgrade(self);
+        thread::spawn(move || loop {
+            thread::sleep(IDLE_CHECK_INTERVAL);
+            let Some(manager) = manager.upgrade() else {
+                debug!("Idle timer stopping, transcription manager is gone");
+                return;
+            };
+            manager.unload_if_idle();
+        });
+    }
+
+    fn unload_if_idle(&self) {
+        let timeout = get_settings(&self.app_handle).model_unload_timeout;
+        // Immediate unloading happens right after each transcription
+        if timeout == ModelUnloadTimeout::Immediately {
+            return;
+        }
+        let Some(limit_seconds) = timeout.to_seconds() else {
+            return;
         };
+        let idle_ms = now_ms().saturating_sub(self.last_activity.load(Ordering::Relaxed));
+        if idle_ms <= limit_seconds * 1000 || !self.is_model_loaded() {
+            return;
+        }
 
-        // Start the idle watcher
-        {
-            let app_handle_cloned = app_handle.clone();
-            let manager_cloned = manager.clone();
-            let shutdown_signal = manager.shutdown_signal.clone();
-            let handle = thread::spawn(move || {
-                while !shutdown_signal.load(Ordering::Relaxed) {
-                    thread::sleep(Duration::from_secs(10)); // Check every 10 seconds
-
-                    // Check shutdown signal again after sleep
-                    if shutdown_signal.load(Ordering::Relaxed) {
-                        break;
-                    }
-
-                    let settings = get_settings(&app_handle_cloned);
-                    let timeout_seconds = settings.model_unload_timeout.to_seconds();
-
-                    if let Some(limit_seconds) = timeout_seconds {
-                        // Skip polling-based unloading for immediate timeout since it's handled directly in transcribe()
-                        if settings.model_unload_timeout == ModelUnloadTimeout::Immediately {
-                            continue;
-                        }
-
-                        let last = manager_cloned.last_activity.load(Ordering::Relaxed);
-                        let now_ms = SystemTime::now()
-                            .duration_since(SystemTime::UNIX_EPOCH)
-                            .unwrap()
-                            .as_millis() as u64;
-
-                        if now_ms.saturating_sub(last) > limit_seconds * 1000 {
-                            // idle -> unload
-                            if manager_cloned.is_model_loaded() {
-                                let unload_start = std::time::Instant::now();
-                                debug!("Starting to unload model due to inactivity");
-
-                                if let Ok(()) = manager_cloned.unload_model() {
-                                    let _ = app_handle_cloned.emit(
-                                        "model-state-changed",
-                                        ModelStateEvent {
-                                            event_type: "unloaded".to_string(),
-                                            model_id: None,
-                                            model_name: None,
-                                            error: None,
-                                        },
-                                    );
-                                    let unload_duration = unload_start.elapsed();
-                                    debug!(
-                                        "Model unloaded due to inactivity (took {}ms)",
-                                        unload_duration.as_millis()
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-                debug!("Idle watcher thread shutting down gracefully");
-            });
-            *manager.watcher_handle.lock().unwrap() = Some(handle);
+        info!(
+            "Unloading model after {}s without transcriptions",
+            idle_ms / 1000
+        );
+        if let Err(e) = self.unload_model() {
+            error!("Failed to unload idle model: {}", e);
+        }
+    }
+
+    /// Run transcriptions on worker threads from now on, in priority order,
+    /// reporting the queue in `transcription-queue-depth` events
+    pub fn start_workers(self: &Arc<Self>) {
+        let app_handle = self.app_handle.clone();
+        let pool = WorkerPool::new(TRANSCRIPTION_WORKERS, Arc::downgrade(self), move |depth| {
+            events::emit(&app_handle, depth)
+        });
+        if self.workers.set(pool).is_err() {
+            warn!("Transcription workers were already started");
         }
+    }
 
-        Ok(manager)
+    /// Run `job` on a worker at `priority` and wait for its result
+    fn run<T, F>(&self, priority: JobPriority, job: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&TranscriptionManager) -> Result<T> + Send + 'static,
+    {
+        // Queued and running jobs keep the model from idling out
+        self.touch_activity();
+        let result = match self.workers.get() {
+            Some(pool) => pool.run(priority, job),
+            None => job(self),
+        };
+        self.touch_activity();
+        result
     }
 
     pub fn is_model_loaded(&self) -> bool {
@@ -138,14 +185,13 @@ impl TranscriptionManager {
 
         {
             let mut engine = self.engine.lock().unwrap();
-            if let Some(ref mut loaded_engine) = *engine {
-                match loaded_engine {
-                    LoadedEngine::Whisper(ref mut whisper) => whisper.unload_model(),
-                    LoadedEngine::Parakeet(ref mut parakeet) => parakeet.unload_model(),
-                }
+            if let Some(backend) = engine.as_mut() {
+                backend.unload();
             }
             *engine = None; // Drop the engine to free memory
         }
+        *self.punctuation.lock().unwrap() = None;
+        *self.model_memory_mb.lock().unwrap() = None;
         {
             let mut current_model = self.current_model_id.lock().unwrap();
             *current_model = None;
@@ -185,72 +231,74 @@ impl TranscriptionManager {
             },
         );
 
-        let model_info = self
-            .model_manager
-            .get_model_info(model_id)
-            .ok_or_else(|| anyhow::anyhow!("Model not found: {}", model_id))?;
-
-        if !model_info.is_downloaded {
-            let error_msg = "Model not downloaded";
+        let settings = get_settings(&self.app_handle);
+        let memory_before = resident_memory_mb();
+        let model_info = self.model_manager.get_model_info(model_id);
+        let model_name = model_info.as_ref().map(|info| info.name.clone());
+        let emit_failure = |error_msg: &str| {
             let _ = self.app_handle.emit(
                 "model-state-changed",
                 ModelStateEvent {
                     event_type: "loading_failed".to_string(),
                     model_id: Some(model_id.to_string()),
-                    model_name: Some(model_info.name.clone()),
+                    model_name: model_name.clone(),
                     error: Some(error_msg.to_string()),
                 },
             );
-            return Err(anyhow::anyhow!(error_msg));
-        }
-
-        let model_path = self.model_manager.get_model_path(model_id)?;
+        };
 
-        // Create appropriate engine based on model type
-        let loaded_engine = match model_info.engine_type {
-            EngineType::Whisper => {
-                let mut engine = WhisperEngine::new();
-                engine.load_model(&model_path).map_err(|e| {
-                    let error_msg = format!("Failed to load whisper model {}: {}", model_id, e);
-                    let _ = self.app_handle.emit(
-                        "model-state-changed",
-                        ModelStateEvent {
-                            event_type: "loading_failed".to_string(),
-                            model_id: Some(model_id.to_string()),
-                            model_name: Some(model_info.name.clone()),
-                            error: Some(error_msg.clone()),
-                        },
-                    );
-                    anyhow::anyhow!(error_msg)
-                })?;
-                LoadedEngine::Whisper(engine)
+        // Create the backend for the selected kind and model type
+        let backend = match settings.transcription_backend {
+            BackendKind::Local => {
+                let model_info =
+                    model_info.ok_or_else(|| anyhow::anyhow!("Model not found: {}", model_id))?;
+                if !model_info.is_downloaded {
+                    let error_msg = "Model not downloaded";
+                    emit_failure(error_msg);
+                    return Err(anyhow::anyhow!(error_msg));
+                }
+                let model_path = self.model_manager.get_model_path(model_id)?;
+                load_local_backend(
+                    &model_info.engine_type,
+                    &model_path,
+                    settings.compute_backend,
+                )
             }
-            EngineType::Parakeet => {
-                let mut engine = ParakeetEngine::new();
-                engine
-                    .load_model_with_params(&model_path, ParakeetModelParams::int8())
-                    .map_err(|e| {
-                        let error_msg =
-                            format!("Failed to load parakeet model {}: {}", model_id, e);
-                        let _ = self.app_handle.emit(
-                            "model-state-changed",
-                            ModelStateEvent {
-                                event_type: "loading_failed".to_string(),
-                                model_id: Some(model_id.to_string()),
-                                model_name: Some(model_info.name.clone()),
-                                error: Some(error_msg.clone()),
-                            },
-                        );
-                        anyhow::anyhow!(error_msg)
-                    })?;
-                LoadedEngine::Parakeet(engine)
+            BackendKind::Cloud => {
+                // The selected model, if downloaded, covers for the cloud while offline
+                let fallback = model_info
+                    .filter(|info| info.is_downloaded)
+                    .and_then(|info| {
+                        self.model_manager
+                            .get_model_path(model_id)
+                            .ok()
+                            .map(|path| {
+                                LocalFallback::new(info.engine_type, path, settings.compute_backend)
+                            })
+                    });
+                CloudBackend::new(&self.app_handle, fallback)
+                    .map(|backend| Box::new(backend) as Box<dyn TranscriptionBackend>)
             }
-        };
+        }
+        .map_err(|e| {
+            let error_msg = format!("Failed to load model {}: {}", model_id, e);
+            emit_failure(&error_msg);
+            anyhow::anyhow!(error_msg)
+        })?;
+        info!(
+            "Loaded {} backend for model {} on {:?}",
+            backend.name(),
+            model_id,
+            backend.compute_backend()
+        );
+        *self.model_memory_mb.lock().unwrap() = memory_before
+            .zip(resident_memory_mb())
+            .map(|(before, after)| after.saturating_sub(before));
 
         // Update the current engine and model ID
         {
             let mut engine = self.engine.lock().unwrap();
-            *engine = Some(loaded_engine);
+            *engine = Some(backend);
         }
         {
             let mut current_model = self.current_model_id.lock().unwrap();
@@ -263,7 +311,7 @@ impl TranscriptionManager {
             ModelStateEvent {
                 event_type: "loading_completed".to_string(),
                 model_id: Some(model_id.to_string()),
-                model_name: Some(model_info.name.clone()),
+                model_name,
                 error: None,
             },
         );
@@ -287,8 +335,7 @@ impl TranscriptionManager {
         *is_loading = true;
         let self_clone = self.clone();
         thread::spawn(move || {
-            let settings = get_settings(&self_clone.app_handle);
-            if let Err(e) = self_clone.load_model(&settings.selected_model) {
+            if let Err(e) = self_clone.load_model(&self_clone.wanted_model()) {
                 error!("Failed to load model: {}", e);
             }
             let mut is_loading = self_clone.is_loading.lock().unwrap();
@@ -297,109 +344,272 @@ impl TranscriptionManager {
         });
     }
 
+    /// Wait for any load in flight, then load the selected model if there is
+    /// still none, as after it was unloaded for being idle
+    fn ensure_model_loaded(&self) -> Result<()> {
+        let mut is_loading = self.is_loading.lock().unwrap();
+        while *is_loading {
+            is_loading = self.loading_condvar.wait(is_loading).unwrap();
+        }
+        if self.is_model_loaded() {
+            return Ok(());
+        }
+        *is_loading = true;
+        drop(is_loading);
+
+        info!("No model loaded, loading it for transcription");
+        let result = self.load_model(&self.wanted_model());
+
+        *self.is_loading.lock().unwrap() = false;
+        self.loading_condvar.notify_all();
+        result
+    }
+
+    /// Run on `model_id` instead of the selected model until called with
+    /// `None`. A loaded model is swapped right away; otherwise the next
+    /// transcription loads the right one.
+    pub fn set_model_override(&self, model_id: Option<String>) -> Result<()> {
+        *self.model_override.lock().unwrap() = model_id;
+        let wanted = self.wanted_model();
+        match self.get_current_model() {
+            Some(current) if current != wanted => {
+                info!("Switching model from {} to {}", current, wanted);
+                self.load_model(&wanted)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Transcriptions waiting and running, all zero before workers start
+    pub fn queue_depth(&self) -> TranscriptionQueueDepth {
+        self.workers
+            .get()
+            .map(|pool| pool.depth())
+            .unwrap_or_default()
+    }
+
+    /// The override if one is set, else the selected model
+    fn wanted_model(&self) -> String {
+        self.model_override
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| get_settings(&self.app_handle).selected_model)
+    }
+
     pub fn get_current_model(&self) -> Option<String> {
         let current_model = self.current_model_id.lock().unwrap();
         current_model.clone()
     }
 
-    pub fn transcribe(&self, audio: Vec<f32>) -> Result<String> {
-        // Update last activity timestamp
-        self.last_activity.store(
-            SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
-            Ordering::Relaxed,
-        );
-
-        let st = std::time::Instant::now();
+    fn touch_activity(&self) {
+        self.last_activity.store(now_ms(), Ordering::Relaxed);
+    }
 
-        debug!("Audio vector length: {}", audio.len());
+    /// Name and capabilities of the loaded backend, if any
+    pub fn backend_info(&self) -> Option<BackendInfo> {
+        let engine = self.engine.lock().unwrap();
+        engine.as_ref().map(|backend| BackendInfo {
+            name: backend.name().to_string(),
+            capabilities: backend.capabilities(),
+        })
+    }
 
-        if audio.len() == 0 {
-            debug!("Empty audio vector");
-            return Ok(String::new());
+    /// The device the loaded model runs on and the memory it takes
+    pub fn compute_info(&self) -> ComputeBackendInfo {
+        let active = self
+            .engine
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|backend| backend.compute_backend());
+        ComputeBackendInfo {
+            requested: get_settings(&self.app_handle).compute_backend,
+            active,
+            available: ComputeBackend::compiled_gpus(),
+            model_memory_mb: *self.model_memory_mb.lock().unwrap(),
+            process_memory_mb: resident_memory_mb(),
         }
+    }
 
-        // Check if model is loaded, if not try to load it
-        {
-            // If the model is loading, wait for it to complete.
-            let mut is_loading = self.is_loading.lock().unwrap();
-            while *is_loading {
-                is_loading = self.loading_condvar.wait(is_loading).unwrap();
-            }
+    /// Run the loaded backend over `audio`, waiting for any in-flight model load.
+    fn infer(
+        &self,
+        audio: Vec<f32>,
+        settings: &AppSettings,
+        granularity: Granularity,
+        prompt: Option<&str>,
+    ) -> Result<Transcript> {
+        self.ensure_model_loaded()?;
+
+        let mut engine_guard = self.engine.lock().unwrap();
+        let backend = engine_guard.as_mut().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Model failed to load after auto-load attempt. Please check your model settings."
+            )
+        })?;
 
-            let engine_guard = self.engine.lock().unwrap();
-            if engine_guard.is_none() {
-                return Err(anyhow::anyhow!("Model is not loaded for transcription."));
-            }
-        }
+        let request = inference_request(settings, granularity, prompt);
+        metrics::time(Stage::Transcribe, || backend.transcribe(audio, &request))
+    }
 
-        // Get current settings for configuration
+    /// Load `model_id` into a throwaway engine and time it over `audio`. The
+    /// active model stays loaded, so both are in memory while this runs.
+    pub fn benchmark_model(&self, model_id: &str, audio: Vec<f32>) -> Result<ModelBenchmark> {
+        let model_id = model_id.to_string();
+        self.run(JobPriority::Background, move |manager| {
+            manager.benchmark_now(&model_id, audio)
+        })
+    }
+
+    fn benchmark_now(&self, model_id: &str, audio: Vec<f32>) -> Result<ModelBenchmark> {
+        let model_info = self
+            .model_manager
+            .get_model_info(model_id)
+            .filter(|info| info.is_downloaded)
+            .ok_or_else(|| anyhow::anyhow!("Model not downloaded: {}", model_id))?;
+        let model_path = self.model_manager.get_model_path(model_id)?;
         let settings = get_settings(&self.app_handle);
+        let audio_secs = audio.len() as f32 / WHISPER_SAMPLE_RATE as f32;
+
+        let load_start = Instant::now();
+        let mut backend = load_local_backend(
+            &model_info.engine_type,
+            &model_path,
+            settings.compute_backend,
+        )?;
+        let load_ms = load_start.elapsed().as_millis() as u64;
+
+        let transcribe_start = Instant::now();
+        let result = backend.transcribe(
+            audio,
+            &inference_request(&settings, Granularity::Segment, None),
+        );
+        let transcribe_elapsed = transcribe_start.elapsed();
+        backend.unload();
+        result?;
+
+        Ok(ModelBenchmark {
+            model_id: model_id.to_string(),
+            load_ms,
+            transcribe_ms: transcribe_elapsed.as_millis() as u64,
+            real_time_factor: transcribe_elapsed.as_secs_f32() / audio_secs,
+        })
+    }
 
-        // Perform transcription with the appropriate engine
-        let result = {
-            let mut engine_guard = self.engine.lock().unwrap();
-            let engine = engine_guard.as_mut().ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Model failed to load after auto-load attempt. Please check your model settings."
-                )
-            })?;
-
-            match engine {
-                LoadedEngine::Whisper(whisper_engine) => {
-                    // Normalize language code for Whisper
-                    // Convert zh-Hans and zh-Hant to zh since Whisper uses ISO 639-1 codes
-                    let whisper_language = if settings.selected_language == "auto" {
-                        None
-                    } else {
-                        let normalized = if settings.selected_language == "zh-Hans"
-                            || settings.selected_language == "zh-Hant"
-                        {
-                            "zh".to_string()
-                        } else {
-                            settings.selected_language.clone()
-                        };
-                        Some(normalized)
-                    };
-
-                    let params = WhisperInferenceParams {
-                        language: whisper_language,
-                        translate: settings.translate_to_english,
-                        ..Default::default()
-                    };
-
-                    whisper_engine
-                        .transcribe_samples(audio, Some(params))
-                        .map_err(|e| anyhow::anyhow!("Whisper transcription failed: {}", e))?
-                }
-                LoadedEngine::Parakeet(parakeet_engine) => {
-                    // Log language setting for debugging
-                    debug!("Parakeet transcription with language: {}", settings.selected_language);
-                    
-                    let params = ParakeetInferenceParams {
-                        timestamp_granularity: TimestampGranularity::Segment,
-                        ..Default::default()
-                    };
-
-                    parakeet_engine
-                        .transcribe_samples(audio, Some(params))
-                        .map_err(|e| anyhow::anyhow!("Parakeet transcription failed: {}", e))?
+    /// Punctuate and case text from models that emit neither, when a
+    /// punctuation model is set up for the transcription language
+    fn maybe_restore_punctuation(&self, text: String, settings: &AppSettings) -> String {
+        let Some(dir) = &settings.punctuation_model_path else {
+            return text;
+        };
+        let language = settings
+            .transcription_params
+            .language_hint
+            .as_deref()
+            .unwrap_or(&settings.selected_language);
+        if !settings.punctuation_languages.iter().any(|l| l == language)
+            || !needs_punctuation(&text)
+        {
+            return text;
+        }
+
+        let mut punctuation = self.punctuation.lock().unwrap();
+        if punctuation
+            .as_ref()
+            .map_or(true, |model| model.dir() != Path::new(dir))
+        {
+            *punctuation = match PunctuationModel::load(Path::new(dir)) {
+                Ok(model) => Some(model),
+                Err(e) => {
+                    warn!("Failed to load punctuation model from {}: {}", dir, e);
+                    return text;
                 }
-            }
+            };
+        }
+        let Some(model) = punctuation.as_mut() else {
+            return text;
         };
+        match model.restore(&text) {
+            Ok(restored) => restored,
+            Err(e) => {
+                warn!("Punctuation restore failed: {}", e);
+                text
+            }
+        }
+    }
 
+    fn apply_corrections(&self, text: &str, settings: &AppSettings) -> String {
         // Apply word correction if custom words are configured
-        let corrected_result = if !settings.custom_words.is_empty() {
+        if !settings.custom_words.is_empty() {
             apply_custom_words(
-                &result.text,
+                text,
                 &settings.custom_words,
+                &settings.custom_word_aliases,
                 settings.word_correction_threshold,
             )
         } else {
-            result.text
+            text.to_string()
+        }
+    }
+
+    fn maybe_unload_after_use(&self, settings: &AppSettings) {
+        // Check if we should immediately unload the model after transcription
+        if settings.model_unload_timeout == ModelUnloadTimeout::Immediately {
+            info!("Immediately unloading model after transcription");
+            if let Err(e) = self.unload_model() {
+                error!("Failed to immediately unload model: {}", e);
+            }
+        }
+    }
+
+    /// Transcribe dictation, ahead of anything else queued
+    pub fn transcribe(&self, audio: Vec<f32>) -> Result<String> {
+        self.transcribe_with_prompt(audio, None, JobPriority::Dictation)
+    }
+
+    /// Transcribe `audio` as the continuation of `prompt`, the text that was
+    /// spoken just before it
+    pub fn transcribe_with_prompt(
+        &self,
+        audio: Vec<f32>,
+        prompt: Option<&str>,
+        priority: JobPriority,
+    ) -> Result<String> {
+        let prompt = prompt.map(str::to_string);
+        self.run(priority, move |tm| {
+            tm.transcribe_now(audio, prompt.as_deref())
+        })
+    }
+
+    fn transcribe_now(&self, audio: Vec<f32>, prompt: Option<&str>) -> Result<String> {
+        // Update last activity timestamp
+        self.touch_activity();
+
+        let st = std::time::Instant::now();
+
+        debug!("Audio vector length: {}", audio.len());
+
+        if audio.len() == 0 {
+            debug!("Empty audio vector");
+            return Ok(String::new());
+        }
+
+        // Get current settings for configuration
+        let settings = get_settings(&self.app_handle);
+
+        // Kept to check the text against when filtering hallucinations
+        let source_audio = settings.hallucination_filter_enabled.then(|| audio.clone());
+
+        // Perform transcription with the appropriate engine
+        let result = self.infer(audio, &settings, Granularity::Segment, prompt)?;
+        let text = match source_audio {
+            Some(audio) => filter_transcript(result, &audio),
+            None => result.text,
         };
+        let text = self.maybe_restore_punctuation(text, &settings);
+
+        let corrected_result = self.apply_corrections(&text, &settings);
 
         let et = std::time::Instant::now();
         let translation_note = if settings.translate_to_english {
@@ -421,32 +631,212 @@ impl TranscriptionManager {
             info!("Transcription result: {}", final_result);
         }
 
-        // Check if we should immediately unload the model after transcription
-        if settings.model_unload_timeout == ModelUnloadTimeout::Immediately {
-            info!("Immediately unloading model after transcription");
-            if let Err(e) = self.unload_model() {
-                error!("Failed to immediately unload model: {}", e);
-            }
-        }
+        self.maybe_unload_after_use(&settings);
 
         Ok(final_result)
     }
+
+    /// Translate speech in `audio` into `target_language`. Whisper's translate
+    /// task is the only on-device translator, so English is the only target.
+    pub fn translate(
+        &self,
+        audio: Vec<f32>,
+        target_language: &str,
+        priority: JobPriority,
+    ) -> Result<String> {
+        if target_language != "en" {
+            return Err(anyhow::anyhow!(
+                "Translation into '{}' is not supported; only English is available",
+                target_language
+            ));
+        }
+        let can_translate = self
+            .backend_info()
+            .is_some_and(|info| info.capabilities.translation);
+        if !can_translate {
+            return Err(anyhow::anyhow!(
+                "The loaded model can't translate; select a Whisper model"
+            ));
+        }
+
+        self.run(priority, move |tm| {
+            tm.touch_activity();
+            let mut settings = get_settings(&tm.app_handle);
+            settings.translate_to_english = true;
+
+            let st = std::time::Instant::now();
+            let result = tm.infer(audio, &settings, Granularity::Segment, None)?;
+            debug!("Translation completed in {}ms", st.elapsed().as_millis());
+
+            Ok(result.text.trim().to_string())
+        })
+    }
+
+    /// Transcribe and keep timing information. Parakeet reports one segment per
+    /// word; Whisper reports sentence-level segments. Times are relative to the
+    /// start of `audio`, in seconds. `prompt` is the text spoken just before.
+    pub fn transcribe_segments(
+        &self,
+        audio: Vec<f32>,
+        prompt: Option<&str>,
+        priority: JobPriority,
+    ) -> Result<Vec<TimedSegment>> {
+        let prompt = prompt.map(str::to_string);
+        self.run(priority, move |tm| {
+            tm.transcribe_segments_now(audio, prompt.as_deref())
+        })
+    }
+
+    fn transcribe_segments_now(
+        &self,
+        audio: Vec<f32>,
+        prompt: Option<&str>,
+    ) -> Result<Vec<TimedSegment>> {
+        self.touch_activity();
+
+        if audio.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let settings = get_settings(&self.app_handle);
+        let st = std::time::Instant::now();
+
+        let source_audio = settings.hallucination_filter_enabled.then(|| audio.clone());
+        let result = self.infer(audio, &settings, Granularity::Word, prompt)?;
+        let segments = match source_audio {
+            Some(audio) => drop_hallucinations(result.segments, &audio),
+            None => result.segments,
+        };
+
+        let segments: Vec<TimedSegment> = segments
+            .into_iter()
+            .filter_map(|segment| {
+                let text = self.apply_corrections(segment.text.trim(), &settings);
+                if text.is_empty() {
+                    None
+                } else {
+                    Some(TimedSegment {
+                        start: segment.start,
+                        end: segment.end,
+                        text,
+                    })
+                }
+            })
+            .collect();
+
+        info!(
+            "Timed transcription completed in {}ms ({} segments)",
+            st.elapsed().as_millis(),
+            segments.len()
+        );
+
+        self.maybe_unload_after_use(&settings);
+
+        Ok(segments)
+    }
 }
 
-impl Drop for TranscriptionManager {
-    fn drop(&mut self) {
-        debug!("Shutting down TranscriptionManager");
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Decoding options for a call, from the transcription settings
+fn inference_request(
+    settings: &AppSettings,
+    granularity: Granularity,
+    prompt: Option<&str>,
+) -> InferenceRequest {
+    // Normalize language code for Whisper
+    // Convert zh-Hans and zh-Hant to zh since Whisper uses ISO 639-1 codes
+    let params = &settings.transcription_params;
+    let language = if let Some(hint) = &params.language_hint {
+        Some(hint.clone())
+    } else if settings.selected_language == "auto" {
+        None
+    } else if settings.selected_language == "zh-Hans" || settings.selected_language == "zh-Hant" {
+        Some("zh".to_string())
+    } else {
+        Some(settings.selected_language.clone())
+    };
+
+    InferenceRequest {
+        language,
+        translate: settings.translate_to_english,
+        granularity,
+        beam_size: params.beam_size,
+        temperature: params.temperature,
+        no_speech_threshold: params.no_speech_threshold,
+        initial_prompt: initial_prompt(settings, prompt),
+        keywords: settings.keyword_boosts.clone(),
+        keyword_bias: settings.keyword_boost_strength,
+    }
+}
 
-        // Signal the watcher thread to shutdown
-        self.shutdown_signal.store(true, Ordering::Relaxed);
+/// The custom words glossary followed by `context`. The context goes last
+/// so it sits right before the audio it leads into.
+fn initial_prompt(settings: &AppSettings, context: Option<&str>) -> Option<String> {
+    match (custom_words_prompt(&settings.custom_words), context) {
+        (Some(glossary), Some(context)) => Some(format!("{} {}", glossary, context)),
+        (glossary, context) => glossary.or_else(|| context.map(str::to_string)),
+    }
+}
 
-        // Wait for the thread to finish gracefully
-        if let Some(handle) = self.watcher_handle.lock().unwrap().take() {
-            if let Err(e) = handle.join() {
-                warn!("Failed to join idle watcher thread: {:?}", e);
-            } else {
-                debug!("Idle watcher thread joined successfully");
+/// Drop segments the model likely invented, judged against the audio they
+/// were transcribed from. Times are relative to the start of `audio`.
+fn drop_hallucinations(segments: Vec<TimedSegment>, audio: &[f32]) -> Vec<TimedSegment> {
+    let text: Vec<&str> = segments.iter().map(|s| s.text.trim()).collect();
+    if let Some(reason) = hallucination_reason(&text.join(" "), &AudioEvidence::measure(audio)) {
+        info!("Dropping transcription ({}): '{}'", reason, text.join(" "));
+        return Vec::new();
+    }
+
+    let to_index =
+        |secs: f32| ((secs.max(0.0) * WHISPER_SAMPLE_RATE as f32) as usize).min(audio.len());
+    segments
+        .into_iter()
+        .filter(|segment| {
+            if segment.end - segment.start < MIN_JUDGED_SEGMENT_SECS {
+                return true;
             }
-        }
+            let span = &audio[to_index(segment.start)..to_index(segment.end)];
+            match hallucination_reason(&segment.text, &AudioEvidence::measure(span)) {
+                Some(reason) => {
+                    info!("Dropping segment ({}): '{}'", reason, segment.text.trim());
+                    false
+                }
+                None => true,
+            }
+        })
+        .collect()
+}
+
+/// Text of `transcript` with invented segments removed. Untouched text is
+/// returned as the backend produced it.
+fn filter_transcript(transcript: Transcript, audio: &[f32]) -> String {
+    if transcript.segments.is_empty() {
+        return match hallucination_reason(&transcript.text, &AudioEvidence::measure(audio)) {
+            Some(reason) => {
+                info!(
+                    "Dropping transcription ({}): '{}'",
+                    reason,
+                    transcript.text.trim()
+                );
+                String::new()
+            }
+            None => transcript.text,
+        };
+    }
+
+    let count = transcript.segments.len();
+    let kept = drop_hallucinations(transcript.segments, audio);
+    if kept.len() == count {
+        return transcript.text;
     }
+    kept.iter()
+        .map(|segment| segment.text.trim())
+        .collect::<Vec<_>>()
+        .join(" ")
 }
@@ -0,0 +1,280 @@
+// Priority queue of transcription jobs run on dedicated worker threads
+// Model invocations run here instead of on the threads asking for them.
+// Dictation jumps ahead of queued caption windows and background work, and
+// caption windows that pile up behind a slow model are shed, oldest first,
+// instead of queueing without bound.
+
+use crate::events::TranscriptionQueueDepth;
+use anyhow::{anyhow, Result};
+use log::{debug, error, warn};
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Condvar, Mutex, Weak};
+use std::thread;
+
+/// Caption windows allowed to wait at once
+const MAX_QUEUED_CAPTIONS: usize = 4;
+
+/// Order in which queued jobs run, lowest last
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum JobPriority {
+    /// Files, history entries and recovered recordings
+    Background,
+    /// Live caption windows
+    Caption,
+    /// Dictation the user is waiting on
+    Dictation,
+}
+
+type Job<C> = Box<dyn FnOnce(&C) + Send>;
+
+struct Queued<C> {
+    priority: JobPriority,
+    job: Job<C>,
+}
+
+struct PoolState<C> {
+    queued: VecDeque<Queued<C>>,
+    running: usize,
+    shutdown: bool,
+}
+
+impl<C> PoolState<C> {
+    fn count(&self, priority: JobPriority) -> usize {
+        self.queued
+            .iter()
+            .filter(|q| q.priority == priority)
+            .count()
+    }
+
+    fn depth(&self) -> TranscriptionQueueDepth {
+        TranscriptionQueueDepth {
+            dictation: self.count(JobPriority::Dictation),
+            caption: self.count(JobPriority::Caption),
+            background: self.count(JobPriority::Background),
+            running: self.running,
+        }
+    }
+
+    /// The oldest job of the highest priority waiting
+    fn next(&mut self) -> Option<Queued<C>> {
+        let priority = self.queued.iter().map(|q| q.priority).max()?;
+        let index = self.queued.iter().position(|q| q.priority == priority)?;
+        self.queued.remove(index)
+    }
+}
+
+type DepthCallback = Box<dyn Fn(TranscriptionQueueDepth) + Send + Sync>;
+
+struct Shared<C> {
+    state: Mutex<PoolState<C>>,
+    available: Condvar,
+    on_depth: DepthCallback,
+}
+
+/// Workers running jobs against a shared context, like the transcription
+/// manager. They hold it weakly and stop once it is gone.
+pub struct WorkerPool<C> {
+    shared: Arc<Shared<C>>,
+}
+
+/// The pending result of a submitted job
+pub struct JobHandle<T> {
+    rx: mpsc::Receiver<Result<T>>,
+}
+
+impl<T> JobHandle<T> {
+    /// Block until the job has run. Fails if it was dropped instead, because
+    /// it was shed, the pool shut down or the job panicked.
+    pub fn wait(self) -> Result<T> {
+        self.rx
+            .recv()
+            .unwrap_or_else(|_| Err(anyhow!("Transcription was dropped before it finished")))
+    }
+}
+
+impl<C: Send + Sync + 'static> WorkerPool<C> {
+    /// Start `workers` threads. `on_depth` hears every change to the queue.
+    pub fn new(
+        workers: usize,
+        context: Weak<C>,
+        on_depth: impl Fn(TranscriptionQueueDepth) + Send + Sync + 'static,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(PoolState {
+                queued: VecDeque::new(),
+                running: 0,
+                shutdown: false,
+            }),
+            available: Condvar::new(),
+            on_depth: Box::new(on_depth),
+        });
+        for _ in 0..workers.max(1) {
+            let shared = Arc::clone(&shared);
+            let context = context.clone();
+            thread::spawn(move || run_worker(&shared, &context));
+        }
+        Self { shared }
+    }
+
+    /// Queue `job` behind everything of its priority or higher
+    pub fn submit<T, F>(&self, priority: JobPriority, job: F) -> JobHandle<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&C) -> Result<T> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let job: Job<C> = Box::new(move |context| {
+            let _ = tx.send(job(context));
+        });
+
+        let mut state = self.shared.state.lock().unwrap();
+        if state.shutdown {
+            return JobHandle { rx };
+        }
+        if priority == JobPriority::Caption && state.count(priority) >= MAX_QUEUED_CAPTIONS {
+            if let Some(index) = state.queued.iter().position(|q| q.priority == priority) {
+                warn!("Transcription is falling behind, dropping the oldest caption window");
+                state.queued.remove(index);
+            }
+        }
+        state.queued.push_back(Queued { priority, job });
+        (self.shared.on_depth)(state.depth());
+        self.shared.available.notify_one();
+        JobHandle { rx }
+    }
+
+    /// Run `job` on a worker and wait for its result
+    pub fn run<T, F>(&self, priority: JobPriority, job: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&C) -> Result<T> + Send + 'static,
+    {
+        self.submit(priority, job).wait()
+    }
+
+    pub fn depth(&self) -> TranscriptionQueueDepth {
+        self.shared.state.lock().unwrap().depth()
+    }
+}
+
+impl<C> Drop for WorkerPool<C> {
+    // Doesn't join: the last job may be the one dropping the context
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.shutdown = true;
+        state.queued.clear();
+        self.shared.available.notify_all();
+    }
+}
+
+fn run_worker<C>(shared: &Shared<C>, context: &Weak<C>) {
+    loop {
+        let queued = {
+            let mut state = shared.state.lock().unwrap();
+            loop {
+                if state.shutdown {
+                    return;
+                }
+                if let Some(next) = state.next() {
+                    state.running += 1;
+                    (shared.on_depth)(state.depth());
+                    break next;
+                }
+                state = shared.available.wait(state).unwrap();
+            }
+        };
+
+        let Some(context) = context.upgrade() else {
+            debug!("Transcription worker stopping, its context is gone");
+            return;
+        };
+        // A panicking job drops its result sender, failing only its own wait
+        if panic::catch_unwind(AssertUnwindSafe(|| (queued.job)(&context))).is_err() {
+            error!("Transcription job panicked");
+        }
+        drop(context);
+
+        let mut state = shared.state.lock().unwrap();
+        state.running -= 1;
+        (shared.on_depth)(state.depth());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // A single worker blocked on a gate job until the returned sender fires
+    fn blocked_pool(
+        depths: Arc<Mutex<Vec<TranscriptionQueueDepth>>>,
+    ) -> (Arc<()>, WorkerPool<()>, mpsc::Sender<()>) {
+        let context = Arc::new(());
+        let pool = WorkerPool::new(1, Arc::downgrade(&context), move |depth| {
+            depths.lock().unwrap().push(depth)
+        });
+        let (gate_tx, gate_rx) = mpsc::channel::<()>();
+        let (started_tx, started_rx) = mpsc::channel();
+        pool.submit(JobPriority::Background, move |_| {
+            started_tx.send(()).unwrap();
+            gate_rx.recv().map_err(Into::into)
+        });
+        started_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        (context, pool, gate_tx)
+    }
+
+    #[test]
+    fn test_dictation_runs_before_queued_captions() {
+        let depths = Arc::new(Mutex::new(Vec::new()));
+        let (_context, pool, gate) = blocked_pool(Arc::clone(&depths));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let submit = |priority, name: &'static str| {
+            let order = Arc::clone(&order);
+            pool.submit(priority, move |_| {
+                order.lock().unwrap().push(name);
+                Ok(())
+            })
+        };
+        let background = submit(JobPriority::Background, "background");
+        let caption = submit(JobPriority::Caption, "caption");
+        let dictation = submit(JobPriority::Dictation, "dictation");
+        assert_eq!(
+            pool.depth(),
+            TranscriptionQueueDepth {
+                dictation: 1,
+                caption: 1,
+                background: 1,
+                running: 1,
+            }
+        );
+
+        gate.send(()).unwrap();
+        for handle in [background, caption, dictation] {
+            handle.wait().unwrap();
+        }
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["dictation", "caption", "background"]
+        );
+        assert_eq!(
+            depths.lock().unwrap().last(),
+            Some(&TranscriptionQueueDepth::default())
+        );
+    }
+
+    #[test]
+    fn test_oldest_caption_is_shed_when_full() {
+        let depths = Arc::new(Mutex::new(Vec::new()));
+        let (_context, pool, gate) = blocked_pool(depths);
+        let handles: Vec<_> = (0..=MAX_QUEUED_CAPTIONS)
+            .map(|i| pool.submit(JobPriority::Caption, move |_| Ok(i)))
+            .collect();
+        assert_eq!(pool.depth().caption, MAX_QUEUED_CAPTIONS);
+
+        gate.send(()).unwrap();
+        let results: Vec<_> = handles.into_iter().map(|h| h.wait().ok()).collect();
+        assert_eq!(results[0], None);
+        assert_eq!(results[MAX_QUEUED_CAPTIONS], Some(MAX_QUEUED_CAPTIONS));
+    }
+}
@@ -0,0 +1,202 @@
+//! Timings of each stage of the audio pipeline and capture glitch counts,
+//! kept in memory only.
+//!
+//! Stages record how long each pass took; `snapshot` summarizes the most
+//! recent passes for the diagnostics panel. Nothing is persisted or sent
+//! anywhere.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Timings kept per stage; averages and percentiles cover only these
+const WINDOW: usize = 200;
+
+/// A step audio goes through between the microphone and the pasted text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// Draining and trimming the recording once it stops
+    Capture,
+    /// Converting one chunk of input to 16kHz, per chunk
+    Resample,
+    /// Voice activity decision, per 30ms frame
+    Vad,
+    /// One model inference, dictation and captions alike
+    Transcribe,
+    /// Putting the text into the focused app
+    Paste,
+}
+
+const STAGES: [Stage; 5] = [
+    Stage::Capture,
+    Stage::Resample,
+    Stage::Vad,
+    Stage::Transcribe,
+    Stage::Paste,
+];
+
+/// Summary of one stage's recent timings, in milliseconds
+#[derive(Clone, Debug, Serialize)]
+pub struct StageMetrics {
+    pub stage: Stage,
+    /// Passes recorded since launch, including those outside the window
+    pub count: u64,
+    pub average_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Default)]
+struct StageTimings {
+    count: u64,
+    recent: VecDeque<Duration>,
+}
+
+impl StageTimings {
+    fn push(&mut self, elapsed: Duration) {
+        self.count += 1;
+        if self.recent.len() == WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(elapsed);
+    }
+
+    fn summarize(&self, stage: Stage) -> StageMetrics {
+        let mut sorted: Vec<f64> = self
+            .recent
+            .iter()
+            .map(|elapsed| elapsed.as_secs_f64() * 1000.0)
+            .collect();
+        sorted.sort_by(f64::total_cmp);
+        let average_ms = if sorted.is_empty() {
+            0.0
+        } else {
+            sorted.iter().sum::<f64>() / sorted.len() as f64
+        };
+        StageMetrics {
+            stage,
+            count: self.count,
+            average_ms,
+            p95_ms: percentile(&sorted, 0.95),
+            max_ms: sorted.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+static TIMINGS: Lazy<Mutex<Vec<StageTimings>>> =
+    Lazy::new(|| Mutex::new(STAGES.iter().map(|_| StageTimings::default()).collect()));
+
+fn index(stage: Stage) -> usize {
+    STAGES.iter().position(|s| *s == stage).unwrap()
+}
+
+/// Record one pass through `stage`
+pub fn record(stage: Stage, elapsed: Duration) {
+    TIMINGS.lock().unwrap()[index(stage)].push(elapsed);
+}
+
+/// Run `f`, recording how long it took as one pass through `stage`
+pub fn time<T>(stage: Stage, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = f();
+    record(stage, started.elapsed());
+    result
+}
+
+/// Recent timings of every stage, in pipeline order
+pub fn snapshot() -> Vec<StageMetrics> {
+    let timings = TIMINGS.lock().unwrap();
+    STAGES
+        .iter()
+        .map(|stage| timings[index(*stage)].summarize(*stage))
+        .collect()
+}
+
+/// A capture stream glitches are counted for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureStream {
+    Microphone,
+    SystemAudio,
+}
+
+/// Something that cost a capture stream audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Glitch {
+    /// Capture stamps skipped ahead of the audio delivered
+    Gap,
+    /// A queue between the callback and its reader filled up
+    Overrun,
+    /// The driver flagged a break in the data it delivered
+    Discontinuity,
+}
+
+/// Glitches counted on one capture stream since launch
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GlitchMetrics {
+    pub gaps: u64,
+    pub overruns: u64,
+    pub discontinuities: u64,
+}
+
+// Indexed by `Glitch`
+#[derive(Default)]
+struct GlitchCounts([AtomicU64; 3]);
+
+// Indexed by `CaptureStream`
+static GLITCHES: Lazy<[GlitchCounts; 2]> = Lazy::new(Default::default);
+
+/// Count one glitch on `stream`
+pub fn record_glitch(stream: CaptureStream, glitch: Glitch) {
+    GLITCHES[stream as usize].0[glitch as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+/// Glitches counted on `stream` since launch
+pub fn glitches(stream: CaptureStream) -> GlitchMetrics {
+    let [gaps, overruns, discontinuities] = &GLITCHES[stream as usize].0;
+    GlitchMetrics {
+        gaps: gaps.load(Ordering::Relaxed),
+        overruns: overruns.load(Ordering::Relaxed),
+        discontinuities: discontinuities.load(Ordering::Relaxed),
+    }
+}
+
+/// Nearest-rank percentile of ascending `sorted`, 0.0 when empty
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_summary() {
+        let mut timings = StageTimings::default();
+        for ms in 1..=100 {
+            timings.push(Duration::from_millis(ms));
+        }
+        let metrics = timings.summarize(Stage::Transcribe);
+        assert_eq!(metrics.count, 100);
+        assert!((metrics.average_ms - 50.5).abs() < 1e-9);
+        assert!((metrics.p95_ms - 95.0).abs() < 1e-9);
+        assert!((metrics.max_ms - 100.0).abs() < 1e-9);
+
+        // Only the last WINDOW timings count towards the summary
+        for _ in 0..WINDOW {
+            timings.push(Duration::from_millis(2));
+        }
+        let metrics = timings.summarize(Stage::Transcribe);
+        assert_eq!(metrics.count, 100 + WINDOW as u64);
+        assert!((metrics.max_ms - 2.0).abs() < 1e-9);
+    }
+}
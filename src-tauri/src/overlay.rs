@@ -1,5 +1,7 @@
+use crate::audio_toolkit::audio::{LevelFrame, SpectrumFrame};
 use crate::settings;
 use crate::settings::OverlayPosition;
+use crate::utils::{emit_throttled, emit_throttled_to};
 use enigo::{Enigo, Mouse};
 use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize};
 
@@ -246,12 +248,15 @@ pub fn hide_recording_overlay(app_handle: &AppHandle) {
     }
 }
 
-pub fn emit_levels(app_handle: &AppHandle, levels: &Vec<f32>) {
+pub fn emit_levels(app_handle: &AppHandle, levels: &LevelFrame) {
     // emit levels to main app
-    let _ = app_handle.emit("mic-level", levels);
+    emit_throttled(app_handle, "mic-level", levels);
 
     // also emit to the recording overlay if it's open
-    if let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") {
-        let _ = overlay_window.emit("mic-level", levels);
-    }
+    emit_throttled_to(app_handle, "recording_overlay", "mic-level", levels);
+}
+
+/// Send the microphone's spectrum to the main app, for its analyser
+pub fn emit_spectrum(app_handle: &AppHandle, spectrum: &SpectrumFrame) {
+    emit_throttled(app_handle, "audio-spectrum", spectrum);
 }
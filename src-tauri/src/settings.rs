@@ -1,3 +1,8 @@
+use crate::audio_toolkit::audio::{Downmix, Gain};
+use crate::audio_toolkit::screencapturekit::CaptureTarget;
+use crate::audio_toolkit::utils::LinuxAudioHost;
+use crate::audio_toolkit::wasapi::WasapiOptions;
+use crate::backend::{BackendKind, ComputeBackend};
 use log::{debug, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -53,6 +58,22 @@ pub enum ModelUnloadTimeout {
     Sec5, // Debug mode only
 }
 
+/// How `selected_model` is chosen
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelSelection {
+    /// Whatever the user picked last
+    Manual,
+    /// The largest downloaded model the hardware runs comfortably, re-picked at startup
+    Recommended,
+}
+
+impl Default for ModelSelection {
+    fn default() -> Self {
+        ModelSelection::Manual
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum PasteMethod {
@@ -60,6 +81,21 @@ pub enum PasteMethod {
     Direct,
     #[cfg(not(target_os = "macos"))]
     ShiftInsert,
+    /// Insert through the Accessibility API, without the clipboard or keystrokes
+    #[cfg(target_os = "macos")]
+    Accessibility,
+}
+
+/// How text is typed on Linux. Only consulted on Linux builds.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LinuxTypingBackend {
+    /// wtype/ydotool on Wayland, the regular paste method on X11
+    Auto,
+    Wtype,
+    Ydotool,
+    Xdotool,
+    ClipboardOnly,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -69,9 +105,11 @@ pub enum ClipboardHandling {
     CopyToClipboard,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+/// How older versions limited history, together with `history_limit`. Only
+/// read to migrate stored settings to `HistoryRetention`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
-pub enum RecordingRetentionPeriod {
+enum RecordingRetentionPeriod {
     Never,
     PreserveLimit,
     Days3,
@@ -86,6 +124,95 @@ pub enum AudioSource {
     SystemAudio,
 }
 
+/// When the always-on caption loop transcribes buffered audio
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionStrategy {
+    /// Fixed sliding window every few seconds
+    Interval,
+    /// Once per utterance, on VAD end-of-speech boundaries
+    Vad,
+}
+
+impl Default for CaptionStrategy {
+    fn default() -> Self {
+        CaptionStrategy::Interval
+    }
+}
+
+/// Letter case forced onto transcripts as the last text-pipeline stage
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TextCasing {
+    /// Leave the model's casing alone
+    Original,
+    Lowercase,
+    /// Lowercase with the first letter of each sentence capitalized
+    Sentence,
+    Uppercase,
+}
+
+impl Default for TextCasing {
+    fn default() -> Self {
+        TextCasing::Original
+    }
+}
+
+/// Where finished text goes once a hotkey recording or caption is transcribed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputMode {
+    /// Use the configured paste method
+    Paste,
+    /// Type the text out instead of going through the clipboard
+    Type,
+    ClipboardOnly,
+    /// Keep the text in history only, and append it to the transcript file
+    /// when one is set up
+    None,
+}
+
+impl Default for OutputMode {
+    fn default() -> Self {
+        OutputMode::Paste
+    }
+}
+
+/// What happens to blocklisted words before text is pasted or captioned
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfanityFilter {
+    Keep,
+    /// Replace all but the first letter with asterisks
+    Mask,
+    Remove,
+}
+
+impl Default for ProfanityFilter {
+    fn default() -> Self {
+        ProfanityFilter::Keep
+    }
+}
+
+/// What a spoken formatting command does to the dictated text
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", content = "text", rename_all = "snake_case")]
+pub enum SpokenCommand {
+    /// Attached to the previous word, e.g. "." or a closing quote
+    Punctuation(String),
+    /// Attached to the next word, e.g. an opening quote
+    Opening(String),
+    /// Inserted without spaces around it, e.g. a line break
+    Break(String),
+    /// Inserted as a word of its own
+    Text(String),
+    /// Uppercase the following words until `CapsOff`
+    CapsOn,
+    CapsOff,
+    /// Capitalize the next word
+    CapitalizeNext,
+}
+
 impl Default for AudioSource {
     fn default() -> Self {
         AudioSource::Microphone
@@ -108,6 +235,12 @@ impl Default for PasteMethod {
     }
 }
 
+impl Default for LinuxTypingBackend {
+    fn default() -> Self {
+        LinuxTypingBackend::Auto
+    }
+}
+
 impl Default for ClipboardHandling {
     fn default() -> Self {
         ClipboardHandling::DontModify
@@ -164,6 +297,300 @@ impl SoundTheme {
     }
 }
 
+/// How numbers are written in transcripts
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberStyle {
+    /// Leave numbers as the model wrote them
+    AsTranscribed,
+    /// "twenty five dollars" becomes "$25"
+    Digits,
+    /// "25" becomes "twenty-five"
+    Words,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThousandsSeparator {
+    None,
+    Comma,
+    Period,
+    Space,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DateFormat {
+    AsTranscribed,
+    /// March 5, 2024
+    Long,
+    /// 2024-03-05; dates without a year are left alone
+    Iso,
+    /// 3/5/2024
+    Us,
+    /// 5/3/2024
+    European,
+}
+
+impl Default for NumberStyle {
+    fn default() -> Self {
+        NumberStyle::AsTranscribed
+    }
+}
+
+impl Default for ThousandsSeparator {
+    fn default() -> Self {
+        ThousandsSeparator::Comma
+    }
+}
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        DateFormat::AsTranscribed
+    }
+}
+
+/// Text normalization applied to numbers and dates after transcription
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct NumberFormatting {
+    #[serde(default)]
+    pub style: NumberStyle,
+    #[serde(default)]
+    pub thousands_separator: ThousandsSeparator,
+    #[serde(default)]
+    pub date_format: DateFormat,
+}
+
+/// Decoder tuning for power users. Backends apply the options they support and
+/// ignore the rest.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TranscriptionParams {
+    /// Beam search width, or `None` for the engine's default strategy
+    pub beam_size: Option<u32>,
+    /// Sampling temperature; 0 decodes greedily
+    pub temperature: f32,
+    /// Segments more likely than this to be silence are dropped
+    pub no_speech_threshold: f32,
+    /// Language code decoded in place of the selected language
+    pub language_hint: Option<String>,
+}
+
+impl Default for TranscriptionParams {
+    fn default() -> Self {
+        Self {
+            beam_size: None,
+            temperature: 0.0,
+            no_speech_threshold: 0.6,
+            language_hint: None,
+        }
+    }
+}
+
+impl TranscriptionParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if matches!(self.beam_size, Some(size) if size == 0 || size > 16) {
+            return Err("Beam size must be between 1 and 16".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.temperature) {
+            return Err("Temperature must be between 0 and 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.no_speech_threshold) {
+            return Err("No-speech threshold must be between 0 and 1".to_string());
+        }
+        if let Some(hint) = &self.language_hint {
+            if hint.is_empty()
+                || hint.len() > 8
+                || !hint.chars().all(|c| c.is_ascii_alphabetic() || c == '-')
+            {
+                return Err(format!("'{}' is not a language code", hint));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Limits history is pruned to in the background. Saved entries are never
+/// pruned; 0 turns a limit off.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HistoryRetention {
+    pub max_age_days: u32,
+    pub max_entries: usize,
+    /// Total size of the recordings kept, in megabytes
+    pub max_audio_mb: u64,
+}
+
+impl Default for HistoryRetention {
+    fn default() -> Self {
+        Self {
+            max_age_days: 0,
+            max_entries: 0,
+            max_audio_mb: 2048,
+        }
+    }
+}
+
+impl HistoryRetention {
+    /// The limits stored settings from older versions had, which kept a
+    /// `recording_retention_period` and `history_limit` instead. None once
+    /// the settings have `history_retention`.
+    fn migrated_from(stored: &serde_json::Value) -> Option<Self> {
+        if stored.get("history_retention").is_some() {
+            return None;
+        }
+        let period = stored
+            .get("recording_retention_period")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or(RecordingRetentionPeriod::PreserveLimit);
+        let limit = stored
+            .get("history_limit")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(5) as usize;
+        let days = |max_age_days| Self {
+            max_age_days,
+            ..Self::default()
+        };
+        Some(match period {
+            // Nothing was ever deleted, so no size limit either
+            RecordingRetentionPeriod::Never => Self {
+                max_age_days: 0,
+                max_entries: 0,
+                max_audio_mb: 0,
+            },
+            RecordingRetentionPeriod::PreserveLimit => Self {
+                max_entries: limit,
+                ..Self::default()
+            },
+            RecordingRetentionPeriod::Days3 => days(3),
+            RecordingRetentionPeriod::Weeks2 => days(14),
+            RecordingRetentionPeriod::Months3 => days(90),
+        })
+    }
+}
+
+/// Which channels of a multi-channel source make up the mono signal
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DownmixMode {
+    Average,
+    Left,
+    Right,
+    /// The channel at `ChannelDownmix::channel`
+    Channel,
+}
+
+/// How a multi-channel capture is reduced to mono, e.g. to take only the
+/// microphone from an interface carrying loopback audio on another channel
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelDownmix {
+    pub mode: DownmixMode,
+    /// Counting from 0; only used by `DownmixMode::Channel`
+    #[serde(default)]
+    pub channel: u16,
+}
+
+impl Default for ChannelDownmix {
+    fn default() -> Self {
+        Self {
+            mode: DownmixMode::Average,
+            channel: 0,
+        }
+    }
+}
+
+impl ChannelDownmix {
+    pub fn downmix(self) -> Downmix {
+        match self.mode {
+            DownmixMode::Average => Downmix::Average,
+            DownmixMode::Left => Downmix::Channel(0),
+            DownmixMode::Right => Downmix::Channel(1),
+            DownmixMode::Channel => Downmix::Channel(self.channel as usize),
+        }
+    }
+}
+
+/// What `mute_while_recording` does to the system output
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MuteMode {
+    #[default]
+    Mute,
+    /// Lower the volume by `duck_percent`
+    Duck,
+}
+
+/// Software gain for one input device
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct InputGain {
+    pub gain_db: f32,
+    /// Compress peaks the boost would clip instead of clipping them
+    #[serde(default)]
+    pub limiter: bool,
+}
+
+impl InputGain {
+    pub fn gain(self) -> Gain {
+        Gain::from_db(self.gain_db, self.limiter)
+    }
+}
+
+/// Where the caption overlay sits on screen
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionAnchor {
+    Top,
+    Center,
+    Bottom,
+}
+
+impl Default for CaptionAnchor {
+    fn default() -> Self {
+        CaptionAnchor::Bottom
+    }
+}
+
+/// Look of the live caption overlay
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CaptionStyle {
+    pub font_size: u32,
+    /// Opacity of the box behind the text, 0 to 1
+    pub background_opacity: f32,
+    /// Lines of caption text kept on screen; older lines scroll off
+    pub max_lines: u32,
+    pub anchor: CaptionAnchor,
+    /// How long a caption stays up with nothing new, or 0 to keep it
+    pub fade_timeout_ms: u64,
+}
+
+impl Default for CaptionStyle {
+    fn default() -> Self {
+        Self {
+            font_size: 18,
+            background_opacity: 0.85,
+            max_lines: 2,
+            anchor: CaptionAnchor::Bottom,
+            fade_timeout_ms: 5000,
+        }
+    }
+}
+
+impl CaptionStyle {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(10..=72).contains(&self.font_size) {
+            return Err("Font size must be between 10 and 72".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.background_opacity) {
+            return Err("Background opacity must be between 0 and 1".to_string());
+        }
+        if !(1..=10).contains(&self.max_lines) {
+            return Err("Max lines must be between 1 and 10".to_string());
+        }
+        if self.fade_timeout_ms > 60_000 {
+            return Err("Fade timeout can be at most 60 seconds".to_string());
+        }
+        Ok(())
+    }
+}
+
 /* still handy for composing the initial JSON in the store ------------- */
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AppSettings {
@@ -180,6 +607,8 @@ pub struct AppSettings {
     pub autostart_enabled: bool,
     #[serde(default = "default_model")]
     pub selected_model: String,
+    #[serde(default)]
+    pub model_selection: ModelSelection,
     #[serde(default = "default_always_on_microphone")]
     pub always_on_microphone: bool,
     #[serde(default)]
@@ -190,6 +619,9 @@ pub struct AppSettings {
     pub selected_output_device: Option<String>,
     #[serde(default)]
     pub audio_source: Option<AudioSource>,
+    /// Display, window or app picked for ScreenCaptureKit capture on macOS
+    #[serde(default)]
+    pub screen_capture_target: Option<CaptureTarget>,
     #[serde(default = "default_translate_to_english")]
     pub translate_to_english: bool,
     #[serde(default = "default_selected_language")]
@@ -206,10 +638,6 @@ pub struct AppSettings {
     pub model_unload_timeout: ModelUnloadTimeout,
     #[serde(default = "default_word_correction_threshold")]
     pub word_correction_threshold: f64,
-    #[serde(default = "default_history_limit")]
-    pub history_limit: usize,
-    #[serde(default = "default_recording_retention_period")]
-    pub recording_retention_period: RecordingRetentionPeriod,
     #[serde(default)]
     pub paste_method: PasteMethod,
     #[serde(default)]
@@ -230,8 +658,208 @@ pub struct AppSettings {
     pub post_process_selected_prompt_id: Option<String>,
     #[serde(default)]
     pub mute_while_recording: bool,
+    #[serde(default)]
+    pub mute_mode: MuteMode,
+    /// How far ducking lowers the output volume, in percent of its level
+    #[serde(default = "default_duck_percent")]
+    pub duck_percent: u8,
     #[serde(default = "default_live_caption_enabled")]
     pub live_caption_enabled: bool,
+    #[serde(default = "default_silence_trim_padding_ms")]
+    pub silence_trim_padding_ms: u64,
+    #[serde(default = "default_system_audio_buffer_seconds")]
+    pub system_audio_buffer_seconds: u32,
+    #[serde(default)]
+    pub caption_alignment_enabled: bool,
+    #[serde(default = "default_caption_overlap_ms")]
+    pub caption_overlap_ms: u64,
+    /// Words of the previous caption passed to the model as context for the
+    /// next window. 0 transcribes each window on its own.
+    #[serde(default = "default_caption_context_words")]
+    pub caption_context_words: usize,
+    #[serde(default)]
+    pub caption_strategy: CaptionStrategy,
+    #[serde(default)]
+    pub debug_audio_logging: bool,
+    #[serde(default)]
+    pub linux_typing_backend: LinuxTypingBackend,
+    #[serde(default)]
+    pub keyword_boosts: Vec<String>,
+    /// Logit bias the decoder adds to `keyword_boosts` tokens
+    #[serde(default = "default_keyword_boost_strength")]
+    pub keyword_boost_strength: f32,
+    /// How long a finished transcription waits before pasting so it can be
+    /// retaken. 0 pastes immediately.
+    #[serde(default)]
+    pub retake_window_ms: u64,
+    /// Recordings started within this many seconds of the previous one are
+    /// pasted together as one dictation. 0 pastes each recording on its own.
+    #[serde(default)]
+    pub dictation_stitch_window_secs: u64,
+    #[serde(default)]
+    pub transcript_file_enabled: bool,
+    #[serde(default)]
+    pub transcript_file_path: Option<String>,
+    #[serde(default = "default_transcript_file_timestamps")]
+    pub transcript_file_timestamps: bool,
+    #[serde(default)]
+    pub transcript_file_daily_rotation: bool,
+    #[serde(default)]
+    pub cloud_daily_quota_minutes: u32,
+    #[serde(default = "default_cloud_cost_per_minute")]
+    pub cloud_cost_per_minute: f64,
+    #[serde(default)]
+    pub caption_server_enabled: bool,
+    #[serde(default = "default_caption_server_port")]
+    pub caption_server_port: u16,
+    #[serde(default = "default_language_cycle")]
+    pub language_cycle: Vec<String>,
+    #[serde(default)]
+    pub caption_finalization_delay_ms: u64,
+    #[serde(default)]
+    pub denoise_enabled: bool,
+    /// User-assigned names keyed by stable device id
+    #[serde(default)]
+    pub device_aliases: HashMap<String, String>,
+    /// Cancel system audio picked up by the microphone (macOS/Windows)
+    #[serde(default)]
+    pub echo_cancellation_enabled: bool,
+    /// Phrases that bookmark the surrounding system audio as a history entry
+    #[serde(default)]
+    pub caption_hotwords: Vec<String>,
+    /// External command the final transcript is piped through before output.
+    /// It runs with the user's privileges; the `text_plugin_*` aliases keep
+    /// settings saved under the old names.
+    #[serde(default, alias = "text_plugin_enabled")]
+    pub external_command_enabled: bool,
+    #[serde(default, alias = "text_plugin_command")]
+    pub external_command: String,
+    #[serde(default, alias = "text_plugin_args")]
+    pub external_command_args: Vec<String>,
+    #[serde(
+        default = "default_external_command_timeout_ms",
+        alias = "text_plugin_timeout_ms"
+    )]
+    pub external_command_timeout_ms: u64,
+    /// Start captioning system audio on launch, independently of the mic's
+    /// always-on mode
+    #[serde(default)]
+    pub auto_caption_system_audio: bool,
+    /// Caption the mic as a second channel while captioning system audio
+    #[serde(default)]
+    pub dual_channel_captions: bool,
+    /// Label system-audio captions with numbered speakers, guessed by
+    /// clustering voiceprints heuristically rather than with a trained model
+    #[serde(default)]
+    pub speaker_diarization_enabled: bool,
+    #[serde(default)]
+    pub text_casing: TextCasing,
+    /// Casing for specific bindings, overriding `text_casing`
+    #[serde(default)]
+    pub binding_text_casing: HashMap<String, TextCasing>,
+    /// Language captions are translated into alongside the original, if any
+    #[serde(default)]
+    pub caption_translation_language: Option<String>,
+    /// Which engine family transcribes; the model picks the engine within it
+    #[serde(default)]
+    pub transcription_backend: BackendKind,
+    /// Device local models run on; applies from the next model load
+    #[serde(default)]
+    pub compute_backend: ComputeBackend,
+    /// Throttle transcription when low on battery or running hot
+    #[serde(default)]
+    pub power_saving_enabled: bool,
+    /// Battery charge below which power saving kicks in; below half of it
+    /// always-on capture pauses
+    #[serde(default = "default_power_saving_battery_percent")]
+    pub power_saving_battery_percent: u8,
+    /// Base URL of the OpenAI-compatible API used by the cloud backend
+    #[serde(default = "default_cloud_api_endpoint")]
+    pub cloud_api_endpoint: String,
+    #[serde(default)]
+    pub cloud_api_key: String,
+    #[serde(default = "default_cloud_model")]
+    pub cloud_model: String,
+    /// Transcribe with the downloaded model while the endpoint is unreachable
+    #[serde(default = "default_cloud_fallback_to_local")]
+    pub cloud_fallback_to_local: bool,
+    /// Recordings longer than this many seconds are split at pauses and
+    /// transcribed while recording continues. 0 transcribes everything at stop.
+    #[serde(default = "default_long_recording_segment_secs")]
+    pub long_recording_segment_secs: u64,
+    #[serde(default)]
+    pub transcription_params: TranscriptionParams,
+    /// Drop text the model invents on silence before it is pasted or saved
+    #[serde(default = "default_hallucination_filter_enabled")]
+    pub hallucination_filter_enabled: bool,
+    /// Spoken forms of custom words, keyed by the word they stand for
+    #[serde(default)]
+    pub custom_word_aliases: HashMap<String, Vec<String>>,
+    /// Turn spoken commands like "new line" into formatting before pasting
+    #[serde(default)]
+    pub spoken_formatting_enabled: bool,
+    /// Spoken formatting for specific bindings, overriding `spoken_formatting_enabled`
+    #[serde(default)]
+    pub binding_spoken_formatting: HashMap<String, bool>,
+    /// Spoken phrase to the formatting it stands for
+    #[serde(default = "default_spoken_commands")]
+    pub spoken_commands: HashMap<String, SpokenCommand>,
+    /// Directory of the punctuation and truecasing model, if one is set up
+    #[serde(default)]
+    pub punctuation_model_path: Option<String>,
+    /// Languages the punctuation model runs for; "auto" covers auto-detection
+    #[serde(default = "default_punctuation_languages")]
+    pub punctuation_languages: Vec<String>,
+    #[serde(default)]
+    pub number_formatting: NumberFormatting,
+    #[serde(default)]
+    pub profanity_filter: ProfanityFilter,
+    /// Words filtered on top of the built-in blocklist
+    #[serde(default)]
+    pub profanity_words: Vec<String>,
+    /// Apps that never get text pasted into them, matched case-insensitively
+    /// against the focused app's name or bundle id / executable
+    #[serde(default = "default_paste_blacklist")]
+    pub paste_blacklist: Vec<String>,
+    #[serde(default)]
+    pub output_mode: OutputMode,
+    /// Output mode for specific bindings, overriding `output_mode`
+    #[serde(default)]
+    pub binding_output_mode: HashMap<String, OutputMode>,
+    /// Also paste live captions into the focused window; off keeps them in
+    /// the overlay and history so they don't spray into whatever has focus
+    #[serde(default)]
+    pub caption_paste_enabled: bool,
+    #[serde(default)]
+    pub caption_style: CaptionStyle,
+    #[serde(default)]
+    pub history_retention: HistoryRetention,
+    /// Audio from before the hotkey prepended to a recording when the
+    /// microphone is always on. 0 turns it off; the recorder keeps at most 3s.
+    #[serde(default = "default_pre_roll_ms")]
+    pub pre_roll_ms: u64,
+    /// Recordings are stopped and transcribed after this many seconds. 0 lets
+    /// them run until the shortcut ends them.
+    #[serde(default = "default_max_recording_secs")]
+    pub max_recording_secs: u64,
+    #[serde(default)]
+    pub microphone_downmix: ChannelDownmix,
+    #[serde(default)]
+    pub system_audio_downmix: ChannelDownmix,
+    /// Microphone gain keyed by stable device id, "default" for the default
+    /// input
+    #[serde(default)]
+    pub input_gains: HashMap<String, InputGain>,
+    /// How microphones and loopback capture are opened on Windows
+    #[serde(default)]
+    pub wasapi: WasapiOptions,
+    /// Sound server audio devices are opened through on Linux
+    #[serde(default)]
+    pub linux_audio_host: LinuxAudioHost,
+}
+
+fn default_duck_percent() -> u8 {
+    70
 }
 
 fn default_model() -> String {
@@ -262,6 +890,131 @@ fn default_live_caption_enabled() -> bool {
     true // Default to enabled for live caption display
 }
 
+fn default_silence_trim_padding_ms() -> u64 {
+    300
+}
+
+fn default_system_audio_buffer_seconds() -> u32 {
+    30
+}
+
+fn default_caption_overlap_ms() -> u64 {
+    1000
+}
+
+fn default_caption_context_words() -> usize {
+    // Well under Whisper's 224 token prompt limit
+    32
+}
+
+fn default_keyword_boost_strength() -> f32 {
+    // Roughly seven times likelier; much more and keywords get forced in
+    2.0
+}
+
+fn default_transcript_file_timestamps() -> bool {
+    true
+}
+
+fn default_cloud_api_endpoint() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_cloud_model() -> String {
+    "whisper-1".to_string()
+}
+
+fn default_cloud_fallback_to_local() -> bool {
+    true
+}
+
+fn default_long_recording_segment_secs() -> u64 {
+    120
+}
+
+fn default_pre_roll_ms() -> u64 {
+    1000
+}
+
+fn default_max_recording_secs() -> u64 {
+    600
+}
+
+fn default_hallucination_filter_enabled() -> bool {
+    true
+}
+
+fn default_punctuation_languages() -> Vec<String> {
+    vec!["en".to_string()]
+}
+
+pub fn default_spoken_commands() -> HashMap<String, SpokenCommand> {
+    use SpokenCommand::*;
+
+    [
+        ("new line", Break("\n".to_string())),
+        ("new paragraph", Break("\n\n".to_string())),
+        ("period", Punctuation(".".to_string())),
+        ("full stop", Punctuation(".".to_string())),
+        ("comma", Punctuation(",".to_string())),
+        ("question mark", Punctuation("?".to_string())),
+        ("exclamation mark", Punctuation("!".to_string())),
+        ("exclamation point", Punctuation("!".to_string())),
+        ("colon", Punctuation(":".to_string())),
+        ("semicolon", Punctuation(";".to_string())),
+        ("open quote", Opening("\"".to_string())),
+        ("close quote", Punctuation("\"".to_string())),
+        ("open paren", Opening("(".to_string())),
+        ("close paren", Punctuation(")".to_string())),
+        ("dash", Text("—".to_string())),
+        ("all caps", CapsOn),
+        ("end caps", CapsOff),
+        ("cap", CapitalizeNext),
+    ]
+    .into_iter()
+    .map(|(phrase, command)| (phrase.to_string(), command))
+    .collect()
+}
+
+fn default_paste_blacklist() -> Vec<String> {
+    [
+        "1password",
+        "bitwarden",
+        "keepass",
+        "lastpass",
+        "dashlane",
+        "keychain access",
+        "terminal",
+        "iterm",
+        "alacritty",
+        "kitty",
+        "wezterm",
+        "konsole",
+        "cmd.exe",
+        "powershell",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn default_cloud_cost_per_minute() -> f64 {
+    // Whisper API list price in USD
+    0.006
+}
+
+fn default_caption_server_port() -> u16 {
+    8787
+}
+
+fn default_language_cycle() -> Vec<String> {
+    vec!["vi".to_string(), "en".to_string()]
+}
+
+fn default_external_command_timeout_ms() -> u64 {
+    2000
+}
+
 fn default_overlay_position() -> OverlayPosition {
     #[cfg(target_os = "linux")]
     return OverlayPosition::None;
@@ -281,12 +1034,8 @@ fn default_word_correction_threshold() -> f64 {
     0.18
 }
 
-fn default_history_limit() -> usize {
-    5
-}
-
-fn default_recording_retention_period() -> RecordingRetentionPeriod {
-    RecordingRetentionPeriod::PreserveLimit
+fn default_power_saving_battery_percent() -> u8 {
+    20
 }
 
 fn default_audio_feedback_volume() -> f32 {
@@ -374,6 +1123,11 @@ pub fn get_default_settings() -> AppSettings {
     #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     let default_shortcut = "alt+space";
 
+    #[cfg(target_os = "macos")]
+    let default_cycle_language_shortcut = "option+shift+l";
+    #[cfg(not(target_os = "macos"))]
+    let default_cycle_language_shortcut = "ctrl+shift+l";
+
     let mut bindings = HashMap::new();
     bindings.insert(
         "transcribe".to_string(),
@@ -385,6 +1139,16 @@ pub fn get_default_settings() -> AppSettings {
             current_binding: default_shortcut.to_string(),
         },
     );
+    bindings.insert(
+        "cycle_language".to_string(),
+        ShortcutBinding {
+            id: "cycle_language".to_string(),
+            name: "Cycle Language".to_string(),
+            description: "Switches to the next language in your language list.".to_string(),
+            default_binding: default_cycle_language_shortcut.to_string(),
+            current_binding: default_cycle_language_shortcut.to_string(),
+        },
+    );
 
     AppSettings {
         bindings,
@@ -395,11 +1159,13 @@ pub fn get_default_settings() -> AppSettings {
         start_hidden: default_start_hidden(),
         autostart_enabled: default_autostart_enabled(),
         selected_model: "".to_string(),
+        model_selection: ModelSelection::Manual,
         always_on_microphone: true, // Always-on mode for continuous recording
         selected_microphone: None,
         clamshell_microphone: None,
         selected_output_device: None,
         audio_source: Some(AudioSource::SystemAudio), // Default to System Audio for testing
+        screen_capture_target: None,
         translate_to_english: false,
         selected_language: "vi".to_string(), // Vietnamese as default
         overlay_position: OverlayPosition::Bottom,
@@ -408,8 +1174,6 @@ pub fn get_default_settings() -> AppSettings {
         custom_words: Vec::new(),
         model_unload_timeout: ModelUnloadTimeout::Never,
         word_correction_threshold: default_word_correction_threshold(),
-        history_limit: default_history_limit(),
-        recording_retention_period: default_recording_retention_period(),
         paste_method: PasteMethod::default(),
         clipboard_handling: ClipboardHandling::default(),
         post_process_enabled: default_post_process_enabled(),
@@ -420,7 +1184,78 @@ pub fn get_default_settings() -> AppSettings {
         post_process_prompts: default_post_process_prompts(),
         post_process_selected_prompt_id: None,
         mute_while_recording: false,
+        mute_mode: MuteMode::Mute,
+        duck_percent: default_duck_percent(),
         live_caption_enabled: default_live_caption_enabled(),
+        silence_trim_padding_ms: default_silence_trim_padding_ms(),
+        system_audio_buffer_seconds: default_system_audio_buffer_seconds(),
+        caption_alignment_enabled: false,
+        caption_overlap_ms: default_caption_overlap_ms(),
+        caption_context_words: default_caption_context_words(),
+        caption_strategy: CaptionStrategy::default(),
+        debug_audio_logging: false,
+        linux_typing_backend: LinuxTypingBackend::default(),
+        keyword_boosts: Vec::new(),
+        keyword_boost_strength: default_keyword_boost_strength(),
+        retake_window_ms: 0,
+        dictation_stitch_window_secs: 0,
+        transcript_file_enabled: false,
+        transcript_file_path: None,
+        transcript_file_timestamps: default_transcript_file_timestamps(),
+        transcript_file_daily_rotation: false,
+        cloud_daily_quota_minutes: 0,
+        cloud_cost_per_minute: default_cloud_cost_per_minute(),
+        caption_server_enabled: false,
+        caption_server_port: default_caption_server_port(),
+        language_cycle: default_language_cycle(),
+        caption_finalization_delay_ms: 0,
+        denoise_enabled: false,
+        device_aliases: HashMap::new(),
+        echo_cancellation_enabled: false,
+        caption_hotwords: Vec::new(),
+        external_command_enabled: false,
+        external_command: String::new(),
+        external_command_args: Vec::new(),
+        external_command_timeout_ms: default_external_command_timeout_ms(),
+        auto_caption_system_audio: true,
+        dual_channel_captions: false,
+        speaker_diarization_enabled: false,
+        text_casing: TextCasing::Original,
+        binding_text_casing: HashMap::new(),
+        caption_translation_language: None,
+        transcription_backend: BackendKind::Local,
+        compute_backend: ComputeBackend::Auto,
+        power_saving_enabled: false,
+        power_saving_battery_percent: default_power_saving_battery_percent(),
+        cloud_api_endpoint: default_cloud_api_endpoint(),
+        cloud_api_key: String::new(),
+        cloud_model: default_cloud_model(),
+        cloud_fallback_to_local: default_cloud_fallback_to_local(),
+        long_recording_segment_secs: default_long_recording_segment_secs(),
+        transcription_params: TranscriptionParams::default(),
+        hallucination_filter_enabled: default_hallucination_filter_enabled(),
+        custom_word_aliases: HashMap::new(),
+        spoken_formatting_enabled: false,
+        binding_spoken_formatting: HashMap::new(),
+        spoken_commands: default_spoken_commands(),
+        punctuation_model_path: None,
+        punctuation_languages: default_punctuation_languages(),
+        number_formatting: NumberFormatting::default(),
+        profanity_filter: ProfanityFilter::Keep,
+        profanity_words: Vec::new(),
+        paste_blacklist: default_paste_blacklist(),
+        output_mode: OutputMode::Paste,
+        binding_output_mode: HashMap::new(),
+        caption_paste_enabled: false,
+        caption_style: CaptionStyle::default(),
+        history_retention: HistoryRetention::default(),
+        pre_roll_ms: default_pre_roll_ms(),
+        max_recording_secs: default_max_recording_secs(),
+        microphone_downmix: ChannelDownmix::default(),
+        system_audio_downmix: ChannelDownmix::default(),
+        input_gains: HashMap::new(),
+        wasapi: WasapiOptions::default(),
+        linux_audio_host: LinuxAudioHost::default(),
     }
 }
 
@@ -445,6 +1280,52 @@ impl AppSettings {
             .iter_mut()
             .find(|provider| provider.id == provider_id)
     }
+
+    /// Gain for the input device saved as `device`, unity when none is set
+    pub fn input_gain(&self, device: Option<&str>) -> Gain {
+        self.input_gains
+            .get(device.unwrap_or("default"))
+            .map_or_else(Gain::default, |gain| gain.gain())
+    }
+
+    /// Whether the caption loop should run for the active audio source
+    pub fn auto_caption_enabled(&self) -> bool {
+        match self.audio_source.unwrap_or(AudioSource::Microphone) {
+            AudioSource::SystemAudio => self.auto_caption_system_audio,
+            AudioSource::Microphone => self.always_on_microphone,
+        }
+    }
+
+    /// Casing applied to transcripts produced through `binding_id`
+    pub fn text_casing_for(&self, binding_id: &str) -> TextCasing {
+        self.binding_text_casing
+            .get(binding_id)
+            .copied()
+            .unwrap_or(self.text_casing)
+    }
+
+    /// Whether spoken commands are formatted in transcripts from `binding_id`
+    pub fn spoken_formatting_for(&self, binding_id: &str) -> bool {
+        self.binding_spoken_formatting
+            .get(binding_id)
+            .copied()
+            .unwrap_or(self.spoken_formatting_enabled)
+    }
+
+    /// Where transcripts produced through `binding_id` are sent
+    pub fn output_mode_for(&self, binding_id: &str) -> OutputMode {
+        self.binding_output_mode
+            .get(binding_id)
+            .copied()
+            .unwrap_or(self.output_mode)
+    }
+
+    /// Whether the mic is being captioned alongside system audio right now
+    pub fn dual_channel_active(&self) -> bool {
+        self.dual_channel_captions
+            && self.auto_caption_system_audio
+            && self.audio_source == Some(AudioSource::SystemAudio)
+    }
 }
 
 pub fn load_or_create_app_settings(app: &AppHandle) -> AppSettings {
@@ -454,10 +1335,23 @@ pub fn load_or_create_app_settings(app: &AppHandle) -> AppSettings {
         .expect("Failed to initialize store");
 
     let mut settings = if let Some(settings_value) = store.get("settings") {
+        // Older versions started system-audio captions from always_on_microphone
+        let inherit_auto_caption = settings_value.get("auto_caption_system_audio").is_none();
+        // and limited history with a retention period and an entry count
+        let legacy_retention = HistoryRetention::migrated_from(&settings_value);
+
         // Parse the entire settings object
         match serde_json::from_value::<AppSettings>(settings_value) {
-            Ok(settings) => {
+            Ok(mut settings) => {
                 debug!("Found existing settings: {:?}", settings);
+                if inherit_auto_caption {
+                    settings.auto_caption_system_audio = settings.always_on_microphone;
+                    store.set("settings", serde_json::to_value(&settings).unwrap());
+                }
+                if let Some(retention) = legacy_retention {
+                    settings.history_retention = retention;
+                    store.set("settings", serde_json::to_value(&settings).unwrap());
+                }
                 settings
             }
             Err(e) => {
@@ -480,6 +1374,18 @@ pub fn load_or_create_app_settings(app: &AppHandle) -> AppSettings {
         store.set("settings", serde_json::to_value(&settings).unwrap());
     }
 
+    // Add bindings introduced after these settings were first saved
+    let mut added_binding = false;
+    for (id, binding) in get_default_settings().bindings {
+        if !settings.bindings.contains_key(&id) {
+            settings.bindings.insert(id, binding);
+            added_binding = true;
+        }
+    }
+    if added_binding {
+        store.set("settings", serde_json::to_value(&settings).unwrap());
+    }
+
     settings
 }
 
@@ -531,12 +1437,33 @@ pub fn get_stored_binding(app: &AppHandle, id: &str) -> ShortcutBinding {
     binding
 }
 
-pub fn get_history_limit(app: &AppHandle) -> usize {
-    let settings = get_settings(app);
-    settings.history_limit
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
 
-pub fn get_recording_retention_period(app: &AppHandle) -> RecordingRetentionPeriod {
-    let settings = get_settings(app);
-    settings.recording_retention_period
+    #[test]
+    fn test_history_retention_migration() {
+        let days = HistoryRetention::migrated_from(&json!({
+            "history_limit": 20,
+            "recording_retention_period": "weeks2",
+        }))
+        .unwrap();
+        assert_eq!((days.max_age_days, days.max_entries), (14, 0));
+        let count = HistoryRetention::migrated_from(&json!({
+            "history_limit": 20,
+            "recording_retention_period": "preserve_limit",
+        }))
+        .unwrap();
+        assert_eq!((count.max_age_days, count.max_entries), (0, 20));
+
+        // Once both are stored, the new limits are the only ones applied
+        let mut both = serde_json::to_value(get_default_settings()).unwrap();
+        both["history_limit"] = json!(20);
+        both["recording_retention_period"] = json!("days3");
+        both["history_retention"]["max_age_days"] = json!(30);
+        assert_eq!(HistoryRetention::migrated_from(&both), None);
+        let settings: AppSettings = serde_json::from_value(both).unwrap();
+        assert_eq!(settings.history_retention.max_age_days, 30);
+    }
 }
@@ -1,13 +1,18 @@
 use log::{error, warn};
 use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_autostart::ManagerExt;
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 
 use crate::actions::ACTION_MAP;
+use crate::caption::server::CaptionServer;
 use crate::settings::ShortcutBinding;
 use crate::settings::{
-    self, get_settings, ClipboardHandling, LLMPrompt, OverlayPosition, PasteMethod, SoundTheme,
+    self, get_settings, CaptionStrategy, ClipboardHandling, LLMPrompt, LinuxTypingBackend,
+    MuteMode, NumberFormatting, OutputMode, OverlayPosition, PasteMethod, ProfanityFilter,
+    SoundTheme, SpokenCommand, TextCasing,
 };
 use crate::ManagedToggleState;
 
@@ -255,11 +260,335 @@ pub fn change_autostart_setting(app: AppHandle, enabled: bool) -> Result<(), Str
 #[tauri::command]
 pub fn update_custom_words(app: AppHandle, words: Vec<String>) -> Result<(), String> {
     let mut settings = settings::get_settings(&app);
+    settings
+        .custom_word_aliases
+        .retain(|word, _| words.contains(word));
     settings.custom_words = words;
     settings::write_settings(&app, settings);
     Ok(())
 }
 
+#[tauri::command]
+pub fn update_custom_word_aliases(
+    app: AppHandle,
+    aliases: HashMap<String, Vec<String>>,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.custom_word_aliases = aliases
+        .into_iter()
+        .filter(|(word, _)| settings.custom_words.contains(word))
+        .map(|(word, list)| {
+            let list = list
+                .into_iter()
+                .map(|alias| alias.trim().to_string())
+                .filter(|alias| !alias.is_empty())
+                .collect();
+            (word, list)
+        })
+        .collect();
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_keyword_boosts(app: AppHandle, keywords: Vec<String>) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.keyword_boosts = keywords;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_keyword_boost_strength_setting(app: AppHandle, strength: f32) -> Result<(), String> {
+    if !(0.0..=10.0).contains(&strength) {
+        return Err("Keyword boost strength must be between 0 and 10".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.keyword_boost_strength = strength;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_retake_window_setting(app: AppHandle, window_ms: u64) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.retake_window_ms = window_ms;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_dictation_stitch_window_setting(app: AppHandle, seconds: u64) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.dictation_stitch_window_secs = seconds;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_transcript_file_enabled_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.transcript_file_enabled = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_transcript_file_path_setting(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.transcript_file_path = path;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_transcript_file_timestamps_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.transcript_file_timestamps = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_transcript_file_rotation_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.transcript_file_daily_rotation = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_cloud_quota_setting(app: AppHandle, minutes: u32) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.cloud_daily_quota_minutes = minutes;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_cloud_cost_per_minute_setting(app: AppHandle, cost: f64) -> Result<(), String> {
+    if !cost.is_finite() || cost < 0.0 {
+        return Err("Cost per minute must be a non-negative number".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.cloud_cost_per_minute = cost;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_cloud_api_endpoint_setting(app: AppHandle, endpoint: String) -> Result<(), String> {
+    let endpoint = endpoint.trim();
+    if !endpoint.starts_with("https://") && !endpoint.starts_with("http://") {
+        return Err("Endpoint must be an http(s) URL".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.cloud_api_endpoint = endpoint.trim_end_matches('/').to_string();
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_cloud_api_key_setting(app: AppHandle, api_key: String) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.cloud_api_key = api_key.trim().to_string();
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_cloud_model_setting(app: AppHandle, model: String) -> Result<(), String> {
+    let model = model.trim();
+    if model.is_empty() {
+        return Err("Model name cannot be empty".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.cloud_model = model.to_string();
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_cloud_fallback_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.cloud_fallback_to_local = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_long_recording_segment_setting(app: AppHandle, seconds: u64) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.long_recording_segment_secs = seconds;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_pre_roll_setting(app: AppHandle, pre_roll_ms: u64) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.pre_roll_ms = pre_roll_ms;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_max_recording_setting(app: AppHandle, seconds: u64) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.max_recording_secs = seconds;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_transcription_params(
+    app: AppHandle,
+    params: settings::TranscriptionParams,
+) -> Result<(), String> {
+    params.validate()?;
+    let mut settings = settings::get_settings(&app);
+    settings.transcription_params = params;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_hallucination_filter_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.hallucination_filter_enabled = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_caption_server_enabled_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    app.state::<Arc<CaptionServer>>()
+        .apply_settings(enabled, settings.caption_server_port)?;
+    settings.caption_server_enabled = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_caption_server_port_setting(app: AppHandle, port: u16) -> Result<(), String> {
+    if port == 0 {
+        return Err("Caption server port must be between 1 and 65535".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+    app.state::<Arc<CaptionServer>>()
+        .apply_settings(settings.caption_server_enabled, port)?;
+    settings.caption_server_port = port;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_language_cycle(app: AppHandle, languages: Vec<String>) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.language_cycle = languages
+        .into_iter()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect();
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_caption_finalization_delay_setting(
+    app: AppHandle,
+    delay_ms: u64,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.caption_finalization_delay_ms = delay_ms;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_denoise_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.denoise_enabled = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_echo_cancellation_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.echo_cancellation_enabled = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_caption_hotwords(app: AppHandle, hotwords: Vec<String>) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.caption_hotwords = hotwords
+        .into_iter()
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .collect();
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_external_command_enabled_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.external_command_enabled = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_external_command_setting(
+    app: AppHandle,
+    command: String,
+    args: Vec<String>,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.external_command = command.trim().to_string();
+    settings.external_command_args = args;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_external_command_timeout_setting(
+    app: AppHandle,
+    timeout_ms: u64,
+) -> Result<(), String> {
+    if timeout_ms == 0 {
+        return Err("Command timeout must be greater than zero".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.external_command_timeout_ms = timeout_ms;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_speaker_diarization_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.speaker_diarization_enabled = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
 #[tauri::command]
 pub fn change_word_correction_threshold_setting(
     app: AppHandle,
@@ -279,6 +608,8 @@ pub fn change_paste_method_setting(app: AppHandle, method: String) -> Result<(),
         "direct" => PasteMethod::Direct,
         #[cfg(not(target_os = "macos"))]
         "shift_insert" => PasteMethod::ShiftInsert,
+        #[cfg(target_os = "macos")]
+        "accessibility" => PasteMethod::Accessibility,
         other => {
             warn!("Invalid paste method '{}', defaulting to ctrl_v", other);
             PasteMethod::CtrlV
@@ -289,6 +620,28 @@ pub fn change_paste_method_setting(app: AppHandle, method: String) -> Result<(),
     Ok(())
 }
 
+#[tauri::command]
+pub fn change_linux_typing_backend_setting(app: AppHandle, backend: String) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    let parsed = match backend.as_str() {
+        "auto" => LinuxTypingBackend::Auto,
+        "wtype" => LinuxTypingBackend::Wtype,
+        "ydotool" => LinuxTypingBackend::Ydotool,
+        "xdotool" => LinuxTypingBackend::Xdotool,
+        "clipboard_only" => LinuxTypingBackend::ClipboardOnly,
+        other => {
+            warn!(
+                "Invalid Linux typing backend '{}', defaulting to auto",
+                other
+            );
+            LinuxTypingBackend::Auto
+        }
+    };
+    settings.linux_typing_backend = parsed;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
 #[tauri::command]
 pub fn change_clipboard_handling_setting(app: AppHandle, handling: String) -> Result<(), String> {
     let mut settings = settings::get_settings(&app);
@@ -629,6 +982,317 @@ pub fn change_mute_while_recording_setting(app: AppHandle, enabled: bool) -> Res
     Ok(())
 }
 
+#[tauri::command]
+pub fn change_mute_mode_setting(app: AppHandle, mode: MuteMode) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.mute_mode = mode;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_duck_percent_setting(app: AppHandle, percent: u8) -> Result<(), String> {
+    if percent > 100 {
+        return Err("Ducking must be between 0 and 100 percent".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.duck_percent = percent;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_silence_trim_padding_setting(app: AppHandle, padding_ms: u64) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.silence_trim_padding_ms = padding_ms;
+    settings::write_settings(&app, settings);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_caption_alignment_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.caption_alignment_enabled = enabled;
+    settings::write_settings(&app, settings);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_caption_overlap_setting(app: AppHandle, overlap_ms: u64) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.caption_overlap_ms = overlap_ms;
+    settings::write_settings(&app, settings);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_caption_context_setting(app: AppHandle, words: usize) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.caption_context_words = words;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_caption_paste_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.caption_paste_enabled = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Save the caption overlay style and let open overlays restyle right away
+#[tauri::command]
+pub fn set_caption_style(app: AppHandle, style: settings::CaptionStyle) -> Result<(), String> {
+    style.validate()?;
+    let mut settings = settings::get_settings(&app);
+    settings.caption_style = style.clone();
+    settings::write_settings(&app, settings);
+
+    let _ = app.emit("caption-style-changed", style);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_caption_strategy_setting(app: AppHandle, strategy: String) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    let parsed = match strategy.as_str() {
+        "interval" => CaptionStrategy::Interval,
+        "vad" => CaptionStrategy::Vad,
+        other => {
+            warn!(
+                "Invalid caption strategy '{}', defaulting to interval",
+                other
+            );
+            CaptionStrategy::Interval
+        }
+    };
+    settings.caption_strategy = parsed;
+    settings::write_settings(&app, settings);
+
+    Ok(())
+}
+
+/// Translate captions into `language`, or stop translating with `None`
+#[tauri::command]
+pub fn change_caption_translation_setting(
+    app: AppHandle,
+    language: Option<String>,
+) -> Result<(), String> {
+    // Whisper's translate task can only produce English
+    if let Some(language) = language.as_deref().filter(|l| *l != "en") {
+        return Err(format!(
+            "Captions can't be translated into '{}'; only English is supported",
+            language
+        ));
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.caption_translation_language = language;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+fn parse_text_casing(casing: &str) -> Result<TextCasing, String> {
+    match casing {
+        "original" => Ok(TextCasing::Original),
+        "lowercase" => Ok(TextCasing::Lowercase),
+        "sentence" => Ok(TextCasing::Sentence),
+        "uppercase" => Ok(TextCasing::Uppercase),
+        other => Err(format!("Invalid text casing '{}'", other)),
+    }
+}
+
+#[tauri::command]
+pub fn change_text_casing_setting(app: AppHandle, casing: String) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.text_casing = parse_text_casing(&casing)?;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Override the casing for one binding; `None` falls back to the global casing
+#[tauri::command]
+pub fn change_binding_text_casing_setting(
+    app: AppHandle,
+    binding_id: String,
+    casing: Option<String>,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    if !settings.bindings.contains_key(&binding_id) {
+        return Err(format!("Unknown binding '{}'", binding_id));
+    }
+    match casing {
+        Some(casing) => {
+            let casing = parse_text_casing(&casing)?;
+            settings.binding_text_casing.insert(binding_id, casing);
+        }
+        None => {
+            settings.binding_text_casing.remove(&binding_id);
+        }
+    }
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_spoken_formatting_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.spoken_formatting_enabled = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Override spoken formatting for one binding; `None` follows the global toggle
+#[tauri::command]
+pub fn change_binding_spoken_formatting_setting(
+    app: AppHandle,
+    binding_id: String,
+    enabled: Option<bool>,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    if !settings.bindings.contains_key(&binding_id) {
+        return Err(format!("Unknown binding '{}'", binding_id));
+    }
+    match enabled {
+        Some(enabled) => {
+            settings
+                .binding_spoken_formatting
+                .insert(binding_id, enabled);
+        }
+        None => {
+            settings.binding_spoken_formatting.remove(&binding_id);
+        }
+    }
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Replace the spoken command map; phrases are stored lowercase
+#[tauri::command]
+pub fn set_spoken_commands(
+    app: AppHandle,
+    commands: HashMap<String, SpokenCommand>,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.spoken_commands = commands
+        .into_iter()
+        .map(|(phrase, command)| (phrase.trim().to_lowercase(), command))
+        .filter(|(phrase, _)| !phrase.is_empty())
+        .collect();
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Point the punctuation pass at a model directory; `None` turns it off
+#[tauri::command]
+pub fn change_punctuation_model_setting(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<(), String> {
+    use crate::audio_toolkit::punctuation::{CONFIG_FILE, MODEL_FILE};
+
+    if let Some(path) = &path {
+        let dir = std::path::Path::new(path);
+        for file in [MODEL_FILE, CONFIG_FILE] {
+            if !dir.join(file).is_file() {
+                return Err(format!("{} has no {}", path, file));
+            }
+        }
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.punctuation_model_path = path;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_punctuation_languages_setting(
+    app: AppHandle,
+    languages: Vec<String>,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.punctuation_languages = languages;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_number_formatting(app: AppHandle, formatting: NumberFormatting) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.number_formatting = formatting;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_profanity_filter_setting(
+    app: AppHandle,
+    filter: ProfanityFilter,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.profanity_filter = filter;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_profanity_words(app: AppHandle, words: Vec<String>) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.profanity_words = words
+        .into_iter()
+        .map(|word| word.trim().to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect();
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn change_output_mode_setting(app: AppHandle, mode: OutputMode) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.output_mode = mode;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Override the output mode for one binding; `None` follows the global mode
+#[tauri::command]
+pub fn change_binding_output_mode_setting(
+    app: AppHandle,
+    binding_id: String,
+    mode: Option<OutputMode>,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    if !settings.bindings.contains_key(&binding_id) {
+        return Err(format!("Unknown binding '{}'", binding_id));
+    }
+    match mode {
+        Some(mode) => {
+            settings.binding_output_mode.insert(binding_id, mode);
+        }
+        None => {
+            settings.binding_output_mode.remove(&binding_id);
+        }
+    }
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_paste_blacklist(app: AppHandle, apps: Vec<String>) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.paste_blacklist = apps
+        .into_iter()
+        .map(|app| app.trim().to_lowercase())
+        .filter(|app| !app.is_empty())
+        .collect();
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
 /// Determine whether a shortcut string contains at least one non-modifier key.
 /// We allow single non-modifier keys (e.g. "f5" or "space") but disallow
 /// modifier-only combos (e.g. "ctrl" or "ctrl+shift").
@@ -712,7 +1376,11 @@ fn _register_shortcut(app: &AppHandle, binding: ShortcutBinding) -> Result<(), S
                 let settings = get_settings(ah);
 
                 if let Some(action) = ACTION_MAP.get(&binding_id_for_closure) {
-                    if settings.push_to_talk {
+                    if action.is_instant() {
+                        if event.state == ShortcutState::Pressed {
+                            action.start(ah, &binding_id_for_closure, &shortcut_string);
+                        }
+                    } else if settings.push_to_talk {
                         if event.state == ShortcutState::Pressed {
                             action.start(ah, &binding_id_for_closure, &shortcut_string);
                         } else if event.state == ShortcutState::Released {
@@ -1,9 +1,15 @@
 use crate::actions::ACTION_MAP;
 use crate::managers::audio::AudioRecordingManager;
 use crate::ManagedToggleState;
-use log::{info, warn};
-use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use log::{error, info, warn};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, Once};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
 
 // Re-export all utility modules for easy access
 // pub use crate::audio_feedback::*;
@@ -54,3 +60,369 @@ pub fn cancel_current_operation(app: &AppHandle) {
 
     info!("Operation cancellation completed - returned to idle state");
 }
+
+/// Stop a binding's action as if its shortcut had ended it, e.g. when a
+/// recording hits its duration limit
+pub fn stop_binding(app: &AppHandle, binding_id: &str) {
+    if let Some(action) = ACTION_MAP.get(binding_id) {
+        action.stop(app, binding_id, "limit");
+    }
+
+    // In toggle mode the next press has to start a new recording
+    let toggle_state_manager = app.state::<ManagedToggleState>();
+    if let Ok(mut states) = toggle_state_manager.lock() {
+        states.active_toggles.insert(binding_id.to_string(), false);
+    } else {
+        warn!("Failed to lock toggle state manager while stopping {binding_id}");
+    }
+}
+
+/// Discard the transcription waiting in its retake window and start recording
+/// again on the same binding. Returns false if there was nothing to retake.
+pub fn retake_last_recording(app: &AppHandle) -> bool {
+    let binding_id = match crate::actions::take_pending_output() {
+        Some(binding_id) => binding_id,
+        None => return false,
+    };
+
+    info!("Retaking recording for binding: {}", binding_id);
+    let _ = app.emit("transcription-discarded", &binding_id);
+
+    if let Some(action) = ACTION_MAP.get(&binding_id) {
+        action.start(app, &binding_id, "retake");
+    }
+
+    // In toggle mode the next press has to stop the new recording
+    if !crate::settings::get_settings(app).push_to_talk {
+        let toggle_state_manager = app.state::<ManagedToggleState>();
+        if let Ok(mut states) = toggle_state_manager.lock() {
+            states.active_toggles.insert(binding_id, true);
+        } else {
+            warn!("Failed to lock toggle state manager during retake");
+        }
+    }
+
+    true
+}
+
+/// What `emit_throttled` does with payloads that arrive within an event's
+/// interval
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Coalesce {
+    /// Only the newest one is sent
+    Latest,
+    /// All of them are sent together, as an array
+    Batch,
+}
+
+/// Minimum time between two sends of `event`, and how the payloads queued in
+/// between are combined
+fn event_limit(event: &str) -> (Duration, Coalesce) {
+    match event {
+        // Roughly the level meter's frame rate
+        "mic-level" => (Duration::from_millis(33), Coalesce::Latest),
+        "audio-spectrum" => (Duration::from_millis(33), Coalesce::Latest),
+        "live-caption-provisional" => (Duration::from_millis(100), Coalesce::Latest),
+        "log-entry" => (Duration::from_millis(250), Coalesce::Batch),
+        _ => (Duration::from_millis(50), Coalesce::Latest),
+    }
+}
+
+/// Target window label, `None` for every window, and event name
+type EventKey = (Option<String>, &'static str);
+
+#[derive(Default)]
+struct EventSlot {
+    last_sent: Option<Instant>,
+    pending: Vec<Value>,
+}
+
+/// Payloads waiting for their event's interval to pass
+#[derive(Default)]
+struct EmitQueue {
+    slots: HashMap<EventKey, EventSlot>,
+}
+
+impl EmitQueue {
+    fn push(&mut self, key: EventKey, payload: Value) {
+        let (_, coalesce) = event_limit(key.1);
+        let slot = self.slots.entry(key).or_default();
+        if coalesce == Coalesce::Latest {
+            slot.pending.clear();
+        }
+        slot.pending.push(payload);
+    }
+
+    /// Take the events that are due at `now` with the payload to send for
+    /// each, and when the next of the rest falls due
+    fn take_due(&mut self, now: Instant) -> (Vec<(EventKey, Value)>, Option<Instant>) {
+        let mut due = Vec::new();
+        let mut next: Option<Instant> = None;
+        for (key, slot) in self.slots.iter_mut() {
+            if slot.pending.is_empty() {
+                continue;
+            }
+            let (interval, coalesce) = event_limit(key.1);
+            let due_at = slot.last_sent.map_or(now, |sent| sent + interval);
+            if due_at > now {
+                next = Some(next.map_or(due_at, |next| next.min(due_at)));
+                continue;
+            }
+
+            let mut pending = std::mem::take(&mut slot.pending);
+            let payload = match coalesce {
+                Coalesce::Latest => pending.pop().unwrap(),
+                Coalesce::Batch => Value::Array(pending),
+            };
+            slot.last_sent = Some(now);
+            due.push((key.clone(), payload));
+        }
+        (due, next)
+    }
+}
+
+static EMIT_QUEUE: Lazy<(Mutex<EmitQueue>, Condvar)> = Lazy::new(Default::default);
+static EMIT_THREAD: Once = Once::new();
+
+/// Emit `event` to every window at most as often as `event_limit` allows,
+/// for events sent from audio threads and other hot paths. Never blocks on
+/// the frontend: a background thread does the emitting.
+pub fn emit_throttled<S: Serialize>(app: &AppHandle, event: &'static str, payload: S) {
+    queue_throttled(app, None, event, payload);
+}
+
+/// `emit_throttled` through the window labelled `window`, skipped if that
+/// window isn't open when the event is sent
+pub fn emit_throttled_to<S: Serialize>(
+    app: &AppHandle,
+    window: &str,
+    event: &'static str,
+    payload: S,
+) {
+    queue_throttled(app, Some(window.to_string()), event, payload);
+}
+
+fn queue_throttled<S: Serialize>(
+    app: &AppHandle,
+    window: Option<String>,
+    event: &'static str,
+    payload: S,
+) {
+    let payload = match serde_json::to_value(payload) {
+        Ok(payload) => payload,
+        Err(e) => {
+            error!("Failed to serialize {} event: {}", event, e);
+            return;
+        }
+    };
+    EMIT_THREAD.call_once(|| {
+        let app = app.clone();
+        thread::spawn(move || send_throttled(&app));
+    });
+
+    let (queue, wake) = &*EMIT_QUEUE;
+    queue.lock().unwrap().push((window, event), payload);
+    wake.notify_one();
+}
+
+/// Send queued events as they fall due. Emitting happens with the queue
+/// unlocked, since emitting can log and logging queues `log-entry`.
+fn send_throttled(app: &AppHandle) {
+    let (queue, wake) = &*EMIT_QUEUE;
+    let mut guard = queue.lock().unwrap();
+    loop {
+        let (due, next) = guard.take_due(Instant::now());
+        if due.is_empty() {
+            guard = match next {
+                Some(at) => {
+                    let timeout = at.saturating_duration_since(Instant::now());
+                    wake.wait_timeout(guard, timeout).unwrap().0
+                }
+                None => wake.wait(guard).unwrap(),
+            };
+            continue;
+        }
+
+        drop(guard);
+        for ((window, event), payload) in due {
+            match window {
+                Some(label) => {
+                    if let Some(window) = app.get_webview_window(&label) {
+                        let _ = window.emit(event, payload);
+                    }
+                }
+                None => {
+                    let _ = app.emit(event, payload);
+                }
+            }
+        }
+        guard = queue.lock().unwrap();
+    }
+}
+
+/// The application that has keyboard focus
+#[derive(Debug, Clone)]
+pub struct FrontmostApp {
+    /// Display name, e.g. "1Password"
+    pub name: String,
+    /// Bundle id on macOS, executable file name elsewhere
+    pub identifier: String,
+}
+
+impl FrontmostApp {
+    /// Whether any blacklist entry appears in the app's name or identifier
+    pub fn matches_any(&self, blacklist: &[String]) -> bool {
+        let name = self.name.to_lowercase();
+        let identifier = self.identifier.to_lowercase();
+        blacklist
+            .iter()
+            .map(|entry| entry.trim().to_lowercase())
+            .filter(|entry| !entry.is_empty())
+            .any(|entry| name.contains(&entry) || identifier.contains(&entry))
+    }
+}
+
+/// Look up the focused application. Returns `None` where the platform gives
+/// no way to ask, e.g. on Wayland.
+#[cfg(target_os = "macos")]
+pub fn frontmost_app() -> Option<FrontmostApp> {
+    use objc::runtime::Object;
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+
+    unsafe fn to_string(string: *mut Object) -> Option<String> {
+        if string.is_null() {
+            return None;
+        }
+        let utf8: *const c_char = msg_send![string, UTF8String];
+        if utf8.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(utf8).to_string_lossy().into_owned())
+    }
+
+    unsafe {
+        let workspace: *mut Object = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let app: *mut Object = msg_send![workspace, frontmostApplication];
+        if app.is_null() {
+            return None;
+        }
+        let name: *mut Object = msg_send![app, localizedName];
+        let bundle_id: *mut Object = msg_send![app, bundleIdentifier];
+        let name = to_string(name).unwrap_or_default();
+        let identifier = to_string(bundle_id).unwrap_or_default();
+        Some(FrontmostApp { name, identifier })
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn frontmost_app() -> Option<FrontmostApp> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_invalid() {
+            return None;
+        }
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(window, Some(&mut pid));
+        if pid == 0 {
+            return None;
+        }
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buffer = [0u16; 1024];
+        let mut len = buffer.len() as u32;
+        let queried = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut len,
+        );
+        let _ = CloseHandle(process);
+        queried.ok()?;
+
+        let path = std::path::PathBuf::from(String::from_utf16_lossy(&buffer[..len as usize]));
+        let identifier = path.file_name()?.to_string_lossy().into_owned();
+        let name = path.file_stem()?.to_string_lossy().into_owned();
+        Some(FrontmostApp { name, identifier })
+    }
+}
+
+/// X11 only: asks xdotool for the focused window's process
+#[cfg(target_os = "linux")]
+pub fn frontmost_app() -> Option<FrontmostApp> {
+    use crate::clipboard::linux::{command_exists, detect_session, SessionType};
+
+    if detect_session() == SessionType::Wayland || !command_exists("xdotool") {
+        return None;
+    }
+
+    let output = std::process::Command::new("xdotool")
+        .args(["getactivewindow", "getwindowpid"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let pid: u32 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+
+    let name = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    let name = name.trim().to_string();
+    let identifier = std::fs::read_link(format!("/proc/{}/exe", pid))
+        .ok()
+        .and_then(|exe| exe.file_name().map(|f| f.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| name.clone());
+    Some(FrontmostApp { name, identifier })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn frontmost_app() -> Option<FrontmostApp> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_emit_queue_coalesces() {
+        let start = Instant::now();
+        let mut queue = EmitQueue::default();
+        let level = (None, "mic-level");
+        let log = (None, "log-entry");
+
+        queue.push(level.clone(), json!([0.1]));
+        queue.push(log.clone(), json!(1));
+        let (due, next) = queue.take_due(start);
+        assert_eq!(due.len(), 2);
+        assert_eq!(next, None);
+
+        // Within the interval only the newest level survives, while every
+        // log entry is kept for the batch
+        queue.push(level.clone(), json!([0.2]));
+        queue.push(level.clone(), json!([0.3]));
+        queue.push(log.clone(), json!(2));
+        queue.push(log.clone(), json!(3));
+        let (due, next) = queue.take_due(start + Duration::from_millis(10));
+        assert!(due.is_empty());
+        assert_eq!(next, Some(start + Duration::from_millis(33)));
+
+        let (due, _) = queue.take_due(start + Duration::from_millis(33));
+        assert_eq!(due, vec![(level, json!([0.3]))]);
+        let (due, next) = queue.take_due(start + Duration::from_millis(250));
+        assert_eq!(due, vec![(log, json!([2, 3]))]);
+        assert_eq!(next, None);
+    }
+}
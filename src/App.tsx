@@ -9,6 +9,12 @@ import { Sidebar, SidebarSection, SECTIONS_CONFIG } from "./components/Sidebar";
 import { useSettings } from "./hooks/useSettings";
 import LiveCaption from "./components/LiveCaption";
 import SystemAudioSetup from "./components/SystemAudioSetup";
+import RecordingRecovery from "./components/RecordingRecovery";
+import PasteBlockedNotice from "./components/PasteBlockedNotice";
+import RecordingLimitNotice from "./components/RecordingLimitNotice";
+import MicrophoneFailoverNotice from "./components/MicrophoneFailoverNotice";
+import AudioGlitchNotice from "./components/AudioGlitchNotice";
+import AudioClippingNotice from "./components/AudioClippingNotice";
 
 const renderSettingsContent = (section: SidebarSection) => {
   const ActiveComponent =
@@ -118,6 +124,12 @@ function App() {
     <>
       <Toaster />
       <SystemAudioSetup />
+      <RecordingRecovery />
+      <PasteBlockedNotice />
+      <RecordingLimitNotice />
+      <MicrophoneFailoverNotice />
+      <AudioGlitchNotice />
+      <AudioClippingNotice />
       {showOnboarding ? (
         <Onboarding onModelSelected={handleModelSelected} />
       ) : (
@@ -141,7 +153,7 @@ function App() {
           {/* Fixed footer at bottom */}
           <Footer />
           {/* Live Caption - Google Translate style */}
-          <LiveCaption enabled={(settings?.live_caption_enabled ?? true) && settings?.always_on_microphone && settings?.audio_source === "system_audio"} />
+          <LiveCaption enabled={(settings?.live_caption_enabled ?? true) && (settings?.auto_caption_system_audio ?? true) && settings?.audio_source === "system_audio"} style={settings?.caption_style} />
         </div>
       )}
     </>
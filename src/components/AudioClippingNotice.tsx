@@ -0,0 +1,24 @@
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import type { AudioClipping } from "../lib/types";
+
+// Tells the user to turn the microphone down when its input keeps clipping,
+// which distorts the audio and hurts transcription
+export default function AudioClippingNotice() {
+  useEffect(() => {
+    const unlisten = listen<AudioClipping>("audio-clipping", () => {
+      toast.warning("Your microphone is too loud", {
+        description:
+          "The input keeps hitting full scale and distorting. Turn the microphone's input level down in your sound settings, or lower its gain.",
+        duration: 10000,
+      });
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  return null;
+}
@@ -0,0 +1,29 @@
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import type { AudioGlitchDetected } from "../lib/types";
+
+// Warns once when the microphone or system audio keeps dropping out, with
+// what to try about it
+export default function AudioGlitchNotice() {
+  useEffect(() => {
+    const unlisten = listen<AudioGlitchDetected>(
+      "audio-glitch-detected",
+      (event) => {
+        const { stream, remedies } = event.payload;
+        const source =
+          stream === "microphone" ? "The microphone" : "System audio";
+        toast.warning(`${source} is dropping out`, {
+          description: `Parts of the audio were lost, which can garble transcriptions. Try: ${remedies.join("; ")}.`,
+          duration: 15000,
+        });
+      },
+    );
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  return null;
+}
@@ -1,16 +1,50 @@
-import { useEffect, useState } from "react";
+import React, { useEffect, useState } from "react";
 import { listen } from "@tauri-apps/api/event";
+import {
+  DEFAULT_CAPTION_STYLE,
+  type CaptionStyle,
+  type LiveCaptionUpdate,
+} from "../lib/types";
 import "./LiveCaption.css";
 
 interface LiveCaptionProps {
   enabled?: boolean;
+  style?: CaptionStyle;
 }
 
-export default function LiveCaption({ enabled = true }: LiveCaptionProps) {
-  const [caption, setCaption] = useState<string>("");
+interface CaptionLine {
+  text: string;
+  translation: string | null;
+}
+
+export default function LiveCaption({
+  enabled = true,
+  style: initialStyle = DEFAULT_CAPTION_STYLE,
+}: LiveCaptionProps) {
+  const [lines, setLines] = useState<CaptionLine[]>([]);
+  const [style, setStyle] = useState<CaptionStyle>(initialStyle);
   const [isVisible, setIsVisible] = useState(false);
   const [isListening, setIsListening] = useState(false);
 
+  useEffect(() => {
+    setStyle(initialStyle);
+  }, [initialStyle]);
+
+  // Restyle as soon as the settings change, without waiting for a reload
+  useEffect(() => {
+    const unlisten = listen<CaptionStyle>("caption-style-changed", (event) => {
+      setStyle(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Drop lines beyond the limit when it shrinks
+  useEffect(() => {
+    setLines((lines) => lines.slice(-style.max_lines));
+  }, [style.max_lines]);
+
   useEffect(() => {
     if (!enabled) {
       setIsVisible(false);
@@ -22,28 +56,38 @@ export default function LiveCaption({ enabled = true }: LiveCaptionProps) {
     setIsListening(true); // Show "Đang nghe..." when enabled
     let timeoutId: NodeJS.Timeout;
     let eventCount = 0;
+    const maxLines = style.max_lines;
+    const fadeTimeoutMs = style.fade_timeout_ms;
 
-    const unlisten = listen<string>("live-caption-update", (event) => {
+    const unlisten = listen<LiveCaptionUpdate>("live-caption-update", (event) => {
       eventCount++;
-      const newCaption = event.payload.trim();
+      const newCaption = event.payload.text.trim();
       const logMsg = `🎯 [LiveCaption] Event #${eventCount} received, payload length: ${newCaption.length}, preview: "${newCaption.substring(0, 50)}${newCaption.length > 50 ? '...' : ''}"`;
       console.log(logMsg);
       
       if (newCaption && newCaption.length > 1) {
         const successMsg = `✅ [LiveCaption] Setting caption (${newCaption.length} chars): "${newCaption}"`;
         console.log(successMsg);
-        setCaption(newCaption);
+        setLines((lines) =>
+          [
+            ...lines,
+            { text: newCaption, translation: event.payload.translation },
+          ].slice(-maxLines),
+        );
         setIsVisible(true);
         setIsListening(false);
         
-        // Auto-hide after 5 seconds of no new caption
+        // Auto-hide once no new caption arrives for the fade timeout
         clearTimeout(timeoutId);
-        timeoutId = setTimeout(() => {
-          const hideMsg = "⏸️ [LiveCaption] Hiding caption after 5s timeout";
-          console.log(hideMsg);
-          setIsVisible(false);
-          setIsListening(true); // Show "Đang nghe..." again
-        }, 5000);
+        if (fadeTimeoutMs > 0) {
+          timeoutId = setTimeout(() => {
+            const hideMsg = `⏸️ [LiveCaption] Hiding caption after ${fadeTimeoutMs}ms timeout`;
+            console.log(hideMsg);
+            setIsVisible(false);
+            setLines([]);
+            setIsListening(true); // Show "Đang nghe..." again
+          }, fadeTimeoutMs);
+        }
       } else {
         const warnMsg = `⚠️ [LiveCaption] Received empty or too short caption (length: ${newCaption.length})`;
         console.warn(warnMsg);
@@ -72,31 +116,56 @@ export default function LiveCaption({ enabled = true }: LiveCaptionProps) {
       }
       clearTimeout(timeoutId);
     };
-  }, [enabled]);
+  }, [enabled, style.max_lines, style.fade_timeout_ms]);
 
   if (!enabled) {
     return null;
   }
 
+  const containerClass = `live-caption-container anchor-${style.anchor}`;
+  const contentStyle = {
+    background: `rgba(0, 0, 0, ${style.background_opacity})`,
+  };
+  const textStyle = { fontSize: `${style.font_size}px` };
+  const translationStyle = {
+    fontSize: `${Math.round(style.font_size * 0.9)}px`,
+  };
+
   // Show "Đang nghe..." when listening but no caption yet
   if (!isVisible && isListening) {
     return (
-      <div className="live-caption-container">
-        <div className="live-caption-content">
-          <span className="live-caption-text listening">Đang nghe...</span>
+      <div className={containerClass}>
+        <div className="live-caption-content" style={contentStyle}>
+          <span className="live-caption-text listening" style={textStyle}>
+            Đang nghe...
+          </span>
         </div>
       </div>
     );
   }
 
-  if (!isVisible || !caption) {
+  if (!isVisible || lines.length === 0) {
     return null;
   }
 
   return (
-    <div className="live-caption-container">
-      <div className="live-caption-content">
-        <span className="live-caption-text">{caption}</span>
+    <div className={containerClass}>
+      <div className="live-caption-content" style={contentStyle}>
+        {lines.map((line, index) => (
+          <React.Fragment key={index}>
+            <span className="live-caption-text" style={textStyle}>
+              {line.text}
+            </span>
+            {line.translation && (
+              <span
+                className="live-caption-text translation"
+                style={translationStyle}
+              >
+                {line.translation}
+              </span>
+            )}
+          </React.Fragment>
+        ))}
       </div>
     </div>
   );
@@ -0,0 +1,40 @@
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import { useSettingsStore } from "../stores/settingsStore";
+import type { MicrophoneFailover } from "../lib/types";
+
+// Keeps the microphone list current as devices come and go, and explains a
+// switch to the default input after the microphone in use went away
+export default function MicrophoneFailoverNotice() {
+  useEffect(() => {
+    const refresh = () => useSettingsStore.getState().refreshAudioDevices();
+    const unlistenAdded = listen("device-added", refresh);
+    const unlistenRemoved = listen("device-removed", refresh);
+    const unlistenFailover = listen<MicrophoneFailover>(
+      "microphone-failover",
+      (event) => {
+        const { from, recording, error } = event.payload;
+        if (error) {
+          toast.error(`${from} was disconnected`, {
+            description: `No other microphone could be opened: ${error}`,
+          });
+          return;
+        }
+        toast.warning(`${from} was disconnected`, {
+          description: recording
+            ? "Recording continues on the default microphone."
+            : "Switched to the default microphone.",
+        });
+      },
+    );
+
+    return () => {
+      unlistenAdded.then((fn) => fn());
+      unlistenRemoved.then((fn) => fn());
+      unlistenFailover.then((fn) => fn());
+    };
+  }, []);
+
+  return null;
+}
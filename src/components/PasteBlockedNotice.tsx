@@ -0,0 +1,20 @@
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+
+// Explains why a transcription landed on the clipboard instead of being pasted
+export default function PasteBlockedNotice() {
+  useEffect(() => {
+    const unlisten = listen<string>("paste-blocked", (event) => {
+      toast(`Copied instead of pasting into ${event.payload}`, {
+        description: "This app is on the do-not-paste list.",
+      });
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  return null;
+}
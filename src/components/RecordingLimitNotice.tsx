@@ -0,0 +1,24 @@
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+
+// Explains why a recording stopped before its shortcut ended it
+export default function RecordingLimitNotice() {
+  useEffect(() => {
+    const unlisten = listen<number>("recording-limit-reached", (event) => {
+      toast.warning(
+        `Recording stopped after ${Math.round(event.payload / 60)} minutes`,
+        {
+          description:
+            "It reached the maximum recording length and was transcribed.",
+        },
+      );
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  return null;
+}
@@ -0,0 +1,57 @@
+import { useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+
+interface OrphanedRecording {
+  id: string;
+  started_at: number;
+  duration_secs: number;
+}
+
+// Offers to recover recordings that were interrupted by a crash
+export default function RecordingRecovery() {
+  useEffect(() => {
+    const recover = async (recording: OrphanedRecording) => {
+      try {
+        await invoke<string>("recover_orphaned_recording", {
+          id: recording.id,
+        });
+        toast.success("Recovered recording saved to history");
+      } catch (error) {
+        toast.error(`Failed to recover recording: ${error}`);
+      }
+    };
+
+    const discard = async (recording: OrphanedRecording) => {
+      try {
+        await invoke("discard_orphaned_recording", { id: recording.id });
+      } catch (error) {
+        console.error("Failed to discard recording:", error);
+      }
+    };
+
+    invoke<OrphanedRecording[]>("list_orphaned_recordings")
+      .then((recordings) => {
+        for (const recording of recordings) {
+          const startedAt = new Date(recording.started_at).toLocaleString();
+          toast(`Recording from ${startedAt} was interrupted`, {
+            description: `${Math.round(recording.duration_secs)}s of audio can be transcribed`,
+            duration: Infinity,
+            action: {
+              label: "Recover",
+              onClick: () => recover(recording),
+            },
+            cancel: {
+              label: "Discard",
+              onClick: () => discard(recording),
+            },
+          });
+        }
+      })
+      .catch((error) => {
+        console.error("Failed to list interrupted recordings:", error);
+      });
+  }, []);
+
+  return null;
+}
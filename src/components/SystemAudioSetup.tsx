@@ -12,9 +12,11 @@ interface SetupDialogProps {
   message: string;
   instructions: string[];
   isRetrying: boolean;
+  // Offer to create the Multi-Output Device instead of doing it by hand
+  onSetupRouting?: () => void;
 }
 
-function SetupDialog({ isOpen, onClose, onRetry, title, message, instructions, isRetrying }: SetupDialogProps) {
+function SetupDialog({ isOpen, onClose, onRetry, title, message, instructions, isRetrying, onSetupRouting }: SetupDialogProps) {
   if (!isOpen) return null;
 
   return (
@@ -48,6 +50,15 @@ function SetupDialog({ isOpen, onClose, onRetry, title, message, instructions, i
           >
             Download BlackHole
           </button>
+          {onSetupRouting && (
+            <button
+              onClick={onSetupRouting}
+              disabled={isRetrying}
+              className="px-4 py-2 bg-blue-600 hover:bg-blue-700 disabled:bg-gray-600 disabled:cursor-not-allowed text-white rounded transition-colors"
+            >
+              Set Up Routing
+            </button>
+          )}
           <button
             onClick={onRetry}
             disabled={isRetrying}
@@ -71,6 +82,7 @@ export default function SystemAudioSetup() {
     title: string;
     message: string;
     instructions: string[];
+    canSetupRouting?: boolean;
   }>({
     isOpen: false,
     title: "",
@@ -95,6 +107,22 @@ export default function SystemAudioSetup() {
     }
   };
 
+  const handleSetupRouting = async () => {
+    setIsRetrying(true);
+    try {
+      // Creates a Multi-Output Device with BlackHole and the current speakers
+      await invoke("setup_blackhole_routing", { enabled: true });
+      await invoke("restart_audio_stream");
+      toast.success("System audio configured successfully!");
+      setSetupDialog(prev => ({ ...prev, isOpen: false }));
+    } catch (error) {
+      console.error("Routing setup failed:", error);
+      toast.error(String(error));
+    } finally {
+      setIsRetrying(false);
+    }
+  };
+
   useEffect(() => {
     console.log("🔧 [SystemAudioSetup] Component mounted, setting up listeners...");
     
@@ -121,16 +149,13 @@ export default function SystemAudioSetup() {
       setSetupDialog({
         isOpen: true,
         title: "🎵 System Audio Setup Required",
-        message: "To capture system audio (e.g., from Chrome, Zoom), you need to install BlackHole and configure a Multi-Output Device:",
+        message: "To capture system audio (e.g., from Chrome, Zoom), you need to install BlackHole and route your output through it:",
         instructions: [
           "Install BlackHole: brew install blackhole-2ch (or download from existential.audio/blackhole/)",
-          "Open Audio MIDI Setup (Applications > Utilities > Audio MIDI Setup)",
-          "Click '+' button at bottom-left, select 'Create Multi-Output Device'",
-          "In the Multi-Output Device, check both 'BlackHole 2ch' and your current speakers (e.g., 'Built-in Output')",
-          "Set Master Device to your speakers (click the gear icon) to keep volume control working",
-          "Go to System Settings > Sound > Output and select the Multi-Output Device you just created",
-          "Click 'I've Completed Setup - Retry' button below"
+          "Click 'Set Up Routing' below. Handy creates a Multi-Output Device with BlackHole and your current speakers and makes it the system output",
+          "To undo it later, pick your speakers in System Settings > Sound > Output, or remove 'Handy Multi-Output' in Audio MIDI Setup"
         ],
+        canSetupRouting: true,
       });
     });
 
@@ -164,6 +189,7 @@ export default function SystemAudioSetup() {
   return (
     <SetupDialog
       {...setupDialog}
+      onSetupRouting={setupDialog.canSetupRouting ? handleSetupRouting : undefined}
       onClose={() => setSetupDialog(prev => ({ ...prev, isOpen: false }))}
       onRetry={handleRetry}
       isRetrying={isRetrying}
@@ -0,0 +1,82 @@
+import React, { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { SettingsGroup } from "../ui/SettingsGroup";
+import { Button } from "../ui/Button";
+import type { AudioSelfTest as SelfTestResult } from "../../lib/types";
+
+// Runs the capture pipeline on the selected source for a few seconds and
+// shows the result as a checklist
+export const AudioSelfTest: React.FC = () => {
+  const [result, setResult] = useState<SelfTestResult | null>(null);
+  const [running, setRunning] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const runTest = async () => {
+    setRunning(true);
+    setError(null);
+    try {
+      setResult(await invoke<SelfTestResult>("run_audio_selftest"));
+    } catch (err) {
+      setResult(null);
+      setError(String(err));
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  return (
+    <SettingsGroup title="Audio Self-Test">
+      <div className="p-4 space-y-3">
+        <div className="flex items-center justify-between gap-3">
+          <p className="text-sm text-text/70">
+            {running
+              ? "Listening for 3 seconds… say a few words or play some audio."
+              : "Check that the selected audio source delivers clean audio."}
+          </p>
+          <Button
+            variant="secondary"
+            size="sm"
+            onClick={runTest}
+            disabled={running}
+          >
+            {running ? "Testing…" : "Run test"}
+          </Button>
+        </div>
+        {error && <p className="text-xs text-red-500">{error}</p>}
+        {result && (
+          <>
+            <p className="text-sm text-text">
+              {result.passed ? "✅ Passed" : "❌ Failed"} · {result.device}
+            </p>
+            <ul className="space-y-2">
+              {result.checks.map((check) => (
+                <li key={check.id} className="flex gap-3">
+                  <span>{check.passed ? "✅" : "❌"}</span>
+                  <div className="flex-1">
+                    <p className="text-sm text-text">{check.label}</p>
+                    {check.hint && (
+                      <p className="text-xs text-text/60">{check.hint}</p>
+                    )}
+                  </div>
+                </li>
+              ))}
+            </ul>
+            <p className="text-xs text-text/50">
+              {[
+                `${result.measured_sample_rate.toFixed(0)}Hz measured (${result.sample_rate}Hz nominal)`,
+                `RMS ${result.rms.toFixed(4)}`,
+                `peak ${result.peak.toFixed(4)}`,
+                `${result.dropouts} dropouts`,
+                result.speech_ratio !== null &&
+                  `${Math.round(result.speech_ratio * 100)}% speech`,
+                `${result.resampled_samples}/${result.expected_resampled_samples} resampled samples`,
+              ]
+                .filter(Boolean)
+                .join(" · ")}
+            </p>
+          </>
+        )}
+      </div>
+    </SettingsGroup>
+  );
+};
@@ -3,6 +3,7 @@ import { Dropdown } from "../ui/Dropdown";
 import { SettingContainer } from "../ui/SettingContainer";
 import { ResetButton } from "../ui/ResetButton";
 import { useSettings } from "../../hooks/useSettings";
+import { usePlatformCapabilities } from "../../hooks/usePlatformCapabilities";
 import { invoke } from "@tauri-apps/api/core";
 
 interface AudioSourceSelectorProps {
@@ -19,6 +20,8 @@ export const AudioSourceSelector: React.FC<AudioSourceSelectorProps> = React.mem
       isUpdating,
       isLoading,
     } = useSettings();
+    const capabilities = usePlatformCapabilities();
+    const systemAudio = capabilities?.system_audio;
 
     const [audioSource, setAudioSource] = React.useState<string>("microphone");
 
@@ -56,13 +59,24 @@ export const AudioSourceSelector: React.FC<AudioSourceSelectorProps> = React.mem
 
     const audioSourceOptions = [
       { value: "microphone", label: "Microphone" },
-      { value: "system_audio", label: "System Audio" },
+      {
+        value: "system_audio",
+        label:
+          systemAudio && !systemAudio.supported
+            ? "System Audio (unavailable)"
+            : "System Audio",
+        disabled: systemAudio ? !systemAudio.supported : false,
+      },
     ];
 
     return (
       <SettingContainer
         title="Audio Source"
-        description="Select audio input source: Microphone or System Audio (macOS/Windows)"
+        description={
+          systemAudio?.note
+            ? `Select audio input source: Microphone or System Audio. ${systemAudio.note}.`
+            : "Select audio input source: Microphone or System Audio (macOS/Windows)"
+        }
         descriptionMode={descriptionMode}
         grouped={grouped}
       >
@@ -0,0 +1,29 @@
+import React from "react";
+import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { useSettings } from "../../hooks/useSettings";
+
+interface AutoCaptionSystemAudioProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+export const AutoCaptionSystemAudio: React.FC<AutoCaptionSystemAudioProps> =
+  React.memo(({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const enabled = getSetting("auto_caption_system_audio") ?? true;
+
+    return (
+      <ToggleSwitch
+        checked={enabled}
+        onChange={(enabled) =>
+          updateSetting("auto_caption_system_audio", enabled)
+        }
+        isUpdating={isUpdating("auto_caption_system_audio")}
+        label="Caption System Audio Automatically"
+        description="Start transcribing system audio as soon as the app launches. Independent of the always-on microphone."
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      />
+    );
+  });
@@ -0,0 +1,137 @@
+import React, { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { type as getOsType } from "@tauri-apps/plugin-os";
+import { toast } from "sonner";
+import { SettingsGroup } from "../ui/SettingsGroup";
+import { Button } from "../ui/Button";
+import type {
+  BlackHoleInstall,
+  BlackHoleRoutingCheck,
+  BlackHoleStatus,
+} from "../../lib/types";
+
+// Detects BlackHole, walks through installing it and checks the routing by
+// playing a test tone into it
+export const BlackHoleSetup: React.FC = () => {
+  const [status, setStatus] = useState<BlackHoleStatus | null>(null);
+  const [check, setCheck] = useState<BlackHoleRoutingCheck | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const refresh = useCallback(async () => {
+    try {
+      setStatus(await invoke<BlackHoleStatus>("get_blackhole_status"));
+    } catch (err) {
+      setError(String(err));
+    }
+  }, []);
+
+  useEffect(() => {
+    if (getOsType() === "macos") {
+      refresh();
+    }
+  }, [refresh]);
+
+  if (!status) return null;
+
+  const run = async (action: () => Promise<void>) => {
+    setBusy(true);
+    setError(null);
+    try {
+      await action();
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const install = () =>
+    run(async () => {
+      const result = await invoke<BlackHoleInstall>("install_blackhole", {
+        channels: 2,
+      });
+      toast.info(
+        result.action === "installer"
+          ? "Follow the installer, then check again"
+          : "Download BlackHole 2ch, open the package, then check again",
+      );
+    });
+
+  const setUpRouting = () =>
+    run(async () => {
+      await invoke("setup_blackhole_routing", { enabled: true });
+      await refresh();
+    });
+
+  const verify = () =>
+    run(async () => {
+      setCheck(await invoke<BlackHoleRoutingCheck>("verify_blackhole_routing"));
+    });
+
+  return (
+    <SettingsGroup title="BlackHole">
+      <div className="p-4 space-y-3">
+        <div className="flex items-center justify-between gap-3">
+          <p className="text-sm text-text/70">
+            {status.installed
+              ? `${status.device} is installed. ${
+                  status.routed
+                    ? `System output plays through ${status.default_output}.`
+                    : "System output does not play into it yet."
+                }`
+              : "BlackHole is not installed. It lets Handy capture system audio on older macOS versions."}
+          </p>
+          <div className="flex gap-2">
+            {!status.installed && (
+              <Button
+                variant="secondary"
+                size="sm"
+                onClick={install}
+                disabled={busy}
+              >
+                Install
+              </Button>
+            )}
+            {status.installed && !status.routed && (
+              <Button
+                variant="secondary"
+                size="sm"
+                onClick={setUpRouting}
+                disabled={busy}
+              >
+                Set Up Routing
+              </Button>
+            )}
+            {status.installed && (
+              <Button
+                variant="secondary"
+                size="sm"
+                onClick={verify}
+                disabled={busy}
+              >
+                {busy ? "Checking…" : "Verify"}
+              </Button>
+            )}
+            <Button
+              variant="secondary"
+              size="sm"
+              onClick={() => run(refresh)}
+              disabled={busy}
+            >
+              Check again
+            </Button>
+          </div>
+        </div>
+        {error && <p className="text-xs text-red-500">{error}</p>}
+        {check && (
+          <p className="text-sm text-text">
+            {check.tone_detected
+              ? `✅ A test tone played on ${check.output} arrived at ${check.loopback}`
+              : `❌ A test tone played on ${check.output} did not reach ${check.loopback}. Set up routing or pick a Multi-Output Device as the output.`}
+          </p>
+        )}
+      </div>
+    </SettingsGroup>
+  );
+};
@@ -0,0 +1,28 @@
+import React from "react";
+import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { useSettings } from "../../hooks/useSettings";
+
+interface CaptionPasteProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+export const CaptionPaste: React.FC<CaptionPasteProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const enabled = getSetting("caption_paste_enabled") ?? false;
+
+    return (
+      <ToggleSwitch
+        checked={enabled}
+        onChange={(enabled) => updateSetting("caption_paste_enabled", enabled)}
+        isUpdating={isUpdating("caption_paste_enabled")}
+        label="Paste Live Captions"
+        description="Also paste each live caption into the focused window. When off, captions only appear in the overlay and history."
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      />
+    );
+  },
+);
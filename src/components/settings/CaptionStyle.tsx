@@ -0,0 +1,98 @@
+import React from "react";
+import { Dropdown } from "../ui/Dropdown";
+import { SettingContainer } from "../ui/SettingContainer";
+import { Slider } from "../ui/Slider";
+import { useSettings } from "../../hooks/useSettings";
+import {
+  DEFAULT_CAPTION_STYLE,
+  type CaptionAnchor,
+  type CaptionStyle,
+} from "../../lib/types";
+
+interface CaptionStyleProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+const anchorOptions = [
+  { value: "top", label: "Top" },
+  { value: "center", label: "Center" },
+  { value: "bottom", label: "Bottom" },
+];
+
+export const CaptionStyleSetting: React.FC<CaptionStyleProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const style = getSetting("caption_style") ?? DEFAULT_CAPTION_STYLE;
+
+    const save = (patch: Partial<CaptionStyle>) =>
+      updateSetting("caption_style", { ...style, ...patch });
+
+    return (
+      <>
+        <Slider
+          value={style.font_size}
+          onChange={(value) => save({ font_size: value })}
+          min={10}
+          max={72}
+          step={1}
+          label="Caption Font Size"
+          description="Size of the live caption text."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+          formatValue={(value) => `${value}px`}
+        />
+        <Slider
+          value={style.background_opacity}
+          onChange={(value) => save({ background_opacity: value })}
+          min={0}
+          max={1}
+          step={0.05}
+          label="Caption Background"
+          description="Opacity of the box behind live captions."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+          formatValue={(value) => `${Math.round(value * 100)}%`}
+        />
+        <Slider
+          value={style.max_lines}
+          onChange={(value) => save({ max_lines: value })}
+          min={1}
+          max={10}
+          step={1}
+          label="Caption Lines"
+          description="How many captions stay on screen before older ones scroll off."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+          formatValue={(value) => value.toString()}
+        />
+        <Slider
+          value={style.fade_timeout_ms / 1000}
+          onChange={(value) => save({ fade_timeout_ms: value * 1000 })}
+          min={0}
+          max={60}
+          step={1}
+          label="Caption Fade"
+          description="Seconds a caption stays up when nothing new is said. 0 keeps it until the next one."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+          formatValue={(value) => (value === 0 ? "Never" : `${value}s`)}
+        />
+        <SettingContainer
+          title="Caption Position"
+          description="Where the live caption overlay sits on screen."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <Dropdown
+            options={anchorOptions}
+            selectedValue={style.anchor}
+            onSelect={(value) => save({ anchor: value as CaptionAnchor })}
+            disabled={isUpdating("caption_style")}
+          />
+        </SettingContainer>
+      </>
+    );
+  },
+);
@@ -0,0 +1,45 @@
+import React from "react";
+import { Dropdown } from "../ui/Dropdown";
+import { SettingContainer } from "../ui/SettingContainer";
+import { useSettings } from "../../hooks/useSettings";
+
+interface CaptionTranslationProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+const OFF = "off";
+
+// Whisper's translate task only produces English
+const translationOptions = [
+  { value: OFF, label: "Off" },
+  { value: "en", label: "English" },
+];
+
+export const CaptionTranslation: React.FC<CaptionTranslationProps> =
+  React.memo(({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const language = getSetting("caption_translation_language") ?? OFF;
+
+    return (
+      <SettingContainer
+        title="Translate Captions"
+        description="Show a translation under each live caption. Translation runs on-device with the Whisper model and roughly doubles the transcription work per caption."
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      >
+        <Dropdown
+          options={translationOptions}
+          selectedValue={language}
+          onSelect={(value) =>
+            updateSetting(
+              "caption_translation_language",
+              value === OFF ? null : value,
+            )
+          }
+          disabled={isUpdating("caption_translation_language")}
+        />
+      </SettingContainer>
+    );
+  });
@@ -0,0 +1,80 @@
+import React, { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { type as getOsType } from "@tauri-apps/plugin-os";
+import { SettingsGroup } from "../ui/SettingsGroup";
+import { Button } from "../ui/Button";
+import { useSettings } from "../../hooks/useSettings";
+import type { CaptureTarget } from "../../lib/types";
+
+const describeTarget = (target: CaptureTarget) => {
+  switch (target.kind) {
+    case "display":
+      return `Display ${target.display_id}`;
+    case "window":
+      return target.app
+        ? `${target.title ?? "Window"} (${target.app})`
+        : (target.title ?? `Window ${target.window_id}`);
+    case "application":
+      return target.name ?? target.bundle_id;
+  }
+};
+
+// Picks the display, window or app ScreenCaptureKit captures audio from with
+// macOS's content-sharing picker
+export const CaptureTargetSetting: React.FC = () => {
+  const { getSetting, refreshSettings } = useSettings();
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  if (getOsType() !== "macos") return null;
+
+  const target = getSetting("screen_capture_target");
+
+  const run = async (command: string) => {
+    setBusy(true);
+    setError(null);
+    try {
+      await invoke(command);
+      await refreshSettings();
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <SettingsGroup title="Capture Source">
+      <div className="p-4 space-y-3">
+        <div className="flex items-center justify-between gap-3">
+          <p className="text-sm text-text/70">
+            {target
+              ? `System audio is captured from ${describeTarget(target)}.`
+              : "All system audio is captured. Choose a display, window or app to capture only its audio."}
+          </p>
+          <div className="flex gap-2">
+            <Button
+              variant="secondary"
+              size="sm"
+              onClick={() => run("pick_capture_target")}
+              disabled={busy}
+            >
+              {busy ? "Choosing…" : "Choose…"}
+            </Button>
+            {target && (
+              <Button
+                variant="secondary"
+                size="sm"
+                onClick={() => run("clear_capture_target")}
+                disabled={busy}
+              >
+                Reset
+              </Button>
+            )}
+          </div>
+        </div>
+        {error && <p className="text-xs text-red-500">{error}</p>}
+      </div>
+    </SettingsGroup>
+  );
+};
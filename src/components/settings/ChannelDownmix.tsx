@@ -0,0 +1,77 @@
+import React from "react";
+import { Dropdown } from "../ui/Dropdown";
+import { SettingContainer } from "../ui/SettingContainer";
+import { useSettings } from "../../hooks/useSettings";
+import { DEFAULT_CHANNEL_DOWNMIX, type ChannelDownmix } from "../../lib/types";
+
+interface ChannelDownmixProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+// Channels past left and right that can be picked on their own
+const EXTRA_CHANNELS = [2, 3, 4, 5, 6, 7];
+
+const options = [
+  { value: "average", label: "Average All Channels" },
+  { value: "left", label: "Left Channel" },
+  { value: "right", label: "Right Channel" },
+  ...EXTRA_CHANNELS.map((channel) => ({
+    value: `channel:${channel}`,
+    label: `Channel ${channel + 1}`,
+  })),
+];
+
+const toValue = (downmix: ChannelDownmix) =>
+  downmix.mode === "channel" ? `channel:${downmix.channel}` : downmix.mode;
+
+const fromValue = (value: string): ChannelDownmix =>
+  value.startsWith("channel:")
+    ? { mode: "channel", channel: Number(value.slice("channel:".length)) }
+    : { mode: value as ChannelDownmix["mode"], channel: 0 };
+
+export const ChannelDownmixSetting: React.FC<ChannelDownmixProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const microphone =
+      getSetting("microphone_downmix") ?? DEFAULT_CHANNEL_DOWNMIX;
+    const systemAudio =
+      getSetting("system_audio_downmix") ?? DEFAULT_CHANNEL_DOWNMIX;
+
+    return (
+      <>
+        <SettingContainer
+          title="Microphone Channels"
+          description="How a multi-channel microphone or interface is reduced to mono. Pick one channel when another carries something else, like loopback audio."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <Dropdown
+            options={options}
+            selectedValue={toValue(microphone)}
+            onSelect={(value) =>
+              updateSetting("microphone_downmix", fromValue(value))
+            }
+            disabled={isUpdating("microphone_downmix")}
+          />
+        </SettingContainer>
+        <SettingContainer
+          title="System Audio Channels"
+          description="How captured system audio is reduced to mono for captions."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <Dropdown
+            options={options}
+            selectedValue={toValue(systemAudio)}
+            onSelect={(value) =>
+              updateSetting("system_audio_downmix", fromValue(value))
+            }
+            disabled={isUpdating("system_audio_downmix")}
+          />
+        </SettingContainer>
+      </>
+    );
+  },
+);
@@ -57,8 +57,8 @@ export const ClamshellMicrophoneSelector: React.FC<ClamshellMicrophoneSelectorPr
     };
 
     const microphoneOptions = audioDevices.map((device) => ({
-      value: device.name,
-      label: device.name,
+      value: device.id,
+      label: device.alias || device.name,
     }));
 
     return (
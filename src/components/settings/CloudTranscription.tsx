@@ -0,0 +1,125 @@
+import React, { useEffect, useState } from "react";
+import { Dropdown } from "../ui/Dropdown";
+import { Input } from "../ui/Input";
+import { SettingContainer } from "../ui/SettingContainer";
+import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { ApiKeyField } from "./PostProcessingSettingsApi/ApiKeyField";
+import { useSettings } from "../../hooks/useSettings";
+
+interface CloudTranscriptionProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+const backendOptions = [
+  { value: "local", label: "On this device" },
+  { value: "cloud", label: "Cloud API" },
+];
+
+// Text field that saves when it loses focus
+const BlurInput: React.FC<{
+  value: string;
+  onSave: (value: string) => void;
+  disabled: boolean;
+  placeholder: string;
+}> = ({ value, onSave, disabled, placeholder }) => {
+  const [localValue, setLocalValue] = useState(value);
+
+  useEffect(() => {
+    setLocalValue(value);
+  }, [value]);
+
+  return (
+    <Input
+      type="text"
+      value={localValue}
+      onChange={(event) => setLocalValue(event.target.value)}
+      onBlur={() => localValue !== value && onSave(localValue)}
+      placeholder={placeholder}
+      variant="compact"
+      disabled={disabled}
+      className="flex-1 min-w-[320px]"
+    />
+  );
+};
+
+export const CloudTranscription: React.FC<CloudTranscriptionProps> =
+  React.memo(({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const backend = getSetting("transcription_backend") ?? "local";
+
+    return (
+      <>
+        <SettingContainer
+          title="Transcription Engine"
+          description="Transcribe on this device with the selected model, or upload audio to an OpenAI-compatible speech API such as OpenAI or Groq."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <Dropdown
+            options={backendOptions}
+            selectedValue={backend}
+            onSelect={(value) =>
+              updateSetting("transcription_backend", value as "local" | "cloud")
+            }
+            disabled={isUpdating("transcription_backend")}
+          />
+        </SettingContainer>
+        {backend === "cloud" && (
+          <>
+            <SettingContainer
+              title="API Endpoint"
+              description="Base URL of the speech API, e.g. https://api.openai.com/v1 or https://api.groq.com/openai/v1."
+              descriptionMode={descriptionMode}
+              grouped={grouped}
+            >
+              <BlurInput
+                value={getSetting("cloud_api_endpoint") ?? ""}
+                onSave={(value) => updateSetting("cloud_api_endpoint", value)}
+                disabled={isUpdating("cloud_api_endpoint")}
+                placeholder="https://api.openai.com/v1"
+              />
+            </SettingContainer>
+            <SettingContainer
+              title="API Key"
+              description="Sent only to the endpoint above."
+              descriptionMode={descriptionMode}
+              grouped={grouped}
+            >
+              <ApiKeyField
+                value={getSetting("cloud_api_key") ?? ""}
+                onBlur={(value) => updateSetting("cloud_api_key", value)}
+                disabled={isUpdating("cloud_api_key")}
+                placeholder="sk-..."
+              />
+            </SettingContainer>
+            <SettingContainer
+              title="Cloud Model"
+              description="Model name at the endpoint, e.g. whisper-1 for OpenAI or whisper-large-v3-turbo for Groq."
+              descriptionMode={descriptionMode}
+              grouped={grouped}
+            >
+              <BlurInput
+                value={getSetting("cloud_model") ?? ""}
+                onSave={(value) => updateSetting("cloud_model", value)}
+                disabled={isUpdating("cloud_model")}
+                placeholder="whisper-1"
+              />
+            </SettingContainer>
+            <ToggleSwitch
+              checked={getSetting("cloud_fallback_to_local") ?? true}
+              onChange={(enabled) =>
+                updateSetting("cloud_fallback_to_local", enabled)
+              }
+              isUpdating={isUpdating("cloud_fallback_to_local")}
+              label="Fall Back to Local Model"
+              description="When the API can't be reached, transcribe with the downloaded model instead."
+              descriptionMode={descriptionMode}
+              grouped={grouped}
+            />
+          </>
+        )}
+      </>
+    );
+  });
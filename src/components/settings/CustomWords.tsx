@@ -9,31 +9,65 @@ interface CustomWordsProps {
   grouped?: boolean;
 }
 
+// "kubectl = cube cuttle, coob control" is a word with two spoken aliases
+const parseEntry = (entry: string) => {
+  const [word, aliases = ""] = entry.split("=", 2);
+  return {
+    word: word.trim().replace(/[<>"'&]/g, ""),
+    aliases: aliases
+      .split(",")
+      .map((alias) => alias.trim())
+      .filter((alias) => alias.length > 0),
+  };
+};
+
+const isValidWord = (word: string) =>
+  word.length > 0 && !word.includes(" ") && word.length <= 50;
+
 export const CustomWords: React.FC<CustomWordsProps> = React.memo(
   ({ descriptionMode = "tooltip", grouped = false }) => {
     const { getSetting, updateSetting, isUpdating } = useSettings();
     const [newWord, setNewWord] = useState("");
     const customWords = getSetting("custom_words") || [];
+    const aliases = getSetting("custom_word_aliases") || {};
+    const entry = parseEntry(newWord);
+    const isBusy =
+      isUpdating("custom_words") || isUpdating("custom_word_aliases");
 
-    const handleAddWord = () => {
-      const trimmedWord = newWord.trim();
-      const sanitizedWord = trimmedWord.replace(/[<>"'&]/g, "");
-      if (
-        sanitizedWord &&
-        !sanitizedWord.includes(" ") &&
-        sanitizedWord.length <= 50 &&
-        !customWords.includes(sanitizedWord)
-      ) {
-        updateSetting("custom_words", [...customWords, sanitizedWord]);
-        setNewWord("");
+    const handleAddWord = async () => {
+      const { word, aliases: newAliases } = entry;
+      if (!isValidWord(word)) {
+        return;
+      }
+      if (!customWords.includes(word)) {
+        await updateSetting("custom_words", [...customWords, word]);
+      } else if (newAliases.length === 0) {
+        return;
       }
+      if (newAliases.length > 0) {
+        const existing = aliases[word] || [];
+        const merged = [...new Set([...existing, ...newAliases])];
+        await updateSetting("custom_word_aliases", {
+          ...aliases,
+          [word]: merged,
+        });
+      }
+      setNewWord("");
     };
 
-    const handleRemoveWord = (wordToRemove: string) => {
-      updateSetting(
+    const handleRemoveWord = async (wordToRemove: string) => {
+      await updateSetting(
         "custom_words",
         customWords.filter((word) => word !== wordToRemove),
       );
+      if (aliases[wordToRemove]) {
+        await updateSetting(
+          "custom_word_aliases",
+          Object.fromEntries(
+            Object.entries(aliases).filter(([word]) => word !== wordToRemove),
+          ),
+        );
+      }
     };
 
     const handleKeyPress = (e: React.KeyboardEvent) => {
@@ -47,29 +81,24 @@ export const CustomWords: React.FC<CustomWordsProps> = React.memo(
       <>
         <SettingContainer
           title="Custom Words"
-          description="Add words that are often misheard or misspelled during transcription. The system will automatically correct similar-sounding words to match your list."
+          description="Add words that are often misheard or misspelled during transcription. They are suggested to the model before it transcribes, and similar-sounding words are corrected to match your list. Add spoken aliases with word = alias, alias."
           descriptionMode={descriptionMode}
           grouped={grouped}
         >
           <div className="flex items-center gap-2">
             <Input
               type="text"
-              className="max-w-40"
+              className="max-w-56"
               value={newWord}
               onChange={(e) => setNewWord(e.target.value)}
               onKeyDown={handleKeyPress}
-              placeholder="Add a word"
+              placeholder="word = alias, alias"
               variant="compact"
-              disabled={isUpdating("custom_words")}
+              disabled={isBusy}
             />
             <Button
               onClick={handleAddWord}
-              disabled={
-                !newWord.trim() ||
-                newWord.includes(" ") ||
-                newWord.trim().length > 50 ||
-                isUpdating("custom_words")
-              }
+              disabled={!isValidWord(entry.word) || isBusy}
               variant="primary"
               size="md"
             >
@@ -85,13 +114,17 @@ export const CustomWords: React.FC<CustomWordsProps> = React.memo(
               <Button
                 key={word}
                 onClick={() => handleRemoveWord(word)}
-                disabled={isUpdating("custom_words")}
+                disabled={isBusy}
                 variant="secondary"
                 size="sm"
                 className="inline-flex items-center gap-1 cursor-pointer"
                 aria-label={`Remove ${word}`}
+                title={aliases[word]?.join(", ")}
               >
-                <span>{word}</span>
+                <span>
+                  {word}
+                  {aliases[word]?.length ? ` (${aliases[word].length})` : ""}
+                </span>
                 <svg
                   className="w-3 h-3"
                   fill="none"
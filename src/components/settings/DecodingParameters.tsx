@@ -0,0 +1,132 @@
+import React, { useEffect, useState } from "react";
+import { Input } from "../ui/Input";
+import { SettingContainer } from "../ui/SettingContainer";
+import { useSettings } from "../../hooks/useSettings";
+import type { TranscriptionParams } from "../../lib/types";
+
+interface DecodingParametersProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+const defaultParams: TranscriptionParams = {
+  beam_size: null,
+  temperature: 0,
+  no_speech_threshold: 0.6,
+  language_hint: null,
+};
+
+// Field that parses its text when it loses focus; empty text means null
+const ParamInput: React.FC<{
+  value: string;
+  onSave: (value: string) => void;
+  disabled: boolean;
+  placeholder: string;
+}> = ({ value, onSave, disabled, placeholder }) => {
+  const [localValue, setLocalValue] = useState(value);
+
+  useEffect(() => {
+    setLocalValue(value);
+  }, [value]);
+
+  return (
+    <Input
+      type="text"
+      value={localValue}
+      onChange={(event) => setLocalValue(event.target.value)}
+      onBlur={() => localValue !== value && onSave(localValue.trim())}
+      placeholder={placeholder}
+      variant="compact"
+      disabled={disabled}
+      className="w-24"
+    />
+  );
+};
+
+export const DecodingParameters: React.FC<DecodingParametersProps> =
+  React.memo(({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const params = getSetting("transcription_params") ?? defaultParams;
+    const disabled = isUpdating("transcription_params");
+
+    const save = (patch: Partial<TranscriptionParams>) =>
+      updateSetting("transcription_params", { ...params, ...patch });
+
+    const parseNumber = (text: string, fallback: number) => {
+      const value = Number(text);
+      return text === "" || Number.isNaN(value) ? fallback : value;
+    };
+
+    return (
+      <>
+        <SettingContainer
+          title="Beam Size"
+          description="Beams searched while decoding. Larger is slower but can be more accurate. Leave empty for the engine default."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <ParamInput
+            value={params.beam_size?.toString() ?? ""}
+            onSave={(text) =>
+              save({ beam_size: text === "" ? null : parseInt(text, 10) })
+            }
+            disabled={disabled}
+            placeholder="default"
+          />
+        </SettingContainer>
+        <SettingContainer
+          title="Temperature"
+          description="Sampling temperature from 0 to 1. 0 always picks the most likely words."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <ParamInput
+            value={params.temperature.toString()}
+            onSave={(text) =>
+              save({
+                temperature: parseNumber(text, defaultParams.temperature),
+              })
+            }
+            disabled={disabled}
+            placeholder="0"
+          />
+        </SettingContainer>
+        <SettingContainer
+          title="No-Speech Threshold"
+          description="Segments more likely than this to be silence are dropped. Lower it if quiet speech goes missing."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <ParamInput
+            value={params.no_speech_threshold.toString()}
+            onSave={(text) =>
+              save({
+                no_speech_threshold: parseNumber(
+                  text,
+                  defaultParams.no_speech_threshold,
+                ),
+              })
+            }
+            disabled={disabled}
+            placeholder="0.6"
+          />
+        </SettingContainer>
+        <SettingContainer
+          title="Language Hint"
+          description="Language code the decoder uses instead of the selected language, e.g. en. Leave empty to use the selected language."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <ParamInput
+            value={params.language_hint ?? ""}
+            onSave={(text) =>
+              save({ language_hint: text === "" ? null : text })
+            }
+            disabled={disabled}
+            placeholder="none"
+          />
+        </SettingContainer>
+      </>
+    );
+  });
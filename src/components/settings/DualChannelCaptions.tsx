@@ -0,0 +1,27 @@
+import React from "react";
+import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { useSettings } from "../../hooks/useSettings";
+
+interface DualChannelCaptionsProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+export const DualChannelCaptions: React.FC<DualChannelCaptionsProps> =
+  React.memo(({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const enabled = getSetting("dual_channel_captions") ?? false;
+
+    return (
+      <ToggleSwitch
+        checked={enabled}
+        onChange={(enabled) => updateSetting("dual_channel_captions", enabled)}
+        isUpdating={isUpdating("dual_channel_captions")}
+        label="Separate Mic and System Captions"
+        description="Also caption your microphone while captioning system audio. Captions are labelled Me and Others, and history entries are tagged by channel."
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      />
+    );
+  });
@@ -0,0 +1,29 @@
+import React from "react";
+import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { useSettings } from "../../hooks/useSettings";
+
+interface HallucinationFilterProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+export const HallucinationFilter: React.FC<HallucinationFilterProps> =
+  React.memo(({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const enabled = getSetting("hallucination_filter_enabled") ?? true;
+
+    return (
+      <ToggleSwitch
+        checked={enabled}
+        onChange={(value) =>
+          updateSetting("hallucination_filter_enabled", value)
+        }
+        isUpdating={isUpdating("hallucination_filter_enabled")}
+        label="Filter Hallucinations"
+        description="Drop text the model invents over silence, like &quot;Thanks for watching&quot; or a phrase repeated over and over."
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      />
+    );
+  });
@@ -13,11 +13,13 @@ import { invoke } from "@tauri-apps/api/core";
 import { toast } from "sonner";
 
 interface HandyShortcutProps {
+  shortcutId?: string;
   descriptionMode?: "inline" | "tooltip";
   grouped?: boolean;
 }
 
 export const HandyShortcut: React.FC<HandyShortcutProps> = ({
+  shortcutId = "transcribe",
   descriptionMode = "tooltip",
   grouped = false,
 }) => {
@@ -267,17 +269,27 @@ export const HandyShortcut: React.FC<HandyShortcutProps> = ({
     );
   }
 
+  const isTranscribe = shortcutId === "transcribe";
+
   return (
     <SettingContainer
-      title="Handy Shortcut"
-      description="Set the keyboard shortcut to start and stop speech-to-text recording"
+      title={
+        isTranscribe
+          ? "Handy Shortcut"
+          : (bindings[shortcutId]?.name ?? "Shortcut")
+      }
+      description={
+        isTranscribe
+          ? "Set the keyboard shortcut to start and stop speech-to-text recording"
+          : (bindings[shortcutId]?.description ?? "")
+      }
       descriptionMode={descriptionMode}
       grouped={grouped}
       tooltipPosition="bottom"
     >
       {(() => {
-        const primaryBinding = Object.values(bindings)[0];
-        const primaryId = Object.keys(bindings)[0];
+        const primaryBinding = bindings[shortcutId];
+        const primaryId = shortcutId;
 
         if (!primaryBinding) {
           return (
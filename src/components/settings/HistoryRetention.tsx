@@ -0,0 +1,118 @@
+import React, { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { useSettings } from "../../hooks/useSettings";
+import { Button } from "../ui/Button";
+import { Input } from "../ui/Input";
+import { SettingContainer } from "../ui/SettingContainer";
+import {
+  DEFAULT_HISTORY_RETENTION,
+  type HistoryRetention,
+} from "../../lib/types";
+
+interface HistoryRetentionProps {
+  descriptionMode?: "tooltip" | "inline";
+  grouped?: boolean;
+}
+
+const limits: {
+  key: keyof HistoryRetention;
+  title: string;
+  description: string;
+  unit: string;
+}[] = [
+  {
+    key: "max_age_days",
+    title: "Max History Age",
+    description: "Delete unsaved entries older than this. 0 keeps them forever.",
+    unit: "days",
+  },
+  {
+    key: "max_entries",
+    title: "Max History Entries",
+    description: "Keep at most this many unsaved entries. 0 means no limit.",
+    unit: "entries",
+  },
+  {
+    key: "max_audio_mb",
+    title: "Max Recording Storage",
+    description: "Delete the oldest unsaved recordings once they take up more than this. 0 means no limit.",
+    unit: "MB",
+  },
+];
+
+export const HistoryRetentionSetting: React.FC<HistoryRetentionProps> = ({
+  descriptionMode = "inline",
+  grouped = false,
+}) => {
+  const { getSetting, updateSetting, isUpdating } = useSettings();
+  const [purging, setPurging] = useState(false);
+
+  const retention =
+    getSetting("history_retention") ?? DEFAULT_HISTORY_RETENTION;
+
+  const handleChange =
+    (key: keyof HistoryRetention) =>
+    (event: React.ChangeEvent<HTMLInputElement>) => {
+      const value = parseInt(event.target.value, 10);
+      if (!isNaN(value) && value >= 0) {
+        updateSetting("history_retention", { ...retention, [key]: value });
+      }
+    };
+
+  const handlePurge = async () => {
+    setPurging(true);
+    try {
+      const removed = await invoke<number>("purge_history", {
+        includeSaved: false,
+      });
+      toast.success(`Deleted ${removed} history entries`);
+    } catch (error) {
+      toast.error(`Failed to purge history: ${error}`);
+    } finally {
+      setPurging(false);
+    }
+  };
+
+  return (
+    <>
+      {limits.map(({ key, title, description, unit }) => (
+        <SettingContainer
+          key={key}
+          title={title}
+          description={description}
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+          layout="horizontal"
+        >
+          <div className="flex items-center space-x-2">
+            <Input
+              type="number"
+              min="0"
+              value={retention[key]}
+              onChange={handleChange(key)}
+              disabled={isUpdating("history_retention")}
+              className="w-24"
+            />
+            <span className="text-sm text-text">{unit}</span>
+          </div>
+        </SettingContainer>
+      ))}
+      <SettingContainer
+        title="Purge History"
+        description="Delete every unsaved entry and its recording now."
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      >
+        <Button
+          variant="danger"
+          size="sm"
+          onClick={handlePurge}
+          disabled={purging}
+        >
+          Purge
+        </Button>
+      </SettingContainer>
+    </>
+  );
+};
@@ -0,0 +1,83 @@
+import React, { useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Slider } from "../ui/Slider";
+import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { useSettings } from "../../hooks/useSettings";
+import { DEFAULT_INPUT_GAIN, type InputGain } from "../../lib/types";
+
+interface InputGainProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+// Every change reopens the microphone, so drags are saved once they settle
+const SAVE_DELAY_MS = 400;
+
+export const InputGainSetting: React.FC<InputGainProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, refreshSettings } = useSettings();
+
+    const selected = getSetting("selected_microphone");
+    const deviceId =
+      !selected || selected === "Default" ? "default" : selected;
+    const saved = getSetting("input_gains")?.[deviceId] ?? DEFAULT_INPUT_GAIN;
+
+    const [gain, setGain] = useState<InputGain>(saved);
+    const saveTimer = useRef<ReturnType<typeof setTimeout> | null>(null);
+
+    // Show the saved gain again when another device is selected
+    useEffect(() => {
+      setGain(saved);
+    }, [deviceId, saved.gain_db, saved.limiter]);
+
+    useEffect(
+      () => () => {
+        if (saveTimer.current) clearTimeout(saveTimer.current);
+      },
+      [],
+    );
+
+    const save = (update: Partial<InputGain>) => {
+      const next = { ...gain, ...update };
+      setGain(next);
+      if (saveTimer.current) clearTimeout(saveTimer.current);
+      saveTimer.current = setTimeout(async () => {
+        try {
+          await invoke("set_input_gain", {
+            deviceId,
+            gainDb: next.gain_db,
+            limiter: next.limiter,
+          });
+          await refreshSettings();
+        } catch (error) {
+          console.error("Failed to update input gain:", error);
+        }
+      }, SAVE_DELAY_MS);
+    };
+
+    return (
+      <>
+        <Slider
+          value={gain.gain_db}
+          onChange={(value) => save({ gain_db: value })}
+          min={-20}
+          max={30}
+          step={1}
+          label="Microphone Gain"
+          description="Boost for the selected microphone when it is too quiet even after normalization. Saved per device."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+          formatValue={(value) => `${value > 0 ? "+" : ""}${value} dB`}
+        />
+        <ToggleSwitch
+          checked={gain.limiter}
+          onChange={(enabled) => save({ limiter: enabled })}
+          label="Soft Limiter"
+          description="Gently compress peaks the boost would otherwise clip."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        />
+      </>
+    );
+  },
+);
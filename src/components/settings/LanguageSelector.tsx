@@ -17,7 +17,13 @@ export const LanguageSelector: React.FC<LanguageSelectorProps> = ({
   descriptionMode = "tooltip",
   grouped = false,
 }) => {
-  const { getSetting, updateSetting, resetSetting, isUpdating } = useSettings();
+  const {
+    getSetting,
+    updateSetting,
+    resetSetting,
+    isUpdating,
+    refreshSettings,
+  } = useSettings();
   const { currentModel, loadCurrentModel } = useModels();
   const [isOpen, setIsOpen] = useState(false);
   const [searchQuery, setSearchQuery] = useState("");
@@ -55,6 +61,17 @@ export const LanguageSelector: React.FC<LanguageSelectorProps> = ({
     };
   }, [loadCurrentModel]);
 
+  // The cycle-language shortcut changes the language from the backend
+  useEffect(() => {
+    const languageUnlisten = listen("language-changed", () => {
+      refreshSettings();
+    });
+
+    return () => {
+      languageUnlisten.then((fn) => fn());
+    };
+  }, [refreshSettings]);
+
   useEffect(() => {
     if (isOpen && searchInputRef.current) {
       searchInputRef.current.focus();
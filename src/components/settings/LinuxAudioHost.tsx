@@ -0,0 +1,47 @@
+import React from "react";
+import { type as getOsType } from "@tauri-apps/plugin-os";
+import { Dropdown } from "../ui/Dropdown";
+import { SettingContainer } from "../ui/SettingContainer";
+import { useSettings } from "../../hooks/useSettings";
+import type { LinuxAudioHost } from "../../lib/types";
+
+interface LinuxAudioHostProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+const options = [
+  { value: "alsa", label: "ALSA" },
+  { value: "jack", label: "JACK / PipeWire" },
+];
+
+export const LinuxAudioHostSetting: React.FC<LinuxAudioHostProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating, refreshAudioDevices } =
+      useSettings();
+
+    if (getOsType() !== "linux") return null;
+
+    const select = async (value: string) => {
+      await updateSetting("linux_audio_host", value as LinuxAudioHost);
+      // Each host lists its own devices
+      await refreshAudioDevices();
+    };
+
+    return (
+      <SettingContainer
+        title="Audio Host"
+        description="Sound server microphones are opened through. JACK, or PipeWire through pipewire-jack, adds Handy to the audio graph so any port can be connected to its inputs."
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      >
+        <Dropdown
+          options={options}
+          selectedValue={getSetting("linux_audio_host") ?? "alsa"}
+          onSelect={select}
+          disabled={isUpdating("linux_audio_host")}
+        />
+      </SettingContainer>
+    );
+  },
+);
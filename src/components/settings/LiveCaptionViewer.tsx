@@ -1,12 +1,17 @@
 import React, { useEffect, useState, useRef } from "react";
 import { listen } from "@tauri-apps/api/event";
+import { invoke } from "@tauri-apps/api/core";
 import { useSettings } from "../../hooks/useSettings";
 import { SettingsGroup } from "../ui/SettingsGroup";
 import { toast } from "sonner";
+import type { CaptionSegment, LiveCaptionUpdate, LogEntry } from "../../lib/types";
 
 export const LiveCaptionViewer: React.FC = () => {
   const { settings } = useSettings();
   const [caption, setCaption] = useState<string>("");
+  // Provisional captions may still change once the next window confirms them
+  const [isProvisional, setIsProvisional] = useState<boolean>(false);
+  const [translation, setTranslation] = useState<string | null>(null);
   const [logs, setLogs] = useState<Array<{ time: string; message: string; type: 'info' | 'warn' | 'error' | 'debug' }>>([]);
   const logEndRef = useRef<HTMLDivElement>(null);
   const logContainerRef = useRef<HTMLDivElement>(null);
@@ -45,16 +50,20 @@ export const LiveCaptionViewer: React.FC = () => {
 
     // Store cleanup functions
     let cleanupCaption: (() => void) | null = null;
+    let cleanupProvisional: (() => void) | null = null;
+    let cleanupSegment: (() => void) | null = null;
     let cleanupLog: (() => void) | null = null;
 
-    const unlistenCaption = listen<string>("live-caption-update", (event) => {
-      const newCaption = event.payload.trim();
-      console.log(`🎯 [LiveCaptionViewer] Event received! Payload length: ${event.payload.length}, trimmed: ${newCaption.length}, content: "${newCaption.substring(0, 50)}${newCaption.length > 50 ? '...' : ''}"`);
-      addLog('info', `🎯 [LiveCaptionViewer] Event received (${event.payload.length} chars raw, ${newCaption.length} chars trimmed)`);
+    const unlistenCaption = listen<LiveCaptionUpdate>("live-caption-update", (event) => {
+      const newCaption = event.payload.text.trim();
+      console.log(`🎯 [LiveCaptionViewer] Event received! Payload length: ${event.payload.text.length}, trimmed: ${newCaption.length}, content: "${newCaption.substring(0, 50)}${newCaption.length > 50 ? '...' : ''}"`);
+      addLog('info', `🎯 [LiveCaptionViewer] Event received (${event.payload.text.length} chars raw, ${newCaption.length} chars trimmed)`);
       
       if (newCaption && newCaption.length > 1) {
         console.log(`✅ [LiveCaptionViewer] Setting caption: "${newCaption}"`);
         setCaption(newCaption);
+        setTranslation(event.payload.translation);
+        setIsProvisional(false);
         addLog('info', `✅ Caption set: "${newCaption}"`);
       } else {
         console.warn(`⚠️ [LiveCaptionViewer] Caption too short or empty: length=${newCaption.length}`);
@@ -68,29 +77,42 @@ export const LiveCaptionViewer: React.FC = () => {
       console.error("❌ [LiveCaptionViewer] Failed to register caption listener:", err);
     });
 
-    // Throttle log listener to prevent UI lag when too many logs come in
-    let lastLogTime = 0;
-    const LOG_THROTTLE_MS = 500; // Only process logs max once per 500ms (increased to reduce lag)
-    
-    const unlistenLog = listen<string>("log-update", (event) => {
-      const now = Date.now();
-      if (now - lastLogTime < LOG_THROTTLE_MS) {
-        return; // Skip if too frequent
+    const unlistenProvisional = listen<string>("live-caption-provisional", (event) => {
+      const newCaption = event.payload.trim();
+      if (newCaption.length > 1) {
+        setCaption(newCaption);
+        setTranslation(null);
+        setIsProvisional(true);
       }
-      lastLogTime = now;
-      
-      const logMessage = event.payload.trim();
+    });
+
+    unlistenProvisional.then((fn) => {
+      cleanupProvisional = fn;
+    }).catch((err) => {
+      console.error("❌ [LiveCaptionViewer] Failed to register provisional caption listener:", err);
+    });
+
+    const unlistenSegment = listen<CaptionSegment>("caption-segment", (event) => {
+      const { text, start, end } = event.payload;
+      addLog('info', `📝 [Caption] ${start.toFixed(1)}s–${end.toFixed(1)}s (${text.length} chars): ${text.substring(0, 50)}`);
+    });
+
+    unlistenSegment.then((fn) => {
+      cleanupSegment = fn;
+    }).catch((err) => {
+      console.error("❌ [LiveCaptionViewer] Failed to register caption segment listener:", err);
+    });
+
+    invoke("subscribe_logs").catch((err) => {
+      console.error("❌ [LiveCaptionViewer] Failed to subscribe to logs:", err);
+    });
+
+    // The backend batches log entries, a few times a second at most
+    const handleLog = (entry: LogEntry) => {
+      const logMessage = entry.message.trim();
       if (logMessage) {
-        // Determine log type from message
-        let logType: 'info' | 'warn' | 'error' | 'debug' = 'info';
-        if (logMessage.includes('❌') || logMessage.includes('error') || logMessage.includes('Error') || logMessage.includes('Failed')) {
-          logType = 'error';
-        } else if (logMessage.includes('⚠️') || logMessage.includes('warn') || logMessage.includes('Warn')) {
-          logType = 'warn';
-        } else if (logMessage.includes('debug') || logMessage.includes('Debug')) {
-          logType = 'debug';
-        }
-        addLog(logType, logMessage);
+        const { level } = entry;
+        addLog(level === 'trace' ? 'debug' : level, logMessage);
 
         // Show popup for permission status (only for important messages)
         if (logMessage.includes('PERMISSION DENIED') || logMessage.includes('❌ PERMISSION DENIED')) {
@@ -110,6 +132,10 @@ export const LiveCaptionViewer: React.FC = () => {
           });
         }
       }
+    };
+
+    const unlistenLog = listen<LogEntry[]>("log-entry", (event) => {
+      event.payload.forEach(handleLog);
     });
 
     unlistenLog.then((fn) => {
@@ -119,6 +145,7 @@ export const LiveCaptionViewer: React.FC = () => {
     });
 
     return () => {
+      invoke("unsubscribe_logs").catch(() => {});
       if (cleanupCaption && typeof cleanupCaption === 'function') {
         try {
           cleanupCaption();
@@ -126,6 +153,20 @@ export const LiveCaptionViewer: React.FC = () => {
           console.warn("⚠️ [LiveCaptionViewer] Error cleaning up caption listener:", err);
         }
       }
+      if (cleanupProvisional && typeof cleanupProvisional === 'function') {
+        try {
+          cleanupProvisional();
+        } catch (err) {
+          console.warn("⚠️ [LiveCaptionViewer] Error cleaning up provisional caption listener:", err);
+        }
+      }
+      if (cleanupSegment && typeof cleanupSegment === 'function') {
+        try {
+          cleanupSegment();
+        } catch (err) {
+          console.warn("⚠️ [LiveCaptionViewer] Error cleaning up caption segment listener:", err);
+        }
+      }
       if (cleanupLog && typeof cleanupLog === 'function') {
         try {
           cleanupLog();
@@ -245,8 +286,15 @@ export const LiveCaptionViewer: React.FC = () => {
           <div className="bg-background-dark rounded-lg p-4 border border-mid-gray/20">
             <div className="text-sm text-text/70 mb-2">Current Caption:</div>
             {caption ? (
-              <div className="text-lg font-medium text-text break-words">
+              <div
+                className={`text-lg font-medium break-words ${isProvisional ? "text-text/60 italic" : "text-text"}`}
+              >
                 {caption}
+                {translation && (
+                  <div className="text-base font-normal text-text/70 mt-1">
+                    {translation}
+                  </div>
+                )}
               </div>
                   ) : (
                     <div className="text-sm text-text/50 italic">
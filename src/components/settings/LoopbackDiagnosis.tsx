@@ -0,0 +1,94 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import { Button } from "../ui/Button";
+import { SettingsGroup } from "../ui/SettingsGroup";
+import type { LoopbackDiagnosis as Diagnosis } from "../../lib/types";
+
+// Checklist shown when the loopback device (e.g. BlackHole) only delivers silence
+export const LoopbackDiagnosis: React.FC = () => {
+  const [diagnosis, setDiagnosis] = useState<Diagnosis | null>(null);
+  const [settingUp, setSettingUp] = useState(false);
+
+  useEffect(() => {
+    const unlisten = listen<Diagnosis>("loopback-diagnosis", (event) => {
+      setDiagnosis(event.payload);
+    });
+    // Audio arriving means whatever was wrong has been fixed
+    const unlistenCaption = listen("live-caption-update", () => {
+      setDiagnosis(null);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+      unlistenCaption.then((fn) => fn());
+    };
+  }, []);
+
+  if (!diagnosis) return null;
+
+  const setUpRouting = async () => {
+    setSettingUp(true);
+    try {
+      const speakers = await invoke<string | null>("setup_blackhole_routing", {
+        enabled: true,
+      });
+      toast.success(
+        `System output now plays through ${speakers} and BlackHole`,
+      );
+    } catch (error) {
+      toast.error(String(error));
+    } finally {
+      setSettingUp(false);
+    }
+  };
+
+  return (
+    <SettingsGroup title="System Audio Diagnosis">
+      <div className="p-4 space-y-3">
+        <p className="text-sm text-text/70">
+          {diagnosis.device} ({diagnosis.sample_rate}Hz, {diagnosis.channels}{" "}
+          channels, {diagnosis.sample_format}) delivered{" "}
+          {diagnosis.frames_received} frames of silence
+          {diagnosis.default_output &&
+            ` while the system plays to ${diagnosis.default_output}`}
+          .
+        </p>
+        <ul className="space-y-2">
+          {diagnosis.checks.map((check) => (
+            <li key={check.id} className="flex gap-3">
+              <span>{check.passed ? "✅" : "❌"}</span>
+              <div className="flex-1">
+                <p className="text-sm text-text">{check.label}</p>
+                {check.hint && (
+                  <p className="text-xs text-text/60">{check.hint}</p>
+                )}
+              </div>
+              {check.id === "output_routing" && !check.passed && (
+                <Button
+                  variant="secondary"
+                  size="sm"
+                  onClick={setUpRouting}
+                  disabled={settingUp}
+                >
+                  {settingUp ? "Setting up…" : "Set Up Routing"}
+                </Button>
+              )}
+            </li>
+          ))}
+        </ul>
+        <p className="text-xs text-text/50">
+          {diagnosis.channel_activity
+            .map(
+              (channel) =>
+                `Channel ${channel.channel}: ${
+                  channel.active ? `peak ${channel.peak.toFixed(4)}` : "silent"
+                }`,
+            )
+            .join(" · ")}
+        </p>
+      </div>
+    </SettingsGroup>
+  );
+};
@@ -0,0 +1,32 @@
+import React from "react";
+import { Slider } from "../ui/Slider";
+import { useSettings } from "../../hooks/useSettings";
+
+interface MaxRecordingDurationProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+export const MaxRecordingDuration: React.FC<MaxRecordingDurationProps> =
+  React.memo(({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting } = useSettings();
+
+    const maxRecordingSecs = getSetting("max_recording_secs") ?? 600;
+
+    return (
+      <Slider
+        value={maxRecordingSecs}
+        onChange={(value: number) =>
+          updateSetting("max_recording_secs", value)
+        }
+        min={0}
+        max={3600}
+        step={60}
+        label="Maximum Recording Length"
+        description="Recordings are stopped and transcribed once they reach this length, so a forgotten recording doesn't grow too long to transcribe."
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+        formatValue={(value) => (value > 0 ? `${value / 60} min` : "No limit")}
+      />
+    );
+  });
@@ -1,8 +1,10 @@
-import React from "react";
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
 import { Dropdown } from "../ui/Dropdown";
 import { SettingContainer } from "../ui/SettingContainer";
 import { ResetButton } from "../ui/ResetButton";
 import { useSettings } from "../../hooks/useSettings";
+import type { DeviceCapabilities } from "../../lib/types";
 
 interface MicrophoneSelectorProps {
   descriptionMode?: "inline" | "tooltip";
@@ -25,6 +27,28 @@ export const MicrophoneSelector: React.FC<MicrophoneSelectorProps> = React.memo(
       getSetting("selected_microphone") === "default"
         ? "Default"
         : getSetting("selected_microphone") || "Default";
+    const downmix = getSetting("microphone_downmix");
+
+    const [warnings, setWarnings] = useState<string[]>([]);
+
+    // Warn about settings the selected device can't satisfy
+    useEffect(() => {
+      let cancelled = false;
+      invoke<DeviceCapabilities>("get_device_capabilities", {
+        deviceId: selectedMicrophone,
+        kind: "input",
+      })
+        .then((capabilities) => {
+          if (!cancelled) setWarnings(capabilities.warnings);
+        })
+        .catch((error) => {
+          console.error("Failed to get device capabilities:", error);
+          if (!cancelled) setWarnings([]);
+        });
+      return () => {
+        cancelled = true;
+      };
+    }, [selectedMicrophone, downmix]);
 
     const handleMicrophoneSelect = async (deviceName: string) => {
       await updateSetting("selected_microphone", deviceName);
@@ -35,8 +59,8 @@ export const MicrophoneSelector: React.FC<MicrophoneSelectorProps> = React.memo(
     };
 
     const microphoneOptions = audioDevices.map((device) => ({
-      value: device.name,
-      label: device.name,
+      value: device.id,
+      label: device.alias || device.name,
     }));
 
     return (
@@ -68,6 +92,11 @@ export const MicrophoneSelector: React.FC<MicrophoneSelectorProps> = React.memo(
             disabled={isUpdating("selected_microphone") || isLoading}
           />
         </div>
+        {warnings.map((warning) => (
+          <p key={warning} className="text-xs text-red-500">
+            {warning}
+          </p>
+        ))}
       </SettingContainer>
     );
   },
@@ -1,5 +1,8 @@
 import React from "react";
 import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { Dropdown } from "../ui/Dropdown";
+import { SettingContainer } from "../ui/SettingContainer";
+import { Slider } from "../ui/Slider";
 import { useSettings } from "../../hooks/useSettings";
 
 interface MuteWhileRecordingToggleProps {
@@ -7,21 +10,63 @@ interface MuteWhileRecordingToggleProps {
   grouped?: boolean;
 }
 
+const muteModeOptions = [
+  { value: "mute", label: "Mute" },
+  { value: "duck", label: "Lower Volume" },
+];
+
 export const MuteWhileRecording: React.FC<MuteWhileRecordingToggleProps> =
   React.memo(({ descriptionMode = "tooltip", grouped = false }) => {
     const { getSetting, updateSetting, isUpdating } = useSettings();
 
     const muteEnabled = getSetting("mute_while_recording") ?? false;
+    const muteMode = getSetting("mute_mode") ?? "mute";
+    const duckPercent = getSetting("duck_percent") ?? 70;
 
     return (
-      <ToggleSwitch
-        checked={muteEnabled}
-        onChange={(enabled) => updateSetting("mute_while_recording", enabled)}
-        isUpdating={isUpdating("mute_while_recording")}
-        label="Mute While Recording"
-        description="Automatically mute all sound output while Handy is recording, then restore it when finished."
-        descriptionMode={descriptionMode}
-        grouped={grouped}
-      />
+      <>
+        <ToggleSwitch
+          checked={muteEnabled}
+          onChange={(enabled) =>
+            updateSetting("mute_while_recording", enabled)
+          }
+          isUpdating={isUpdating("mute_while_recording")}
+          label="Mute While Recording"
+          description="Automatically mute or lower all sound output while Handy is recording, then restore the previous volume when finished."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        />
+        {muteEnabled && (
+          <SettingContainer
+            title="While Recording"
+            description="Mute the output entirely, or only lower its volume so you can still hear it."
+            descriptionMode={descriptionMode}
+            grouped={grouped}
+          >
+            <Dropdown
+              options={muteModeOptions}
+              selectedValue={muteMode}
+              onSelect={(value) =>
+                updateSetting("mute_mode", value as "mute" | "duck")
+              }
+              disabled={isUpdating("mute_mode")}
+            />
+          </SettingContainer>
+        )}
+        {muteEnabled && muteMode === "duck" && (
+          <Slider
+            value={duckPercent}
+            onChange={(value) => updateSetting("duck_percent", value)}
+            min={10}
+            max={100}
+            step={5}
+            label="Lower Volume By"
+            description="How much quieter the output gets while recording."
+            descriptionMode={descriptionMode}
+            grouped={grouped}
+            formatValue={(value) => `${value}%`}
+          />
+        )}
+      </>
     );
   });
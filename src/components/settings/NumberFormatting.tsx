@@ -0,0 +1,101 @@
+import React from "react";
+import { Dropdown } from "../ui/Dropdown";
+import { SettingContainer } from "../ui/SettingContainer";
+import { useSettings } from "../../hooks/useSettings";
+import type { NumberFormatting } from "../../lib/types";
+
+interface NumberFormattingProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+const defaultFormatting: NumberFormatting = {
+  style: "as_transcribed",
+  thousands_separator: "comma",
+  date_format: "as_transcribed",
+};
+
+const styleOptions = [
+  { value: "as_transcribed", label: "As Transcribed" },
+  { value: "digits", label: "Digits ($25)" },
+  { value: "words", label: "Words (twenty-five)" },
+];
+
+const separatorOptions = [
+  { value: "comma", label: "10,000" },
+  { value: "period", label: "10.000" },
+  { value: "space", label: "10 000" },
+  { value: "none", label: "10000" },
+];
+
+const dateOptions = [
+  { value: "as_transcribed", label: "As Transcribed" },
+  { value: "long", label: "March 5, 2024" },
+  { value: "iso", label: "2024-03-05" },
+  { value: "us", label: "3/5/2024" },
+  { value: "european", label: "5/3/2024" },
+];
+
+export const NumberFormattingSetting: React.FC<NumberFormattingProps> =
+  React.memo(({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const formatting = getSetting("number_formatting") ?? defaultFormatting;
+    const disabled = isUpdating("number_formatting");
+
+    const save = (patch: Partial<NumberFormatting>) =>
+      updateSetting("number_formatting", { ...formatting, ...patch });
+
+    return (
+      <>
+        <SettingContainer
+          title="Numbers"
+          description="Write spoken numbers as digits, with currency and percentages, or spell numbers out as words. Applies to pasted text and live captions."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <Dropdown
+            options={styleOptions}
+            selectedValue={formatting.style}
+            onSelect={(value) =>
+              save({ style: value as NumberFormatting["style"] })
+            }
+            disabled={disabled}
+          />
+        </SettingContainer>
+        <SettingContainer
+          title="Thousands Separator"
+          description="How digits of numbers from 10,000 up are grouped. Four-digit numbers stay ungrouped so years read naturally."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <Dropdown
+            options={separatorOptions}
+            selectedValue={formatting.thousands_separator}
+            onSelect={(value) =>
+              save({
+                thousands_separator:
+                  value as NumberFormatting["thousands_separator"],
+              })
+            }
+            disabled={disabled || formatting.style !== "digits"}
+          />
+        </SettingContainer>
+        <SettingContainer
+          title="Dates"
+          description="Rewrite dates like March 5th, 2024 or the fifth of March in one format."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <Dropdown
+            options={dateOptions}
+            selectedValue={formatting.date_format}
+            onSelect={(value) =>
+              save({ date_format: value as NumberFormatting["date_format"] })
+            }
+            disabled={disabled}
+          />
+        </SettingContainer>
+      </>
+    );
+  });
@@ -38,8 +38,8 @@ export const OutputDeviceSelector: React.FC<OutputDeviceSelectorProps> =
       };
 
       const outputDeviceOptions = outputDevices.map((device: AudioDevice) => ({
-        value: device.name,
-        label: device.name,
+        value: device.id,
+        label: device.alias || device.name,
       }));
 
       return (
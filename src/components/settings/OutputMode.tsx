@@ -0,0 +1,89 @@
+import React, { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Dropdown } from "../ui/Dropdown";
+import { SettingContainer } from "../ui/SettingContainer";
+import { useSettings } from "../../hooks/useSettings";
+import type { OutputMode } from "../../lib/types";
+
+interface OutputModeProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+const modeOptions = [
+  { value: "paste", label: "Paste" },
+  { value: "type", label: "Type" },
+  { value: "clipboard_only", label: "Clipboard only" },
+  { value: "none", label: "None (history only)" },
+];
+
+// Bindings without an override follow the global output mode
+const DEFAULT_MODE = "default";
+
+export const OutputModeSetting: React.FC<OutputModeProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating, refreshSettings } =
+      useSettings();
+    const [updatingBinding, setUpdatingBinding] = useState<string | null>(
+      null,
+    );
+
+    const selectedMode = (getSetting("output_mode") || "paste") as OutputMode;
+    const bindings = getSetting("bindings") || {};
+    const overrides = getSetting("binding_output_mode") || {};
+
+    const updateBindingMode = async (bindingId: string, value: string) => {
+      setUpdatingBinding(bindingId);
+      try {
+        await invoke("change_binding_output_mode_setting", {
+          bindingId,
+          mode: value === DEFAULT_MODE ? null : value,
+        });
+        await refreshSettings();
+      } catch (error) {
+        console.error("Failed to update binding output mode:", error);
+      } finally {
+        setUpdatingBinding(null);
+      }
+    };
+
+    return (
+      <>
+        <SettingContainer
+          title="Output Mode"
+          description="Where finished transcriptions and live captions go: pasted, typed out, only copied to the clipboard, or kept in history and the transcript file only."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <Dropdown
+            options={modeOptions}
+            selectedValue={selectedMode}
+            onSelect={(value) =>
+              updateSetting("output_mode", value as OutputMode)
+            }
+            disabled={isUpdating("output_mode")}
+          />
+        </SettingContainer>
+        {Object.values(bindings).map((binding) => (
+          <SettingContainer
+            key={binding.id}
+            title={`${binding.name} Output`}
+            description={`Output mode for the ${binding.name} shortcut, overriding Output Mode.`}
+            descriptionMode={descriptionMode}
+            grouped={grouped}
+          >
+            <Dropdown
+              options={[
+                { value: DEFAULT_MODE, label: "Same as Output Mode" },
+                ...modeOptions,
+              ]}
+              selectedValue={overrides[binding.id] ?? DEFAULT_MODE}
+              onSelect={(value) => updateBindingMode(binding.id, value)}
+              disabled={updatingBinding === binding.id}
+            />
+          </SettingContainer>
+        ))}
+      </>
+    );
+  },
+);
@@ -0,0 +1,53 @@
+import React, { useEffect, useState } from "react";
+import { Input } from "../ui/Input";
+import { SettingContainer } from "../ui/SettingContainer";
+import { useSettings } from "../../hooks/useSettings";
+
+interface PasteBlacklistProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+export const PasteBlacklist: React.FC<PasteBlacklistProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const apps = getSetting("paste_blacklist") ?? [];
+    const savedText = apps.join(", ");
+    const [appsText, setAppsText] = useState(savedText);
+
+    useEffect(() => {
+      setAppsText(savedText);
+    }, [savedText]);
+
+    const saveApps = () => {
+      const parsed = appsText
+        .split(",")
+        .map((app) => app.trim().toLowerCase())
+        .filter((app) => app.length > 0);
+      if (parsed.join(", ") !== savedText) {
+        updateSetting("paste_blacklist", parsed);
+      }
+    };
+
+    return (
+      <SettingContainer
+        title="Never Paste Into"
+        description="Comma-separated app names or bundle ids, e.g. password managers and terminals. When one of them is focused the text is copied to the clipboard instead of pasted."
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      >
+        <Input
+          type="text"
+          value={appsText}
+          onChange={(event) => setAppsText(event.target.value)}
+          onBlur={saveApps}
+          placeholder="none"
+          variant="compact"
+          disabled={isUpdating("paste_blacklist")}
+          className="w-56"
+        />
+      </SettingContainer>
+    );
+  },
+);
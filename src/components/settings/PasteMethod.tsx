@@ -24,6 +24,14 @@ const getPasteMethodOptions = (osType: string) => {
     });
   }
 
+  // Accessibility API insertion is macOS only
+  if (osType === "macos") {
+    baseOptions.push({
+      value: "accessibility",
+      label: "Accessibility API",
+    });
+  }
+
   return baseOptions;
 };
 
@@ -44,7 +52,7 @@ export const PasteMethodSetting: React.FC<PasteMethodProps> = React.memo(
     return (
       <SettingContainer
         title="Paste Method"
-        description="Clipboard (Ctrl+V) simulates Ctrl/Cmd+V keystrokes to paste from your clipboard. Direct tries to use system input methods if possible, otherwise inputs keystrokes one by one into the text field. Clipboard (Shift+Insert) uses the more universal Shift+Insert shortcut, ideal for terminal applications and SSH clients."
+        description="Clipboard (Ctrl+V) simulates Ctrl/Cmd+V keystrokes to paste from your clipboard. Direct tries to use system input methods if possible, otherwise inputs keystrokes one by one into the text field. Clipboard (Shift+Insert) uses the more universal Shift+Insert shortcut, ideal for terminal applications and SSH clients. Accessibility API (macOS) inserts text into the focused field without touching the clipboard, and works in apps that block paste."
         descriptionMode={descriptionMode}
         grouped={grouped}
         tooltipPosition="bottom"
@@ -0,0 +1,33 @@
+import React from "react";
+import { Slider } from "../ui/Slider";
+import { useSettings } from "../../hooks/useSettings";
+
+interface PreRollProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+export const PreRoll: React.FC<PreRollProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting } = useSettings();
+
+    const preRollMs = getSetting("pre_roll_ms") ?? 1000;
+    const alwaysOnMode = getSetting("always_on_microphone") || false;
+
+    return (
+      <Slider
+        value={preRollMs}
+        onChange={(value: number) => updateSetting("pre_roll_ms", value)}
+        min={0}
+        max={3000}
+        step={500}
+        label="Pre-Roll"
+        description="Audio from just before the shortcut is pressed that is kept at the start of a recording, so the first word isn't cut off. Needs the always-on microphone."
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+        formatValue={(value) => (value > 0 ? `${value / 1000}s` : "Off")}
+        disabled={!alwaysOnMode}
+      />
+    );
+  },
+);
@@ -0,0 +1,78 @@
+import React, { useEffect, useState } from "react";
+import { Dropdown } from "../ui/Dropdown";
+import { Input } from "../ui/Input";
+import { SettingContainer } from "../ui/SettingContainer";
+import { useSettings } from "../../hooks/useSettings";
+import type { ProfanityFilter } from "../../lib/types";
+
+interface ProfanityFilterProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+const filterOptions = [
+  { value: "keep", label: "Keep" },
+  { value: "mask", label: "Mask (s***)" },
+  { value: "remove", label: "Remove" },
+];
+
+export const ProfanityFilterSetting: React.FC<ProfanityFilterProps> =
+  React.memo(({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const filter = (getSetting("profanity_filter") ?? "keep") as ProfanityFilter;
+    const words = getSetting("profanity_words") ?? [];
+    const savedText = words.join(", ");
+    const [wordsText, setWordsText] = useState(savedText);
+
+    useEffect(() => {
+      setWordsText(savedText);
+    }, [savedText]);
+
+    const saveWords = () => {
+      const parsed = wordsText
+        .split(",")
+        .map((word) => word.trim().toLowerCase())
+        .filter((word) => word.length > 0);
+      if (parsed.join(", ") !== savedText) {
+        updateSetting("profanity_words", parsed);
+      }
+    };
+
+    return (
+      <>
+        <SettingContainer
+          title="Profanity Filter"
+          description="Mask or remove swear words before text is pasted or shown as a live caption, e.g. when captioning a stream."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <Dropdown
+            options={filterOptions}
+            selectedValue={filter}
+            onSelect={(value) =>
+              updateSetting("profanity_filter", value as ProfanityFilter)
+            }
+            disabled={isUpdating("profanity_filter")}
+          />
+        </SettingContainer>
+        <SettingContainer
+          title="Extra Blocked Words"
+          description="Comma-separated words filtered on top of the built-in list."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <Input
+            type="text"
+            value={wordsText}
+            onChange={(event) => setWordsText(event.target.value)}
+            onBlur={saveWords}
+            placeholder="none"
+            variant="compact"
+            disabled={filter === "keep" || isUpdating("profanity_words")}
+            className="w-56"
+          />
+        </SettingContainer>
+      </>
+    );
+  });
@@ -0,0 +1,89 @@
+import React, { useEffect, useState } from "react";
+import { Input } from "../ui/Input";
+import { SettingContainer } from "../ui/SettingContainer";
+import { useSettings } from "../../hooks/useSettings";
+
+interface PunctuationModelProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+// Field that saves its text when it loses focus
+const BlurInput: React.FC<{
+  value: string;
+  onSave: (value: string) => void;
+  disabled: boolean;
+  placeholder: string;
+  className: string;
+}> = ({ value, onSave, disabled, placeholder, className }) => {
+  const [localValue, setLocalValue] = useState(value);
+
+  useEffect(() => {
+    setLocalValue(value);
+  }, [value]);
+
+  return (
+    <Input
+      type="text"
+      value={localValue}
+      onChange={(event) => setLocalValue(event.target.value)}
+      onBlur={() => localValue !== value && onSave(localValue.trim())}
+      placeholder={placeholder}
+      variant="compact"
+      disabled={disabled}
+      className={className}
+    />
+  );
+};
+
+export const PunctuationModel: React.FC<PunctuationModelProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const modelPath = getSetting("punctuation_model_path") ?? "";
+    const languages = getSetting("punctuation_languages") ?? ["en"];
+
+    return (
+      <>
+        <SettingContainer
+          title="Punctuation Model"
+          description="Folder with a punctuation and capitalization model (model.onnx and config.json), applied to transcripts that come back lowercase and unpunctuated. Leave empty to turn it off."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <BlurInput
+            value={modelPath}
+            onSave={(text) =>
+              updateSetting("punctuation_model_path", text || null)
+            }
+            disabled={isUpdating("punctuation_model_path")}
+            placeholder="No model"
+            className="w-56"
+          />
+        </SettingContainer>
+        <SettingContainer
+          title="Punctuation Languages"
+          description="Comma-separated language codes the punctuation model runs for. Add auto to also run it when the language is detected automatically."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <BlurInput
+            value={languages.join(", ")}
+            onSave={(text) =>
+              updateSetting(
+                "punctuation_languages",
+                text
+                  .split(",")
+                  .map((code) => code.trim())
+                  .filter((code) => code.length > 0),
+              )
+            }
+            disabled={!modelPath || isUpdating("punctuation_languages")}
+            placeholder="en"
+            className="w-32"
+          />
+        </SettingContainer>
+      </>
+    );
+  },
+);
@@ -0,0 +1,29 @@
+import React from "react";
+import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { useSettings } from "../../hooks/useSettings";
+
+interface SpeakerDiarizationProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+export const SpeakerDiarization: React.FC<SpeakerDiarizationProps> =
+  React.memo(({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const enabled = getSetting("speaker_diarization_enabled") ?? false;
+
+    return (
+      <ToggleSwitch
+        checked={enabled}
+        onChange={(enabled) =>
+          updateSetting("speaker_diarization_enabled", enabled)
+        }
+        isUpdating={isUpdating("speaker_diarization_enabled")}
+        label="Label Speakers"
+        description="Guess who is talking in system audio from the pitch and tone of each caption, and label captions Speaker 1, Speaker 2, and so on. This is a rough heuristic, not a trained voice model: similar voices may share a label, one voice may get two, and overlapping speech gets one label. Works best with clear audio and one person talking at a time."
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      />
+    );
+  });
@@ -0,0 +1,221 @@
+import React, { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Button } from "../ui/Button";
+import { Dropdown } from "../ui/Dropdown";
+import { Input } from "../ui/Input";
+import { SettingContainer } from "../ui/SettingContainer";
+import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { useSettings } from "../../hooks/useSettings";
+import type { SpokenCommand } from "../../lib/types";
+
+interface SpokenFormattingProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+const kindOptions = [
+  { value: "punctuation", label: "Punctuation" },
+  { value: "opening", label: "Opening mark" },
+  { value: "break", label: "Line break" },
+  { value: "text", label: "Text" },
+  { value: "caps_on", label: "Caps on" },
+  { value: "caps_off", label: "Caps off" },
+  { value: "capitalize_next", label: "Capitalize next" },
+];
+
+// Bindings without an override follow the global toggle
+const DEFAULT_OVERRIDE = "default";
+
+const overrideOptions = [
+  { value: DEFAULT_OVERRIDE, label: "Same as Spoken Formatting" },
+  { value: "on", label: "On" },
+  { value: "off", label: "Off" },
+];
+
+const describeCommand = (command: SpokenCommand) => {
+  const kind = kindOptions.find((option) => option.value === command.kind);
+  if (!("text" in command)) {
+    return kind?.label ?? command.kind;
+  }
+  return `${kind?.label ?? command.kind}: ${JSON.stringify(command.text)}`;
+};
+
+// Text typed in the editor, with \n standing for a line break
+const toCommand = (kind: string, text: string): SpokenCommand | null => {
+  const value = text.replace(/\\n/g, "\n");
+  switch (kind) {
+    case "punctuation":
+    case "opening":
+    case "break":
+    case "text":
+      return value ? { kind, text: value } : null;
+    case "caps_on":
+    case "caps_off":
+    case "capitalize_next":
+      return { kind };
+    default:
+      return null;
+  }
+};
+
+export const SpokenFormatting: React.FC<SpokenFormattingProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating, refreshSettings } =
+      useSettings();
+    const [updatingBinding, setUpdatingBinding] = useState<string | null>(
+      null,
+    );
+    const [phrase, setPhrase] = useState("");
+    const [kind, setKind] = useState("punctuation");
+    const [text, setText] = useState("");
+
+    const enabled = getSetting("spoken_formatting_enabled") ?? false;
+    const bindings = getSetting("bindings") || {};
+    const overrides = getSetting("binding_spoken_formatting") || {};
+    const commands = getSetting("spoken_commands") || {};
+
+    const updateBindingOverride = async (bindingId: string, value: string) => {
+      setUpdatingBinding(bindingId);
+      try {
+        await invoke("change_binding_spoken_formatting_setting", {
+          bindingId,
+          enabled: value === DEFAULT_OVERRIDE ? null : value === "on",
+        });
+        await refreshSettings();
+      } catch (error) {
+        console.error("Failed to update binding spoken formatting:", error);
+      } finally {
+        setUpdatingBinding(null);
+      }
+    };
+
+    const newCommand = toCommand(kind, text);
+    const trimmedPhrase = phrase.trim().toLowerCase();
+
+    const handleAdd = () => {
+      if (!trimmedPhrase || !newCommand) {
+        return;
+      }
+      updateSetting("spoken_commands", {
+        ...commands,
+        [trimmedPhrase]: newCommand,
+      });
+      setPhrase("");
+      setText("");
+    };
+
+    const handleRemove = (phraseToRemove: string) => {
+      updateSetting(
+        "spoken_commands",
+        Object.fromEntries(
+          Object.entries(commands).filter(([key]) => key !== phraseToRemove),
+        ),
+      );
+    };
+
+    return (
+      <>
+        <ToggleSwitch
+          checked={enabled}
+          onChange={(value) =>
+            updateSetting("spoken_formatting_enabled", value)
+          }
+          isUpdating={isUpdating("spoken_formatting_enabled")}
+          label="Spoken Formatting"
+          description="Turn spoken commands like &quot;new line&quot;, &quot;period&quot; or &quot;open quote&quot; into formatting in the pasted text."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        />
+        {Object.values(bindings).map((binding) => (
+          <SettingContainer
+            key={binding.id}
+            title={`${binding.name} Spoken Formatting`}
+            description={`Spoken formatting for the ${binding.name} shortcut, overriding the toggle above.`}
+            descriptionMode={descriptionMode}
+            grouped={grouped}
+          >
+            <Dropdown
+              options={overrideOptions}
+              selectedValue={
+                overrides[binding.id] === undefined
+                  ? DEFAULT_OVERRIDE
+                  : overrides[binding.id]
+                    ? "on"
+                    : "off"
+              }
+              onSelect={(value) => updateBindingOverride(binding.id, value)}
+              disabled={updatingBinding === binding.id}
+            />
+          </SettingContainer>
+        ))}
+        <SettingContainer
+          title="Spoken Commands"
+          description="Phrases recognized as formatting commands. Punctuation attaches to the previous word, opening marks to the next one. Write \n for a line break."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <div className="flex items-center gap-2">
+            <Input
+              type="text"
+              className="max-w-32"
+              value={phrase}
+              onChange={(e) => setPhrase(e.target.value)}
+              placeholder="Phrase"
+              variant="compact"
+              disabled={isUpdating("spoken_commands")}
+            />
+            <Dropdown
+              options={kindOptions}
+              selectedValue={kind}
+              onSelect={setKind}
+              disabled={isUpdating("spoken_commands")}
+            />
+            {newCommand === null || "text" in newCommand ? (
+              <Input
+                type="text"
+                className="max-w-20"
+                value={text}
+                onChange={(e) => setText(e.target.value)}
+                placeholder="Text"
+                variant="compact"
+                disabled={isUpdating("spoken_commands")}
+              />
+            ) : null}
+            <Button
+              onClick={handleAdd}
+              disabled={
+                !trimmedPhrase || !newCommand || isUpdating("spoken_commands")
+              }
+              variant="primary"
+              size="md"
+            >
+              Add
+            </Button>
+          </div>
+        </SettingContainer>
+        {Object.keys(commands).length > 0 && (
+          <div
+            className={`px-4 p-2 ${grouped ? "" : "rounded-lg border border-mid-gray/20"} flex flex-wrap gap-1`}
+          >
+            {Object.entries(commands)
+              .sort(([a], [b]) => a.localeCompare(b))
+              .map(([key, command]) => (
+                <Button
+                  key={key}
+                  onClick={() => handleRemove(key)}
+                  disabled={isUpdating("spoken_commands")}
+                  variant="secondary"
+                  size="sm"
+                  className="cursor-pointer"
+                  title={describeCommand(command)}
+                  aria-label={`Remove ${key}`}
+                >
+                  {key}
+                </Button>
+              ))}
+          </div>
+        )}
+      </>
+    );
+  },
+);
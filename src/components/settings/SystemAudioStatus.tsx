@@ -2,6 +2,7 @@ import React, { useEffect, useState } from "react";
 import { listen } from "@tauri-apps/api/event";
 import { invoke } from "@tauri-apps/api/core";
 import { SettingsGroup } from "../ui/SettingsGroup";
+import type { LogEntry } from "../../lib/types";
 
 type Status = "unknown" | "granted" | "denied" | "waiting" | "active" | "error";
 
@@ -78,8 +79,12 @@ export const SystemAudioStatus: React.FC = () => {
   useEffect(() => {
     console.log("🎯 [SystemAudioStatus] Setting up log listener...");
     
-    const unlistenLog = listen<string>("log-update", (event) => {
-      const logMessage = event.payload.trim();
+    invoke("subscribe_logs").catch((err) => {
+      console.error("❌ [SystemAudioStatus] Failed to subscribe to logs:", err);
+    });
+
+    const handleLog = (entry: LogEntry) => {
+      const logMessage = entry.message.trim();
       const now = new Date().toLocaleTimeString();
       setLastUpdate(now);
 
@@ -90,7 +95,7 @@ export const SystemAudioStatus: React.FC = () => {
 
       // Permission status - check DENIED first to override any previous GRANTED status
       // Check multiple patterns to catch all variations
-      // Note: Log format is "[SCK Helper] ✅ PERMISSION GRANTED!" so we need to match after prefix
+      // Note: Log format is "✅ PERMISSION GRANTED - Using ScreenCaptureKit ..." so we need to match anywhere in the message
       const isDenied = logMessage.includes("PERMISSION DENIED") || 
                        logMessage.includes("❌ PERMISSION DENIED") || 
                        logMessage.includes("declined TCCs") || 
@@ -187,6 +192,10 @@ export const SystemAudioStatus: React.FC = () => {
           return prevPerm;
         });
       }
+    };
+
+    const unlistenLog = listen<LogEntry[]>("log-entry", (event) => {
+      event.payload.forEach(handleLog);
     });
 
     // Store cleanup function
@@ -214,6 +223,7 @@ export const SystemAudioStatus: React.FC = () => {
 
     return () => {
       isMounted = false;
+      invoke("unsubscribe_logs").catch(() => {});
       if (cleanupFn && typeof cleanupFn === 'function') {
         try {
           cleanupFn();
@@ -0,0 +1,90 @@
+import React, { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Dropdown } from "../ui/Dropdown";
+import { SettingContainer } from "../ui/SettingContainer";
+import { useSettings } from "../../hooks/useSettings";
+import type { TextCasing } from "../../lib/types";
+
+interface TextCasingProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+const casingOptions = [
+  { value: "original", label: "As Transcribed" },
+  { value: "lowercase", label: "lowercase" },
+  { value: "sentence", label: "Sentence case" },
+  { value: "uppercase", label: "UPPERCASE" },
+];
+
+// Bindings without an override follow the global casing
+const DEFAULT_CASING = "default";
+
+export const TextCasingSetting: React.FC<TextCasingProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating, refreshSettings } =
+      useSettings();
+    const [updatingBinding, setUpdatingBinding] = useState<string | null>(
+      null,
+    );
+
+    const selectedCasing = (getSetting("text_casing") ||
+      "original") as TextCasing;
+    const bindings = getSetting("bindings") || {};
+    const overrides = getSetting("binding_text_casing") || {};
+
+    const updateBindingCasing = async (bindingId: string, value: string) => {
+      setUpdatingBinding(bindingId);
+      try {
+        await invoke("change_binding_text_casing_setting", {
+          bindingId,
+          casing: value === DEFAULT_CASING ? null : value,
+        });
+        await refreshSettings();
+      } catch (error) {
+        console.error("Failed to update binding casing:", error);
+      } finally {
+        setUpdatingBinding(null);
+      }
+    };
+
+    return (
+      <>
+        <SettingContainer
+          title="Text Casing"
+          description="Force transcripts into lowercase, Sentence case or UPPERCASE before they are pasted, for example when dictating into code comments or chat."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        >
+          <Dropdown
+            options={casingOptions}
+            selectedValue={selectedCasing}
+            onSelect={(value) =>
+              updateSetting("text_casing", value as TextCasing)
+            }
+            disabled={isUpdating("text_casing")}
+          />
+        </SettingContainer>
+        {Object.values(bindings).map((binding) => (
+          <SettingContainer
+            key={binding.id}
+            title={`${binding.name} Casing`}
+            description={`Casing for transcripts from the ${binding.name} shortcut, overriding Text Casing.`}
+            descriptionMode={descriptionMode}
+            grouped={grouped}
+          >
+            <Dropdown
+              options={[
+                { value: DEFAULT_CASING, label: "Same as Text Casing" },
+                ...casingOptions,
+              ]}
+              selectedValue={overrides[binding.id] ?? DEFAULT_CASING}
+              onSelect={(value) => updateBindingCasing(binding.id, value)}
+              disabled={updatingBinding === binding.id}
+            />
+          </SettingContainer>
+        ))}
+      </>
+    );
+  },
+);
@@ -0,0 +1,97 @@
+import React, { useEffect, useRef, useState } from "react";
+import { type as getOsType } from "@tauri-apps/plugin-os";
+import { Slider } from "../ui/Slider";
+import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { useSettings } from "../../hooks/useSettings";
+import { DEFAULT_WASAPI_OPTIONS, type WasapiOptions } from "../../lib/types";
+
+interface WasapiOptionsProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+// Longest device buffer the backend accepts
+const MAX_BUFFER_MS = 500;
+// Every change reopens the devices, so drags are saved once they settle
+const SAVE_DELAY_MS = 400;
+
+export const WasapiOptionsSetting: React.FC<WasapiOptionsProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+
+    const options = getSetting("wasapi") ?? DEFAULT_WASAPI_OPTIONS;
+    const [bufferMs, setBufferMs] = useState(options.buffer_ms);
+    const saveTimer = useRef<ReturnType<typeof setTimeout> | null>(null);
+
+    useEffect(() => {
+      setBufferMs(options.buffer_ms);
+    }, [options.buffer_ms]);
+
+    useEffect(
+      () => () => {
+        if (saveTimer.current) clearTimeout(saveTimer.current);
+      },
+      [],
+    );
+
+    if (getOsType() !== "windows") return null;
+
+    const disabled = isUpdating("wasapi");
+    const update = (change: Partial<WasapiOptions>) =>
+      updateSetting("wasapi", { ...options, ...change });
+
+    const updateBuffer = (value: number) => {
+      setBufferMs(value);
+      if (saveTimer.current) clearTimeout(saveTimer.current);
+      saveTimer.current = setTimeout(
+        () => update({ buffer_ms: value }),
+        SAVE_DELAY_MS,
+      );
+    };
+
+    return (
+      <>
+        <ToggleSwitch
+          checked={options.exclusive}
+          onChange={(exclusive) => update({ exclusive })}
+          isUpdating={disabled}
+          label="Exclusive Microphone Access"
+          description="Open the microphone for Handy alone, bypassing the Windows mixer. Lowers latency, but other apps can't use the microphone while it is open."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        />
+        <ToggleSwitch
+          checked={options.event_driven}
+          onChange={(event_driven) => update({ event_driven })}
+          isUpdating={disabled}
+          label="Event-Driven Capture"
+          description="Read audio as soon as the device signals it. Turn off to poll instead if a driver delivers audio unevenly."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        />
+        <ToggleSwitch
+          checked={options.raw}
+          onChange={(raw) => update({ raw })}
+          isUpdating={disabled}
+          label="Raw Audio"
+          description="Skip the driver's and Windows' processing, like automatic gain and noise suppression, where the device allows it."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+        />
+        <Slider
+          value={bufferMs}
+          onChange={updateBuffer}
+          min={0}
+          max={MAX_BUFFER_MS}
+          step={5}
+          disabled={disabled}
+          label="Device Buffer"
+          description="Length of the WASAPI buffer. Longer buffers ride out glitches, shorter ones lower latency. 0 keeps the device default."
+          descriptionMode={descriptionMode}
+          grouped={grouped}
+          formatValue={(value) => (value === 0 ? "Default" : `${value} ms`)}
+        />
+      </>
+    );
+  },
+);
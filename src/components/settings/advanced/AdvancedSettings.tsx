@@ -8,6 +8,17 @@ import { StartHidden } from "../StartHidden";
 import { AutostartToggle } from "../AutostartToggle";
 import { PasteMethodSetting } from "../PasteMethod";
 import { ClipboardHandlingSetting } from "../ClipboardHandling";
+import { PasteBlacklist } from "../PasteBlacklist";
+import { OutputModeSetting } from "../OutputMode";
+import { TextCasingSetting } from "../TextCasing";
+import { SpokenFormatting } from "../SpokenFormatting";
+import { NumberFormattingSetting } from "../NumberFormatting";
+import { ProfanityFilterSetting } from "../ProfanityFilter";
+import { CloudTranscription } from "../CloudTranscription";
+import { DecodingParameters } from "../DecodingParameters";
+import { HallucinationFilter } from "../HallucinationFilter";
+import { PunctuationModel } from "../PunctuationModel";
+import { MaxRecordingDuration } from "../MaxRecordingDuration";
 
 export const AdvancedSettings: React.FC = () => {
   return (
@@ -18,10 +29,23 @@ export const AdvancedSettings: React.FC = () => {
         <ShowOverlay descriptionMode="tooltip" grouped={true} />
         <PasteMethodSetting descriptionMode="tooltip" grouped={true} />
         <ClipboardHandlingSetting descriptionMode="tooltip" grouped={true} />
+        <PasteBlacklist descriptionMode="tooltip" grouped={true} />
+        <OutputModeSetting descriptionMode="tooltip" grouped={true} />
+        <TextCasingSetting descriptionMode="tooltip" grouped={true} />
+        <SpokenFormatting descriptionMode="tooltip" grouped={true} />
+        <NumberFormattingSetting descriptionMode="tooltip" grouped={true} />
+        <ProfanityFilterSetting descriptionMode="tooltip" grouped={true} />
         <TranslateToEnglish descriptionMode="tooltip" grouped={true} />
         <ModelUnloadTimeoutSetting descriptionMode="tooltip" grouped={true} />
+        <MaxRecordingDuration descriptionMode="tooltip" grouped={true} />
         <CustomWords descriptionMode="tooltip" grouped />
       </SettingsGroup>
+      <SettingsGroup title="Transcription Engine">
+        <CloudTranscription descriptionMode="tooltip" grouped={true} />
+        <DecodingParameters descriptionMode="tooltip" grouped={true} />
+        <HallucinationFilter descriptionMode="tooltip" grouped={true} />
+        <PunctuationModel descriptionMode="tooltip" grouped={true} />
+      </SettingsGroup>
     </div>
   );
 };
@@ -3,13 +3,17 @@ import { WordCorrectionThreshold } from "./WordCorrectionThreshold";
 import { LogDirectory } from "./LogDirectory";
 import { LogLevelSelector } from "./LogLevelSelector";
 import { SettingsGroup } from "../../ui/SettingsGroup";
-import { HistoryLimit } from "../HistoryLimit";
+import { HistoryRetentionSetting } from "../HistoryRetention";
 import { AlwaysOnMicrophone } from "../AlwaysOnMicrophone";
+import { PreRoll } from "../PreRoll";
 import { SoundPicker } from "../SoundPicker";
 import { PostProcessingToggle } from "../PostProcessingToggle";
 import { MuteWhileRecording } from "../MuteWhileRecording";
-import { RecordingRetentionPeriodSelector } from "../RecordingRetentionPeriod";
 import { ClamshellMicrophoneSelector } from "../ClamshellMicrophoneSelector";
+import { ChannelDownmixSetting } from "../ChannelDownmix";
+import { InputGainSetting } from "../InputGain";
+import { WasapiOptionsSetting } from "../WasapiOptions";
+import { LinuxAudioHostSetting } from "../LinuxAudioHost";
 
 export const DebugSettings: React.FC = () => {
   return (
@@ -22,13 +26,14 @@ export const DebugSettings: React.FC = () => {
           description="Choose a sound theme for recording start and stop feedback"
         />
         <WordCorrectionThreshold descriptionMode="tooltip" grouped={true} />
-        <HistoryLimit descriptionMode="tooltip" grouped={true} />
-        <RecordingRetentionPeriodSelector
-          descriptionMode="tooltip"
-          grouped={true}
-        />
+        <HistoryRetentionSetting descriptionMode="tooltip" grouped={true} />
         <AlwaysOnMicrophone descriptionMode="tooltip" grouped={true} />
+        <PreRoll descriptionMode="tooltip" grouped={true} />
         <ClamshellMicrophoneSelector descriptionMode="tooltip" grouped={true} />
+        <ChannelDownmixSetting descriptionMode="tooltip" grouped={true} />
+        <InputGainSetting descriptionMode="tooltip" grouped={true} />
+        <WasapiOptionsSetting descriptionMode="tooltip" grouped={true} />
+        <LinuxAudioHostSetting descriptionMode="tooltip" grouped={true} />
         <PostProcessingToggle descriptionMode="tooltip" grouped={true} />
         <MuteWhileRecording descriptionMode="tooltip" grouped={true} />
       </SettingsGroup>
@@ -10,8 +10,18 @@ import { AudioFeedback } from "../AudioFeedback";
 import { useSettings } from "../../../hooks/useSettings";
 import { VolumeSlider } from "../VolumeSlider";
 import { LiveCaptionToggle } from "../LiveCaptionToggle";
+import { AutoCaptionSystemAudio } from "../AutoCaptionSystemAudio";
+import { DualChannelCaptions } from "../DualChannelCaptions";
+import { CaptionPaste } from "../CaptionPaste";
+import { SpeakerDiarization } from "../SpeakerDiarization";
+import { CaptionTranslation } from "../CaptionTranslation";
+import { CaptionStyleSetting } from "../CaptionStyle";
 import { LiveCaptionViewer } from "../LiveCaptionViewer";
 import { SystemAudioStatus } from "../SystemAudioStatus";
+import { LoopbackDiagnosis } from "../LoopbackDiagnosis";
+import { BlackHoleSetup } from "../BlackHoleSetup";
+import { CaptureTargetSetting } from "../CaptureTargetSetting";
+import { AudioSelfTest } from "../AudioSelfTest";
 
 export const GeneralSettings: React.FC = () => {
   const { audioFeedbackEnabled } = useSettings();
@@ -20,6 +30,11 @@ export const GeneralSettings: React.FC = () => {
       <SettingsGroup title="General">
         <HandyShortcut descriptionMode="tooltip" grouped={true} />
         <LanguageSelector descriptionMode="tooltip" grouped={true} />
+        <HandyShortcut
+          shortcutId="cycle_language"
+          descriptionMode="tooltip"
+          grouped={true}
+        />
         <PushToTalk descriptionMode="tooltip" grouped={true} />
       </SettingsGroup>
       <SettingsGroup title="Sound">
@@ -35,8 +50,18 @@ export const GeneralSettings: React.FC = () => {
       </SettingsGroup>
       <SettingsGroup title="Display">
         <LiveCaptionToggle descriptionMode="tooltip" grouped={true} />
+        <AutoCaptionSystemAudio descriptionMode="tooltip" grouped={true} />
+        <DualChannelCaptions descriptionMode="tooltip" grouped={true} />
+        <CaptionPaste descriptionMode="tooltip" grouped={true} />
+        <SpeakerDiarization descriptionMode="tooltip" grouped={true} />
+        <CaptionTranslation descriptionMode="tooltip" grouped={true} />
+        <CaptionStyleSetting descriptionMode="tooltip" grouped={true} />
       </SettingsGroup>
       <SystemAudioStatus />
+      <CaptureTargetSetting />
+      <LoopbackDiagnosis />
+      <BlackHoleSetup />
+      <AudioSelfTest />
       <LiveCaptionViewer />
     </div>
   );
@@ -0,0 +1,111 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { downloadDir, join } from "@tauri-apps/api/path";
+import { toast } from "sonner";
+import { Button } from "../../ui/Button";
+import { Dropdown } from "../../ui/Dropdown";
+import { Input } from "../../ui/Input";
+
+type ExportFormat = "json" | "csv" | "markdown" | "text";
+
+const formatOptions = [
+  { value: "json", label: "JSON" },
+  { value: "csv", label: "CSV" },
+  { value: "markdown", label: "Markdown" },
+  { value: "text", label: "Plain text" },
+];
+
+const extensions: Record<ExportFormat, string> = {
+  json: "json",
+  csv: "csv",
+  markdown: "md",
+  text: "txt",
+};
+
+// "YYYY-MM-DD" from a date input to unix seconds at the start or end of that
+// local day
+const toTimestamp = (date: string, endOfDay: boolean) => {
+  if (!date) return null;
+  const time = new Date(`${date}T${endOfDay ? "23:59:59" : "00:00:00"}`);
+  return Math.floor(time.getTime() / 1000);
+};
+
+export const HistoryExport: React.FC = () => {
+  const [format, setFormat] = useState<ExportFormat>("markdown");
+  const [from, setFrom] = useState("");
+  const [to, setTo] = useState("");
+  const [path, setPath] = useState("");
+  const [exporting, setExporting] = useState(false);
+
+  // Suggest a file in Downloads whenever the format changes
+  useEffect(() => {
+    downloadDir()
+      .then((dir) => join(dir, `handy-history.${extensions[format]}`))
+      .then(setPath)
+      .catch((error) => console.error("Failed to get downloads dir:", error));
+  }, [format]);
+
+  const handleExport = async () => {
+    setExporting(true);
+    try {
+      const count = await invoke<number>("export_history", {
+        path,
+        format,
+        from: toTimestamp(from, false),
+        to: toTimestamp(to, true),
+      });
+      toast.success(`Exported ${count} entries to ${path}`);
+    } catch (error) {
+      toast.error(`Failed to export history: ${error}`);
+    } finally {
+      setExporting(false);
+    }
+  };
+
+  return (
+    <div className="space-y-2">
+      <div className="px-4">
+        <h2 className="text-xs font-medium text-mid-gray uppercase tracking-wide">
+          Export
+        </h2>
+      </div>
+      <div className="bg-background border border-mid-gray/20 rounded-lg px-4 py-3 flex flex-col gap-3">
+        <div className="flex items-center gap-2">
+          <Dropdown
+            options={formatOptions}
+            selectedValue={format}
+            onSelect={(value) => setFormat(value as ExportFormat)}
+          />
+          <Input
+            type="date"
+            value={from}
+            onChange={(event) => setFrom(event.target.value)}
+            title="From"
+          />
+          <span className="text-sm text-text/60">to</span>
+          <Input
+            type="date"
+            value={to}
+            onChange={(event) => setTo(event.target.value)}
+            title="To"
+          />
+        </div>
+        <div className="flex items-center gap-2">
+          <Input
+            type="text"
+            value={path}
+            onChange={(event) => setPath(event.target.value)}
+            className="flex-1"
+          />
+          <Button
+            onClick={handleExport}
+            size="sm"
+            disabled={exporting || !path.trim()}
+          >
+            Export
+          </Button>
+        </div>
+      </div>
+    </div>
+  );
+};
@@ -0,0 +1,173 @@
+import React, { useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { Play, Pause } from "lucide-react";
+
+type PlaybackStatus = "playing" | "paused" | "ended";
+
+interface PlaybackProgress {
+  entry_id: number;
+  position_ms: number;
+  duration_ms: number;
+  status: PlaybackStatus;
+}
+
+interface HistoryPlayerProps {
+  entryId: number;
+  // Known before playback starts for entries that recorded it
+  durationMs?: number | null;
+  // Peak envelope stored with the entry, values in 0..1
+  waveform?: number[] | null;
+  className?: string;
+}
+
+const formatTime = (ms: number): string => {
+  const seconds = Math.floor(ms / 1000);
+  return `${Math.floor(seconds / 60)}:${(seconds % 60).toString().padStart(2, "0")}`;
+};
+
+const Waveform: React.FC<{ peaks: number[]; progress: number }> = ({
+  peaks,
+  progress,
+}) => (
+  <svg
+    viewBox={`0 0 ${peaks.length} 100`}
+    preserveAspectRatio="none"
+    className="w-full h-8"
+    aria-hidden="true"
+  >
+    {peaks.map((peak, i) => {
+      const height = Math.max(2, peak * 100);
+      return (
+        <rect
+          key={i}
+          x={i + 0.15}
+          y={(100 - height) / 2}
+          width={0.7}
+          height={height}
+          className={
+            i / peaks.length < progress
+              ? "fill-logo-primary"
+              : "fill-mid-gray/40"
+          }
+        />
+      );
+    })}
+  </svg>
+);
+
+// Plays a history recording through the backend so it comes out of the
+// selected output device
+export const HistoryPlayer: React.FC<HistoryPlayerProps> = ({
+  entryId,
+  durationMs,
+  waveform,
+  className = "",
+}) => {
+  const [status, setStatus] = useState<PlaybackStatus>("ended");
+  const [position, setPosition] = useState(0);
+  const [duration, setDuration] = useState(durationMs ?? 0);
+  const [dragging, setDragging] = useState(false);
+  const statusRef = useRef(status);
+  statusRef.current = status;
+
+  useEffect(() => {
+    const unlisten = listen<PlaybackProgress>("history-playback", (event) => {
+      if (event.payload.entry_id !== entryId) return;
+      setStatus(event.payload.status);
+      if (event.payload.duration_ms > 0) {
+        setDuration(event.payload.duration_ms);
+      }
+      setPosition((current) =>
+        dragging ? current : event.payload.position_ms,
+      );
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [entryId, dragging]);
+
+  // Don't leave a recording playing once its entry is gone
+  useEffect(
+    () => () => {
+      if (statusRef.current !== "ended") {
+        invoke("stop_history_audio").catch(console.error);
+      }
+    },
+    [],
+  );
+
+  const togglePlay = async () => {
+    try {
+      if (status === "playing") {
+        await invoke("pause_history_audio");
+      } else {
+        await invoke("play_history_audio", { id: entryId });
+      }
+    } catch (error) {
+      console.error("Playback failed:", error);
+    }
+  };
+
+  const commitSeek = async () => {
+    setDragging(false);
+    if (status === "ended") return;
+    try {
+      await invoke("seek_history_audio", { positionMs: Math.round(position) });
+    } catch (error) {
+      console.error("Seek failed:", error);
+    }
+  };
+
+  const progressPercent =
+    duration > 0 ? Math.min(100, (position / duration) * 100) : 0;
+
+  return (
+    <div className={`flex flex-col gap-1 ${className}`}>
+      {waveform && waveform.length > 0 && (
+        <Waveform peaks={waveform} progress={progressPercent / 100} />
+      )}
+      <div className="flex items-center gap-3">
+        <button
+          onClick={togglePlay}
+          className="transition-colors cursor-pointer text-text hover:text-logo-primary"
+          aria-label={status === "playing" ? "Pause" : "Play"}
+        >
+          {status === "playing" ? (
+            <Pause width={20} height={20} fill="currentColor" />
+          ) : (
+            <Play width={20} height={20} fill="currentColor" />
+          )}
+        </button>
+
+        <div className="flex-1 flex items-center gap-2">
+          <span className="text-xs text-text/60 min-w-[30px] tabular-nums">
+            {formatTime(position)}
+          </span>
+
+          <input
+            type="range"
+            min="0"
+            max={duration}
+            step="10"
+            value={position}
+            onChange={(event) => setPosition(parseFloat(event.target.value))}
+            onMouseDown={() => setDragging(true)}
+            onTouchStart={() => setDragging(true)}
+            onMouseUp={commitSeek}
+            onTouchEnd={commitSeek}
+            disabled={status === "ended"}
+            className="flex-1 h-1 rounded-lg appearance-none cursor-pointer focus:outline-none focus:ring-1 focus:ring-logo-primary disabled:cursor-default"
+            style={{
+              background: `linear-gradient(to right, #FAA2CA 0%, #FAA2CA ${progressPercent}%, rgba(128, 128, 128, 0.2) ${progressPercent}%, rgba(128, 128, 128, 0.2) 100%)`,
+            }}
+          />
+
+          <span className="text-xs text-text/60 min-w-[30px] tabular-nums">
+            {formatTime(duration)}
+          </span>
+        </div>
+      </div>
+    </div>
+  );
+};
@@ -0,0 +1,150 @@
+import React, { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { ChevronDown, ChevronRight, Trash2 } from "lucide-react";
+
+interface HistorySession {
+  id: number;
+  title: string;
+  started_at: number;
+  ended_at: number | null;
+  entry_count: number;
+  duration_ms: number;
+}
+
+interface SessionEntry {
+  id: number;
+  timestamp: number;
+  transcription_text: string;
+}
+
+const formatDuration = (ms: number) => {
+  const seconds = Math.round(ms / 1000);
+  const minutes = Math.floor(seconds / 60);
+  return minutes > 0 ? `${minutes}m ${seconds % 60}s` : `${seconds}s`;
+};
+
+// Live captions grouped by the caption stream run they came from
+export const HistorySessions: React.FC = () => {
+  const [sessions, setSessions] = useState<HistorySession[]>([]);
+  const [openSession, setOpenSession] = useState<number | null>(null);
+  const [entries, setEntries] = useState<SessionEntry[]>([]);
+
+  const loadSessions = useCallback(async () => {
+    try {
+      const loaded = await invoke<HistorySession[]>("get_history_sessions");
+      setSessions(loaded.filter((session) => session.entry_count > 0));
+    } catch (error) {
+      console.error("Failed to load history sessions:", error);
+    }
+  }, []);
+
+  const loadEntries = useCallback(async (sessionId: number) => {
+    try {
+      const loaded = await invoke<SessionEntry[]>(
+        "get_history_session_entries",
+        { sessionId },
+      );
+      setEntries(loaded);
+    } catch (error) {
+      console.error("Failed to load session entries:", error);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadSessions();
+
+    const unlisten = listen("history-updated", () => {
+      loadSessions();
+      if (openSession !== null) {
+        loadEntries(openSession);
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadSessions, loadEntries, openSession]);
+
+  const toggleSession = (sessionId: number) => {
+    if (openSession === sessionId) {
+      setOpenSession(null);
+      return;
+    }
+    setEntries([]);
+    setOpenSession(sessionId);
+    loadEntries(sessionId);
+  };
+
+  const deleteSession = async (sessionId: number) => {
+    try {
+      await invoke("delete_history_session", { sessionId });
+      if (openSession === sessionId) {
+        setOpenSession(null);
+      }
+    } catch (error) {
+      console.error("Failed to delete session:", error);
+    }
+  };
+
+  if (sessions.length === 0) {
+    return null;
+  }
+
+  return (
+    <div className="space-y-2">
+      <div className="px-4">
+        <h2 className="text-xs font-medium text-mid-gray uppercase tracking-wide">
+          Caption Sessions
+        </h2>
+      </div>
+      <div className="bg-background border border-mid-gray/20 rounded-lg overflow-visible">
+        <div className="divide-y divide-mid-gray/20">
+          {sessions.map((session) => (
+            <div key={session.id} className="px-4 py-2 flex flex-col gap-2">
+              <div className="flex justify-between items-center">
+                <button
+                  onClick={() => toggleSession(session.id)}
+                  className="flex items-center gap-1 text-sm font-medium cursor-pointer"
+                >
+                  {openSession === session.id ? (
+                    <ChevronDown width={16} height={16} />
+                  ) : (
+                    <ChevronRight width={16} height={16} />
+                  )}
+                  {session.title}
+                </button>
+                <div className="flex items-center gap-3">
+                  <span className="text-xs text-text/60">
+                    {session.entry_count} captions ·{" "}
+                    {formatDuration(session.duration_ms)}
+                    {session.ended_at === null && " · live"}
+                  </span>
+                  <button
+                    onClick={() => deleteSession(session.id)}
+                    className="text-text/50 hover:text-logo-primary transition-colors cursor-pointer"
+                    title="Delete session"
+                  >
+                    <Trash2 width={16} height={16} />
+                  </button>
+                </div>
+              </div>
+              {openSession === session.id && (
+                <div className="pl-5 pb-2 space-y-1">
+                  {entries.map((entry) => (
+                    <p key={entry.id} className="text-sm text-text/90">
+                      <span className="text-xs text-text/50 mr-2">
+                        {new Date(entry.timestamp * 1000).toLocaleTimeString()}
+                      </span>
+                      {entry.transcription_text}
+                    </p>
+                  ))}
+                </div>
+              )}
+            </div>
+          ))}
+        </div>
+      </div>
+    </div>
+  );
+};
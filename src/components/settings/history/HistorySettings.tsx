@@ -1,9 +1,21 @@
 import React, { useState, useEffect, useCallback } from "react";
-import { AudioPlayer } from "../../ui/AudioPlayer";
 import { Button } from "../../ui/Button";
-import { Copy, Star, Check, Trash2, FolderOpen } from "lucide-react";
-import { convertFileSrc, invoke } from "@tauri-apps/api/core";
+import { Textarea } from "../../ui/Textarea";
+import {
+  Copy,
+  Star,
+  Check,
+  Trash2,
+  FolderOpen,
+  Pencil,
+  History,
+} from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
+import { HistoryExport } from "./HistoryExport";
+import { HistoryPlayer } from "./HistoryPlayer";
+import { HistorySessions } from "./HistorySessions";
+import { HistoryStats } from "./HistoryStats";
 
 interface HistoryEntry {
   id: number;
@@ -12,6 +24,47 @@ interface HistoryEntry {
   saved: boolean;
   title: string;
   transcription_text: string;
+  waveform?: number[] | null;
+  tag?: string | null;
+  session_id?: number | null;
+  duration_ms?: number | null;
+  revision_count: number;
+  metadata?: TranscriptionMetadata | null;
+}
+
+interface TranscriptionMetadata {
+  model?: string | null;
+  source?: "microphone" | "system_audio" | "file" | "recovered" | null;
+  latency_ms?: number | null;
+  language?: string | null;
+  app_name?: string | null;
+}
+
+const sourceLabels: Record<string, string> = {
+  microphone: "Microphone",
+  system_audio: "System audio",
+  file: "File",
+  recovered: "Recovered",
+};
+
+// "whisper-small · Microphone · Slack · en · 1.2s"
+const describeMetadata = (metadata: TranscriptionMetadata) =>
+  [
+    metadata.model,
+    metadata.source && sourceLabels[metadata.source],
+    metadata.app_name,
+    metadata.language,
+    metadata.latency_ms != null &&
+      `${(metadata.latency_ms / 1000).toFixed(1)}s`,
+  ]
+    .filter(Boolean)
+    .join(" · ");
+
+interface HistoryRevision {
+  id: number;
+  entry_id: number;
+  text: string;
+  replaced_at: number;
 }
 
 interface OpenRecordingsButtonProps {
@@ -90,19 +143,6 @@ export const HistorySettings: React.FC = () => {
     }
   };
 
-  const getAudioUrl = async (fileName: string) => {
-    try {
-      const filePath = await invoke<string>("get_audio_file_path", {
-        fileName,
-      });
-
-      return convertFileSrc(`${filePath}`, "asset");
-    } catch (error) {
-      console.error("Failed to get audio file path:", error);
-      return null;
-    }
-  };
-
   const deleteAudioEntry = async (id: number) => {
     try {
       await invoke("delete_history_entry", { id });
@@ -166,6 +206,8 @@ export const HistorySettings: React.FC = () => {
 
   return (
     <div className="max-w-3xl w-full mx-auto space-y-6">
+      <HistoryStats />
+      <HistorySessions />
       <div className="space-y-2">
         <div className="px-4 flex items-center justify-between">
           <div>
@@ -183,13 +225,13 @@ export const HistorySettings: React.FC = () => {
                 entry={entry}
                 onToggleSaved={() => toggleSaved(entry.id)}
                 onCopyText={() => copyToClipboard(entry.transcription_text)}
-                getAudioUrl={getAudioUrl}
                 deleteAudio={deleteAudioEntry}
               />
             ))}
           </div>
         </div>
       </div>
+      <HistoryExport />
     </div>
   );
 };
@@ -198,7 +240,6 @@ interface HistoryEntryProps {
   entry: HistoryEntry;
   onToggleSaved: () => void;
   onCopyText: () => void;
-  getAudioUrl: (fileName: string) => Promise<string | null>;
   deleteAudio: (id: number) => Promise<void>;
 }
 
@@ -206,19 +247,12 @@ const HistoryEntryComponent: React.FC<HistoryEntryProps> = ({
   entry,
   onToggleSaved,
   onCopyText,
-  getAudioUrl,
   deleteAudio,
 }) => {
-  const [audioUrl, setAudioUrl] = useState<string | null>(null);
   const [showCopied, setShowCopied] = useState(false);
-
-  useEffect(() => {
-    const loadAudio = async () => {
-      const url = await getAudioUrl(entry.file_name);
-      setAudioUrl(url);
-    };
-    loadAudio();
-  }, [entry.file_name, getAudioUrl]);
+  const [editing, setEditing] = useState(false);
+  const [draft, setDraft] = useState(entry.transcription_text);
+  const [revisions, setRevisions] = useState<HistoryRevision[] | null>(null);
 
   const handleCopyText = () => {
     onCopyText();
@@ -226,6 +260,37 @@ const HistoryEntryComponent: React.FC<HistoryEntryProps> = ({
     setTimeout(() => setShowCopied(false), 2000);
   };
 
+  const startEditing = () => {
+    setDraft(entry.transcription_text);
+    setEditing(true);
+  };
+
+  const saveEdit = async () => {
+    try {
+      await invoke("update_history_text", { id: entry.id, text: draft });
+      setEditing(false);
+      setRevisions(null);
+    } catch (error) {
+      console.error("Failed to update transcription:", error);
+    }
+  };
+
+  const toggleRevisions = async () => {
+    if (revisions) {
+      setRevisions(null);
+      return;
+    }
+    try {
+      setRevisions(
+        await invoke<HistoryRevision[]>("get_history_revisions", {
+          id: entry.id,
+        }),
+      );
+    } catch (error) {
+      console.error("Failed to load revisions:", error);
+    }
+  };
+
   const handleDeleteEntry = async () => {
     try {
       await deleteAudio(entry.id);
@@ -238,7 +303,14 @@ const HistoryEntryComponent: React.FC<HistoryEntryProps> = ({
   return (
     <div className="px-4 py-2 pb-5 flex flex-col gap-3">
       <div className="flex justify-between items-center">
-        <p className="text-sm font-medium">{entry.title}</p>
+        <p className="text-sm font-medium">
+          {entry.title}
+          {entry.tag && (
+            <span className="ml-2 px-1.5 py-0.5 rounded text-xs bg-logo-primary/20 text-logo-primary">
+              {entry.tag}
+            </span>
+          )}
+        </p>
         <div className="flex items-center gap-1">
           <button
             onClick={handleCopyText}
@@ -251,6 +323,28 @@ const HistoryEntryComponent: React.FC<HistoryEntryProps> = ({
               <Copy width={16} height={16} />
             )}
           </button>
+          <button
+            onClick={startEditing}
+            className="p-2 text-text/50 hover:text-logo-primary transition-colors cursor-pointer"
+            title="Edit transcription"
+          >
+            <Pencil width={16} height={16} />
+          </button>
+          {entry.revision_count > 0 && (
+            <button
+              onClick={toggleRevisions}
+              className={`transition-colors cursor-pointer ${
+                revisions
+                  ? "text-logo-primary"
+                  : "text-text/50 hover:text-logo-primary"
+              }`}
+              title={`${entry.revision_count} earlier version${
+                entry.revision_count === 1 ? "" : "s"
+              }`}
+            >
+              <History width={16} height={16} />
+            </button>
+          )}
           <button
             onClick={onToggleSaved}
             className={`p-2 rounded  transition-colors cursor-pointer ${
@@ -275,10 +369,54 @@ const HistoryEntryComponent: React.FC<HistoryEntryProps> = ({
           </button>
         </div>
       </div>
-      <p className="italic text-text/90 text-sm pb-2">
-        {entry.transcription_text}
-      </p>
-      {audioUrl && <AudioPlayer src={audioUrl} className="w-full" />}
+      {editing ? (
+        <div className="flex flex-col gap-2">
+          <Textarea
+            value={draft}
+            onChange={(event) => setDraft(event.target.value)}
+            variant="compact"
+          />
+          <div className="flex justify-end gap-2">
+            <Button
+              onClick={() => setEditing(false)}
+              variant="secondary"
+              size="sm"
+            >
+              Cancel
+            </Button>
+            <Button onClick={saveEdit} size="sm" disabled={!draft.trim()}>
+              Save
+            </Button>
+          </div>
+        </div>
+      ) : (
+        <p className="italic text-text/90 text-sm pb-2">
+          {entry.transcription_text}
+        </p>
+      )}
+      {entry.metadata && (
+        <p className="text-xs text-text/50">
+          {describeMetadata(entry.metadata)}
+        </p>
+      )}
+      {revisions && (
+        <div className="flex flex-col gap-1 border-l-2 border-mid-gray/20 pl-3">
+          {revisions.map((revision) => (
+            <div key={revision.id} className="text-xs text-text/60">
+              <span className="text-mid-gray">
+                {new Date(revision.replaced_at * 1000).toLocaleString()}
+              </span>
+              <p className="line-through">{revision.text}</p>
+            </div>
+          ))}
+        </div>
+      )}
+      <HistoryPlayer
+        entryId={entry.id}
+        durationMs={entry.duration_ms}
+        waveform={entry.waveform}
+        className="w-full"
+      />
     </div>
   );
 };
@@ -0,0 +1,127 @@
+import React, { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+interface DailyUsage {
+  date: string;
+  words: number;
+  entries: number;
+  duration_ms: number;
+}
+
+interface AppUsage {
+  name: string;
+  entries: number;
+  words: number;
+}
+
+interface UsageStats {
+  total_entries: number;
+  total_words: number;
+  total_duration_ms: number;
+  average_latency_ms: number | null;
+  daily: DailyUsage[];
+  top_apps: AppUsage[];
+}
+
+const formatDuration = (ms: number) => {
+  const minutes = Math.round(ms / 60000);
+  const hours = Math.floor(minutes / 60);
+  return hours > 0 ? `${hours}h ${minutes % 60}m` : `${minutes}m`;
+};
+
+const Stat: React.FC<{ label: string; value: string }> = ({
+  label,
+  value,
+}) => (
+  <div className="flex flex-col">
+    <span className="text-lg font-semibold tabular-nums">{value}</span>
+    <span className="text-xs text-text/60">{label}</span>
+  </div>
+);
+
+// Words per day over the last month, totals and the apps dictated into most
+export const HistoryStats: React.FC = () => {
+  const [stats, setStats] = useState<UsageStats | null>(null);
+
+  const loadStats = useCallback(async () => {
+    try {
+      setStats(await invoke<UsageStats>("get_usage_stats"));
+    } catch (error) {
+      console.error("Failed to load usage stats:", error);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadStats();
+
+    const unlisten = listen("history-updated", () => {
+      loadStats();
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadStats]);
+
+  if (!stats || stats.total_entries === 0) {
+    return null;
+  }
+
+  const maxWords = Math.max(1, ...stats.daily.map((day) => day.words));
+
+  return (
+    <div className="space-y-2">
+      <div className="px-4">
+        <h2 className="text-xs font-medium text-mid-gray uppercase tracking-wide">
+          Usage
+        </h2>
+      </div>
+      <div className="bg-background border border-mid-gray/20 rounded-lg px-4 py-3 flex flex-col gap-4">
+        <div className="grid grid-cols-4 gap-3">
+          <Stat label="Words" value={stats.total_words.toLocaleString()} />
+          <Stat
+            label="Transcriptions"
+            value={stats.total_entries.toLocaleString()}
+          />
+          <Stat
+            label="Dictation time"
+            value={formatDuration(stats.total_duration_ms)}
+          />
+          <Stat
+            label="Avg. latency"
+            value={
+              stats.average_latency_ms != null
+                ? `${(stats.average_latency_ms / 1000).toFixed(1)}s`
+                : "–"
+            }
+          />
+        </div>
+
+        <div className="flex items-end gap-0.5 h-16">
+          {stats.daily.map((day) => (
+            <div
+              key={day.date}
+              className="flex-1 bg-logo-primary rounded-sm min-h-px"
+              style={{ height: `${(day.words / maxWords) * 100}%` }}
+              title={`${day.date}: ${day.words} words`}
+            />
+          ))}
+        </div>
+
+        {stats.top_apps.length > 0 && (
+          <div className="flex flex-col gap-1">
+            {stats.top_apps.map((app) => (
+              <div key={app.name} className="flex justify-between text-sm">
+                <span className="truncate">{app.name}</span>
+                <span className="text-text/60 tabular-nums">
+                  {app.entries} · {app.words.toLocaleString()} words
+                </span>
+              </div>
+            ))}
+          </div>
+        )}
+      </div>
+    </div>
+  );
+};
@@ -12,18 +12,35 @@ export { AudioSourceSelector } from "./AudioSourceSelector";
 export { ClamshellMicrophoneSelector } from "./ClamshellMicrophoneSelector";
 export { OutputDeviceSelector } from "./OutputDeviceSelector";
 export { AlwaysOnMicrophone } from "./AlwaysOnMicrophone";
+export { AutoCaptionSystemAudio } from "./AutoCaptionSystemAudio";
+export { DualChannelCaptions } from "./DualChannelCaptions";
+export { CaptionPaste } from "./CaptionPaste";
+export { CaptionStyleSetting } from "./CaptionStyle";
+export { SpeakerDiarization } from "./SpeakerDiarization";
+export { TextCasingSetting } from "./TextCasing";
+export { OutputModeSetting } from "./OutputMode";
+export { SpokenFormatting } from "./SpokenFormatting";
+export { NumberFormattingSetting } from "./NumberFormatting";
+export { ChannelDownmixSetting } from "./ChannelDownmix";
+export { InputGainSetting } from "./InputGain";
+export { ProfanityFilterSetting } from "./ProfanityFilter";
+export { CloudTranscription } from "./CloudTranscription";
+export { DecodingParameters } from "./DecodingParameters";
+export { HallucinationFilter } from "./HallucinationFilter";
+export { PunctuationModel } from "./PunctuationModel";
+export { CaptionTranslation } from "./CaptionTranslation";
 export { PushToTalk } from "./PushToTalk";
 export { AudioFeedback } from "./AudioFeedback";
 export { ShowOverlay } from "./ShowOverlay";
 export { HandyShortcut } from "./HandyShortcut";
 export { TranslateToEnglish } from "./TranslateToEnglish";
 export { CustomWords } from "./CustomWords";
+export { PasteBlacklist } from "./PasteBlacklist";
 export { PostProcessingToggle } from "./PostProcessingToggle";
 export { PostProcessingSettingsApi } from "./PostProcessingSettingsApi";
 export { PostProcessingSettingsPrompts } from "./PostProcessingSettingsPrompts";
 export { AppDataDirectory } from "./AppDataDirectory";
 export { ModelUnloadTimeoutSetting } from "./ModelUnloadTimeout";
 export { StartHidden } from "./StartHidden";
-export { HistoryLimit } from "./HistoryLimit";
-export { RecordingRetentionPeriodSelector } from "./RecordingRetentionPeriod";
+export { HistoryRetentionSetting } from "./HistoryRetention";
 export { AutostartToggle } from "./AutostartToggle";
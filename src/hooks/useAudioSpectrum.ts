@@ -0,0 +1,26 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import type { SpectrumFrame } from "../lib/types";
+
+// The microphone's spectrum in `bands` bands, updated up to about 30 times a
+// second while the component is mounted; null until the first frame arrives
+export const useAudioSpectrum = (bands: number) => {
+  const [spectrum, setSpectrum] = useState<SpectrumFrame | null>(null);
+
+  useEffect(() => {
+    const unlisten = listen<SpectrumFrame>("audio-spectrum", (event) => {
+      setSpectrum(event.payload);
+    });
+    invoke("subscribe_spectrum", { bands }).catch((error) => {
+      console.error("Failed to subscribe to the audio spectrum:", error);
+    });
+
+    return () => {
+      invoke("unsubscribe_spectrum").catch(() => {});
+      unlisten.then((fn) => fn());
+    };
+  }, [bands]);
+
+  return spectrum;
+};
@@ -117,6 +117,23 @@ export const useModels = () => {
     }
   };
 
+  // Resolves false when the model's files changed since it was downloaded
+  const verifyModel = async (modelId: string) => {
+    try {
+      setError(null);
+      const ok = await invoke<boolean>("verify_model", { modelId });
+      if (!ok) {
+        setError(
+          `Model ${modelId} is corrupted. Delete and download it again.`,
+        );
+      }
+      return ok;
+    } catch (err) {
+      setError(`Failed to verify model: ${err}`);
+      return false;
+    }
+  };
+
   const getModelInfo = (modelId: string): ModelInfo | undefined => {
     return models.find((model) => model.id === modelId);
   };
@@ -231,6 +248,7 @@ export const useModels = () => {
     selectModel,
     downloadModel,
     deleteModel,
+    verifyModel,
     getModelInfo,
     isModelDownloading,
     isModelExtracting,
@@ -0,0 +1,21 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import type { PlatformCapabilities } from "../lib/types";
+
+// Capabilities don't change while the app runs, so fetch them once
+let cached: Promise<PlatformCapabilities> | null = null;
+
+export const usePlatformCapabilities = () => {
+  const [capabilities, setCapabilities] =
+    useState<PlatformCapabilities | null>(null);
+
+  useEffect(() => {
+    cached ??= invoke<PlatformCapabilities>("get_platform_capabilities");
+    cached.then(setCapabilities).catch((error) => {
+      console.error("Failed to load platform capabilities:", error);
+      cached = null;
+    });
+  }, []);
+
+  return capabilities;
+};
@@ -14,8 +14,10 @@ export const ShortcutBindingsMapSchema = z.record(
 );
 
 export const AudioDeviceSchema = z.object({
+  id: z.string(),
   index: z.string(),
   name: z.string(),
+  alias: z.string().nullable().optional(),
   is_default: z.boolean(),
 });
 
@@ -34,7 +36,24 @@ export const ModelUnloadTimeoutSchema = z.enum([
 ]);
 export type ModelUnloadTimeout = z.infer<typeof ModelUnloadTimeoutSchema>;
 
-export const PasteMethodSchema = z.enum(["ctrl_v", "direct", "shift_insert"]);
+export const ComputeBackendSchema = z.enum([
+  "auto",
+  "cpu",
+  "metal",
+  "cuda",
+  "vulkan",
+]);
+export type ComputeBackend = z.infer<typeof ComputeBackendSchema>;
+
+export const ModelSelectionSchema = z.enum(["manual", "recommended"]);
+export type ModelSelection = z.infer<typeof ModelSelectionSchema>;
+
+export const PasteMethodSchema = z.enum([
+  "ctrl_v",
+  "direct",
+  "shift_insert",
+  "accessibility",
+]);
 export type PasteMethod = z.infer<typeof PasteMethodSchema>;
 
 export const ClipboardHandlingSchema = z.enum([
@@ -43,19 +62,138 @@ export const ClipboardHandlingSchema = z.enum([
 ]);
 export type ClipboardHandling = z.infer<typeof ClipboardHandlingSchema>;
 
+export const LinuxTypingBackendSchema = z.enum([
+  "auto",
+  "wtype",
+  "ydotool",
+  "xdotool",
+  "clipboard_only",
+]);
+export type LinuxTypingBackend = z.infer<typeof LinuxTypingBackendSchema>;
+
+export const LinuxAudioHostSchema = z.enum(["alsa", "jack"]);
+export type LinuxAudioHost = z.infer<typeof LinuxAudioHostSchema>;
+
+export const TextCasingSchema = z.enum([
+  "original",
+  "lowercase",
+  "sentence",
+  "uppercase",
+]);
+export type TextCasing = z.infer<typeof TextCasingSchema>;
+
+export const SpokenCommandSchema = z.discriminatedUnion("kind", [
+  z.object({ kind: z.literal("punctuation"), text: z.string() }),
+  z.object({ kind: z.literal("opening"), text: z.string() }),
+  z.object({ kind: z.literal("break"), text: z.string() }),
+  z.object({ kind: z.literal("text"), text: z.string() }),
+  z.object({ kind: z.literal("caps_on") }),
+  z.object({ kind: z.literal("caps_off") }),
+  z.object({ kind: z.literal("capitalize_next") }),
+]);
+export type SpokenCommand = z.infer<typeof SpokenCommandSchema>;
+
 export const LogLevelSchema = z.number().int().min(1).max(5).default(2);
 export type LogLevelValue = z.infer<typeof LogLevelSchema>;
 
-export const RecordingRetentionPeriodSchema = z.enum([
-  "never",
-  "preserve_limit",
-  "days3",
-  "weeks2",
-  "months3",
+export const TranscriptionParamsSchema = z.object({
+  beam_size: z.number().int().nullable(),
+  temperature: z.number(),
+  no_speech_threshold: z.number(),
+  language_hint: z.string().nullable(),
+});
+export type TranscriptionParams = z.infer<typeof TranscriptionParamsSchema>;
+
+export const NumberFormattingSchema = z.object({
+  style: z.enum(["as_transcribed", "digits", "words"]),
+  thousands_separator: z.enum(["none", "comma", "period", "space"]),
+  date_format: z.enum(["as_transcribed", "long", "iso", "us", "european"]),
+});
+export type NumberFormatting = z.infer<typeof NumberFormattingSchema>;
+
+export const ProfanityFilterSchema = z.enum(["keep", "mask", "remove"]);
+export type ProfanityFilter = z.infer<typeof ProfanityFilterSchema>;
+
+export const OutputModeSchema = z.enum([
+  "paste",
+  "type",
+  "clipboard_only",
+  "none",
 ]);
-export type RecordingRetentionPeriod = z.infer<
-  typeof RecordingRetentionPeriodSchema
->;
+export type OutputMode = z.infer<typeof OutputModeSchema>;
+
+export const CaptionAnchorSchema = z.enum(["top", "center", "bottom"]);
+export type CaptionAnchor = z.infer<typeof CaptionAnchorSchema>;
+
+export const CaptionStyleSchema = z.object({
+  font_size: z.number().int(),
+  background_opacity: z.number(),
+  max_lines: z.number().int(),
+  anchor: CaptionAnchorSchema,
+  fade_timeout_ms: z.number().int(),
+});
+export type CaptionStyle = z.infer<typeof CaptionStyleSchema>;
+
+export const DEFAULT_CAPTION_STYLE: CaptionStyle = {
+  font_size: 18,
+  background_opacity: 0.85,
+  max_lines: 2,
+  anchor: "bottom",
+  fade_timeout_ms: 5000,
+};
+
+export const HistoryRetentionSchema = z.object({
+  max_age_days: z.number().int(),
+  max_entries: z.number().int(),
+  max_audio_mb: z.number().int(),
+});
+export type HistoryRetention = z.infer<typeof HistoryRetentionSchema>;
+
+export const DEFAULT_HISTORY_RETENTION: HistoryRetention = {
+  max_age_days: 0,
+  max_entries: 0,
+  max_audio_mb: 2048,
+};
+
+export const ChannelDownmixSchema = z.object({
+  mode: z.enum(["average", "left", "right", "channel"]),
+  // Counting from 0; only used by the "channel" mode
+  channel: z.number().int().optional().default(0),
+});
+export type ChannelDownmix = z.infer<typeof ChannelDownmixSchema>;
+
+export const DEFAULT_CHANNEL_DOWNMIX: ChannelDownmix = {
+  mode: "average",
+  channel: 0,
+};
+
+// How microphones and loopback capture are opened on Windows
+export const WasapiOptionsSchema = z.object({
+  exclusive: z.boolean().optional().default(false),
+  event_driven: z.boolean().optional().default(true),
+  // Device buffer length; 0 keeps the device default
+  buffer_ms: z.number().int().optional().default(0),
+  raw: z.boolean().optional().default(false),
+});
+export type WasapiOptions = z.infer<typeof WasapiOptionsSchema>;
+
+export const DEFAULT_WASAPI_OPTIONS: WasapiOptions = {
+  exclusive: false,
+  event_driven: true,
+  buffer_ms: 0,
+  raw: false,
+};
+
+export const InputGainSchema = z.object({
+  gain_db: z.number(),
+  limiter: z.boolean().optional().default(false),
+});
+export type InputGain = z.infer<typeof InputGainSchema>;
+
+export const DEFAULT_INPUT_GAIN: InputGain = {
+  gain_db: 0,
+  limiter: false,
+};
 
 export const LLMPromptSchema = z.object({
   id: z.string(),
@@ -79,6 +217,24 @@ export const PostProcessProviderSchema = z.object({
 
 export type PostProcessProvider = z.infer<typeof PostProcessProviderSchema>;
 
+// What ScreenCaptureKit captures audio from, picked with the content picker
+export const CaptureTargetSchema = z.discriminatedUnion("kind", [
+  z.object({ kind: z.literal("display"), display_id: z.number() }),
+  z.object({
+    kind: z.literal("window"),
+    window_id: z.number(),
+    title: z.string().nullable(),
+    app: z.string().nullable(),
+  }),
+  z.object({
+    kind: z.literal("application"),
+    bundle_id: z.string(),
+    name: z.string().nullable(),
+  }),
+]);
+
+export type CaptureTarget = z.infer<typeof CaptureTargetSchema>;
+
 export const SettingsSchema = z.object({
   bindings: ShortcutBindingsMapSchema,
   push_to_talk: z.boolean(),
@@ -91,11 +247,13 @@ export const SettingsSchema = z.object({
   start_hidden: z.boolean().optional().default(false),
   autostart_enabled: z.boolean().optional().default(false),
   selected_model: z.string(),
+  model_selection: ModelSelectionSchema.optional().default("manual"),
   always_on_microphone: z.boolean(),
   selected_microphone: z.string().nullable().optional(),
   clamshell_microphone: z.string().nullable().optional(),
   selected_output_device: z.string().nullable().optional(),
   audio_source: z.enum(["microphone", "system_audio"]).nullable().optional(),
+  screen_capture_target: CaptureTargetSchema.nullable().optional(),
   live_caption_enabled: z.boolean().optional().default(true),
   translate_to_english: z.boolean(),
   selected_language: z.string(),
@@ -105,9 +263,6 @@ export const SettingsSchema = z.object({
   custom_words: z.array(z.string()).optional().default([]),
   model_unload_timeout: ModelUnloadTimeoutSchema.optional().default("never"),
   word_correction_threshold: z.number().optional().default(0.18),
-  history_limit: z.number().optional().default(5),
-  recording_retention_period:
-    RecordingRetentionPeriodSchema.optional().default("preserve_limit"),
   paste_method: PasteMethodSchema.optional().default("ctrl_v"),
   clipboard_handling: ClipboardHandlingSchema.optional().default("dont_modify"),
   post_process_enabled: z.boolean().optional().default(false),
@@ -121,6 +276,99 @@ export const SettingsSchema = z.object({
   post_process_prompts: z.array(LLMPromptSchema).optional().default([]),
   post_process_selected_prompt_id: z.string().nullable().optional(),
   mute_while_recording: z.boolean().optional().default(false),
+  mute_mode: z.enum(["mute", "duck"]).optional().default("mute"),
+  duck_percent: z.number().optional().default(70),
+  silence_trim_padding_ms: z.number().optional().default(300),
+  system_audio_buffer_seconds: z.number().optional().default(30),
+  caption_alignment_enabled: z.boolean().optional().default(false),
+  caption_overlap_ms: z.number().optional().default(1000),
+  caption_context_words: z.number().optional().default(32),
+  caption_strategy: z.enum(["interval", "vad"]).optional().default("interval"),
+  debug_audio_logging: z.boolean().optional().default(false),
+  linux_typing_backend: LinuxTypingBackendSchema.optional().default("auto"),
+  keyword_boosts: z.array(z.string()).optional().default([]),
+  keyword_boost_strength: z.number().optional().default(2),
+  retake_window_ms: z.number().optional().default(0),
+  dictation_stitch_window_secs: z.number().optional().default(0),
+  transcript_file_enabled: z.boolean().optional().default(false),
+  transcript_file_path: z.string().nullable().optional(),
+  transcript_file_timestamps: z.boolean().optional().default(true),
+  transcript_file_daily_rotation: z.boolean().optional().default(false),
+  cloud_daily_quota_minutes: z.number().optional().default(0),
+  cloud_cost_per_minute: z.number().optional().default(0.006),
+  caption_server_enabled: z.boolean().optional().default(false),
+  caption_server_port: z.number().optional().default(8787),
+  language_cycle: z.array(z.string()).optional().default(["vi", "en"]),
+  caption_finalization_delay_ms: z.number().optional().default(0),
+  denoise_enabled: z.boolean().optional().default(false),
+  device_aliases: z.record(z.string()).optional().default({}),
+  echo_cancellation_enabled: z.boolean().optional().default(false),
+  caption_hotwords: z.array(z.string()).optional().default([]),
+  external_command_enabled: z.boolean().optional().default(false),
+  external_command: z.string().optional().default(""),
+  external_command_args: z.array(z.string()).optional().default([]),
+  external_command_timeout_ms: z.number().optional().default(2000),
+  auto_caption_system_audio: z.boolean().optional().default(true),
+  dual_channel_captions: z.boolean().optional().default(false),
+  speaker_diarization_enabled: z.boolean().optional().default(false),
+  text_casing: TextCasingSchema.optional().default("original"),
+  binding_text_casing: z.record(TextCasingSchema).optional().default({}),
+  caption_translation_language: z.string().nullable().optional(),
+  transcription_backend: z
+    .enum(["local", "cloud"])
+    .optional()
+    .default("local"),
+  compute_backend: ComputeBackendSchema.optional().default("auto"),
+  power_saving_enabled: z.boolean().optional().default(false),
+  power_saving_battery_percent: z.number().optional().default(20),
+  cloud_api_endpoint: z
+    .string()
+    .optional()
+    .default("https://api.openai.com/v1"),
+  cloud_api_key: z.string().optional().default(""),
+  cloud_model: z.string().optional().default("whisper-1"),
+  cloud_fallback_to_local: z.boolean().optional().default(true),
+  long_recording_segment_secs: z.number().optional().default(120),
+  transcription_params: TranscriptionParamsSchema.optional().default({
+    beam_size: null,
+    temperature: 0,
+    no_speech_threshold: 0.6,
+    language_hint: null,
+  }),
+  hallucination_filter_enabled: z.boolean().optional().default(true),
+  custom_word_aliases: z.record(z.array(z.string())).optional().default({}),
+  spoken_formatting_enabled: z.boolean().optional().default(false),
+  binding_spoken_formatting: z.record(z.boolean()).optional().default({}),
+  spoken_commands: z.record(SpokenCommandSchema).optional().default({}),
+  punctuation_model_path: z.string().nullable().optional().default(null),
+  punctuation_languages: z.array(z.string()).optional().default(["en"]),
+  number_formatting: NumberFormattingSchema.optional().default({
+    style: "as_transcribed",
+    thousands_separator: "comma",
+    date_format: "as_transcribed",
+  }),
+  profanity_filter: ProfanityFilterSchema.optional().default("keep"),
+  profanity_words: z.array(z.string()).optional().default([]),
+  paste_blacklist: z.array(z.string()).optional().default([]),
+  output_mode: OutputModeSchema.optional().default("paste"),
+  binding_output_mode: z.record(OutputModeSchema).optional().default({}),
+  caption_paste_enabled: z.boolean().optional().default(false),
+  caption_style: CaptionStyleSchema.optional().default(DEFAULT_CAPTION_STYLE),
+  history_retention: HistoryRetentionSchema.optional().default(
+    DEFAULT_HISTORY_RETENTION,
+  ),
+  pre_roll_ms: z.number().optional().default(1000),
+  max_recording_secs: z.number().optional().default(600),
+  microphone_downmix: ChannelDownmixSchema.optional().default(
+    DEFAULT_CHANNEL_DOWNMIX,
+  ),
+  system_audio_downmix: ChannelDownmixSchema.optional().default(
+    DEFAULT_CHANNEL_DOWNMIX,
+  ),
+  // Keyed by device id, "default" for the default input
+  input_gains: z.record(InputGainSchema).optional().default({}),
+  wasapi: WasapiOptionsSchema.optional().default(DEFAULT_WASAPI_OPTIONS),
+  linux_audio_host: LinuxAudioHostSchema.optional().default("alsa"),
 });
 
 export const BindingResponseSchema = z.object({
@@ -148,6 +396,305 @@ export const ModelInfoSchema = z.object({
   is_directory: z.boolean(),
   accuracy_score: z.number(),
   speed_score: z.number(),
+  sha256: z.string().nullable().optional(),
 });
 
 export type ModelInfo = z.infer<typeof ModelInfoSchema>;
+
+// Result of `get_hardware_profile`
+export interface HardwareProfile {
+  cpu_cores: number;
+  total_memory_mb: number | null;
+  gpu: "metal" | "cuda" | null;
+}
+
+// Result of `get_compute_backend_info`
+export interface ComputeBackendInfo {
+  requested: ComputeBackend;
+  active: ComputeBackend | null;
+  available: ComputeBackend[];
+  model_memory_mb: number | null;
+  process_memory_mb: number | null;
+}
+
+// One entry of `benchmark_models`
+export interface ModelBenchmark {
+  model_id: string;
+  load_ms: number;
+  transcribe_ms: number;
+  real_time_factor: number;
+}
+
+// Payload of the `live-caption-update` event
+export interface LiveCaptionUpdate {
+  text: string;
+  speaker: string | null;
+  translation: string | null;
+}
+
+// Lifecycle event payloads, mirroring src-tauri/src/events.rs. Timestamps
+// are unix milliseconds.
+export type CaptureSource = "microphone" | "system_audio" | "file" | "recovered";
+
+// `recording-started`
+export interface RecordingStarted {
+  recording_id: number;
+  binding_id: string;
+  source: CaptureSource;
+  started_at: number;
+}
+
+// `recording-stopped`
+export interface RecordingStopped {
+  recording_id: number;
+  binding_id: string;
+  stopped_at: number;
+  duration_ms: number;
+  cancelled: boolean;
+}
+
+// `transcription-started`
+export interface TranscriptionStarted {
+  recording_id: number;
+  binding_id: string;
+  started_at: number;
+  audio_ms: number;
+}
+
+// `transcription-finished`
+export interface TranscriptionFinished {
+  recording_id: number;
+  binding_id: string;
+  finished_at: number;
+  elapsed_ms: number;
+  text: string;
+  error: string | null;
+}
+
+// `caption-segment`, with `start` and `end` in seconds on the session
+// timeline
+export interface CaptionSegment {
+  session_id: number | null;
+  source: CaptureSource;
+  text: string;
+  speaker: string | null;
+  start: number;
+  end: number;
+  emitted_at: number;
+}
+
+// `transcription-queue-depth`: jobs waiting by priority, and those running
+export interface TranscriptionQueueDepth {
+  dictation: number;
+  caption: number;
+  background: number;
+  running: number;
+}
+
+// Payload of the `loopback-diagnosis` event, sent when a loopback device
+// such as BlackHole delivers only silence
+export interface LoopbackDiagnosis {
+  device: string;
+  sample_rate: number;
+  channels: number;
+  sample_format: string;
+  default_output: string | null;
+  output_sample_rate: number | null;
+  callbacks: number;
+  frames_received: number;
+  channel_activity: {
+    channel: number;
+    peak: number;
+    rms: number;
+    active: boolean;
+  }[];
+  checks: {
+    id: string;
+    label: string;
+    passed: boolean;
+    hint: string | null;
+  }[];
+}
+
+// Result of `run_audio_selftest`
+export interface AudioSelfTest {
+  source: "microphone" | "system_audio";
+  device: string;
+  duration_ms: number;
+  sample_rate: number;
+  measured_sample_rate: number;
+  rms: number;
+  peak: number;
+  dropouts: number;
+  dropped_samples: number;
+  speech_ratio: number | null;
+  resampled_samples: number;
+  expected_resampled_samples: number;
+  checks: LoopbackDiagnosis["checks"];
+  passed: boolean;
+}
+
+// Result of `get_blackhole_status`
+export interface BlackHoleStatus {
+  installed: boolean;
+  device: string | null;
+  channels: number | null;
+  default_output: string | null;
+  routed: boolean;
+}
+
+// Result of `install_blackhole`
+export type BlackHoleInstall =
+  | { action: "installer"; path: string }
+  | { action: "download"; url: string };
+
+// Result of `verify_blackhole_routing`
+export interface BlackHoleRoutingCheck {
+  output: string;
+  loopback: string;
+  tone_level: number;
+  peak: number;
+  tone_detected: boolean;
+}
+
+export interface ConfigRange {
+  channels: number;
+  min_sample_rate: number;
+  max_sample_rate: number;
+  sample_format: string;
+}
+
+// Result of `get_device_capabilities`
+export interface DeviceCapabilities {
+  sample_rates: number[];
+  channel_counts: number[];
+  sample_formats: string[];
+  ranges: ConfigRange[];
+  default_sample_rate: number | null;
+  default_channels: number | null;
+  warnings: string[];
+}
+
+export interface FeatureSupport {
+  supported: boolean;
+  note: string | null;
+}
+
+export interface StageMetrics {
+  stage: "capture" | "resample" | "vad" | "transcribe" | "paste";
+  count: number;
+  average_ms: number;
+  p95_ms: number;
+  max_ms: number;
+}
+
+// Result of `get_performance_metrics`
+export interface PerformanceMetrics {
+  stages: StageMetrics[];
+  microphone_dropped_samples: number;
+  system_audio_buffered_ms: number;
+  system_audio_dropped_samples: number;
+  microphone_glitches: GlitchMetrics;
+  system_audio_glitches: GlitchMetrics;
+  transcription_queue: TranscriptionQueueDepth;
+}
+
+// Capture glitches counted on one stream since launch
+export interface GlitchMetrics {
+  gaps: number;
+  overruns: number;
+  discontinuities: number;
+}
+
+// Result of `get_platform_capabilities`: which features work on this
+// platform and session
+export interface PlatformCapabilities {
+  os: "linux" | "macos" | "windows";
+  session: "x11" | "wayland" | "unknown" | null;
+  audio_server: "pipewire" | "pulseaudio" | "alsa" | null;
+  system_audio: FeatureSupport;
+  mute: FeatureSupport;
+  clamshell: FeatureSupport;
+  global_shortcuts: FeatureSupport;
+  paste_backend: string;
+  paste: FeatureSupport;
+}
+
+// One record from the backend log buffer, returned by `get_recent_logs` and
+// sent in batches as `log-entry` while subscribed with `subscribe_logs`
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+export interface LogEntry {
+  id: number;
+  timestamp: number;
+  level: LogLevel;
+  module: string;
+  message: string;
+}
+
+// Payload of the `device-added` and `device-removed` events
+export interface DeviceAdded {
+  id: string;
+  name: string;
+  is_default: boolean;
+}
+
+export interface DeviceRemoved {
+  id: string;
+  name: string;
+  was_default: boolean;
+}
+
+// Payload of the `microphone-failover` event
+export interface MicrophoneFailover {
+  from: string;
+  recording: boolean;
+  error: string | null;
+}
+
+// Payload of the `mic-level` event
+export interface LevelFrame {
+  // Level bars, 0 to 1
+  bars: number[];
+  // Loudest sample since the previous frame, in dBFS
+  peak_db: number;
+  // Loudest recent peak, held for a moment before falling back
+  held_peak_db: number;
+  // The input clipped within the hold time
+  clipping: boolean;
+}
+
+// Payload of the `audio-clipping` event
+export interface AudioClipping {
+  clips: number;
+}
+
+// Payload of the `audio-spectrum` event, sent while `subscribe_spectrum` is
+// in effect. Bands are spaced evenly on a log scale from min_hz to max_hz.
+export interface SpectrumFrame {
+  // dBFS, a full-scale sine reading 0
+  bands: number[];
+  min_hz: number;
+  max_hz: number;
+}
+
+// Payload of the `audio-glitch-detected` event
+export interface AudioGlitchDetected {
+  stream: "microphone" | "system_audio";
+  glitches: number;
+  window_secs: number;
+  remedies: string[];
+}
+
+// Payload of the `power-throttle-changed` event, also returned by
+// `get_power_state`
+export interface PowerThrottleChanged {
+  throttle: "full" | "reduced" | "paused";
+  status: {
+    on_battery: boolean;
+    battery_percent: number | null;
+    power_saver: boolean;
+    thermal: "nominal" | "fair" | "serious" | "critical";
+  };
+  model_override: string | null;
+}
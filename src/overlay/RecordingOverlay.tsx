@@ -1,20 +1,29 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import React, { useEffect, useRef, useState } from "react";
+import { Pause, Play } from "lucide-react";
 import {
   MicrophoneIcon,
   TranscriptionIcon,
   CancelIcon,
 } from "../components/icons";
+import type { LevelFrame } from "../lib/types";
 import "./RecordingOverlay.css";
 
 type OverlayState = "recording" | "transcribing";
 
+type RecordingState =
+  | { state: "idle" }
+  | { state: "recording"; binding_id: string; paused: boolean };
+
 const RecordingOverlay: React.FC = () => {
   const [isVisible, setIsVisible] = useState(false);
   const [state, setState] = useState<OverlayState>("recording");
+  const [paused, setPaused] = useState(false);
   const [levels, setLevels] = useState<number[]>(Array(16).fill(0));
   const smoothedLevelsRef = useRef<number[]>(Array(16).fill(0));
+  const [heldPeakDb, setHeldPeakDb] = useState(-100);
+  const [clipping, setClipping] = useState(false);
 
   useEffect(() => {
     const setupEventListeners = async () => {
@@ -30,9 +39,20 @@ const RecordingOverlay: React.FC = () => {
         setIsVisible(false);
       });
 
+      const unlistenRecording = await listen<RecordingState>(
+        "recording-state",
+        (event) => {
+          setPaused(
+            event.payload.state === "recording" && event.payload.paused,
+          );
+        },
+      );
+
       // Listen for mic-level updates
-      const unlistenLevel = await listen<number[]>("mic-level", (event) => {
-        const newLevels = event.payload as number[];
+      const unlistenLevel = await listen<LevelFrame>("mic-level", (event) => {
+        const newLevels = event.payload.bars;
+        setHeldPeakDb(event.payload.held_peak_db);
+        setClipping(event.payload.clipping);
 
         // Apply smoothing to reduce jitter
         const smoothed = smoothedLevelsRef.current.map((prev, i) => {
@@ -48,6 +68,7 @@ const RecordingOverlay: React.FC = () => {
       return () => {
         unlistenShow();
         unlistenHide();
+        unlistenRecording();
         unlistenLevel();
       };
     };
@@ -68,8 +89,11 @@ const RecordingOverlay: React.FC = () => {
       <div className="overlay-left">{getIcon()}</div>
 
       <div className="overlay-middle">
-        {state === "recording" && (
-          <div className="bars-container">
+        {state === "recording" && paused && (
+          <div className="transcribing-text">Paused</div>
+        )}
+        {state === "recording" && !paused && (
+          <div className={`bars-container ${clipping ? "clipping" : ""}`}>
             {levels.map((v, i) => (
               <div
                 key={i}
@@ -81,6 +105,13 @@ const RecordingOverlay: React.FC = () => {
                 }}
               />
             ))}
+            <div
+              className="peak-hold"
+              style={{
+                // -60 dBFS and below sits at the bottom, full scale at the top
+                height: `${2 + Math.max(0, (heldPeakDb + 60) / 60) * 18}px`,
+              }}
+            />
           </div>
         )}
         {state === "transcribing" && (
@@ -89,6 +120,20 @@ const RecordingOverlay: React.FC = () => {
       </div>
 
       <div className="overlay-right">
+        {state === "recording" && (
+          <div
+            className="cancel-button"
+            onClick={() => {
+              invoke(paused ? "resume_recording" : "pause_recording");
+            }}
+          >
+            {paused ? (
+              <Play width={14} height={14} color="#ffe5ee" fill="#ffe5ee" />
+            ) : (
+              <Pause width={14} height={14} color="#ffe5ee" fill="#ffe5ee" />
+            )}
+          </div>
+        )}
         {state === "recording" && (
           <div
             className="cancel-button"
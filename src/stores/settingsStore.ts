@@ -74,12 +74,11 @@ const DEFAULT_SETTINGS: Partial<Settings> = {
   debug_mode: false,
   log_level: 2,
   custom_words: [],
-  history_limit: 5,
-  recording_retention_period: "preserve_limit",
   mute_while_recording: false,
 };
 
 const DEFAULT_AUDIO_DEVICE: AudioDevice = {
+  id: "Default",
   index: "default",
   name: "Default",
   is_default: true,
@@ -117,12 +116,12 @@ const settingUpdaters: {
     invoke("set_audio_source", { source: value }),
   live_caption_enabled: (value) =>
     invoke("change_live_caption_enabled_setting", { enabled: value }),
-  recording_retention_period: (value) =>
-    invoke("update_recording_retention_period", { period: value }),
   translate_to_english: (value) =>
     invoke("change_translate_to_english_setting", { enabled: value }),
   selected_language: (value) =>
     invoke("change_selected_language_setting", { language: value }),
+  model_selection: (value) =>
+    invoke("set_model_selection", { selection: value }),
   overlay_position: (value) =>
     invoke("change_overlay_position_setting", { position: value }),
   debug_mode: (value) =>
@@ -134,13 +133,144 @@ const settingUpdaters: {
     invoke("change_paste_method_setting", { method: value }),
   clipboard_handling: (value) =>
     invoke("change_clipboard_handling_setting", { handling: value }),
-  history_limit: (value) => invoke("update_history_limit", { limit: value }),
   post_process_enabled: (value) =>
     invoke("change_post_process_enabled_setting", { enabled: value }),
   post_process_selected_prompt_id: (value) =>
     invoke("set_post_process_selected_prompt", { id: value }),
   mute_while_recording: (value) =>
     invoke("change_mute_while_recording_setting", { enabled: value }),
+  mute_mode: (value) => invoke("change_mute_mode_setting", { mode: value }),
+  duck_percent: (value) =>
+    invoke("change_duck_percent_setting", { percent: value }),
+  caption_alignment_enabled: (value) =>
+    invoke("change_caption_alignment_setting", { enabled: value }),
+  caption_overlap_ms: (value) =>
+    invoke("change_caption_overlap_setting", { overlapMs: value }),
+  caption_context_words: (value) =>
+    invoke("change_caption_context_setting", { words: value }),
+  caption_strategy: (value) =>
+    invoke("change_caption_strategy_setting", { strategy: value }),
+  debug_audio_logging: (value) =>
+    invoke("set_debug_audio_logging", { enabled: value }),
+  linux_typing_backend: (value) =>
+    invoke("change_linux_typing_backend_setting", { backend: value }),
+  keyword_boosts: (value) =>
+    invoke("update_keyword_boosts", { keywords: value }),
+  keyword_boost_strength: (value) =>
+    invoke("change_keyword_boost_strength_setting", { strength: value }),
+  retake_window_ms: (value) =>
+    invoke("change_retake_window_setting", { windowMs: value }),
+  dictation_stitch_window_secs: (value) =>
+    invoke("change_dictation_stitch_window_setting", { seconds: value }),
+  transcript_file_enabled: (value) =>
+    invoke("change_transcript_file_enabled_setting", { enabled: value }),
+  transcript_file_path: (value) =>
+    invoke("change_transcript_file_path_setting", { path: value }),
+  transcript_file_timestamps: (value) =>
+    invoke("change_transcript_file_timestamps_setting", { enabled: value }),
+  transcript_file_daily_rotation: (value) =>
+    invoke("change_transcript_file_rotation_setting", { enabled: value }),
+  cloud_daily_quota_minutes: (value) =>
+    invoke("change_cloud_quota_setting", { minutes: value }),
+  cloud_cost_per_minute: (value) =>
+    invoke("change_cloud_cost_per_minute_setting", { cost: value }),
+  caption_server_enabled: (value) =>
+    invoke("change_caption_server_enabled_setting", { enabled: value }),
+  caption_server_port: (value) =>
+    invoke("change_caption_server_port_setting", { port: value }),
+  language_cycle: (value) =>
+    invoke("update_language_cycle", { languages: value }),
+  caption_finalization_delay_ms: (value) =>
+    invoke("change_caption_finalization_delay_setting", { delayMs: value }),
+  denoise_enabled: (value) =>
+    invoke("change_denoise_setting", { enabled: value }),
+  echo_cancellation_enabled: (value) =>
+    invoke("change_echo_cancellation_setting", { enabled: value }),
+  caption_hotwords: (value) =>
+    invoke("update_caption_hotwords", { hotwords: value }),
+  external_command_enabled: (value) =>
+    invoke("change_external_command_enabled_setting", { enabled: value }),
+  // The command and its arguments are saved together
+  external_command: (value) =>
+    invoke("change_external_command_setting", {
+      command: value,
+      args: useSettingsStore.getState().settings?.external_command_args ?? [],
+    }),
+  external_command_args: (value) =>
+    invoke("change_external_command_setting", {
+      command: useSettingsStore.getState().settings?.external_command ?? "",
+      args: value,
+    }),
+  external_command_timeout_ms: (value) =>
+    invoke("change_external_command_timeout_setting", { timeoutMs: value }),
+  auto_caption_system_audio: (value) =>
+    invoke("change_auto_caption_system_audio_setting", { enabled: value }),
+  dual_channel_captions: (value) =>
+    invoke("change_dual_channel_captions_setting", { enabled: value }),
+  speaker_diarization_enabled: (value) =>
+    invoke("change_speaker_diarization_setting", { enabled: value }),
+  text_casing: (value) =>
+    invoke("change_text_casing_setting", { casing: value }),
+  caption_translation_language: (value) =>
+    invoke("change_caption_translation_setting", { language: value ?? null }),
+  transcription_backend: (value) =>
+    invoke("change_transcription_backend_setting", { backend: value }),
+  compute_backend: (value) =>
+    invoke("change_compute_backend_setting", { backend: value }),
+  power_saving_enabled: (value) =>
+    invoke("change_power_saving_setting", { enabled: value }),
+  power_saving_battery_percent: (value) =>
+    invoke("change_power_saving_battery_percent_setting", { percent: value }),
+  cloud_api_endpoint: (value) =>
+    invoke("change_cloud_api_endpoint_setting", { endpoint: value }),
+  cloud_api_key: (value) =>
+    invoke("change_cloud_api_key_setting", { apiKey: value }),
+  cloud_model: (value) => invoke("change_cloud_model_setting", { model: value }),
+  cloud_fallback_to_local: (value) =>
+    invoke("change_cloud_fallback_setting", { enabled: value }),
+  long_recording_segment_secs: (value) =>
+    invoke("change_long_recording_segment_setting", { seconds: value }),
+  transcription_params: (value) =>
+    invoke("set_transcription_params", { params: value }),
+  hallucination_filter_enabled: (value) =>
+    invoke("change_hallucination_filter_setting", { enabled: value }),
+  custom_word_aliases: (value) =>
+    invoke("update_custom_word_aliases", { aliases: value }),
+  spoken_formatting_enabled: (value) =>
+    invoke("change_spoken_formatting_setting", { enabled: value }),
+  spoken_commands: (value) =>
+    invoke("set_spoken_commands", { commands: value }),
+  punctuation_model_path: (value) =>
+    invoke("change_punctuation_model_setting", { path: value }),
+  punctuation_languages: (value) =>
+    invoke("change_punctuation_languages_setting", { languages: value }),
+  number_formatting: (value) =>
+    invoke("set_number_formatting", { formatting: value }),
+  profanity_filter: (value) =>
+    invoke("change_profanity_filter_setting", { filter: value }),
+  profanity_words: (value) =>
+    invoke("update_profanity_words", { words: value }),
+  paste_blacklist: (value) =>
+    invoke("update_paste_blacklist", { apps: value }),
+  output_mode: (value) =>
+    invoke("change_output_mode_setting", { mode: value }),
+  caption_paste_enabled: (value) =>
+    invoke("change_caption_paste_setting", { enabled: value }),
+  caption_style: (value) => invoke("set_caption_style", { style: value }),
+  history_retention: (value) =>
+    invoke("set_history_retention", { retention: value }),
+  pre_roll_ms: (value) =>
+    invoke("change_pre_roll_setting", { preRollMs: value }),
+  max_recording_secs: (value) =>
+    invoke("change_max_recording_setting", { seconds: value }),
+  microphone_downmix: (value) =>
+    invoke("set_channel_downmix", { source: "microphone", downmix: value }),
+  system_audio_downmix: (value) =>
+    invoke("set_channel_downmix", { source: "system_audio", downmix: value }),
+  wasapi: (value) => invoke("set_wasapi_options", { options: value }),
+  linux_audio_host: (value) => invoke("set_linux_audio_host", { host: value }),
+  silence_trim_padding_ms: (value) =>
+    invoke("change_silence_trim_padding_setting", { paddingMs: value }),
   log_level: (value) => invoke("set_log_level", { level: value }),
 };
 